@@ -6,6 +6,23 @@ mod _rel_config {
     {
         pub quickplay_mlvl: u32,
         pub quickplay_mrea: u32,
+        pub show_igt: bool,
+        pub show_room_timer: bool,
+        pub splits_address: u32,
+        pub ghost_address: u32,
+        pub ghost_card_functions: [u32; 7], // see CardFunctions::from_addresses, 0s to not dump
+        pub progressive_hook_address: u32,
+        pub progressive_chains: [[u8; 8]; 8],
+        pub ammo_cost_hook_address: u32,
+        pub pickup_counter_hook_address: u32,
+        pub pickup_counter_total: u32,
+        pub pickup_counter_percentage: bool,
+        pub starting_preset_hook_address: u32,
+        pub starting_mlvl: u32,
+        pub starting_presets: [[u32; 2]; 3], // mlvl and mrea, by save file
+        pub debug_hook_address: u32,
+        pub tracker_address: u32,
+        pub integrity_regions: [[u32; 3]; 8], // address, length and CRC32 of the regions to check
     }
 }
 pub(crate) use self::_rel_config::RelConfig;
@@ -5,8 +5,8 @@ use std::fmt;
 use proc_macro::TokenStream;
 use quote::{quote, ToTokens};
 use syn::{
-    parenthesized, parse::Parser, parse_macro_input, punctuated::Punctuated, spanned::Spanned,
-    Token,
+    ext::IdentExt, parenthesized, parse::Parser, parse_macro_input, punctuated::Punctuated,
+    spanned::Spanned, Token,
 };
 
 struct NameExprPair {
@@ -71,7 +71,8 @@ impl syn::parse::Parse for Flags {
                 if kind.is_some() {
                     Err(syn::Error::new(pair.ident.span(), "Duplicate `kind` flag"))?;
                 }
-                let ident = <syn::Ident as syn::parse::Parse>::parse.parse2(pair.tokens)?;
+                // `return` is a keyword, so it has to be parsed with `parse_any`
+                let ident = syn::Ident::parse_any.parse2(pair.tokens)?;
 
                 kind = if ident == "call" {
                     Some(PatchKind::Call)
@@ -0,0 +1,71 @@
+// The memory card functions of the Dolphin SDK. They aren't in the symbol tables of every version,
+// so they're called through the addresses the patcher puts in the rel's config, rather than linked
+// against.
+
+pub const CARD_RESULT_READY: i32 = 0;
+pub const CARD_RESULT_BUSY: i32 = -1;
+pub const CARD_RESULT_NOFILE: i32 = -4;
+
+pub const CARD_WORKAREA_SIZE: usize = 5 * 8 * 1024;
+// Of the memory cards Nintendo made, files are created and written in whole sectors
+pub const CARD_SECTOR_SIZE: usize = 8 * 1024;
+
+#[repr(C)]
+pub struct CARDFileInfo {
+    pub chan: i32,
+    pub file_no: i32,
+    pub offset: i32,
+    pub length: i32,
+    pub i_block: u16,
+    pub padding: u16,
+}
+
+pub type CARDCallback = extern "C" fn(chan: i32, result: i32);
+
+#[derive(Copy, Clone)]
+pub struct CardFunctions {
+    pub mount_async: unsafe extern "C" fn(
+        chan: i32,
+        work_area: *mut u8,
+        detach_callback: Option<CARDCallback>,
+        attach_callback: Option<CARDCallback>,
+    ) -> i32,
+    pub unmount: unsafe extern "C" fn(chan: i32) -> i32,
+    pub open:
+        unsafe extern "C" fn(chan: i32, file_name: *const u8, file_info: *mut CARDFileInfo) -> i32,
+    pub create_async: unsafe extern "C" fn(
+        chan: i32,
+        file_name: *const u8,
+        size: u32,
+        file_info: *mut CARDFileInfo,
+        callback: Option<CARDCallback>,
+    ) -> i32,
+    pub write_async: unsafe extern "C" fn(
+        file_info: *mut CARDFileInfo,
+        buf: *const u8,
+        length: i32,
+        offset: i32,
+        callback: Option<CARDCallback>,
+    ) -> i32,
+    pub close: unsafe extern "C" fn(file_info: *mut CARDFileInfo) -> i32,
+    pub get_result_code: unsafe extern "C" fn(chan: i32) -> i32,
+}
+
+impl CardFunctions {
+    /// From the addresses of CARDMountAsync, CARDUnmount, CARDOpen, CARDCreateAsync,
+    /// CARDWriteAsync, CARDClose and CARDGetResultCode, in that order. None if any is 0.
+    pub unsafe fn from_addresses(addresses: &[u32; 7]) -> Option<Self> {
+        if addresses.contains(&0) {
+            return None;
+        }
+        Some(CardFunctions {
+            mount_async: core::mem::transmute(addresses[0] as usize),
+            unmount: core::mem::transmute(addresses[1] as usize),
+            open: core::mem::transmute(addresses[2] as usize),
+            create_async: core::mem::transmute(addresses[3] as usize),
+            write_async: core::mem::transmute(addresses[4] as usize),
+            close: core::mem::transmute(addresses[5] as usize),
+            get_result_code: core::mem::transmute(addresses[6] as usize),
+        })
+    }
+}
@@ -0,0 +1,66 @@
+// Checks that the game's code wasn't modified after it was patched, by comparing CRC32s of regions
+// of memory against those the patcher computed from the patched DOL. Only the game's own code
+// should be checked: Nintendont patches the SDK's (e.g. DVD and controller functions) in memory.
+//
+// The expected CRC32s ship in rel_config.bin, so a tampered disc could update them as well. The
+// CRC32s of what was actually loaded (see regions_crc32) are therefore also published in the
+// tracker region, for organizers to compare with those the patcher printed for the seed.
+
+// Results of the check, as stored in the tracker region
+pub const INTEGRITY_NOT_CHECKED: u32 = 0;
+pub const INTEGRITY_PASSED: u32 = 1;
+pub const INTEGRITY_FAILED: u32 = 2;
+
+// The usual reflected CRC32 (as in zlib), without a table to keep the rel small
+pub fn crc32(bytes: &[u8]) -> u32 {
+    crc32_update(0, bytes)
+}
+
+// Continues the CRC32 of the bytes before `bytes`, so that data can be hashed in chunks
+pub fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for b in bytes {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Checks regions given as (address, length, CRC32), those with a length of 0 are unused
+pub unsafe fn check_regions(regions: &[[u32; 3]]) -> u32 {
+    let mut checked = false;
+    for &[addr, len, expected] in regions {
+        if len == 0 {
+            continue;
+        }
+        let bytes = core::slice::from_raw_parts(addr as *const u8, len as usize);
+        if crc32(bytes) != expected {
+            return INTEGRITY_FAILED;
+        }
+        checked = true;
+    }
+    if checked {
+        INTEGRITY_PASSED
+    } else {
+        INTEGRITY_NOT_CHECKED
+    }
+}
+
+/// CRC32 of the regions, one after the other, as they are in memory
+pub unsafe fn regions_crc32(regions: &[[u32; 3]]) -> u32 {
+    regions
+        .iter()
+        .filter(|&&[_, len, _]| len != 0)
+        .fold(0, |crc, &[addr, len, _]| {
+            crc32_update(
+                crc,
+                core::slice::from_raw_parts(addr as *const u8, len as usize),
+            )
+        })
+}
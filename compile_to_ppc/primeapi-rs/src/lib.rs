@@ -15,11 +15,14 @@ pub use primeapi_macros::{cpp_method, cw_link_name, patch_fn, prolog_fn};
 
 pub mod rstl;
 pub mod dol_sdk {
+    pub mod card;
     pub mod dvd;
     pub mod os;
 }
 pub mod alignment_utils;
+pub mod integrity;
 pub mod mp1;
+pub mod tracker;
 
 #[doc(hidden)]
 pub mod reexport {
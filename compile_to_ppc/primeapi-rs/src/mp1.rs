@@ -41,28 +41,43 @@ impl CStringTable {
     }
 }
 
-// #[repr(C)]
-// #[derive(Copy, Clone, Debug)]
-// struct CPowerUp
-// {
-//     amount: u32,
-//     capacity: u32,
-// }
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct CPowerUp {
+    pub amount: u32,
+    pub capacity: u32,
+}
 
 pub enum CPlayerState {}
 impl CPlayerState {
+    // rstl::reserved_vector<CPowerUp, 41>, indexed by EItemType. The size is at 0x24
+    cpp_field!(power_ups: [CPowerUp; 41]; ptr @ 0x28);
+
     #[cpp_method(CPlayerState::GetItemCapacity(CPlayerState::EItemType) const)]
     pub unsafe fn get_item_capacity(this: *const CPlayerState, type_: i32) -> u32 {}
 
     #[cpp_method(CPlayerState::IncrPickup(CPlayerState::EItemType, u32))]
     pub unsafe fn incr_pickup(this: *const CPlayerState, type_: i32, amount: u32) {}
 
-    #[cpp_method(CPlayerState::DecrPickup(CPlayerState::EItemType, u32) const)]
-    pub unsafe fn decr_pickup(this: *const CPlayerState, type_: i32, amount: u32) {}
+    #[cpp_method(CPlayerState::DecrPickUp(CPlayerState::EItemType, int))]
+    pub unsafe fn decr_pickup(this: *mut CPlayerState, type_: i32, amount: i32) {}
+
+    #[cpp_method(CPlayerState::HasPowerUp(CPlayerState::EItemType) const)]
+    pub unsafe fn has_power_up(this: *const CPlayerState, type_: i32) -> bool {}
+
+    #[cpp_method(CPlayerState::InitializePowerUp(CPlayerState::EItemType, int))]
+    pub unsafe fn initialize_power_up(this: *mut CPlayerState, type_: i32, capacity: i32) {}
+
+    // The number of items the game counts towards the item percentage
+    #[cpp_method(CPlayerState::CalculateItemCollectionRate(void))]
+    pub unsafe fn calculate_item_collection_rate(this: *mut CPlayerState) -> u32 {}
 }
 
 pub enum CWorldState {}
 impl CWorldState {
+    cpp_field!(mlvl_id: u32; ro_val @ 0x0);
+    cpp_field!(area_id: TAreaId; ro_val @ 0x4);
+
     #[cpp_method(CWorldState::SetDesiredAreaAssetId(unsigned int))]
     pub unsafe fn set_desired_area_asset_id(this: *mut CWorldState, id: u32) {}
 }
@@ -72,6 +87,7 @@ impl CGameState {
     // TODO: I guess this should actually be shared_ptr
     cpp_field!(player_state: *mut *mut CPlayerState; val @ 0x98);
     cpp_field!(play_time: f64; val @ 0xa0);
+    cpp_field!(save_file_idx: u32; ro_val @ 0x20c);
 
     pub fn global_instance() -> *mut Self {
         extern "C" {
@@ -89,7 +105,61 @@ impl CGameState {
 
 pub enum CStateManager {}
 impl CStateManager {
+    cpp_field!(player: *mut CPlayer; ro_val @ 0x84c);
     cpp_field!(player_state: *mut CPlayerState; ptr @ 0x8b8);
+
+    pub fn global_instance() -> *mut Self {
+        extern "C" {
+            static g_StateManager: u8;
+        }
+        unsafe { &g_StateManager as *const u8 as *mut Self }
+    }
+}
+
+pub enum CPlayer {}
+impl CPlayer {
+    // Row-major 3x4, the translation is the last column
+    cpp_field!(transform: [f32; 12]; ro_val @ 0x34);
+
+    // Moves the player to `xf`, facing its front vector, and stops it
+    #[cpp_method(CPlayer::Teleport(const CTransform4f &, CStateManager &, bool))]
+    pub unsafe fn teleport(
+        this: *mut CPlayer,
+        xf: *const [f32; 12],
+        mgr: *mut CStateManager,
+        reset_ball_cam: bool,
+    ) {
+    }
+}
+
+pub enum CFinalInput {}
+impl CFinalInput {
+    pub const A: u8 = 0x80;
+    pub const B: u8 = 0x40;
+    pub const X: u8 = 0x20;
+    pub const Y: u8 = 0x10;
+    pub const Z: u8 = 0x08;
+    pub const L: u8 = 0x04;
+    pub const R: u8 = 0x02;
+    pub const DPAD_UP: u8 = 0x01;
+
+    pub const PRESSED_Z: u8 = 0x80;
+    pub const PRESSED_L: u8 = 0x40;
+    pub const PRESSED_R: u8 = 0x20;
+    pub const PRESSED_DPAD_UP: u8 = 0x10;
+    pub const PRESSED_DPAD_RIGHT: u8 = 0x08;
+    pub const PRESSED_DPAD_DOWN: u8 = 0x04;
+    pub const PRESSED_DPAD_LEFT: u8 = 0x02;
+    pub const PRESSED_START: u8 = 0x01;
+
+    cpp_field!(delta_time: f32; ro_val @ 0x0);
+    cpp_field!(left_x: f32; ro_val @ 0x8);
+    cpp_field!(left_y: f32; ro_val @ 0xc);
+    cpp_field!(right_x: f32; ro_val @ 0x10);
+    cpp_field!(right_y: f32; ro_val @ 0x14);
+    // Bit fields of the buttons held (A to D-pad up) and of those pressed this frame (Z to Start)
+    cpp_field!(held_buttons: u8; ro_val @ 0x2c);
+    cpp_field!(pressed_buttons: u8; ro_val @ 0x2e);
 }
 
 #[repr(C)]
@@ -102,6 +172,8 @@ pub struct CHudMemoParams {
 
 pub enum CSamusHud {}
 impl CSamusHud {
+    cpp_field!(base_hud_frame: *mut CGuiFrame; ro_val @ 0x274);
+
     #[cpp_method(CSamusHud::DisplayHudMemo(const wstring &, const SHudMemoInfo &))]
     pub unsafe fn display_hud_memo(s: *const WString, info: *const CHudMemoParams) {}
 }
@@ -119,6 +191,12 @@ pub struct SConnection {
     pub obj_id: u32,
 }
 
+#[repr(C)]
+pub struct CWeaponMode {
+    // TODO: This is actually an enum, followed by the charged/comboed/instant kill flags
+    pub weapon_type: u32,
+}
+
 pub enum IVisitor {}
 
 #[repr(C)]
@@ -0,0 +1,49 @@
+// Layout of the memory region at preferences.trackerAddress, which the rel patches refresh every
+// frame for external trackers (e.g. reading it from Dolphin's memory). The layout is stable:
+// fields are only ever added at the end, bumping TRACKER_VERSION, so that a tracker which checks
+// `magic` and `version >= N` keeps working with later versions of the patcher.
+//
+// Everything is big-endian, like the rest of the GameCube's memory. The region is 376 bytes:
+//
+// | Offset | Type             | Field                                                          |
+// |--------|------------------|----------------------------------------------------------------|
+// | 0x00   | u32              | magic, "TRCK"                                                  |
+// | 0x04   | u32              | version                                                        |
+// | 0x08   | u32              | frame_count, incremented on every update (wraps around)        |
+// | 0x0C   | u32              | mlvl, asset id of the current world                            |
+// | 0x10   | u32              | area, index of the current room in its world's MLVL            |
+// | 0x14   | u32              | artifacts, bit N set if the Nth artifact (Truth = 0) was found |
+// | 0x18   | f64              | igt, in-game time in seconds                                   |
+// | 0x20   | [(u32, u32); 41] | power_ups, amount and capacity of each item, by item type      |
+// | 0x168  | u32              | integrity, result of the boot-time code check (version 2)      |
+// | 0x16C  | u32              | config_crc, CRC32 of randomprime.json as read from the disc    |
+// | 0x170  | u32              | code_crc, CRC32 of the checked code as it is in memory         |
+// | 0x174  | u32              | rel_crc, CRC32 of patches.rel as read from the disc            |
+//
+// The CRC32s (version 3) are only set with integrityCheck, and are those the patcher prints for
+// the seed if nothing was tampered with.
+
+use crate::mp1::CPowerUp;
+
+pub const TRACKER_MAGIC: u32 = 0x5452434B; // "TRCK"
+pub const TRACKER_VERSION: u32 = 3;
+
+// The item types of the artifacts, Truth to Newborn
+pub const FIRST_ARTIFACT: usize = 29;
+pub const ARTIFACT_COUNT: usize = 12;
+
+#[repr(C)]
+pub struct TrackerRegion {
+    pub magic: u32,
+    pub version: u32,
+    pub frame_count: u32,
+    pub mlvl: u32,
+    pub area: u32,
+    pub artifacts: u32,
+    pub igt: f64,
+    pub power_ups: [CPowerUp; 41],
+    pub integrity: u32, // one of the INTEGRITY_ constants of crate::integrity
+    pub config_crc: u32,
+    pub code_crc: u32,
+    pub rel_crc: u32,
+}
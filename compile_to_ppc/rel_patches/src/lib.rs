@@ -7,13 +7,23 @@ use core::mem::MaybeUninit;
 use linkme::distributed_slice;
 use primeapi::{
     alignment_utils::Aligned32,
-    dol_sdk::dvd::DVDFileInfo,
+    dol_sdk::{
+        card::{
+            CARDFileInfo, CardFunctions, CARD_RESULT_BUSY, CARD_RESULT_NOFILE, CARD_RESULT_READY,
+            CARD_SECTOR_SIZE, CARD_WORKAREA_SIZE,
+        },
+        dvd::DVDFileInfo,
+    },
+    integrity,
     mp1::{
-        CArchitectureQueue, CGameState, CGuiFrame, CGuiTextPane, CGuiTextSupport, CGuiWidget,
-        CMainFlow, CStringTable, CWorldState,
+        CArchitectureQueue, CEntity, CFinalInput, CGameState, CGuiFrame, CGuiTextPane,
+        CGuiTextSupport, CGuiWidget, CHudMemoParams, CMainFlow, CPlayer, CPlayerState, CSamusHud,
+        CStateManager, CStringTable, CWeaponMode, CWorldState,
     },
     patch_fn, prolog_fn,
     rstl::WString,
+    sprintf,
+    tracker::{TrackerRegion, ARTIFACT_COUNT, FIRST_ARTIFACT, TRACKER_MAGIC, TRACKER_VERSION},
     GameVersion,
 };
 
@@ -21,8 +31,29 @@ include!("../../patches_config.rs");
 static mut REL_CONFIG: RelConfig = RelConfig {
     quickplay_mlvl: 0xFFFFFFFF,
     quickplay_mrea: 0xFFFFFFFF,
+    show_igt: false,
+    show_room_timer: false,
+    splits_address: 0,
+    ghost_address: 0,
+    ghost_card_functions: [0; 7],
+    progressive_hook_address: 0,
+    progressive_chains: [[0xFF; 8]; 8],
+    ammo_cost_hook_address: 0,
+    pickup_counter_hook_address: 0,
+    pickup_counter_total: 0,
+    pickup_counter_percentage: false,
+    starting_preset_hook_address: 0,
+    starting_mlvl: 0xFFFFFFFF,
+    starting_presets: [[0xFFFFFFFF; 2]; 3],
+    debug_hook_address: 0,
+    tracker_address: 0,
+    integrity_regions: [[0; 3]; 8],
 };
 
+static mut INTEGRITY: u32 = integrity::INTEGRITY_NOT_CHECKED;
+// CRC32s of the config, the checked code and the rel, see TrackerRegion
+static mut SIGNATURE: [u32; 3] = [0; 3];
+
 #[prolog_fn]
 unsafe extern "C" fn setup_global_state() {
     {
@@ -42,6 +73,236 @@ unsafe extern "C" fn setup_global_state() {
             .unwrap()
             .0;
     }
+
+    // The rel loader applied the rel's own patches before this runs, so the regions mustn't cover
+    // any of them. The code is checked before the hook pointers below are written, but those are
+    // past the end of the dol's code anyway.
+    INTEGRITY = integrity::check_regions(&REL_CONFIG.integrity_regions);
+    if INTEGRITY != integrity::INTEGRITY_NOT_CHECKED {
+        SIGNATURE = [
+            disc_file_crc32(b"randomprime.json\0"),
+            integrity::regions_crc32(&REL_CONFIG.integrity_regions),
+            disc_file_crc32(b"patches.rel\0"),
+        ];
+    }
+
+    // The dol calls through this pointer at the start of CPlayerState::IncrPickUp
+    if REL_CONFIG.progressive_hook_address != 0 {
+        let hook_ptr = REL_CONFIG.progressive_hook_address as *mut u32;
+        *hook_ptr = remap_progressive_item as usize as u32;
+    }
+
+    // The dol calls through this pointer at the start of CStateManager::ApplyLocalDamage
+    if REL_CONFIG.ammo_cost_hook_address != 0 {
+        let hook_ptr = REL_CONFIG.ammo_cost_hook_address as *mut u32;
+        *hook_ptr = apply_ammo_cost as usize as u32;
+    }
+
+    // The dol calls through this pointer at the start of CSamusHud::Update
+    if REL_CONFIG.pickup_counter_hook_address != 0 {
+        let hook_ptr = REL_CONFIG.pickup_counter_hook_address as *mut u32;
+        *hook_ptr = update_pickup_counter as usize as u32;
+    }
+
+    // The dol calls through this pointer at the start of CGameState::SetCurrentWorldId
+    if REL_CONFIG.starting_preset_hook_address != 0 {
+        let hook_ptr = REL_CONFIG.starting_preset_hook_address as *mut u32;
+        *hook_ptr = apply_starting_preset as usize as u32;
+    }
+
+    // The dol calls through this pointer at the start of CPlayer::UpdateVisorState
+    if REL_CONFIG.debug_hook_address != 0 {
+        let hook_ptr = REL_CONFIG.debug_hook_address as *mut u32;
+        *hook_ptr = update_debug_features as usize as u32;
+    }
+
+    // The splits and the ghost are allocated from the game's heap, and their addresses published at
+    // the ones the config gives for external tools to follow
+    if REL_CONFIG.splits_address != 0 {
+        SPLITS = alloc_zeroed::<SplitsRegion>();
+        (*SPLITS).magic = SPLITS_MAGIC;
+        *(REL_CONFIG.splits_address as *mut u32) = SPLITS as u32;
+    }
+    if REL_CONFIG.ghost_address != 0 {
+        GHOST = alloc_zeroed::<GhostRegion>();
+        (*GHOST).magic = GHOST_MAGIC;
+        *(REL_CONFIG.ghost_address as *mut u32) = GHOST as u32;
+
+        CARD = CardFunctions::from_addresses(&REL_CONFIG.ghost_card_functions);
+        if CARD.is_some() {
+            CARD_WORK_AREA = alloc_aligned32(CARD_WORKAREA_SIZE);
+            GHOST_DUMP = alloc_aligned32(GHOST_DUMP_SIZE) as *mut GhostRegion;
+            core::ptr::write_bytes(GHOST_DUMP as *mut u8, 0, GHOST_DUMP_SIZE);
+        }
+    }
+}
+
+// CRC32 of a file of the disc, read in chunks to keep the buffer small, or 0 if it's missing
+unsafe fn disc_file_crc32(name: &[u8]) -> u32 {
+    const CHUNK_SIZE: usize = 0x8000;
+
+    let mut fi = if let Some(fi) = DVDFileInfo::new(name) {
+        fi
+    } else {
+        return 0;
+    };
+    let size = fi.file_length() as usize;
+    let mut recv_buf = alloc::vec![MaybeUninit::<u8>::uninit(); CHUNK_SIZE + 31];
+    let recv_buf = Aligned32::split_unaligned_prefix_mut(&mut recv_buf[..]).1;
+
+    let mut crc = 0;
+    let mut offset = 0;
+    while offset < size {
+        let len = (size - offset).min(CHUNK_SIZE);
+        let chunk = &mut recv_buf[..(len + 31) & !31];
+        {
+            let _ = fi.read_async(chunk, offset as u32, 0);
+        }
+        crc = integrity::crc32_update(crc, chunk[..len].assume_init());
+        offset += len;
+    }
+    crc
+}
+
+unsafe fn alloc_zeroed<T>() -> *mut T {
+    let ptr = primeapi::malloc(core::mem::size_of::<T>()) as *mut T;
+    core::ptr::write_bytes(ptr, 0, 1);
+    ptr
+}
+
+// The memory card needs its buffers 32-byte aligned
+unsafe fn alloc_aligned32(len: usize) -> *mut u8 {
+    let ptr = primeapi::malloc(len + 31) as usize;
+    ((ptr + 31) & !31) as *mut u8
+}
+
+// Progressive pickups give the first item of their chain, with an amount of
+// PROGRESSIVE_AMOUNT + the chain's index and no capacity. Must be kept in sync with
+// PROGRESSIVE_PICKUP_AMOUNT in src/patch_config.rs
+const PROGRESSIVE_AMOUNT: u32 = 0x70;
+const NO_ITEM: u8 = 0xFF;
+
+// Returns the item IncrPickUp should give instead
+unsafe extern "C" fn remap_progressive_item(
+    player_state: *mut CPlayerState,
+    item: i32,
+    amount: u32,
+) -> i32 {
+    let chain = match REL_CONFIG
+        .progressive_chains
+        .get(amount.wrapping_sub(PROGRESSIVE_AMOUNT) as usize)
+    {
+        Some(chain) if chain[0] as i32 == item => chain,
+        _ => return item,
+    };
+
+    // Once the whole chain has been collected, the pickup gives nothing new
+    let next = chain
+        .iter()
+        .take_while(|chain_item| **chain_item != NO_ITEM)
+        .map(|chain_item| *chain_item as i32)
+        .find(|chain_item| !CPlayerState::has_power_up(player_state, *chain_item));
+    match next {
+        Some(next) => {
+            CPlayerState::initialize_power_up(player_state, next, 1);
+            next
+        }
+        None => item,
+    }
+}
+
+// Blast shields with an ammo cost have an extra connection which is never sent, using this state,
+// the item as its message and the cost as its target. Must be kept in sync with
+// AMMO_COST_CONNECTION_STATE in src/patches.rs
+const AMMO_COST_CONNECTION_STATE: u32 = 0x414D4D4F;
+const MISSILES: u32 = 4;
+const POWER_BOMBS: u32 = 7;
+const WEAPON_TYPE_POWER_BOMB: u32 = 5;
+const WEAPON_TYPE_MISSILE: u32 = 6;
+
+// Returns the damage ApplyLocalDamage should deal instead. A costed blast shield only takes
+// damage if the player can pay for it, in which case the ammo is taken away
+unsafe extern "C" fn apply_ammo_cost(
+    _state_mgr: *mut CStateManager,
+    _pos: *const [f32; 3],
+    _dir: *const [f32; 3],
+    damagee: *const CEntity,
+    damage: f32,
+    weapon_mode: *const CWeaponMode,
+) -> f32 {
+    let cost = (*damagee)
+        .connections
+        .iter()
+        .find(|conn| conn.state == AMMO_COST_CONNECTION_STATE);
+    let (item, cost) = match cost {
+        Some(cost) => (cost.msg, cost.obj_id),
+        None => return damage,
+    };
+
+    let weapon_type = match item {
+        MISSILES => WEAPON_TYPE_MISSILE,
+        POWER_BOMBS => WEAPON_TYPE_POWER_BOMB,
+        _ => return damage,
+    };
+    if (*weapon_mode).weapon_type != weapon_type || damage <= 0.0 {
+        return damage;
+    }
+
+    let player_state = *CGameState::player_state(CGameState::global_instance());
+    let power_up = &(*CPlayerState::power_ups(player_state))[item as usize];
+    if power_up.amount < cost {
+        return 0.0;
+    }
+
+    CPlayerState::decr_pickup(player_state, item as i32, cost as i32);
+    damage
+}
+
+// Text pane which the patcher adds to FRME_BaseHud. Must be kept in sync with
+// PICKUP_COUNTER_WIDGET in src/patches.rs
+const PICKUP_COUNTER_WIDGET: &[u8] = b"textpane_pickupcounter\0";
+
+// Refreshes the pickup counter whenever the item count changes or the HUD is reloaded
+unsafe extern "C" fn update_pickup_counter(hud: *mut CSamusHud) {
+    static mut LAST_UPDATE: Option<(*mut CGuiFrame, u32)> = None;
+
+    let frame = CSamusHud::base_hud_frame(hud);
+    if frame.is_null() || REL_CONFIG.pickup_counter_total == 0 {
+        return;
+    }
+    let widget = CGuiFrame::find_widget(frame, PICKUP_COUNTER_WIDGET.as_ptr());
+    if widget.is_null() {
+        return;
+    }
+
+    let player_state = *CGameState::player_state(CGameState::global_instance());
+    let collected = CPlayerState::calculate_item_collection_rate(player_state);
+    if LAST_UPDATE == Some((frame, collected)) {
+        return;
+    }
+    LAST_UPDATE = Some((frame, collected));
+
+    let total = REL_CONFIG.pickup_counter_total;
+    let mut text = [0u8; 32];
+    if REL_CONFIG.pickup_counter_percentage {
+        sprintf(
+            text.as_mut_ptr(),
+            b"Items %d%%\0".as_ptr(),
+            collected.min(total) * 100 / total,
+        );
+    } else {
+        sprintf(
+            text.as_mut_ptr(),
+            b"Items %d/%d\0".as_ptr(),
+            collected,
+            total,
+        );
+    }
+    let len = text.iter().position(|b| *b == 0).unwrap();
+
+    let s = WString::from_ascii(&text[..len]);
+    let text_support = CGuiTextPane::text_support_mut(widget as *mut CGuiTextPane);
+    CGuiTextSupport::set_text(text_support, &s);
 }
 
 #[patch_fn(kind = call,
@@ -102,7 +363,10 @@ unsafe extern "C" fn quickplay_hook_advance_game_state(
         INIT = true;
         if REL_CONFIG.quickplay_mlvl != 0xFFFFFFFF {
             let game_state = CGameState::global_instance();
+            // Quickplay always goes to the starting room, whichever file is selected
+            SKIP_STARTING_PRESET = true;
             CGameState::set_current_world_id(game_state, REL_CONFIG.quickplay_mlvl);
+            SKIP_STARTING_PRESET = false;
             let world_state = CGameState::get_current_world_state(game_state);
             CWorldState::set_desired_area_asset_id(world_state, REL_CONFIG.quickplay_mrea);
             CMainFlow::set_game_state(flow, CMainFlow::CLIENT_FLOW_STATE_GAME, q);
@@ -111,3 +375,505 @@ unsafe extern "C" fn quickplay_hook_advance_game_state(
     }
     CMainFlow::advance_game_state(flow, q)
 }
+
+static mut SKIP_STARTING_PRESET: bool = false;
+
+// Returns the world CGameState::SetCurrentWorldId should switch to. When a new game goes to the
+// starting world on a save file with a starting preset, it goes to the preset's room instead
+unsafe extern "C" fn apply_starting_preset(game_state: *mut CGameState, mlvl: u32) -> u32 {
+    if SKIP_STARTING_PRESET
+        || mlvl != REL_CONFIG.starting_mlvl
+        || CGameState::play_time(game_state) != 0.0
+    {
+        return mlvl;
+    }
+    let [preset_mlvl, preset_mrea] = match REL_CONFIG
+        .starting_presets
+        .get(CGameState::save_file_idx(game_state) as usize)
+    {
+        Some(&preset) if preset[0] != 0xFFFFFFFF => preset,
+        _ => return mlvl,
+    };
+
+    // The world state of the preset's world only exists once it's the current world, so it's
+    // switched to here (without coming back through this hook) to set the area it starts in
+    SKIP_STARTING_PRESET = true;
+    CGameState::set_current_world_id(game_state, preset_mlvl);
+    SKIP_STARTING_PRESET = false;
+    let world_state = CGameState::get_current_world_state(game_state);
+    CWorldState::set_desired_area_asset_id(world_state, preset_mrea);
+    preset_mlvl
+}
+
+// Noclip and the fly camera move the player through walls with the sticks. The fly camera puts it
+// back where it was turned on once it's turned off, so that a layout can be inspected without
+// losing one's place.
+const DEBUG_FLY_SPEED: f32 = 15.0; // Units per second
+
+#[derive(Copy, Clone, PartialEq)]
+enum DebugMovement {
+    Off,
+    Noclip,
+    FlyCamera,
+}
+
+unsafe fn debug_memo(text: &[u8]) {
+    let s = WString::from_ascii(text);
+    let params = CHudMemoParams {
+        display_time: 2.0,
+        clear_memo_window: 0,
+        fadeout_only: 0,
+        hint_memo: 0,
+    };
+    CSamusHud::display_hud_memo(&s, &params);
+}
+
+unsafe extern "C" fn update_debug_features(
+    player: *mut CPlayer,
+    input: *const CFinalInput,
+    mgr: *mut CStateManager,
+) {
+    static mut MOVEMENT: DebugMovement = DebugMovement::Off;
+    static mut POSITION: [f32; 3] = [0.0; 3];
+    static mut FLY_CAMERA_START: [f32; 3] = [0.0; 3];
+
+    let mut transform = CPlayer::transform(player);
+
+    // L + R + D-pad down toggles noclip, L + R + D-pad up the fly camera
+    let held = CFinalInput::held_buttons(input);
+    let pressed = CFinalInput::pressed_buttons(input);
+    let toggled = if held & CFinalInput::L == 0 || held & CFinalInput::R == 0 {
+        None
+    } else if pressed & CFinalInput::PRESSED_DPAD_DOWN != 0 {
+        Some(DebugMovement::Noclip)
+    } else if pressed & CFinalInput::PRESSED_DPAD_UP != 0 {
+        Some(DebugMovement::FlyCamera)
+    } else {
+        None
+    };
+
+    if let Some(movement) = toggled {
+        let mut position = [transform[3], transform[7], transform[11]];
+        if MOVEMENT == DebugMovement::FlyCamera {
+            position = FLY_CAMERA_START;
+            transform[3] = position[0];
+            transform[7] = position[1];
+            transform[11] = position[2];
+            CPlayer::teleport(player, &transform, mgr, false);
+        }
+        MOVEMENT = if MOVEMENT == movement {
+            DebugMovement::Off
+        } else {
+            movement
+        };
+        POSITION = position;
+        FLY_CAMERA_START = position;
+        let memo: &[u8] = match MOVEMENT {
+            DebugMovement::Off => b"Debug movement off",
+            DebugMovement::Noclip => b"Noclip on",
+            DebugMovement::FlyCamera => b"Fly camera on",
+        };
+        debug_memo(memo);
+    }
+
+    if MOVEMENT == DebugMovement::Off {
+        return;
+    }
+
+    // The position is kept here rather than read back from the player, so that gravity and
+    // collisions don't move it in between frames
+    let distance = DEBUG_FLY_SPEED * CFinalInput::delta_time(input);
+    let forward = CFinalInput::left_y(input) * distance;
+    let right = CFinalInput::left_x(input) * distance;
+    let up = CFinalInput::right_y(input) * distance;
+    let mut position = POSITION;
+    for (i, coord) in position.iter_mut().enumerate() {
+        *coord += transform[i * 4] * right + transform[i * 4 + 1] * forward;
+    }
+    position[2] += up;
+    POSITION = position;
+
+    transform[3] = position[0];
+    transform[7] = position[1];
+    transform[11] = position[2];
+    CPlayer::teleport(player, &transform, mgr, false);
+}
+
+// Layout of the region whose address is written to REL_CONFIG.splits_address, meant to be read by
+// an auto-splitter. `splits` is a ring buffer, the most recent split is at index
+// (split_count - 1) % MAX_SPLITS
+const SPLITS_MAGIC: u32 = 0x53504C54; // "SPLT"
+const MAX_SPLITS: usize = 256;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct Split {
+    mlvl: u32,
+    area: u32,
+    room_time: f32,
+    igt: f32,
+}
+
+#[repr(C)]
+struct SplitsRegion {
+    magic: u32,
+    split_count: u32,
+    current_mlvl: u32,
+    current_area: u32,
+    igt: f64,
+    splits: [Split; MAX_SPLITS],
+}
+
+static mut SPLITS: *mut SplitsRegion = core::ptr::null_mut();
+
+unsafe fn format_time(buf: *mut u8, fmt: *const u8, time: f64) {
+    let centiseconds = (time * 100.0) as u32;
+    let seconds = centiseconds / 100;
+    sprintf(
+        buf,
+        fmt,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        centiseconds % 100,
+    );
+}
+
+unsafe fn display_split(room_time: f64, igt: f64) {
+    let mut text = [0u8; 64];
+    let mut len = 0;
+    if REL_CONFIG.show_room_timer {
+        format_time(
+            text.as_mut_ptr(),
+            b"Room %d:%02d:%02d.%02d\0".as_ptr(),
+            room_time,
+        );
+        len = text.iter().position(|b| *b == 0).unwrap();
+    }
+    if REL_CONFIG.show_igt {
+        if len != 0 {
+            text[len..len + 3].copy_from_slice(b" | ");
+            len += 3;
+        }
+        format_time(
+            text[len..].as_mut_ptr(),
+            b"IGT %d:%02d:%02d.%02d\0".as_ptr(),
+            igt,
+        );
+        len = text.iter().position(|b| *b == 0).unwrap();
+    }
+
+    let s = WString::from_ascii(&text[..len]);
+    let params = CHudMemoParams {
+        display_time: 3.0,
+        clear_memo_window: 0,
+        fadeout_only: 0,
+        hint_memo: 0,
+    };
+    CSamusHud::display_hud_memo(&s, &params);
+}
+
+unsafe fn update_split_timer() {
+    static mut CURRENT_ROOM: Option<(u32, u32)> = None;
+    static mut ROOM_START_TIME: f64 = 0.0;
+
+    if !REL_CONFIG.show_igt && !REL_CONFIG.show_room_timer && REL_CONFIG.splits_address == 0 {
+        return;
+    }
+
+    let game_state = CGameState::global_instance();
+    let igt = CGameState::play_time(game_state);
+    let world_state = CGameState::get_current_world_state(game_state);
+    let room = (
+        CWorldState::mlvl_id(world_state),
+        CWorldState::area_id(world_state),
+    );
+
+    let splits = if !SPLITS.is_null() {
+        let splits = SPLITS;
+        (*splits).current_mlvl = room.0;
+        (*splits).current_area = room.1;
+        (*splits).igt = igt;
+        Some(splits)
+    } else {
+        None
+    };
+
+    // The clock went backwards, so a save was loaded. Start timing the room over.
+    if igt < ROOM_START_TIME {
+        CURRENT_ROOM = Some(room);
+        ROOM_START_TIME = igt;
+        return;
+    }
+
+    let previous_room = match CURRENT_ROOM {
+        Some(previous_room) if previous_room != room => previous_room,
+        Some(_) => return,
+        None => {
+            CURRENT_ROOM = Some(room);
+            ROOM_START_TIME = igt;
+            return;
+        }
+    };
+
+    let room_time = igt - ROOM_START_TIME;
+    if let Some(splits) = splits {
+        let index = (*splits).split_count as usize % MAX_SPLITS;
+        (*splits).splits[index] = Split {
+            mlvl: previous_room.0,
+            area: previous_room.1,
+            room_time: room_time as f32,
+            igt: igt as f32,
+        };
+        (*splits).split_count += 1;
+    }
+
+    if REL_CONFIG.show_igt || REL_CONFIG.show_room_timer {
+        display_split(room_time, igt);
+    }
+
+    CURRENT_ROOM = Some(room);
+    ROOM_START_TIME = igt;
+}
+
+// Layout of the region whose address is written to REL_CONFIG.ghost_address. `frames` is a ring
+// buffer holding the player's position and facing for the last MAX_GHOST_FRAMES frames, and
+// `last_room_start` and `last_room_end` delimit the frames of the last room that was exited.
+const GHOST_MAGIC: u32 = 0x47485354; // "GHST"
+const MAX_GHOST_FRAMES: usize = 4096;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct GhostFrame {
+    igt: f32,
+    mlvl: u32,
+    area: u32,
+    position: [f32; 3],
+    facing: [f32; 3],
+}
+
+#[repr(C)]
+struct GhostRegion {
+    magic: u32,
+    frame_count: u32,
+    room_start: u32,
+    last_room_start: u32,
+    last_room_end: u32,
+    frames: [GhostFrame; MAX_GHOST_FRAMES],
+}
+
+static mut GHOST: *mut GhostRegion = core::ptr::null_mut();
+
+unsafe fn record_ghost_frame() {
+    if GHOST.is_null() {
+        return;
+    }
+
+    let player = CStateManager::player(CStateManager::global_instance());
+    if player.is_null() {
+        return;
+    }
+
+    let game_state = CGameState::global_instance();
+    let world_state = CGameState::get_current_world_state(game_state);
+    let mlvl = CWorldState::mlvl_id(world_state);
+    let area = CWorldState::area_id(world_state);
+
+    // Exiting a room dumps its frames for the extractor
+    let ghost = GHOST;
+    let frame_count = (*ghost).frame_count;
+    if frame_count != 0 {
+        let previous = (*ghost).frames[(frame_count as usize - 1) % MAX_GHOST_FRAMES];
+        if previous.mlvl != mlvl || previous.area != area {
+            (*ghost).last_room_start = (*ghost).room_start;
+            (*ghost).last_room_end = frame_count;
+            (*ghost).room_start = frame_count;
+            start_ghost_dump();
+        }
+    }
+
+    let transform = CPlayer::transform(player);
+    (*ghost).frames[frame_count as usize % MAX_GHOST_FRAMES] = GhostFrame {
+        igt: CGameState::play_time(game_state) as f32,
+        mlvl,
+        area,
+        position: [transform[3], transform[7], transform[11]],
+        facing: [transform[1], transform[5], transform[9]],
+    };
+    (*ghost).frame_count = frame_count.wrapping_add(1);
+
+    update_ghost_dump();
+}
+
+// When the memory card functions are known, the frames of every room that's exited are also
+// written to a file on the memory card in slot A, in the same layout as the ghost region, for the
+// extractor to read from a GCI. The write takes a few frames, rooms exited meanwhile aren't dumped.
+const CARD_CHANNEL: i32 = 0;
+const GHOST_FILE_NAME: &[u8] = b"randomprime_ghost\0";
+const GHOST_DUMP_SIZE: usize =
+    (core::mem::size_of::<GhostRegion>() + CARD_SECTOR_SIZE - 1) & !(CARD_SECTOR_SIZE - 1);
+
+#[derive(Copy, Clone, PartialEq)]
+enum GhostDump {
+    Idle,
+    Mounting,
+    Creating,
+    Writing,
+}
+
+static mut CARD: Option<CardFunctions> = None;
+static mut CARD_WORK_AREA: *mut u8 = core::ptr::null_mut();
+static mut CARD_FILE: CARDFileInfo = CARDFileInfo {
+    chan: 0,
+    file_no: 0,
+    offset: 0,
+    length: 0,
+    i_block: 0,
+    padding: 0,
+};
+static mut GHOST_DUMP: *mut GhostRegion = core::ptr::null_mut();
+static mut GHOST_DUMP_STATE: GhostDump = GhostDump::Idle;
+
+unsafe fn start_ghost_dump() {
+    let card = match CARD {
+        Some(card) if GHOST_DUMP_STATE == GhostDump::Idle => card,
+        _ => return,
+    };
+
+    // The dump holds the last room only, from its first frame
+    let (ghost, dump) = (GHOST, GHOST_DUMP);
+    let end = (*ghost).last_room_end;
+    let start = (*ghost)
+        .last_room_start
+        .max(end.saturating_sub(MAX_GHOST_FRAMES as u32));
+    for i in start..end {
+        (*dump).frames[(i - start) as usize] = (*ghost).frames[i as usize % MAX_GHOST_FRAMES];
+    }
+    (*dump).magic = GHOST_MAGIC;
+    (*dump).frame_count = end - start;
+    (*dump).room_start = end - start;
+    (*dump).last_room_start = 0;
+    (*dump).last_room_end = end - start;
+
+    if (card.mount_async)(CARD_CHANNEL, CARD_WORK_AREA, None, None) == CARD_RESULT_READY {
+        GHOST_DUMP_STATE = GhostDump::Mounting;
+    }
+}
+
+unsafe fn write_ghost_dump(card: CardFunctions) -> GhostDump {
+    let result = (card.write_async)(
+        &mut CARD_FILE,
+        GHOST_DUMP as *const u8,
+        GHOST_DUMP_SIZE as i32,
+        0,
+        None,
+    );
+    if result == CARD_RESULT_READY {
+        GhostDump::Writing
+    } else {
+        (card.close)(&mut CARD_FILE);
+        GhostDump::Idle
+    }
+}
+
+// Moves the dump on once the card is done with the previous step
+unsafe fn update_ghost_dump() {
+    let card = match CARD {
+        Some(card) if GHOST_DUMP_STATE != GhostDump::Idle => card,
+        _ => return,
+    };
+    let result = (card.get_result_code)(CARD_CHANNEL);
+    if result == CARD_RESULT_BUSY {
+        return;
+    }
+
+    let next = match (GHOST_DUMP_STATE, result) {
+        (GhostDump::Mounting, CARD_RESULT_READY) => {
+            match (card.open)(CARD_CHANNEL, GHOST_FILE_NAME.as_ptr(), &mut CARD_FILE) {
+                CARD_RESULT_READY => write_ghost_dump(card),
+                CARD_RESULT_NOFILE => {
+                    let result = (card.create_async)(
+                        CARD_CHANNEL,
+                        GHOST_FILE_NAME.as_ptr(),
+                        GHOST_DUMP_SIZE as u32,
+                        &mut CARD_FILE,
+                        None,
+                    );
+                    if result == CARD_RESULT_READY {
+                        GhostDump::Creating
+                    } else {
+                        GhostDump::Idle
+                    }
+                }
+                _ => GhostDump::Idle,
+            }
+        }
+        (GhostDump::Creating, CARD_RESULT_READY) => write_ghost_dump(card),
+        (GhostDump::Writing, _) => {
+            (card.close)(&mut CARD_FILE);
+            GhostDump::Idle
+        }
+        _ => GhostDump::Idle,
+    };
+
+    // The card is only mounted for as long as the dump, so that the game's saves find it as usual
+    if next == GhostDump::Idle {
+        (card.unmount)(CARD_CHANNEL);
+    }
+    GHOST_DUMP_STATE = next;
+}
+
+unsafe fn update_tracker() {
+    if REL_CONFIG.tracker_address == 0 {
+        return;
+    }
+
+    let game_state = CGameState::global_instance();
+    let world_state = CGameState::get_current_world_state(game_state);
+    let player_state = *CGameState::player_state(game_state);
+    let power_ups = *CPlayerState::power_ups(player_state);
+    let artifacts = power_ups[FIRST_ARTIFACT..FIRST_ARTIFACT + ARTIFACT_COUNT]
+        .iter()
+        .enumerate()
+        .filter(|(_, power_up)| power_up.amount != 0)
+        .fold(0, |artifacts, (i, _)| artifacts | (1 << i));
+
+    let tracker = REL_CONFIG.tracker_address as *mut TrackerRegion;
+    (*tracker).magic = TRACKER_MAGIC;
+    (*tracker).version = TRACKER_VERSION;
+    (*tracker).frame_count = (*tracker).frame_count.wrapping_add(1);
+    (*tracker).mlvl = CWorldState::mlvl_id(world_state);
+    (*tracker).area = CWorldState::area_id(world_state);
+    (*tracker).artifacts = artifacts;
+    (*tracker).igt = CGameState::play_time(game_state);
+    (*tracker).power_ups = power_ups;
+    (*tracker).integrity = INTEGRITY;
+    (*tracker).config_crc = SIGNATURE[0];
+    (*tracker).code_crc = SIGNATURE[1];
+    (*tracker).rel_crc = SIGNATURE[2];
+}
+
+// Runs once per frame, in place of UpdateEscapeSequenceTimer's return
+#[patch_fn(kind = return,
+           target = "UpdateEscapeSequenceTimer__13CStateManagerFf" + 0x17c,
+           version = Ntsc0_00)]
+#[patch_fn(kind = return,
+           target = "UpdateEscapeSequenceTimer__13CStateManagerFf" + 0x17c,
+           version = Ntsc0_01)]
+#[patch_fn(kind = return,
+           target = "UpdateEscapeSequenceTimer__13CStateManagerFf" + 0x17c,
+           version = Ntsc0_02)]
+#[patch_fn(kind = return,
+           target = "UpdateEscapeSequenceTimer__13CStateManagerFf" + 0x17c,
+           version = NtscK)]
+#[patch_fn(kind = return,
+           target = "UpdateEscapeSequenceTimer__13CStateManagerFf" + 0x184,
+           version = NtscJ)]
+#[patch_fn(kind = return,
+           target = "UpdateEscapeSequenceTimer__13CStateManagerFf" + 0x184,
+           version = Pal)]
+unsafe extern "C" fn on_frame() {
+    update_split_timer();
+    record_ghost_frame();
+    update_tracker();
+}
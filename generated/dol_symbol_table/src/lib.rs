@@ -20,6 +20,34 @@ pub struct Mp1Symbol {
     // pub addr_trilogy_pal: Option<u32>,
 }
 
+/// The versions of the game that have a symbol table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DolVersion {
+    NtscU0_00,
+    NtscU0_01,
+    NtscU0_02,
+    Pal,
+    NtscK,
+    NtscJ,
+}
+
+impl Mp1Symbol {
+    /// The address of the symbol in the given version of the game, if it's known. Besides the
+    /// names from the linker maps, the tables also name the places inside functions that patches
+    /// need, so that a patch only has to name what it changes and every version with that name
+    /// in its table supports it.
+    pub fn addr(&self, version: DolVersion) -> Option<u32> {
+        match version {
+            DolVersion::NtscU0_00 => self.addr_0_00,
+            DolVersion::NtscU0_01 => self.addr_0_01,
+            DolVersion::NtscU0_02 => self.addr_0_02,
+            DolVersion::Pal => self.addr_pal,
+            DolVersion::NtscK => self.addr_kor,
+            DolVersion::NtscJ => self.addr_jpn,
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! mp1_symbol {
     ($syn_name:tt) => {
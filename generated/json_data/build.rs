@@ -24,4 +24,8 @@ fn main() {
     helper("skippable_cutscenes_competitive.jsonc");
     helper("skippable_cutscenes_pal.jsonc");
     helper("qol.jsonc");
+    helper("shuffle_bounds.jsonc");
+    helper("preset_casual.jsonc");
+    helper("preset_competitive.jsonc");
+    helper("preset_glitchless.jsonc");
 }
@@ -11,3 +11,15 @@ pub const SKIPPABLE_CUTSCENES_COMPETITIVE: &str = include_str!(concat!(
     "/skippable_cutscenes_competitive.jsonc.min.json"
 ));
 pub const QOL: &str = include_str!(concat!(env!("OUT_DIR"), "/qol.jsonc.min.json"));
+pub const SHUFFLE_BOUNDS: &str =
+    include_str!(concat!(env!("OUT_DIR"), "/shuffle_bounds.jsonc.min.json"));
+pub const PRESET_CASUAL: &str =
+    include_str!(concat!(env!("OUT_DIR"), "/preset_casual.jsonc.min.json"));
+pub const PRESET_COMPETITIVE: &str = include_str!(concat!(
+    env!("OUT_DIR"),
+    "/preset_competitive.jsonc.min.json"
+));
+pub const PRESET_GLITCHLESS: &str = include_str!(concat!(
+    env!("OUT_DIR"),
+    "/preset_glitchless.jsonc.min.json"
+));
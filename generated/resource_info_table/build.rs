@@ -0,0 +1,124 @@
+use std::{
+    env,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+// Builds runtime lookup tables of every texture, audio group, frame and font so that they can be
+// referred to by name from the config, plus a table of every resource by ID for the asset browser.
+fn main() {
+    let output_path = Path::new(&env::var("OUT_DIR").unwrap()).join("lookup_tables.rs");
+    let mut output_file = BufWriter::new(File::create(output_path).unwrap());
+
+    let resources_path =
+        Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("resource_info.txt");
+    println!("cargo:rerun-if-changed={}", resources_path.display());
+    let resources_file = BufReader::new(File::open(resources_path).unwrap());
+
+    let mut textures: Vec<(String, String)> = vec![];
+    let mut audio_groups: Vec<(String, String)> = vec![];
+    let mut frames: Vec<(String, String)> = vec![];
+    let mut fonts: Vec<(String, String)> = vec![];
+    let mut all_resources: Vec<(u32, String, String)> = vec![];
+    for line in resources_file.lines() {
+        let line = line.unwrap();
+        if line.is_empty() {
+            continue;
+        }
+        let mut parts = line.split('"');
+        assert_eq!(parts.next(), Some(""));
+        let long_name = parts.next().unwrap();
+        let res_id = &parts.next().unwrap()[2..12];
+        let res_type = parts.next().unwrap();
+        let mut pak_names = parts
+            .filter(|s| !(s.contains(',') || s.contains('[') || s.contains(']') || s.is_empty()))
+            .collect::<Vec<_>>();
+
+        let last_pak = pak_names.last().unwrap();
+        let short_name = if last_pak[last_pak.len() - 4..].to_lowercase() != ".pak" {
+            pak_names.pop()
+        } else {
+            None
+        };
+
+        let pak_names_formatted = pak_names
+            .iter()
+            .map(|name| format!("b{:?}", name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let resource_data = format!(
+            "crate::ResourceInfo {{
+                long_name: {:?},
+                short_name: {:?},
+                res_id: {},
+                fourcc: reader_writer::FourCC::from_bytes(b\"{}\"),
+                paks: &[{}],
+            }}",
+            long_name, short_name, res_id, res_type, pak_names_formatted
+        );
+        all_resources.push((
+            u32::from_str_radix(&res_id[2..], 16).unwrap(),
+            long_name.to_string(),
+            resource_data.clone(),
+        ));
+
+        let resources = match res_type {
+            "TXTR" => &mut textures,
+            "AGSC" => &mut audio_groups,
+            "FRME" => &mut frames,
+            "FONT" => &mut fonts,
+            _ => continue,
+        };
+        if let Some(short_name) = short_name {
+            resources.push((short_name.to_string(), resource_data.clone()));
+        }
+        resources.push((long_name.to_string(), resource_data));
+    }
+    write_map(&mut output_file, "TEXTURES", &textures);
+    write_map(&mut output_file, "AUDIO_GROUPS", &audio_groups);
+    write_map(&mut output_file, "FRAMES", &frames);
+    write_map(&mut output_file, "FONTS", &fonts);
+
+    // Every resource by ID, and the IDs by full path
+    write!(
+        output_file,
+        "static RESOURCES: phf::Map<u32, crate::ResourceInfo> = "
+    )
+    .unwrap();
+    let mut map_generator = phf_codegen::Map::new();
+    for (res_id, _, resource_data) in &all_resources {
+        map_generator.entry(*res_id, resource_data);
+    }
+    write!(output_file, "{}", map_generator.build()).unwrap();
+    writeln!(output_file, ";").unwrap();
+
+    write!(
+        output_file,
+        "static RESOURCE_IDS: phf::Map<&'static str, u32> = "
+    )
+    .unwrap();
+    let mut map_generator = phf_codegen::Map::new();
+    for (res_id, long_name, _) in &all_resources {
+        map_generator.entry(&long_name[..], &format!("0x{:08x}", res_id));
+    }
+    write!(output_file, "{}", map_generator.build()).unwrap();
+    writeln!(output_file, ";").unwrap();
+}
+
+fn write_map(output_file: &mut impl Write, name: &str, resources: &[(String, String)]) {
+    write!(
+        output_file,
+        "static {}: phf::Map<&'static str, crate::ResourceInfo> = ",
+        name
+    )
+    .unwrap();
+
+    let mut map_generator = phf_codegen::Map::new();
+    for (resource_name, resource_data) in resources {
+        map_generator.entry(&resource_name[..], resource_data);
+    }
+    write!(output_file, "{}", map_generator.build()).unwrap();
+    writeln!(output_file, ";").unwrap();
+}
@@ -28,3 +28,46 @@ impl<'a> From<ResourceInfo> for (&'a [u8], u32) {
         (val.paks[0], val.res_id)
     }
 }
+
+include!(concat!(env!("OUT_DIR"), "/lookup_tables.rs"));
+
+/// Looks up a TXTR by either its full path or its short name
+pub fn texture_info(name: &str) -> Option<ResourceInfo> {
+    TEXTURES.get(name).copied()
+}
+
+/// Looks up an AGSC by either its full path or its short name, with or without the extension
+pub fn audio_group_info(name: &str) -> Option<ResourceInfo> {
+    AUDIO_GROUPS
+        .get(name)
+        .or_else(|| AUDIO_GROUPS.get(&format!("{}.AGSC", name)[..]))
+        .copied()
+}
+
+/// Looks up a FRME by either its full path or its short name, with or without the extension
+pub fn frame_info(name: &str) -> Option<ResourceInfo> {
+    FRAMES
+        .get(name)
+        .or_else(|| FRAMES.get(&format!("{}.FRME", name)[..]))
+        .copied()
+}
+
+/// Looks up a FONT by either its full path or its short name, with or without the extension
+pub fn font_info(name: &str) -> Option<ResourceInfo> {
+    FONTS
+        .get(name)
+        .or_else(|| FONTS.get(&format!("{}.FONT", name)[..]))
+        .copied()
+}
+
+/// Looks up any resource by its asset ID
+pub fn resource_info_by_id(res_id: u32) -> Option<ResourceInfo> {
+    RESOURCES.get(&res_id).copied()
+}
+
+/// Looks up any resource by its full path
+pub fn resource_info_by_name(name: &str) -> Option<ResourceInfo> {
+    RESOURCE_IDS
+        .get(name)
+        .and_then(|res_id| resource_info_by_id(*res_id))
+}
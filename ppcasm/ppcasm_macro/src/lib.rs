@@ -376,6 +376,7 @@ decl_instrs! {
     andis,       (r:d), (r:a), (i:imm)  => (6;29) | d | a | (16;imm);
     b[l][a],    (l:li)                  => (6;18) | (24;li) | (?a) | (?l);
     blr                                 => (32;0x4e800020);
+    bctr[l]                             => (6;19) | (5;20) | (5;0) | (5;0) | (10;528) | (?l);
     blt[l][a],  (l:li)                  => (6;16) | (5;12) | (5;0) | (14;li) | (?a) | (?l);
     bge[l][a],  (l:li)                  => (6;16) | (5;4)  | (5;0) | (14;li) | (?a) | (?l);
     bgt[l][a],  (l:li)                  => (6;16) | (5;12) | (5;1) | (14;li) | (?a) | (?l);
@@ -392,6 +393,7 @@ decl_instrs! {
     fadds[.],   (f:d), (f:a), (f:b)     => (6;59) | d | a | b | (10;21) | (?.);
     fcmpu,      (cr:a), (f:d), (f:s)    => (6;63) | a | (2;0) | d | s | (11;0);
     fdivs[.],   (f:d), (f:a), (f:b)     => (6;59) | d | a | b | (10;18) | (?.);
+    fmuls[.],   (f:d), (f:a), (f:c)     => (6;59) | d | a | (5;0) | c | (5;25) | (?.);
     fsubs[.],   (f:d), (f:a), (f:b)     => (6;59) | d | a | b | (10;20) | (?.);
     fmr,        (f:a), (f:s)            => (6;63) | a | (5;0) | s | (11;144);
     lbz,        (r:d), (r:a:dis)        => (6;34) | d | a | (16;dis);
@@ -408,6 +410,7 @@ decl_instrs! {
     mflr,       (r:d)                   => (6;31) | d | (10;0x100) | (10;339) | (1;0);
     mr,         (r:a), (r:s)            => (6;31) | s | a | s | (10;444) | (1;0);
     mtlr,       (r:d)                   => (6;31) | d | (10;0x100) | (10;467) | (1;0);
+    mtctr,      (r:d)                   => (6;31) | d | (10;0x120) | (10;467) | (1;0);
     mullw[o][.],(r:d), (r:a), (r:b)     => (6;31) | d | a | b | (?o) | (9;235) | (?.);
     nop                                 => (32;0x60000000);
     or[.],      (r:d), (r:a), (r:b)     => (6;31) | d | a | b | (10;444) | (?.);
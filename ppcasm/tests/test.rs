@@ -18,12 +18,24 @@ fn test_ppcasm() {
     let asm = ppcasm!(0x0, { mflr r6 });
     assert_eq!(asm.encoded_bytes(), &[0x7c, 0xc8, 0x02, 0xa6]);
 
+    let asm = ppcasm!(0x0, {
+        mtctr   r12;
+        bctrl;
+    });
+    assert_eq!(
+        asm.encoded_bytes(),
+        &[0x7d, 0x89, 0x03, 0xa6, 0x4e, 0x80, 0x04, 0x21]
+    );
+
     let asm = ppcasm!(0x10000, { beq @{ 0x9c - 0x24 }; });
     assert_eq!(asm.encoded_bytes(), &[0x41, 0x82, 0x00, 0x78]);
 
     let asm = ppcasm!(0x0, { cntlzw  r0, r0; });
     assert_eq!(asm.encoded_bytes(), &[0x7c, 0x00, 0x00, 0x34]);
 
+    let asm = ppcasm!(0x0, { fmuls  f2, f2, f0; });
+    assert_eq!(asm.encoded_bytes(), &[0xec, 0x42, 0x00, 0x32]);
+
     let asm = ppcasm!(0x0, { lis     r4, {ppcasm::upper_bits(0x158f0000)} });
     assert_eq!(asm.encoded_bytes(), &[0x3C, 0x80, 0x15, 0x8F]);
 
@@ -1,19 +1,21 @@
 use std::{collections::HashMap, convert::TryInto, iter};
 
 use reader_writer::{CStrConversionExtension, FourCC, Reader};
-use resource_info_table::resource_info;
+use resource_info_table::{resource_info, ResourceInfo};
 use structs::{res_id, ResId, SclyPropertyData};
 
 use crate::{
+    custom_assets::{asset_dependencies, custom_asset_ids},
     door_meta::DoorType,
     mlvl_wrapper,
     patch_config::{
         ActorKeyFrameConfig, ActorRotateConfig, BlockConfig, BombSlotConfig, CameraConfig,
         CameraFilterKeyframeConfig, CameraWaypointConfig, ControllerActionConfig, CounterConfig,
-        DamageType, FogConfig, GenericTexture, HudmemoConfig, LockOnPoint, PlatformConfig,
-        PlatformType, PlayerActorConfig, PlayerHintConfig, RelayConfig, SpawnPointConfig,
-        SpecialFunctionConfig, StreamedAudioConfig, SwitchConfig, TimerConfig, TriggerConfig,
-        WaterConfig, WaypointConfig, WorldLightFaderConfig,
+        DamageType, EffectConfig, FogConfig, GenericTexture, HudmemoConfig, LockOnPoint,
+        ObjectiveConfig, PlatformConfig, PlatformType, PlayerActorConfig, PlayerHintConfig,
+        RelayConfig, SpawnPointConfig, SpecialFunctionConfig, SpecialFunctionType, StationConfig,
+        StationType, StreamedAudioConfig, SwitchConfig, TimerConfig, TriggerConfig, WaterConfig,
+        WaypointConfig, WorldLightFaderConfig,
     },
     patcher::PatcherState,
     patches::{string_to_cstr, WaterType},
@@ -607,6 +609,85 @@ pub fn patch_add_trigger(
     add_edit_obj_helper!(area, config.id, config.layer, Trigger, new, update);
 }
 
+pub fn patch_add_effect(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+    config: EffectConfig,
+) -> Result<(), String> {
+    macro_rules! new {
+        () => {
+            structs::Effect {
+                name: b"my effect\0".as_cstr(),
+                position: config.position.unwrap_or([0.0, 0.0, 0.0]).into(),
+                rotation: config.rotation.unwrap_or([0.0, 0.0, 0.0]).into(),
+                scale: config.scale.unwrap_or([1.0, 1.0, 1.0]).into(),
+                part: ResId::<res_id::PART>::new(config.part.unwrap_or(0xFFFFFFFF)),
+                elsc: ResId::invalid(),
+                hot_in_thermal: config.hot_in_thermal.unwrap_or(false) as u8,
+                no_timer_unless_area_occluded: 0,
+                rebuild_systems_on_active: 1,
+                active: config.active.unwrap_or(true) as u8,
+                use_rate_inverse_cam_dist: 0,
+                rate_inverse_cam_dist: 5.0,
+                rate_inverse_cam_dist_rate: 0.5,
+                duration: 0.2,
+                dureation_reset_while_visible: 0.1,
+                use_rate_cam_dist_range: 0,
+                rate_cam_dist_range_min: 20.0,
+                rate_cam_dist_range_max: 30.0,
+                rate_cam_dist_range_far_rate: 0.0,
+                combat_visor_visible: 1,
+                thermal_visor_visible: 1,
+                xray_visor_visible: 1,
+                die_when_systems_done: 0,
+                light_params: structs::scly_structs::LightParameters {
+                    unknown0: 1,
+                    unknown1: 1.0,
+                    shadow_tessellation: 0,
+                    unknown2: 1.0,
+                    unknown3: 20.0,
+                    color: [1.0, 1.0, 1.0, 1.0].into(),
+                    unknown4: 0,
+                    world_lighting: 1,
+                    light_recalculation: 1,
+                    unknown5: [0.0, 0.0, 0.0].into(),
+                    unknown6: 4,
+                    unknown7: 4,
+                    unknown8: 0,
+                    light_layer_id: 0,
+                },
+            }
+        };
+    }
+
+    macro_rules! update {
+        ($obj:expr) => {
+            let property_data = $obj.property_data.as_effect_mut().unwrap();
+
+            if let Some(active) = config.active {
+                property_data.active = active as u8
+            }
+            if let Some(position) = config.position {
+                property_data.position = position.into()
+            }
+            if let Some(rotation) = config.rotation {
+                property_data.rotation = rotation.into()
+            }
+            if let Some(scale) = config.scale {
+                property_data.scale = scale.into()
+            }
+            if let Some(part) = config.part {
+                property_data.part = ResId::<res_id::PART>::new(part)
+            }
+            if let Some(hot_in_thermal) = config.hot_in_thermal {
+                property_data.hot_in_thermal = hot_in_thermal as u8
+            }
+        };
+    }
+
+    add_edit_obj_helper!(area, config.id, config.layer, Effect, new, update);
+}
+
 pub fn patch_add_special_fn(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
@@ -884,6 +965,100 @@ pub fn patch_add_counter(
     add_edit_obj_helper!(area, Some(config.id), config.layer, Counter, new, update);
 }
 
+pub fn patch_add_objective(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+    config: ObjectiveConfig,
+    door_connections: Vec<structs::Connection>,
+) -> Result<(), String> {
+    let mrea_id = area.mlvl_area.mrea.to_u32();
+    let count = config.count.unwrap_or(config.enemy_ids.len() as u32);
+    if count == 0 {
+        return Err(format!(
+            "Objective in room 0x{:X} needs at least one enemy",
+            mrea_id
+        ));
+    }
+
+    let layer = config.layer.unwrap_or(0);
+    while area.layer_flags.layer_count <= layer {
+        area.add_layer(b"New Layer\0".as_cstr());
+    }
+
+    let layer = layer as usize;
+    let counter_id = area.new_object_id_from_layer_id(layer);
+    let relay_id = area.new_object_id_from_layer_id(layer);
+
+    let scly = area.mrea().scly_section_mut();
+    let layers = scly.layers.as_mut_vec();
+
+    // Each enemy death counts towards the objective
+    for enemy_id in config.enemy_ids.iter() {
+        let enemy = layers
+            .iter_mut()
+            .flat_map(|layer| layer.objects.iter_mut())
+            .find(|obj| obj.instance_id & 0x00FFFFFF == enemy_id & 0x00FFFFFF)
+            .ok_or_else(|| {
+                format!(
+                    "Could not find enemy 0x{:X} for objective in room 0x{:X}",
+                    enemy_id, mrea_id
+                )
+            })?;
+        enemy.connections.as_mut_vec().push(structs::Connection {
+            state: structs::ConnectionState::DEAD,
+            message: structs::ConnectionMsg::INCREMENT,
+            target_object_id: counter_id,
+        });
+    }
+
+    let mut relay_connections = door_connections;
+    for &target_object_id in config.activate_ids.iter().flatten() {
+        relay_connections.push(structs::Connection {
+            state: structs::ConnectionState::ZERO,
+            message: structs::ConnectionMsg::ACTIVATE,
+            target_object_id,
+        });
+    }
+    for &target_object_id in config.play_ids.iter().flatten() {
+        relay_connections.push(structs::Connection {
+            state: structs::ConnectionState::ZERO,
+            message: structs::ConnectionMsg::PLAY,
+            target_object_id,
+        });
+    }
+
+    layers[layer].objects.as_mut_vec().extend_from_slice(&[
+        structs::SclyObject {
+            instance_id: counter_id,
+            property_data: structs::Counter {
+                name: b"objective counter\0".as_cstr(),
+                start_value: 0,
+                max_value: count,
+                auto_reset: 0,
+                active: config.active.unwrap_or(true) as u8,
+            }
+            .into(),
+            connections: vec![structs::Connection {
+                state: structs::ConnectionState::MAX_REACHED,
+                message: structs::ConnectionMsg::SET_TO_ZERO,
+                target_object_id: relay_id,
+            }]
+            .into(),
+        },
+        structs::SclyObject {
+            instance_id: relay_id,
+            property_data: structs::Relay {
+                name: b"objective relay\0".as_cstr(),
+                active: 1,
+            }
+            .into(),
+            connections: relay_connections.into(),
+        },
+    ]);
+
+    Ok(())
+}
+
 pub fn patch_add_switch(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
@@ -1952,7 +2127,7 @@ pub fn patch_add_platform<'r>(
         _ => DoorType::Disabled.vulnerability(),
     };
 
-    let connections = match platform_type {
+    let mut connections = match platform_type {
         PlatformType::BombBox => {
             let ids = ids.as_ref().unwrap();
 
@@ -2136,7 +2311,7 @@ pub fn patch_add_platform<'r>(
                     unknown5: 1.0,
                 },
 
-                speed: 5.0,
+                speed: config.speed.unwrap_or(5.0),
                 active: config.active.unwrap_or(true) as u8,
 
                 dcln,
@@ -2174,6 +2349,10 @@ pub fn patch_add_platform<'r>(
             if let Some(active) = config.active {
                 property_data.active = active as u8;
             }
+
+            if let Some(speed) = config.speed {
+                property_data.speed = speed;
+            }
         };
     }
 
@@ -2477,6 +2656,132 @@ pub fn patch_add_platform<'r>(
         ]);
     }
 
+    // Moving platforms follow a chain of waypoints once they are started
+    if let Some(waypoints) = config.waypoints.as_ref() {
+        let mrea_id = area.mlvl_area.mrea.to_u32();
+
+        if platform_type == PlatformType::BombBox {
+            Err(format!(
+                "Bomb box platforms can't follow waypoints (room 0x{:X})",
+                mrea_id
+            ))?;
+        }
+
+        if waypoints.is_empty() {
+            Err(format!(
+                "Moving platform in room 0x{:X} needs at least one waypoint",
+                mrea_id
+            ))?;
+        }
+
+        let layer_id = config.layer.unwrap_or(0) as usize;
+        while area.layer_flags.layer_count <= layer_id as u32 {
+            area.add_layer(b"New Layer\0".as_cstr());
+        }
+
+        let waypoint_ids: Vec<u32> = waypoints
+            .iter()
+            .map(|_| area.new_object_id_from_layer_id(layer_id))
+            .collect();
+        let start_id = area.new_object_id_from_layer_id(layer_id);
+        let looping = config.looping.unwrap_or(true);
+
+        connections.push(structs::Connection {
+            state: structs::ConnectionState::PLAY,
+            message: structs::ConnectionMsg::FOLLOW,
+            target_object_id: waypoint_ids[0],
+        });
+
+        let scly = area.mrea().scly_section_mut();
+        let objects = scly.layers.as_mut_vec()[layer_id].objects.as_mut_vec();
+
+        for (i, position) in waypoints.iter().enumerate() {
+            let next_id = if i + 1 < waypoint_ids.len() {
+                Some(waypoint_ids[i + 1])
+            } else if looping {
+                Some(waypoint_ids[0])
+            } else {
+                None
+            };
+
+            let connections: Vec<structs::Connection> = next_id
+                .map(|next_id| structs::Connection {
+                    state: structs::ConnectionState::ARRIVED,
+                    message: structs::ConnectionMsg::NEXT,
+                    target_object_id: next_id,
+                })
+                .into_iter()
+                .collect();
+
+            objects.push(structs::SclyObject {
+                instance_id: waypoint_ids[i],
+                property_data: structs::Waypoint {
+                    name: b"my platform waypoint\0".as_cstr(),
+                    position: (*position).into(),
+                    rotation: [0.0, 0.0, 0.0].into(),
+                    active: 1,
+                    speed: config.speed.unwrap_or(5.0),
+                    pause: config.pause.unwrap_or(0.0),
+                    pattern_translate: 0,
+                    pattern_orient: 0,
+                    pattern_fit: 0,
+                    behaviour: 0,
+                    behaviour_orient: 0,
+                    behaviour_modifiers: 0,
+                    animation: 0,
+                }
+                .into(),
+                connections: connections.into(),
+            });
+        }
+
+        // The platform stays put until the player enters the trigger, or starts as soon as the
+        // room loads if there isn't one
+        let start_connections = vec![structs::Connection {
+            state: match config.activation_trigger {
+                Some(_) => structs::ConnectionState::ENTERED,
+                None => structs::ConnectionState::ZERO,
+            },
+            message: structs::ConnectionMsg::START,
+            target_object_id: undamaged_block_id,
+        }];
+
+        let property_data: structs::SclyProperty = match config.activation_trigger.as_ref() {
+            Some(trigger) => structs::Trigger {
+                name: b"my platform trigger\0".as_cstr(),
+                position: trigger.position.into(),
+                scale: trigger.scale.unwrap_or([5.0, 5.0, 5.0]).into(),
+                damage_info: structs::scly_structs::DamageInfo {
+                    weapon_type: DamageType::Power as u32,
+                    damage: 0.0,
+                    radius: 0.0,
+                    knockback_power: 0.0,
+                },
+                force: [0.0, 0.0, 0.0].into(),
+                flags: 1, // detect player
+                active: 1,
+                deactivate_on_enter: 1,
+                deactivate_on_exit: 0,
+            }
+            .into(),
+            None => structs::Timer {
+                name: b"my platform timer\0".as_cstr(),
+                start_time: 0.1,
+                max_random_add: 0.0,
+                looping: 0,
+                start_immediately: 1,
+                active: 1,
+            }
+            .into(),
+        };
+
+        objects.push(structs::SclyObject {
+            instance_id: start_id,
+            property_data,
+            connections: start_connections.into(),
+        });
+    }
+
     let id = config.id;
     let requested_layer_id = config.layer;
     let mrea_id = area.mlvl_area.mrea.to_u32();
@@ -2520,6 +2825,13 @@ pub fn patch_add_platform<'r>(
         if let Some(info) = info {
             let (layer_id, _) = info;
 
+            if config.waypoints.is_some() {
+                Err(format!(
+                    "Failed to edit existing platform 0x{:X} in room 0x{:X}: Only new platforms can follow waypoints",
+                    id, mrea_id
+                ))?;
+            }
+
             // move and update
             if requested_layer_id.is_some() && requested_layer_id.unwrap() != layer_id {
                 let requested_layer_id = requested_layer_id.unwrap();
@@ -2588,6 +2900,234 @@ pub fn patch_add_platform<'r>(
     Ok(())
 }
 
+// The vanilla room each type of station is copied from
+pub fn station_template(station_type: StationType) -> ResourceInfo {
+    match station_type {
+        StationType::Save => resource_info!("1_savestation.MREA"),
+        // The power bomb station is the missile station with another special function
+        StationType::Missile | StationType::PowerBomb => {
+            resource_info!("missilerechargestation_crater.MREA")
+        }
+    }
+}
+
+/// The objects of the vanilla station of `station_type`, as they are in the room it's copied from:
+/// its special function first, then the actors, triggers, effects, sounds and logic connected to it
+pub fn station_template_objects<'r>(
+    game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+    station_type: StationType,
+) -> Result<Vec<structs::SclyObject<'r>>, String> {
+    // How many connections away from the special function an object can be, and how far
+    const MAX_DEPTH: usize = 3;
+    const MAX_DISTANCE: f32 = 15.0;
+    const OBJECT_TYPES: &[u8] = &[
+        structs::Actor::OBJECT_TYPE,
+        structs::Counter::OBJECT_TYPE,
+        structs::Effect::OBJECT_TYPE,
+        structs::PointOfInterest::OBJECT_TYPE,
+        structs::Relay::OBJECT_TYPE,
+        structs::Sound::OBJECT_TYPE,
+        structs::SpecialFunction::OBJECT_TYPE,
+        structs::Timer::OBJECT_TYPE,
+        structs::Trigger::OBJECT_TYPE,
+    ];
+
+    let template = station_template(station_type);
+    let mrea = game_resources
+        .get(&template.into())
+        .and_then(|res| res.kind.as_mrea())
+        .ok_or_else(|| format!("Could not read the station room {}", template.long_name))?;
+    let mut objects: Vec<structs::SclyObject<'r>> = mrea
+        .scly_section()
+        .layers
+        .iter()
+        .flat_map(|layer| {
+            layer
+                .objects
+                .iter()
+                .map(|obj| obj.into_owned())
+                .collect::<Vec<_>>()
+        })
+        .filter(|obj| OBJECT_TYPES.contains(&obj.property_data.object_type()))
+        .collect();
+
+    let special_function_type = match station_type {
+        StationType::Save => SpecialFunctionType::SaveStation,
+        StationType::Missile | StationType::PowerBomb => SpecialFunctionType::MissileStation,
+    } as u32;
+    let station_idx = objects
+        .iter()
+        .position(|obj| {
+            obj.property_data
+                .as_special_function()
+                .map(|special_function| special_function.type_ == special_function_type)
+                .unwrap_or(false)
+        })
+        .ok_or_else(|| format!("Could not find the station in {}", template.long_name))?;
+    objects.swap(0, station_idx);
+    let origin = objects[0].property_data.get_position();
+
+    // Walk the connections from and to the special function
+    let mut station = vec![objects[0].instance_id];
+    let mut frontier = station.clone();
+    for _ in 0..MAX_DEPTH {
+        let targets: Vec<u32> = objects
+            .iter()
+            .filter(|obj| frontier.contains(&obj.instance_id))
+            .flat_map(|obj| {
+                obj.connections
+                    .iter()
+                    .map(|conn| conn.target_object_id)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        frontier = vec![];
+        for obj in objects.iter_mut() {
+            if station.contains(&obj.instance_id) {
+                continue;
+            }
+            let connected = targets.contains(&obj.instance_id)
+                || obj
+                    .connections
+                    .iter()
+                    .any(|conn| station.contains(&conn.target_object_id));
+            if !connected {
+                continue;
+            }
+            if obj.property_data.supports_position() {
+                let position = obj.property_data.get_position();
+                let distance = (0..3)
+                    .map(|i| (position[i] - origin[i]).powi(2))
+                    .sum::<f32>()
+                    .sqrt();
+                if distance > MAX_DISTANCE {
+                    continue;
+                }
+            }
+            frontier.push(obj.instance_id);
+        }
+        station.extend_from_slice(&frontier);
+    }
+
+    objects.retain(|obj| station.contains(&obj.instance_id));
+    Ok(objects)
+}
+
+pub fn patch_add_station<'r>(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
+    game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+    config: StationConfig,
+) -> Result<(), String> {
+    let layer = config.layer.unwrap_or(0);
+    while area.layer_flags.layer_count <= layer {
+        area.add_layer(b"New Layer\0".as_cstr());
+    }
+
+    if config.pad.unwrap_or(true) {
+        patch_add_platform(
+            ps,
+            area,
+            game_resources,
+            PlatformConfig {
+                id: None,
+                layer: Some(layer),
+                active: config.active,
+                position: config.position,
+                rotation: config.rotation,
+                alt_platform: None,
+                platform_type: Some(PlatformType::Metal),
+                xray_only: None,
+                thermal_only: None,
+                waypoints: None,
+                speed: None,
+                pause: None,
+                looping: None,
+                activation_trigger: None,
+            },
+        )?;
+    }
+
+    let layer = layer as usize;
+    let mut objects = station_template_objects(game_resources, config.station_type)?;
+
+    let asset_ids = objects
+        .iter()
+        .flat_map(|obj| obj.property_data.get_asset_ids())
+        .map(|(id, _)| id);
+    let (deps, _) = asset_dependencies(game_resources, asset_ids)?;
+    let deps_iter = deps
+        .into_iter()
+        .map(|(asset_id, asset_type)| structs::Dependency {
+            asset_id,
+            asset_type,
+        });
+    area.add_dependencies(game_resources, layer, deps_iter);
+
+    // Move the station from where it is in its room to the configured position, keeping the
+    // objects where they are relative to its special function
+    let template_position = objects[0].property_data.get_position();
+    let template_yaw = objects[0].property_data.get_rotation()[2];
+    let rotation = config.rotation.unwrap_or([0.0, 0.0, 0.0]);
+    let new_ids: HashMap<u32, u32> = objects
+        .iter()
+        .map(|obj| (obj.instance_id, area.new_object_id_from_layer_id(layer)))
+        .collect();
+
+    for obj in objects.iter_mut() {
+        obj.instance_id = new_ids[&obj.instance_id];
+
+        let connections = obj.connections.as_mut_vec();
+        connections.retain(|conn| new_ids.contains_key(&conn.target_object_id));
+        for conn in connections.iter_mut() {
+            conn.target_object_id = new_ids[&conn.target_object_id];
+        }
+
+        if obj.property_data.supports_position() {
+            let position = obj.property_data.get_position();
+            let offset = [
+                position[0] - template_position[0],
+                position[1] - template_position[1],
+                position[2] - template_position[2],
+            ];
+            let offset = relative_offset([0.0, 0.0, 0.0], [0.0, 0.0, -template_yaw], offset);
+            obj.property_data
+                .set_position(relative_offset(config.position, rotation, offset));
+        }
+        if obj.property_data.supports_rotation() {
+            let object_rotation = obj.property_data.get_rotation();
+            obj.property_data.set_rotation([
+                object_rotation[0] + rotation[0],
+                object_rotation[1] + rotation[1],
+                object_rotation[2] - template_yaw + rotation[2],
+            ]);
+        }
+
+        // The station is used through its triggers
+        if !config.active.unwrap_or(true) {
+            if let Some(trigger) = obj.property_data.as_trigger_mut() {
+                trigger.active = 0;
+            }
+        }
+    }
+
+    if config.station_type == StationType::PowerBomb {
+        objects[0]
+            .property_data
+            .as_special_function_mut()
+            .unwrap()
+            .type_ = SpecialFunctionType::PowerBombStation as u32;
+    }
+
+    let scly = area.mrea().scly_section_mut();
+    scly.layers.as_mut_vec()[layer]
+        .objects
+        .as_mut_vec()
+        .extend(objects);
+
+    Ok(())
+}
+
 pub fn patch_add_block<'r>(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
@@ -2607,16 +3147,43 @@ pub fn patch_add_block<'r>(
     });
     area.add_dependencies(game_resources, 0, deps_iter);
 
+    let vulnerability = match config.vulnerability.as_ref() {
+        Some(vulnerability) => DoorType::from_string(vulnerability.to_string())
+            .ok_or_else(|| format!("Unknown block vulnerability '{}'", vulnerability))?,
+        None => DoorType::Disabled,
+    };
+
+    let mut scale = config.scale.unwrap_or([1.0, 1.0, 1.0]);
+    if let Some(size) = config.size {
+        scale = [scale[0] * size, scale[1] * size, scale[2] * size];
+    }
+
+    let mut connections = vec![];
+    if let Some(destroyed_relay_id) = config.destroyed_relay_id {
+        if vulnerability == DoorType::Disabled {
+            Err("Blocks with a destroyedRelayId must also specify a vulnerability")?;
+        }
+
+        connections.push(structs::Connection {
+            state: structs::ConnectionState::DEAD,
+            message: structs::ConnectionMsg::SET_TO_ZERO,
+            target_object_id: destroyed_relay_id,
+        });
+    }
+
     add_block(
         area,
         config.id,
         config.position,
-        config.scale.unwrap_or([1.0, 1.0, 1.0]),
-        texture,
+        scale,
+        texture.cmdl(),
         1,
         config.layer,
         config.active.unwrap_or(true),
         old_scale,
+        vulnerability.vulnerability(),
+        config.health.unwrap_or(5.0),
+        connections,
     );
 
     Ok(())
@@ -2628,11 +3195,14 @@ pub fn add_block(
     id: Option<u32>,
     position: [f32; 3],
     scale: [f32; 3],
-    texture: GenericTexture,
+    cmdl: ResId<res_id::CMDL>,
     is_tangible: u8,
     layer: Option<u32>,
     active: bool,
     old_scale: bool,
+    vulnerability: structs::scly_structs::DamageVulnerability,
+    health: f32,
+    connections: Vec<structs::Connection>,
 ) {
     let layer_id = layer.unwrap_or(0);
 
@@ -2667,11 +3237,11 @@ pub fn add_block(
             unknown1: 1.0,
             unknown2: 0.0,
             health_info: structs::scly_structs::HealthInfo {
-                health: 5.0,
+                health,
                 knockback_resistance: 1.0,
             },
-            damage_vulnerability: DoorType::Disabled.vulnerability(),
-            cmdl: texture.cmdl(),
+            damage_vulnerability: vulnerability,
+            cmdl,
             ancs: structs::scly_structs::AncsProp {
                 file_id: ResId::invalid(), // None
                 node_index: 0,
@@ -2729,10 +3299,77 @@ pub fn add_block(
             unknown13: 0,
         }
         .into(),
-        connections: vec![].into(),
+        connections: connections.into(),
     });
 }
 
+/// Adds a translucent, intangible box showing the extent of every Trigger and DamageableTrigger
+/// whose id is in `ids`, and of every other volume (position and scale) in `volumes`
+pub fn patch_visualize_volumes<'r>(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
+    game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+    ids: &[u32],
+    volumes: &[([f32; 3], [f32; 3])],
+) -> Result<(), String> {
+    let mut volumes = volumes.to_vec();
+    let scly = area.mrea().scly_section();
+    for layer in scly.layers.iter() {
+        for obj in layer.objects.iter() {
+            if !ids
+                .iter()
+                .any(|id| id & 0x00FFFFFF == obj.instance_id & 0x00FFFFFF)
+            {
+                continue;
+            }
+            if let Some(trigger) = obj.property_data.as_trigger() {
+                volumes.push((trigger.position.into(), trigger.scale.into()));
+            } else if let Some(trigger) = obj.property_data.as_damageable_trigger() {
+                volumes.push((trigger.position.into(), trigger.scale.into()));
+            }
+        }
+    }
+
+    if volumes.is_empty() {
+        return Ok(());
+    }
+
+    let cmdl = custom_asset_ids::VOLUME_BOX_CMDL;
+    let deps = [
+        (cmdl.to_u32(), FourCC::from_bytes(b"CMDL")),
+        (
+            GenericTexture::Crater.txtr().to_u32(),
+            FourCC::from_bytes(b"TXTR"),
+        ),
+    ];
+    let deps_iter = deps
+        .iter()
+        .map(|&(asset_id, asset_type)| structs::Dependency {
+            asset_id,
+            asset_type,
+        });
+    area.add_dependencies(game_resources, 0, deps_iter);
+
+    for (position, scale) in volumes {
+        add_block(
+            area,
+            None,
+            position,
+            scale,
+            cmdl,
+            0,
+            None,
+            true,
+            false,
+            DoorType::Disabled.vulnerability(),
+            5.0,
+            vec![],
+        );
+    }
+
+    Ok(())
+}
+
 pub fn patch_lock_on_point<'r>(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
@@ -0,0 +1,323 @@
+// Replacement of the samples inside AGSCs (MusyX audio groups) with standard Nintendo .dsp files.
+//
+// An AGSC is the audio directory and group names as C strings, followed by the pool, project,
+// sample data and sample directory sections, each prefixed by its size. The sample directory is a
+// list of 0x20 byte entries terminated by 0xFFFFFFFF, and every entry points to the DSP-ADPCM
+// decoder parameters of its sample, which are also stored in the sample directory.
+//
+// Replacement samples are appended to the sample data, and their entry and decoder parameters are
+// rewritten in place. Sounds keep using the same sample ids, so macros and volumes are untouched.
+
+use std::{collections::HashMap, path::Path};
+
+const DSP_HEADER_SIZE: usize = 0x60;
+const SDIR_ENTRY_SIZE: usize = 0x20;
+const SDIR_END: u32 = 0xFFFFFFFF;
+const SAMPLE_FORMAT_DSP: u32 = 0;
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| format!("Unexpected end of data at offset 0x{:X}", offset))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| format!("Unexpected end of data at offset 0x{:X}", offset))
+}
+
+fn write_u16(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+}
+
+fn align_32(offset: usize) -> usize {
+    (offset + 31) & !31
+}
+
+// Every 8 byte DSP-ADPCM frame is a header byte and 14 samples, loop points are given in nibbles
+fn nibble_to_sample(nibble: u32) -> u32 {
+    (nibble / 16) * 14 + (nibble % 16).saturating_sub(2)
+}
+
+#[derive(Clone, Debug)]
+pub struct DspSample {
+    pub num_samples: u32,
+    pub sample_rate: u16,
+    pub loop_samples: Option<(u32, u32)>, // start, length
+    pub coefs: [u8; 32],
+    pub ps: u8,
+    pub hist1: u16,
+    pub hist2: u16,
+    pub loop_ps: u8,
+    pub data: Vec<u8>,
+}
+
+impl DspSample {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("Failed to read sample '{}': {}", path.display(), e))?;
+        Self::parse(&bytes).map_err(|e| format!("Failed to parse '{}': {}", path.display(), e))
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let num_samples = read_u32(bytes, 0x00)?;
+        let num_nibbles = read_u32(bytes, 0x04)?;
+        let sample_rate = read_u32(bytes, 0x08)?;
+        let loop_flag = read_u16(bytes, 0x0C)?;
+        let format = read_u16(bytes, 0x0E)?;
+        let loop_start = read_u32(bytes, 0x10)?;
+        let loop_end = read_u32(bytes, 0x14)?;
+
+        if format != 0 {
+            Err(format!("Unsupported .dsp format {}", format))?;
+        }
+        if num_samples > 0xFFFFFF {
+            Err(format!("Too many samples ({})", num_samples))?;
+        }
+        let sample_rate: u16 = sample_rate
+            .try_into()
+            .map_err(|_| format!("Unsupported sample rate {}", sample_rate))?;
+
+        let data_size = ((num_nibbles + 1) / 2) as usize;
+        let data = bytes
+            .get(DSP_HEADER_SIZE..DSP_HEADER_SIZE + data_size)
+            .ok_or_else(|| "The sample data is shorter than the header says".to_string())?;
+
+        let loop_samples = if loop_flag != 0 {
+            let start = nibble_to_sample(loop_start);
+            let end = nibble_to_sample(loop_end);
+            if end < start || end >= num_samples {
+                Err("Invalid loop points")?;
+            }
+            Some((start, end - start + 1))
+        } else {
+            None
+        };
+
+        Ok(DspSample {
+            num_samples,
+            sample_rate,
+            loop_samples,
+            coefs: bytes[0x1C..0x3C].try_into().unwrap(),
+            ps: bytes[0x3F],
+            hist1: read_u16(bytes, 0x40)?,
+            hist2: read_u16(bytes, 0x42)?,
+            loop_ps: bytes[0x45],
+            data: data.to_vec(),
+        })
+    }
+}
+
+struct AgscSections {
+    // offset and size of the sample data and the sample directory
+    samp: (usize, usize),
+    sdir: (usize, usize),
+}
+
+fn agsc_sections(agsc: &[u8]) -> Result<AgscSections, String> {
+    let mut offset = 0;
+    for _ in 0..2 {
+        let len = agsc[offset..]
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| "Unterminated AGSC name".to_string())?;
+        offset += len + 1;
+    }
+
+    let mut sections = [(0, 0); 4];
+    for section in sections.iter_mut() {
+        let size = read_u32(agsc, offset)? as usize;
+        *section = (offset + 4, size);
+        offset += 4 + size;
+    }
+    if offset > agsc.len() {
+        Err("AGSC section sizes exceed the file size")?;
+    }
+
+    Ok(AgscSections {
+        samp: sections[2],
+        sdir: sections[3],
+    })
+}
+
+/// Replaces the samples with the given ids in an AGSC and returns the new file
+pub fn replace_agsc_samples(
+    agsc: &[u8],
+    samples: &HashMap<u16, DspSample>,
+) -> Result<Vec<u8>, String> {
+    let sections = agsc_sections(agsc)?;
+    let (samp_start, samp_size) = sections.samp;
+    let (sdir_start, sdir_size) = sections.sdir;
+
+    let mut samp = agsc[samp_start..samp_start + samp_size].to_vec();
+    let mut sdir = agsc[sdir_start..sdir_start + sdir_size].to_vec();
+
+    let mut replaced = vec![];
+    let mut entry = 0;
+    while read_u32(&sdir, entry)? != SDIR_END {
+        let id = read_u16(&sdir, entry)?;
+        if let Some(sample) = samples.get(&id) {
+            let format = read_u32(&sdir, entry + 0x10)? >> 24;
+            if format != SAMPLE_FORMAT_DSP {
+                Err(format!(
+                    "Sample {} is not DSP-ADPCM (format {})",
+                    id, format
+                ))?;
+            }
+
+            samp.resize(align_32(samp.len()), 0);
+            let sample_offset = samp.len() as u32;
+            samp.extend_from_slice(&sample.data);
+
+            let (loop_start, loop_length) = sample.loop_samples.unwrap_or((0, 0));
+            write_u32(&mut sdir, entry + 0x04, sample_offset);
+            write_u16(&mut sdir, entry + 0x0E, sample.sample_rate);
+            write_u32(&mut sdir, entry + 0x10, (format << 24) | sample.num_samples);
+            write_u32(&mut sdir, entry + 0x14, loop_start);
+            write_u32(&mut sdir, entry + 0x18, loop_length);
+
+            // Decoder parameters: bytes per frame, ps, loop ps, hist2, hist1, coefficients
+            let parms = read_u32(&sdir, entry + 0x1C)? as usize;
+            if parms + 0x28 > sdir.len() {
+                Err(format!("Sample {} has invalid decoder parameters", id))?;
+            }
+            sdir[parms + 0x02] = sample.ps;
+            sdir[parms + 0x03] = sample.loop_ps;
+            write_u16(&mut sdir, parms + 0x04, sample.hist2);
+            write_u16(&mut sdir, parms + 0x06, sample.hist1);
+            sdir[parms + 0x08..parms + 0x28].copy_from_slice(&sample.coefs);
+
+            replaced.push(id);
+        }
+        entry += SDIR_ENTRY_SIZE;
+    }
+
+    let mut missing: Vec<u16> = samples
+        .keys()
+        .filter(|id| !replaced.contains(id))
+        .copied()
+        .collect();
+    missing.sort();
+    if !missing.is_empty() {
+        Err(format!("Unknown sample ids {:?}", missing))?;
+    }
+
+    let mut bytes = agsc[..samp_start - 4].to_vec();
+    bytes.extend_from_slice(&(samp.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&samp);
+    bytes.extend_from_slice(&(sdir.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&sdir);
+    bytes.extend_from_slice(&agsc[sdir_start + sdir_size..]);
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+fn test_dsp(num_samples: u32, loop_samples: Option<(u32, u32)>) -> Vec<u8> {
+    // Loop points are in nibbles, after the 2 header nibbles of their frame
+    let sample_to_nibble = |sample: u32| (sample / 14) * 16 + sample % 14 + 2;
+    let num_nibbles = sample_to_nibble(num_samples - 1) + 1;
+
+    let mut bytes = vec![0; DSP_HEADER_SIZE];
+    write_u32(&mut bytes, 0x00, num_samples);
+    write_u32(&mut bytes, 0x04, num_nibbles);
+    write_u32(&mut bytes, 0x08, 22050);
+    if let Some((start, length)) = loop_samples {
+        write_u16(&mut bytes, 0x0C, 1);
+        write_u32(&mut bytes, 0x10, sample_to_nibble(start));
+        write_u32(&mut bytes, 0x14, sample_to_nibble(start + length - 1));
+    }
+    for (i, b) in bytes[0x1C..0x3C].iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    bytes[0x3F] = 0x17;
+    write_u16(&mut bytes, 0x40, 0x1234);
+    write_u16(&mut bytes, 0x42, 0x5678);
+    bytes[0x45] = 0x27;
+    bytes.extend((0..(num_nibbles + 1) / 2).map(|i| i as u8));
+    bytes
+}
+
+#[cfg(test)]
+fn test_agsc(sample_ids: &[u16]) -> Vec<u8> {
+    let mut sdir = vec![0; sample_ids.len() * SDIR_ENTRY_SIZE];
+    for (i, id) in sample_ids.iter().enumerate() {
+        let entry = i * SDIR_ENTRY_SIZE;
+        write_u16(&mut sdir, entry, *id);
+        write_u32(&mut sdir, entry + 0x04, i as u32 * 0x20);
+        write_u16(&mut sdir, entry + 0x0E, 32000);
+        write_u32(&mut sdir, entry + 0x10, (SAMPLE_FORMAT_DSP << 24) | 28);
+        let parms = sample_ids.len() * SDIR_ENTRY_SIZE + 4 + i * 0x28;
+        write_u32(&mut sdir, entry + 0x1C, parms as u32);
+    }
+    sdir.extend_from_slice(&SDIR_END.to_be_bytes());
+    sdir.resize(sdir.len() + sample_ids.len() * 0x28, 0xAA);
+
+    let mut agsc = b"Audio/test.dir\0test\0".to_vec();
+    for section in [&[1u8; 8][..], &[2; 12], &[3; 0x40], &sdir] {
+        agsc.extend_from_slice(&(section.len() as u32).to_be_bytes());
+        agsc.extend_from_slice(section);
+    }
+    agsc
+}
+
+#[test]
+fn test_dsp_parse() {
+    let sample = DspSample::parse(&test_dsp(100, Some((20, 50)))).unwrap();
+    assert_eq!(sample.num_samples, 100);
+    assert_eq!(sample.sample_rate, 22050);
+    assert_eq!(sample.loop_samples, Some((20, 50)));
+    assert_eq!(sample.ps, 0x17);
+    assert_eq!(sample.loop_ps, 0x27);
+    assert_eq!((sample.hist1, sample.hist2), (0x1234, 0x5678));
+    assert_eq!(sample.coefs[31], 31);
+    assert_eq!(sample.data.len(), 58); // 100 samples make 8 frames, the last one partial
+
+    let sample = DspSample::parse(&test_dsp(100, None)).unwrap();
+    assert_eq!(sample.loop_samples, None);
+    let mut truncated = test_dsp(100, None);
+    truncated.pop();
+    assert!(DspSample::parse(&truncated).is_err());
+}
+
+#[test]
+fn test_replace_agsc_samples_round_trip() {
+    let agsc = test_agsc(&[5, 9]);
+    let sample = DspSample::parse(&test_dsp(100, Some((20, 50)))).unwrap();
+    let samples = HashMap::from([(9, sample.clone())]);
+    let replaced = replace_agsc_samples(&agsc, &samples).unwrap();
+
+    // The sample data is appended, 32 byte aligned, and everything else is kept
+    let sections = agsc_sections(&replaced).unwrap();
+    let (samp_start, samp_size) = sections.samp;
+    let (sdir_start, sdir_size) = sections.sdir;
+    assert_eq!(samp_size, 0x40 + sample.data.len());
+    assert!(replaced[samp_start..samp_start + 0x40] == [3; 0x40]);
+    assert!(replaced[samp_start + 0x40..samp_start + samp_size] == sample.data[..]);
+    assert_eq!(sdir_size, 2 * SDIR_ENTRY_SIZE + 4 + 2 * 0x28);
+    assert!(replaced[..samp_start - 4] == agsc[..samp_start - 4]);
+
+    let sdir = &replaced[sdir_start..sdir_start + sdir_size];
+    let original_sdir = &agsc[agsc.len() - sdir_size..];
+    assert!(sdir[..SDIR_ENTRY_SIZE] == original_sdir[..SDIR_ENTRY_SIZE]);
+    let entry = SDIR_ENTRY_SIZE;
+    assert_eq!(read_u16(sdir, entry).unwrap(), 9);
+    assert_eq!(read_u32(sdir, entry + 0x04).unwrap(), 0x40);
+    assert_eq!(read_u16(sdir, entry + 0x0E).unwrap(), 22050);
+    assert_eq!(read_u32(sdir, entry + 0x10).unwrap(), 100);
+    assert_eq!(read_u32(sdir, entry + 0x14).unwrap(), 20);
+    assert_eq!(read_u32(sdir, entry + 0x18).unwrap(), 50);
+    let parms = read_u32(sdir, entry + 0x1C).unwrap() as usize;
+    assert_eq!((sdir[parms + 0x02], sdir[parms + 0x03]), (0x17, 0x27));
+    assert_eq!(read_u16(sdir, parms + 0x04).unwrap(), 0x5678);
+    assert_eq!(read_u16(sdir, parms + 0x06).unwrap(), 0x1234);
+    assert!(sdir[parms + 0x08..parms + 0x28] == sample.coefs);
+
+    let samples = HashMap::from([(7, sample)]);
+    assert!(replace_agsc_samples(&agsc, &samples).is_err());
+}
@@ -0,0 +1,205 @@
+// Reads the collision mesh of an area (the MREA's collision section, the same format as a DCLN's
+// collision) so that positions picked by the patcher can be checked against the room's geometry.
+
+use crate::mlvl_wrapper::MlvlArea;
+
+// The triangle is wound the other way, so that its normal points the other way
+const FLIPPED_TRIANGLE: u32 = 1 << 25;
+
+// How far below a point the floor can be for it to be snapped onto it, and the headroom needed
+// above the floor
+const MAX_DROP: f32 = 30.0;
+const MIN_HEADROOM: f32 = 2.0;
+
+// The floor can't be steeper than about 45 degrees
+const MIN_FLOOR_NORMAL_Z: f32 = 0.7;
+
+pub struct AreaCollision {
+    triangles: Vec<[[f32; 3]; 3]>, // counter-clockwise, seen from the side the normal points to
+}
+
+struct SectionReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> SectionReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + len)
+            .ok_or("The collision section is truncated")?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn normal_z(triangle: &[[f32; 3]; 3]) -> f32 {
+    let u = sub(triangle[1], triangle[0]);
+    let v = sub(triangle[2], triangle[0]);
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if len == 0.0 {
+        0.0
+    } else {
+        n[2] / len
+    }
+}
+
+// The height of the triangle right above or below (x, y), if it's over that point
+fn height_at(triangle: &[[f32; 3]; 3], x: f32, y: f32) -> Option<f32> {
+    let [a, b, c] = *triangle;
+    let det = (b[1] - c[1]) * (a[0] - c[0]) + (c[0] - b[0]) * (a[1] - c[1]);
+    if det.abs() < 1e-6 {
+        return None; // vertical
+    }
+    let l1 = ((b[1] - c[1]) * (x - c[0]) + (c[0] - b[0]) * (y - c[1])) / det;
+    let l2 = ((c[1] - a[1]) * (x - c[0]) + (a[0] - c[0]) * (y - c[1])) / det;
+    let l3 = 1.0 - l1 - l2;
+    if l1 < 0.0 || l2 < 0.0 || l3 < 0.0 {
+        return None;
+    }
+    Some(l1 * a[2] + l2 * b[2] + l3 * c[2])
+}
+
+impl AreaCollision {
+    pub fn from_area(area: &mut MlvlArea) -> Result<Self, String> {
+        let mrea = area.mrea();
+        let section = mrea
+            .sections
+            .iter()
+            .nth(mrea.collision_section_idx as usize)
+            .ok_or("The area has no collision section")?;
+        match &*section {
+            structs::MreaSection::Unknown(reader) => AreaCollision::parse(&reader[..]),
+            _ => Err("The collision section was already parsed as something else".to_string()),
+        }
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = SectionReader { bytes, offset: 0 };
+
+        reader.u32()?; // unknown
+        reader.u32()?; // size
+        let magic = reader.u32()?;
+        if magic != 0xDEAFBABE {
+            Err(format!("Unexpected collision magic 0x{:08X}", magic))?;
+        }
+        reader.u32()?; // version
+        reader.take(6 * 4)?; // bounds
+        reader.u32()?; // root node type
+        let octree_size = reader.u32()?;
+        reader.take(octree_size as usize)?;
+
+        let material_count = reader.u32()?;
+        let mut materials = vec![];
+        for _ in 0..material_count {
+            materials.push(reader.u32()?);
+        }
+        let vertex_material_count = reader.u32()?;
+        reader.take(vertex_material_count as usize)?;
+        let edge_material_count = reader.u32()?;
+        reader.take(edge_material_count as usize)?;
+        let triangle_material_count = reader.u32()?;
+        let triangle_materials = reader.take(triangle_material_count as usize)?;
+
+        let edge_count = reader.u32()?;
+        let mut edges = vec![];
+        for _ in 0..edge_count {
+            edges.push([reader.u16()? as usize, reader.u16()? as usize]);
+        }
+        let triangle_edge_count = reader.u32()?;
+        let mut triangle_edges = vec![];
+        for _ in 0..triangle_edge_count {
+            triangle_edges.push(reader.u16()? as usize);
+        }
+        let vertex_count = reader.u32()?;
+        let mut vertices = vec![];
+        for _ in 0..vertex_count {
+            vertices.push([reader.f32()?, reader.f32()?, reader.f32()?]);
+        }
+
+        let out_of_range = || "The collision mesh references something which doesn't exist";
+        let mut triangles = vec![];
+        for (i, edge_idxs) in triangle_edges.chunks_exact(3).enumerate() {
+            let e0 = *edges.get(edge_idxs[0]).ok_or_else(out_of_range)?;
+            let e1 = *edges.get(edge_idxs[1]).ok_or_else(out_of_range)?;
+            let third = if e1[0] != e0[0] && e1[0] != e0[1] {
+                e1[0]
+            } else {
+                e1[1]
+            };
+            let material = triangle_materials
+                .get(i)
+                .and_then(|idx| materials.get(*idx as usize))
+                .ok_or_else(out_of_range)?;
+            let idxs = if material & FLIPPED_TRIANGLE != 0 {
+                [e0[1], e0[0], third]
+            } else {
+                [e0[0], e0[1], third]
+            };
+            let mut triangle = [[0.0; 3]; 3];
+            for (vertex, idx) in triangle.iter_mut().zip(idxs) {
+                *vertex = *vertices.get(idx).ok_or_else(out_of_range)?;
+            }
+            triangles.push(triangle);
+        }
+
+        Ok(AreaCollision { triangles })
+    }
+
+    /// Moves `position` straight down onto the floor under it, keeping it `height` above the
+    /// floor. Returns None if there is no floor close enough under it, if the floor is too
+    /// steep or too low under the ceiling, or if the position is inside the geometry (the
+    /// surface right under it faces down, or the one right above it faces up).
+    pub fn snap_to_floor(&self, position: [f32; 3], height: f32) -> Option<[f32; 3]> {
+        let [x, y, z] = position;
+        let mut below: Option<(f32, f32)> = None; // height, normal z
+        let mut above: Option<(f32, f32)> = None;
+        for triangle in self.triangles.iter() {
+            let surface_z = match height_at(triangle, x, y) {
+                Some(surface_z) => surface_z,
+                None => continue,
+            };
+            if surface_z <= z {
+                if below.map_or(true, |(below_z, _)| surface_z > below_z) {
+                    below = Some((surface_z, normal_z(triangle)));
+                }
+            } else if above.map_or(true, |(above_z, _)| surface_z < above_z) {
+                above = Some((surface_z, normal_z(triangle)));
+            }
+        }
+
+        let (floor_z, floor_normal_z) = below?;
+        if floor_normal_z < MIN_FLOOR_NORMAL_Z || z - floor_z > MAX_DROP {
+            return None;
+        }
+        if let Some((ceiling_z, ceiling_normal_z)) = above {
+            if ceiling_normal_z > 0.0 || ceiling_z - floor_z < MIN_HEADROOM {
+                return None;
+            }
+        }
+
+        Some([x, y, floor_z + height])
+    }
+}
@@ -0,0 +1,320 @@
+// Fills in the dependency lists of patched areas from the assets their objects reference, so that
+// an object added or edited by a patch can't crash the game by using an asset the area never
+// loads. Patches still add the dependencies they know about with `MlvlArea::add_dependencies`,
+// this only catches the ones they missed. It can also drop the dependencies only the objects a
+// patch removed (e.g. water, locks) needed, so that the area loads faster.
+
+use std::collections::{HashMap, HashSet};
+
+use log::{debug, info, warn};
+use reader_writer::FourCC;
+use structs::{Dependency, Resource, ResourceKind, SclyPropertyData};
+
+use crate::{
+    extern_assets::referenced_assets, mlvl_wrapper::MlvlArea, room_lookup::ROOM_BY_MREA,
+    ResourceData,
+};
+
+// The object types which don't reference any asset, unlike the other types which don't list
+// theirs (e.g. enemies, doors and water)
+const ASSET_FREE_OBJECT_TYPES: &[u8] = &[
+    structs::Waypoint::OBJECT_TYPE,
+    structs::Trigger::OBJECT_TYPE,
+    structs::Timer::OBJECT_TYPE,
+    structs::Counter::OBJECT_TYPE,
+    structs::Sound::OBJECT_TYPE,
+    structs::Dock::OBJECT_TYPE,
+    structs::Camera::OBJECT_TYPE,
+    structs::CameraWaypoint::OBJECT_TYPE,
+    structs::SpawnPoint::OBJECT_TYPE,
+    structs::CameraHint::OBJECT_TYPE,
+    structs::MemoryRelay::OBJECT_TYPE,
+    structs::Relay::OBJECT_TYPE,
+    structs::CameraBlurKeyframe::OBJECT_TYPE,
+    structs::ActorKeyFrame::OBJECT_TYPE,
+    structs::CoverPoint::OBJECT_TYPE,
+    structs::SpiderBallWaypoint::OBJECT_TYPE,
+    structs::GrapplePoint::OBJECT_TYPE,
+    structs::DistanceFog::OBJECT_TYPE,
+    structs::ActorRotate::OBJECT_TYPE,
+    structs::SpecialFunction::OBJECT_TYPE,
+    structs::PlayerHint::OBJECT_TYPE,
+    structs::PickupGenerator::OBJECT_TYPE,
+    structs::BallTrigger::OBJECT_TYPE,
+    structs::ControllerAction::OBJECT_TYPE,
+    structs::Switch::OBJECT_TYPE,
+    structs::AIJumpPoint::OBJECT_TYPE,
+    structs::StreamedAudio::OBJECT_TYPE,
+    structs::CameraPitchVolume::OBJECT_TYPE,
+    structs::CameraHintTrigger::OBJECT_TYPE,
+    structs::WorldLightFader::OBJECT_TYPE,
+    structs::NewCameraShaker::OBJECT_TYPE,
+];
+
+/// The resources of a pak, as they were before it was patched
+pub struct PakResources<'r> {
+    resources: HashMap<(u32, FourCC), Resource<'r>>,
+}
+
+impl<'r> PakResources<'r> {
+    pub fn new(pak: &structs::Pak<'r>) -> Self {
+        let resources = pak
+            .resources
+            .iter()
+            .map(|res| ((res.file_id, res.fourcc()), res.into_owned()))
+            .collect();
+        PakResources { resources }
+    }
+
+    // The assets an asset of the pak references, or None if it isn't in the pak
+    fn references(&self, asset: (u32, FourCC)) -> Option<Vec<(u32, FourCC)>> {
+        let res = self.resources.get(&asset)?;
+        let data = match res.kind {
+            ResourceKind::Unknown(..) => ResourceData::new(res),
+            ResourceKind::External(..) => ResourceData::new_external(res),
+            _ => return Some(vec![]),
+        };
+        Some(referenced_assets(asset.1, &data.decompress()))
+    }
+}
+
+/// The assets referenced by the objects of an area, with the index of the layer of the object
+/// referencing them
+pub fn object_assets(area: &mut MlvlArea) -> Vec<(usize, (u32, FourCC))> {
+    let mut assets = vec![];
+    let scly = area.mrea().scly_section();
+    for (i, layer) in scly.layers.iter().enumerate() {
+        for obj in layer.objects.iter() {
+            for asset in obj.property_data.get_asset_ids() {
+                assets.push((i, asset));
+            }
+        }
+    }
+    assets
+}
+
+// The name of the type of the first object of an area which may reference assets without
+// listing them, if any
+fn unlisted_object_type(area: &mut MlvlArea) -> Option<String> {
+    let scly = area.mrea().scly_section();
+    for layer in scly.layers.iter() {
+        for obj in layer.objects.iter() {
+            let property_data = &obj.property_data;
+            let object_type = property_data.object_type();
+            if !property_data.supports_asset_ids()
+                && !ASSET_FREE_OBJECT_TYPES.contains(&object_type)
+            {
+                return Some(match property_data.object_type_name() {
+                    Some(name) => name.to_string(),
+                    None => format!("0x{:02X}", object_type),
+                });
+            }
+        }
+    }
+    None
+}
+
+/// The assets the dependency lists of an area, of every layer, contain
+pub fn dependency_assets(area: &MlvlArea) -> HashSet<(u32, FourCC)> {
+    area.mlvl_area
+        .dependencies
+        .deps
+        .iter()
+        .flat_map(|layer| {
+            layer
+                .iter()
+                .map(|dep| (dep.asset_id, dep.asset_type))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+// The assets `assets` reference, including themselves, down to the last reference
+fn asset_closure(
+    pak_resources: &PakResources,
+    assets: impl Iterator<Item = (u32, FourCC)>,
+) -> HashSet<(u32, FourCC)> {
+    let mut closure = HashSet::new();
+    let mut to_visit: Vec<_> = assets.collect();
+    while let Some(asset) = to_visit.pop() {
+        if asset.0 == 0 || asset.0 == 0xFFFFFFFF || !closure.insert(asset) {
+            continue;
+        }
+        to_visit.extend(pak_resources.references(asset).unwrap_or_default());
+    }
+    closure
+}
+
+// The textures of the world geometry of an area, which are listed at the start of its material
+// section
+fn geometry_textures(area: &mut MlvlArea) -> Result<HashSet<(u32, FourCC)>, String> {
+    let mrea = area.mrea();
+    let section = mrea
+        .sections
+        .iter()
+        .nth(mrea.world_geometry_section_idx as usize)
+        .ok_or("The area has no world geometry section")?;
+    let bytes = match &*section {
+        structs::MreaSection::Unknown(reader) => &reader[..],
+        _ => Err("The world geometry section was already parsed as something else")?,
+    };
+    let read_u32 = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+            .ok_or("The material section is truncated")
+    };
+    let texture_count = read_u32(0)? as usize;
+    (0..texture_count)
+        .map(|i| Ok((read_u32(4 + i * 4)?, FourCC::from_bytes(b"TXTR"))))
+        .collect()
+}
+
+/// Removes the dependencies of `area` which only the objects a patch removed needed: the assets
+/// referenced, directly or not, by the objects in `baseline_assets` (those from before the area
+/// was patched), which were in `baseline_deps` (its dependencies from before it was patched) and
+/// which nothing left in the area references anymore. The textures of the world geometry are
+/// always kept, and so are the dependencies the patches added. Areas with objects which may use
+/// assets without listing them (e.g. enemies, doors or water) are left alone, as those could
+/// share an asset with a removed object.
+pub fn remove_orphaned_dependencies(
+    area: &mut MlvlArea,
+    pak_resources: &PakResources,
+    baseline_assets: &[(usize, (u32, FourCC))],
+    baseline_deps: &HashSet<(u32, FourCC)>,
+) {
+    let mrea_id = area.mrea_file_id();
+    let room = match ROOM_BY_MREA.get(&mrea_id) {
+        Some(room) => format!("{}:{}", room.region_name, room.room_name),
+        None => format!("0x{:08X}", mrea_id),
+    };
+
+    if let Some(object_type) = unlisted_object_type(area) {
+        debug!(
+            "Not pruning the dependencies of {}: the assets of its {} objects aren't known",
+            room, object_type
+        );
+        return;
+    }
+
+    let geometry_textures = match geometry_textures(area) {
+        Ok(textures) => textures,
+        Err(e) => {
+            warn!("Not pruning the dependencies of {}: {}", room, e);
+            return;
+        }
+    };
+    let used = asset_closure(
+        pak_resources,
+        object_assets(area).into_iter().map(|(_, asset)| asset),
+    );
+    let orphaned: HashSet<(u32, FourCC)> = asset_closure(
+        pak_resources,
+        baseline_assets.iter().map(|(_, asset)| *asset),
+    )
+    .into_iter()
+    .filter(|asset| {
+        baseline_deps.contains(asset) && !used.contains(asset) && !geometry_textures.contains(asset)
+    })
+    .collect();
+    if orphaned.is_empty() {
+        return;
+    }
+
+    let mut removed = vec![];
+    for layer in area.mlvl_area.dependencies.deps.as_mut_vec().iter_mut() {
+        layer.as_mut_vec().retain(|dep| {
+            let asset = (dep.asset_id, dep.asset_type);
+            if orphaned.contains(&asset) {
+                removed.push(asset);
+                false
+            } else {
+                true
+            }
+        });
+    }
+    info!(
+        "Removed {} orphaned dependencies from {}: {}",
+        removed.len(),
+        room,
+        removed
+            .iter()
+            .map(|(id, fourcc)| format!("{} 0x{:08X}", fourcc, id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
+
+/// Adds the assets the objects of `area` reference, and those they reference in turn (e.g. the
+/// textures of a model), to the dependencies of the layer of the object when no layer of the
+/// area depends on them yet. The assets in `baseline`, those referenced before the area was
+/// patched, are left alone. Every character of an ANCS is added, as which ones an object uses
+/// isn't known.
+pub fn add_missing_dependencies(
+    area: &mut MlvlArea,
+    pak_resources: &PakResources,
+    baseline: &[(usize, (u32, FourCC))],
+) {
+    let mut known: HashSet<(u32, FourCC)> = baseline.iter().map(|(_, asset)| *asset).collect();
+    for layer in area.mlvl_area.dependencies.deps.iter() {
+        for dep in layer.iter() {
+            known.insert((dep.asset_id, dep.asset_type));
+        }
+    }
+
+    let object_assets = object_assets(area);
+    let deps = area.mlvl_area.dependencies.deps.as_mut_vec();
+    let mut added = vec![];
+    let mut missing = vec![];
+    for (layer, asset) in object_assets {
+        let mut to_add = vec![asset];
+        while let Some(asset) = to_add.pop() {
+            if asset.0 == 0 || asset.0 == 0xFFFFFFFF || !known.insert(asset) {
+                continue;
+            }
+            match pak_resources.references(asset) {
+                Some(references) => {
+                    deps[layer].as_mut_vec().push(Dependency {
+                        asset_id: asset.0,
+                        asset_type: asset.1,
+                    });
+                    added.push(asset);
+                    to_add.extend(references);
+                }
+                None => missing.push(asset),
+            }
+        }
+    }
+
+    if added.is_empty() && missing.is_empty() {
+        return;
+    }
+
+    let mrea_id = area.mrea_file_id();
+    let room = match ROOM_BY_MREA.get(&mrea_id) {
+        Some(room) => format!("{}:{}", room.region_name, room.room_name),
+        None => format!("0x{:08X}", mrea_id),
+    };
+    let format_assets = |assets: &[(u32, FourCC)]| {
+        assets
+            .iter()
+            .map(|(id, fourcc)| format!("{} 0x{:08X}", fourcc, id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if !added.is_empty() {
+        info!(
+            "Added {} missing dependencies to {}: {}",
+            added.len(),
+            room,
+            format_assets(&added)
+        );
+    }
+    if !missing.is_empty() {
+        warn!(
+            "{} references assets which aren't in its pak: {}",
+            room,
+            format_assets(&missing)
+        );
+    }
+}
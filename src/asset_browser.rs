@@ -0,0 +1,147 @@
+use reader_writer::{FourCC, Readable, Reader, Writable};
+use resource_info_table::{resource_info_by_id, resource_info_by_name};
+
+use crate::{GcDiscLookupExtensions, ResourceData};
+
+/// A resource of one of the paks of a disc
+#[derive(Clone, Debug)]
+pub struct AssetEntry {
+    pub pak_name: String,
+    pub res_id: u32,
+    pub fourcc: FourCC,
+    pub size: usize,
+    pub name: Option<&'static str>,
+}
+
+/// The names of the paks in the root of a disc
+pub fn pak_names(gc_disc: &structs::GcDisc) -> Vec<String> {
+    gc_disc
+        .file_system_root
+        .dir_entries()
+        .unwrap()
+        .iter()
+        .map(|entry| entry.name().to_string_lossy().into_owned())
+        .filter(|name| name.to_lowercase().ends_with(".pak"))
+        .collect()
+}
+
+fn read_pak<'r>(gc_disc: &structs::GcDisc<'r>, pak_name: &str) -> Result<structs::Pak<'r>, String> {
+    match gc_disc.find_file(pak_name).and_then(|entry| entry.file()) {
+        Some(structs::FstEntryFile::Unknown(reader)) => Ok(reader.clone().read(())),
+        Some(structs::FstEntryFile::Pak(pak)) => Ok(pak.clone()),
+        _ => Err(format!("Failed to find {} on the disc", pak_name)),
+    }
+}
+
+/// Parses an asset given either by its ID in hex or by its full path in the resource table
+pub fn parse_asset_id(asset: &str) -> Result<u32, String> {
+    if let Some(info) = resource_info_by_name(asset) {
+        return Ok(info.res_id);
+    }
+
+    let hex = asset.trim_start_matches("0x").trim_start_matches("0X");
+    u32::from_str_radix(hex, 16)
+        .map_err(|_| format!("'{}' is neither an asset ID nor a known asset name", asset))
+}
+
+/// Lists the resources of the paks of a disc, optionally only those of one pak and/or type
+pub fn list_assets(
+    gc_disc: &structs::GcDisc,
+    pak_filter: Option<&str>,
+    fourcc_filter: Option<FourCC>,
+) -> Result<Vec<AssetEntry>, String> {
+    let pak_names = match pak_filter {
+        Some(pak_name) => vec![pak_name.to_string()],
+        None => pak_names(gc_disc),
+    };
+
+    let mut assets = vec![];
+    for pak_name in pak_names {
+        let pak = read_pak(gc_disc, &pak_name)?;
+        for res in pak.resources.iter() {
+            if fourcc_filter.is_some_and(|fourcc| fourcc != res.fourcc()) {
+                continue;
+            }
+            assets.push(AssetEntry {
+                pak_name: pak_name.clone(),
+                res_id: res.file_id,
+                fourcc: res.fourcc(),
+                size: res.size(),
+                name: resource_info_by_id(res.file_id).map(|info| info.long_name),
+            });
+        }
+    }
+
+    Ok(assets)
+}
+
+/// Reads a resource from a disc, decompressed. It's looked for in the paks the resource table
+/// says it's in, or in every pak if it isn't in the table. An asset ending in ".pak" is instead
+/// the whole pak, which can be given to `import-room` or `assets replace`.
+pub fn extract_asset(gc_disc: &structs::GcDisc, asset: &str) -> Result<Vec<u8>, String> {
+    if asset.to_lowercase().ends_with(".pak") {
+        let mut bytes = vec![];
+        read_pak(gc_disc, asset)?
+            .write_to(&mut bytes)
+            .map_err(|e| format!("Failed to write {}: {}", asset, e))?;
+        return Ok(bytes);
+    }
+
+    let res_id = parse_asset_id(asset)?;
+    let pak_names = match resource_info_by_id(res_id) {
+        Some(info) => info
+            .paks
+            .iter()
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect(),
+        None => pak_names(gc_disc),
+    };
+
+    for pak_name in pak_names {
+        let pak = read_pak(gc_disc, &pak_name)?;
+        if let Some(res) = pak.resources.iter().find(|res| res.file_id == res_id) {
+            return Ok(ResourceData::new(&res).decompress().into_owned());
+        }
+    }
+
+    Err(format!("Failed to find asset 0x{:08X} on the disc", res_id))
+}
+
+/// Replaces a resource of a pak with the uncompressed contents of a file, keeping its type.
+/// Returns the new pak.
+pub fn replace_asset(pak_bytes: &[u8], asset: &str, data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let res_id = parse_asset_id(asset)?;
+    let mut pak: structs::Pak = Reader::new(pak_bytes).read(());
+
+    let mut cursor = pak.resources.cursor();
+    loop {
+        let found = match cursor.peek() {
+            Some(res) => res.file_id == res_id,
+            None => Err(format!("The pak has no asset 0x{:08X}", res_id))?,
+        };
+        if found {
+            break;
+        }
+        cursor.next();
+    }
+    let res = cursor.value().unwrap();
+    res.compressed = false;
+    res.kind = structs::ResourceKind::External(data, res.fourcc());
+
+    let mut bytes = vec![];
+    pak.write_to(&mut bytes)
+        .map_err(|e| format!("Failed to write the pak: {}", e))?;
+    Ok(bytes)
+}
+
+/// Formats a resource for `assets list`
+pub fn format_asset(asset: &AssetEntry) -> String {
+    format!(
+        "0x{:08X} {} {:>9} {:<14} {}",
+        asset.res_id,
+        asset.fourcc,
+        asset.size,
+        asset.pak_name,
+        asset.name.unwrap_or("")
+    )
+}
@@ -0,0 +1,85 @@
+// An on-disk cache of generated assets, keyed by a hash of everything they're built from, so that
+// patching again with the same cosmetic settings doesn't have to redo the slow parts (mostly the
+// CMPR compression of recolored textures).
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::{debug, warn};
+
+use crate::sha256::{to_hex, Sha256};
+
+// Bump this whenever the output of a cached builder changes for the same inputs
+const CACHE_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Default)]
+pub struct AssetCache {
+    dir: Option<PathBuf>,
+}
+
+impl AssetCache {
+    /// Caches the assets in the "assets" subdirectory of `cache_dir`. Caching is disabled if it
+    /// can't be created.
+    pub fn new(cache_dir: &str) -> Self {
+        let dir = Path::new(cache_dir).join("assets");
+        if let Err(e) = fs::create_dir_all(&dir) {
+            warn!(
+                "Failed to create the asset cache dir {}, generated assets won't be cached: {}",
+                dir.display(),
+                e
+            );
+            return AssetCache::disabled();
+        }
+        AssetCache { dir: Some(dir) }
+    }
+
+    pub fn disabled() -> Self {
+        AssetCache { dir: None }
+    }
+
+    fn entry_path(&self, kind: &str, inputs: &[&[u8]]) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        let mut sha = Sha256::new();
+        sha.update(&CACHE_VERSION.to_be_bytes());
+        sha.update(kind.as_bytes());
+        for input in inputs {
+            // Length-prefixed so that moving bytes between two inputs changes the key
+            sha.update(&(input.len() as u64).to_be_bytes());
+            sha.update(input);
+        }
+        Some(dir.join(format!("{}.{}", to_hex(&sha.finish()), kind)))
+    }
+
+    /// Returns the asset of type `kind` built from `inputs`, running `build` only if it isn't
+    /// cached yet. `inputs` must cover everything the output depends on.
+    pub fn get_or_build<F>(&self, kind: &str, inputs: &[&[u8]], build: F) -> Result<Vec<u8>, String>
+    where
+        F: FnOnce() -> Result<Vec<u8>, String>,
+    {
+        let path = match self.entry_path(kind, inputs) {
+            Some(path) => path,
+            None => return build(),
+        };
+
+        if let Ok(bytes) = fs::read(&path) {
+            return Ok(bytes);
+        }
+
+        let bytes = build()?;
+
+        // Written under a temporary name first, so that an interrupted run can't leave a
+        // truncated entry behind
+        let tmp_path = path.with_extension("tmp");
+        if let Err(e) = fs::write(&tmp_path, &bytes).and_then(|()| fs::rename(&tmp_path, &path)) {
+            debug!(
+                "Failed to write {} to the asset cache: {}",
+                path.display(),
+                e
+            );
+        }
+
+        Ok(bytes)
+    }
+}
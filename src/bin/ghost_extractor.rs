@@ -0,0 +1,160 @@
+//! Extracts the ghost recorded by the patches.rel payload (see `preferences.ghostRecordingAddress`)
+//! from a dump of the GameCube's main memory, or from the file `preferences.ghostMemoryCardDump`
+//! writes to the memory card, and converts it to a JSON path.
+
+use std::{convert::TryInto, fs::File};
+
+use clap::{clap_app, crate_authors, crate_version};
+use serde::Serialize;
+
+// Must be kept in sync with GhostRegion in compile_to_ppc/rel_patches
+const GHOST_MAGIC: u32 = 0x47485354;
+const MAX_GHOST_FRAMES: usize = 4096;
+const GHOST_HEADER_SIZE: usize = 0x14;
+const GHOST_FRAME_SIZE: usize = 0x24;
+
+const MEM1_BASE: u32 = 0x80000000;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GhostFrame {
+    igt: f32,
+    mlvl: u32,
+    area: u32,
+    position: [f32; 3],
+    facing: [f32; 3],
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| format!("Ghost data is truncated at offset 0x{:X}", offset))
+}
+
+fn read_f32(data: &[u8], offset: usize) -> Result<f32, String> {
+    read_u32(data, offset).map(f32::from_bits)
+}
+
+fn read_frame(data: &[u8], offset: usize) -> Result<GhostFrame, String> {
+    Ok(GhostFrame {
+        igt: read_f32(data, offset)?,
+        mlvl: read_u32(data, offset + 0x4)?,
+        area: read_u32(data, offset + 0x8)?,
+        position: [
+            read_f32(data, offset + 0xC)?,
+            read_f32(data, offset + 0x10)?,
+            read_f32(data, offset + 0x14)?,
+        ],
+        facing: [
+            read_f32(data, offset + 0x18)?,
+            read_f32(data, offset + 0x1C)?,
+            read_f32(data, offset + 0x20)?,
+        ],
+    })
+}
+
+// Exported memory card files start with the file's directory entry
+const GCI_HEADER_SIZE: usize = 0x40;
+
+fn mem1_offset(address: u32) -> Result<usize, String> {
+    address
+        .checked_sub(MEM1_BASE)
+        .map(|offset| offset as usize)
+        .ok_or_else(|| format!("Address 0x{:X} is not within MEM1", address))
+}
+
+fn extract_ghost(
+    input: &str,
+    output: &str,
+    address: Option<u32>,
+    last_room_only: bool,
+) -> Result<(), String> {
+    let input_file = File::open(input).map_err(|e| format!("Failed to open input file: {}", e))?;
+    let mmap = unsafe { memmap::Mmap::map(&input_file) }
+        .map_err(|e| format!("Failed to map input file: {}", e))?;
+
+    // In a MEM1 dump, the address given holds the address of the ghost. A GCI dumped by
+    // ghostMemoryCardDump holds the ghost itself.
+    let base = match address {
+        Some(address) => {
+            let ghost_address = read_u32(&mmap[..], mem1_offset(address)?)?;
+            mem1_offset(ghost_address)?
+        }
+        None => GCI_HEADER_SIZE,
+    };
+    let data = mmap
+        .get(base..)
+        .ok_or_else(|| format!("Offset 0x{:X} is past the end of the input", base))?;
+
+    if read_u32(data, 0)? != GHOST_MAGIC {
+        Err("No ghost was recorded")?
+    }
+    let frame_count = read_u32(data, 0x4)?;
+    let (start, end) = if last_room_only {
+        (read_u32(data, 0xC)?, read_u32(data, 0x10)?)
+    } else {
+        (0, frame_count)
+    };
+
+    // Older frames have been overwritten by the ring buffer
+    let start = start.max(frame_count.saturating_sub(MAX_GHOST_FRAMES as u32));
+    let frames = (start..end)
+        .map(|i| {
+            let offset = GHOST_HEADER_SIZE + (i as usize % MAX_GHOST_FRAMES) * GHOST_FRAME_SIZE;
+            read_frame(data, offset)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let output_file =
+        File::create(output).map_err(|e| format!("Failed to open output file: {}", e))?;
+    serde_json::to_writer_pretty(output_file, &frames)
+        .map_err(|e| format!("Failed to write JSON: {}", e))?;
+
+    Ok(())
+}
+
+fn parse_address(s: &str) -> Result<u32, String> {
+    let s = s.trim();
+    let res = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16)
+    } else {
+        s.parse::<u32>()
+    };
+    res.map_err(|_| format!("Invalid address \"{}\"", s))
+}
+
+fn main() {
+    let app = clap_app!(app =>
+        (version: crate_version!())
+        (author: crate_authors!())
+        (about: "Converts a ghost recorded in a MEM1 dump to a JSON path.")
+        (@arg input: -i --input +takes_value +required "MEM1 dump to read the ghost from.")
+        (@arg output: -o --output +takes_value +required "Output path to write the JSON file.")
+        (@arg address: -a --address +takes_value
+            { |s| parse_address(&s).map(|_| ()) }
+            "Address the ghost's address was written to (ghostRecordingAddress)."
+        )
+        (@arg gci: -g --gci conflicts_with[address]
+            "The input is the ghost's file exported from the memory card, rather than a MEM1 dump."
+        )
+        (@arg last_room: -l --last_room "Only extract the frames of the last room that was exited.")
+    );
+    let matches = app.get_matches();
+
+    let address = matches
+        .value_of("address")
+        .map(|address| parse_address(address).unwrap());
+    let res = if address.is_none() && !matches.is_present("gci") {
+        Err("Either --address or --gci is required".to_string())
+    } else {
+        extract_ghost(
+            matches.value_of("input").unwrap(),
+            matches.value_of("output").unwrap(),
+            address,
+            matches.is_present("last_room"),
+        )
+    };
+    if let Err(s) = res {
+        eprintln!("{} {}", clap::Format::Error("error:"), s);
+    }
+}
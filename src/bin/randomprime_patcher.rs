@@ -1,53 +1,22 @@
-use std::{panic, process::Command};
+use std::{
+    fs::{self, File, OpenOptions},
+    panic,
+    process::Command,
+};
 
-use clap::Format;
-use randomprime::{patch_config::PatchConfig, patches, reader_writer, structs};
-
-struct ProgressNotifier {
-    total_size: usize,
-    bytes_so_far: usize,
-    quiet: bool,
-}
-
-impl ProgressNotifier {
-    fn new(quiet: bool) -> ProgressNotifier {
-        ProgressNotifier {
-            total_size: 0,
-            bytes_so_far: 0,
-            quiet,
-        }
-    }
-}
-
-impl structs::ProgressNotifier for ProgressNotifier {
-    fn notify_total_bytes(&mut self, total_size: usize) {
-        self.total_size = total_size
-    }
-
-    fn notify_writing_file(&mut self, file_name: &reader_writer::CStr, file_bytes: usize) {
-        if self.quiet {
-            return;
-        }
-        let percent = self.bytes_so_far as f64 / self.total_size as f64 * 100.;
-        println!("{:02.0}% -- Writing file {:?}", percent, file_name);
-        self.bytes_so_far += file_bytes;
-    }
-
-    fn notify_writing_header(&mut self) {
-        if self.quiet {
-            return;
-        }
-        let percent = self.bytes_so_far as f64 / self.total_size as f64 * 100.;
-        println!("{:02.0}% -- Writing ISO header", percent);
-    }
-
-    fn notify_flushing_to_disk(&mut self) {
-        if self.quiet {
-            return;
-        }
-        println!("Flushing written data to the disk...");
-    }
-}
+use clap::{crate_version, App, Arg, ArgMatches, Format, SubCommand};
+use randomprime::{
+    asset_browser, bps,
+    generator::{self, GeneratorSettings},
+    memmap,
+    patch_config::PatchConfig,
+    patch_manifest::{self, PatchManifest},
+    patches,
+    progress::ConsoleProgressNotifier,
+    reader_writer::{FourCC, Reader},
+    room_dump::{self, RoomDump},
+    structs,
+};
 
 #[cfg(windows)]
 fn was_launched_by_windows_explorer() -> bool {
@@ -77,10 +46,336 @@ fn maybe_pause_at_exit() {
     }
 }
 
+fn app() -> App<'static, 'static> {
+    let iso_arg = || {
+        Arg::with_name("iso path")
+            .long("iso")
+            .takes_value(true)
+            .required(true)
+    };
+    let output_arg = || {
+        Arg::with_name("output path")
+            .long("output")
+            .takes_value(true)
+            .required(true)
+    };
+    App::new("randomprime ISO patcher")
+        .version(crate_version!())
+        // Patching without a subcommand, as it was done before there were any
+        .args(&PatchConfig::cli_args())
+        .subcommand(
+            SubCommand::with_name("patch")
+                .about("Patches an ISO, the same as running without a subcommand")
+                .args(&PatchConfig::cli_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("validate")
+                .about("Checks a config without writing the output ISO")
+                .args(&PatchConfig::cli_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("apply-patch")
+                .about("Applies a .bps patch made by randomprime to an unmodified ISO")
+                .arg(
+                    Arg::with_name("input iso path")
+                        .long("input-iso")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("patch path")
+                        .long("patch")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("output iso path")
+                        .long("output-iso")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("generate")
+                .about("Generates a layout config from a settings file")
+                .arg(
+                    Arg::with_name("settings path")
+                        .long("settings")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(output_arg())
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .help("Overrides the seed of the settings file")
+                        .takes_value(true)
+                        .validator(|s| {
+                            s.parse::<u64>()
+                                .map(|_| ())
+                                .map_err(|_| "Expected an integer".to_string())
+                        }),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("Checks an ISO against the manifest written when it was patched")
+                .arg(iso_arg())
+                .arg(
+                    Arg::with_name("manifest path")
+                        .long("manifest")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("extract")
+                .alias("extract-room")
+                .about("Dumps the layers and objects of a room of a pak as JSON")
+                .arg(
+                    Arg::with_name("pak path")
+                        .long("pak")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("room")
+                        .long("room")
+                        .help("The room to dump, e.g. \"Chozo Ruins:Hive Totem\"")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(output_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("import-room")
+                .about("Replaces the layers and objects of a room of a pak with a JSON dump")
+                .arg(
+                    Arg::with_name("pak path")
+                        .long("pak")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("room json path")
+                        .long("room-json")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(output_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("assets")
+                .about("Lists, extracts and replaces the resources of the paks")
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Lists the resources of an ISO")
+                        .arg(iso_arg())
+                        .arg(
+                            Arg::with_name("pak")
+                                .long("pak")
+                                .help("Only list the resources of this pak, e.g. Metroid2.pak")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("type")
+                                .long("type")
+                                .help("Only list the resources of this type, e.g. TXTR")
+                                .takes_value(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("extract")
+                        .about("Extracts a resource, decompressed, or a whole pak from an ISO")
+                        .arg(iso_arg())
+                        .arg(
+                            Arg::with_name("asset")
+                                .long("asset")
+                                .help("The ID or the name of the resource, or the name of a pak")
+                                .takes_value(true)
+                                .required(true),
+                        )
+                        .arg(output_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("replace")
+                        .about("Replaces a resource of a pak with the contents of a file")
+                        .arg(
+                            Arg::with_name("pak path")
+                                .long("pak")
+                                .takes_value(true)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("asset")
+                                .long("asset")
+                                .help("The ID or the name of the resource")
+                                .takes_value(true)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("input path")
+                                .long("input")
+                                .takes_value(true)
+                                .required(true),
+                        )
+                        .arg(output_arg()),
+                ),
+        )
+}
+
+fn patch(matches: &ArgMatches) -> Result<(), String> {
+    let patch_config = PatchConfig::from_cli_matches(matches)?;
+    let pn = ConsoleProgressNotifier::new(patch_config.quiet);
+    patches::patch_iso(patch_config, pn)
+}
+
+fn apply_patch(matches: &ArgMatches) -> Result<(), String> {
+    let input_iso_path = matches.value_of("input iso path").unwrap();
+    let input_iso_file = File::open(input_iso_path)
+        .map_err(|e| format!("Failed to open {}: {}", input_iso_path, e))?;
+    let input_iso = unsafe { memmap::Mmap::map(&input_iso_file) }
+        .map_err(|e| format!("Failed to open {}: {}", input_iso_path, e))?;
+    let patch_path = matches.value_of("patch path").unwrap();
+    let patch =
+        fs::read(patch_path).map_err(|e| format!("Failed to open {}: {}", patch_path, e))?;
+    let output_iso_path = matches.value_of("output iso path").unwrap();
+    let mut output_iso = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(output_iso_path)
+        .map_err(|e| format!("Failed to open {}: {}", output_iso_path, e))?;
+
+    bps::apply_bps_patch(&input_iso, &patch, &mut output_iso)
+}
+
+fn generate(matches: &ArgMatches) -> Result<(), String> {
+    let settings_path = matches.value_of("settings path").unwrap();
+    let settings = fs::read_to_string(settings_path)
+        .map_err(|e| format!("Failed to open {}: {}", settings_path, e))?;
+    let mut settings: GeneratorSettings = serde_json::from_str(&settings)
+        .map_err(|e| format!("Failed to parse {}: {}", settings_path, e))?;
+    if let Some(seed) = matches.value_of("seed") {
+        settings.seed = Some(seed.parse().unwrap());
+    }
+
+    let layout = generator::generate_layout(&settings)?;
+    println!("Seed: {}", layout["seed"]);
+
+    let output_path = matches.value_of("output path").unwrap();
+    let layout = serde_json::to_string_pretty(&layout).unwrap();
+    fs::write(output_path, layout).map_err(|e| format!("Failed to write {}: {}", output_path, e))
+}
+
+fn verify(matches: &ArgMatches) -> Result<(), String> {
+    let manifest_path = matches.value_of("manifest path").unwrap();
+    let manifest = fs::read_to_string(manifest_path)
+        .map_err(|e| format!("Failed to open {}: {}", manifest_path, e))?;
+    let manifest: PatchManifest = serde_json::from_str(&manifest)
+        .map_err(|e| format!("Failed to parse {}: {}", manifest_path, e))?;
+
+    let iso_path = matches.value_of("iso path").unwrap();
+    let iso_file =
+        File::open(iso_path).map_err(|e| format!("Failed to open {}: {}", iso_path, e))?;
+    let iso = unsafe { memmap::Mmap::map(&iso_file) }
+        .map_err(|e| format!("Failed to open {}: {}", iso_path, e))?;
+
+    patch_manifest::verify_iso(&iso, &manifest)?;
+    println!("The ISO matches the manifest");
+    Ok(())
+}
+
+fn extract_room(matches: &ArgMatches) -> Result<(), String> {
+    let pak_path = matches.value_of("pak path").unwrap();
+    let pak = fs::read(pak_path).map_err(|e| format!("Failed to open {}: {}", pak_path, e))?;
+
+    let dump = room_dump::dump_room(&pak, matches.value_of("room").unwrap())?;
+
+    let output_path = matches.value_of("output path").unwrap();
+    let dump = serde_json::to_string_pretty(&dump).unwrap();
+    fs::write(output_path, dump).map_err(|e| format!("Failed to write {}: {}", output_path, e))
+}
+
+fn import_room(matches: &ArgMatches) -> Result<(), String> {
+    let dump_path = matches.value_of("room json path").unwrap();
+    let dump = fs::read_to_string(dump_path)
+        .map_err(|e| format!("Failed to open {}: {}", dump_path, e))?;
+    let dump: RoomDump =
+        serde_json::from_str(&dump).map_err(|e| format!("Failed to parse {}: {}", dump_path, e))?;
+
+    let pak_path = matches.value_of("pak path").unwrap();
+    let pak = fs::read(pak_path).map_err(|e| format!("Failed to open {}: {}", pak_path, e))?;
+
+    let pak = room_dump::import_room(&pak, &dump)?;
+
+    let output_path = matches.value_of("output path").unwrap();
+    fs::write(output_path, pak).map_err(|e| format!("Failed to write {}: {}", output_path, e))
+}
+
+fn assets(matches: &ArgMatches) -> Result<(), String> {
+    if let Some(matches) = matches.subcommand_matches("replace") {
+        let pak_path = matches.value_of("pak path").unwrap();
+        let pak = fs::read(pak_path).map_err(|e| format!("Failed to open {}: {}", pak_path, e))?;
+        let input_path = matches.value_of("input path").unwrap();
+        let data =
+            fs::read(input_path).map_err(|e| format!("Failed to open {}: {}", input_path, e))?;
+
+        let pak = asset_browser::replace_asset(&pak, matches.value_of("asset").unwrap(), data)?;
+
+        let output_path = matches.value_of("output path").unwrap();
+        return fs::write(output_path, pak)
+            .map_err(|e| format!("Failed to write {}: {}", output_path, e));
+    }
+
+    let (subcommand, matches) = match matches.subcommand() {
+        (subcommand @ ("list" | "extract"), Some(matches)) => (subcommand, matches),
+        _ => Err("Expected one of 'list', 'extract' or 'replace'".to_string())?,
+    };
+    let iso_path = matches.value_of("iso path").unwrap();
+    let iso_file =
+        File::open(iso_path).map_err(|e| format!("Failed to open {}: {}", iso_path, e))?;
+    let iso = unsafe { memmap::Mmap::map(&iso_file) }
+        .map_err(|e| format!("Failed to open {}: {}", iso_path, e))?;
+    let gc_disc: structs::GcDisc = Reader::new(&iso[..]).read(());
+
+    if subcommand == "list" {
+        let fourcc = match matches.value_of("type") {
+            Some(fourcc) => match <&[u8; 4]>::try_from(fourcc.as_bytes()) {
+                Ok(bytes) => Some(FourCC::from_bytes(bytes)),
+                Err(_) => Err(format!("'{}' is not a resource type", fourcc))?,
+            },
+            None => None,
+        };
+        for asset in asset_browser::list_assets(&gc_disc, matches.value_of("pak"), fourcc)? {
+            println!("{}", asset_browser::format_asset(&asset));
+        }
+        return Ok(());
+    }
+
+    let data = asset_browser::extract_asset(&gc_disc, matches.value_of("asset").unwrap())?;
+    let output_path = matches.value_of("output path").unwrap();
+    fs::write(output_path, data).map_err(|e| format!("Failed to write {}: {}", output_path, e))
+}
+
 fn main_inner() -> Result<(), String> {
-    let patch_config = PatchConfig::from_cli_options()?;
-    let pn = ProgressNotifier::new(patch_config.quiet);
-    patches::patch_iso(patch_config, pn)?;
+    let matches = app().get_matches();
+    match matches.subcommand() {
+        ("patch", Some(matches)) => patch(matches)?,
+        ("validate", Some(matches)) => {
+            PatchConfig::validate_cli_matches(matches)?;
+            println!("The config is valid");
+            return Ok(());
+        }
+        ("apply-patch", Some(matches)) => apply_patch(matches)?,
+        ("generate", Some(matches)) => generate(matches)?,
+        ("verify", Some(matches)) => return verify(matches),
+        ("extract", Some(matches)) => extract_room(matches)?,
+        ("import-room", Some(matches)) => import_room(matches)?,
+        ("assets", Some(matches)) => return assets(matches),
+        _ => patch(&matches)?,
+    }
     println!("Done");
     Ok(())
 }
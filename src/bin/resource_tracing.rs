@@ -13,7 +13,9 @@ use std::{
 pub use randomprime::*;
 use randomprime::{
     custom_assets::custom_asset_ids,
-    pickup_meta::{pickup_model_for_pickup, PickupModel, PickupType, ScriptObjectLocation},
+    pickup_meta::{
+        pickup_model_for_pickup, DoorOrientation, PickupModel, PickupType, ScriptObjectLocation,
+    },
 };
 use reader_writer::{FourCC, Readable, Reader, Writable};
 use resource_info_table::{resource_info, ResourceInfo};
@@ -34,6 +36,7 @@ pub struct PickupLocation {
 pub struct DoorLocation {
     door_location: Option<ScriptObjectLocation>,
     door_rotation: Option<[f32; 3]>,
+    door_orientation: Option<DoorOrientation>,
     door_force_locations: Vec<ScriptObjectLocation>,
     door_shield_locations: Vec<ScriptObjectLocation>,
     dock_number: u32,
@@ -1248,6 +1251,7 @@ fn main() {
                     door_locations.push(DoorLocation {
                         door_location: door_loc,
                         door_rotation,
+                        door_orientation: door_rotation.map(DoorOrientation::from_rotation),
                         door_force_locations,
                         door_shield_locations,
                         dock_number: dock.dock_index,
@@ -1563,6 +1567,10 @@ fn main() {
                     "                    door_rotation: {:?},",
                     door.door_rotation
                 );
+                println!(
+                    "                    door_orientation: {:?},",
+                    door.door_orientation
+                );
                 println!(
                     "                    door_force_locations: &{:?},",
                     door.door_force_locations
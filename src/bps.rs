@@ -0,0 +1,447 @@
+use std::{
+    cmp::min,
+    collections::HashMap,
+    io::{self, Read, Seek, Write},
+    mem,
+};
+
+use flate2::Crc;
+use reader_writer::byteorder::{LittleEndian, WriteBytesExt};
+
+// Based on byuu's BPS format specification
+
+const BPS_MAGIC: &[u8; 4] = b"BPS1";
+
+const SOURCE_READ: u64 = 0;
+const TARGET_READ: u64 = 1;
+const SOURCE_COPY: u64 = 2;
+const TARGET_COPY: u64 = 3;
+
+// Files on the disc are 32 byte aligned, so matches against the source are looked for one
+// granule at a time. Only granules at the start of each block of the source are indexed, a file
+// that moved resyncs with the source within a block of its start.
+const GRANULE_SIZE: usize = 32;
+const INDEX_BLOCK_SIZE: usize = 4096;
+
+// Data that isn't in the source is written out as it comes in, in actions of at most this size
+const MAX_LITERAL_SIZE: usize = 0x10000;
+
+const ZEROES: &[u8; 0x8000] = &[0u8; 0x8000];
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
+
+/// Writes the data it receives as a BPS patch from `source` instead of writing it directly, so
+/// the target image is never held in memory.
+pub struct BpsWriter<'s, W: Write> {
+    out: W,
+    patch_crc: Crc,
+
+    source: &'s [u8],
+    source_index: HashMap<u64, usize>,
+    source_relative_offset: usize,
+
+    target_size: usize,
+    target_pos: usize,
+    target_crc: Crc,
+
+    granule: Vec<u8>,
+    pending_literal: Vec<u8>,
+    pending_copy: Option<(usize, usize)>, // (source offset, length)
+}
+
+impl<'s, W: Write> BpsWriter<'s, W> {
+    pub fn new(out: W, source: &'s [u8], target_size: usize) -> io::Result<BpsWriter<'s, W>> {
+        let mut source_index = HashMap::new();
+        for offset in (0..source.len().saturating_sub(GRANULE_SIZE)).step_by(INDEX_BLOCK_SIZE) {
+            source_index
+                .entry(fnv1a(&source[offset..offset + GRANULE_SIZE]))
+                .or_insert(offset);
+        }
+
+        let mut writer = BpsWriter {
+            out,
+            patch_crc: Crc::new(),
+
+            source,
+            source_index,
+            source_relative_offset: 0,
+
+            target_size,
+            target_pos: 0,
+            target_crc: Crc::new(),
+
+            granule: Vec::with_capacity(GRANULE_SIZE),
+            pending_literal: Vec::new(),
+            pending_copy: None,
+        };
+        writer.write_patch_bytes(BPS_MAGIC)?;
+        writer.write_number(source.len() as u64)?;
+        writer.write_number(target_size as u64)?;
+        writer.write_number(0)?; // No metadata
+        Ok(writer)
+    }
+
+    /// Pads the target with zeroes up to its full size and writes the checksums at the end of
+    /// the patch.
+    pub fn finish(mut self) -> io::Result<W> {
+        while self.target_pos + self.granule.len() < self.target_size {
+            let l = min(
+                ZEROES.len(),
+                self.target_size - self.target_pos - self.granule.len(),
+            );
+            self.write_all(&ZEROES[..l])?;
+        }
+        let granule = mem::take(&mut self.granule);
+        if !granule.is_empty() {
+            self.process_granule(&granule)?;
+        }
+        self.flush_pending_copy()?;
+        self.flush_pending_literal()?;
+
+        let source_crc = crc32(self.source);
+        let target_crc = self.target_crc.sum();
+        let mut footer = vec![];
+        footer.write_u32::<LittleEndian>(source_crc)?;
+        footer.write_u32::<LittleEndian>(target_crc)?;
+        self.write_patch_bytes(&footer)?;
+        let patch_crc = self.patch_crc.sum();
+        self.out.write_u32::<LittleEndian>(patch_crc)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn write_patch_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.patch_crc.update(bytes);
+        self.out.write_all(bytes)
+    }
+
+    fn write_number(&mut self, mut n: u64) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(10);
+        loop {
+            let x = (n & 0x7f) as u8;
+            n >>= 7;
+            if n == 0 {
+                bytes.push(0x80 | x);
+                break;
+            }
+            bytes.push(x);
+            n -= 1;
+        }
+        self.write_patch_bytes(&bytes)
+    }
+
+    fn write_action(&mut self, command: u64, length: usize) -> io::Result<()> {
+        self.write_number(((length as u64 - 1) << 2) | command)
+    }
+
+    fn flush_pending_literal(&mut self) -> io::Result<()> {
+        if self.pending_literal.is_empty() {
+            return Ok(());
+        }
+        let literal = mem::take(&mut self.pending_literal);
+        self.write_action(TARGET_READ, literal.len())?;
+        self.write_patch_bytes(&literal)?;
+        self.pending_literal = literal;
+        self.pending_literal.clear();
+        Ok(())
+    }
+
+    fn flush_pending_copy(&mut self) -> io::Result<()> {
+        let (source_offset, length) = match self.pending_copy.take() {
+            Some(copy) => copy,
+            None => return Ok(()),
+        };
+        if source_offset == self.target_pos - length {
+            self.write_action(SOURCE_READ, length)
+        } else {
+            self.write_action(SOURCE_COPY, length)?;
+            let delta = source_offset as i64 - self.source_relative_offset as i64;
+            self.write_number((delta.unsigned_abs() << 1) | (delta < 0) as u64)?;
+            self.source_relative_offset = source_offset + length;
+            Ok(())
+        }
+    }
+
+    fn source_matches(&self, offset: usize, bytes: &[u8]) -> bool {
+        self.source.get(offset..offset + bytes.len()) == Some(bytes)
+    }
+
+    fn process_granule(&mut self, granule: &[u8]) -> io::Result<()> {
+        if let Some((source_offset, length)) = self.pending_copy {
+            if self.source_matches(source_offset + length, granule) {
+                self.pending_copy = Some((source_offset, length + granule.len()));
+                self.target_pos += granule.len();
+                return Ok(());
+            }
+            self.flush_pending_copy()?;
+        }
+
+        // Prefer the same offset in the source, it's the cheapest to encode
+        let candidates = [
+            Some(self.target_pos),
+            self.source_index.get(&fnv1a(granule)).copied(),
+        ];
+        for source_offset in candidates.iter().flatten() {
+            if self.source_matches(*source_offset, granule) {
+                self.flush_pending_literal()?;
+                self.pending_copy = Some((*source_offset, granule.len()));
+                self.target_pos += granule.len();
+                return Ok(());
+            }
+        }
+
+        self.pending_literal.extend_from_slice(granule);
+        self.target_pos += granule.len();
+        if self.pending_literal.len() >= MAX_LITERAL_SIZE {
+            self.flush_pending_literal()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'s, W: Write> Write for BpsWriter<'s, W> {
+    fn write(&mut self, mut buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len();
+        if self.target_pos + self.granule.len() + len > self.target_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Wrote past the end of the BPS target",
+            ));
+        }
+        self.target_crc.update(buf);
+
+        if !self.granule.is_empty() {
+            let l = min(GRANULE_SIZE - self.granule.len(), buf.len());
+            self.granule.extend_from_slice(&buf[..l]);
+            buf = &buf[l..];
+            if self.granule.len() == GRANULE_SIZE {
+                let granule = mem::take(&mut self.granule);
+                self.process_granule(&granule)?;
+                self.granule = granule;
+                self.granule.clear();
+            }
+        }
+        while buf.len() >= GRANULE_SIZE {
+            self.process_granule(&buf[..GRANULE_SIZE])?;
+            buf = &buf[GRANULE_SIZE..];
+        }
+        self.granule.extend_from_slice(buf);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+impl<'s, W: Write> structs::WriteExt for BpsWriter<'s, W> {
+    fn skip_bytes(&mut self, mut bytes: u64) -> io::Result<()> {
+        while bytes > 0 {
+            let l = min(ZEROES.len() as u64, bytes);
+            self.write_all(&ZEROES[..l as usize])?;
+            bytes -= l;
+        }
+        Ok(())
+    }
+}
+
+struct PatchReader<'a> {
+    patch: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .patch
+            .get(self.pos..self.pos + len)
+            .ok_or("Unexpected end of BPS patch")?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_number(&mut self) -> Result<u64, String> {
+        let mut n = 0u64;
+        let mut shift = 1u64;
+        loop {
+            let x = self.read_bytes(1)?[0] as u64;
+            n = n
+                .checked_add((x & 0x7f) * shift)
+                .ok_or("Invalid number in BPS patch")?;
+            if x & 0x80 != 0 {
+                return Ok(n);
+            }
+            shift <<= 7;
+            n += shift;
+        }
+    }
+}
+
+/// Applies a BPS patch made by `BpsWriter` (or any other tool) to `source`, writing the
+/// patched image to `target`.
+pub fn apply_bps_patch<W>(source: &[u8], patch: &[u8], target: &mut W) -> Result<(), String>
+where
+    W: Read + Write + Seek,
+{
+    if patch.len() < BPS_MAGIC.len() + 12 || !patch.starts_with(BPS_MAGIC) {
+        Err("Not a BPS patch")?
+    }
+    let footer = &patch[patch.len() - 12..];
+    let read_crc =
+        |offset: usize| u32::from_le_bytes(footer[offset..offset + 4].try_into().unwrap());
+    if crc32(&patch[..patch.len() - 4]) != read_crc(8) {
+        Err("The BPS patch is corrupt")?
+    }
+    if crc32(source) != read_crc(0) {
+        Err("The input ISO doesn't match the one the BPS patch was made from")?
+    }
+
+    let mut reader = PatchReader {
+        patch: &patch[..patch.len() - 12],
+        pos: BPS_MAGIC.len(),
+    };
+    let source_size = reader.read_number()? as usize;
+    let target_size = reader.read_number()? as usize;
+    let metadata_size = reader.read_number()? as usize;
+    reader.read_bytes(metadata_size)?;
+    if source_size != source.len() {
+        Err("The input ISO doesn't match the one the BPS patch was made from")?
+    }
+
+    let io_err = |e: io::Error| format!("Failed to write output file: {}", e);
+    let mut target_crc = Crc::new();
+    let mut target_pos = 0usize;
+    let mut source_relative_offset = 0i64;
+    let mut target_relative_offset = 0i64;
+    let mut write = |target: &mut W, bytes: &[u8], target_pos: &mut usize| {
+        target_crc.update(bytes);
+        *target_pos += bytes.len();
+        target.write_all(bytes).map_err(io_err)
+    };
+
+    target.seek(io::SeekFrom::Start(0)).map_err(io_err)?;
+    while reader.pos < reader.patch.len() {
+        let data = reader.read_number()?;
+        let length = (data >> 2) as usize + 1;
+        if target_pos + length > target_size {
+            Err("The BPS patch writes past the end of the output")?
+        }
+        match data & 3 {
+            SOURCE_READ => {
+                let bytes = source
+                    .get(target_pos..target_pos + length)
+                    .ok_or("The BPS patch reads past the end of the input ISO")?;
+                write(target, bytes, &mut target_pos)?;
+            }
+            TARGET_READ => {
+                let bytes = reader.read_bytes(length)?;
+                write(target, bytes, &mut target_pos)?;
+            }
+            SOURCE_COPY => {
+                let offset = reader.read_number()?;
+                let delta = (offset >> 1) as i64 * if offset & 1 == 1 { -1 } else { 1 };
+                source_relative_offset += delta;
+                let start = source_relative_offset as usize;
+                let bytes = source
+                    .get(start..start + length)
+                    .ok_or("The BPS patch reads past the end of the input ISO")?;
+                write(target, bytes, &mut target_pos)?;
+                source_relative_offset += length as i64;
+            }
+            TARGET_COPY => {
+                let offset = reader.read_number()?;
+                let delta = (offset >> 1) as i64 * if offset & 1 == 1 { -1 } else { 1 };
+                target_relative_offset += delta;
+                let start = target_relative_offset as usize;
+                if start >= target_pos {
+                    Err("The BPS patch copies from the part of the output not written yet")?
+                }
+
+                // The copy may overlap what it's writing, which repeats the bytes in between
+                let mut remaining = length;
+                let mut copy_from = start;
+                while remaining > 0 {
+                    let l = min(remaining, target_pos - copy_from);
+                    let mut bytes = vec![0u8; l];
+                    target
+                        .seek(io::SeekFrom::Start(copy_from as u64))
+                        .and_then(|_| target.read_exact(&mut bytes))
+                        .and_then(|_| target.seek(io::SeekFrom::Start(target_pos as u64)))
+                        .map_err(io_err)?;
+                    write(target, &bytes, &mut target_pos)?;
+                    copy_from += l;
+                    remaining -= l;
+                }
+                target_relative_offset += length as i64;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    if target_pos != target_size {
+        Err("The BPS patch is incomplete")?
+    }
+    if target_crc.sum() != read_crc(4) {
+        Err("The patched ISO doesn't match the one the BPS patch was made for")?
+    }
+    target.flush().map_err(io_err)
+}
+
+#[test]
+fn test_bps_round_trip() {
+    let mut seed = 1u32;
+    let mut source: Vec<u8> = (0..0x20000)
+        .map(|_| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as u8
+        })
+        .collect();
+    source[0xC000..0xE000].fill(0);
+
+    let mut target = source[0x8000..0xC000].to_vec(); // Moved
+    target.extend_from_slice(b"new data that isn't in the source");
+    target.resize(0x10000, 0);
+    target.extend_from_slice(&source[0x10000..0x18000]); // Unmoved
+
+    let mut writer = BpsWriter::new(vec![], &source, target.len() + 0x1000).unwrap();
+    writer.write_all(&target[..0x1234]).unwrap();
+    writer.write_all(&target[0x1234..]).unwrap();
+    let patch = writer.finish().unwrap();
+    target.resize(target.len() + 0x1000, 0);
+
+    let mut patched = io::Cursor::new(vec![]);
+    apply_bps_patch(&source, &patch, &mut patched).unwrap();
+    assert!(patched.into_inner() == target);
+    assert!(patch.len() < 0x400);
+}
+
+#[test]
+fn test_bps_long_literal() {
+    let source = vec![0u8; 0x1000];
+    let mut seed = 7u32;
+    let target: Vec<u8> = (0..MAX_LITERAL_SIZE * 3 + 0x100)
+        .map(|_| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as u8
+        })
+        .collect();
+
+    let mut writer = BpsWriter::new(vec![], &source, target.len()).unwrap();
+    writer.write_all(&target).unwrap();
+    assert!(writer.pending_literal.len() < MAX_LITERAL_SIZE);
+    let patch = writer.finish().unwrap();
+
+    let mut patched = io::Cursor::new(vec![]);
+    apply_bps_patch(&source, &patch, &mut patched).unwrap();
+    assert!(patched.into_inner() == target);
+}
@@ -8,6 +8,8 @@ use std::{
 
 use serde::Serialize;
 
+use structs::ProgressStage;
+
 use crate::{patch_config::PatchConfig, patches};
 
 #[derive(Serialize)]
@@ -15,8 +17,18 @@ use crate::{patch_config::PatchConfig, patches};
 #[serde(rename_all = "camelCase")]
 enum CbMessage<'a> {
     Success,
-    Error { msg: &'a str },
-    Progress { percent: f64, msg: &'a str },
+    Error {
+        msg: &'a str,
+    },
+    Progress {
+        percent: f64,
+        msg: &'a str,
+    },
+    Stage {
+        stage: &'a str,
+        percent: f64,
+        msg: &'a str,
+    },
 }
 
 impl<'a> CbMessage<'a> {
@@ -36,6 +48,16 @@ impl<'a> CbMessage<'a> {
         CString::new(serde_json::to_string(&cbmsg).unwrap()).unwrap()
     }
 
+    fn stage_json(stage: &str, percent: f64, msg: &str) -> CString {
+        let msg = CbMessage::fix_msg(msg);
+        let cbmsg = CbMessage::Stage {
+            stage,
+            percent,
+            msg,
+        };
+        CString::new(serde_json::to_string(&cbmsg).unwrap()).unwrap()
+    }
+
     /// Remove all of the bytes after the first null byte
     fn fix_msg(msg: &str) -> &str {
         if let Some(pos) = msg.bytes().position(|i| i == b'\0') {
@@ -69,14 +91,13 @@ impl structs::ProgressNotifier for ProgressNotifier {
         self.total_size = total_size
     }
 
-    fn notify_writing_file(&mut self, file_name: &reader_writer::CStr, file_bytes: usize) {
+    fn notify_writing_file(&mut self, file_name: &reader_writer::CStr, _file_bytes: usize) {
         let percent = self.bytes_so_far as f64 / self.total_size as f64 * 100.;
         let msg = format!("Writing file {:?}", file_name);
         (self.cb)(
             self.cb_data,
-            CbMessage::progress_json(percent, &msg).as_ptr(),
+            CbMessage::progress_json(percent.min(100.), &msg).as_ptr(),
         );
-        self.bytes_so_far += file_bytes;
     }
 
     fn notify_writing_header(&mut self) {
@@ -93,6 +114,29 @@ impl structs::ProgressNotifier for ProgressNotifier {
             CbMessage::progress_json(100., "Flushing written data to the disk").as_ptr(),
         );
     }
+
+    fn notify_stage(&mut self, stage: ProgressStage) {
+        // The percentage is how far along the stage is, only patching has more than one step
+        let (stage, percent, msg) = match stage {
+            ProgressStage::Extract => ("extract", 0., "Reading input ISO".to_owned()),
+            ProgressStage::BuildAssets => ("buildAssets", 0., "Building custom assets".to_owned()),
+            ProgressStage::PatchPak { name, index, count } => (
+                "patchPak",
+                index as f64 / count as f64 * 100.,
+                format!("Patching {}", String::from_utf8_lossy(name)),
+            ),
+            ProgressStage::Compress => ("compress", 0., "Compressing output ISO".to_owned()),
+            ProgressStage::Write => ("write", 0., "Writing output ISO".to_owned()),
+        };
+        (self.cb)(
+            self.cb_data,
+            CbMessage::stage_json(stage, percent, &msg).as_ptr(),
+        );
+    }
+
+    fn notify_bytes_written(&mut self, bytes: usize) {
+        self.bytes_so_far += bytes;
+    }
 }
 
 fn inner(
@@ -4,6 +4,7 @@ use std::{
     iter,
 };
 
+use log::error;
 use reader_writer::byteorder::{LittleEndian, WriteBytesExt};
 
 use crate::gcz_writer::ZEROES;
@@ -111,7 +112,7 @@ impl<W: Write + Seek> Drop for CisoWriter<W> {
         }();
         // We really don't want to panic from a destructor, so just write a warning instead
         if let Err(e) = res {
-            eprintln!("Error closing GczWriter: {}", e);
+            error!("Error closing CisoWriter: {}", e);
         };
     }
 }
@@ -0,0 +1,312 @@
+// Conversion of CMDLs from later games in the series to the layout Metroid Prime expects.
+//
+// Echoes (version 4) CMDLs share almost everything with Prime (version 2). The differences are:
+//  - Each material has two extra u32s directly after the vertex attribute flags
+//  - Each surface header has an extra u32 directly after the reflection direction
+// Everything else (vertex data, display lists, TEV stages, UV animations) is read identically.
+//
+// Corruption (version 5) CMDLs use an entirely different material format and are rejected.
+
+const CMDL_MAGIC: u32 = 0xDEADBABE;
+const CMDL_VERSION_PRIME: u32 = 2;
+const CMDL_VERSION_ECHOES: u32 = 4;
+const CMDL_VERSION_CORRUPTION: u32 = 5;
+
+// Konst, Transparent, Masked, Reflection, Depth Write, Surface Reflection, Occluder,
+// Indirect Stage, Lightmap & Short UVs
+const PRIME_MATERIAL_FLAGS_MASK: u32 = 0x2FF8;
+
+const CMDL_HEADER_SIZE: usize = 0x2C;
+const SURFACE_HEADER_SIZE: usize = 0x2C;
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| format!("Unexpected end of CMDL data at offset 0x{:X}", offset))
+}
+
+fn write_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_be_bytes());
+}
+
+fn align_32(offset: usize) -> usize {
+    (offset + 31) & !31
+}
+
+fn pad_to_32(data: &mut Vec<u8>) {
+    data.resize(align_32(data.len()), 0);
+}
+
+pub fn cmdl_version(bytes: &[u8]) -> Result<u32, String> {
+    if read_u32(bytes, 0)? != CMDL_MAGIC {
+        return Err("Not a CMDL file (bad magic)".to_string());
+    }
+    read_u32(bytes, 4)
+}
+
+fn convert_echoes_material(material: &[u8]) -> Result<Vec<u8>, String> {
+    let flags = read_u32(material, 0)?;
+    let texture_count = read_u32(material, 4)? as usize;
+    let vertex_attribute_flags_offset = 8 + texture_count * 4;
+    let unknowns_offset = vertex_attribute_flags_offset + 4;
+    if material.len() < unknowns_offset + 8 {
+        return Err("Echoes material is truncated".to_string());
+    }
+
+    let mut converted = Vec::with_capacity(material.len() - 8);
+    write_u32(&mut converted, flags & PRIME_MATERIAL_FLAGS_MASK);
+    converted.extend_from_slice(&material[4..unknowns_offset]);
+    converted.extend_from_slice(&material[unknowns_offset + 8..]);
+
+    Ok(converted)
+}
+
+fn convert_echoes_material_set(section: &[u8]) -> Result<Vec<u8>, String> {
+    let texture_count = read_u32(section, 0)? as usize;
+    let material_count_offset = 4 + texture_count * 4;
+    let material_count = read_u32(section, material_count_offset)? as usize;
+    let end_offsets_offset = material_count_offset + 4;
+    let materials_offset = end_offsets_offset + material_count * 4;
+
+    let mut materials = Vec::with_capacity(material_count);
+    let mut start = 0;
+    for i in 0..material_count {
+        let end = read_u32(section, end_offsets_offset + i * 4)? as usize;
+        let material = section
+            .get(materials_offset + start..materials_offset + end)
+            .ok_or_else(|| format!("Material #{} is out of bounds", i))?;
+        materials.push(convert_echoes_material(material)?);
+        start = end;
+    }
+
+    let mut converted = Vec::with_capacity(section.len());
+    converted.extend_from_slice(&section[..end_offsets_offset]);
+    let mut end = 0;
+    for material in materials.iter() {
+        end += material.len();
+        write_u32(&mut converted, end as u32);
+    }
+    for material in materials.iter() {
+        converted.extend_from_slice(material);
+    }
+    pad_to_32(&mut converted);
+
+    Ok(converted)
+}
+
+fn convert_echoes_surface(section: &[u8]) -> Result<Vec<u8>, String> {
+    let extra_data_size = read_u32(section, 0x1C)? as usize;
+    let extra_data_offset = SURFACE_HEADER_SIZE + 4;
+    let primitives_offset = align_32(extra_data_offset + extra_data_size);
+    if section.len() < primitives_offset {
+        return Err("Echoes surface is truncated".to_string());
+    }
+
+    let mut converted = Vec::with_capacity(section.len());
+    converted.extend_from_slice(&section[..SURFACE_HEADER_SIZE]);
+    converted.extend_from_slice(&section[extra_data_offset..extra_data_offset + extra_data_size]);
+    pad_to_32(&mut converted);
+    converted.extend_from_slice(&section[primitives_offset..]);
+    pad_to_32(&mut converted);
+
+    Ok(converted)
+}
+
+fn convert_echoes_cmdl(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let flags = read_u32(bytes, 8)?;
+    let data_section_count = read_u32(bytes, 0x24)? as usize;
+    let material_set_count = read_u32(bytes, 0x28)? as usize;
+
+    let mut section_sizes = Vec::with_capacity(data_section_count);
+    for i in 0..data_section_count {
+        section_sizes.push(read_u32(bytes, CMDL_HEADER_SIZE + i * 4)? as usize);
+    }
+
+    let mut sections = Vec::with_capacity(data_section_count);
+    let mut offset = align_32(CMDL_HEADER_SIZE + data_section_count * 4);
+    for (i, size) in section_sizes.iter().enumerate() {
+        let section = bytes
+            .get(offset..offset + size)
+            .ok_or_else(|| format!("CMDL data section #{} is out of bounds", i))?;
+        sections.push(section);
+        offset += size;
+    }
+
+    // Material sets, then positions, normals, colors, float UVs, (short UVs), surface offsets,
+    // and finally one section per surface
+    let short_uvs = (flags & 0x4) != 0;
+    let surface_offsets_index = material_set_count + if short_uvs { 5 } else { 4 };
+    if surface_offsets_index >= data_section_count {
+        return Err("CMDL is missing geometry sections".to_string());
+    }
+
+    let mut converted_sections: Vec<Vec<u8>> = Vec::with_capacity(data_section_count);
+    for (i, section) in sections.iter().enumerate() {
+        if i < material_set_count {
+            converted_sections.push(convert_echoes_material_set(section)?);
+        } else if i > surface_offsets_index {
+            converted_sections.push(convert_echoes_surface(section)?);
+        } else {
+            converted_sections.push(section.to_vec());
+        }
+    }
+
+    // Surfaces may have changed size, so the end offsets need to be rebuilt
+    let mut surface_offsets = Vec::new();
+    let surface_count = data_section_count - surface_offsets_index - 1;
+    write_u32(&mut surface_offsets, surface_count as u32);
+    let mut end = 0;
+    for section in converted_sections.iter().skip(surface_offsets_index + 1) {
+        end += section.len();
+        write_u32(&mut surface_offsets, end as u32);
+    }
+    pad_to_32(&mut surface_offsets);
+    converted_sections[surface_offsets_index] = surface_offsets;
+
+    let mut converted = Vec::with_capacity(bytes.len());
+    converted.extend_from_slice(&bytes[..4]);
+    write_u32(&mut converted, CMDL_VERSION_PRIME);
+    converted.extend_from_slice(&bytes[8..CMDL_HEADER_SIZE]);
+    for section in converted_sections.iter() {
+        write_u32(&mut converted, section.len() as u32);
+    }
+    pad_to_32(&mut converted);
+    for section in converted_sections.iter() {
+        converted.extend_from_slice(section);
+    }
+
+    Ok(converted)
+}
+
+pub fn convert_cmdl_to_prime(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    match cmdl_version(bytes)? {
+        CMDL_VERSION_PRIME => Ok(bytes.to_vec()),
+        CMDL_VERSION_ECHOES => convert_echoes_cmdl(bytes),
+        CMDL_VERSION_CORRUPTION => Err(
+            "Metroid Prime 3 CMDLs use an incompatible material format and cannot be converted"
+                .to_string(),
+        ),
+        version => Err(format!("Unsupported CMDL version {}", version)),
+    }
+}
+
+#[cfg(test)]
+fn test_material(echoes: bool, flags: u32) -> Vec<u8> {
+    let mut material = vec![];
+    write_u32(&mut material, flags);
+    write_u32(&mut material, 1); // Texture count
+    write_u32(&mut material, 0); // Texture index
+    write_u32(&mut material, 0x3); // Vertex attribute flags
+    if echoes {
+        write_u32(&mut material, 0x11111111);
+        write_u32(&mut material, 0x22222222);
+    }
+    material.extend_from_slice(&[0xAB; 12]); // TEV stages
+    material
+}
+
+#[cfg(test)]
+fn test_material_set(materials: &[Vec<u8>]) -> Vec<u8> {
+    let mut section = vec![];
+    write_u32(&mut section, 1); // Texture count
+    write_u32(&mut section, 0x12345678); // Texture id
+    write_u32(&mut section, materials.len() as u32);
+    let mut end = 0;
+    for material in materials {
+        end += material.len();
+        write_u32(&mut section, end as u32);
+    }
+    for material in materials {
+        section.extend_from_slice(material);
+    }
+    pad_to_32(&mut section);
+    section
+}
+
+#[cfg(test)]
+fn test_surface(echoes: bool) -> Vec<u8> {
+    let mut surface = vec![0x5A; SURFACE_HEADER_SIZE];
+    surface[0x1C..0x20].copy_from_slice(&4u32.to_be_bytes()); // Extra data size
+    if echoes {
+        write_u32(&mut surface, 0x33333333);
+    }
+    write_u32(&mut surface, 0x44444444); // Extra data
+    pad_to_32(&mut surface);
+    surface.extend_from_slice(&[0x98, 0, 1, 0, 0, 0, 0, 0]); // Primitives
+    pad_to_32(&mut surface);
+    surface
+}
+
+#[cfg(test)]
+fn test_cmdl(version: u32, material_sets: &[Vec<u8>], surfaces: &[Vec<u8>]) -> Vec<u8> {
+    let mut sections = material_sets.to_vec();
+    // Positions, normals, colors and float UVs
+    for _ in 0..4 {
+        sections.push(vec![0x77; 32]);
+    }
+    let mut surface_offsets = vec![];
+    write_u32(&mut surface_offsets, surfaces.len() as u32);
+    let mut end = 0;
+    for surface in surfaces {
+        end += surface.len();
+        write_u32(&mut surface_offsets, end as u32);
+    }
+    pad_to_32(&mut surface_offsets);
+    sections.push(surface_offsets);
+    sections.extend_from_slice(surfaces);
+
+    let mut cmdl = vec![];
+    write_u32(&mut cmdl, CMDL_MAGIC);
+    write_u32(&mut cmdl, version);
+    write_u32(&mut cmdl, 0); // Flags
+    cmdl.extend_from_slice(&[0x3F; 24]); // Bounding box
+    write_u32(&mut cmdl, sections.len() as u32);
+    write_u32(&mut cmdl, material_sets.len() as u32);
+    for section in sections.iter() {
+        write_u32(&mut cmdl, section.len() as u32);
+    }
+    pad_to_32(&mut cmdl);
+    for section in sections.iter() {
+        cmdl.extend_from_slice(section);
+    }
+    cmdl
+}
+
+#[test]
+fn test_convert_echoes_cmdl() {
+    let echoes = test_cmdl(
+        CMDL_VERSION_ECHOES,
+        &[test_material_set(&[
+            test_material(true, 0xFFFFFFFF),
+            test_material(true, 0x0008),
+        ])],
+        &[test_surface(true), test_surface(true)],
+    );
+    let prime = test_cmdl(
+        CMDL_VERSION_PRIME,
+        &[test_material_set(&[
+            test_material(false, PRIME_MATERIAL_FLAGS_MASK),
+            test_material(false, 0x0008),
+        ])],
+        &[test_surface(false), test_surface(false)],
+    );
+
+    assert_eq!(convert_cmdl_to_prime(&echoes).unwrap(), prime);
+    // Prime CMDLs are left as they are
+    assert_eq!(convert_cmdl_to_prime(&prime).unwrap(), prime);
+}
+
+#[test]
+fn test_convert_cmdl_errors() {
+    let echoes = test_cmdl(
+        CMDL_VERSION_ECHOES,
+        &[test_material_set(&[test_material(true, 0)])],
+        &[test_surface(true)],
+    );
+    assert!(convert_cmdl_to_prime(&echoes[..echoes.len() - 32]).is_err());
+    assert!(convert_cmdl_to_prime(&echoes[4..]).is_err());
+
+    let mut corruption = echoes;
+    corruption[4..8].copy_from_slice(&CMDL_VERSION_CORRUPTION.to_be_bytes());
+    assert!(convert_cmdl_to_prime(&corruption).is_err());
+}
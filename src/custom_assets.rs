@@ -1,6 +1,8 @@
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
+    iter,
+    path::Path,
 };
 
 use reader_writer::{FourCC, Reader, Writable};
@@ -8,12 +10,23 @@ use resource_info_table::resource_info;
 use structs::{res_id, ResId, Resource, ResourceKind};
 
 use crate::{
-    door_meta::{BlastShieldType, DoorType},
+    add_modify_obj_patches::{station_template, station_template_objects},
+    asset_cache::AssetCache,
+    door_meta::{colorblind_txtr_hue_degs, BlastShieldType, DoorType},
     elevators::{SpawnRoomData, World},
-    extern_assets::ExternPickupModel,
-    patch_config::{GenericTexture, PatchConfig, Version},
-    patches::WaterType,
+    extern_assets::{referenced_assets, ExternPickupModel},
+    particle_elements::particle_elements,
+    patch_config::{
+        parse_texture_id, ColorblindMode, ElevatorHologramCosmetics, GenericTexture,
+        LogbookCategory, PatchConfig, StationType, Version,
+    },
+    patches::{
+        patch_artifact_totem_scan_strg, ArtifactTotemReveal, WaterType, ARTIFACT_TOTEM_SCANS,
+        ARTIFACT_TOTEM_SCAN_STRGS,
+    },
     pickup_meta::{self, PickupModel, PickupType},
+    transliteration::{Transliterator, TEXT_FONT},
+    txtr_conversions::{huerotate_matrix, huerotate_txtr, remap_txtr, replace_txtr},
     GcDiscLookupExtensions, ResourceData,
 };
 
@@ -313,7 +326,8 @@ pub mod custom_asset_ids {
         XRAY_VISOR_BLAST_SHIELD_SCAN: SCAN,
         XRAY_VISOR_BLAST_SHIELD_STRG: STRG,
 
-        // Pickup dot icon
+        // Pickup dot icon, replaced by MAP_PICKUP_ICON_MAJOR_TXTR. Kept so that the ids after it
+        // don't move.
         MAP_PICKUP_ICON_TXTR: TXTR,
 
         // Ice trap assets
@@ -324,7 +338,46 @@ pub mod custom_asset_ids {
         DEFAULT_PICKUP_SCANS: SCAN = DEFAULT_PICKUP_SCAN_STRGS.to_u32() + 50,
         DEFAULT_PICKUP_HUDMEMO_STRGS: STRG = DEFAULT_PICKUP_SCANS.to_u32() + 50,
 
-        EXTRA_IDS_START: STRG = DEFAULT_PICKUP_HUDMEMO_STRGS.to_u32() + 50,
+        // Recolored copies of the beam particles and their textures
+        BEAM_COSMETIC_ASSETS: PART = DEFAULT_PICKUP_HUDMEMO_STRGS.to_u32() + 50,
+
+        // Copies of the elevator hologram and its textures for each destination world, for
+        // `cosmetics.elevatorHolograms`
+        ELEVATOR_HOLOGRAM_CMDLS: CMDL = BEAM_COSMETIC_ASSETS.to_u32() + 2000,
+        ELEVATOR_HOLOGRAM_TXTRS: TXTR = ELEVATOR_HOLOGRAM_CMDLS.to_u32() + 8,
+
+        // Pickup dot icons, in the order of `PickupIcon`'s variants, then their crossed out
+        // versions for `mapCollectedPickupIcons`
+        MAP_PICKUP_ICON_MAJOR_TXTR: TXTR = ELEVATOR_HOLOGRAM_TXTRS.to_u32() + 64,
+        MAP_PICKUP_ICON_EXPANSION_TXTR: TXTR,
+        MAP_PICKUP_ICON_ARTIFACT_TXTR: TXTR,
+        MAP_PICKUP_ICON_NOTHING_TXTR: TXTR,
+        MAP_PICKUP_ICON_COLLECTED_TXTR: TXTR,
+        MAP_PICKUP_ICON_EXPANSION_COLLECTED_TXTR: TXTR,
+        MAP_PICKUP_ICON_ARTIFACT_COLLECTED_TXTR: TXTR,
+        MAP_PICKUP_ICON_NOTHING_COLLECTED_TXTR: TXTR,
+
+        // Recolored copies of an artifact and its textures for each key, for `key` pickups
+        KEY_CMDLS: CMDL = MAP_PICKUP_ICON_NOTHING_COLLECTED_TXTR.to_u32() + 1,
+        KEY_ANCS: ANCS = KEY_CMDLS.to_u32() + 16,
+        KEY_TXTRS: TXTR = KEY_ANCS.to_u32() + 16,
+
+        // Logbook scan listing the settings of the seed, for `seedInfo`
+        SEED_INFO_SCAN: SCAN = KEY_TXTRS.to_u32() + 128,
+        SEED_INFO_STRG: STRG,
+
+        // Translucent box showing the extent of a volume, for `visualizeVolumes`
+        VOLUME_BOX_CMDL: CMDL,
+
+        // Exact hints of the Artifact Temple totems, for `artifactTotemHints.progressive`
+        ARTIFACT_TOTEM_HINT_STRGS: STRG = VOLUME_BOX_CMDL.to_u32() + 1,
+        ARTIFACT_TOTEM_HINT_SCANS: SCAN = ARTIFACT_TOTEM_HINT_STRGS.to_u32() + 12,
+
+        EXTRA_IDS_START: STRG = ARTIFACT_TOTEM_HINT_SCANS.to_u32() + 12,
+
+        // The frames of the save icon after TXTR_SaveIcon1, past the ids handed out from
+        // EXTRA_IDS_START
+        SAVE_ICON_TXTRS: TXTR = EXTRA_IDS_START.to_u32() + 0x10000,
     }
 }
 
@@ -368,8 +421,12 @@ fn extern_assets_runtime<'r>(
     let (extern_models, extern_assets) =
         ExternPickupModel::parse(&extern_assets_dir.clone().unwrap())?;
 
+    // Sorted so that the resources are always added to the paks in the same order
+    let mut extern_assets: Vec<_> = extern_assets.iter().collect();
+    extern_assets.sort_by_key(|(id, _)| **id);
+
     let mut resources = Vec::<Resource<'r>>::new();
-    for (id, asset) in extern_assets.iter() {
+    for (id, asset) in extern_assets {
         let resource = ResourceKind::External(asset.bytes.clone(), asset.fourcc);
         resources.push(build_resource_raw(*id, resource));
     }
@@ -391,7 +448,29 @@ macro_rules! extern_asset {
 fn extern_assets_compile_time<'r>() -> Vec<Resource<'r>> {
     let extern_assets: &[(ResId<_>, [u8; 4], &[u8])] = &[
         /* Map Assets */
-        extern_asset!(MAP_PICKUP_ICON_TXTR, "map_pickupdot.txtr"),
+        extern_asset!(MAP_PICKUP_ICON_MAJOR_TXTR, "map_pickupdot.txtr"),
+        extern_asset!(
+            MAP_PICKUP_ICON_EXPANSION_TXTR,
+            "map_pickupdot_expansion.txtr"
+        ),
+        extern_asset!(MAP_PICKUP_ICON_ARTIFACT_TXTR, "map_pickupdot_artifact.txtr"),
+        extern_asset!(MAP_PICKUP_ICON_NOTHING_TXTR, "map_pickupdot_nothing.txtr"),
+        extern_asset!(
+            MAP_PICKUP_ICON_COLLECTED_TXTR,
+            "map_pickupdot_collected.txtr"
+        ),
+        extern_asset!(
+            MAP_PICKUP_ICON_EXPANSION_COLLECTED_TXTR,
+            "map_pickupdot_expansion_collected.txtr"
+        ),
+        extern_asset!(
+            MAP_PICKUP_ICON_ARTIFACT_COLLECTED_TXTR,
+            "map_pickupdot_artifact_collected.txtr"
+        ),
+        extern_asset!(
+            MAP_PICKUP_ICON_NOTHING_COLLECTED_TXTR,
+            "map_pickupdot_nothing_collected.txtr"
+        ),
         /* Pickup Assets */
         extern_asset!(NOTHING_TXTR, "nothing_texture.txtr"),
         extern_asset!(PHAZON_SUIT_TXTR1, "phazon_suit_texure_1.txtr"),
@@ -589,6 +668,7 @@ fn extern_assets_compile_time<'r>() -> Vec<Resource<'r>> {
 pub fn custom_assets<'r>(
     resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
     starting_memo: Option<&str>,
+    artifact_totem_reveals: &[ArtifactTotemReveal],
     pickup_hudmemos: &mut HashMap<PickupHashKey, ResId<res_id::STRG>>,
     pickup_scans: &mut HashMap<PickupHashKey, (ResId<res_id::SCAN>, ResId<res_id::STRG>)>,
     extra_scans: &mut HashMap<PickupHashKey, (ResId<res_id::SCAN>, ResId<res_id::STRG>)>,
@@ -633,6 +713,9 @@ pub fn custom_assets<'r>(
     /* Mapping of SCAN id to logbook category for easier SAVW entry creation */
     let mut savw_scan_logbook_category: HashMap<u32, u32> = HashMap::new();
 
+    // Replaces characters the game font can't display in user-provided text
+    let transliterator = Transliterator::new(resources.get(&TEXT_FONT), config.quiet);
+
     // External assets
     let mut assets = extern_assets_compile_time();
     let extern_models = if config.extern_assets_dir.is_some() {
@@ -727,16 +810,70 @@ pub fn custom_assets<'r>(
     local_savw_scans_to_add[World::TallonOverworld as usize]
         .push(custom_asset_ids::TOURNEY_WINNERS_SCAN);
 
-    if starting_memo.is_some() {
+    if let Some(seed_info) = config.seed_info.as_ref() {
+        let category = seed_info.logbook_category.unwrap_or(4); // Research
+        let lines: Vec<String> = seed_info_lines(config)
+            .iter()
+            .map(|line| transliterator.transliterate(line))
+            .collect();
+        assets.extend_from_slice(&create_item_scan_strg_pair_2(
+            custom_asset_ids::SEED_INFO_SCAN,
+            custom_asset_ids::SEED_INFO_STRG,
+            seed_info_pages(&lines),
+            0,
+            category,
+            config.version,
+        ));
+        global_savw_scans_to_add.push(custom_asset_ids::SEED_INFO_SCAN);
+        savw_scan_logbook_category.insert(custom_asset_ids::SEED_INFO_SCAN.to_u32(), category);
+    }
+
+    // The loadout goes after the starting memo, the message box pages through whatever doesn't fit
+    let mut starting_memo_parts: Vec<String> =
+        starting_memo.iter().map(|s| s.to_string()).collect();
+    if config.loadout_memo {
+        starting_memo_parts.push(loadout_memo_lines(config).join("\n"));
+    }
+    if !starting_memo_parts.is_empty() {
         assets.push(build_resource(
             custom_asset_ids::STARTING_ITEMS_HUDMEMO_STRG,
             structs::ResourceKind::Strg(structs::Strg::from_strings(vec![format!(
                 "&just=center;{}\0",
-                starting_memo.unwrap()
+                transliterator.transliterate(&starting_memo_parts.join("\n\n"))
             )])),
         ));
     }
 
+    // Copies of the totems' scans with their exact hints, swapped in by the layer switches of
+    // the artifacts which reveal them
+    for reveal in artifact_totem_reveals {
+        let scan_id = ResId::<res_id::SCAN>::new(
+            custom_asset_ids::ARTIFACT_TOTEM_HINT_SCANS.to_u32() + reveal.totem as u32,
+        );
+        let strg_id = ResId::<res_id::STRG>::new(
+            custom_asset_ids::ARTIFACT_TOTEM_HINT_STRGS.to_u32() + reveal.totem as u32,
+        );
+
+        let mut scan = resources[&ARTIFACT_TOTEM_SCANS[reveal.totem].into()]
+            .kind
+            .as_scan()
+            .unwrap()
+            .into_owned();
+        scan.strg = strg_id;
+        assets.push(build_resource(scan_id, ResourceKind::Scan(scan)));
+
+        let strg = resources[&ARTIFACT_TOTEM_SCAN_STRGS[reveal.totem].into()]
+            .kind
+            .as_strg()
+            .unwrap()
+            .into_owned();
+        let mut strg = build_resource(strg_id, ResourceKind::Strg(strg));
+        patch_artifact_totem_scan_strg(&mut strg, &reveal.text, config.version)?;
+        assets.push(strg);
+
+        local_savw_scans_to_add[World::TallonOverworld as usize].push(scan_id);
+    }
+
     // Create fallback/default scan/scan-text/hudmemo assets //
     for pt in PickupType::iter() {
         let name: &str = pt.name();
@@ -759,17 +896,46 @@ pub fn custom_assets<'r>(
         ));
     }
 
+    // Number each pickup among the pickups of the same type, in game order, for {count} //
+    let mut pickup_counts: HashMap<(&str, &str, u32), u32> = HashMap::new();
+    let mut type_counts: HashMap<PickupType, u32> = HashMap::new();
+    for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
+        let world = World::from_pak(pak_name).unwrap();
+        let (level_name, level) = match config.level_data.get_key_value(world.to_json_key()) {
+            Some(level) => level,
+            None => continue,
+        };
+        for room_info in rooms.iter() {
+            let (room_name, room) = match level.rooms.get_key_value(room_info.name().trim()) {
+                Some(room) => room,
+                None => continue,
+            };
+            for (idx, pickup) in room.pickups.iter().flatten().enumerate() {
+                let count = type_counts
+                    .entry(PickupType::from_str(&pickup.pickup_type))
+                    .or_insert(0);
+                *count += 1;
+                pickup_counts.insert((level_name, room_name, idx as u32), *count);
+            }
+        }
+    }
+
     // Create user-defined hudmemo and scan strings and map to locations //
+    // Sorted so that the same config always gets the same asset IDs
     let mut custom_asset_offset = 0;
-    for (level_name, level) in config.level_data.iter() {
+    let mut levels: Vec<_> = config.level_data.iter().collect();
+    levels.sort_by(|a, b| a.0.cmp(b.0));
+    for (level_name, level) in levels {
         let world = World::from_json_key(level_name);
-        for (room_name, room) in level.rooms.iter() {
+        let mut rooms: Vec<_> = level.rooms.iter().collect();
+        rooms.sort_by(|a, b| a.0.cmp(b.0));
+        for (room_name, room) in rooms {
             let mut pickup_idx = 0;
             let mut extra_scans_idx = 0;
 
             if room.extra_scans.is_some() {
                 for custom_scan in room.extra_scans.as_ref().unwrap().iter() {
-                    let contents = &custom_scan.text;
+                    let contents = &transliterator.transliterate(&custom_scan.text);
 
                     // Check if this string already has a scan_id //
                     if string_to_scan_strg.contains_key(contents) {
@@ -882,13 +1048,26 @@ pub fn custom_assets<'r>(
             }
 
             if room.doors.is_some() {
-                for (_, door) in room.doors.as_ref().unwrap().iter() {
-                    if door.destination.is_none() {
-                        continue;
+                // A door's scan gate text comes before its lock scan and its destination
+                let mut door_strings = Vec::new();
+                let mut doors: Vec<_> = room.doors.as_ref().unwrap().iter().collect();
+                doors.sort_by_key(|(dock_num, _)| **dock_num);
+                for (_, door) in doors {
+                    if let Some(scan_text) = door.scan_text.as_ref() {
+                        door_strings.push(format!("{}\0", transliterator.transliterate(scan_text)));
                     }
+                    if let Some(key) = door.key.as_ref() {
+                        door_strings.push(format!(
+                            "Locked. The {} is needed to open this door.\0",
+                            transliterator.transliterate(&key_model_name(key))
+                        ));
+                    }
+                    if let Some(destination) = door.destination.as_ref() {
+                        door_strings.push(destination.room_name.clone() + "\0");
+                    }
+                }
 
-                    let string = door.destination.as_ref().unwrap().room_name.clone() + "\0";
-
+                for string in door_strings {
                     // Check if this string already has a scan_id //
                     if string_to_scan_strg.contains_key(&string.clone()) {
                         let (scan_id, strg_id) = string_to_scan_strg.get(&string.clone()).unwrap();
@@ -943,7 +1122,10 @@ pub fn custom_assets<'r>(
                         continue;
                     }
 
-                    let string = format!("{}\0", hudmemo_config.text.as_ref().unwrap());
+                    let string = format!(
+                        "{}\0",
+                        transliterator.transliterate(hudmemo_config.text.as_ref().unwrap())
+                    );
 
                     // todo: subroutine
 
@@ -999,8 +1181,17 @@ pub fn custom_assets<'r>(
             };
             for pickup in room.pickups.as_ref().unwrap().iter() {
                 // custom hudmemo string
-                if pickup.hudmemo_text.is_some() {
-                    let hudmemo_text = pickup.hudmemo_text.as_ref().unwrap();
+                let count = *pickup_counts
+                    .get(&(level_name, room_name, pickup_idx))
+                    .unwrap_or(&1);
+
+                if let Some(hudmemo_text) = &pickup.hudmemo_text {
+                    let hudmemo_text = &transliterator.transliterate(&expand_pickup_text(
+                        hudmemo_text,
+                        &pickup.pickup_type,
+                        room_name,
+                        count,
+                    ));
 
                     // Get next ID //
                     let strg_id = ResId::<res_id::STRG>::new(
@@ -1009,15 +1200,41 @@ pub fn custom_assets<'r>(
                     custom_asset_offset += 1;
 
                     // Build resource //
-                    let strg = structs::ResourceKind::Strg(structs::Strg {
-                        string_tables: vec![structs::StrgStringTable {
-                            lang: b"ENGL".into(),
-                            strings: vec![format!("&just=center;{}\u{0}", hudmemo_text).into()]
+                    let mut resource = match &pickup.hudmemo_text_translations {
+                        None => build_resource(
+                            strg_id,
+                            structs::ResourceKind::Strg(structs::Strg {
+                                string_tables: vec![structs::StrgStringTable {
+                                    lang: b"ENGL".into(),
+                                    strings: vec![
+                                        format!("&just=center;{}\u{0}", hudmemo_text).into()
+                                    ]
+                                    .into(),
+                                }]
                                 .into(),
-                        }]
-                        .into(),
-                    });
-                    let resource = build_resource(strg_id, strg);
+                            }),
+                        ),
+                        Some(_) => build_resource(
+                            strg_id,
+                            localized_strg(
+                                vec![format!("&just=center;{}\u{0}", hudmemo_text)],
+                                config.version,
+                            ),
+                        ),
+                    };
+                    if let Some(translations) = &pickup.hudmemo_text_translations {
+                        translate_strg(
+                            &mut resource,
+                            translations,
+                            config.version,
+                            &transliterator,
+                            |text| {
+                                let text =
+                                    expand_pickup_text(text, &pickup.pickup_type, room_name, count);
+                                format!("&just=center;{}\u{0}", text)
+                            },
+                        )?;
+                    }
                     assets.push(resource);
 
                     // Map for easy lookup when patching //
@@ -1026,11 +1243,20 @@ pub fn custom_assets<'r>(
                 }
 
                 // Custom scan string
-                if pickup.scan_text.is_some() {
-                    let scan_text = pickup.scan_text.as_ref().unwrap();
+                if let Some(scan_text) = &pickup.scan_text {
+                    let scan_text = &transliterator.transliterate(&expand_pickup_text(
+                        scan_text,
+                        &pickup.pickup_type,
+                        room_name,
+                        count,
+                    ));
+
+                    // Translated scans are never shared since the English text alone doesn't
+                    // identify them
+                    let cacheable = pickup.scan_text_translations.is_none();
 
                     // Check if this string already has a scan_id //
-                    if string_to_scan_strg.contains_key(scan_text) {
+                    if cacheable && string_to_scan_strg.contains_key(scan_text) {
                         let (scan_id, strg_id) = string_to_scan_strg.get(scan_text).unwrap();
 
                         // Add this scan_id as a dep of this world if it wasn't already //
@@ -1053,25 +1279,44 @@ pub fn custom_assets<'r>(
                         custom_asset_offset += 1;
 
                         // Build resource //
-                        if room_name.trim().to_lowercase() == "research core"
+                        let mut scan_strg_pair = if room_name.trim().to_lowercase()
+                            == "research core"
                         // make the research core scan red because it goes on the terminal
                         {
-                            assets.extend_from_slice(&create_item_scan_strg_pair_2(
+                            create_item_scan_strg_pair_2(
                                 scan_id,
                                 strg_id,
                                 vec![format!("{}\0", scan_text)],
                                 1,
                                 0,
                                 config.version,
-                            ));
+                            )
                         } else {
-                            assets.extend_from_slice(&create_item_scan_strg_pair(
+                            create_item_scan_strg_pair(
                                 scan_id,
                                 strg_id,
                                 format!("{}\0", scan_text),
                                 config.version,
-                            ));
+                            )
+                        };
+                        if let Some(translations) = &pickup.scan_text_translations {
+                            translate_strg(
+                                &mut scan_strg_pair[1],
+                                translations,
+                                config.version,
+                                &transliterator,
+                                |text| {
+                                    let text = expand_pickup_text(
+                                        text,
+                                        &pickup.pickup_type,
+                                        room_name,
+                                        count,
+                                    );
+                                    format!("{}\0", text)
+                                },
+                            )?;
                         }
+                        assets.extend_from_slice(&scan_strg_pair);
 
                         // Map for easy lookup when patching //
                         let key = PickupHashKey::from_location(level_name, room_name, pickup_idx);
@@ -1079,7 +1324,9 @@ pub fn custom_assets<'r>(
                         local_savw_scans_to_add[world as usize].push(scan_id);
 
                         // Cache this scan/strg pair for re-use //
-                        string_to_scan_strg.insert(scan_text.to_string(), (scan_id, strg_id));
+                        if cacheable {
+                            string_to_scan_strg.insert(scan_text.to_string(), (scan_id, strg_id));
+                        }
                     }
                 }
 
@@ -1128,6 +1375,7 @@ pub fn custom_assets<'r>(
     for gt in GenericTexture::iter() {
         assets.push(create_custom_block_cmdl(resources, gt.txtr(), gt.cmdl()));
     }
+    assets.push(create_volume_box_cmdl(resources));
 
     // Custom door assets
     for door_type in DoorType::iter() {
@@ -1230,6 +1478,7 @@ pub fn custom_assets<'r>(
 pub fn collect_game_resources<'r>(
     gc_disc: &structs::GcDisc<'r>,
     starting_memo: Option<&str>,
+    artifact_totem_reveals: &[ArtifactTotemReveal],
     config: &PatchConfig,
 ) -> Result<
     (
@@ -1348,6 +1597,34 @@ pub fn collect_game_resources<'r>(
     ];
     looking_for.extend(custom_scan_point_deps);
 
+    looking_for.insert(TEXT_FONT);
+
+    if !artifact_totem_reveals.is_empty() {
+        looking_for.extend(
+            ARTIFACT_TOTEM_SCANS
+                .iter()
+                .chain(ARTIFACT_TOTEM_SCAN_STRGS)
+                .map(|res_info| (*res_info).into()),
+        );
+    }
+
+    let elevator_holograms = config
+        .cosmetics
+        .elevator_holograms
+        .clone()
+        .unwrap_or_default();
+    // Teleporter pads get an elevator hologram as well
+    let teleporter_pads = config
+        .level_data
+        .values()
+        .flat_map(|level| level.rooms.values())
+        .flat_map(|room| room.teleporters.iter().flatten())
+        .any(|teleporter| teleporter.pad.unwrap_or(true));
+    let needs_holobeam = !elevator_holograms.is_empty() || teleporter_pads;
+    if needs_holobeam {
+        looking_for.insert(resource_info!("holobeam.CMDL").into());
+    }
+
     if config.enable_ice_traps {
         let player_freeze_deps: Vec<(u32, FourCC)> = vec![
             resource_info!("breakFreezeVisor.PART").into(),
@@ -1359,6 +1636,23 @@ pub fn collect_game_resources<'r>(
         looking_for.extend(player_freeze_deps);
     }
 
+    let mut station_types: Vec<StationType> = vec![];
+    for station in config
+        .level_data
+        .values()
+        .flat_map(|level| level.rooms.values())
+        .flat_map(|room| room.stations.iter().flatten())
+    {
+        if !station_types.contains(&station.station_type) {
+            station_types.push(station.station_type);
+        }
+    }
+    looking_for.extend(
+        station_types
+            .iter()
+            .map(|station_type| station_template(*station_type).into()),
+    );
+
     // Dependencies read from paks and custom assets will go here //
     let mut found = HashMap::with_capacity(looking_for.len());
 
@@ -1371,22 +1665,49 @@ pub fn collect_game_resources<'r>(
     paks.push("NoARAM.pak");
     paks.push("MiscData.pak");
     paks.push("TestAnim.Pak");
-    for pak_name in paks {
-        let file_entry = gc_disc.find_file(pak_name).unwrap();
-        let pak = match *file_entry.file().unwrap() {
-            structs::FstEntryFile::Pak(ref pak) => Cow::Borrowed(pak),
-            structs::FstEntryFile::Unknown(ref reader) => Cow::Owned(reader.clone().read(())),
-            _ => panic!(),
-        };
+    find_resources(gc_disc, &paks, &mut looking_for, &mut found);
+
+    // The textures of the elevator hologram are only known once its model has been read //
+    if needs_holobeam {
+        let holobeam = ResourceData::new(&found[&resource_info!("holobeam.CMDL").into()]);
+        let textures = referenced_assets(FourCC::from_bytes(b"CMDL"), &holobeam.decompress());
+        for txtr in textures {
+            if !found.contains_key(&txtr) {
+                looking_for.insert(txtr);
+            }
+        }
+        find_resources(
+            gc_disc,
+            &[World::TallonOverworld.to_pak_str()],
+            &mut looking_for,
+            &mut found,
+        );
+    }
 
-        // Iterate through all resources in level Pak //
-        for res in pak.resources.iter() {
-            // If this resource is a dependency needed by the patcher, add the resource to the output list //
-            let key = (res.file_id, res.fourcc());
-            if looking_for.remove(&key) {
-                found.insert(key, res.into_owned());
+    // The stations are copies of the objects of vanilla rooms, which need the assets those
+    // reference down to the textures of their particles, wherever they are
+    if !station_types.is_empty() {
+        let mut asset_ids = vec![];
+        for station_type in station_types.iter() {
+            for obj in station_template_objects(&found, *station_type)? {
+                asset_ids.extend(
+                    obj.property_data
+                        .get_asset_ids()
+                        .into_iter()
+                        .map(|(id, _)| id),
+                );
             }
         }
+        let mut searched = HashSet::new();
+        loop {
+            let (_, missing) = asset_dependencies(&found, asset_ids.iter().copied())?;
+            let mut missing: HashSet<u32> = missing.difference(&searched).copied().collect();
+            if missing.is_empty() {
+                break;
+            }
+            searched.extend(missing.iter().copied());
+            find_resources_by_id(gc_disc, &paks, &mut missing, &mut found);
+        }
     }
 
     // Maps pickup location to STRG to use
@@ -1404,10 +1725,11 @@ pub fn collect_game_resources<'r>(
         global_savw_scans_to_add,
         local_savw_scans_to_add,
         savw_scan_logbook_category,
-        extern_models,
+        mut extern_models,
     ) = custom_assets(
         &found,
         starting_memo,
+        artifact_totem_reveals,
         &mut pickup_hudmemos,
         &mut pickup_scans,
         &mut extra_scans,
@@ -1423,6 +1745,47 @@ pub fn collect_game_resources<'r>(
         panic!("error - still looking for {:?}", looking_for);
     }
 
+    // Recolor the door/blast shield textures up front so that every room they get copied into //
+    // ends up consistent with the patched vanilla doors                                          //
+    if config.colorblind_mode != ColorblindMode::None {
+        let asset_cache = AssetCache::new(&config.cache_dir);
+        for (txtr_id, angle) in colorblind_txtr_hue_degs(config.colorblind_mode) {
+            if let Some(res) = found.get_mut(&(txtr_id, FourCC::from_bytes(b"TXTR"))) {
+                huerotate_txtr(res, huerotate_matrix(angle as f32), &asset_cache)?;
+            }
+        }
+    }
+
+    if !elevator_holograms.is_empty() {
+        let asset_cache = AssetCache::new(&config.cache_dir);
+        let holograms = create_elevator_hologram_assets(&found, &elevator_holograms, &asset_cache)?;
+        for res in holograms {
+            found.insert((res.file_id, res.fourcc()), res);
+        }
+    }
+
+    // Numbered in name order so that the same config always gets the same asset IDs
+    let keys: BTreeSet<&String> = config
+        .level_data
+        .values()
+        .flat_map(|level| level.rooms.values())
+        .flat_map(|room| room.pickups.iter().flatten())
+        .filter_map(|pickup| pickup.key.as_ref())
+        .collect();
+    if !keys.is_empty() {
+        let asset_cache = AssetCache::new(&config.cache_dir);
+        for (index, key) in keys.into_iter().enumerate() {
+            let (key_assets, key_model) =
+                create_key_model_assets(&found, index, key, &asset_cache)?;
+            for res in key_assets {
+                found.insert((res.file_id, res.fourcc()), res);
+            }
+            extern_models
+                .entry(key_model_name(key))
+                .or_insert(key_model);
+        }
+    }
+
     Ok((
         found,
         pickup_hudmemos,
@@ -1436,6 +1799,325 @@ pub fn collect_game_resources<'r>(
     ))
 }
 
+fn find_resources<'r>(
+    gc_disc: &structs::GcDisc<'r>,
+    paks: &[&str],
+    looking_for: &mut HashSet<(u32, FourCC)>,
+    found: &mut HashMap<(u32, FourCC), structs::Resource<'r>>,
+) {
+    for pak_name in paks {
+        let file_entry = gc_disc.find_file(pak_name).unwrap();
+        let pak = match *file_entry.file().unwrap() {
+            structs::FstEntryFile::Pak(ref pak) => Cow::Borrowed(pak),
+            structs::FstEntryFile::Unknown(ref reader) => Cow::Owned(reader.clone().read(())),
+            _ => panic!(),
+        };
+
+        // Iterate through all resources in level Pak //
+        for res in pak.resources.iter() {
+            // If this resource is a dependency needed by the patcher, add the resource to the output list //
+            let key = (res.file_id, res.fourcc());
+            if looking_for.remove(&key) {
+                found.insert(key, res.into_owned());
+            }
+        }
+    }
+}
+
+// Like find_resources, for assets of which only the id is known (e.g. those particles reference)
+fn find_resources_by_id<'r>(
+    gc_disc: &structs::GcDisc<'r>,
+    paks: &[&str],
+    looking_for: &mut HashSet<u32>,
+    found: &mut HashMap<(u32, FourCC), structs::Resource<'r>>,
+) {
+    for pak_name in paks {
+        let file_entry = gc_disc.find_file(pak_name).unwrap();
+        let pak = match *file_entry.file().unwrap() {
+            structs::FstEntryFile::Pak(ref pak) => Cow::Borrowed(pak),
+            structs::FstEntryFile::Unknown(ref reader) => Cow::Owned(reader.clone().read(())),
+            _ => panic!(),
+        };
+
+        for res in pak.resources.iter() {
+            if looking_for.remove(&res.file_id) {
+                found.insert((res.file_id, res.fourcc()), res.into_owned());
+            }
+        }
+    }
+}
+
+/// The scans of the logbook categories, as listed in the SAVW of every world
+pub fn logbook_scans(gc_disc: &structs::GcDisc, categories: &[LogbookCategory]) -> Vec<u32> {
+    let mut scans = vec![];
+    if categories.is_empty() {
+        return scans;
+    }
+    for (pak_name, _) in pickup_meta::ROOM_INFO.iter() {
+        let file_entry = gc_disc.find_file(pak_name).unwrap();
+        let pak = match *file_entry.file().unwrap() {
+            structs::FstEntryFile::Pak(ref pak) => Cow::Borrowed(pak),
+            structs::FstEntryFile::Unknown(ref reader) => Cow::Owned(reader.clone().read(())),
+            _ => panic!(),
+        };
+
+        for res in pak.resources.iter() {
+            if res.fourcc() != b"SAVW".into() {
+                continue;
+            }
+            let savw = res.kind.as_savw().unwrap();
+            for entry in savw.scan_array.iter() {
+                let in_category = categories
+                    .iter()
+                    .any(|category| *category as u32 == entry.logbook_category);
+                if in_category && !scans.contains(&entry.scan.to_u32()) {
+                    scans.push(entry.scan.to_u32());
+                }
+            }
+        }
+    }
+    scans
+}
+
+// The ids of the assets an asset references directly
+fn asset_references(res: &Resource) -> Result<Vec<u32>, String> {
+    let data = match res.kind {
+        ResourceKind::Unknown(..) => ResourceData::new(res),
+        ResourceKind::External(..) => ResourceData::new_external(res),
+        _ => return Ok(vec![]),
+    };
+    let bytes = data.decompress();
+    let particle_fourccs = [b"PART", b"ELSC", b"SWHC", b"WPSC", b"CRSC", b"DPSC"];
+    if particle_fourccs
+        .iter()
+        .any(|fourcc| res.fourcc() == FourCC::from_bytes(fourcc))
+    {
+        Ok(particle_elements(&bytes)?
+            .asset_refs
+            .into_iter()
+            .map(|i| u32::from_be_bytes(bytes[i..i + 4].try_into().unwrap()))
+            .collect())
+    } else {
+        Ok(referenced_assets(res.fourcc(), &bytes)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect())
+    }
+}
+
+/// The assets the assets `ids` reference, including themselves, down to the last reference, and
+/// the ids of those of them which aren't in `resources`
+#[allow(clippy::type_complexity)]
+pub fn asset_dependencies(
+    resources: &HashMap<(u32, FourCC), Resource>,
+    ids: impl Iterator<Item = u32>,
+) -> Result<(Vec<(u32, FourCC)>, HashSet<u32>), String> {
+    let kinds: HashMap<u32, FourCC> = resources.keys().copied().collect();
+    let mut dependencies = vec![];
+    let mut missing = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut to_visit: Vec<u32> = ids.collect();
+    while let Some(id) = to_visit.pop() {
+        if id == 0 || id == 0xFFFFFFFF || !visited.insert(id) {
+            continue;
+        }
+        match kinds.get(&id) {
+            Some(&fourcc) => {
+                dependencies.push((id, fourcc));
+                to_visit.extend(asset_references(&resources[&(id, fourcc)])?);
+            }
+            None => {
+                missing.insert(id);
+            }
+        }
+    }
+    Ok((dependencies, missing))
+}
+
+/// The copy of the elevator hologram shown by elevators going to `world`, if it has custom
+/// cosmetics
+pub fn elevator_hologram_cmdl(world: World) -> ResId<res_id::CMDL> {
+    ResId::new(custom_asset_ids::ELEVATOR_HOLOGRAM_CMDLS.to_u32() + world as u32)
+}
+
+// A copy of the elevator hologram for each destination world, using copies of its textures which
+// are replaced and then recolored as configured
+fn create_elevator_hologram_assets<'r>(
+    resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+    elevator_holograms: &HashMap<String, ElevatorHologramCosmetics>,
+    asset_cache: &AssetCache,
+) -> Result<Vec<structs::Resource<'r>>, String> {
+    const MAX_TEXTURES: u32 = 8;
+
+    let holobeam = ResourceData::new(&resources[&resource_info!("holobeam.CMDL").into()]);
+    let holobeam_bytes = holobeam.decompress().into_owned();
+
+    let mut assets = vec![];
+    for (world_name, hologram) in elevator_holograms {
+        let world = World::from_json_key(world_name);
+        let first_txtr_id =
+            custom_asset_ids::ELEVATOR_HOLOGRAM_TXTRS.to_u32() + world as u32 * MAX_TEXTURES;
+
+        // Point the model to the copies of its textures
+        let mut cmdl = Reader::new(&holobeam_bytes[..]).read::<structs::Cmdl>(());
+        let mut txtr_ids = HashMap::<u32, u32>::new(); // vanilla -> copy
+        for material_set in cmdl.material_sets.as_mut_vec() {
+            for txtr_id in material_set.texture_ids.as_mut_vec() {
+                let next_id = first_txtr_id + txtr_ids.len() as u32;
+                *txtr_id = ResId::new(*txtr_ids.entry(txtr_id.to_u32()).or_insert(next_id));
+            }
+        }
+        if txtr_ids.len() as u32 > MAX_TEXTURES {
+            Err(format!(
+                "The elevator hologram has more than {} textures",
+                MAX_TEXTURES
+            ))?;
+        }
+
+        let mut replacements = HashMap::new();
+        for (texture, path) in hologram.textures.iter().flatten() {
+            let txtr_id = parse_texture_id(texture, "cosmetics.elevatorHolograms")?;
+            if !txtr_ids.contains_key(&txtr_id) {
+                Err(format!(
+                    "'{}' in 'cosmetics.elevatorHolograms.{}.textures' is not an elevator hologram texture",
+                    texture, world_name
+                ))?;
+            }
+            replacements.insert(txtr_id, path);
+        }
+
+        let remap = hologram.color_remap();
+        for (vanilla_id, copy_id) in txtr_ids {
+            let mut txtr = resources[&(vanilla_id, FourCC::from_bytes(b"TXTR"))].clone();
+            txtr.file_id = copy_id;
+            if let Some(path) = replacements.get(&vanilla_id) {
+                replace_txtr(&mut txtr, Path::new(path), true)?;
+            }
+            if let Some(remap) = remap.as_ref() {
+                remap_txtr(&mut txtr, remap, asset_cache)?;
+            }
+            assets.push(txtr);
+        }
+
+        let mut cmdl_bytes = vec![];
+        cmdl.write_to(&mut cmdl_bytes).unwrap();
+        cmdl_bytes.extend(reader_writer::pad_bytes(32, cmdl_bytes.len()).iter());
+        assets.push(build_resource(
+            elevator_hologram_cmdl(world),
+            structs::ResourceKind::External(cmdl_bytes, b"CMDL".into()),
+        ));
+    }
+
+    Ok(assets)
+}
+
+/// At most this many different keys can be given by pickups
+pub const MAX_KEYS: usize = 16;
+
+/// The pickup model `key` pickups get unless they have one
+pub fn key_model_name(key: &str) -> String {
+    format!("{} Key", key)
+}
+
+// The hue of the usual color names, otherwise one picked from the name
+fn key_hue_deg(key: &str) -> f32 {
+    match key.trim().to_lowercase().as_str() {
+        "red" => 0.0,
+        "orange" => 30.0,
+        "yellow" => 60.0,
+        "green" => 120.0,
+        "cyan" => 180.0,
+        "blue" => 240.0,
+        "purple" => 270.0,
+        "pink" | "magenta" => 310.0,
+        name => {
+            let hash = name
+                .bytes()
+                .fold(0u32, |hash, b| hash.wrapping_mul(31).wrapping_add(b as u32));
+            (hash % 360) as f32
+        }
+    }
+}
+
+// A copy of the Artifact of Truth for the key, using copies of its textures rotated to the key's
+// hue
+fn create_key_model_assets<'r>(
+    resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+    index: usize,
+    key: &str,
+    asset_cache: &AssetCache,
+) -> Result<(Vec<structs::Resource<'r>>, ExternPickupModel), String> {
+    const MAX_TEXTURES: u32 = 8;
+    const ARTIFACT_HUE_DEG: f32 = 30.0;
+
+    let artifact = PickupModel::ArtifactOfTruth.pickup_data();
+    let cmdl_id = custom_asset_ids::KEY_CMDLS.to_u32() + index as u32;
+    let ancs_id = custom_asset_ids::KEY_ANCS.to_u32() + index as u32;
+    let first_txtr_id = custom_asset_ids::KEY_TXTRS.to_u32() + index as u32 * MAX_TEXTURES;
+
+    let artifact_cmdl =
+        ResourceData::new(&resources[&(artifact.cmdl.to_u32(), FourCC::from_bytes(b"CMDL"))]);
+    let cmdl_bytes = artifact_cmdl.decompress().into_owned();
+    let mut cmdl = Reader::new(&cmdl_bytes[..]).read::<structs::Cmdl>(());
+    let mut txtr_ids = HashMap::<u32, u32>::new(); // vanilla -> copy
+    for material_set in cmdl.material_sets.as_mut_vec() {
+        for txtr_id in material_set.texture_ids.as_mut_vec() {
+            let next_id = first_txtr_id + txtr_ids.len() as u32;
+            *txtr_id = ResId::new(*txtr_ids.entry(txtr_id.to_u32()).or_insert(next_id));
+        }
+    }
+    if txtr_ids.len() as u32 > MAX_TEXTURES {
+        Err(format!(
+            "The key model has more than {} textures",
+            MAX_TEXTURES
+        ))?;
+    }
+
+    let mut assets = vec![];
+    let matrix = huerotate_matrix(key_hue_deg(key) - ARTIFACT_HUE_DEG);
+    for (vanilla_id, copy_id) in txtr_ids.iter() {
+        let mut txtr = resources[&(*vanilla_id, FourCC::from_bytes(b"TXTR"))].clone();
+        txtr.file_id = *copy_id;
+        huerotate_txtr(&mut txtr, matrix, asset_cache)?;
+        assets.push(txtr);
+    }
+
+    let mut new_cmdl_bytes = vec![];
+    cmdl.write_to(&mut new_cmdl_bytes).unwrap();
+    new_cmdl_bytes.extend(reader_writer::pad_bytes(32, new_cmdl_bytes.len()).iter());
+    assets.push(build_resource_raw(
+        cmdl_id,
+        structs::ResourceKind::External(new_cmdl_bytes, b"CMDL".into()),
+    ));
+
+    let artifact_ancs = ResourceData::new(
+        &resources[&(artifact.ancs.file_id.to_u32(), FourCC::from_bytes(b"ANCS"))],
+    );
+    let ancs_bytes = artifact_ancs.decompress().into_owned();
+    let mut ancs = Reader::new(&ancs_bytes[..]).read::<structs::Ancs>(());
+    ancs.char_set.char_info.as_mut_vec()[artifact.ancs.node_index as usize].cmdl =
+        ResId::new(cmdl_id);
+    let mut new_ancs_bytes = vec![];
+    ancs.write_to(&mut new_ancs_bytes).unwrap();
+    assets.push(build_resource_raw(
+        ancs_id,
+        structs::ResourceKind::External(new_ancs_bytes, b"ANCS".into()),
+    ));
+
+    // The rest of the artifact is still needed, e.g. its skin and animations
+    let mut dependencies = PickupModel::ArtifactOfTruth.dependencies().to_vec();
+    dependencies.extend(assets.iter().map(|res| (res.file_id, res.fourcc())));
+
+    let model = ExternPickupModel {
+        ancs: ancs_id,
+        cmdl: cmdl_id,
+        scale: artifact.scale[0],
+        character: artifact.ancs.node_index,
+        dependencies,
+    };
+    Ok((assets, model))
+}
+
 fn create_custom_block_cmdl<'r>(
     resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
     txtr_id: ResId<res_id::TXTR>,
@@ -1467,6 +2149,74 @@ fn create_custom_block_cmdl<'r>(
     )
 }
 
+// The block model, drawn additively and without writing depth so that whatever is inside the box
+// can be seen through it
+fn create_volume_box_cmdl<'r>(
+    resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+) -> structs::Resource<'r> {
+    let mut block = create_custom_block_cmdl(
+        resources,
+        GenericTexture::Crater.txtr(),
+        custom_asset_ids::VOLUME_BOX_CMDL,
+    );
+    let cmdl_bytes = match &mut block.kind {
+        structs::ResourceKind::External(bytes, _) => bytes,
+        _ => unreachable!(),
+    };
+    let cmdl = Reader::new(&cmdl_bytes[..]).read::<structs::Cmdl>(());
+    let material_set_sizes: Vec<u32> = cmdl.material_set_sizes.iter().collect();
+
+    // The fixed part of the header is 44 bytes, followed by the size of each section
+    let header_size = 44 + 4 * cmdl.data_section_count as usize;
+    let mut set_start = (header_size + 31) & !31;
+    for set_size in material_set_sizes {
+        make_materials_additive(&mut cmdl_bytes[set_start..set_start + set_size as usize]);
+        set_start += set_size as usize;
+    }
+
+    block
+}
+
+fn make_materials_additive(material_set: &mut [u8]) {
+    const KONST_COLORS: u32 = 0x8;
+    const ALPHA_BLEND: u32 = 0x10;
+    const DEPTH_WRITE: u32 = 0x80;
+    const GX_BL_ONE: u16 = 1;
+
+    let read_u32 = |bytes: &[u8], offset: usize| {
+        u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize
+    };
+
+    // The material set starts with its textures, then the end offset of each material
+    let texture_count = read_u32(material_set, 0);
+    let material_count = read_u32(material_set, 4 + 4 * texture_count);
+    let end_offsets_start = 8 + 4 * texture_count;
+    let materials_start = end_offsets_start + 4 * material_count;
+
+    for i in 0..material_count {
+        let start = materials_start
+            + if i == 0 {
+                0
+            } else {
+                read_u32(material_set, end_offsets_start + 4 * (i - 1))
+            };
+
+        let flags = read_u32(material_set, start) as u32;
+        let new_flags = (flags | ALPHA_BLEND) & !DEPTH_WRITE;
+        material_set[start..start + 4].copy_from_slice(&new_flags.to_be_bytes());
+
+        // Flags, the material's textures, its vertex attributes and group index, then its
+        // konst colors if it has any, then the blend factors (destination first)
+        let material_texture_count = read_u32(material_set, start + 4);
+        let mut blend_offset = start + 8 + 4 * material_texture_count + 8;
+        if flags & KONST_COLORS != 0 {
+            blend_offset += 4 + 4 * read_u32(material_set, blend_offset);
+        }
+        material_set[blend_offset..blend_offset + 2].copy_from_slice(&GX_BL_ONE.to_be_bytes());
+        material_set[blend_offset + 2..blend_offset + 4].copy_from_slice(&GX_BL_ONE.to_be_bytes());
+    }
+}
+
 fn create_custom_blast_shield_cmdl<'r>(
     blast_shield_type: BlastShieldType,
 ) -> structs::Resource<'r> {
@@ -1885,6 +2635,74 @@ fn create_ice_trap_icon_ancs<'r>(
     ]
 }
 
+// Fills in the {item}, {room} and {count} placeholders of custom pickup text
+fn expand_pickup_text(text: &str, item: &str, room_name: &str, count: u32) -> String {
+    text.replace("{item}", item)
+        .replace("{room}", room_name.trim())
+        .replace("{count}", &count.to_string())
+}
+
+fn localized_strg<'r>(contents: Vec<String>, version: Version) -> structs::ResourceKind<'r> {
+    if version == Version::Pal {
+        structs::ResourceKind::Strg(structs::Strg::from_strings_pal(contents))
+    } else if version == Version::NtscJ {
+        structs::ResourceKind::Strg(structs::Strg::from_strings_jpn(contents))
+    } else {
+        structs::ResourceKind::Strg(structs::Strg::from_strings(contents))
+    }
+}
+
+// Replaces the contents of the string tables of `res` which have a translation. Translations for
+// languages the STRG has no table for are ignored, e.g. French on an NTSC-U ISO. Japanese text
+// uses its own font, so only the other languages are transliterated.
+fn translate_strg<F>(
+    res: &mut structs::Resource,
+    translations: &HashMap<String, String>,
+    version: Version,
+    transliterator: &Transliterator,
+    format_text: F,
+) -> Result<(), String>
+where
+    F: Fn(&str) -> String,
+{
+    let strg = match &mut res.kind {
+        structs::ResourceKind::Strg(strg) => strg,
+        _ => panic!(),
+    };
+    for (language, text) in translations.iter() {
+        let lang: FourCC = match language.to_lowercase().trim() {
+            "english" => b"ENGL".into(),
+            "french" => b"FREN".into(),
+            "german" => b"GERM".into(),
+            "spanish" => b"SPAN".into(),
+            "italian" => b"ITAL".into(),
+            "dutch" => b"DUTC".into(),
+            "japanese" => b"JAPN".into(),
+            _ => Err(format!(
+                "Unknown language '{}' in text translations",
+                language
+            ))?,
+        };
+        let text = if lang == b"JAPN".into() {
+            text.to_string()
+        } else {
+            transliterator.transliterate(text)
+        };
+        let text = if version == Version::NtscJ {
+            format!("&line-extra-space=4;&font=C29C51F1;{}", format_text(&text))
+        } else {
+            format_text(&text)
+        };
+        for table in strg.string_tables.as_mut_vec().iter_mut() {
+            if table.lang == lang {
+                table.strings = vec![text.clone().into()].into();
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn create_item_scan_strg_pair<'r>(
     new_scan: ResId<res_id::SCAN>,
     new_strg: ResId<res_id::STRG>,
@@ -1894,6 +2712,115 @@ fn create_item_scan_strg_pair<'r>(
     create_item_scan_strg_pair_2(new_scan, new_strg, vec![contents], 0, 0, version)
 }
 
+// FNV-1a, so that the hash doesn't change between builds of the patcher
+fn seed_hash(config: &PatchConfig) -> String {
+    let mut pickups = Vec::new();
+    for (level_name, level) in config.level_data.iter() {
+        for (room_name, room) in level.rooms.iter() {
+            for (i, pickup) in room.pickups.iter().flatten().enumerate() {
+                pickups.push(format!(
+                    "{}:{}:{}:{}",
+                    level_name, room_name, i, pickup.pickup_type
+                ));
+            }
+        }
+    }
+    pickups.sort();
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let seed = config.seed.to_string();
+    for bytes in iter::once(seed.as_bytes()).chain(pickups.iter().map(|p| p.as_bytes())) {
+        for b in bytes.iter().chain(iter::once(&0)) {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    format!("{:08X}", (hash >> 32) as u32 ^ hash as u32)
+}
+
+fn seed_info_lines(config: &PatchConfig) -> Vec<String> {
+    let seed_info = config.seed_info.clone().unwrap_or_default();
+    let mut lines = vec![
+        format!(
+            "Seed Hash: {}",
+            seed_info.hash.unwrap_or_else(|| seed_hash(config))
+        ),
+        format!("Seed: {}", config.seed),
+    ];
+    if let Some(trick_level) = seed_info.trick_level {
+        lines.push(format!("Trick Level: {}", trick_level));
+    }
+    lines.push(format!(
+        "Artifacts Required: {}",
+        config.required_artifact_count.unwrap_or(12)
+    ));
+
+    let starting_items = config.starting_items.names();
+    if starting_items.is_empty() {
+        lines.push("Starting Items: None".to_string());
+    } else {
+        lines.push(format!("Starting Items: {}", starting_items.join(", ")));
+    }
+
+    lines.extend(seed_info.settings.unwrap_or_default());
+    lines
+}
+
+fn loadout_memo_lines(config: &PatchConfig) -> Vec<String> {
+    let mut lines = vec!["Starting Loadout".to_string()];
+    let starting_items = config.starting_items.names();
+    if starting_items.is_empty() {
+        lines.push("No starting items".to_string());
+    } else {
+        lines.extend(starting_items);
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+        "Artifacts Required: {}",
+        config.required_artifact_count.unwrap_or(12)
+    ));
+    if config.etank_capacity != 100 {
+        lines.push(format!("Energy Tank Capacity: {}", config.etank_capacity));
+    }
+    if config.spring_ball {
+        lines.push("Spring Ball: On".to_string());
+    }
+    if config.shuffle_pickup_position {
+        lines.push("Pickup Positions: Shuffled".to_string());
+    }
+    if config.phazon_suit_from_pickup {
+        lines.push("Phazon Suit: From Pickup".to_string());
+    }
+    if let Some(seed_info) = config.seed_info.as_ref() {
+        lines.extend(seed_info.settings.clone().unwrap_or_default());
+    }
+    lines
+}
+
+// Packs whole lines into scan pages, the second string is the logbook title
+fn seed_info_pages(lines: &[String]) -> Vec<String> {
+    const PAGINATION_SIZE: usize = 123;
+
+    let mut pages: Vec<String> = vec![];
+    let mut page = String::new();
+    for line in lines {
+        if !page.is_empty() && page.len() + 1 + line.len() > PAGINATION_SIZE {
+            pages.push(page + "\0");
+            page = String::new();
+        }
+        if !page.is_empty() {
+            page.push('\n');
+        }
+        page.push_str(line);
+    }
+    pages.push(page + "\0");
+    pages.insert(1, "Seed Info\0".to_string());
+
+    pages
+}
+
 fn create_item_scan_strg_pair_2<'r>(
     new_scan: ResId<res_id::SCAN>,
     new_strg: ResId<res_id::STRG>,
@@ -1954,15 +2881,7 @@ fn create_item_scan_strg_pair_2<'r>(
         }),
     );
 
-    let kind = if version == Version::Pal {
-        structs::ResourceKind::Strg(structs::Strg::from_strings_pal(contents))
-    } else if version == Version::NtscJ {
-        structs::ResourceKind::Strg(structs::Strg::from_strings_jpn(contents))
-    } else {
-        structs::ResourceKind::Strg(structs::Strg::from_strings(contents))
-    };
-
-    let strg = build_resource(new_strg, kind);
+    let strg = build_resource(new_strg, localized_strg(contents, version));
 
     [scan, strg]
 }
@@ -302,6 +302,21 @@ impl<'a> DolPatcher<'a> {
         Ok(self)
     }
 
+    /// Reads a word of the original, unpatched code. Used to relocate the instruction that gets
+    /// overwritten when hooking a function.
+    pub fn read_original_u32(&self, addr: u32) -> Result<u32, String> {
+        for seg in self.text_segments.iter().chain(&self.data_segments) {
+            if let DolSegment::Patched(seg_addr, patcher) = seg {
+                if addr >= *seg_addr && addr + 4 <= *seg_addr + patcher.len() as u32 {
+                    let offset = (addr - seg_addr) as usize;
+                    let bytes = &patcher.data[offset..offset + 4];
+                    return Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+                }
+            }
+        }
+        Err(format!("Failed to find segment to read at {:x}", addr))
+    }
+
     pub fn ppcasm_patch<A, L>(&mut self, asm: &ppcasm::AsmBlock<A, L>) -> Result<&mut Self, String>
     where
         A: AsRef<[u32]>,
@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use reader_writer::FourCC;
 use structs::{res_id, scly_structs::TypeVulnerability, ResId};
 
 use crate::{
     custom_assets::custom_asset_ids,
+    patch_config::ColorblindMode,
     structs::scly_props::structs::{BeamCombos, ChargedBeams, DamageVulnerability},
 };
 
@@ -315,6 +318,20 @@ impl DoorType {
         }
     }
 
+    pub fn colorblind_hue_deg(&self, mode: ColorblindMode) -> i16 {
+        // Rotate the vanilla beam colors onto the axis that the viewer can still tell apart
+        let door = self.to_horizontal().to_primary_color();
+
+        match (mode, door) {
+            (ColorblindMode::Protanopia, DoorType::Purple) => 140, // purple -> yellow
+            (ColorblindMode::Protanopia, DoorType::Red) => 20,     // dark red -> bright orange
+            (ColorblindMode::Deuteranopia, DoorType::Purple) => 140, // purple -> yellow
+            (ColorblindMode::Tritanopia, DoorType::Blue) => -30,   // blue -> cyan
+            (ColorblindMode::Tritanopia, DoorType::Purple) => 180, // purple -> yellow-green
+            _ => 0,
+        }
+    }
+
     // The following three are the textures for the damageable trigger
 
     pub fn pattern0_txtr(&self) -> ResId<res_id::TXTR> {
@@ -1077,7 +1094,7 @@ impl BlastShieldType {
             BlastShieldType::Phazon => custom_asset_ids::PHAZON_BEAM_BLAST_SHIELD_SCAN,
             BlastShieldType::Thermal => custom_asset_ids::THERMAL_VISOR_BLAST_SHIELD_SCAN,
             BlastShieldType::XRay => custom_asset_ids::XRAY_VISOR_BLAST_SHIELD_SCAN,
-            BlastShieldType::Scan => ResId::invalid(), // scan actor added later
+            BlastShieldType::Scan => custom_asset_ids::SCAN_VISOR_BLAST_SHIELD_SCAN,
             BlastShieldType::Missile => ResId::<res_id::SCAN>::new(0x05F56F9D),
             _ => panic!("none/unchanged blast shield doesn't have scan"),
         }
@@ -1096,7 +1113,7 @@ impl BlastShieldType {
             BlastShieldType::Phazon => custom_asset_ids::PHAZON_BEAM_BLAST_SHIELD_STRG,
             BlastShieldType::Thermal => custom_asset_ids::THERMAL_VISOR_BLAST_SHIELD_STRG,
             BlastShieldType::XRay => custom_asset_ids::XRAY_VISOR_BLAST_SHIELD_STRG,
-            BlastShieldType::Scan => ResId::invalid(), // scan actor added later
+            BlastShieldType::Scan => custom_asset_ids::SCAN_VISOR_BLAST_SHIELD_STRG,
             BlastShieldType::Missile => ResId::<res_id::STRG>::new(0x265142BA),
             _ => panic!("none/unchanged blast shield doesn't have strg"),
         }
@@ -1148,6 +1165,10 @@ impl BlastShieldType {
                                                   "\0".to_string(),
                                                   "This Blast Shield can be unlocked with &push;&main-color=#D91818;X-Ray Visor&pop;.\0".to_string(),
                                                  ],
+            BlastShieldType::Scan         => vec!["Analysis complete.\0".to_string(),
+                                                  "\0".to_string(),
+                                                  "Scan Gate unlocked. The Blast Shield has been &push;&main-color=#D91818;disengaged&pop;.\0".to_string(),
+                                                 ],
             _ => vec!["\0".to_string()], // Vanilla missile locks do not have scans associated with the actor
         }
     }
@@ -1237,7 +1258,24 @@ impl BlastShieldType {
     }
 
     pub fn vulnerability(&self) -> DamageVulnerability {
-        self.door_type_counterpart().vulnerability()
+        match self {
+            // Scan gates can only be removed by scanning them
+            BlastShieldType::Scan => DoorType::Disabled.vulnerability(),
+            _ => self.door_type_counterpart().vulnerability(),
+        }
+    }
+
+    pub fn colorblind_hue_deg(&self, mode: ColorblindMode) -> i16 {
+        // Shields tinted like a beam door follow that door, the rest are handled individually
+        match (mode, self) {
+            (_, BlastShieldType::Missile) => DoorType::Red.colorblind_hue_deg(mode),
+            (_, BlastShieldType::Wavebuster) => DoorType::Purple.colorblind_hue_deg(mode),
+            (_, BlastShieldType::Icespreader) => DoorType::White.colorblind_hue_deg(mode),
+            (_, BlastShieldType::Flamethrower) => DoorType::Red.colorblind_hue_deg(mode),
+            (ColorblindMode::Protanopia, BlastShieldType::Super) => 100, // green -> blue
+            (ColorblindMode::Deuteranopia, BlastShieldType::Super) => 100, // green -> blue
+            _ => 0,
+        }
     }
 
     pub const fn door_type_counterpart(&self) -> DoorType {
@@ -1259,3 +1297,29 @@ impl BlastShieldType {
         }
     }
 }
+
+// Every door and blast shield texture which gets recolored for the given colorblind mode
+pub fn colorblind_txtr_hue_degs(mode: ColorblindMode) -> HashMap<u32, i16> {
+    let mut angles = HashMap::new();
+
+    for door in DoorType::iter() {
+        let angle = door.colorblind_hue_deg(mode);
+        for txtr in [door.color_txtr(), door.holorim_txtr()] {
+            angles.entry(txtr.to_u32()).or_insert(angle);
+        }
+    }
+
+    for shield in BlastShieldType::iter() {
+        let angle = shield.colorblind_hue_deg(mode);
+        for txtr in [
+            shield.glow_border_txtr(),
+            shield.glow_trim_txtr(),
+            shield.animated_glow_txtr(),
+        ] {
+            angles.entry(txtr.to_u32()).or_insert(angle);
+        }
+    }
+
+    angles.retain(|_, angle| *angle % 360 != 0);
+    angles
+}
@@ -5,9 +5,11 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use reader_writer::FourCC;
+use reader_writer::{FourCC, Reader};
 use serde::Deserialize;
 
+use crate::cmdl_conversions::convert_cmdl_to_prime;
+
 /* Public Structs */
 #[derive(Debug, Clone)]
 pub struct ExternPickupModel {
@@ -62,6 +64,97 @@ fn parse_dir(dir: &String) -> Result<Vec<PathBuf>, std::io::Error> {
     Ok(files)
 }
 
+fn find_asset_file(files: &[PathBuf], id: u32) -> Option<&PathBuf> {
+    files.iter().find(|file| {
+        file.to_str()
+            .unwrap()
+            .to_string()
+            .contains(&format!("{}", id))
+    })
+}
+
+fn read_asset_file(filename: &Path) -> Result<ExternAsset, String> {
+    // Derrive FourCC from file extension
+    // (I dislike Rust; This is just for parsing 4 letters)
+    let fourcc = filename.to_str().unwrap();
+    let fourcc = fourcc.split('.');
+    let fourcc: Vec<&str> = fourcc.collect();
+    if fourcc.len() < 2 {
+        panic!("Extern asset, unexpected asset filename format");
+    }
+    let fourcc = fourcc[fourcc.len() - 1];
+    let fourcc = fourcc.as_bytes();
+    let fourcc: [u8; 4] = [fourcc[0], fourcc[1], fourcc[2], fourcc[3]];
+    let fourcc = FourCC::from_bytes(&fourcc);
+
+    // Read file contents to RAM
+    let mut file = File::open(filename).expect("no file found");
+    let metadata = fs::metadata(filename).expect("unable to read metadata");
+    let mut bytes = vec![0; metadata.len() as usize];
+    file.read_exact(&mut bytes).expect("buffer overflow");
+
+    // Models ripped from Echoes can be used as-is
+    if fourcc == FourCC::from_bytes(b"CMDL") {
+        bytes = convert_cmdl_to_prime(&bytes)
+            .map_err(|e| format!("Failed to convert '{}': {}", filename.display(), e))?;
+    }
+
+    Ok(ExternAsset { fourcc, bytes })
+}
+
+/// Assets referenced from within an uncompressed CMDL, ANCS or SCAN. Other asset types are treated
+/// as leaves.
+pub fn referenced_assets(fourcc: FourCC, bytes: &[u8]) -> Vec<(u32, FourCC)> {
+    let mut refs: Vec<(u32, FourCC)> = Vec::new();
+    let mut reader = Reader::new(bytes);
+
+    if fourcc == FourCC::from_bytes(b"CMDL") {
+        let cmdl: structs::Cmdl = reader.read(());
+        for material_set in cmdl.material_sets.iter() {
+            for txtr in material_set.texture_ids.iter() {
+                refs.push((*txtr).into());
+            }
+        }
+    } else if fourcc == FourCC::from_bytes(b"ANCS") {
+        let ancs: structs::Ancs = reader.read(());
+        for char_info in ancs.char_set.char_info.iter() {
+            refs.push(char_info.cmdl.into());
+            refs.push(char_info.cskr.into());
+            refs.push(char_info.cinf.into());
+            if let Some(overlay_cmdl) = char_info.overlay_cmdl {
+                refs.push(overlay_cmdl.into());
+            }
+            if let Some(overlay_cskr) = char_info.overlay_cskr {
+                refs.push(overlay_cskr.into());
+            }
+            for part in char_info.particles.part_assets.iter() {
+                refs.push((*part, FourCC::from_bytes(b"PART")));
+            }
+            for swhc in char_info.particles.swhc_assets.iter() {
+                refs.push(swhc.into());
+            }
+            if let Some(elsc_assets) = &char_info.particles.elsc_assets {
+                for elsc in elsc_assets.iter() {
+                    refs.push(elsc.into());
+                }
+            }
+        }
+        if let Some(animation_resources) = &ancs.anim_set.animation_resources {
+            for res in animation_resources.iter() {
+                refs.push(res.anim.into());
+                refs.push(res.evnt.into());
+            }
+        }
+    } else if fourcc == FourCC::from_bytes(b"SCAN") {
+        let scan: structs::Scan = reader.read(());
+        refs.push(scan.frme.into());
+        refs.push(scan.strg.into());
+    }
+
+    refs.retain(|(id, _)| *id != 0xFFFFFFFF && *id != 0);
+    refs
+}
+
 impl ExternPickupModel {
     #[allow(clippy::type_complexity)]
     pub fn parse(
@@ -107,6 +200,36 @@ impl ExternPickupModel {
                 }
             }
 
+            // Anything the model actually references must ship alongside it, otherwise the game
+            // crashes as soon as the room loads
+            let mut to_trace = dependencies.clone();
+            while let Some((id, fourcc)) = to_trace.pop() {
+                if fourcc != FourCC::from_bytes(b"CMDL") && fourcc != FourCC::from_bytes(b"ANCS") {
+                    continue;
+                }
+
+                let filename = find_asset_file(&files, id).ok_or(format!(
+                    "Extern model '{}' depends on {} 0x{:X}, which is not in the extern assets dir",
+                    name, fourcc, id
+                ))?;
+                let asset = read_asset_file(filename)?;
+                for (ref_id, ref_fourcc) in referenced_assets(asset.fourcc, &asset.bytes) {
+                    if deps.contains(&ref_id) {
+                        continue;
+                    }
+                    if find_asset_file(&files, ref_id).is_none() {
+                        return Err(format!(
+                            "Extern model '{}' references {} 0x{:X} from {} 0x{:X}, but it is not in the extern assets dir",
+                            name, ref_fourcc, ref_id, fourcc, id
+                        ));
+                    }
+
+                    dependencies.push((ref_id, ref_fourcc));
+                    deps.insert(ref_id);
+                    to_trace.push((ref_id, ref_fourcc));
+                }
+            }
+
             // Add model to list of availible models
             models.insert(
                 name.to_string(),
@@ -134,45 +257,10 @@ impl ExternPickupModel {
         let mut assets: HashMap<u32, ExternAsset> = HashMap::new();
         for id in ids_to_find {
             // Find the file which corresponds to this id
-            let mut filename = None;
-            let mut found = false;
-            for file in &files {
-                if file
-                    .to_str()
-                    .unwrap()
-                    .to_string()
-                    .contains(&format!("{}", id))
-                {
-                    found = true;
-                    filename = Some(file);
-                    break;
-                }
-            }
-            if !found {
-                panic!("Failed to find file corresponding to asset id {}", id)
-            }
-            let filename = filename.unwrap();
-            // Derrive FourCC from file extension
-            // (I dislike Rust; This is just for parsing 4 letters)
-            let fourcc = filename.clone();
-            let fourcc = fourcc.to_str().unwrap();
-            let fourcc = fourcc.split('.');
-            let fourcc: Vec<&str> = fourcc.collect();
-            if fourcc.len() < 2 {
-                panic!("Extern asset, unexpected asset filename format");
-            }
-            let fourcc = fourcc[fourcc.len() - 1];
-            let fourcc = fourcc.as_bytes();
-            let fourcc: [u8; 4] = [fourcc[0], fourcc[1], fourcc[2], fourcc[3]];
-            let fourcc = FourCC::from_bytes(&fourcc);
-
-            // Read file contents to RAM
-            let mut file = File::open(filename).expect("no file found");
-            let metadata = fs::metadata(filename).expect("unable to read metadata");
-            let mut bytes = vec![0; metadata.len() as usize];
-            file.read_exact(&mut bytes).expect("buffer overflow");
-
-            assets.insert(id, ExternAsset { fourcc, bytes });
+            let filename = find_asset_file(&files, id)
+                .unwrap_or_else(|| panic!("Failed to find file corresponding to asset id {}", id));
+
+            assets.insert(id, read_asset_file(filename)?);
         }
 
         Ok((models, assets))
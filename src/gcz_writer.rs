@@ -5,6 +5,7 @@ use std::{
 
 use adler32::adler32;
 use flate2::{self, Compress, Compression, FlushCompress};
+use log::error;
 use reader_writer::byteorder::{LittleEndian, WriteBytesExt};
 use structs;
 
@@ -191,7 +192,7 @@ impl<W: Write + Seek> Drop for GczWriter<W> {
         }();
         // We really don't want to panic from a destructor, so just write a warning instead
         if let Err(e) = res {
-            eprintln!("Error closing GczWriter: {}", e);
+            error!("Error closing GczWriter: {}", e);
         };
     }
 }
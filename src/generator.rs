@@ -0,0 +1,736 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    elevators::{Elevator, SpawnRoomData, World},
+    patch_config::{
+        AttainmentAudio, ElevatorAudioConfig, LevelConfig, NothingCosmeticsConfig, PickupConfig,
+    },
+    pickup_meta,
+    pickup_meta::PickupType,
+    starting_items::StartingItems,
+};
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TrickLevel {
+    #[serde(alias = "noLogic", alias = "none")]
+    NoLogic,
+    #[serde(alias = "standard")]
+    Standard,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GeneratorSettings {
+    pub seed: Option<u64>,
+    pub trick_level: Option<TrickLevel>,
+    pub item_pool: Option<HashMap<String, u32>>, // e.g. "Missile":50
+    pub starting_items: Option<StartingItems>,
+}
+
+const ARTIFACTS: [PickupType; 12] = [
+    PickupType::ArtifactOfTruth,
+    PickupType::ArtifactOfStrength,
+    PickupType::ArtifactOfElder,
+    PickupType::ArtifactOfWild,
+    PickupType::ArtifactOfLifegiver,
+    PickupType::ArtifactOfWarrior,
+    PickupType::ArtifactOfChozo,
+    PickupType::ArtifactOfNature,
+    PickupType::ArtifactOfSun,
+    PickupType::ArtifactOfWorld,
+    PickupType::ArtifactOfSpirit,
+    PickupType::ArtifactOfNewborn,
+];
+
+fn vanilla_item_pool() -> Vec<PickupType> {
+    let mut pool = vec![
+        PickupType::IceBeam,
+        PickupType::WaveBeam,
+        PickupType::PlasmaBeam,
+        PickupType::MorphBall,
+        PickupType::MorphBallBomb,
+        PickupType::BoostBall,
+        PickupType::SpiderBall,
+        PickupType::SpaceJumpBoots,
+        PickupType::GrappleBeam,
+        PickupType::ChargeBeam,
+        PickupType::SuperMissile,
+        PickupType::Wavebuster,
+        PickupType::IceSpreader,
+        PickupType::Flamethrower,
+        PickupType::VariaSuit,
+        PickupType::GravitySuit,
+        PickupType::PhazonSuit,
+        PickupType::ThermalVisor,
+        PickupType::XRayVisor,
+    ];
+    pool.extend_from_slice(&ARTIFACTS);
+    pool.extend(std::iter::repeat(PickupType::Missile).take(50));
+    pool.extend(std::iter::repeat(PickupType::EnergyTank).take(14));
+    pool.extend(std::iter::repeat(PickupType::PowerBomb).take(5));
+    pool
+}
+
+fn parse_item_pool(item_pool: &HashMap<String, u32>) -> Result<Vec<PickupType>, String> {
+    let mut pool = Vec::new();
+    for (name, count) in item_pool {
+        let pickup_type = PickupType::iter()
+            .find(|pt| pt.name().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("Unknown item '{}' in itemPool", name))?;
+        pool.extend(std::iter::repeat(pickup_type).take(*count as usize));
+    }
+
+    // HashMap order isn't stable, sort so that the same seed gives the same layout
+    pool.sort_by_key(|pt| *pt as u32);
+    Ok(pool)
+}
+
+// The parts of the game reachable from the Landing Site with the same items, by standard (no
+// tricks) logic. Every location requires the items of its region, plus those its room needs.
+#[derive(Copy, Clone, Debug)]
+enum Region {
+    TallonFront,
+    TallonBack,
+    TallonFrigate,
+    ChozoWest,
+    ChozoEast,
+    ChozoFurnace,
+    MagmoorNorth,
+    MagmoorSouth,
+    PhendranaFront,
+    PhendranaLabs,
+    PhendranaDeep,
+    MinesUpper,
+    MinesLower,
+}
+
+impl Region {
+    fn requirements(self) -> &'static [PickupType] {
+        match self {
+            Region::TallonFront => &[],
+            Region::TallonBack => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+                PickupType::SpaceJumpBoots,
+            ],
+            Region::TallonFrigate => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+                PickupType::SpaceJumpBoots,
+                PickupType::GravitySuit,
+                PickupType::ThermalVisor,
+                PickupType::WaveBeam,
+                PickupType::IceBeam,
+            ],
+            Region::ChozoWest => &[],
+            Region::ChozoEast => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+            ],
+            Region::ChozoFurnace => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+                PickupType::SpaceJumpBoots,
+                PickupType::WaveBeam,
+                PickupType::BoostBall,
+            ],
+            Region::MagmoorNorth => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+                PickupType::VariaSuit,
+            ],
+            Region::MagmoorSouth => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+                PickupType::VariaSuit,
+                PickupType::SpaceJumpBoots,
+                PickupType::WaveBeam,
+                PickupType::IceBeam,
+                PickupType::GrappleBeam,
+            ],
+            Region::PhendranaFront => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+                PickupType::VariaSuit,
+                PickupType::SpaceJumpBoots,
+            ],
+            Region::PhendranaLabs => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+                PickupType::VariaSuit,
+                PickupType::SpaceJumpBoots,
+                PickupType::WaveBeam,
+                PickupType::BoostBall,
+            ],
+            Region::PhendranaDeep => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+                PickupType::VariaSuit,
+                PickupType::SpaceJumpBoots,
+                PickupType::WaveBeam,
+                PickupType::BoostBall,
+                PickupType::SpiderBall,
+                PickupType::ThermalVisor,
+                PickupType::GrappleBeam,
+                PickupType::IceBeam,
+                PickupType::PowerBomb,
+            ],
+            Region::MinesUpper => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+                PickupType::VariaSuit,
+                PickupType::SpaceJumpBoots,
+                PickupType::WaveBeam,
+                PickupType::IceBeam,
+                PickupType::GrappleBeam,
+                PickupType::SpiderBall,
+                PickupType::PowerBomb,
+            ],
+            Region::MinesLower => &[
+                PickupType::Missile,
+                PickupType::MorphBall,
+                PickupType::MorphBallBomb,
+                PickupType::VariaSuit,
+                PickupType::SpaceJumpBoots,
+                PickupType::WaveBeam,
+                PickupType::IceBeam,
+                PickupType::GrappleBeam,
+                PickupType::SpiderBall,
+                PickupType::PowerBomb,
+                PickupType::BoostBall,
+                PickupType::PlasmaBeam,
+                PickupType::XRayVisor,
+                PickupType::ThermalVisor,
+            ],
+        }
+    }
+}
+
+// (world, room, region, items the room needs on top of its region's). The order of the pickups
+// of a room isn't known, so rooms with several list what the hardest of them needs.
+#[rustfmt::skip]
+const LOCATION_LOGIC: &[(&str, &str, Region, &[PickupType])] = &[
+    ("Chozo Ruins", "Main Plaza", Region::ChozoWest, &[PickupType::Missile, PickupType::MorphBall, PickupType::MorphBallBomb, PickupType::BoostBall, PickupType::SpaceJumpBoots, PickupType::GrappleBeam, PickupType::SuperMissile, PickupType::ChargeBeam]),
+    ("Chozo Ruins", "Ruined Fountain", Region::ChozoWest, &[PickupType::Missile, PickupType::MorphBall, PickupType::SpiderBall]),
+    ("Chozo Ruins", "Ruined Shrine", Region::ChozoWest, &[PickupType::Missile, PickupType::MorphBall, PickupType::MorphBallBomb, PickupType::BoostBall]),
+    ("Chozo Ruins", "Vault", Region::ChozoWest, &[PickupType::Missile, PickupType::MorphBall, PickupType::MorphBallBomb]),
+    ("Chozo Ruins", "Training Chamber", Region::ChozoEast, &[PickupType::WaveBeam, PickupType::BoostBall, PickupType::SpiderBall, PickupType::SpaceJumpBoots]),
+    ("Chozo Ruins", "Ruined Nursery", Region::ChozoWest, &[PickupType::Missile, PickupType::MorphBall, PickupType::MorphBallBomb]),
+    ("Chozo Ruins", "Training Chamber Access", Region::ChozoEast, &[PickupType::WaveBeam]),
+    ("Chozo Ruins", "Magma Pool", Region::ChozoWest, &[PickupType::Missile, PickupType::VariaSuit, PickupType::PowerBomb, PickupType::GrappleBeam]),
+    ("Chozo Ruins", "Tower of Light", Region::ChozoEast, &[PickupType::WaveBeam, PickupType::SpaceJumpBoots]),
+    ("Chozo Ruins", "Tower Chamber", Region::ChozoEast, &[PickupType::WaveBeam, PickupType::SpaceJumpBoots, PickupType::GravitySuit]),
+    ("Chozo Ruins", "Ruined Gallery", Region::ChozoWest, &[PickupType::Missile, PickupType::MorphBall, PickupType::MorphBallBomb]),
+    ("Chozo Ruins", "Transport Access North", Region::ChozoWest, &[PickupType::Missile]),
+    ("Chozo Ruins", "Gathering Hall", Region::ChozoEast, &[PickupType::SpaceJumpBoots]),
+    ("Chozo Ruins", "Hive Totem", Region::ChozoWest, &[]),
+    ("Chozo Ruins", "Sunchamber", Region::ChozoEast, &[PickupType::SpaceJumpBoots, PickupType::SuperMissile, PickupType::ChargeBeam]),
+    ("Chozo Ruins", "Watery Hall Access", Region::ChozoEast, &[]),
+    ("Chozo Ruins", "Watery Hall", Region::ChozoEast, &[PickupType::SpaceJumpBoots, PickupType::GravitySuit]),
+    ("Chozo Ruins", "Dynamo", Region::ChozoWest, &[PickupType::Missile, PickupType::MorphBall, PickupType::MorphBallBomb, PickupType::SpiderBall]),
+    ("Chozo Ruins", "Burn Dome", Region::ChozoWest, &[PickupType::Missile, PickupType::MorphBall, PickupType::MorphBallBomb]),
+    ("Chozo Ruins", "Furnace", Region::ChozoFurnace, &[PickupType::PowerBomb, PickupType::SpiderBall]),
+    ("Chozo Ruins", "Hall of the Elders", Region::ChozoFurnace, &[PickupType::IceBeam, PickupType::SpiderBall]),
+    ("Chozo Ruins", "Crossway", Region::ChozoFurnace, &[PickupType::SpiderBall, PickupType::SuperMissile, PickupType::ChargeBeam]),
+    ("Chozo Ruins", "Elder Chamber", Region::ChozoFurnace, &[PickupType::IceBeam, PickupType::SpiderBall, PickupType::PlasmaBeam]),
+    ("Chozo Ruins", "Antechamber", Region::ChozoFurnace, &[PickupType::IceBeam]),
+    ("Phendrana Drifts", "Phendrana Shorelines", Region::PhendranaFront, &[PickupType::PlasmaBeam, PickupType::SpiderBall]),
+    ("Phendrana Drifts", "Chozo Ice Temple", Region::PhendranaFront, &[PickupType::PlasmaBeam]),
+    ("Phendrana Drifts", "Ice Ruins West", Region::PhendranaFront, &[PickupType::PlasmaBeam]),
+    ("Phendrana Drifts", "Ice Ruins East", Region::PhendranaFront, &[PickupType::PlasmaBeam, PickupType::SpiderBall]),
+    ("Phendrana Drifts", "Chapel of the Elders", Region::PhendranaFront, &[PickupType::WaveBeam]),
+    ("Phendrana Drifts", "Ruined Courtyard", Region::PhendranaFront, &[PickupType::WaveBeam, PickupType::SpiderBall, PickupType::BoostBall]),
+    ("Phendrana Drifts", "Phendrana Canyon", Region::PhendranaFront, &[PickupType::BoostBall]),
+    ("Phendrana Drifts", "Quarantine Cave", Region::PhendranaDeep, &[]),
+    ("Phendrana Drifts", "Research Lab Hydra", Region::PhendranaLabs, &[PickupType::SuperMissile, PickupType::ChargeBeam]),
+    ("Phendrana Drifts", "Quarantine Monitor", Region::PhendranaDeep, &[]),
+    ("Phendrana Drifts", "Observatory", Region::PhendranaLabs, &[]),
+    ("Phendrana Drifts", "Transport Access", Region::PhendranaDeep, &[PickupType::PlasmaBeam]),
+    ("Phendrana Drifts", "Control Tower", Region::PhendranaLabs, &[PickupType::PlasmaBeam]),
+    ("Phendrana Drifts", "Research Core", Region::PhendranaLabs, &[PickupType::ThermalVisor]),
+    ("Phendrana Drifts", "Frost Cave", Region::PhendranaDeep, &[PickupType::GravitySuit]),
+    ("Phendrana Drifts", "Research Lab Aether", Region::PhendranaLabs, &[]),
+    ("Phendrana Drifts", "Gravity Chamber", Region::PhendranaDeep, &[PickupType::GravitySuit, PickupType::PlasmaBeam]),
+    ("Phendrana Drifts", "Storage Cave", Region::PhendranaDeep, &[PickupType::PlasmaBeam]),
+    ("Phendrana Drifts", "Security Cave", Region::PhendranaDeep, &[]),
+    ("Tallon Overworld", "Landing Site", Region::TallonFront, &[PickupType::MorphBall]),
+    ("Tallon Overworld", "Alcove", Region::TallonFront, &[PickupType::SpaceJumpBoots]),
+    ("Tallon Overworld", "Frigate Crash Site", Region::TallonBack, &[PickupType::GravitySuit]),
+    ("Tallon Overworld", "Overgrown Cavern", Region::TallonBack, &[PickupType::IceBeam]),
+    ("Tallon Overworld", "Root Cave", Region::TallonBack, &[PickupType::GrappleBeam, PickupType::XRayVisor]),
+    ("Tallon Overworld", "Artifact Temple", Region::TallonFront, &[PickupType::Missile]),
+    ("Tallon Overworld", "Transport Tunnel B", Region::TallonBack, &[]),
+    ("Tallon Overworld", "Arbor Chamber", Region::TallonBack, &[PickupType::GrappleBeam, PickupType::XRayVisor]),
+    ("Tallon Overworld", "Cargo Freight Lift to Deck Gamma", Region::TallonFrigate, &[]),
+    ("Tallon Overworld", "Biohazard Containment", Region::TallonFrigate, &[PickupType::SuperMissile, PickupType::ChargeBeam]),
+    ("Tallon Overworld", "Hydro Access Tunnel", Region::TallonFrigate, &[PickupType::BoostBall]),
+    ("Tallon Overworld", "Great Tree Chamber", Region::TallonBack, &[PickupType::IceBeam, PickupType::XRayVisor]),
+    ("Tallon Overworld", "Life Grove Tunnel", Region::TallonBack, &[PickupType::IceBeam, PickupType::PowerBomb, PickupType::BoostBall]),
+    ("Tallon Overworld", "Life Grove", Region::TallonBack, &[PickupType::IceBeam, PickupType::PowerBomb, PickupType::BoostBall, PickupType::SpiderBall, PickupType::XRayVisor]),
+    ("Phazon Mines", "Main Quarry", Region::MinesUpper, &[PickupType::ThermalVisor]),
+    ("Phazon Mines", "Security Access A", Region::MinesUpper, &[]),
+    ("Phazon Mines", "Storage Depot B", Region::MinesUpper, &[]),
+    ("Phazon Mines", "Storage Depot A", Region::MinesUpper, &[PickupType::PlasmaBeam]),
+    ("Phazon Mines", "Elite Research", Region::MinesUpper, &[PickupType::BoostBall]),
+    ("Phazon Mines", "Elite Control Access", Region::MinesUpper, &[]),
+    ("Phazon Mines", "Ventilation Shaft", Region::MinesLower, &[]),
+    ("Phazon Mines", "Phazon Processing Center", Region::MinesLower, &[]),
+    ("Phazon Mines", "Processing Center Access", Region::MinesLower, &[]),
+    ("Phazon Mines", "Elite Quarters", Region::MinesLower, &[]),
+    ("Phazon Mines", "Central Dynamo", Region::MinesLower, &[]),
+    ("Phazon Mines", "Metroid Quarantine B", Region::MinesLower, &[PickupType::SuperMissile, PickupType::ChargeBeam]),
+    ("Phazon Mines", "Metroid Quarantine A", Region::MinesLower, &[]),
+    ("Phazon Mines", "Fungal Hall B", Region::MinesLower, &[]),
+    ("Phazon Mines", "Phazon Mining Tunnel", Region::MinesLower, &[PickupType::PhazonSuit]),
+    ("Phazon Mines", "Fungal Hall Access", Region::MinesLower, &[]),
+    ("Magmoor Caverns", "Lava Lake", Region::MagmoorNorth, &[PickupType::SpaceJumpBoots]),
+    ("Magmoor Caverns", "Triclops Pit", Region::MagmoorNorth, &[PickupType::SpaceJumpBoots, PickupType::XRayVisor]),
+    ("Magmoor Caverns", "Storage Cavern", Region::MagmoorNorth, &[]),
+    ("Magmoor Caverns", "Transport Tunnel A", Region::MagmoorNorth, &[]),
+    ("Magmoor Caverns", "Warrior Shrine", Region::MagmoorNorth, &[PickupType::SpaceJumpBoots]),
+    ("Magmoor Caverns", "Shore Tunnel", Region::MagmoorNorth, &[PickupType::SpaceJumpBoots, PickupType::PowerBomb]),
+    ("Magmoor Caverns", "Fiery Shores", Region::MagmoorNorth, &[PickupType::SpaceJumpBoots, PickupType::PowerBomb]),
+    ("Magmoor Caverns", "Plasma Processing", Region::MagmoorSouth, &[PickupType::PlasmaBeam, PickupType::SpiderBall, PickupType::BoostBall, PickupType::PowerBomb]),
+    ("Magmoor Caverns", "Magmoor Workstation", Region::MagmoorSouth, &[PickupType::ThermalVisor]),
+];
+
+// What it takes to reach and defeat Metroid Prime, on top of the artifacts
+const FINAL_BOSS_REQUIREMENTS: [PickupType; 11] = [
+    PickupType::Missile,
+    PickupType::MorphBall,
+    PickupType::MorphBallBomb,
+    PickupType::SpaceJumpBoots,
+    PickupType::PowerBomb,
+    PickupType::IceBeam,
+    PickupType::WaveBeam,
+    PickupType::PlasmaBeam,
+    PickupType::XRayVisor,
+    PickupType::ThermalVisor,
+    PickupType::PhazonSuit,
+];
+
+struct Location {
+    world: &'static str,
+    room: &'static str,
+    requirements: Vec<PickupType>,
+}
+
+fn pickup_locations() -> Result<Vec<Location>, String> {
+    let mut locations = Vec::new();
+    for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
+        let world = World::from_pak(pak_name).unwrap().to_json_key();
+        for room_info in rooms.iter() {
+            if room_info.pickup_locations.is_empty() {
+                continue;
+            }
+
+            let (_, _, region, items) = LOCATION_LOGIC
+                .iter()
+                .find(|(w, r, _, _)| *w == world && *r == room_info.name())
+                .ok_or_else(|| format!("There is no logic for {} - {}", world, room_info.name()))?;
+            let mut requirements = region.requirements().to_vec();
+            requirements.extend(items.iter().filter(|pt| !requirements.contains(pt)));
+
+            for _ in room_info.pickup_locations.iter() {
+                locations.push(Location {
+                    world,
+                    room: room_info.name(),
+                    requirements: requirements.clone(),
+                });
+            }
+        }
+    }
+    Ok(locations)
+}
+
+fn starting_pickups(starting_items: Option<&StartingItems>) -> HashSet<PickupType> {
+    let si = match starting_items {
+        Some(si) => si,
+        None => return HashSet::new(),
+    };
+    [
+        (PickupType::Missile, si.missiles > 0),
+        (PickupType::EnergyTank, si.energy_tanks > 0),
+        (PickupType::PowerBomb, si.power_bombs > 0),
+        (PickupType::WaveBeam, si.wave),
+        (PickupType::IceBeam, si.ice),
+        (PickupType::PlasmaBeam, si.plasma),
+        (PickupType::ChargeBeam, si.charge),
+        (PickupType::MorphBall, si.morph_ball),
+        (PickupType::MorphBallBomb, si.bombs),
+        (PickupType::SpiderBall, si.spider_ball),
+        (PickupType::BoostBall, si.boost_ball),
+        (PickupType::VariaSuit, si.varia_suit),
+        (PickupType::GravitySuit, si.gravity_suit),
+        (PickupType::PhazonSuit, si.phazon_suit),
+        (PickupType::ThermalVisor, si.thermal_visor),
+        (PickupType::XRayVisor, si.xray),
+        (PickupType::SpaceJumpBoots, si.space_jump),
+        (PickupType::GrappleBeam, si.grapple),
+        (PickupType::SuperMissile, si.super_missile),
+        (PickupType::Wavebuster, si.wavebuster),
+        (PickupType::IceSpreader, si.ice_spreader),
+        (PickupType::Flamethrower, si.flamethrower),
+    ]
+    .into_iter()
+    .filter(|(_, has)| *has)
+    .map(|(pt, _)| pt)
+    .collect()
+}
+
+// Starting from the Landing Site, collects every item which can be reached with those collected so
+// far, until no more can be. Returns the items collected and whether each location was reached.
+fn collect_reachable(
+    locations: &[Location],
+    placement: &[Option<PickupType>],
+    mut items: HashSet<PickupType>,
+) -> (HashSet<PickupType>, Vec<bool>) {
+    let mut reached = vec![false; locations.len()];
+    loop {
+        let mut progress = false;
+        for (i, location) in locations.iter().enumerate() {
+            if reached[i] || !location.requirements.iter().all(|pt| items.contains(pt)) {
+                continue;
+            }
+            reached[i] = true;
+            if let Some(pickup_type) = placement[i] {
+                progress |= items.insert(pickup_type);
+            }
+        }
+        if !progress {
+            return (items, reached);
+        }
+    }
+}
+
+/// Simulates collecting the items of `placement` from the start of the game, and checks that it
+/// ends with everything needed to enter Impact Crater and defeat Metroid Prime
+fn check_completable(
+    locations: &[Location],
+    placement: &[Option<PickupType>],
+    starting_items: Option<&StartingItems>,
+) -> Result<(), String> {
+    let (items, _) = collect_reachable(locations, placement, starting_pickups(starting_items));
+
+    let missing = ARTIFACTS
+        .iter()
+        .chain(FINAL_BOSS_REQUIREMENTS.iter())
+        .filter(|pt| !items.contains(pt))
+        .map(|pt| pt.name())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        Err(format!(
+            "The layout can't be completed, {} can't be collected",
+            missing.join(", ")
+        ))?;
+    }
+
+    Ok(())
+}
+
+// Assumed fill: each item which some location needs is placed, in a random order, at a random
+// empty location reachable with the items which remain to be placed. So every item can be
+// collected before it's needed, whatever the order the player goes in. The other items (e.g.
+// artifacts) are then spread over the locations left, which are all reachable at that point.
+fn try_assumed_fill(
+    locations: &[Location],
+    pool: &[PickupType],
+    start: &HashSet<PickupType>,
+    rng: &mut StdRng,
+) -> Option<Vec<Option<PickupType>>> {
+    let mut pool = pool.to_vec();
+    pool.shuffle(rng);
+
+    // Only the first of each item unlocks anything, the copies are placed with the rest
+    let mut progression = Vec::new();
+    let mut others = Vec::new();
+    for pickup_type in pool {
+        let unlocks = locations
+            .iter()
+            .any(|l| l.requirements.contains(&pickup_type));
+        if unlocks && !progression.contains(&pickup_type) && !start.contains(&pickup_type) {
+            progression.push(pickup_type);
+        } else {
+            others.push(pickup_type);
+        }
+    }
+
+    let mut placement = vec![None; locations.len()];
+    while let Some(pickup_type) = progression.pop() {
+        let mut assumed = start.clone();
+        assumed.extend(progression.iter().copied());
+        let (_, reached) = collect_reachable(locations, &placement, assumed);
+
+        let empty = (0..locations.len())
+            .filter(|i| reached[*i] && placement[*i].is_none())
+            .collect::<Vec<_>>();
+        placement[*empty.choose(rng)?] = Some(pickup_type);
+    }
+
+    let mut empty = (0..locations.len())
+        .filter(|i| placement[*i].is_none())
+        .collect::<Vec<_>>();
+    empty.shuffle(rng);
+    for (index, pickup_type) in empty.into_iter().zip(others) {
+        placement[index] = Some(pickup_type);
+    }
+
+    Some(placement)
+}
+
+// Few locations can be reached at the start, so an unlucky order can leave no room for the last
+// items placed. Those attempts are thrown away and the fill tried again with another order.
+const ASSUMED_FILL_ATTEMPTS: usize = 100;
+
+fn assumed_fill(
+    locations: &[Location],
+    pool: Vec<PickupType>,
+    starting_items: Option<&StartingItems>,
+    rng: &mut StdRng,
+) -> Result<Vec<Option<PickupType>>, String> {
+    let start = starting_pickups(starting_items);
+    for _ in 0..ASSUMED_FILL_ATTEMPTS {
+        if let Some(placement) = try_assumed_fill(locations, &pool, &start, rng) {
+            return Ok(placement);
+        }
+    }
+
+    Err(format!(
+        "Could not place the item pool in {} attempts, the locations which can be reached at the start are all needed for other items",
+        ASSUMED_FILL_ATTEMPTS
+    ))
+}
+
+/// Places the item pool into the vanilla pickup locations and returns the resulting layout
+/// as a config JSON which can be passed to the patcher.
+pub fn generate_layout(settings: &GeneratorSettings) -> Result<Value, String> {
+    let seed = settings.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let pool = match settings.item_pool.as_ref() {
+        Some(item_pool) => parse_item_pool(item_pool)?,
+        None => vanilla_item_pool(),
+    };
+
+    let locations = pickup_locations()?;
+    if pool.len() > locations.len() {
+        Err(format!(
+            "The item pool has {} items, but there are only {} locations",
+            pool.len(),
+            locations.len()
+        ))?;
+    }
+
+    let placement = if settings.trick_level.unwrap_or(TrickLevel::Standard) == TrickLevel::NoLogic {
+        let mut pool = pool.into_iter().map(Some).collect::<Vec<_>>();
+        pool.resize(locations.len(), None);
+        pool.shuffle(&mut rng);
+        pool
+    } else {
+        let placement = assumed_fill(&locations, pool, settings.starting_items.as_ref(), &mut rng)?;
+        check_completable(&locations, &placement, settings.starting_items.as_ref())?;
+        placement
+    };
+
+    let mut level_data = Map::new();
+    for (location, pickup_type) in locations.iter().zip(placement) {
+        let pickup_type = pickup_type.unwrap_or(PickupType::Nothing);
+        let rooms = level_data
+            .entry(location.world)
+            .or_insert_with(|| json!({ "rooms": {} }))["rooms"]
+            .as_object_mut()
+            .unwrap();
+        let pickups = rooms
+            .entry(location.room)
+            .or_insert_with(|| json!({ "pickups": [] }))["pickups"]
+            .as_array_mut()
+            .unwrap();
+        pickups.push(json!({ "type": pickup_type.name() }));
+    }
+
+    let mut layout = json!({
+        "seed": seed,
+        "levelData": level_data,
+    });
+    if let Some(starting_items) = settings.starting_items.as_ref() {
+        layout["gameConfig"] = json!({ "startingItems": starting_items });
+    }
+
+    Ok(layout)
+}
+
+fn is_expansion(pickup_type: PickupType) -> bool {
+    [
+        PickupType::Missile,
+        PickupType::EnergyTank,
+        PickupType::PowerBomb,
+    ]
+    .contains(&pickup_type)
+}
+
+/// Replaces `random`, `random:major` and `random:expansion` pickup types with items from the
+/// vanilla item pool which the layout doesn't already place.
+pub fn fill_random_placeholders(
+    level_data: &mut HashMap<String, LevelConfig>,
+    seed: u64,
+) -> Result<(), String> {
+    // Sort so that the same seed gives the same layout
+    let mut pickups: Vec<&mut PickupConfig> = Vec::new();
+    let mut worlds: Vec<_> = level_data.iter_mut().collect();
+    worlds.sort_by(|a, b| a.0.cmp(b.0));
+    for (_, level) in worlds {
+        let mut rooms: Vec<_> = level.rooms.iter_mut().collect();
+        rooms.sort_by(|a, b| a.0.cmp(b.0));
+        for (_, room) in rooms {
+            pickups.extend(room.pickups.iter_mut().flatten());
+        }
+    }
+
+    let mut pool = vanilla_item_pool();
+    for pickup in pickups.iter() {
+        let placed =
+            PickupType::iter().find(|pt| pt.name().eq_ignore_ascii_case(&pickup.pickup_type));
+        if let Some(index) = placed.and_then(|placed| pool.iter().position(|pt| *pt == placed)) {
+            pool.remove(index);
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    pool.shuffle(&mut rng);
+
+    for pickup in pickups {
+        let placeholder = pickup.pickup_type.to_lowercase();
+        let matches: fn(PickupType) -> bool = match placeholder.trim() {
+            "random" => |_| true,
+            "random:major" => |pt| !is_expansion(pt),
+            "random:expansion" => is_expansion,
+            _ => continue,
+        };
+
+        let index = pool.iter().position(|pt| matches(*pt)).ok_or_else(|| {
+            format!(
+                "There are no items left in the pool to fill '{}'",
+                pickup.pickup_type
+            )
+        })?;
+        pickup.pickup_type = pool.remove(index).name().to_string();
+    }
+
+    Ok(())
+}
+
+/// Gives every nothing pickup which doesn't set its own model or hudmemo text one picked from the
+/// pools of `config`, so that they don't all look the same.
+pub fn fill_nothing_cosmetics(
+    level_data: &mut HashMap<String, LevelConfig>,
+    config: &NothingCosmeticsConfig,
+    seed: u64,
+) {
+    let models = config.models.as_deref().unwrap_or_default();
+    let hudmemo_texts = config.hudmemo_texts.as_deref().unwrap_or_default();
+
+    // Sort so that the same seed gives the same looks
+    let mut pickups: Vec<&mut PickupConfig> = Vec::new();
+    let mut worlds: Vec<_> = level_data.iter_mut().collect();
+    worlds.sort_by(|a, b| a.0.cmp(b.0));
+    for (_, level) in worlds {
+        let mut rooms: Vec<_> = level.rooms.iter_mut().collect();
+        rooms.sort_by(|a, b| a.0.cmp(b.0));
+        for (_, room) in rooms {
+            pickups.extend(room.pickups.iter_mut().flatten());
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    for pickup in pickups {
+        if !pickup.pickup_type.trim().eq_ignore_ascii_case("nothing") {
+            continue;
+        }
+
+        // Always rolled, so that setting one pickup's model doesn't change the others
+        let model = models.choose(&mut rng);
+        let hudmemo_text = hudmemo_texts.choose(&mut rng);
+        if pickup.model.is_none() {
+            pickup.model = model.cloned();
+        }
+        if pickup.hudmemo_text.is_none() {
+            pickup.hudmemo_text = hudmemo_text.cloned();
+        }
+    }
+}
+
+/// Picks the audio played by each elevator when it's activated, from the pool of the world it
+/// leads to, or else from the shared pool of `config`. Keyed by the MREA of the elevator's room.
+pub fn pick_elevator_audio(
+    level_data: &HashMap<String, LevelConfig>,
+    config: &ElevatorAudioConfig,
+    seed: u64,
+) -> HashMap<u32, AttainmentAudio> {
+    let mut destinations: HashMap<Elevator, u32> = HashMap::new();
+    for level in level_data.values() {
+        for (elevator_name, destination_name) in level.transports.iter() {
+            if let Some(elevator) = Elevator::from_str(elevator_name) {
+                destinations.insert(elevator, SpawnRoomData::from_str(destination_name).mlvl);
+            }
+        }
+    }
+
+    let files = config.files.as_deref().unwrap_or_default();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut elevator_audio = HashMap::new();
+    for elevator in Elevator::iter() {
+        let mlvl = destinations
+            .get(&elevator)
+            .copied()
+            .unwrap_or(elevator.default_dest.mlvl);
+        let pool = config
+            .destinations
+            .iter()
+            .flatten()
+            .find(|(world_name, _)| {
+                World::iter().any(|world| {
+                    world.mlvl() == mlvl
+                        && world_name.trim().to_lowercase() == world.to_json_key().to_lowercase()
+                })
+            })
+            .map(|(_, files)| &files[..])
+            .unwrap_or(files);
+
+        if let Some(file) = pool.choose(&mut rng) {
+            elevator_audio.insert(elevator.mrea, AttainmentAudio::File(file.clone()));
+        }
+    }
+
+    elevator_audio
+}
+
+#[test]
+fn test_generate_layout_is_completable() {
+    let locations = pickup_locations().unwrap();
+    for seed in 0..20 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let placement = assumed_fill(&locations, vanilla_item_pool(), None, &mut rng).unwrap();
+        check_completable(&locations, &placement, None).unwrap();
+    }
+
+    // Without Phazon Suit, Metroid Prime can't be defeated
+    let mut pool = vanilla_item_pool();
+    pool.retain(|pt| *pt != PickupType::PhazonSuit);
+    let mut rng = StdRng::seed_from_u64(0);
+    let placement = assumed_fill(&locations, pool, None, &mut rng).unwrap();
+    assert!(check_completable(&locations, &placement, None).is_err());
+}
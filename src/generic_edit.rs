@@ -41,7 +41,10 @@ pub fn patch_edit_objects(
 
     /* Move Objects */
 
-    for (obj_id, config) in edit_objs.iter() {
+    // Sorted so that moved objects always end up in the same order
+    let mut moved_objs: Vec<_> = edit_objs.iter().collect();
+    moved_objs.sort_by_key(|(obj_id, _)| **obj_id);
+    for (obj_id, config) in moved_objs {
         if config.layer.is_none() {
             continue;
         }
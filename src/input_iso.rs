@@ -0,0 +1,63 @@
+use std::{fmt, fs::File, io::Read, ops::Deref};
+
+use reader_writer::{Readable, Reader};
+
+/// The source ISO. It's memory-mapped when possible, so that resources are only read from the
+/// disk when the patcher needs them, and read into memory otherwise (e.g. when mapping isn't
+/// supported by the filesystem it's on).
+pub enum InputIso {
+    Mapped(memmap::Mmap),
+    Buffered(Vec<u8>, Option<String>), // why it couldn't be mapped, if it was attempted
+}
+
+impl InputIso {
+    pub fn open(path: &str, mmap: bool) -> Result<Self, String> {
+        let mut file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+
+        // The logger isn't set up while the config is being parsed, so a failure is only
+        // reported once patching starts
+        let mmap_error = if mmap {
+            match unsafe { memmap::Mmap::map(&file) } {
+                Ok(mmap) => return Ok(InputIso::Mapped(mmap)),
+                Err(e) => Some(e.to_string()),
+            }
+        } else {
+            None
+        };
+
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Ok(InputIso::Buffered(bytes, mmap_error))
+    }
+}
+
+impl Deref for InputIso {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            InputIso::Mapped(mmap) => &mmap[..],
+            InputIso::Buffered(bytes, _) => &bytes[..],
+        }
+    }
+}
+
+// Without printing the whole ISO
+impl fmt::Debug for InputIso {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputIso::Mapped(mmap) => write!(f, "InputIso::Mapped({} bytes)", mmap.len()),
+            InputIso::Buffered(bytes, _) => write!(f, "InputIso::Buffered({} bytes)", bytes.len()),
+        }
+    }
+}
+
+/// Reads only the header of an ISO, e.g. to tell its version before opening all of it
+pub fn read_header(path: &str) -> Result<structs::GcDiscHeader, String> {
+    let mut bytes = vec![0; structs::GcDiscHeader::fixed_size().unwrap()];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut bytes))
+        .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    Ok(Reader::new(&bytes[..]).read(()))
+}
@@ -9,23 +9,46 @@ use reader_writer::{LCow, Reader};
 pub use structs;
 
 pub mod add_modify_obj_patches;
+pub mod agsc_conversions;
+pub mod area_collision;
+pub mod area_dependencies;
+pub mod asset_browser;
+pub mod asset_cache;
+pub mod bps;
 pub mod c_interface;
 pub mod ciso_writer;
+pub mod cmdl_conversions;
 pub mod custom_assets;
 pub mod dol_patcher;
 pub mod door_meta;
 pub mod elevators;
 pub mod extern_assets;
 pub mod gcz_writer;
+pub mod generator;
 pub mod generic_edit;
+pub mod input_iso;
 pub mod mlvl_wrapper;
+pub mod pak_optimizer;
+pub mod particle_elements;
 pub mod patch_config;
+pub mod patch_log;
+pub mod patch_manifest;
 pub mod patcher;
 pub mod patches;
 pub mod pickup_meta;
+pub mod progress;
+pub mod resumable_writer;
+pub mod room_dump;
 pub mod room_lookup;
+pub mod scly_snapshot;
+pub mod scly_validation;
+pub mod sha1;
+pub mod sha256;
+pub mod softlock_fixes;
 pub mod starting_items;
+pub mod transliteration;
 pub mod txtr_conversions;
+pub mod version_compat;
 
 pub trait GcDiscLookupExtensions<'a> {
     fn find_file(&self, name: &str) -> Option<&structs::FstEntry<'a>>;
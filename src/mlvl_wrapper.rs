@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap, ffi::CString};
 
 use reader_writer::{CStr, CStrConversionExtension, FourCC, LazyArray};
 use structs::{
@@ -6,6 +6,20 @@ use structs::{
     SclyLayer, SclyObject,
 };
 
+/// Whether a layer is active when its room is first loaded
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LayerDefault {
+    Enabled,
+    Disabled,
+}
+
+/// An object of an area, which stays valid while other objects are added or removed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ObjectHandle {
+    pub layer: usize,
+    pub instance_id: u32,
+}
+
 pub struct MlvlEditor<'r> {
     pub mlvl: Mlvl<'r>,
 }
@@ -61,15 +75,74 @@ impl<'r, 'mlvl, 'cursor, 'list> MlvlArea<'r, 'mlvl, 'cursor, 'list> {
     }
 
     pub fn get_layer_id_from_name(&mut self, layer_name: &str) -> usize {
+        match self.layer_index(layer_name) {
+            Some(layer_id) => layer_id,
+            None => panic!("Layer {} doesn't exist", layer_name),
+        }
+    }
+
+    /// The index of the first layer named `layer_name`
+    pub fn layer_index(&self, layer_name: &str) -> Option<usize> {
         let layer_name_nul = format!("{}\0", layer_name);
         let c_layer_name = (layer_name_nul[..]).as_bytes().as_cstr();
-        let layer_id = self.layer_names.iter().position(|x| x.eq(&c_layer_name));
+        self.layer_names.iter().position(|x| x.eq(&c_layer_name))
+    }
+
+    /// Adds a layer, even if one with the same name exists, and returns its index
+    pub fn add_named_layer(&mut self, layer_name: &str, default: LayerDefault) -> usize {
+        let name = CString::new(layer_name).unwrap();
+        self.add_layer(Cow::Owned(name));
+        let layer_id = self.layer_flags.layer_count as usize - 1;
+        self.set_layer_default(layer_id, default);
+        layer_id
+    }
 
-        if layer_id.is_none() {
-            panic!("Layer {} doesn't exist", layer_name);
+    /// Returns the index of the layer named `layer_name`, adding it if there's none. `default`
+    /// only applies to a new layer.
+    pub fn get_or_add_layer(&mut self, layer_name: &str, default: LayerDefault) -> usize {
+        match self.layer_index(layer_name) {
+            Some(layer_id) => layer_id,
+            None => self.add_named_layer(layer_name, default),
         }
+    }
 
-        layer_id.unwrap()
+    pub fn layer_default(&self, layer_id: usize) -> LayerDefault {
+        if self.layer_flags.flags & (1 << layer_id) != 0 {
+            LayerDefault::Enabled
+        } else {
+            LayerDefault::Disabled
+        }
+    }
+
+    pub fn set_layer_default(&mut self, layer_id: usize, default: LayerDefault) {
+        assert!(layer_id < self.layer_flags.layer_count as usize);
+        match default {
+            LayerDefault::Enabled => self.layer_flags.flags |= 1 << layer_id,
+            LayerDefault::Disabled => self.layer_flags.flags &= !(1 << layer_id),
+        }
+    }
+
+    /// Handles to the objects of a layer, in order
+    pub fn layer_objects(&mut self, layer_id: usize) -> Vec<ObjectHandle> {
+        let scly = self.mrea().scly_section();
+        let layer = scly.layers.get(layer_id).unwrap();
+        let handles = layer
+            .objects
+            .iter()
+            .map(|obj| ObjectHandle {
+                layer: layer_id,
+                instance_id: obj.instance_id,
+            })
+            .collect();
+        handles
+    }
+
+    pub fn object_mut(&mut self, handle: ObjectHandle) -> Option<&mut SclyObject<'r>> {
+        self.mrea().scly_section_mut().layers.as_mut_vec()[handle.layer]
+            .objects
+            .as_mut_vec()
+            .iter_mut()
+            .find(|obj| obj.instance_id == handle.instance_id)
     }
 
     pub fn object_id_from_layer_name(&mut self, layer_name: &str, internal_idx: usize) -> u32 {
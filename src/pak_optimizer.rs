@@ -0,0 +1,126 @@
+// Rebuilds the world PAKs once all of the patches have been applied.
+//
+// Patching leaves duplicate copies of some resources and resources nothing depends on anymore
+// (e.g. the models of removed objects). This pass drops both, then re-orders each PAK so that an
+// area's dependencies are stored directly before its MREA, like the original PAKs are laid out.
+
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use reader_writer::{FourCC, Reader, Writable};
+use structs::{GcDisc, Resource};
+
+// Resources the engine loads on its own, rather than through an area's dependency list
+const PRESERVED_FOURCCS: &[&[u8; 4]] = &[b"MLVL", b"MAPW", b"MAPA", b"SAVW", b"AGSC"];
+
+fn optimize_pak(pak: &mut structs::Pak) -> Result<(), String> {
+    // The MLVL has been serialized back to bytes if it was patched, so it's read from those
+    let (mlvl_id, mlvl_bytes) = match pak
+        .resources
+        .iter()
+        .find(|res| res.fourcc() == b"MLVL".into())
+    {
+        Some(res) => {
+            let mut bytes = vec![];
+            res.write_to(&mut bytes)
+                .map_err(|e| format!("Failed to read the MLVL: {}", e))?;
+            (res.file_id, bytes)
+        }
+        // Gutted (e.g. frigate skip) or not a world PAK
+        None => return Ok(()),
+    };
+    let mlvl: structs::Mlvl = Reader::new(&mlvl_bytes[..]).read(());
+
+    // Keep the first copy of each resource
+    let mut resources: Vec<Resource> = Vec::with_capacity(pak.resources.len());
+    let mut indices: HashMap<(u32, FourCC), usize> = HashMap::new();
+    for res in pak.resources.iter() {
+        let key = (res.file_id, res.fourcc());
+        if let Entry::Vacant(entry) = indices.entry(key) {
+            entry.insert(resources.len());
+            resources.push(res.into_owned());
+        }
+    }
+
+    let mut world_resources: Vec<(u32, FourCC)> = vec![
+        (mlvl_id, b"MLVL".into()),
+        (mlvl.world_name_strg.to_u32(), b"STRG".into()),
+        (mlvl.world_savw.to_u32(), b"SAVW".into()),
+        (mlvl.default_skybox_cmdl.to_u32(), b"CMDL".into()),
+        (mlvl.world_map_mapw, b"MAPW".into()),
+    ];
+    world_resources.extend(
+        mlvl.audio_groups
+            .iter()
+            .map(|group| (group.agsc.to_u32(), b"AGSC".into())),
+    );
+    world_resources.extend(
+        pak.named_resources
+            .iter()
+            .map(|named| (named.file_id, named.fourcc)),
+    );
+
+    let mut order = Vec::with_capacity(resources.len());
+    let mut placed = vec![false; resources.len()];
+    let mut place = |key: &(u32, FourCC), order: &mut Vec<usize>| {
+        if let Some(&i) = indices.get(key) {
+            if !placed[i] {
+                placed[i] = true;
+                order.push(i);
+            }
+        }
+    };
+
+    for key in world_resources.iter() {
+        place(key, &mut order);
+    }
+    for area in mlvl.areas.iter() {
+        place(&(area.area_name_strg.to_u32(), b"STRG".into()), &mut order);
+        for layer in area.dependencies.deps.iter() {
+            for dep in layer.iter() {
+                place(&(dep.asset_id, dep.asset_type), &mut order);
+            }
+        }
+        place(&(area.mrea.to_u32(), b"MREA".into()), &mut order);
+    }
+
+    // Anything the engine loads by type stays, in its original position relative to the rest
+    let preserved: HashSet<FourCC> = PRESERVED_FOURCCS
+        .iter()
+        .map(|fourcc| FourCC::from_bytes(fourcc))
+        .collect();
+    for (i, res) in resources.iter().enumerate() {
+        if !placed[i] && preserved.contains(&res.fourcc()) {
+            placed[i] = true;
+            order.push(i);
+        }
+    }
+
+    let mut resources: Vec<Option<Resource>> = resources.into_iter().map(Some).collect();
+    pak.resources = order
+        .into_iter()
+        .map(|i| resources[i].take().unwrap())
+        .collect();
+
+    Ok(())
+}
+
+pub fn optimize_paks(gc_disc: &mut GcDisc) -> Result<(), String> {
+    for (name, fst_entry) in gc_disc.file_system_root.dir_files_iter_mut() {
+        if !name.starts_with(b"Metroid") || !name.ends_with(b".pak") {
+            continue;
+        }
+
+        fst_entry.guess_kind();
+        if let Some(structs::FstEntryFile::Pak(pak)) = fst_entry.file_mut() {
+            optimize_pak(pak).map_err(|e| {
+                format!(
+                    "Failed to optimize {}: {}",
+                    String::from_utf8_lossy(&name),
+                    e
+                )
+            })?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,518 @@
+// Walks the element trees of the particle formats (PART, ELSC, SWHC, WPSC, CRSC and DPSC) to find
+// the assets they reference and the constant colors they use, without building the elements.
+//
+// Every format is a magic followed by (property, element) pairs up to an "_END" property. Each
+// element is a class fourcc and the arguments of that class, which are mostly elements themselves,
+// so the type of every property and argument has to be known to get to the next one. Anything
+// unknown is an error rather than a guess, as skipping the wrong number of bytes would make the
+// rest of the file be read as something it isn't.
+
+// Offsets of the asset ids and of the colors of a particle file
+#[derive(Default, Debug)]
+pub struct ParticleElements {
+    pub asset_refs: Vec<usize>,
+    // The offsets of the red, green and blue f32s of every constant color
+    pub colors: Vec<[usize; 3]>,
+}
+
+#[derive(Copy, Clone)]
+enum Element {
+    Real,
+    Int,
+    Vector,
+    ModVector,
+    Color,
+    Emitter,
+    Uv,
+    Bool,
+    // A particle, model, swoosh, electric, decal or collision response, "NONE" or a class and id
+    Asset,
+    // Same encoding as Asset, but the id is that of a sound
+    Sound,
+    SpawnSystem,
+}
+
+fn part_property(property: &[u8; 4]) -> Option<Element> {
+    use Element::*;
+    Some(match property {
+        b"PMCL" | b"COLR" | b"LCLR" => Color,
+        b"LFOR" | b"GRTE" | b"LENG" | b"LINT" | b"LSLA" | b"PSTS" | b"ROTA" | b"SIZE" | b"WIDT"
+        | b"ADV1" | b"ADV2" | b"ADV3" | b"ADV4" | b"ADV5" | b"ADV6" | b"ADV7" | b"ADV8" => Real,
+        b"CSSD" | b"LFOT" | b"LTYP" | b"LTME" | b"MAXP" | b"MBDM" | b"MBSP" | b"NCSY" | b"NDSY"
+        | b"PSLT" | b"PISY" | b"PSWT" | b"SEED" | b"SISY" | b"SSSD" | b"SESD" => Int,
+        b"ILOC" | b"IVEC" | b"LDIR" | b"LOFF" | b"PMOP" | b"PMRT" | b"PMSC" | b"PSIV" | b"PSOV"
+        | b"POFS" | b"SSPO" | b"SEPO" => Vector,
+        b"PSVM" | b"VEL1" | b"VEL2" | b"VEL3" | b"VEL4" => ModVector,
+        b"CIND" | b"AAPH" | b"FXLL" | b"LINE" | b"LIT_" | b"MBLR" | b"ORNT" | b"OPTS" | b"PMAB"
+        | b"PMUS" | b"PMOO" | b"RSOP" | b"SORT" | b"VMD1" | b"VMD2" | b"VMD3" | b"VMD4"
+        | b"ZBUF" => Bool,
+        b"IDTS" | b"ICTS" | b"IITS" | b"PMDL" | b"SELC" | b"SSWH" => Asset,
+        b"EMTR" => Emitter,
+        b"TEXR" | b"TIND" => Uv,
+        b"KSSM" => SpawnSystem,
+        _ => return None,
+    })
+}
+
+fn elsc_property(property: &[u8; 4]) -> Option<Element> {
+    use Element::*;
+    Some(match property {
+        b"LIFE" | b"SLIF" | b"SCNT" | b"SSEG" => Int,
+        b"GRAT" | b"AMPL" | b"AMPD" | b"LWD1" | b"LWD2" | b"LWD3" => Real,
+        b"COLR" | b"LCL1" | b"LCL2" | b"LCL3" => Color,
+        b"IEMT" | b"FEMT" => Emitter,
+        b"SSWH" | b"GPSM" | b"EPSM" => Asset,
+        b"ZERY" => Bool,
+        _ => return None,
+    })
+}
+
+fn swhc_property(property: &[u8; 4]) -> Option<Element> {
+    use Element::*;
+    Some(match property {
+        b"PSLT" | b"LENG" | b"SIDE" | b"SPLN" | b"TSPN" => Int,
+        b"TIME" | b"LRAD" | b"RRAD" | b"IROT" | b"ROTM" => Real,
+        b"COLR" => Color,
+        b"POFS" | b"IVEL" | b"NPOS" => Vector,
+        b"VELM" | b"VLM2" => ModVector,
+        b"TEXR" => Uv,
+        b"LLRD" | b"CROS" | b"VLS1" | b"VLS2" | b"SROT" | b"WIRE" | b"TEXW" | b"AALP" | b"ZBUF"
+        | b"ORNT" | b"CRND" => Bool,
+        _ => return None,
+    })
+}
+
+fn wpsc_property(property: &[u8; 4]) -> Option<Element> {
+    use Element::*;
+    Some(match property {
+        b"IORN" | b"IVEC" | b"PSOV" | b"PSCL" | b"POFS" | b"OFST" => Vector,
+        b"PSVM" => ModVector,
+        b"PSLT" => Int,
+        b"TRAT" | b"RNGE" | b"FOFF" => Real,
+        b"PCOL" => Color,
+        b"VMD2" | b"APSO" | b"HOMG" | b"AP11" | b"AP21" | b"AS11" | b"AS12" | b"AS13" | b"EWTR"
+        | b"LWTR" | b"SWTR" => Bool,
+        b"APSM" | b"APS2" | b"ASW1" | b"ASW2" | b"ASW3" | b"OHEF" | b"COLR" => Asset,
+        b"PJFX" => Sound,
+        _ => return None,
+    })
+}
+
+// Apart from its range and offset, every property of a CRSC is a particle, decal or sound. These
+// are encoded alike, so the ids of the sounds are reported as asset references too: callers only
+// use those that are the id of an asset.
+fn crsc_property(property: &[u8; 4]) -> Option<Element> {
+    match property {
+        b"RNGE" | b"FOFF" => Some(Element::Real),
+        _ => Some(Element::Asset),
+    }
+}
+
+fn dpsc_property(property: &[u8; 4]) -> Option<Element> {
+    use Element::*;
+    Some(match property {
+        b"1SZE" | b"1ROT" | b"2SZE" | b"2ROT" => Real,
+        b"1LFT" | b"2LFT" | b"DLFT" => Int,
+        b"1OFF" | b"2OFF" | b"DMOO" | b"DMOP" | b"DMRT" | b"DMSC" => Vector,
+        b"1CLR" | b"2CLR" | b"DMCL" => Color,
+        b"1TEX" | b"2TEX" => Uv,
+        b"1ADD" | b"2ADD" | b"DMAB" => Bool,
+        b"DMDL" => Asset,
+        _ => return None,
+    })
+}
+
+struct Walker<'a> {
+    data: &'a [u8],
+    pos: usize,
+    elements: ParticleElements,
+}
+
+impl<'a> Walker<'a> {
+    fn skip(&mut self, len: usize) -> Result<usize, String> {
+        let start = self.pos;
+        if start + len > self.data.len() {
+            Err(format!(
+                "Unexpected end of particle data at offset 0x{:X}",
+                start
+            ))?
+        }
+        self.pos += len;
+        Ok(start)
+    }
+
+    fn fourcc(&mut self) -> Result<[u8; 4], String> {
+        let start = self.skip(4)?;
+        Ok(self.data[start..start + 4].try_into().unwrap())
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        self.fourcc().map(u32::from_be_bytes)
+    }
+
+    fn unknown_class(&self, element: &str, class: [u8; 4]) -> String {
+        format!(
+            "Unknown {} element '{}' at offset 0x{:X} of particle data",
+            element,
+            String::from_utf8_lossy(&class),
+            self.pos - 4
+        )
+    }
+
+    // The keyframe emitters of every element type share a header, followed by `count` keys
+    fn keyframes(&mut self, key_size: usize) -> Result<(usize, usize), String> {
+        // Percent, unknown, loop, unknown, loop end, loop start
+        self.skip(4 + 4 + 1 + 1 + 4 + 4)?;
+        let count = self.u32()? as usize;
+        let keys = self.skip(count * key_size)?;
+        Ok((keys, count))
+    }
+
+    fn element(&mut self, element: Element) -> Result<(), String> {
+        match element {
+            Element::Real => self.real().map(|_| ()),
+            Element::Int => self.int(),
+            Element::Vector => self.vector(),
+            Element::ModVector => self.mod_vector(),
+            Element::Color => self.color(true),
+            Element::Emitter => self.emitter(),
+            Element::Uv => self.uv(),
+            Element::Bool => self.bool(),
+            Element::Asset => self.asset(true),
+            Element::Sound => self.asset(false),
+            Element::SpawnSystem => self.spawn_system(),
+        }
+    }
+
+    fn elements(&mut self, elements: &[Element]) -> Result<(), String> {
+        for element in elements {
+            self.element(*element)?;
+        }
+        Ok(())
+    }
+
+    // Returns the offset of the value if the element is a constant
+    fn real(&mut self) -> Result<Option<usize>, String> {
+        use Element::*;
+        let class = self.fourcc()?;
+        match &class {
+            b"CNST" => return self.skip(4).map(Some),
+            b"KEYE" | b"KEYP" => self.keyframes(4).map(|_| ())?,
+            b"LFTW" | b"CHG_" | b"ADD_" | b"IRND" | b"RAND" | b"MULT" | b"ISWT" | b"SUB_" => {
+                self.elements(&[Real, Real])?
+            }
+            b"CLMP" | b"SINE" => self.elements(&[Real, Real, Real])?,
+            b"CLTN" | b"CEQL" => self.elements(&[Real, Real, Real, Real])?,
+            b"CRNG" => self.elements(&[Real, Real, Real, Real, Real])?,
+            b"SPOS" | b"RLPT" => self.element(Real)?,
+            b"PULS" => self.elements(&[Int, Int, Real, Real])?,
+            b"DOTP" => self.elements(&[Vector, Vector])?,
+            b"VMAG" | b"VXTR" | b"VYTR" | b"VZTR" => self.element(Vector)?,
+            b"CEXT" => self.element(Int)?,
+            b"ITRL" => self.elements(&[Int, Real])?,
+            // These pick a component of a color, so it mustn't be recolored
+            b"GTCR" | b"GTCG" | b"GTCB" | b"GTCA" => self.color(false)?,
+            b"PAP1" | b"PAP2" | b"PAP3" | b"PAP4" | b"PAP5" | b"PAP6" | b"PAP7" | b"PAP8"
+            | b"PSLL" | b"PRLW" | b"NONE" => (),
+            _ => Err(self.unknown_class("real", class))?,
+        }
+        Ok(None)
+    }
+
+    fn int(&mut self) -> Result<(), String> {
+        use Element::*;
+        let class = self.fourcc()?;
+        match &class {
+            b"CNST" => self.skip(4).map(|_| ())?,
+            b"KEYE" | b"KEYP" => self.keyframes(4).map(|_| ())?,
+            b"DETH" | b"CHG_" | b"ADD_" | b"IRND" | b"RAND" | b"MULT" | b"DIVD" | b"MODU"
+            | b"SUB_" => self.elements(&[Int, Int])?,
+            b"CLMP" | b"SPAH" => self.elements(&[Int, Int, Int])?,
+            b"PULS" => self.elements(&[Int, Int, Int, Int])?,
+            b"IMPL" | b"ILPT" => self.element(Int)?,
+            b"RTOI" => self.elements(&[Real, Real])?,
+            b"TSCL" => self.element(Real)?,
+            b"GAPC" | b"GEMT" | b"GTE_" | b"NONE" => (),
+            _ => Err(self.unknown_class("int", class))?,
+        }
+        Ok(())
+    }
+
+    fn vector(&mut self) -> Result<(), String> {
+        use Element::*;
+        let class = self.fourcc()?;
+        match &class {
+            b"CNST" => self.elements(&[Real, Real, Real])?,
+            b"KEYE" | b"KEYP" => self.keyframes(12).map(|_| ())?,
+            b"CONE" => self.elements(&[Vector, Real])?,
+            b"CHAN" => self.elements(&[Vector, Vector, Int])?,
+            b"CIRC" => self.elements(&[Vector, Vector, Real, Real, Real])?,
+            b"ANGC" => self.elements(&[Real, Real, Real, Real, Real])?,
+            b"ADD_" | b"MULT" | b"SUB_" => self.elements(&[Vector, Vector])?,
+            b"CCLU" => self.elements(&[Vector, Vector, Int, Real])?,
+            b"RTOV" => self.element(Real)?,
+            b"PULS" => self.elements(&[Int, Int, Vector, Vector])?,
+            b"CTVC" => self.color(false)?,
+            b"PVEL" | b"PLCO" | b"PLOC" | b"PSOF" | b"PSOU" | b"PSOR" | b"PSTR" | b"NONE" => (),
+            _ => Err(self.unknown_class("vector", class))?,
+        }
+        Ok(())
+    }
+
+    fn mod_vector(&mut self) -> Result<(), String> {
+        use Element::*;
+        let class = self.fourcc()?;
+        match &class {
+            b"IMPL" | b"EMPL" | b"LMPL" => self.elements(&[Vector, Real, Real, Real, Bool])?,
+            b"CHAN" => self.elements(&[ModVector, ModVector, Int])?,
+            b"BNCE" => self.elements(&[Vector, Vector, Real, Real, Bool])?,
+            b"CNST" => self.elements(&[Real, Real, Real])?,
+            b"GRAV" | b"SPOS" => self.element(Vector)?,
+            b"EXPL" => self.elements(&[Real, Real])?,
+            b"PULS" => self.elements(&[Int, Int, ModVector, ModVector])?,
+            b"WIND" => self.elements(&[Vector, Real])?,
+            b"SWRL" => self.elements(&[Vector, Vector, Real, Real])?,
+            b"NONE" => (),
+            _ => Err(self.unknown_class("mod vector", class))?,
+        }
+        Ok(())
+    }
+
+    // Only the colors of color properties are reported, not those other elements are made from
+    fn color(&mut self, report: bool) -> Result<(), String> {
+        use Element::*;
+        let class = self.fourcc()?;
+        match &class {
+            b"CNST" => {
+                let rgb = [self.real()?, self.real()?, self.real()?];
+                self.real()?;
+                if let (true, [Some(r), Some(g), Some(b)]) = (report, rgb) {
+                    self.elements.colors.push([r, g, b]);
+                }
+            }
+            b"KEYE" | b"KEYP" => {
+                let (keys, count) = self.keyframes(16)?;
+                if report {
+                    for key in (0..count).map(|i| keys + i * 16) {
+                        self.elements.colors.push([key, key + 4, key + 8]);
+                    }
+                }
+            }
+            b"CHAN" => {
+                self.color(report)?;
+                self.color(report)?;
+                self.element(Int)?;
+            }
+            b"CFDE" | b"FADE" => {
+                self.color(report)?;
+                self.color(report)?;
+                self.elements(&[Real, Real])?;
+            }
+            b"PULS" => {
+                self.elements(&[Int, Int])?;
+                self.color(report)?;
+                self.color(report)?;
+            }
+            b"PCOL" | b"NONE" => (),
+            _ => Err(self.unknown_class("color", class))?,
+        }
+        Ok(())
+    }
+
+    fn emitter(&mut self) -> Result<(), String> {
+        use Element::*;
+        let class = self.fourcc()?;
+        match &class {
+            // The initial location and velocity, each only if tagged as such
+            b"SETR" => {
+                for tag in [b"ILOC", b"IVEC"] {
+                    let start = self.pos;
+                    if &self.fourcc()? == tag {
+                        self.element(Vector)?;
+                    } else {
+                        self.pos = start;
+                        break;
+                    }
+                }
+            }
+            b"SEMR" => self.elements(&[Vector, Vector])?,
+            b"SPHE" => self.elements(&[Vector, Real, Real])?,
+            b"ASPH" => self.elements(&[Vector, Real, Real, Real, Real, Real, Real])?,
+            b"NONE" => (),
+            _ => Err(self.unknown_class("emitter", class))?,
+        }
+        Ok(())
+    }
+
+    fn uv(&mut self) -> Result<(), String> {
+        use Element::*;
+        let class = self.fourcc()?;
+        match &class {
+            b"CNST" | b"ATEX" => {
+                // The type of the texture, then its id unless there is none
+                if &self.fourcc()? == b"NONE" {
+                    return Ok(());
+                }
+                let id = self.skip(4)?;
+                self.elements.asset_refs.push(id);
+                if &class == b"ATEX" {
+                    // Tile width and height, stride width and height, cycle frames, loop
+                    self.elements(&[Int, Int, Int, Int, Int, Bool])?;
+                }
+            }
+            b"NONE" => (),
+            _ => Err(self.unknown_class("UV", class))?,
+        }
+        Ok(())
+    }
+
+    fn bool(&mut self) -> Result<(), String> {
+        if &self.fourcc()? == b"CNST" {
+            self.skip(1)?;
+        }
+        Ok(())
+    }
+
+    fn asset(&mut self, report: bool) -> Result<(), String> {
+        if &self.fourcc()? == b"NONE" {
+            return Ok(());
+        }
+        let id = self.skip(4)?;
+        if report {
+            self.elements.asset_refs.push(id);
+        }
+        Ok(())
+    }
+
+    // Particles spawned at given frames, see structs::Kssm
+    fn spawn_system(&mut self) -> Result<(), String> {
+        if &self.fourcc()? != b"CNST" {
+            return Ok(());
+        }
+        self.skip(4 * 4)?;
+        let list_count = self.u32()?;
+        for _ in 0..list_count {
+            self.skip(4)?;
+            let item_count = self.u32()?;
+            for _ in 0..item_count {
+                let id = self.skip(4)?;
+                self.elements.asset_refs.push(id);
+                self.skip(3 * 4)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds the asset references and constant colors of a particle file of any of the formats
+pub fn particle_elements(data: &[u8]) -> Result<ParticleElements, String> {
+    let mut walker = Walker {
+        data,
+        pos: 0,
+        elements: ParticleElements::default(),
+    };
+    let magic = walker.fourcc()?;
+    let property_element: fn(&[u8; 4]) -> Option<Element> = match &magic {
+        b"GPSM" => part_property,
+        b"ELSM" => elsc_property,
+        b"SWSH" => swhc_property,
+        b"WPSM" => wpsc_property,
+        b"CRSM" => crsc_property,
+        b"DPSM" => dpsc_property,
+        _ => Err(format!(
+            "Unknown particle format '{}'",
+            String::from_utf8_lossy(&magic)
+        ))?,
+    };
+
+    loop {
+        let property = walker.fourcc()?;
+        if &property == b"_END" {
+            break;
+        }
+        let element = property_element(&property).ok_or_else(|| {
+            format!(
+                "Unknown property '{}' at offset 0x{:X} of a {} particle",
+                String::from_utf8_lossy(&property),
+                walker.pos - 4,
+                String::from_utf8_lossy(&magic)
+            )
+        })?;
+        walker.element(element)?;
+    }
+    Ok(walker.elements)
+}
+
+#[cfg(test)]
+fn test_particle(properties: &[&[u8]]) -> Vec<u8> {
+    let mut data = b"GPSM".to_vec();
+    for property in properties {
+        data.extend_from_slice(property);
+    }
+    data.extend_from_slice(b"_END");
+    data
+}
+
+#[test]
+fn test_particle_elements_colors_and_refs() {
+    let f = |x: f32| x.to_be_bytes();
+    let cnst_real = |x: f32| [&b"CNST"[..], &f(x)].concat();
+    let color = [
+        &b"COLRCNST"[..],
+        &cnst_real(1.5),
+        &cnst_real(0.5),
+        &cnst_real(0.25),
+        &cnst_real(1.0),
+    ]
+    .concat();
+    let keyframes = [
+        &b"LCLRKEYE"[..],
+        &[0; 4 + 4 + 1 + 1 + 4 + 4],
+        &2u32.to_be_bytes(),
+        &[0; 32],
+    ]
+    .concat();
+    let data = test_particle(&[
+        b"MAXPCNST\0\0\0\x10",
+        &color[..],
+        &keyframes[..],
+        b"TEXRCNSTTXTR\x12\x34\x56\x78",
+        b"IDTSCNST\x87\x65\x43\x21",
+        b"SIZERANDCNST\0\0\0\0CNST\x3F\x80\0\0",
+        b"ZBUFCNST\x01",
+        b"PMDLNONE",
+        // A component of a color isn't a color to remap
+        &[&b"LENGGTCR"[..], &color[4..]].concat(),
+    ]);
+
+    let elements = particle_elements(&data).unwrap();
+    let texture = data
+        .windows(4)
+        .position(|w| w == b"\x12\x34\x56\x78")
+        .unwrap();
+    let child = data
+        .windows(4)
+        .position(|w| w == b"\x87\x65\x43\x21")
+        .unwrap();
+    assert_eq!(elements.asset_refs, vec![texture, child]);
+
+    let colr = data.windows(4).position(|w| w == b"COLR").unwrap();
+    let lclr = data.windows(4).position(|w| w == b"LCLR").unwrap();
+    let keys = lclr + 8 + 22;
+    assert_eq!(
+        elements.colors,
+        vec![
+            [colr + 12, colr + 20, colr + 28],
+            [keys, keys + 4, keys + 8],
+            [keys + 16, keys + 20, keys + 24],
+        ]
+    );
+}
+
+#[test]
+fn test_particle_elements_rejects_unknown_classes() {
+    let data = test_particle(&[b"SIZEXXXX\0\0\0\0"]);
+    assert!(particle_elements(&data).is_err());
+    let data = test_particle(&[b"XXXXCNST\0\0\0\0"]);
+    assert!(particle_elements(&data).is_err());
+}
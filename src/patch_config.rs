@@ -3,22 +3,36 @@ use std::{
     fmt,
     fs::{self, File, OpenOptions},
     io::Read,
+    path::Path,
     str::FromStr,
 };
 
-use clap::{crate_version, App, Arg};
+use clap::{crate_version, App, Arg, ArgMatches};
+use dol_symbol_table::DolVersion;
 use json_data::*;
 use json_strip::strip_jsonc_comments;
-use reader_writer::{FourCC, Reader};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use reader_writer::FourCC;
 use serde::{
     de::{Error, Visitor},
     Deserialize, Deserializer, Serialize,
 };
-use structs::{res_id, MapaObjectVisibilityMode, ResId};
+use structs::{res_id, MapaObjectType, MapaObjectVisibilityMode, ResId};
 
 use crate::{
-    custom_assets::custom_asset_ids, door_meta::DoorType, elevators::World,
-    pickup_meta::PickupType, room_lookup::ROOM_BY_INTERNAL_ID, starting_items::StartingItems,
+    custom_assets::{custom_asset_ids, key_model_name, MAX_KEYS},
+    door_meta::{BlastShieldType, DoorType},
+    elevators::{SpawnRoomData, World},
+    generator::{fill_nothing_cosmetics, fill_random_placeholders, pick_elevator_audio},
+    input_iso::{self, InputIso},
+    pickup_meta::PickupType,
+    room_lookup::{ROOM_BY_INTERNAL_ID, ROOM_BY_NAME},
+    softlock_fixes::SoftlockFix,
+    starting_items::StartingItems,
+    txtr_conversions::{
+        ColorRemap, GRAVITY_SUIT_TEXTURES, PHAZON_SUIT_TEXTURES, POWER_SUIT_TEXTURES,
+        VARIA_SUIT_TEXTURES,
+    },
 };
 
 /*** Parsed Config (fn patch_iso) ***/
@@ -37,6 +51,7 @@ pub enum IsoFormat {
     Iso,
     Gcz,
     Ciso,
+    Bps,
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
@@ -47,6 +62,15 @@ pub enum ArtifactHintBehavior {
     All,
 }
 
+#[derive(PartialEq, Debug, Serialize, Deserialize, Copy, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum ColorblindMode {
+    None,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
 #[derive(PartialEq, Debug, Serialize, Deserialize, Copy, Clone)]
 #[serde(deny_unknown_fields)]
 pub enum CutsceneMode {
@@ -58,6 +82,13 @@ pub enum CutsceneMode {
     Major,
 }
 
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Copy, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum CutsceneOverride {
+    Skip,
+    Keep,
+}
+
 #[derive(PartialEq, Debug, Serialize, Deserialize, Copy, Clone)]
 #[serde(deny_unknown_fields)]
 pub enum Visor {
@@ -67,6 +98,17 @@ pub enum Visor {
     Thermal,
 }
 
+// Numbered like the logbook categories of the scans in the SAVWs
+#[derive(PartialEq, Debug, Serialize, Deserialize, Copy, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum LogbookCategory {
+    PirateData = 1,
+    ChozoLore = 2,
+    Creatures = 3,
+    Research = 4,
+    Artifacts = 5,
+}
+
 #[derive(PartialEq, Debug, Serialize, Deserialize, Copy, Clone)]
 #[serde(deny_unknown_fields)]
 pub enum Beam {
@@ -84,9 +126,46 @@ pub struct GameBanner {
     pub developer: Option<String>,
     pub developer_full: Option<String>,
     pub description: Option<String>,
+    pub image: Option<String>,
+    pub save_banner: Option<String>,
+    pub save_icons: Option<Vec<String>>,
+    pub save_icon_speed: Option<u32>, // 1 to 3, for 4, 8 (the game's) or 12 frames per icon
+    pub languages: Option<HashMap<String, BannerText>>, // e.g. "French":{"gameName":"..."}
 }
 
-#[derive(Deserialize, Debug, Default, Clone)]
+// The index of the language in the BNR2 of PAL discs, after English
+pub fn banner_language_index(language: &str) -> Option<usize> {
+    match language.to_lowercase().trim() {
+        "german" => Some(0),
+        "french" => Some(1),
+        "spanish" => Some(2),
+        "italian" => Some(3),
+        "dutch" => Some(4),
+        _ => None,
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BannerText {
+    pub game_name: Option<String>,
+    pub game_name_full: Option<String>,
+    pub developer: Option<String>,
+    pub developer_full: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TitleScreen {
+    pub press_start: Option<String>,
+    pub nintendo_logo: Option<String>,
+    pub retro_logo: Option<String>,
+    pub dolby_logo: Option<String>,
+    pub billboards: Option<HashMap<String, String>>, // e.g. "Chozo Ruins":"ruins.png"
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct PickupConfig {
     pub id: Option<u32>,
@@ -97,18 +176,88 @@ pub struct PickupConfig {
     pub model: Option<String>,
     pub scan_text: Option<String>,
     pub hudmemo_text: Option<String>,
+    pub scan_text_translations: Option<HashMap<String, String>>, // e.g. "French":"..."
+    pub hudmemo_text_translations: Option<HashMap<String, String>>,
     pub respawn: Option<bool>,
     pub position: Option<[f32; 3]>,
     pub modal_hudmemo: Option<bool>,
+    pub attainment_audio: Option<AttainmentAudio>,
+    pub sound_id: Option<u32>,
     pub jumbo_scan: Option<bool>,
     pub destination: Option<String>,
-    pub show_icon: Option<bool>,
+    pub show_icon: Option<PickupIcon>,
     pub invisible_and_silent: Option<bool>,
     pub thermal_only: Option<bool>,
     pub scale: Option<[f32; 3]>,
+    pub key: Option<String>, // e.g. "Red", unlocks doors with the same key
 }
 
-#[derive(Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum AttainmentAudio {
+    Enabled(bool),
+    File(String), // e.g. "itm_x_short_02", "/audio/itm_x_short_02.dsp" or "my_jingle.dsp"
+}
+
+impl AttainmentAudio {
+    pub fn enabled(&self) -> bool {
+        *self != AttainmentAudio::Enabled(false)
+    }
+
+    /// Path of the replacement audio on the disc
+    pub fn file_name(&self) -> Option<String> {
+        let file = match self {
+            AttainmentAudio::Enabled(_) => return None,
+            AttainmentAudio::File(file) => file,
+        };
+
+        if file.to_lowercase().starts_with("/audio/") {
+            return Some(file.clone());
+        }
+
+        let name = Path::new(file)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(file);
+        if name.to_lowercase().ends_with(".dsp") {
+            Some(format!("/audio/{}", name))
+        } else {
+            Some(format!("/audio/{}.dsp", name))
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ItemAcquisitionConfig {
+    pub modal_hudmemo: Option<bool>,
+    pub attainment_audio: Option<bool>,
+    pub save_prompt: Option<bool>, // open the save station prompt once the item is acquired
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NothingCosmeticsConfig {
+    pub models: Option<Vec<String>>, // e.g. "Missile", or the name of an extern model
+    pub hudmemo_texts: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ElevatorAudioConfig {
+    pub files: Option<Vec<String>>, // e.g. "evt_x_event_00", "/audio/evt_x_event_00.dsp" or "my_line.dsp"
+    pub destinations: Option<HashMap<String, Vec<String>>>, // destination world -> files, instead of `files`
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ArtifactTotemHintsConfig {
+    pub templates: Option<Vec<String>>, // with {pickup}, {room} and {world}
+    pub vague_templates: Option<Vec<String>>, // with {pickup} and {world}
+    pub progressive: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct ScanConfig {
     pub id: Option<u32>,
@@ -122,20 +271,59 @@ pub struct ScanConfig {
     pub text: String,
 }
 
-#[derive(Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct DoorDestination {
     pub room_name: String,
     pub dock_num: u32,
 }
 
-#[derive(Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct DoorConfig {
     #[serde(alias = "type")]
     pub shield_type: Option<String>,
     pub blast_shield_type: Option<String>,
     pub destination: Option<DoorDestination>, // Must be in same area. Ex: "destination":"Main Plaza"
+    pub key: Option<String>, // Door can't be opened until a pickup with this key is collected
+    pub blast_shield_cost: Option<u32>, // Missiles/Power Bombs taken when the blast shield is destroyed
+    pub scan_text: Option<String>, // Scan text of a "Scan" blast shield, which is removed by scanning it
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SoundOverride {
+    pub mute: Option<bool>,
+    pub volume: Option<u32>,
+    pub pitch: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub enum FrmeWidgetType {
+    TextPane,
+    Model,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FrmeWidgetConfig {
+    pub name: String,
+    pub parent: Option<String>, // defaults to the frame's head widget
+    #[serde(alias = "type")]
+    pub kind: FrmeWidgetType,
+    pub position: [f32; 3],
+    pub color: Option<[f32; 4]>, // RGBA, 0 - 1.0
+    pub visible: Option<bool>,
+
+    // TextPane
+    pub font: Option<String>,
+    pub size: Option<[f32; 2]>, // width, height
+    pub fill_color: Option<[f32; 4]>,
+    pub outline_color: Option<[f32; 4]>,
+
+    // Model
+    pub model: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -147,6 +335,256 @@ pub struct SuitColors {
     pub phazon_deg: Option<i16>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SuitCosmetics {
+    pub hue_deg: Option<f32>,
+    pub saturation: Option<f32>, // 1.0 is vanilla
+    pub value: Option<f32>,      // 1.0 is vanilla
+    pub rgb_matrix: Option<[f32; 9]>,
+    pub textures: Option<HashMap<String, String>>, // key=texture name or asset ID, value=image path
+}
+
+impl SuitCosmetics {
+    pub fn color_remap(&self) -> Option<ColorRemap> {
+        if self.hue_deg.is_none()
+            && self.saturation.is_none()
+            && self.value.is_none()
+            && self.rgb_matrix.is_none()
+        {
+            return None;
+        }
+
+        let default = ColorRemap::default();
+        Some(ColorRemap {
+            hue_deg: self.hue_deg.unwrap_or(default.hue_deg),
+            saturation: self.saturation.unwrap_or(default.saturation),
+            value: self.value.unwrap_or(default.value),
+            matrix: self.rgb_matrix.unwrap_or(default.matrix),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SuitsCosmeticsConfig {
+    pub power: Option<SuitCosmetics>,
+    pub varia: Option<SuitCosmetics>,
+    pub gravity: Option<SuitCosmetics>,
+    pub phazon: Option<SuitCosmetics>,
+}
+
+impl SuitsCosmeticsConfig {
+    pub fn color_remaps(&self) -> [Option<ColorRemap>; 4] {
+        [&self.power, &self.varia, &self.gravity, &self.phazon]
+            .map(|suit| suit.as_ref().and_then(|suit| suit.color_remap()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BeamCosmetics {
+    pub hue_deg: Option<f32>,
+    pub saturation: Option<f32>, // 1.0 is vanilla
+    pub value: Option<f32>,      // 1.0 is vanilla
+    pub rgb_matrix: Option<[f32; 9]>,
+    pub random_hue: Option<bool>, // pick the hue from the seed instead of hueDeg
+    pub projectile: Option<Beam>, // fire the shots of another beam
+}
+
+impl BeamCosmetics {
+    pub fn color_remap(&self, rng: &mut StdRng) -> Option<ColorRemap> {
+        let random_hue = self.random_hue.unwrap_or(false);
+        if !random_hue
+            && self.hue_deg.is_none()
+            && self.saturation.is_none()
+            && self.value.is_none()
+            && self.rgb_matrix.is_none()
+        {
+            return None;
+        }
+
+        let default = ColorRemap::default();
+        let hue_deg = if random_hue {
+            rng.gen_range(0.0, 360.0)
+        } else {
+            self.hue_deg.unwrap_or(default.hue_deg)
+        };
+        Some(ColorRemap {
+            hue_deg,
+            saturation: self.saturation.unwrap_or(default.saturation),
+            value: self.value.unwrap_or(default.value),
+            matrix: self.rgb_matrix.unwrap_or(default.matrix),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BeamsCosmeticsConfig {
+    pub power: Option<BeamCosmetics>,
+    pub ice: Option<BeamCosmetics>,
+    pub wave: Option<BeamCosmetics>,
+    pub plasma: Option<BeamCosmetics>,
+}
+
+impl BeamsCosmeticsConfig {
+    // Indexed by `Beam`
+    pub fn beams(&self) -> [Option<&BeamCosmetics>; 4] {
+        [&self.power, &self.ice, &self.wave, &self.plasma].map(|beam| beam.as_ref())
+    }
+
+    pub fn color_remaps(&self, seed: u64) -> [Option<ColorRemap>; 4] {
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.beams()
+            .map(|beam| beam.and_then(|beam| beam.color_remap(&mut rng)))
+    }
+
+    pub fn projectiles(&self) -> [Option<Beam>; 4] {
+        self.beams()
+            .map(|beam| beam.and_then(|beam| beam.projectile))
+    }
+}
+
+// Same order as the per-visor colors in GuiColors.CTWK
+#[derive(PartialEq, Eq, Hash, Debug, Serialize, Deserialize, Copy, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum HudVisor {
+    Combat = 0,
+    Scan = 1,
+    XRay = 2,
+    Thermal = 3,
+    MorphBall = 4,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HudVisorCosmetics {
+    pub tint: Option<[f32; 3]>,             // RGB, 0 - 1.0
+    pub energy_bar_color: Option<[f32; 3]>, // RGB, 0 - 1.0
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HudCosmeticsConfig {
+    pub hud_color: Option<[f32; 3]>, // RGB, 0 - 1.0, takes priority over tweaks.hudColor
+    pub energy_bar_color: Option<[f32; 3]>, // RGB, 0 - 1.0
+    pub visors: Option<HashMap<HudVisor, HudVisorCosmetics>>,
+    pub pickup_counter: Option<PickupCounterConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PickupCounterConfig {
+    pub position: Option<[f32; 3]>, // in FRME_BaseHud
+    pub color: Option<[f32; 4]>,    // RGBA, 0 - 1.0
+    pub total: Option<u32>,         // what the game counts as 100%
+    pub percentage: Option<bool>,
+}
+
+impl HudCosmeticsConfig {
+    pub fn visor_tint(&self, visor: HudVisor) -> Option<[f32; 3]> {
+        self.visors.as_ref()?.get(&visor)?.tint
+    }
+
+    pub fn visor_energy_bar_color(&self, visor: HudVisor) -> Option<[f32; 3]> {
+        self.visors
+            .as_ref()
+            .and_then(|visors| visors.get(&visor))
+            .and_then(|visor| visor.energy_bar_color)
+            .or(self.energy_bar_color)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ElevatorHologramCosmetics {
+    pub hue_deg: Option<f32>,
+    pub saturation: Option<f32>, // 1.0 is vanilla
+    pub value: Option<f32>,      // 1.0 is vanilla
+    pub rgb_matrix: Option<[f32; 9]>,
+    pub textures: Option<HashMap<String, String>>, // key=texture name or asset ID, value=image path
+}
+
+impl ElevatorHologramCosmetics {
+    pub fn color_remap(&self) -> Option<ColorRemap> {
+        if self.hue_deg.is_none()
+            && self.saturation.is_none()
+            && self.value.is_none()
+            && self.rgb_matrix.is_none()
+        {
+            return None;
+        }
+
+        let default = ColorRemap::default();
+        Some(ColorRemap {
+            hue_deg: self.hue_deg.unwrap_or(default.hue_deg),
+            saturation: self.saturation.unwrap_or(default.saturation),
+            value: self.value.unwrap_or(default.value),
+            matrix: self.rgb_matrix.unwrap_or(default.matrix),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CosmeticsConfig {
+    pub suits: Option<SuitsCosmeticsConfig>,
+    pub beams: Option<BeamsCosmeticsConfig>,
+    pub hud: Option<HudCosmeticsConfig>,
+    pub sfx: Option<HashMap<String, HashMap<String, Vec<String>>>>, // AGSC -> sample id -> .dsp paths
+    pub pickups: Option<PickupsCosmeticsConfig>,
+    pub elevator_holograms: Option<HashMap<String, ElevatorHologramCosmetics>>, // key=destination world
+    pub low_energy_alarm_threshold: Option<f32>, // instead of 30 (100 with 4 or more energy tanks)
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PickupJitterConfig {
+    pub rotation: Option<f32>, // at most this many degrees either way, around the vertical axis
+    pub scale: Option<f32>,    // e.g. 0.1 for up to 10% smaller or larger
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PickupsCosmeticsConfig {
+    pub major: Option<PickupJitterConfig>,
+    pub expansion: Option<PickupJitterConfig>,
+    pub artifact: Option<PickupJitterConfig>,
+    pub nothing: Option<PickupJitterConfig>,
+}
+
+impl PickupsCosmeticsConfig {
+    pub fn jitter(&self, pickup_type: PickupType) -> Option<PickupJitterConfig> {
+        let kind = pickup_type.kind();
+        if kind == PickupType::Nothing.kind() {
+            self.nothing
+        } else if kind >= PickupType::ArtifactOfTruth.kind()
+            && kind <= PickupType::ArtifactOfNewborn.kind()
+        {
+            self.artifact
+        } else if [
+            PickupType::Missile,
+            PickupType::EnergyTank,
+            PickupType::PowerBomb,
+        ]
+        .contains(&pickup_type)
+        {
+            self.expansion
+        } else {
+            self.major
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SplitTimerConfig {
+    pub show_igt: Option<bool>,
+    pub show_room_timer: Option<bool>,
+    pub splits_address: Option<u32>, // where the address of the room splits is written to
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct DefaultGameOptions {
@@ -202,6 +640,53 @@ pub struct PlatformConfig {
     pub platform_type: Option<PlatformType>,
     pub xray_only: Option<bool>,
     pub thermal_only: Option<bool>,
+    pub waypoints: Option<Vec<[f32; 3]>>, // positions the platform moves through, in order
+    pub speed: Option<f32>,
+    pub pause: Option<f32>, // seconds to wait at each waypoint
+    pub looping: Option<bool>,
+    pub activation_trigger: Option<PlatformActivationTriggerConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PlatformActivationTriggerConfig {
+    pub position: [f32; 3],
+    pub scale: Option<[f32; 3]>,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Copy, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum StationType {
+    #[serde(alias = "save", alias = "SAVE")]
+    Save, // also restores energy and ammo
+    #[serde(alias = "missile", alias = "MISSILE")]
+    Missile,
+    #[serde(alias = "powerBomb", alias = "POWERBOMB")]
+    PowerBomb,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct StationConfig {
+    pub layer: Option<u32>,
+    pub active: Option<bool>,
+    pub position: [f32; 3],
+    pub rotation: Option<[f32; 3]>,
+    #[serde(alias = "type")]
+    pub station_type: StationType,
+    pub pad: Option<bool>, // place a metal platform under the station
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TeleporterConfig {
+    pub layer: Option<u32>,
+    pub active: Option<bool>,
+    pub position: [f32; 3],
+    pub destination: String, // e.g. "Tallon Overworld:Landing Site"
+    pub required_item: Option<PickupType>,
+    pub scan_text: Option<String>, // added to the room's extraScans
+    pub pad: Option<bool>,
 }
 
 #[derive(PartialEq, Debug, Serialize, Deserialize, Copy, Clone)]
@@ -270,7 +755,11 @@ pub struct BlockConfig {
     pub layer: Option<u32>,
     pub position: [f32; 3],
     pub scale: Option<[f32; 3]>,
+    pub size: Option<f32>,
     pub texture: Option<GenericTexture>,
+    pub vulnerability: Option<String>, // maps to DoorType
+    pub health: Option<f32>,
+    pub destroyed_relay_id: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -370,6 +859,19 @@ pub struct TriggerConfig {
     pub deactivate_on_exit: Option<bool>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EffectConfig {
+    pub id: Option<u32>,
+    pub layer: Option<u32>,
+    pub active: Option<bool>,
+    pub position: Option<[f32; 3]>,
+    pub rotation: Option<[f32; 3]>,
+    pub scale: Option<[f32; 3]>,
+    pub part: Option<u32>, // PART asset ID, it must be in the room's pak
+    pub hot_in_thermal: Option<bool>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub enum SpecialFunctionType {
@@ -541,6 +1043,40 @@ pub struct FogConfig {
     pub range_delta: Option<[f32; 2]>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub enum WorldLightingOptions {
+    NormalWorld = 1,
+    NoShadowCast,
+    DisableWorld,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct LightParamsConfig {
+    pub ambient_color: Option<[f32; 4]>, // RGBA, used when no lights affect the object
+    pub world_lighting: Option<WorldLightingOptions>,
+    pub light_layer: Option<u32>,
+    pub max_dynamic_lights: Option<u32>,
+    pub max_area_lights: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct LightingConfig {
+    pub ambient_color: Option<[f32; 3]>, // RGB
+    pub brightness_scale: Option<f32>,   // scales every light in the room, 1.0 is default lighting
+    pub world_lighting: Option<WorldLightingOptions>,
+    pub light_params: Option<HashMap<u32, LightParamsConfig>>, // key=instance_id
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RoomPhysicsConfig {
+    pub gravity_scale: Option<f32>,       // 1.0 is default gravity
+    pub underwater_physics: Option<bool>, // true = swim everywhere, false = never swim
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct BombSlotConfig {
@@ -617,6 +1153,18 @@ pub struct CounterConfig {
     pub auto_reset: Option<bool>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ObjectiveConfig {
+    pub layer: Option<u32>,
+    pub active: Option<bool>,
+    pub enemy_ids: Vec<u32>,
+    pub count: Option<u32>,             // defaults to the number of enemies
+    pub open_doors: Option<Vec<u32>>,   // dock numbers
+    pub activate_ids: Option<Vec<u32>>, // e.g. a pickup which starts inactive
+    pub play_ids: Option<Vec<u32>>,     // sound/streamed audio objects
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct SwitchConfig {
@@ -865,6 +1413,50 @@ pub enum ConnectionState {
     INHERIT_BOUNDS = 0x20,
 }
 
+impl ConnectionState {
+    /// The state with the value `value`, if it has a name
+    pub fn from_u32(value: u32) -> Option<Self> {
+        use ConnectionState::*;
+        [
+            ANY,
+            ACTIVE,
+            ARRIVED,
+            CLOSED,
+            ENTERED,
+            EXITED,
+            INACTIVE,
+            INSIDE,
+            MAX_REACHED,
+            OPEN,
+            ZERO,
+            ATTACK,
+            RETREAT,
+            PATROL,
+            DEAD,
+            CAMERA_PATH,
+            CAMERA_TARGET,
+            DEACTIVATE_STATE,
+            PLAY,
+            MASSIVE_DEATH,
+            DEATH_RATTLE,
+            ABOUT_TO_MASSIVELY_DIE,
+            DAMAGE,
+            INVULN_DAMAGE,
+            MASSIVE_FROZEN_DEATH,
+            MODIFY,
+            SCAN_START,
+            SCAN_PROCESSING,
+            SCAN_DONE,
+            UNFROZEN,
+            DEFAULT,
+            REFLECTED_DAMAGE,
+            INHERIT_BOUNDS,
+        ]
+        .into_iter()
+        .find(|state| *state as u32 == value)
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
 #[repr(u32)]
@@ -921,6 +1513,67 @@ pub enum ConnectionMsg {
     SUSPENDED_MOVE = 0x30,
 }
 
+impl ConnectionMsg {
+    /// The message with the value `value`, if it has a name
+    pub fn from_u32(value: u32) -> Option<Self> {
+        use ConnectionMsg::*;
+        [
+            NONE,
+            UNKM0,
+            ACTIVATE,
+            ARRIVED,
+            CLOSE,
+            DEACTIVATE,
+            DECREMENT,
+            FOLLOW,
+            INCREMENT,
+            NEXT,
+            OPEN,
+            RESET,
+            RESET_AND_START,
+            SET_TO_MAX,
+            SET_TO_ZERO,
+            START,
+            STOP,
+            STOP_AND_RESET,
+            TOGGLE_ACTIVE,
+            UNKM18,
+            ACTION,
+            PLAY,
+            ALERT,
+            INTERNAL_MESSAGE00,
+            ON_FLOOR,
+            INTERNAL_MESSAGE02,
+            INTERNAL_MESSAGE03,
+            FALLING,
+            ON_ICE_SURFACE,
+            ON_MUD_SLOW_SURFACE,
+            ON_NORMAL_SURFACE,
+            TOUCHED,
+            ADD_PLATFORM_RIDER,
+            LAND_ON_NOT_FLOOR,
+            REGISTERED,
+            DELETED,
+            INITIALIZED_IN_AREA,
+            WORLD_INITIALIZED,
+            ADD_SPLASH_INHABITANT,
+            UPDATE_SPLASH_INHABITANT,
+            REMOVE_SPLASH_INHABITANT,
+            JUMPED,
+            DAMAGE,
+            INVULN_DAMAGE,
+            PROJECTILE_COLLIDE,
+            IN_SNAKE_WEED,
+            ADD_PHAZON_POOOL_INHABITANT,
+            UPDATE_PHAZON_POOL_INHABITANT,
+            REMOVE_PHAZON_POOL_INHABITANT,
+            SUSPENDED_MOVE,
+        ]
+        .into_iter()
+        .find(|message| *message as u32 == value)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct ConnectionConfig {
@@ -986,13 +1639,46 @@ pub struct WorldLightFaderConfig {
     pub fade_speed: Option<f32>,
 }
 
-#[derive(Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct RoomConfig {
-    pub superheated: Option<bool>,
-    pub remove_water: Option<bool>,
-    pub submerge: Option<bool>,
+pub struct MapIconConfig {
+    pub icon: MapaObjectType,
+    pub position: [f32; 3],
+    pub visibility_mode: Option<MapaObjectVisibilityMode>,
+    pub editor_id: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MapObjectConfig {
+    pub editor_id: u32,
+    pub icon: Option<MapaObjectType>,
+    pub visibility_mode: Option<MapaObjectVisibilityMode>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RoomMapConfig {
+    pub default_state: Option<MapaObjectVisibilityMode>,
+    pub icons: Option<Vec<MapIconConfig>>,
+    pub objects: Option<Vec<MapObjectConfig>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ShuffleBoundsConfig {
+    pub boxes: Option<Vec<[f32; 6]>>, // xmin, ymin, zmin, xmax, ymax, zmax
+    pub avoid_edges: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RoomConfig {
+    pub superheated: Option<bool>,
+    pub remove_water: Option<bool>,
+    pub submerge: Option<bool>,
     pub map_default_state: Option<MapaObjectVisibilityMode>,
+    pub map_config: Option<RoomMapConfig>,
     pub liquids: Option<Vec<WaterConfig>>,
     pub pickups: Option<Vec<PickupConfig>>,
     pub extra_scans: Option<Vec<ScanConfig>>,
@@ -1000,12 +1686,17 @@ pub struct RoomConfig {
     pub spawn_position_override: Option<[f32; 3]>,
     pub bounding_box_offset: Option<[f32; 3]>,
     pub bounding_box_scale: Option<[f32; 3]>,
+    pub shuffle_bounds: Option<ShuffleBoundsConfig>,
     pub platforms: Option<Vec<PlatformConfig>>,
+    pub stations: Option<Vec<StationConfig>>,
+    pub teleporters: Option<Vec<TeleporterConfig>>,
     pub camera_hints: Option<Vec<CameraHintConfig>>,
     pub blocks: Option<Vec<BlockConfig>>,
     pub lock_on_points: Option<Vec<LockOnPoint>>,
     pub fog: Option<FogConfig>,
     pub ambient_lighting_scale: Option<f32>, // 1.0 is default lighting
+    pub lighting: Option<LightingConfig>,
+    pub physics: Option<RoomPhysicsConfig>,
     pub enviornmental_effect: Option<EnviornmentalEffect>,
     pub initial_enviornmental_effect: Option<f32>,
     pub initial_thermal_heat_level: Option<f32>,
@@ -1031,6 +1722,7 @@ pub struct RoomConfig {
     pub edit_objs: Option<HashMap<u32, EditObjConfig>>,
     pub waypoints: Option<Vec<WaypointConfig>>,
     pub counters: Option<Vec<CounterConfig>>,
+    pub objectives: Option<Vec<ObjectiveConfig>>,
     pub switches: Option<Vec<SwitchConfig>>,
     pub player_hints: Option<Vec<PlayerHintConfig>>,
     pub distance_fogs: Option<Vec<FogConfig>>,
@@ -1041,10 +1733,11 @@ pub struct RoomConfig {
     pub cameras: Option<Vec<CameraConfig>>,
     pub camera_waypoints: Option<Vec<CameraWaypointConfig>>,
     pub camera_filter_keyframes: Option<Vec<CameraFilterKeyframeConfig>>,
+    pub effects: Option<Vec<EffectConfig>>,
     // Don't forget to update merge_json when adding here
 }
 
-#[derive(Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct LevelConfig {
     #[serde(default)]
@@ -1059,6 +1752,7 @@ pub struct LevelConfig {
 pub struct CtwkConfig {
     pub fov: Option<f32>,
     pub player_size: Option<f32>,
+    pub player_size_physics: Option<bool>, // also scale eye height, morph ball and cameras
     pub morph_ball_size: Option<f32>,
     pub easy_lava_escape: Option<bool>,
     pub move_while_scan: Option<bool>,
@@ -1099,11 +1793,35 @@ pub struct CtwkConfig {
     pub min_space_jump_time: Option<f32>,
     pub falling_space_jump: Option<bool>,
     pub impulse_space_jump: Option<bool>,
+    pub vertical_jump_accel: Option<f32>,
+    pub horizontal_jump_accel: Option<f32>,
+    pub air_acceleration: Option<f32>,
+    pub air_friction: Option<f32>,
+    pub air_max_speed: Option<f32>,
+    pub fluid_gravity: Option<f32>,
+    pub water_acceleration: Option<f32>,
+    pub water_max_speed: Option<f32>,
+    pub water_jump_factor: Option<f32>,
+    pub water_ball_jump_factor: Option<f32>,
+    pub lava_jump_factor: Option<f32>,
+    pub lava_ball_jump_factor: Option<f32>,
+    pub phazon_jump_factor: Option<f32>,
+    pub phazon_ball_jump_factor: Option<f32>,
+    pub grapple_jump_force: Option<f32>,
+    pub grapple_pull_speed: Option<f32>,
 
     // PlayerGun.CTWK
     pub gun_position: Option<[f32; 3]>, // offset
     pub gun_damage: Option<f32>,
     pub gun_cooldown: Option<f32>,
+    pub missile_damage: Option<f32>,
+    pub charged_beam_damage: Option<f32>,
+    pub bomb_damage: Option<f32>,
+    pub power_bomb_damage: Option<f32>,
+    pub bomb_fuse_time: Option<f32>,
+    pub bomb_drop_delay_time: Option<f32>,
+    pub beam_switch_time: Option<f32>,
+    pub gun_holster_time: Option<f32>,
 
     // Ball.CTWK
     pub max_translation_accel: Option<f32>,
@@ -1121,6 +1839,9 @@ pub struct CtwkConfig {
     pub boost_incremental_speed0: Option<f32>,
     pub boost_incremental_speed1: Option<f32>,
     pub boost_incremental_speed2: Option<f32>,
+    pub ball_air_acceleration: Option<f32>,
+    pub ball_water_acceleration: Option<f32>,
+    pub ball_door_camera_distance: Option<f32>,
 
     // GuiColors.CTWK
     pub hud_color: Option<[f32; 3]>, // RGB, 0 - 1.0
@@ -1149,6 +1870,99 @@ pub struct HallOfTheEldersBombSlotCoversConfig {
     pub plasma: Option<BombSlotCover>,
 }
 
+// The keys of bosses, lowercase without spaces or underscores
+const BOSS_NAMES: &[&str] = &[
+    "parasitequeen",
+    "idrone",
+    "incineratordrone",
+    "zoid",
+    "flaahgra",
+    "adultsheegoth",
+    "thardus",
+    "elitepirate1",
+    "elitepirate2",
+    "elitepirate3",
+    "phazonelite",
+    "omegapirate",
+    "ridley",
+    "metaridley",
+    "exo",
+    "metroidprime",
+    "metroidprimeexoskeleton",
+    "essence",
+    "metroidprimeessence",
+    "platedbeetle",
+    "cloakeddrone",
+];
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BossConfig {
+    pub health_multiplier: Option<f32>,
+    pub skip_phases: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FinalBossesConfig {
+    pub skip_meta_ridley: Option<bool>,
+    pub skip_exo: Option<bool>,
+    pub skip_essence: Option<bool>,
+    pub portal_opens_immediately: Option<bool>, // no artifacts or Meta Ridley needed to reach the crater
+    pub skip_ending_cinematic: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BlackoutConfig {
+    pub room_count: Option<u32>,
+    pub brightness: Option<f32>,
+    pub thermal_visor_buff: Option<bool>, // pickups and doors glow in dark rooms
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(deny_unknown_fields)]
+pub enum HazardKind {
+    #[serde(alias = "acidRain", alias = "ACIDRAIN")]
+    AcidRain,
+    #[serde(alias = "sporeCloud", alias = "SPORECLOUD")]
+    SporeCloud,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HazardsConfig {
+    pub kinds: Option<Vec<HazardKind>>, // all of them if unset
+    pub room_count: Option<u32>,
+    pub hazards_per_room: Option<u32>,
+    pub damage_per_sec: Option<f32>,
+    pub size: Option<f32>, // edge length of the damaging volumes
+    pub exclude_rooms: Option<Vec<String>>, // e.g. "Tallon Overworld:Landing Site"
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RespawnEnemiesConfig {
+    pub intensity: Option<f32>, // chance of each ambush room of the world to respawn its enemies
+    pub exclude_rooms: Option<Vec<String>>, // room names, the world is the key of this config
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SeedInfoConfig {
+    pub hash: Option<String>, // shown instead of the hash computed from the layout
+    pub trick_level: Option<String>,
+    pub settings: Option<Vec<String>>, // extra lines, e.g. "Door Lock Rando: Types"
+    pub logbook_category: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DisplayConfig {
+    pub widescreen: Option<bool>, // render natively at 16:9 for a stretched display (NTSC 0-00/0-02 only)
+    pub fov: Option<f32>,         // used in place of the Game.CTWK FOV unless tweaks.fov is set
+}
+
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub enum BombSlotCover {
@@ -1166,6 +1980,21 @@ pub enum PhazonDamageModifier {
     Default,
     LinearDelayed, // Default but the damages don't increase over time
     Linear,        // Starts directly and deals linear damages
+    None,          // Phazon never deals damage
+}
+
+/// Damage rules that change which items are needed to get through an area. They duplicate fields
+/// found elsewhere in the config but are grouped here so trackers can read them from
+/// randomprime.json without knowing the patcher's defaults.
+#[derive(Serialize, Debug, Copy, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogicFlags {
+    pub varia_only_heat_protection: bool,
+    pub heat_damage_per_sec: f32,
+    pub lava_surface_damage: bool,
+    pub poison_damage_per_sec: f32,
+    pub phazon_damage_modifier: PhazonDamageModifier,
+    pub phazon_damage_per_sec: f32,
 }
 
 #[derive(Serialize, Debug, PartialEq, Copy, Clone)]
@@ -1181,6 +2010,28 @@ pub enum Version {
     PalTrilogy,
 }
 
+#[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone)]
+pub enum SaveProtection {
+    Off,       // Saves are shared with every other disc of the same version, patched or not
+    PerLayout, // Only discs patched with the same layout share saves
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum SaveFile {
+    A,
+    B,
+    C,
+}
+
+/// Where new games started on one of the save files begin, instead of the starting room
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct StartingPreset {
+    pub file: SaveFile,
+    pub starting_room: String,
+    pub starting_items: Option<StartingItems>, // the game's starting items if unset
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone)]
 pub enum DoorOpenMode {
     Original,           // Doors always stay the same color after opening
@@ -1190,6 +2041,23 @@ pub enum DoorOpenMode {
                      // BlueAll, // All Doors downgrade to Blue after opening
 }
 
+#[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone)]
+pub enum PalTiming {
+    Default, // Leave the video mode up to the game and console settings
+    Pal60,   // Always output 60Hz in EURGB60, so the game runs at the same speed as NTSC
+    Ntsc,    // Always output 480i NTSC video, matching NTSC frame timing exactly
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,  // Progress and results
+    Debug, // Also the time taken patching each pak and room
+    Trace,
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone)]
 pub enum DifficultyBehavior {
     Either,
@@ -1253,6 +2121,126 @@ impl<'de> Deserialize<'de> for SuitDamageReduction {
     }
 }
 
+// Which icon marks a pickup on the map. The variants after `Auto` must stay in the same order
+// as the MAP_PICKUP_ICON TXTRs, from MAP_PICKUP_ICON_MAJOR_TXTR on.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub enum PickupIcon {
+    #[default]
+    Hidden,
+    Auto,
+    Major,
+    Expansion,
+    Artifact,
+    Nothing,
+}
+
+impl PickupIcon {
+    /// Resolves `Auto` according to the pickup's type. Returns the index of the texture to
+    /// use, if any.
+    pub fn texture_index(&self, pickup_type: PickupType) -> Option<u32> {
+        let icon = match self {
+            PickupIcon::Auto => match pickup_type {
+                PickupType::Missile | PickupType::EnergyTank | PickupType::PowerBomb => {
+                    PickupIcon::Expansion
+                }
+                PickupType::Nothing
+                | PickupType::FloatyJump
+                | PickupType::HealthRefill
+                | PickupType::IceTrap => PickupIcon::Nothing,
+                p if p.kind() >= PickupType::ArtifactOfTruth.kind()
+                    && p.kind() <= PickupType::ArtifactOfNewborn.kind() =>
+                {
+                    PickupIcon::Artifact
+                }
+                _ => PickupIcon::Major,
+            },
+            icon => *icon,
+        };
+        match icon {
+            PickupIcon::Hidden | PickupIcon::Auto => None,
+            PickupIcon::Major => Some(0),
+            PickupIcon::Expansion => Some(1),
+            PickupIcon::Artifact => Some(2),
+            PickupIcon::Nothing => Some(3),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PickupIcon {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PickupIconVisitor;
+
+        impl<'de> Visitor<'de> for PickupIconVisitor {
+            type Value = PickupIcon;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    r#""Hidden", "Auto", "Major", "Expansion", "Artifact", "Nothing", true, or false"#
+                )
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                if v {
+                    Ok(PickupIcon::Auto)
+                } else {
+                    Ok(PickupIcon::Hidden)
+                }
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                match v {
+                    "Hidden" => Ok(PickupIcon::Hidden),
+                    "Auto" => Ok(PickupIcon::Auto),
+                    "Major" => Ok(PickupIcon::Major),
+                    "Expansion" => Ok(PickupIcon::Expansion),
+                    "Artifact" => Ok(PickupIcon::Artifact),
+                    "Nothing" => Ok(PickupIcon::Nothing),
+                    variant => Err(E::unknown_variant(
+                        variant,
+                        &[
+                            "Hidden",
+                            "Auto",
+                            "Major",
+                            "Expansion",
+                            "Artifact",
+                            "Nothing",
+                        ],
+                    )),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(PickupIconVisitor)
+    }
+}
+
+impl Version {
+    /// The symbol table of this version's DOL, None for the Trilogy versions which don't have one
+    pub fn dol_version(&self) -> Option<DolVersion> {
+        match self {
+            Version::NtscU0_00 => Some(DolVersion::NtscU0_00),
+            Version::NtscU0_01 => Some(DolVersion::NtscU0_01),
+            Version::NtscU0_02 => Some(DolVersion::NtscU0_02),
+            Version::NtscK => Some(DolVersion::NtscK),
+            Version::NtscJ => Some(DolVersion::NtscJ),
+            Version::Pal => Some(DolVersion::Pal),
+            Version::NtscUTrilogy => None,
+            Version::NtscJTrilogy => None,
+            Version::PalTrilogy => None,
+        }
+    }
+}
+
 impl fmt::Display for Version {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self {
@@ -1283,13 +2271,23 @@ pub struct PatchConfig {
     pub version: Version,
 
     #[serde(skip_serializing)]
-    pub input_iso: memmap::Mmap,
+    pub input_iso: InputIso,
     pub iso_format: IsoFormat,
     #[serde(skip_serializing)]
     pub output_iso: File,
+    #[serde(skip_serializing)]
+    pub output_iso_journal: Option<String>,
+    #[serde(skip_serializing)]
+    pub output_iso_manifest: Option<String>,
+    #[serde(skip_serializing)]
+    pub output_iso_sha256: Option<String>,
+    #[serde(skip_serializing)]
+    pub output_iso_patch_manifest: Option<String>,
 
     pub qol_cutscenes: CutsceneMode,
+    pub cutscene_overrides: HashMap<u32, CutsceneOverride>, // keyed by MREA
     pub qol_game_breaking: bool,
+    pub softlock_fixes: HashMap<SoftlockFix, bool>,
     pub qol_cosmetic: bool,
     pub qol_pickup_scans: bool,
     pub qol_general: bool,
@@ -1318,6 +2316,7 @@ pub struct PatchConfig {
 
     pub starting_room: String,
     pub starting_memo: Option<String>,
+    pub loadout_memo: bool,
     pub spring_ball: bool,
     pub warp_to_start: bool,
     pub warp_to_start_delay_s: f32,
@@ -1326,22 +2325,47 @@ pub struct PatchConfig {
     pub etank_capacity: u32,
     pub shuffle_pickup_position: bool,
     pub shuffle_pickup_pos_all_rooms: bool,
+    pub exclude_rooms: Vec<u32>, // MREA IDs of the rooms the automated passes leave alone
     pub remove_vanilla_blast_shields: bool,
     pub nonvaria_heat_damage: bool,
     pub heat_damage_per_sec: f32,
     pub poison_damage_per_sec: f32,
     pub phazon_damage_per_sec: f32,
     pub phazon_damage_modifier: PhazonDamageModifier,
+    pub no_lava_surface_damage: bool,
+    pub logic_flags: LogicFlags,
     pub staggered_suit_damage: SuitDamageReduction,
     pub item_max_capacity: HashMap<PickupType, u32>,
+    pub item_acquisition: HashMap<PickupType, ItemAcquisitionConfig>,
+    pub progressive_chains: Vec<Vec<PickupType>>,
     pub map_default_state: MapaObjectVisibilityMode,
+    pub map_collected_pickup_icons: bool,
     pub auto_enabled_elevators: bool,
+    pub elevator_audio: HashMap<u32, AttainmentAudio>, // elevator room MREA -> audio played on activation
     pub skip_ridley: bool,
+    pub skip_exo: bool,
+    pub skip_essence: bool,
+    pub skip_ending_cinematic: Option<bool>, // decided by the transports if unset
+    pub blackout: Option<BlackoutConfig>,
+    pub hazards: Option<HazardsConfig>,
+    pub respawn_enemies: HashMap<String, RespawnEnemiesConfig>, // world -> settings
+    pub phazon_suit_from_pickup: bool,
     pub multiworld_dol_patches: bool,
     pub update_hint_state_replacement: Option<Vec<u8>>,
     pub quiet: bool,
+    pub strict_version: bool,
+    pub validate_output: bool,
+    pub auto_dependencies: bool,
+    pub prune_dependencies: bool,
+    pub save_protection: SaveProtection,
+    pub log_level: LogLevel,
+    pub log_file: Option<String>, // JSON lines, for frontends to parse
 
     pub starting_items: StartingItems,
+    pub starting_presets: Vec<StartingPreset>,
+    pub starting_maps: Vec<String>,
+    pub starting_visited_rooms: Vec<String>,
+    pub starting_logbook: Vec<LogbookCategory>,
     pub item_loss_items: StartingItems,
     pub disable_item_loss: bool,
     pub starting_visor: Visor,
@@ -1359,28 +2383,49 @@ pub struct PatchConfig {
     pub skip_splash_screens: bool,
     pub default_game_options: Option<DefaultGameOptions>,
     pub suit_colors: Option<SuitColors>,
+    pub cosmetics: CosmeticsConfig,
     pub force_fusion: bool,
     pub cache_dir: String,
+    pub fog_shuffle: bool,
+    pub colorblind_mode: ColorblindMode,
+    pub texture_overrides: HashMap<u32, String>,
+    pub sound_overrides: HashMap<u32, SoundOverride>,
+    pub frme_widgets: HashMap<String, Vec<FrmeWidgetConfig>>,
 
     pub quickplay: bool,
+    pub debug_features: bool,
+    pub visualize_volumes: bool,
     pub quickpatch: bool,
+    pub optimize_paks: bool,
+    pub fast_disc_layout: bool,
+    pub pal_timing: PalTiming,
+    pub timer_config: SplitTimerConfig,
+    pub ghost_recording_address: Option<u32>,
+    pub ghost_memory_card_dump: bool,
+    pub tracker_address: Option<u32>,
+    pub integrity_check: bool,
 
     pub game_banner: GameBanner,
+    pub title_screen: TitleScreen,
     pub comment: String,
     pub main_menu_message: String,
 
     pub credits_string: Option<String>,
     pub results_string: Option<String>,
     pub artifact_hints: Option<HashMap<String, String>>, // e.g. "Strength":"This item can be found in Ruined Fountain"
+    pub artifact_totem_hints: Option<ArtifactTotemHintsConfig>,
     pub required_artifact_count: Option<u32>,
     pub artifact_temple_layer_overrides: Option<HashMap<String, bool>>,
     pub no_doors: bool,
     pub boss_sizes: HashMap<String, f32>,
+    pub bosses: HashMap<String, BossConfig>,
     pub shoot_in_grapple: bool,
     pub difficulty_behavior: DifficultyBehavior,
     pub legacy_block_size: bool,
     pub patch_wallcrawling: bool,
+    pub display: DisplayConfig,
     pub ctwk_config: CtwkConfig,
+    pub seed_info: Option<SeedInfoConfig>,
 }
 
 /*** Un-Parsed Config (doubles as JSON input specification) ***/
@@ -1391,16 +2436,25 @@ struct Preferences {
     skip_splash_screens: Option<bool>,
     default_game_options: Option<DefaultGameOptions>,
     suit_colors: Option<SuitColors>,
+    cosmetics: Option<CosmeticsConfig>,
     force_fusion: Option<bool>,
     cache_dir: Option<String>,
+    fog_shuffle: Option<bool>,
+    colorblind_mode: Option<String>,
+    texture_overrides: Option<HashMap<String, String>>,
+    sound_overrides: Option<HashMap<String, SoundOverride>>,
+    frme_widgets: Option<HashMap<String, Vec<FrmeWidgetConfig>>>, // e.g. "FRME_BallHud":[...]
 
     qol_game_breaking: Option<bool>,
+    softlock_fixes: Option<HashMap<SoftlockFix, bool>>,
     qol_cosmetic: Option<bool>,
     qol_cutscenes: Option<String>,
+    cutscene_overrides: Option<HashMap<String, CutsceneOverride>>, // e.g. "Chozo Ruins:Hive Totem":"Keep"
     qol_pickup_scans: Option<bool>,
     qol_general: Option<bool>,
 
     map_default_state: Option<String>,
+    map_collected_pickup_icons: Option<bool>,
     artifact_hint_behavior: Option<String>,
     automatic_crash_screen: Option<bool>,
     visible_bounding_box: Option<bool>,
@@ -1408,8 +2462,31 @@ struct Preferences {
 
     trilogy_disc_path: Option<String>,
     quickplay: Option<bool>,
+    debug_features: Option<bool>,
+    visualize_volumes: Option<bool>,
     quickpatch: Option<bool>,
+    optimize_paks: Option<bool>,
+    fast_disc_layout: Option<bool>,
+    pal_timing: Option<PalTiming>,
+    resumable_write: Option<bool>,
+    mmap_input_iso: Option<bool>,
+    in_place: Option<bool>,
+    output_sha256: Option<bool>,
+    output_manifest: Option<bool>,
+    timer_config: Option<SplitTimerConfig>,
+    ghost_recording_address: Option<u32>,
+    ghost_memory_card_dump: Option<bool>,
+    tracker_address: Option<u32>,
+    integrity_check: Option<bool>,
     quiet: Option<bool>,
+    strict_version: Option<bool>,
+    validate_output: Option<bool>,
+    auto_dependencies: Option<bool>,
+    prune_dependencies: Option<bool>,
+    save_protection: Option<SaveProtection>,
+    log_level: Option<LogLevel>,
+    log_file: Option<String>,
+    display: Option<DisplayConfig>,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -1417,25 +2494,40 @@ struct Preferences {
 struct GameConfig {
     starting_room: Option<String>,
     starting_memo: Option<String>,
+    loadout_memo: Option<bool>,
     spring_ball: Option<bool>,
     warp_to_start: Option<bool>,
     warp_to_start_delay_s: Option<f32>,
 
     shuffle_pickup_position: Option<bool>,
     shuffle_pickup_pos_all_rooms: Option<bool>,
+    exclude_rooms: Option<Vec<String>>, // e.g. "Chozo Ruins:Hive Totem"
     remove_vanilla_blast_shields: Option<bool>,
+    #[serde(alias = "variaOnlyHeatProtection")]
     nonvaria_heat_damage: Option<bool>,
     staggered_suit_damage: Option<SuitDamageReduction>,
     heat_damage_per_sec: Option<f32>,
     poison_damage_per_sec: Option<f32>,
     phazon_damage_per_sec: Option<f32>,
     phazon_damage_modifier: Option<String>,
+    no_lava_surface_damage: Option<bool>,
     auto_enabled_elevators: Option<bool>,
+    elevator_audio: Option<ElevatorAudioConfig>,
     skip_ridley: Option<bool>,
+    final_bosses: Option<FinalBossesConfig>,
+    blackout: Option<BlackoutConfig>,
+    hazards: Option<HazardsConfig>,
+    respawn_enemies: Option<HashMap<String, RespawnEnemiesConfig>>,
+    phazon_suit_from_pickup: Option<bool>,
     multiworld_dol_patches: Option<bool>,
     update_hint_state_replacement: Option<Vec<u8>>,
 
     starting_items: Option<StartingItems>,
+    starting_presets: Option<Vec<StartingPreset>>,
+    starting_maps: Option<Vec<String>>,
+    starting_visited_rooms: Option<Vec<String>>,
+    starting_logbook: Option<Vec<LogbookCategory>>,
+    starting_map_reveal: Option<f32>, // percent of the rooms of each world
     item_loss_items: Option<StartingItems>,
     disable_item_loss: Option<bool>,
     starting_visor: Option<String>,
@@ -1447,6 +2539,9 @@ struct GameConfig {
 
     etank_capacity: Option<u32>,
     item_max_capacity: Option<HashMap<String, u32>>,
+    item_acquisition: Option<HashMap<String, ItemAcquisitionConfig>>,
+    nothing_cosmetics: Option<NothingCosmeticsConfig>,
+    progressive_items: Option<HashMap<String, Vec<String>>>, // e.g. "Beam":["Wave Beam", ...]
 
     phazon_elite_without_dynamo: Option<bool>,
     main_plaza_door: Option<bool>,
@@ -1464,20 +2559,24 @@ struct GameConfig {
     hall_of_the_elders_bomb_slot_covers: Option<HallOfTheEldersBombSlotCoversConfig>,
 
     game_banner: Option<GameBanner>,
+    title_screen: Option<TitleScreen>,
     comment: Option<String>,
     main_menu_message: Option<String>,
 
     credits_string: Option<String>,
     results_string: Option<String>,
     artifact_hints: Option<HashMap<String, String>>, // e.g. "Strength":"This item can be found in Ruined Fountain"
+    artifact_totem_hints: Option<ArtifactTotemHintsConfig>,
     artifact_temple_layer_overrides: Option<HashMap<String, bool>>,
     required_artifact_count: Option<u32>,
     no_doors: Option<bool>, // Remove every door from the game
     boss_sizes: Option<HashMap<String, f32>>,
+    bosses: Option<HashMap<String, BossConfig>>,
     shoot_in_grapple: Option<bool>,
     difficulty_behavior: Option<DifficultyBehavior>,
     legacy_block_size: Option<bool>,
     patch_wallcrawling: Option<bool>,
+    seed_info: Option<SeedInfoConfig>, // adds a logbook entry listing the seed's settings
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -1491,6 +2590,7 @@ struct PatchConfigPrivate {
     export_asset_dir: Option<String>,
     input_iso: Option<String>,
     output_iso: Option<String>,
+    iso_format: Option<String>, // instead of the one picked from the extension of output_iso
     force_vanilla_layout: Option<bool>,
     extern_assets_dir: Option<String>,
     seed: Option<u64>,
@@ -1514,6 +2614,70 @@ struct PatchConfigPrivate {
 
 /*** Parse Patcher Input ***/
 
+// Must be kept in sync with PROGRESSIVE_AMOUNT in compile_to_ppc/rel_patches
+const PROGRESSIVE_PICKUP_AMOUNT: i32 = 0x70;
+const MAX_PROGRESSIVE_CHAINS: usize = 8;
+const MAX_PROGRESSIVE_CHAIN_LENGTH: usize = 8;
+
+// The blast shields which can have a `blastShieldCost`, those opened with ammo
+fn is_costed_blast_shield(blast_shield_type: Option<&str>) -> bool {
+    matches!(
+        blast_shield_type.and_then(BlastShieldType::from_str),
+        Some(BlastShieldType::Missile) | Some(BlastShieldType::PowerBomb)
+    )
+}
+
+// e.g. "Progressive:Beam" -> "Beam"
+fn progressive_chain_name(pickup_type: &str) -> Option<&str> {
+    let (group, name) = pickup_type.split_once(':')?;
+    if group.trim().eq_ignore_ascii_case("progressive") {
+        Some(name.trim())
+    } else {
+        None
+    }
+}
+
+fn parse_progressive_chain(name: &str, items: &[String]) -> Result<Vec<PickupType>, String> {
+    if items.is_empty() || items.len() > MAX_PROGRESSIVE_CHAIN_LENGTH {
+        Err(format!(
+            "Progressive item '{}' must have between 1 and {} items",
+            name, MAX_PROGRESSIVE_CHAIN_LENGTH
+        ))?;
+    }
+
+    items
+        .iter()
+        .map(|item| {
+            let pickup_type = PickupType::iter()
+                .find(|pt| pt.name().eq_ignore_ascii_case(item.trim()))
+                .ok_or_else(|| format!("Unknown item '{}' in progressive item '{}'", item, name))?;
+
+            // The payload only knows how to give items the player can have a single one of
+            if [
+                PickupType::Missile,
+                PickupType::PowerBomb,
+                PickupType::EnergyTank,
+                PickupType::UnknownItem1,
+                PickupType::HealthRefill,
+                PickupType::UnknownItem2,
+                PickupType::Nothing,
+                PickupType::FloatyJump,
+                PickupType::IceTrap,
+            ]
+            .contains(&pickup_type)
+            {
+                Err(format!(
+                    "{} cannot be part of progressive item '{}'",
+                    pickup_type.name(),
+                    name
+                ))?;
+            }
+
+            Ok(pickup_type)
+        })
+        .collect()
+}
+
 fn extend_option_vec<T>(dest: &mut Option<Vec<T>>, src: Option<Vec<T>>) {
     if let Some(src_vec) = src {
         if dest.is_none() {
@@ -1551,149 +2715,204 @@ macro_rules! merge_optional {
 }
 
 impl PatchConfig {
+    /// Relative paths to presets are looked up from the working directory
     pub fn from_json(json: &str) -> Result<Self, String> {
-        let result = strip_jsonc_comments(json, true);
-        let result = serde_json::from_str(result.as_str());
-        let result: PatchConfigPrivate = result.map_err(|e| format!("JSON parse failed: {}", e))?;
-        result.parse()
+        parse_config_json(json, Path::new(""))?.parse()
     }
 
     pub fn from_cli_options() -> Result<Self, String> {
         let matches = App::new("randomprime ISO patcher")
             .version(crate_version!())
-            .arg(Arg::with_name("input iso path")
+            .args(&PatchConfig::cli_args())
+            .get_matches();
+        PatchConfig::from_cli_matches(&matches)
+    }
+
+    /// The options of the patcher, shared by the `patch` and `validate` subcommands and the
+    /// invocation without a subcommand
+    pub fn cli_args() -> Vec<Arg<'static, 'static>> {
+        vec![
+            Arg::with_name("input iso path")
                 .long("input-iso")
-                .takes_value(true))
-            .arg(Arg::with_name("output iso path")
+                .takes_value(true),
+            Arg::with_name("output iso path")
                 .long("output-iso")
-                .takes_value(true))
-            .arg(Arg::with_name("extern assets dir")
+                .takes_value(true),
+            Arg::with_name("iso format")
+                .long("iso-format")
+                .help("Format of the output ISO, instead of the one picked from its extension")
+                .possible_values(&["iso", "gcz", "ciso", "bps"])
+                .case_insensitive(true)
+                .takes_value(true),
+            Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .validator(|s| s.parse::<u64>().map(|_| ())
+                                            .map_err(|_| "Expected an integer".to_string())),
+            Arg::with_name("extern assets dir")
                 .long("extern-assets-dir")
-                .takes_value(true))
-            .arg(Arg::with_name("profile json path")
-                .long("profile")
+                .takes_value(true),
+            Arg::with_name("profile json path")
+                .long("config")
+                .alias("profile")
                 .help("Path to JSON file with patch configuration (cli config takes priority). See documentation for details.")
-                .takes_value(true))
-            .arg(Arg::with_name("force vanilla layout")
+                .takes_value(true),
+            Arg::with_name("force vanilla layout")
                 .long("force-vanilla-layout")
-                .help("use this to play the vanilla game, but with a custom size factor"))
-            .arg(Arg::with_name("qol game breaking")
+                .help("use this to play the vanilla game, but with a custom size factor"),
+            Arg::with_name("qol game breaking")
                 .long("qol-game-breaking")
-                .help("Fix soft locks and crashes that retro didn't bother addressing"))
-            .arg(Arg::with_name("qol cosmetic")
+                .help("Fix soft locks and crashes that retro didn't bother addressing"),
+            Arg::with_name("qol cosmetic")
                 .long("qol-cosmetic")
-                .help("Patch cutscenes to fix continuity errors and UI to improve QoL without affecting IGT or the story"))
-            .arg(Arg::with_name("qol cutscenes")
+                .help("Patch cutscenes to fix continuity errors and UI to improve QoL without affecting IGT or the story"),
+            Arg::with_name("qol cutscenes")
                 .long("qol-cutscenes")
                 .help("Original, Competitive, Minor, Major")
-                .takes_value(true))
-            .arg(Arg::with_name("starting room")
+                .takes_value(true),
+            Arg::with_name("starting room")
                 .long("starting-room")
                 .help("Room which the player starts their adventure from. Format - <world>:<room name>, where <world> is [Frigate|Tallon|Chozo|Magmoor|Phendrana|Mines|Crater]")
-                .takes_value(true))
-            .arg(Arg::with_name("starting memo")
+                .takes_value(true),
+            Arg::with_name("starting memo")
                 .long("starting-memo")
                 .help("String which is shown to the player after they start a new save file")
-                .takes_value(true))
-            .arg(Arg::with_name("spring ball")
+                .takes_value(true),
+            Arg::with_name("spring ball")
                 .long("spring-ball")
-                .help("Allows player to use spring ball when bombs are acquired"))
-            .arg(Arg::with_name("warp to start")
+                .help("Allows player to use spring ball when bombs are acquired"),
+            Arg::with_name("warp to start")
                 .long("warp-to-start")
-                .help("Allows player to warp to start from any save station"))
-            .arg(Arg::with_name("automatic crash screen")
+                .help("Allows player to warp to start from any save station"),
+            Arg::with_name("automatic crash screen")
                 .long("automatic-crash-screen")
-                .help("Makes the crash screen appear without any button combination required"))
-            .arg(Arg::with_name("etank capacity")
+                .help("Makes the crash screen appear without any button combination required"),
+            Arg::with_name("etank capacity")
                 .long("etank-capacity")
                 .help("Set the etank capacity and base health")
-                .takes_value(true))
-            .arg(Arg::with_name("nonvaria heat damage")
+                .takes_value(true),
+            Arg::with_name("nonvaria heat damage")
                 .long("nonvaria-heat-damage")
-                .help("If the Varia Suit has not been collect, heat damage applies"))
-            .arg(Arg::with_name("heat damage per sec")
+                .help("If the Varia Suit has not been collect, heat damage applies"),
+            Arg::with_name("no lava surface damage")
+                .long("no-lava-surface-damage")
+                .help("Lava no longer damages the player on contact"),
+            Arg::with_name("heat damage per sec")
                 .long("heat-damage-per-sec")
                 .help("Set the heat damage per seconds spent in a superheated room")
-                .takes_value(true))
-            .arg(Arg::with_name("poison damage per sec")
+                .takes_value(true),
+            Arg::with_name("poison damage per sec")
                 .long("poison-damage-per-sec")
                 .help("Set the poison damage per seconds spent in poison water")
-                .takes_value(true))
-            .arg(Arg::with_name("phazon damage per sec")
+                .takes_value(true),
+            Arg::with_name("phazon damage per sec")
                 .long("phazon-damage-per-sec")
                 .help("Set the phazon damage per seconds spent in phazon (Applies only when using linear damages)")
-                .takes_value(true))
-            .arg(Arg::with_name("phazon damage modifier")
+                .takes_value(true),
+            Arg::with_name("phazon damage modifier")
                 .long("phazon-damage-modifier")
-                .help("Change the phazon damage modifier (Either default, linear or linear_delayed)")
-                .takes_value(true))
-            .arg(Arg::with_name("staggered suit damage")
+                .help("Change the phazon damage modifier (Either default, linear, linear_delayed or none)")
+                .takes_value(true),
+            Arg::with_name("staggered suit damage")
                 .long("staggered-suit-damage")
                 .help(concat!("The suit damage reduction is determinted by the number of suits ",
-                                "collected rather than the most powerful one collected.")))
-            .arg(Arg::with_name("map default state")
+                                "collected rather than the most powerful one collected.")),
+            Arg::with_name("map default state")
                 .long("map-default-state")
                 .help("Change the default state of map for each world (Either default, visible or visited)")
-                .takes_value(true))
-            .arg(Arg::with_name("skip ridley")
+                .takes_value(true),
+            Arg::with_name("skip ridley")
                 .long("skip-ridley")
-                .help("Skips Ridley fight when you have all the required artifacts and activate the portal to impact crater"))
-            .arg(Arg::with_name("auto enabled elevators")
+                .help("Skips Ridley fight when you have all the required artifacts and activate the portal to impact crater"),
+            Arg::with_name("auto enabled elevators")
                 .long("auto-enabled-elevators")
-                .help("Every elevator will be automatically enabled without scaning its terminal"))
-            .arg(Arg::with_name("artifact hint behavior")
+                .help("Every elevator will be automatically enabled without scaning its terminal"),
+            Arg::with_name("artifact hint behavior")
                 .long("artifact-hint-behavior")
                 .help("Set the behavior of artifact temple hints. Can be 'all', 'none', or 'default' (vanilla)")
-                .takes_value(true))
-            .arg(Arg::with_name("trilogy disc path")
+                .takes_value(true),
+            Arg::with_name("trilogy disc path")
                 .long("flaahgra-music-disc-path")
                 .help(concat!("Location of a ISO of Metroid Prime Trilogy. If provided the ",
                                 "Flaahgra fight music will be used to replace the original"))
-                .takes_value(true))
-            .arg(Arg::with_name("quiet")
+                .takes_value(true),
+            Arg::with_name("quiet")
                 .long("quiet")
-                .help("Don't print the progress messages"))
-            .arg(Arg::with_name("main menu message")
+                .help("Don't print the progress messages"),
+            Arg::with_name("log level")
+                .long("log-level")
+                .help(concat!("Verbosity of the messages printed. Can be 'off', 'error', 'warn', ",
+                                "'info', 'debug' or 'trace'"))
+                .takes_value(true),
+            Arg::with_name("log file")
+                .long("log-file")
+                .help("Also write the messages to this file, one JSON object per line")
+                .takes_value(true),
+            Arg::with_name("strict version")
+                .long("strict-version")
+                .help(concat!("Fail instead of warning when an option is not supported for the ",
+                                "version of the input ISO")),
+            Arg::with_name("validate output")
+                .long("validate-output")
+                .help(concat!("Check the object ids and connections of every patched room, ",
+                                "failing instead of producing an ISO which may crash")),
+            Arg::with_name("main menu message")
                 .long("main-menu-message")
                 .hidden(true)
-                .takes_value(true))
-            .arg(Arg::with_name("starting items")
+                .takes_value(true),
+            Arg::with_name("starting items")
                 .long("starting-items")
                 .takes_value(true)
                 .validator(|s| s.parse::<u64>().map(|_| ())
-                                            .map_err(|_| "Expected an integer".to_string())))
-            .arg(Arg::with_name("item loss items")
+                                            .map_err(|_| "Expected an integer".to_string())),
+            Arg::with_name("item loss items")
                 .long("item-loss-items")
                 .takes_value(true)
                 .validator(|s| s.parse::<u64>().map(|_| ())
-                                            .map_err(|_| "Expected an integer".to_string())))
-            .arg(Arg::with_name("quickplay")
+                                            .map_err(|_| "Expected an integer".to_string())),
+            Arg::with_name("quickplay")
                 .long("quickplay")
-                .hidden(true))
-            .arg(Arg::with_name("skip_splash_screens")
+                .hidden(true),
+            Arg::with_name("skip_splash_screens")
                 .long("skip_splash_screens")
-                .hidden(true))
-            .arg(Arg::with_name("quickpatch")
+                .hidden(true),
+            Arg::with_name("quickpatch")
                 .long("quickpatch")
-                .hidden(true))
-            .arg(Arg::with_name("text file comment")
+                .hidden(true),
+            Arg::with_name("text file comment")
                 .long("text-file-comment")
                 .hidden(true)
-                .takes_value(true))
-            .arg(Arg::with_name("run mode")
+                .takes_value(true),
+            Arg::with_name("run mode")
                 .long("run-mode")
                 .hidden(false)
-                .takes_value(true))
-            .get_matches();
+                .takes_value(true),
+        ]
+    }
+
+    pub fn from_cli_matches(matches: &ArgMatches) -> Result<Self, String> {
+        PatchConfigPrivate::from_cli_matches(matches)?.parse()
+    }
+
+    /// Checks the config given on the command line, and that the input ISO is supported, without
+    /// writing anything
+    pub fn validate_cli_matches(matches: &ArgMatches) -> Result<(), String> {
+        PatchConfigPrivate::from_cli_matches(matches)?.validate()
+    }
+}
 
+impl PatchConfigPrivate {
+    fn from_cli_matches(matches: &ArgMatches) -> Result<Self, String> {
         let mut patch_config = if matches.is_present("profile json path") {
             let json_path = matches.value_of("profile json path").unwrap();
             let cli_json_config_raw: &str = &fs::read_to_string(json_path)
                 .map_err(|e| format!("Could not read JSON file: {}", e))
                 .unwrap();
 
-            serde_json::from_str(strip_jsonc_comments(cli_json_config_raw, true).as_str())
-                .map_err(|e| format!("JSON parse failed: {}", e))?
+            let dir = Path::new(json_path)
+                .parent()
+                .unwrap_or_else(|| Path::new(""));
+            parse_config_json(cli_json_config_raw, dir)?
         } else {
             PatchConfigPrivate::default()
         };
@@ -1718,7 +2937,10 @@ impl PatchConfig {
             "skip_splash_screens" => patch_config.preferences.skip_splash_screens,
             "quickpatch" => patch_config.preferences.quickpatch,
             "quiet" => patch_config.preferences.quiet,
+            "strict version" => patch_config.preferences.strict_version,
+            "validate output" => patch_config.preferences.validate_output,
             "nonvaria heat damage" => patch_config.game_config.nonvaria_heat_damage,
+            "no lava surface damage" => patch_config.game_config.no_lava_surface_damage,
             "auto enabled elevators" => patch_config.game_config.auto_enabled_elevators,
             "spring ball" => patch_config.game_config.spring_ball,
             "warp to start" => patch_config.game_config.warp_to_start,
@@ -1734,6 +2956,9 @@ impl PatchConfig {
         if let Some(output_iso_path) = matches.value_of("output iso path") {
             patch_config.output_iso = Some(output_iso_path.to_string());
         }
+        if let Some(iso_format) = matches.value_of("iso format") {
+            patch_config.iso_format = Some(iso_format.to_string());
+        }
         if let Some(extern_assets_dir) = matches.value_of("extern assets dir") {
             patch_config.extern_assets_dir = Some(extern_assets_dir.to_string());
         }
@@ -1758,6 +2983,20 @@ impl PatchConfig {
         if let Some(run_mode) = matches.value_of("run mode") {
             patch_config.run_mode = Some(run_mode.to_string());
         }
+        if let Some(log_level) = matches.value_of("log level") {
+            patch_config.preferences.log_level = Some(match log_level.to_lowercase().as_str() {
+                "off" => LogLevel::Off,
+                "error" => LogLevel::Error,
+                "warn" => LogLevel::Warn,
+                "info" => LogLevel::Info,
+                "debug" => LogLevel::Debug,
+                "trace" => LogLevel::Trace,
+                _ => Err(format!("Unknown log level '{}'", log_level))?,
+            });
+        }
+        if let Some(log_file) = matches.value_of("log file") {
+            patch_config.preferences.log_file = Some(log_file.to_string());
+        }
 
         // integer/float
         if let Some(s) = matches.value_of("seed") {
@@ -1792,8 +3031,102 @@ impl PatchConfig {
             ));
         }
 
-        patch_config.parse()
+        Ok(patch_config)
+    }
+}
+
+pub fn parse_texture_id(texture: &str, field: &str) -> Result<u32, String> {
+    if let Some(info) = resource_info_table::texture_info(texture) {
+        return Ok(info.res_id);
+    }
+
+    let hex = texture.trim_start_matches("0x").trim_start_matches("0X");
+    u32::from_str_radix(hex, 16).map_err(|_| {
+        format!(
+            "'{}' in '{}' is neither a texture asset ID nor a known texture name",
+            texture, field
+        )
+    })
+}
+
+// The presets a config can inherit from by name rather than by path
+const BUNDLED_PRESETS: &[(&str, &str)] = &[
+    ("casual", PRESET_CASUAL),
+    ("competitive", PRESET_COMPETITIVE),
+    ("glitchless", PRESET_GLITCHLESS),
+];
+
+// Merges `overrides` into `base`. Objects are merged key by key, anything else (arrays included)
+// replaces what the base had, and null removes the key from the base.
+fn deep_merge_json(base: &mut serde_json::Value, overrides: serde_json::Value) {
+    match (base, overrides) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                if value.is_null() {
+                    base.remove(&key);
+                } else {
+                    let entry = base.entry(key).or_insert(serde_json::Value::Null);
+                    deep_merge_json(entry, value);
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+// Replaces the "preset" field of a config with the preset it names, with the rest of the config
+// merged on top. Presets can inherit from presets themselves. Relative paths are looked up from
+// `dir`, the directory of the config naming the preset.
+fn resolve_preset(
+    mut config: serde_json::Value,
+    dir: &Path,
+    seen: &mut Vec<String>,
+) -> Result<serde_json::Value, String> {
+    let preset = match config.as_object_mut().and_then(|obj| obj.remove("preset")) {
+        Some(preset) => preset,
+        None => return Ok(config),
+    };
+    let preset = preset.as_str().ok_or("'preset' must be a string")?.trim();
+
+    let bundled = BUNDLED_PRESETS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(preset));
+    let (text, preset_dir, key) = match bundled {
+        Some((name, text)) => (text.to_string(), dir.to_path_buf(), name.to_string()),
+        None => {
+            let path = dir.join(preset);
+            let text = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read preset {}: {}", path.display(), e))?;
+            let path = fs::canonicalize(&path).unwrap_or(path);
+            let preset_dir = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+            (text, preset_dir, path.display().to_string())
+        }
+    };
+    if seen.contains(&key) {
+        Err(format!("Preset '{}' inherits from itself", preset))?;
+    }
+    seen.push(key);
+
+    let base = serde_json::from_str(strip_jsonc_comments(&text, true).as_str())
+        .map_err(|e| format!("Failed to parse preset '{}': {}", preset, e))?;
+    let mut base = resolve_preset(base, &preset_dir, seen)?;
+    deep_merge_json(&mut base, config);
+    Ok(base)
+}
+
+// Parses a layout config, along with the presets it inherits from
+fn parse_config_json(json: &str, dir: &Path) -> Result<PatchConfigPrivate, String> {
+    let json = strip_jsonc_comments(json, true);
+    let config: serde_json::Value =
+        serde_json::from_str(json.as_str()).map_err(|e| format!("JSON parse failed: {}", e))?;
+    if config.get("preset").is_none() {
+        // Parsed from the text rather than the value so that errors point at a line
+        return serde_json::from_str(json.as_str())
+            .map_err(|e| format!("JSON parse failed: {}", e));
     }
+
+    let config = resolve_preset(config, dir, &mut vec![])?;
+    serde_json::from_value(config).map_err(|e| format!("JSON parse failed: {}", e))
 }
 
 fn merge_json(config: &mut PatchConfigPrivate, text: &'static str) -> Result<(), String> {
@@ -1902,6 +3235,8 @@ impl PatchConfigPrivate {
                 extend_option_vec!(pickups, self_room_config, other_room_config);
                 extend_option_vec!(extra_scans, self_room_config, other_room_config);
                 extend_option_vec!(platforms, self_room_config, other_room_config);
+                extend_option_vec!(stations, self_room_config, other_room_config);
+                extend_option_vec!(teleporters, self_room_config, other_room_config);
                 extend_option_vec!(camera_hints, self_room_config, other_room_config);
                 extend_option_vec!(blocks, self_room_config, other_room_config);
                 extend_option_vec!(lock_on_points, self_room_config, other_room_config);
@@ -1922,6 +3257,7 @@ impl PatchConfigPrivate {
                 extend_option_vec!(streamed_audios, self_room_config, other_room_config);
                 extend_option_vec!(waypoints, self_room_config, other_room_config);
                 extend_option_vec!(counters, self_room_config, other_room_config);
+                extend_option_vec!(objectives, self_room_config, other_room_config);
                 extend_option_vec!(switches, self_room_config, other_room_config);
                 extend_option_vec!(player_hints, self_room_config, other_room_config);
                 extend_option_vec!(distance_fogs, self_room_config, other_room_config);
@@ -1932,6 +3268,11 @@ impl PatchConfigPrivate {
                 extend_option_vec!(cameras, self_room_config, other_room_config);
                 extend_option_vec!(camera_waypoints, self_room_config, other_room_config);
                 extend_option_vec!(camera_filter_keyframes, self_room_config, other_room_config);
+                extend_option_vec!(effects, self_room_config, other_room_config);
+
+                if self_room_config.shuffle_bounds.is_none() {
+                    self_room_config.shuffle_bounds = other_room_config.shuffle_bounds.clone();
+                }
 
                 if let Some(other_layers) = &other_room_config.layers {
                     if self_room_config.layers.is_none() {
@@ -2067,18 +3408,24 @@ impl PatchConfigPrivate {
 
     // parse and then handle configuration macros (e.g. a bool loading in several pages of JSON changes)
     fn parse(&self) -> Result<PatchConfig, String> {
+        let (config, version) = self.with_builtin_configs()?;
+        Ok(config.parse_inner(version, false)?.unwrap())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let (config, version) = self.with_builtin_configs()?;
+        config.parse_inner(version, true)?;
+        Ok(())
+    }
+
+    // The config merged with the built-in ones it enables, and the version of the input ISO
+    fn with_builtin_configs(&self) -> Result<(Self, Version), String> {
         // Parse version
         let version = {
             let input_iso_path = self.input_iso.as_deref().unwrap_or("prime.iso");
-            let input_iso_file = File::open(input_iso_path.trim())
-                .map_err(|e| format!("Failed to open {}: {}", input_iso_path, e))?;
-            let input_iso = unsafe { memmap::Mmap::map(&input_iso_file) }
-                .map_err(|e| format!("Failed to open {}: {}", input_iso_path, e))?;
+            let header = input_iso::read_header(input_iso_path.trim())?;
 
-            let mut reader = Reader::new(&input_iso[..]);
-            let gc_disc: structs::GcDisc = reader.read(());
-
-            match (&gc_disc.header.game_identifier(), gc_disc.header.disc_id, gc_disc.header.version) {
+            match (&header.game_identifier(), header.disc_id, header.version) {
                 (b"GM8E01", 0, 0)  => Version::NtscU0_00,
                 (b"GM8E01", 0, 1)  => Version::NtscU0_01,
                 (b"GM8E01", 0, 2)  => Version::NtscU0_02,
@@ -2135,10 +3482,18 @@ impl PatchConfigPrivate {
             merge_json(&mut result, QOL)?;
         }
 
-        result.parse_inner(version)
+        // Bounds set by the config take precedence over the built-in ones
+        merge_json(&mut result, SHUFFLE_BOUNDS)?;
+
+        Ok((result, version))
     }
 
-    fn parse_inner(&self, version: Version) -> Result<PatchConfig, String> {
+    // Returns None when only validating, in which case the output ISO isn't touched
+    fn parse_inner(
+        &self,
+        version: Version,
+        validate_only: bool,
+    ) -> Result<Option<PatchConfig>, String> {
         let run_mode = {
             if self.run_mode.is_some() {
                 match self.run_mode.as_ref().unwrap().to_lowercase().trim() {
@@ -2153,29 +3508,69 @@ impl PatchConfigPrivate {
         };
 
         let input_iso_path = self.input_iso.as_deref().unwrap_or("prime.iso");
-        let input_iso_file = File::open(input_iso_path.trim())
-            .map_err(|e| format!("Failed to open {}: {}", input_iso_path, e))?;
-
-        let input_iso = unsafe { memmap::Mmap::map(&input_iso_file) }
-            .map_err(|e| format!("Failed to open {}: {}", input_iso_path, e))?;
+        let input_iso = InputIso::open(
+            input_iso_path.trim(),
+            self.preferences.mmap_input_iso.unwrap_or(true),
+        )?;
 
         let output_iso_path = self.output_iso.as_deref().unwrap_or("prime_out.iso");
 
-        let output_iso = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(output_iso_path)
-            .map_err(|e| format!("Failed to open {}: {}", output_iso_path, e))?;
-
-        let iso_format = if output_iso_path.ends_with(".gcz") {
+        // Keep what was already written if there's a journal to resume from
+        let output_iso_journal = if self.preferences.resumable_write.unwrap_or(false) {
+            Some(format!("{}.journal", output_iso_path))
+        } else {
+            None
+        };
+        let resuming = output_iso_journal
+            .as_ref()
+            .map(|path| std::path::Path::new(path).exists())
+            .unwrap_or(false);
+
+        let iso_format = if let Some(iso_format) = self.iso_format.as_ref() {
+            match iso_format.trim().to_lowercase().as_str() {
+                "iso" => IsoFormat::Iso,
+                "gcz" => IsoFormat::Gcz,
+                "ciso" => IsoFormat::Ciso,
+                "bps" => IsoFormat::Bps,
+                _ => Err(format!("Unknown isoFormat '{}'", iso_format))?,
+            }
+        } else if output_iso_path.ends_with(".gcz") {
             IsoFormat::Gcz
         } else if output_iso_path.ends_with(".ciso") {
             IsoFormat::Ciso
+        } else if output_iso_path.ends_with(".bps") {
+            IsoFormat::Bps
         } else {
             IsoFormat::Iso
         };
 
+        // The layout of the output ISO is kept next to it so the next run can patch it in place
+        let output_iso_manifest = if self.preferences.in_place.unwrap_or(false) {
+            if !matches!(iso_format, IsoFormat::Iso) {
+                Err("inPlace can only be used when outputting an uncompressed .iso")?
+            }
+            Some(format!("{}.manifest.json", output_iso_path))
+        } else {
+            None
+        };
+
+        // A sha256sum compatible hash of the output, to check that a seed generated identically
+        let output_iso_sha256 = if self.preferences.output_sha256.unwrap_or(false) {
+            Some(format!("{}.sha256", output_iso_path))
+        } else {
+            None
+        };
+
+        // Per-file hashes of the output, which the verify subcommand checks a disc against
+        let output_iso_patch_manifest = if self.preferences.output_manifest.unwrap_or(false) {
+            if !matches!(iso_format, IsoFormat::Iso) {
+                Err("outputManifest can only be used when outputting an uncompressed .iso")?
+            }
+            Some(format!("{}.hashes.json", output_iso_path))
+        } else {
+            None
+        };
+
         let force_vanilla_layout = self.force_vanilla_layout.unwrap_or(false);
 
         let artifact_hint_behavior = {
@@ -2200,6 +3595,272 @@ impl PatchConfigPrivate {
             }
         };
 
+        let colorblind_mode = {
+            let colorblind_mode_string = self
+                .preferences
+                .colorblind_mode
+                .as_deref()
+                .unwrap_or("none")
+                .trim()
+                .to_lowercase();
+            if colorblind_mode_string == "none" {
+                ColorblindMode::None
+            } else if colorblind_mode_string == "protanopia" {
+                ColorblindMode::Protanopia
+            } else if colorblind_mode_string == "deuteranopia" {
+                ColorblindMode::Deuteranopia
+            } else if colorblind_mode_string == "tritanopia" {
+                ColorblindMode::Tritanopia
+            } else {
+                Err(format!(
+                    "Unhandled colorblind mode - '{}'",
+                    colorblind_mode_string
+                ))?
+            }
+        };
+
+        let timer_config = self.preferences.timer_config.clone().unwrap_or_default();
+        let addresses = [
+            ("Splits", timer_config.splits_address),
+            ("Ghost recording", self.preferences.ghost_recording_address),
+            ("Tracker", self.preferences.tracker_address),
+        ];
+        for (name, address) in addresses {
+            if let Some(address) = address {
+                if !(0x80000000..0x81800000).contains(&address) || address % 8 != 0 {
+                    Err(format!(
+                        "{} address 0x{:X} must be 8-byte aligned and within MEM1",
+                        name, address
+                    ))?
+                }
+            }
+        }
+        // The frames dumped are those of the recording
+        if self.preferences.ghost_memory_card_dump.unwrap_or(false)
+            && self.preferences.ghost_recording_address.is_none()
+        {
+            Err("preferences.ghostMemoryCardDump requires preferences.ghostRecordingAddress")?;
+        }
+        // The result of the check is only visible in the tracker region
+        if self.preferences.integrity_check.unwrap_or(false)
+            && self.preferences.tracker_address.is_none()
+        {
+            Err("preferences.integrityCheck requires preferences.trackerAddress")?;
+        }
+
+        for (boss_name, boss_config) in self.game_config.bosses.iter().flatten() {
+            let name = boss_name.to_lowercase().replace([' ', '_'], "");
+            if !BOSS_NAMES.contains(&name.as_str()) {
+                Err(format!("Unknown boss '{}' in bosses", boss_name))?;
+            }
+            if boss_config.health_multiplier.is_some_and(|m| m <= 0.0) {
+                Err(format!(
+                    "bosses.{}.healthMultiplier must be greater than 0",
+                    boss_name
+                ))?;
+            }
+            if boss_config.skip_phases.unwrap_or(false) && name != "omegapirate" {
+                Err(format!(
+                    "bosses.{}.skipPhases is only supported for omegaPirate",
+                    boss_name
+                ))?;
+            }
+        }
+
+        let final_bosses = self.game_config.final_bosses.unwrap_or_default();
+        let portal_opens_immediately = final_bosses.portal_opens_immediately.unwrap_or(false);
+
+        let mut texture_overrides = HashMap::new();
+        for (texture, path) in self
+            .preferences
+            .texture_overrides
+            .iter()
+            .flat_map(|overrides| overrides.iter())
+        {
+            let res_id = parse_texture_id(texture, "textureOverrides")?;
+            texture_overrides.insert(res_id, path.clone());
+        }
+
+        // Custom suit textures are just texture overrides restricted to that suit's textures
+        let cosmetics = self.preferences.cosmetics.clone().unwrap_or_default();
+        if let Some(suits) = cosmetics.suits.as_ref() {
+            for (name, suit, suit_textures) in [
+                ("power", &suits.power, POWER_SUIT_TEXTURES),
+                ("varia", &suits.varia, VARIA_SUIT_TEXTURES),
+                ("gravity", &suits.gravity, GRAVITY_SUIT_TEXTURES),
+                ("phazon", &suits.phazon, PHAZON_SUIT_TEXTURES),
+            ] {
+                for (texture, path) in suit.iter().flat_map(|suit| suit.textures.iter()).flatten() {
+                    let res_id = parse_texture_id(texture, "cosmetics.suits")?;
+                    if !suit_textures.iter().any(|info| info.res_id == res_id) {
+                        Err(format!(
+                            "'{}' in 'cosmetics.suits.{}.textures' is not a {} suit texture",
+                            texture, name, name
+                        ))?;
+                    }
+                    texture_overrides.insert(res_id, path.clone());
+                }
+            }
+        }
+
+        // Big enough to be noticed, small enough that pickups still look right and fit in their
+        // hitbox
+        if let Some(pickups) = cosmetics.pickups.as_ref() {
+            for (name, jitter) in [
+                ("major", &pickups.major),
+                ("expansion", &pickups.expansion),
+                ("artifact", &pickups.artifact),
+                ("nothing", &pickups.nothing),
+            ] {
+                let jitter = jitter.unwrap_or_default();
+                if !(0.0..=180.0).contains(&jitter.rotation.unwrap_or(0.0)) {
+                    Err(format!(
+                        "'cosmetics.pickups.{}.rotation' must be between 0 and 180",
+                        name
+                    ))?;
+                }
+                if !(0.0..=0.25).contains(&jitter.scale.unwrap_or(0.0)) {
+                    Err(format!(
+                        "'cosmetics.pickups.{}.scale' must be between 0 and 0.25",
+                        name
+                    ))?;
+                }
+            }
+        }
+
+        if cosmetics
+            .low_energy_alarm_threshold
+            .is_some_and(|threshold| threshold < 0.0)
+        {
+            Err("'cosmetics.lowEnergyAlarmThreshold' must be at least 0".to_string())?;
+        }
+
+        for (world_name, hologram) in cosmetics.elevator_holograms.iter().flatten() {
+            let is_world = World::iter().any(|world| {
+                world != World::EndCinema
+                    && world_name.trim().to_lowercase() == world.to_json_key().to_lowercase()
+            });
+            if !is_world {
+                Err(format!(
+                    "'{}' in 'cosmetics.elevatorHolograms' is not a world",
+                    world_name
+                ))?;
+            }
+            for (texture, _) in hologram.textures.iter().flatten() {
+                parse_texture_id(texture, "cosmetics.elevatorHolograms")?;
+            }
+        }
+
+        let mut sound_overrides = HashMap::new();
+        for (sound_id, sound_override) in self
+            .preferences
+            .sound_overrides
+            .iter()
+            .flat_map(|overrides| overrides.iter())
+        {
+            let parsed = match sound_id.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => sound_id.parse::<u32>(),
+            };
+            let sound_id = parsed.map_err(|_| {
+                format!("'{}' in 'soundOverrides' is not a valid sound ID", sound_id)
+            })?;
+            if matches!(sound_override.volume, Some(volume) if volume > 127) {
+                Err(format!(
+                    "Volume for sound {} in 'soundOverrides' must be between 0 and 127",
+                    sound_id
+                ))?;
+            }
+            sound_overrides.insert(sound_id, *sound_override);
+        }
+
+        // Normalize to "World" and "World:Room" so they can be compared against ROOM_INFO
+        let starting_maps: Vec<String> = self
+            .game_config
+            .starting_maps
+            .clone()
+            .unwrap_or_default()
+            .iter()
+            .map(|world| World::from_json_key(world).to_json_key().to_string())
+            .collect();
+
+        let mut starting_visited_rooms: Vec<String> = self
+            .game_config
+            .starting_visited_rooms
+            .clone()
+            .unwrap_or_default()
+            .iter()
+            .map(|room| {
+                let (world, room_name) = room.split_once(':').ok_or_else(|| {
+                    format!(
+                        "Invalid startingVisitedRooms entry '{}', expected 'World:Room'",
+                        room
+                    )
+                })?;
+                Ok(format!(
+                    "{}:{}",
+                    World::from_json_key(world).to_json_key(),
+                    room_name.trim()
+                ))
+            })
+            .collect::<Result<_, String>>()?;
+
+        if let Some(elevator_audio) = self.game_config.elevator_audio.as_ref() {
+            if matches!(&elevator_audio.files, Some(files) if files.is_empty()) {
+                Err("elevatorAudio.files must not be empty")?;
+            }
+            for (world_name, files) in elevator_audio.destinations.iter().flatten() {
+                let is_world = World::iter().any(|world| {
+                    world != World::EndCinema
+                        && world_name.trim().to_lowercase() == world.to_json_key().to_lowercase()
+                });
+                if !is_world {
+                    Err(format!(
+                        "'{}' in 'elevatorAudio.destinations' is not a world",
+                        world_name
+                    ))?;
+                }
+                if files.is_empty() {
+                    Err(format!(
+                        "elevatorAudio.destinations.{} must not be empty",
+                        world_name
+                    ))?;
+                }
+            }
+        }
+
+        if let Some(totem_hints) = self.game_config.artifact_totem_hints.as_ref() {
+            for (name, templates) in [
+                ("templates", &totem_hints.templates),
+                ("vagueTemplates", &totem_hints.vague_templates),
+            ] {
+                if matches!(templates, Some(templates) if templates.is_empty()) {
+                    Err(format!("artifactTotemHints.{} must not be empty", name))?;
+                }
+            }
+        }
+
+        // A random selection of the rooms of each world, the same for the same seed
+        if let Some(reveal) = self.game_config.starting_map_reveal {
+            if !(0.0..=100.0).contains(&reveal) {
+                Err("startingMapReveal must be between 0 and 100")?;
+            }
+            let mut rng = StdRng::seed_from_u64(self.seed.unwrap_or(123));
+            for world in World::iter() {
+                let mut rooms: Vec<&str> = ROOM_BY_NAME
+                    .keys()
+                    .filter(|(region, _)| region == world.to_json_key())
+                    .map(|(_, room)| room.as_str())
+                    .collect();
+                rooms.sort();
+                rooms.shuffle(&mut rng);
+                let count = (rooms.len() as f32 * reveal / 100.0).round() as usize;
+                for room in rooms.into_iter().take(count) {
+                    starting_visited_rooms.push(format!("{}:{}", world.to_json_key(), room));
+                }
+            }
+        }
+
         let map_default_state = {
             let map_default_state_string = self
                 .preferences
@@ -2239,6 +3900,14 @@ impl PatchConfigPrivate {
             panic!("Illegal pickup name in 'itemMaxCapacity'");
         }
 
+        let item_acquisition = match &self.game_config.item_acquisition {
+            Some(item_acquisition) => item_acquisition
+                .iter()
+                .map(|(name, config)| (PickupType::from_str(name), *config))
+                .collect(),
+            None => HashMap::new(),
+        };
+
         let qol_game_breaking = self
             .preferences
             .qol_game_breaking
@@ -2275,6 +3944,70 @@ impl PatchConfigPrivate {
             ),
         };
 
+        let mut cutscene_overrides = HashMap::new();
+        for (room, cutscene_override) in self.preferences.cutscene_overrides.iter().flatten() {
+            let (region_name, room_name) = room
+                .split_once(':')
+                .ok_or_else(|| format!("Expected 'World:Room' in cutscene override '{}'", room))?;
+            let room_lookup = ROOM_BY_NAME
+                .get(&(region_name.trim().to_string(), room_name.trim().to_string()))
+                .ok_or_else(|| format!("Unknown room '{}' in cutscene overrides", room))?;
+            cutscene_overrides.insert(room_lookup.mrea_id, *cutscene_override);
+        }
+
+        let mut exclude_rooms = Vec::new();
+        for room in self.game_config.exclude_rooms.iter().flatten() {
+            let (region_name, room_name) = room
+                .split_once(':')
+                .ok_or_else(|| format!("Expected 'World:Room' in excludeRooms, got '{}'", room))?;
+            let room_lookup = ROOM_BY_NAME
+                .get(&(region_name.trim().to_string(), room_name.trim().to_string()))
+                .ok_or_else(|| format!("Unknown room '{}' in excludeRooms", room))?;
+            exclude_rooms.push(room_lookup.mrea_id);
+        }
+
+        if let Some(hazards) = self.game_config.hazards.as_ref() {
+            // The particles and volumes are only added to the rooms' dependencies automatically
+            if !self.preferences.auto_dependencies.unwrap_or(true) {
+                Err("gameConfig.hazards requires preferences.autoDependencies")?;
+            }
+            for room in hazards.exclude_rooms.iter().flatten() {
+                let (region_name, room_name) = room.split_once(':').ok_or_else(|| {
+                    format!(
+                        "Expected 'World:Room' in hazards.excludeRooms, got '{}'",
+                        room
+                    )
+                })?;
+                let key = (region_name.trim().to_string(), room_name.trim().to_string());
+                if !ROOM_BY_NAME.contains_key(&key) {
+                    Err(format!("Unknown room '{}' in hazards.excludeRooms", room))?;
+                }
+            }
+        }
+
+        for (world_name, respawn) in self.game_config.respawn_enemies.iter().flatten() {
+            if !World::iter().any(|world| world.to_json_key() == world_name) {
+                Err(format!("Unknown world '{}' in respawnEnemies", world_name))?;
+            }
+            if let Some(intensity) = respawn.intensity {
+                if !(0.0..=1.0).contains(&intensity) {
+                    Err(format!(
+                        "respawnEnemies.{}.intensity must be between 0 and 1",
+                        world_name
+                    ))?;
+                }
+            }
+            for room in respawn.exclude_rooms.iter().flatten() {
+                let key = (world_name.to_string(), room.trim().to_string());
+                if !ROOM_BY_NAME.contains_key(&key) {
+                    Err(format!(
+                        "Unknown room '{}' in respawnEnemies.{}.excludeRooms",
+                        room, world_name
+                    ))?;
+                }
+            }
+        }
+
         let starting_room = {
             let room = self.game_config.starting_room.as_ref();
             match room {
@@ -2304,6 +4037,38 @@ impl PatchConfigPrivate {
             }
         };
 
+        let starting_presets = self
+            .game_config
+            .starting_presets
+            .clone()
+            .unwrap_or_default();
+        {
+            let mut files = HashSet::new();
+            let mut rooms = vec![SpawnRoomData::from_str(&starting_room).mrea];
+            for preset in starting_presets.iter() {
+                if !files.insert(preset.file) {
+                    Err(format!(
+                        "There is more than one starting preset for file {:?}",
+                        preset.file
+                    ))?;
+                }
+                let room = SpawnRoomData::from_str(&preset.starting_room);
+                if room.mlvl == World::FrigateOrpheon.mlvl() {
+                    Err(format!(
+                        "The starting preset of file {:?} can't start in the Frigate",
+                        preset.file
+                    ))?;
+                }
+                if rooms.contains(&room.mrea) {
+                    Err(format!(
+                        "The starting preset of file {:?} starts in '{}', which is already the starting room of the game or of another preset",
+                        preset.file, preset.starting_room
+                    ))?;
+                }
+                rooms.push(room.mrea);
+            }
+        }
+
         let default_starting_visor = if starting_items.combat_visor {
             "combat"
         } else if starting_items.thermal_visor {
@@ -2421,6 +4186,7 @@ impl PatchConfigPrivate {
                 "default" => PhazonDamageModifier::Default,
                 "linear_delayed" => PhazonDamageModifier::LinearDelayed,
                 "linear" => PhazonDamageModifier::Linear,
+                "none" => PhazonDamageModifier::None,
                 _ => Err(format!(
                     "Unhandled phazon damage modifier - '{}'",
                     map_default_state_string
@@ -2428,6 +4194,315 @@ impl PatchConfigPrivate {
             }
         };
 
+        let logic_flags = LogicFlags {
+            varia_only_heat_protection: self.game_config.nonvaria_heat_damage.unwrap_or(false),
+            heat_damage_per_sec: self.game_config.heat_damage_per_sec.unwrap_or(10.0),
+            lava_surface_damage: !self.game_config.no_lava_surface_damage.unwrap_or(false),
+            poison_damage_per_sec: self.game_config.poison_damage_per_sec.unwrap_or(0.11),
+            phazon_damage_modifier,
+            phazon_damage_per_sec: if phazon_damage_modifier == PhazonDamageModifier::None {
+                0.0
+            } else {
+                self.game_config.phazon_damage_per_sec.unwrap_or(0.964)
+            },
+        };
+
+        let pal_timing = self.preferences.pal_timing.unwrap_or(PalTiming::Default);
+        if pal_timing != PalTiming::Default && version != Version::Pal {
+            Err(format!(
+                "palTiming is only supported for PAL Metroid Prime, not {}",
+                version
+            ))?;
+        }
+
+        let game_banner = self.game_config.game_banner.clone().unwrap_or_default();
+        for language in game_banner.languages.iter().flat_map(|l| l.keys()) {
+            if banner_language_index(language).is_none() {
+                Err(format!(
+                    "Unknown language '{}' in gameBanner.languages",
+                    language
+                ))?;
+            }
+            // Only PAL discs (BNR2) have text for languages other than English
+            if version != Version::Pal {
+                Err(format!(
+                    "gameBanner.languages is only supported for PAL Metroid Prime, not {}",
+                    version
+                ))?;
+            }
+        }
+        let save_icon_count = game_banner
+            .save_icons
+            .as_ref()
+            .map_or(0, |icons| icons.len());
+        if save_icon_count > 8 {
+            Err("gameBanner.saveIcons supports at most 8 animation frames")?;
+        }
+        if !(1..=3).contains(&game_banner.save_icon_speed.unwrap_or(2)) {
+            Err("gameBanner.saveIconSpeed must be between 1 and 3")?;
+        }
+        // The frames are locked through CCardFileInfo::LockIconToken, which only these symbol
+        // tables have
+        if (save_icon_count > 2 || game_banner.save_icon_speed.is_some())
+            && !matches!(
+                version,
+                Version::NtscU0_00 | Version::NtscU0_02 | Version::Pal
+            )
+        {
+            Err(format!(
+                "More than 2 gameBanner.saveIcons and saveIconSpeed are not supported for version {}",
+                version
+            ))?;
+        }
+
+        let starting_logbook = self
+            .game_config
+            .starting_logbook
+            .clone()
+            .unwrap_or_default();
+        // The scans are set in CPlayerState::InitializeScanTimes, which only the NTSC 0-00 and
+        // 0-02 symbol tables have
+        if !starting_logbook.is_empty()
+            && ![Version::NtscU0_00, Version::NtscU0_02].contains(&version)
+        {
+            Err(format!(
+                "startingLogbook is only supported for NTSC 0-00 and 0-02 Metroid Prime, not {}",
+                version
+            ))?;
+        }
+
+        let display = self.preferences.display.unwrap_or_default();
+        // The aspect ratio is widened in the CGameCamera constructor, which only the NTSC 0-00
+        // and 0-02 symbol tables have
+        if display.widescreen.unwrap_or(false)
+            && ![Version::NtscU0_00, Version::NtscU0_02].contains(&version)
+        {
+            Err(format!(
+                "widescreen is only supported for NTSC 0-00 and 0-02 Metroid Prime, not {}",
+                version
+            ))?;
+        }
+        let mut ctwk_config = self.tweaks.clone();
+        if ctwk_config.fov.is_none() {
+            ctwk_config.fov = display.fov;
+        }
+
+        // Fill in random pickups and the looks of nothing pickups, resolve progressive pickups, key
+        // pickup text and place
+        // teleporter scan text with the room's extra scans
+        let mut level_data = self.level_data.clone();
+        fill_random_placeholders(&mut level_data, self.seed.unwrap_or(123))?;
+        if let Some(nothing_cosmetics) = self.game_config.nothing_cosmetics.as_ref() {
+            fill_nothing_cosmetics(&mut level_data, nothing_cosmetics, self.seed.unwrap_or(123));
+        }
+
+        let elevator_audio = match self.game_config.elevator_audio.as_ref() {
+            Some(elevator_audio) => {
+                pick_elevator_audio(&level_data, elevator_audio, self.seed.unwrap_or(123))
+            }
+            None => HashMap::new(),
+        };
+
+        let mut progressive_items: HashMap<String, Vec<PickupType>> = HashMap::new();
+        progressive_items.insert(
+            "beam".to_string(),
+            vec![
+                PickupType::WaveBeam,
+                PickupType::IceBeam,
+                PickupType::PlasmaBeam,
+            ],
+        );
+        progressive_items.insert(
+            "suit".to_string(),
+            vec![
+                PickupType::VariaSuit,
+                PickupType::GravitySuit,
+                PickupType::PhazonSuit,
+            ],
+        );
+        for (name, items) in self.game_config.progressive_items.iter().flatten() {
+            let chain = parse_progressive_chain(name, items)?;
+            progressive_items.insert(name.trim().to_lowercase(), chain);
+        }
+
+        // Chains are numbered in name order, only the ones which are placed are sent to the payload
+        let mut progressive_names: Vec<String> = level_data
+            .values()
+            .flat_map(|level| level.rooms.values())
+            .flat_map(|room| room.pickups.iter().flatten())
+            .filter_map(|pickup| progressive_chain_name(&pickup.pickup_type))
+            .map(|name| name.to_lowercase())
+            .collect();
+        progressive_names.sort();
+        progressive_names.dedup();
+        if progressive_names.len() > MAX_PROGRESSIVE_CHAINS {
+            Err(format!(
+                "At most {} different progressive items can be placed",
+                MAX_PROGRESSIVE_CHAINS
+            ))?;
+        }
+        let progressive_chains = progressive_names
+            .iter()
+            .map(|name| {
+                progressive_items
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| format!("Unknown progressive item '{}'", name))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if !progressive_chains.is_empty()
+            && [
+                Version::NtscUTrilogy,
+                Version::NtscJTrilogy,
+                Version::PalTrilogy,
+            ]
+            .contains(&version)
+        {
+            Err("Progressive items are not supported on Metroid Prime Trilogy")?;
+        }
+
+        let mut key_worlds: HashMap<String, String> = HashMap::new();
+        let mut door_keys: Vec<(String, String)> = Vec::new();
+        for (world, level) in level_data.iter_mut() {
+            for room in level.rooms.values_mut() {
+                for pickup in room.pickups.iter_mut().flatten() {
+                    // The pickup gives the first item of the chain, which the payload replaces
+                    // with the next one the player doesn't have yet
+                    if let Some(name) = progressive_chain_name(&pickup.pickup_type) {
+                        let name = name.to_string();
+                        let index = progressive_names
+                            .iter()
+                            .position(|n| n.eq_ignore_ascii_case(&name))
+                            .unwrap();
+                        pickup.pickup_type = progressive_chains[index][0].name().to_string();
+                        pickup.curr_increase = Some(PROGRESSIVE_PICKUP_AMOUNT + index as i32);
+                        pickup.max_increase = Some(0);
+
+                        if pickup.scan_text.is_none() {
+                            pickup.scan_text = Some(format!("Progressive {}", name));
+                        }
+                        if pickup.hudmemo_text.is_none() {
+                            pickup.hudmemo_text = Some(format!("Progressive {} acquired!", name));
+                        }
+                    }
+
+                    if let Some(key) = pickup.key.as_ref() {
+                        let key_world = key_worlds.entry(key.clone()).or_insert(world.clone());
+                        if key_world != world {
+                            Err(format!(
+                                "Key '{}' is used in both {} and {}, keys only work within one world",
+                                key, key_world, world
+                            ))?;
+                        }
+
+                        if pickup.scan_text.is_none() {
+                            pickup.scan_text = Some(key_model_name(key));
+                        }
+                        if pickup.hudmemo_text.is_none() {
+                            pickup.hudmemo_text =
+                                Some(format!("{} acquired!", key_model_name(key)));
+                        }
+                        if pickup.model.is_none() {
+                            pickup.model = Some(key_model_name(key));
+                        }
+                    }
+                }
+
+                for door in room.doors.iter().flat_map(|doors| doors.values()) {
+                    if let Some(key) = door.key.as_ref() {
+                        door_keys.push((world.clone(), key.clone()));
+
+                        // The other side is found in vanilla, which a new destination would break
+                        if door.destination.is_some() {
+                            Err(format!(
+                                "A door in {} has both a key and a destination, locked doors can't lead elsewhere",
+                                world
+                            ))?;
+                        }
+                    }
+
+                    if door.scan_text.is_some()
+                        && door
+                            .blast_shield_type
+                            .as_deref()
+                            .and_then(BlastShieldType::from_str)
+                            != Some(BlastShieldType::Scan)
+                    {
+                        Err(format!(
+                            "A door in {} has scanText, but only Scan blast shields can be scanned",
+                            world
+                        ))?;
+                    }
+
+                    if door.blast_shield_cost.is_some() {
+                        if !is_costed_blast_shield(door.blast_shield_type.as_deref()) {
+                            Err(format!(
+                                "A door in {} has a blastShieldCost, but only Missile and Power Bomb blast shields can cost ammo",
+                                world
+                            ))?;
+                        }
+                        if [
+                            Version::NtscUTrilogy,
+                            Version::NtscJTrilogy,
+                            Version::PalTrilogy,
+                        ]
+                        .contains(&version)
+                        {
+                            Err("Blast shield costs are not supported on Metroid Prime Trilogy")?;
+                        }
+                    }
+                }
+
+                if let Some(teleporters) = room.teleporters.as_ref() {
+                    let extra_scans = room.extra_scans.get_or_insert_with(Vec::new);
+                    for teleporter in teleporters {
+                        if let Some(text) = teleporter.scan_text.as_ref() {
+                            let [x, y, z] = teleporter.position;
+                            extra_scans.push(ScanConfig {
+                                id: None,
+                                layer: teleporter.layer,
+                                position: [x, y, z + 1.5],
+                                combat_visible: Some(false),
+                                rotation: None,
+                                is_red: None,
+                                logbook_category: None,
+                                logbook_title: None,
+                                text: text.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        if key_worlds.len() > MAX_KEYS {
+            Err(format!(
+                "Pickups give {} different keys, at most {} are supported",
+                key_worlds.len(),
+                MAX_KEYS
+            ))?;
+        }
+
+        for (world, key) in door_keys {
+            if key_worlds.get(&key) != Some(&world) {
+                Err(format!(
+                    "A door in {} requires key '{}', but no pickup in that world gives it",
+                    world, key
+                ))?;
+            }
+        }
+
+        if validate_only {
+            return Ok(None);
+        }
+        let output_iso = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(!resuming && output_iso_manifest.is_none())
+            .open(output_iso_path)
+            .map_err(|e| format!("Failed to open {}: {}", output_iso_path, e))?;
+
         let result = PatchConfig {
             run_mode,
             logbook_filename: self.logbook_filename.clone(),
@@ -2436,18 +4511,24 @@ impl PatchConfigPrivate {
             input_iso,
             iso_format,
             output_iso,
+            output_iso_journal,
+            output_iso_manifest,
+            output_iso_sha256,
+            output_iso_patch_manifest,
             force_vanilla_layout,
 
             seed: self.seed.unwrap_or(123),
             uuid: self.uuid,
             extern_assets_dir: self.extern_assets_dir.clone(),
 
-            level_data: self.level_data.clone(),
+            level_data,
             strg: self.strg.clone(),
 
             qol_game_breaking,
+            softlock_fixes: self.preferences.softlock_fixes.clone().unwrap_or_default(),
             qol_cosmetic,
             qol_cutscenes,
+            cutscene_overrides,
             qol_pickup_scans,
             qol_general,
 
@@ -2482,20 +4563,53 @@ impl PatchConfigPrivate {
             artifact_hint_behavior,
             flaahgra_music_files,
             suit_colors: self.preferences.suit_colors.clone(),
+            cosmetics,
             force_fusion: self.preferences.force_fusion.unwrap_or(false),
             cache_dir: self
                 .preferences
                 .cache_dir
                 .clone()
                 .unwrap_or("cache".to_string()),
+            fog_shuffle: self.preferences.fog_shuffle.unwrap_or(false),
+            colorblind_mode,
+            texture_overrides,
+            sound_overrides,
+            frme_widgets: self.preferences.frme_widgets.clone().unwrap_or_default(),
             skip_splash_screens: self.preferences.skip_splash_screens.unwrap_or(false),
             default_game_options: self.preferences.default_game_options.clone(),
             quiet: self.preferences.quiet.unwrap_or(false),
+            strict_version: self.preferences.strict_version.unwrap_or(false),
+            validate_output: self.preferences.validate_output.unwrap_or(false),
+            auto_dependencies: self.preferences.auto_dependencies.unwrap_or(true),
+            prune_dependencies: self.preferences.prune_dependencies.unwrap_or(false),
+            save_protection: self
+                .preferences
+                .save_protection
+                .unwrap_or(SaveProtection::Off),
+            log_level: self.preferences.log_level.unwrap_or(
+                if self.preferences.quiet.unwrap_or(false) {
+                    LogLevel::Warn
+                } else {
+                    LogLevel::Info
+                },
+            ),
+            log_file: self.preferences.log_file.clone(),
             quickplay: self.preferences.quickplay.unwrap_or(false),
+            debug_features: self.preferences.debug_features.unwrap_or(false),
+            visualize_volumes: self.preferences.visualize_volumes.unwrap_or(false),
             quickpatch: self.preferences.quickpatch.unwrap_or(false),
+            optimize_paks: self.preferences.optimize_paks.unwrap_or(false),
+            fast_disc_layout: self.preferences.fast_disc_layout.unwrap_or(false),
+            pal_timing,
+            timer_config,
+            ghost_recording_address: self.preferences.ghost_recording_address,
+            ghost_memory_card_dump: self.preferences.ghost_memory_card_dump.unwrap_or(false),
+            tracker_address: self.preferences.tracker_address,
+            integrity_check: self.preferences.integrity_check.unwrap_or(false),
 
             starting_room,
             starting_memo: self.game_config.starting_memo.clone(),
+            loadout_memo: self.game_config.loadout_memo.unwrap_or(false),
             spring_ball,
             warp_to_start,
             warp_to_start_delay_s: self.game_config.warp_to_start_delay_s.unwrap_or(0.0),
@@ -2505,18 +4619,31 @@ impl PatchConfigPrivate {
                 .game_config
                 .shuffle_pickup_pos_all_rooms
                 .unwrap_or(false),
+            exclude_rooms,
             remove_vanilla_blast_shields: self
                 .game_config
                 .remove_vanilla_blast_shields
                 .unwrap_or(false),
-            nonvaria_heat_damage: self.game_config.nonvaria_heat_damage.unwrap_or(false),
+            nonvaria_heat_damage: logic_flags.varia_only_heat_protection,
             staggered_suit_damage: self.game_config.staggered_suit_damage.unwrap_or_default(),
-            heat_damage_per_sec: self.game_config.heat_damage_per_sec.unwrap_or(10.0),
-            poison_damage_per_sec: self.game_config.poison_damage_per_sec.unwrap_or(0.11),
-            phazon_damage_per_sec: self.game_config.phazon_damage_per_sec.unwrap_or(0.964),
+            heat_damage_per_sec: logic_flags.heat_damage_per_sec,
+            poison_damage_per_sec: logic_flags.poison_damage_per_sec,
+            phazon_damage_per_sec: logic_flags.phazon_damage_per_sec,
             phazon_damage_modifier,
+            no_lava_surface_damage: !logic_flags.lava_surface_damage,
+            logic_flags,
             auto_enabled_elevators: self.game_config.auto_enabled_elevators.unwrap_or(false),
-            skip_ridley: self.game_config.skip_ridley.unwrap_or(false),
+            elevator_audio,
+            skip_ridley: self.game_config.skip_ridley.unwrap_or(false)
+                || final_bosses.skip_meta_ridley.unwrap_or(false)
+                || portal_opens_immediately,
+            skip_exo: final_bosses.skip_exo.unwrap_or(false),
+            skip_essence: final_bosses.skip_essence.unwrap_or(false),
+            skip_ending_cinematic: final_bosses.skip_ending_cinematic,
+            blackout: self.game_config.blackout,
+            hazards: self.game_config.hazards.clone(),
+            respawn_enemies: self.game_config.respawn_enemies.clone().unwrap_or_default(),
+            phazon_suit_from_pickup: self.game_config.phazon_suit_from_pickup.unwrap_or(false),
             multiworld_dol_patches: self.game_config.multiworld_dol_patches.unwrap_or(false),
             update_hint_state_replacement: self.game_config.update_hint_state_replacement.clone(),
             artifact_temple_layer_overrides: self
@@ -2525,6 +4652,7 @@ impl PatchConfigPrivate {
                 .clone(),
             no_doors: self.game_config.no_doors.unwrap_or(false),
             boss_sizes: self.game_config.boss_sizes.clone().unwrap_or_default(),
+            bosses: self.game_config.bosses.clone().unwrap_or_default(),
             shoot_in_grapple: self.game_config.shoot_in_grapple.unwrap_or(false),
             difficulty_behavior: self
                 .game_config
@@ -2533,8 +4661,16 @@ impl PatchConfigPrivate {
             legacy_block_size: self.game_config.legacy_block_size.unwrap_or(false),
             patch_wallcrawling: self.game_config.patch_wallcrawling.unwrap_or(false),
             map_default_state,
+            map_collected_pickup_icons: self
+                .preferences
+                .map_collected_pickup_icons
+                .unwrap_or(false),
 
             starting_items,
+            starting_presets,
+            starting_maps,
+            starting_visited_rooms,
+            starting_logbook,
             item_loss_items: self
                 .game_config
                 .item_loss_items
@@ -2553,20 +4689,30 @@ impl PatchConfigPrivate {
 
             etank_capacity: self.game_config.etank_capacity.unwrap_or(100),
             item_max_capacity,
+            item_acquisition,
+            progressive_chains,
 
-            game_banner: self.game_config.game_banner.clone().unwrap_or_default(),
+            game_banner,
+            title_screen: self.game_config.title_screen.clone().unwrap_or_default(),
             comment: self.game_config.comment.clone().unwrap_or_default(),
             main_menu_message,
 
             credits_string,
             results_string,
             artifact_hints: self.game_config.artifact_hints.clone(),
-            required_artifact_count: self.game_config.required_artifact_count,
+            artifact_totem_hints: self.game_config.artifact_totem_hints.clone(),
+            required_artifact_count: if portal_opens_immediately {
+                Some(0)
+            } else {
+                self.game_config.required_artifact_count
+            },
 
-            ctwk_config: self.tweaks.clone(),
+            display,
+            ctwk_config,
+            seed_info: self.game_config.seed_info.clone(),
         };
 
-        Ok(result)
+        Ok(Some(result))
     }
 }
 
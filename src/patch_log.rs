@@ -0,0 +1,91 @@
+use std::{fs::File, io::Write, sync::Mutex, time::Instant};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::json;
+
+use crate::patch_config::LogLevel;
+
+/// Prints the patcher's log messages to stdout and, if a log file was given, also writes each of
+/// them to it as a line of JSON so that frontends can parse them.
+struct PatchLogger {
+    json_log: Mutex<Option<(Instant, File)>>,
+}
+
+static LOGGER: PatchLogger = PatchLogger {
+    json_log: Mutex::new(None),
+};
+
+impl Log for PatchLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        match record.level() {
+            Level::Error => println!("Error: {}", record.args()),
+            Level::Warn => println!("Warning: {}", record.args()),
+            Level::Info => println!("{}", record.args()),
+            Level::Debug | Level::Trace => {
+                println!(
+                    "[{}] {}",
+                    record.level().to_string().to_lowercase(),
+                    record.args()
+                )
+            }
+        }
+
+        if let Some((start, file)) = self.json_log.lock().unwrap().as_mut() {
+            let line = json!({
+                "elapsedMs": start.elapsed().as_millis() as u64,
+                "level": record.level().to_string(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+            // A log line failing to be written isn't worth aborting the patching for
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some((_, file)) = self.json_log.lock().unwrap().as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Sets up the logger for a run of the patcher. It can be called again for every run in the same
+/// process (e.g. through the C interface), replacing the level and the log file.
+pub fn init(level: LogLevel, json_log_path: Option<&str>) -> Result<(), String> {
+    let json_log = match json_log_path {
+        Some(path) => {
+            let file = File::create(path)
+                .map_err(|e| format!("Failed to create log file {}: {}", path, e))?;
+            Some((Instant::now(), file))
+        }
+        None => None,
+    };
+    *LOGGER.json_log.lock().unwrap() = json_log;
+
+    // Only fails if the logger was already set by a previous run
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level.into());
+
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use reader_writer::Reader;
+use serde::{Deserialize, Serialize};
+
+use crate::{sha1::sha1, sha256::to_hex, GcDiscLookupExtensions};
+
+/// Hashes of the files of a patched disc, written next to the output so that a disc can later be
+/// checked against it with the `verify` subcommand.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PatchManifest {
+    pub randomprime_version: String,
+    /// SHA-1 of the randomprime.json on the disc, i.e. the config it was patched with
+    pub config_sha1: Option<String>,
+    pub dol_sha1: String,
+    pub paks: Vec<FileHash>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FileHash {
+    pub name: String,
+    pub sha1: String,
+}
+
+// Offsets in the header of a GameCube disc
+const DISC_MAGIC_OFFSET: usize = 0x1C;
+const DISC_MAGIC: u32 = 0xc2339f3d;
+const FST_OFFSET_OFFSET: usize = 0x424;
+const APPLOADER_OFFSET: usize = 0x2440;
+const FST_ENTRY_SIZE: usize = 12;
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+// Reading a disc follows the offsets of its header and FST without checking them, so make sure
+// they all point inside `iso` first
+fn check_disc(iso: &[u8]) -> Result<(), String> {
+    if read_u32(iso, DISC_MAGIC_OFFSET) != Some(DISC_MAGIC) {
+        Err("The file is not a GameCube disc image")?;
+    }
+    let out_of_bounds = |what: &str| format!("The {} of the disc image is out of bounds", what);
+
+    let apploader_size = read_u32(iso, APPLOADER_OFFSET + 0x14)
+        .zip(read_u32(iso, APPLOADER_OFFSET + 0x18))
+        .and_then(|(size, trailer_size)| size.checked_add(trailer_size))
+        .ok_or_else(|| out_of_bounds("apploader"))?;
+    if APPLOADER_OFFSET + 0x20 + apploader_size as usize > iso.len() {
+        Err(out_of_bounds("apploader"))?;
+    }
+
+    let fst = read_u32(iso, FST_OFFSET_OFFSET)
+        .zip(read_u32(iso, FST_OFFSET_OFFSET + 4))
+        .and_then(|(offset, length)| {
+            let offset = offset as usize;
+            iso.get(offset..offset.checked_add(length as usize)?)
+        })
+        .ok_or_else(|| out_of_bounds("FST"))?;
+    let entry_count = read_u32(fst, 8).ok_or_else(|| out_of_bounds("FST"))? as usize;
+    let string_table = entry_count
+        .checked_mul(FST_ENTRY_SIZE)
+        .and_then(|start| fst.get(start..))
+        .ok_or_else(|| out_of_bounds("FST"))?;
+
+    let entries = fst[..entry_count * FST_ENTRY_SIZE].chunks_exact(FST_ENTRY_SIZE);
+    for (index, entry) in entries.enumerate() {
+        let name_offset = read_u32(entry, 0).unwrap() & 0xFFFF;
+        if !string_table
+            .get(name_offset as usize..)
+            .is_some_and(|name| name.contains(&0))
+        {
+            Err(out_of_bounds("file names"))?;
+        }
+
+        let (offset, length) = (read_u32(entry, 4).unwrap(), read_u32(entry, 8).unwrap());
+        let is_dir = entry[0] == 1;
+        let in_bounds = if is_dir {
+            // The index of the parent and the one after the last child
+            (offset as usize) < index.max(1)
+                && index < length as usize
+                && length as usize <= entry_count
+        } else {
+            offset as u64 + length as u64 <= iso.len() as u64
+        };
+        if !in_bounds {
+            Err(out_of_bounds("FST"))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn file_sha1(gc_disc: &structs::GcDisc, name: &str) -> Result<Option<String>, String> {
+    let file = match gc_disc.find_file(name).and_then(|entry| entry.file()) {
+        Some(file) => file,
+        None => return Ok(None),
+    };
+    match file {
+        structs::FstEntryFile::Unknown(reader) => Ok(Some(to_hex(&sha1(reader)))),
+        _ => Err(format!("{} has already been parsed", name)),
+    }
+}
+
+impl PatchManifest {
+    /// Hashes the files of a disc which was just read, before any of its files were parsed
+    pub fn from_disc(gc_disc: &structs::GcDisc) -> Result<Self, String> {
+        let dol_sha1 = file_sha1(gc_disc, "default.dol")?
+            .ok_or_else(|| "The disc has no default.dol".to_string())?;
+        let config_sha1 = file_sha1(gc_disc, "randomprime.json")?;
+
+        let mut paks = Vec::new();
+        for entry in gc_disc.file_system_root.dir_entries().unwrap_or_default() {
+            let name = String::from_utf8_lossy(entry.name().to_bytes()).into_owned();
+            if entry.is_folder() || !name.to_lowercase().ends_with(".pak") {
+                continue;
+            }
+            paks.push(FileHash {
+                sha1: file_sha1(gc_disc, &name)?.unwrap(),
+                name,
+            });
+        }
+
+        Ok(PatchManifest {
+            randomprime_version: env!("CARGO_PKG_VERSION").to_string(),
+            config_sha1,
+            dol_sha1,
+            paks,
+        })
+    }
+
+    pub fn from_iso(iso: &[u8]) -> Result<Self, String> {
+        check_disc(iso)?;
+        let gc_disc: structs::GcDisc = Reader::new(iso).read(());
+        Self::from_disc(&gc_disc)
+    }
+
+    /// Lists every file of `self` that doesn't match `expected`. The randomprime version is not
+    /// compared, only the contents of the disc.
+    pub fn differences(&self, expected: &PatchManifest) -> Vec<String> {
+        let mut differences = Vec::new();
+
+        if self.config_sha1 != expected.config_sha1 {
+            differences.push("randomprime.json".to_string());
+        }
+        if self.dol_sha1 != expected.dol_sha1 {
+            differences.push("default.dol".to_string());
+        }
+        for pak in expected.paks.iter() {
+            match self.paks.iter().find(|p| p.name == pak.name) {
+                Some(p) if p.sha1 == pak.sha1 => (),
+                Some(_) => differences.push(pak.name.clone()),
+                None => differences.push(format!("{} (missing)", pak.name)),
+            }
+        }
+        for pak in self.paks.iter() {
+            if !expected.paks.iter().any(|p| p.name == pak.name) {
+                differences.push(format!("{} (unexpected)", pak.name));
+            }
+        }
+
+        differences
+    }
+}
+
+/// Checks an ISO against a manifest written when it was patched
+pub fn verify_iso(iso: &[u8], expected: &PatchManifest) -> Result<(), String> {
+    let manifest = PatchManifest::from_iso(iso)?;
+    let differences = manifest.differences(expected);
+    if !differences.is_empty() {
+        Err(format!(
+            "The ISO doesn't match the manifest, these files differ: {}",
+            differences.join(", ")
+        ))?;
+    }
+
+    Ok(())
+}
+
+// A disc image with the files at its root, after the FST
+#[cfg(test)]
+fn test_iso(files: &[(&str, &[u8])]) -> Vec<u8> {
+    const FST_OFFSET: usize = 0x2500;
+    const DATA_OFFSET: usize = 0x3000;
+
+    let mut entries = vec![];
+    let mut names = vec![0]; // The root's name
+    let mut data = vec![];
+    entries.extend(0x0100_0000u32.to_be_bytes());
+    entries.extend(0u32.to_be_bytes());
+    entries.extend((files.len() as u32 + 1).to_be_bytes());
+    for (name, bytes) in files {
+        entries.extend((names.len() as u32).to_be_bytes());
+        entries.extend(((DATA_OFFSET + data.len()) as u32).to_be_bytes());
+        entries.extend((bytes.len() as u32).to_be_bytes());
+        names.extend(name.as_bytes());
+        names.push(0);
+        data.extend(*bytes);
+        data.resize(data.len().next_multiple_of(4), 0);
+    }
+    let fst = [entries, names].concat();
+
+    let mut iso = vec![0; DATA_OFFSET];
+    iso[DISC_MAGIC_OFFSET..DISC_MAGIC_OFFSET + 4].copy_from_slice(&DISC_MAGIC.to_be_bytes());
+    iso[FST_OFFSET_OFFSET..FST_OFFSET_OFFSET + 4]
+        .copy_from_slice(&(FST_OFFSET as u32).to_be_bytes());
+    iso[FST_OFFSET_OFFSET + 4..FST_OFFSET_OFFSET + 8]
+        .copy_from_slice(&(fst.len() as u32).to_be_bytes());
+    iso[FST_OFFSET..FST_OFFSET + fst.len()].copy_from_slice(&fst);
+    iso.extend(data);
+    iso
+}
+
+#[test]
+fn test_patch_manifest_round_trip() {
+    let iso = test_iso(&[
+        ("default.dol", b"dol"),
+        ("randomprime.json", b"{}"),
+        ("Metroid1.pak", b"pak 1"),
+        ("Metroid2.pak", b"pak 2"),
+        ("opening.bnr", b"banner"),
+    ]);
+
+    let manifest = PatchManifest::from_iso(&iso).unwrap();
+    assert_eq!(manifest.dol_sha1, to_hex(&sha1(b"dol")));
+    assert_eq!(manifest.config_sha1, Some(to_hex(&sha1(b"{}"))));
+    assert_eq!(
+        manifest.paks,
+        vec![
+            FileHash {
+                name: "Metroid1.pak".to_string(),
+                sha1: to_hex(&sha1(b"pak 1")),
+            },
+            FileHash {
+                name: "Metroid2.pak".to_string(),
+                sha1: to_hex(&sha1(b"pak 2")),
+            },
+        ]
+    );
+
+    let json = serde_json::to_string(&manifest).unwrap();
+    let read_back: PatchManifest = serde_json::from_str(&json).unwrap();
+    assert_eq!(read_back, manifest);
+    verify_iso(&iso, &read_back).unwrap();
+
+    let tampered = test_iso(&[
+        ("default.dol", b"dol"),
+        ("randomprime.json", b"{}"),
+        ("Metroid1.pak", b"pak 1"),
+        ("Metroid2.pak", b"pak 3"),
+        ("Metroid3.pak", b"pak 3"),
+    ]);
+    let differences = PatchManifest::from_iso(&tampered)
+        .unwrap()
+        .differences(&manifest);
+    assert_eq!(
+        differences,
+        vec!["Metroid2.pak", "Metroid3.pak (unexpected)"]
+    );
+    assert!(verify_iso(&tampered, &manifest).is_err());
+}
+
+#[test]
+fn test_patch_manifest_rejects_other_files() {
+    assert!(PatchManifest::from_iso(b"").is_err());
+    assert!(PatchManifest::from_iso(&[0; 0x4000]).is_err());
+
+    // Truncated, the files are past the end
+    let iso = test_iso(&[("default.dol", b"dol"), ("Metroid1.pak", b"pak 1")]);
+    assert!(PatchManifest::from_iso(&iso[..0x3000]).is_err());
+
+    // A file name outside of the FST
+    let mut iso = test_iso(&[("default.dol", b"dol")]);
+    iso[0x2500 + 12 + 2..0x2500 + 12 + 4].copy_from_slice(&0x1000u16.to_be_bytes());
+    assert!(PatchManifest::from_iso(&iso).is_err());
+}
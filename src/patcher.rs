@@ -1,9 +1,17 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
 
+use log::{debug, trace};
 use reader_writer::FourCC;
-use structs::{FstEntryFile, GcDisc, Resource, ResourceKind};
+use structs::{FstEntryFile, GcDisc, ProgressNotifier, ProgressStage, Resource, ResourceKind};
 
-use crate::mlvl_wrapper::{MlvlArea, MlvlEditor};
+use crate::{
+    area_dependencies::{self, PakResources},
+    mlvl_wrapper::{MlvlArea, MlvlEditor},
+    scly_validation,
+};
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Debug)]
 struct ResourceKey<'r> {
@@ -32,6 +40,19 @@ pub struct PrimePatcher<'r, 's> {
         Box<dyn FnMut(&mut Resource<'r>) -> Result<(), String> + 's>,
     )>,
     scly_patches: Vec<(MreaKey<'s>, Vec<Box<SclyPatch<'r, 's>>>)>,
+    // While `skip_excluded_rooms` is set, patches added for these rooms are dropped
+    excluded_rooms: Vec<u32>,
+    skip_excluded_rooms: bool,
+    validate_areas: bool,
+    build_area_dependencies: bool,
+    prune_area_dependencies: bool,
+}
+
+#[derive(Default)]
+pub struct KeyLock {
+    pub memory_relay_ids: Vec<u32>,
+    // The objects to message once the key is collected, e.g. activate a door's force field
+    pub locked_objects: Vec<(u32, structs::ConnectionMsg)>,
 }
 
 #[derive(Default)]
@@ -40,6 +61,7 @@ pub struct PatcherState {
         note = "Please use mlvl_wrapper.MlvlArea.new_object_id_from_layer_id/name instead!"
     )]
     pub fresh_instance_id_range: (),
+    pub key_locks: HashMap<String, KeyLock>,
 }
 
 impl<'r, 's> PrimePatcher<'r, 's> {
@@ -48,9 +70,46 @@ impl<'r, 's> PrimePatcher<'r, 's> {
             file_patches: HashMap::new(),
             resource_patches: Vec::new(),
             scly_patches: Vec::new(),
+            excluded_rooms: Vec::new(),
+            skip_excluded_rooms: false,
+            validate_areas: false,
+            build_area_dependencies: false,
+            prune_area_dependencies: false,
         }
     }
 
+    pub fn set_excluded_rooms(&mut self, room_ids: &[u32]) {
+        self.excluded_rooms = room_ids.to_vec();
+    }
+
+    /// Set around the automated passes (QoL, cutscene skips, ...) so that they don't touch the
+    /// excluded rooms, while the patches configured for those rooms specifically still apply
+    pub fn set_skip_excluded_rooms(&mut self, skip: bool) {
+        self.skip_excluded_rooms = skip;
+    }
+
+    /// Check the scripting of every room once its patches ran, failing on any problem (e.g. a
+    /// duplicate object id) the room didn't already have
+    pub fn set_validate_areas(&mut self, validate: bool) {
+        self.validate_areas = validate;
+    }
+
+    /// Add the assets the objects of every patched room reference to its dependencies, when the
+    /// patches didn't
+    pub fn set_build_area_dependencies(&mut self, build: bool) {
+        self.build_area_dependencies = build;
+    }
+
+    /// Remove the dependencies of every patched room which only the objects its patches removed
+    /// needed
+    pub fn set_prune_area_dependencies(&mut self, prune: bool) {
+        self.prune_area_dependencies = prune;
+    }
+
+    fn is_excluded(&self, room_id: u32) -> bool {
+        self.skip_excluded_rooms && self.excluded_rooms.contains(&room_id)
+    }
+
     pub fn add_file_patch<F>(&mut self, name: &'s [u8], f: F)
     where
         F: FnMut(&mut FstEntryFile<'r>) -> Result<(), String> + 's,
@@ -69,6 +128,9 @@ impl<'r, 's> PrimePatcher<'r, 's> {
     ) where
         F: Clone + FnMut(&mut Resource<'r>) -> Result<(), String> + 's,
     {
+        if fourcc == FourCC::from_bytes(b"MREA") && self.is_excluded(res_id) {
+            return;
+        }
         for pak_name in paks {
             let key = ResourceKey {
                 pak_name,
@@ -83,6 +145,9 @@ impl<'r, 's> PrimePatcher<'r, 's> {
     where
         F: FnMut(&mut PatcherState, &mut MlvlArea<'r, '_, '_, '_>) -> Result<(), String> + 's,
     {
+        if self.is_excluded(room_id) {
+            return;
+        }
         let key = MreaKey { pak_name, room_id };
         if let Some((_, v)) = self.scly_patches.iter_mut().find(|p| p.0 == key) {
             v.push(Box::new(f));
@@ -91,7 +156,10 @@ impl<'r, 's> PrimePatcher<'r, 's> {
         }
     }
 
-    pub fn run(&mut self, gc_disc: &mut GcDisc<'r>) -> Result<(), String> {
+    pub fn run<N>(&mut self, gc_disc: &mut GcDisc<'r>, notifier: &mut N) -> Result<(), String>
+    where
+        N: ProgressNotifier,
+    {
         let mut patcher_state = PatcherState::default();
 
         let files_to_patch = self
@@ -104,9 +172,18 @@ impl<'r, 's> PrimePatcher<'r, 's> {
         let files = gc_disc
             .file_system_root
             .dir_files_iter_mut()
-            .filter(|(path, _)| files_to_patch.contains(&path[..]));
+            .filter(|(path, _)| files_to_patch.contains(&path[..]))
+            .collect::<Vec<_>>();
+
+        let count = files.len();
+        for (index, (name, fst_entry)) in files.into_iter().enumerate() {
+            notifier.notify_stage(ProgressStage::PatchPak {
+                name: &name[..],
+                index,
+                count,
+            });
+            let file_start = Instant::now();
 
-        for (name, fst_entry) in files {
             if let Some(patches) = self.file_patches.get_mut(&name[..]) {
                 fst_entry.guess_kind();
                 for patch in patches.iter_mut() {
@@ -120,7 +197,12 @@ impl<'r, 's> PrimePatcher<'r, 's> {
                 .map(|p| p.0.pak_name)
                 .chain(self.scly_patches.iter().map(|p| p.0.pak_name))
                 .any(|n| n == &name[..]);
+            // Once compacted, a pak's resources can't be parsed anymore, so every patch of a pak
+            // has to run before this point
             if !pak_patch_exists {
+                if let structs::FstEntryFile::Pak(pak) = fst_entry.file_mut().unwrap() {
+                    pak.resources.compact();
+                }
                 continue;
             }
 
@@ -157,6 +239,14 @@ impl<'r, 's> PrimePatcher<'r, 's> {
                 None
             };
 
+            let pak_resources = if scly_patch_exists
+                && (self.build_area_dependencies || self.prune_area_dependencies)
+            {
+                Some(PakResources::new(pak))
+            } else {
+                None
+            };
+
             let mut cursor = pak.resources.cursor();
             while cursor.peek().is_some() {
                 let mut cursor = cursor.cursor_advancer();
@@ -177,10 +267,52 @@ impl<'r, 's> PrimePatcher<'r, 's> {
                     room_id: cursor.peek().unwrap().file_id,
                 };
                 if let Some((_, patches)) = self.scly_patches.iter_mut().find(|p| p.0 == mrea_key) {
+                    let room_start = Instant::now();
                     let mut mlvl_area = mlvl_editor.as_mut().unwrap().get_area(&mut cursor);
+                    let baseline = if self.validate_areas {
+                        scly_validation::area_problems(&mut mlvl_area)
+                    } else {
+                        vec![]
+                    };
+                    let baseline_assets = if pak_resources.is_some() {
+                        area_dependencies::object_assets(&mut mlvl_area)
+                    } else {
+                        vec![]
+                    };
+                    let baseline_deps = if self.prune_area_dependencies {
+                        area_dependencies::dependency_assets(&mlvl_area)
+                    } else {
+                        HashSet::new()
+                    };
                     for patch in patches.iter_mut() {
                         patch(&mut patcher_state, &mut mlvl_area)?;
                     }
+                    if let Some(pak_resources) = &pak_resources {
+                        if self.prune_area_dependencies {
+                            area_dependencies::remove_orphaned_dependencies(
+                                &mut mlvl_area,
+                                pak_resources,
+                                &baseline_assets,
+                                &baseline_deps,
+                            );
+                        }
+                        if self.build_area_dependencies {
+                            area_dependencies::add_missing_dependencies(
+                                &mut mlvl_area,
+                                pak_resources,
+                                &baseline_assets,
+                            );
+                        }
+                    }
+                    if self.validate_areas {
+                        scly_validation::check_area(&mut mlvl_area, &baseline)?;
+                    }
+                    trace!(
+                        "Patched room 0x{:08X} with {} patches in {:?}",
+                        mrea_key.room_id,
+                        patches.len(),
+                        room_start.elapsed()
+                    );
                 }
 
                 if cursor.peek().unwrap().fourcc() == b"MLVL".into() && mlvl_editor.is_some() {
@@ -188,6 +320,13 @@ impl<'r, 's> PrimePatcher<'r, 's> {
                     cursor.value().unwrap().kind = ResourceKind::Mlvl(mlvl);
                 }
             }
+            pak.resources.compact();
+
+            debug!(
+                "Patched {} in {:?}",
+                String::from_utf8_lossy(&name),
+                file_start.elapsed()
+            );
         }
         Ok(())
     }
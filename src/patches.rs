@@ -1,18 +1,19 @@
 use std::{
     borrow::Cow,
-    collections::{hash_map::DefaultHasher, HashMap},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
     convert::TryInto,
     ffi::CString,
     fs::{self, File},
     hash::{Hash, Hasher},
-    io::{Read, Write},
+    io::{self, Read, Write},
     iter, mem,
-    path::Path,
+    path::{Path, PathBuf},
     time::Instant,
 };
 
 use dol_symbol_table::mp1_symbol;
 use encoding::{all::WINDOWS_1252, EncoderTrap, Encoding};
+use log::{info, warn};
 use ppcasm::ppcasm;
 use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 use reader_writer::{
@@ -28,42 +29,64 @@ use structs::{
 
 use crate::{
     add_modify_obj_patches::*,
+    agsc_conversions::{replace_agsc_samples, DspSample},
+    area_collision::AreaCollision,
+    asset_cache::AssetCache,
+    bps::BpsWriter,
     ciso_writer::CisoWriter,
     custom_assets::{
-        collect_game_resources, custom_asset_filename, custom_asset_ids, PickupHashKey,
+        build_resource_raw, collect_game_resources, custom_asset_filename, custom_asset_ids,
+        elevator_hologram_cmdl, logbook_scans, PickupHashKey,
     },
     dol_patcher::DolPatcher,
-    door_meta::{BlastShieldType, DoorType},
+    door_meta::{colorblind_txtr_hue_degs, BlastShieldType, DoorType},
     elevators::{is_elevator, Elevator, SpawnRoom, SpawnRoomData, World},
-    extern_assets::ExternPickupModel,
+    extern_assets::{referenced_assets, ExternPickupModel},
     gcz_writer::GczWriter,
     generic_edit::patch_edit_objects,
-    mlvl_wrapper,
+    input_iso::InputIso,
+    mlvl_wrapper::{self, LayerDefault},
+    pak_optimizer,
+    particle_elements::particle_elements,
     patch_config::{
-        ArtifactHintBehavior, BombSlotCover, ConnectionConfig, ConnectionMsg, ConnectionState,
-        CtwkConfig, CutsceneMode, DifficultyBehavior, DoorConfig, DoorOpenMode, FogConfig,
-        GameBanner, GenericTexture, HallOfTheEldersBombSlotCoversConfig, IsoFormat, LevelConfig,
-        PatchConfig, PhazonDamageModifier, PickupConfig, PlatformConfig, PlatformType, RoomConfig,
-        RunMode, SpecialFunctionType, SuitDamageReduction, Version, Visor,
+        ArtifactHintBehavior, ArtifactTotemHintsConfig, AttainmentAudio, Beam, BombSlotCover,
+        ColorblindMode, ConnectionConfig, ConnectionMsg, ConnectionState, CtwkConfig, CutsceneMode,
+        CutsceneOverride, DamageType, DifficultyBehavior, DoorConfig, DoorOpenMode, EffectConfig,
+        ElevatorHologramCosmetics, FogConfig, FrmeWidgetConfig, FrmeWidgetType, GameBanner,
+        GenericTexture, HallOfTheEldersBombSlotCoversConfig, HazardKind, HazardsConfig,
+        HudCosmeticsConfig, HudVisor, IsoFormat, LevelConfig, LightingConfig, PalTiming,
+        PatchConfig, PhazonDamageModifier, PickupConfig, PickupCounterConfig, PickupJitterConfig,
+        PickupsCosmeticsConfig, PlatformConfig, PlatformType, RoomConfig, RoomMapConfig,
+        RoomPhysicsConfig, RunMode, SaveProtection, ShuffleBoundsConfig, SoundOverride,
+        SpecialFunctionType, SplitTimerConfig, StartingPreset, SuitDamageReduction,
+        TeleporterConfig, TitleScreen, TriggerConfig, Version, Visor,
     },
+    patch_log,
+    patch_manifest::PatchManifest,
     patcher::{PatcherState, PrimePatcher},
     pickup_meta::{
-        self, pickup_model_for_pickup, pickup_type_for_pickup, DoorLocation, ObjectsToRemove,
-        PickupModel, PickupType, ScriptObjectLocation,
+        self, pickup_model_for_pickup, pickup_type_for_pickup, DoorLocation, DoorOrientation,
+        ObjectsToRemove, PickupModel, PickupType, ScriptObjectLocation,
     },
+    resumable_writer::{self, ResumableWriter},
+    sha256,
+    softlock_fixes::{patch_softlock_fixes, SoftlockFix},
     starting_items::StartingItems,
     structs::LightLayer,
     txtr_conversions::{
-        cmpr_compress, cmpr_decompress, huerotate_color, huerotate_in_place, huerotate_matrix,
-        GRAVITY_SUIT_TEXTURES, PHAZON_SUIT_TEXTURES, POWER_SUIT_TEXTURES, VARIA_SUIT_TEXTURES,
+        cmpr_compress, cmpr_decompress, encode_rgb5a3_image, huerotate_color, huerotate_in_place,
+        huerotate_matrix, huerotate_txtr, read_texture_file, remap_color, remap_rgb, remap_txtr,
+        replace_txtr, resize_rgba, ColorRemap, GRAVITY_SUIT_TEXTURES, PHAZON_SUIT_TEXTURES,
+        POWER_SUIT_TEXTURES, VARIA_SUIT_TEXTURES,
     },
-    GcDiscLookupExtensions,
+    version_compat, GcDiscLookupExtensions,
 };
 
 #[derive(Clone, Debug)]
 struct ModifiableDoorLocation {
     pub door_location: Option<ScriptObjectLocation>,
     pub door_rotation: Option<[f32; 3]>,
+    pub door_orientation: Option<DoorOrientation>,
     pub door_force_locations: Box<[ScriptObjectLocation]>,
     pub door_shield_locations: Box<[ScriptObjectLocation]>,
     pub dock_number: u32,
@@ -83,6 +106,7 @@ impl From<DoorLocation> for ModifiableDoorLocation {
         ModifiableDoorLocation {
             door_location: door_loc.door_location,
             door_rotation: door_loc.door_rotation,
+            door_orientation: door_loc.door_orientation,
             door_force_locations: door_loc.door_force_locations.to_vec().into_boxed_slice(),
             door_shield_locations: door_loc.door_shield_locations.to_vec().into_boxed_slice(),
             dock_number: door_loc.dock_number,
@@ -92,6 +116,44 @@ impl From<DoorLocation> for ModifiableDoorLocation {
     }
 }
 
+// Some doors have their object IDs changed in non NTSC-U versions
+// NTSC-K is based on NTSC-U and shouldn't be part of those changes
+fn version_door_location(
+    version: Version,
+    room_info: &pickup_meta::RoomInfo,
+    door_loc: &DoorLocation,
+) -> ModifiableDoorLocation {
+    let mut local_dl: ModifiableDoorLocation = (*door_loc).into();
+    if [
+        Version::Pal,
+        Version::NtscJ,
+        Version::NtscJTrilogy,
+        Version::NtscUTrilogy,
+        Version::PalTrilogy,
+    ]
+    .contains(&version)
+    {
+        // Tallon Overworld - Temple Security Station
+        if room_info.room_id.to_u32() == 0xBDB1FCAC
+            && local_dl.door_location.unwrap().instance_id == 0x00070055
+        {
+            local_dl.door_location = Some(ScriptObjectLocation {
+                layer: 0,
+                instance_id: 0x000700a5,
+            });
+            local_dl.door_force_locations = Box::new([ScriptObjectLocation {
+                layer: 0,
+                instance_id: 0x000700a6,
+            }]);
+            local_dl.door_shield_locations = Box::new([ScriptObjectLocation {
+                layer: 0,
+                instance_id: 0x000700a8,
+            }]);
+        }
+    }
+    local_dl
+}
+
 const ARTIFACT_OF_TRUTH_REQ_LAYER: u32 = 23;
 
 fn artifact_layer_change_template<'r>(
@@ -116,6 +178,42 @@ fn artifact_layer_change_template<'r>(
     }
 }
 
+// The layer switches of Artifact Temple which swap the vague hint of a totem for the exact one,
+// and the connections the pickup needs to trigger them
+fn artifact_totem_hint_switches<'r>(
+    instance_ids: [u32; 2],
+    (vague_layer, exact_layer): (u32, u32),
+) -> ([structs::SclyObject<'r>; 2], [structs::Connection; 2]) {
+    let function = |instance_id, layer| structs::SclyObject {
+        instance_id,
+        connections: vec![].into(),
+        property_data: structs::SpecialFunction::layer_change_fn(
+            b"Totem Hint Layer Switch\0".as_cstr(),
+            0xCD2B0EA2,
+            layer,
+        )
+        .into(),
+    };
+    (
+        [
+            function(instance_ids[0], vague_layer),
+            function(instance_ids[1], exact_layer),
+        ],
+        [
+            structs::Connection {
+                state: structs::ConnectionState::ARRIVED,
+                message: structs::ConnectionMsg::DECREMENT,
+                target_object_id: instance_ids[0],
+            },
+            structs::Connection {
+                state: structs::ConnectionState::ARRIVED,
+                message: structs::ConnectionMsg::INCREMENT,
+                target_object_id: instance_ids[1],
+            },
+        ],
+    )
+}
+
 fn post_pickup_relay_template<'r>(
     instance_id: u32,
     connections: &'static [structs::Connection],
@@ -131,35 +229,135 @@ fn post_pickup_relay_template<'r>(
     }
 }
 
+// The artifacts, in the order of the totems' hints
+const ARTIFACT_TOTEM_PICKUPS: [PickupType; 12] = [
+    PickupType::ArtifactOfLifegiver,
+    PickupType::ArtifactOfWild,
+    PickupType::ArtifactOfWorld,
+    PickupType::ArtifactOfSun,
+    PickupType::ArtifactOfElder,
+    PickupType::ArtifactOfSpirit,
+    PickupType::ArtifactOfTruth,
+    PickupType::ArtifactOfChozo,
+    PickupType::ArtifactOfWarrior,
+    PickupType::ArtifactOfNewborn,
+    PickupType::ArtifactOfNature,
+    PickupType::ArtifactOfStrength,
+];
+
+pub const ARTIFACT_TOTEM_SCANS: &[ResourceInfo] = &[
+    resource_info!("07_Over_Stonehenge Totem 5.SCAN"), // Lifegiver
+    resource_info!("07_Over_Stonehenge Totem 4.SCAN"), // Wild
+    resource_info!("07_Over_Stonehenge Totem 10.SCAN"), // World
+    resource_info!("07_Over_Stonehenge Totem 9.SCAN"), // Sun
+    resource_info!("07_Over_Stonehenge Totem 3.SCAN"), // Elder
+    resource_info!("07_Over_Stonehenge Totem 11.SCAN"), // Spirit
+    resource_info!("07_Over_Stonehenge Totem 1.SCAN"), // Truth
+    resource_info!("07_Over_Stonehenge Totem 7.SCAN"), // Chozo
+    resource_info!("07_Over_Stonehenge Totem 6.SCAN"), // Warrior
+    resource_info!("07_Over_Stonehenge Totem 12.SCAN"), // Newborn
+    resource_info!("07_Over_Stonehenge Totem 8.SCAN"), // Nature
+    resource_info!("07_Over_Stonehenge Totem 2.SCAN"), // Strength
+];
+
+pub const ARTIFACT_TOTEM_SCAN_STRGS: &[ResourceInfo] = &[
+    resource_info!("07_Over_Stonehenge Totem 5.STRG"), // Lifegiver
+    resource_info!("07_Over_Stonehenge Totem 4.STRG"), // Wild
+    resource_info!("07_Over_Stonehenge Totem 10.STRG"), // World
+    resource_info!("07_Over_Stonehenge Totem 9.STRG"), // Sun
+    resource_info!("07_Over_Stonehenge Totem 3.STRG"), // Elder
+    resource_info!("07_Over_Stonehenge Totem 11.STRG"), // Spirit
+    resource_info!("07_Over_Stonehenge Totem 1.STRG"), // Truth
+    resource_info!("07_Over_Stonehenge Totem 7.STRG"), // Chozo
+    resource_info!("07_Over_Stonehenge Totem 6.STRG"), // Warrior
+    resource_info!("07_Over_Stonehenge Totem 12.STRG"), // Newborn
+    resource_info!("07_Over_Stonehenge Totem 8.STRG"), // Nature
+    resource_info!("07_Over_Stonehenge Totem 2.STRG"), // Strength
+];
+
+/// The exact hint of a totem which only gives a vague one until an artifact is collected
+pub struct ArtifactTotemReveal {
+    pub totem: usize, // index in ARTIFACT_TOTEM_SCANS
+    pub text: String,
+    pub artifact: PickupType,
+}
+
 fn build_artifact_temple_totem_scan_strings<R>(
     level_data: &HashMap<String, LevelConfig>,
     rng: &mut R,
     artifact_hints: Option<HashMap<String, String>>,
-) -> [String; 12]
+    artifact_totem_hints: Option<&ArtifactTotemHintsConfig>,
+    required_artifact_count: Option<u32>,
+) -> ([String; 12], Vec<ArtifactTotemReveal>)
 where
     R: Rng,
 {
-    let mut generic_text_templates = [
-        "I mean, maybe it'll be in &push;&main-color=#43CD80;{room}&pop;. I forgot, to be honest.\0",
-        "I'm not sure where the artifact exactly is, but like, you can try &push;&main-color=#43CD80;{room}&pop;.\0",
-        "Hey man, some of the Chozo are telling me that there might be a thing in &push;&main-color=#43CD80;{room}&pop;. Just sayin'.\0",
-        "Uhh umm... Where was it...? Uhhh, errr, it's definitely in &push;&main-color=#43CD80;{room}&pop;! I am 100% not totally making it up...\0",
-        "Some say it may be in &push;&main-color=#43CD80;{room}&pop;. Others say that you have no business here. Please leave me alone.\0",
-        "A buddy and I were drinking and thought 'Hey, wouldn't be crazy if we put it in &push;&main-color=#43CD80;{room}&pop;?' It took both of us just to put it there!\0",
-        "So, uhhh, I kind of got lazy and just dropped mine somewhere... Maybe it's in the &push;&main-color=#43CD80;{room}&pop;? Who knows.\0",
-        "I was super late and someone had to cover for me. She said she put it in &push;&main-color=#43CD80;{room}&pop;, so you'll just have to trust her.\0",
-        "Okay, so this jerk forgets to hide his so I had to hide two. This is literally saving the planet. Anyways, mine is in &push;&main-color=#43CD80;{room}&pop;.\0",
-        "To be honest, I don't really remember. I think it was... um... yeah we'll just go with that: It was &push;&main-color=#43CD80;{room}&pop;.\0",
-        "Hear the words of Oh Leer, last Chozo of the Artifact Temple. May they serve you... Alright, whatever. It's in &push;&main-color=#43CD80;{room}&pop;.\0",
-        "I kind of just played Frisbee with mine. It flew too far and I didn't see where it landed. Somewhere in &push;&main-color=#43CD80;{room}&pop;.\0",
+    let default_templates = [
+        "I mean, maybe it'll be in {room}. I forgot, to be honest.",
+        "I'm not sure where the artifact exactly is, but like, you can try {room}.",
+        "Hey man, some of the Chozo are telling me that there might be a thing in {room}. Just sayin'.",
+        "Uhh umm... Where was it...? Uhhh, errr, it's definitely in {room}! I am 100% not totally making it up...",
+        "Some say it may be in {room}. Others say that you have no business here. Please leave me alone.",
+        "A buddy and I were drinking and thought 'Hey, wouldn't be crazy if we put it in {room}?' It took both of us just to put it there!",
+        "So, uhhh, I kind of got lazy and just dropped mine somewhere... Maybe it's in the {room}? Who knows.",
+        "I was super late and someone had to cover for me. She said she put it in {room}, so you'll just have to trust her.",
+        "Okay, so this jerk forgets to hide his so I had to hide two. This is literally saving the planet. Anyways, mine is in {room}.",
+        "To be honest, I don't really remember. I think it was... um... yeah we'll just go with that: It was {room}.",
+        "Hear the words of Oh Leer, last Chozo of the Artifact Temple. May they serve you... Alright, whatever. It's in {room}.",
+        "I kind of just played Frisbee with mine. It flew too far and I didn't see where it landed. Somewhere in {room}.",
     ];
+    let default_vague_templates = [
+        "I don't remember the room, but it's somewhere in {world}. Bring the others their artifacts and it might come back to me.",
+        "{pickup}? Somewhere in {world}, I think. Ask me again once you've found a few more of these things.",
+        "All I know is that it's in {world}. The others are supposed to tell me where exactly once you bring theirs back.",
+        "Look, it's in {world}. I'll tell you the room when I feel like it. Go find some other artifacts first.",
+    ];
+    let progressive = artifact_totem_hints
+        .and_then(|hints| hints.progressive)
+        .unwrap_or(false);
+
+    // The pools are cycled through, as the ones from the config can be shorter than the number
+    // of artifacts
+    let mut generic_text_templates: Vec<String> =
+        match artifact_totem_hints.and_then(|hints| hints.templates.as_ref()) {
+            Some(templates) => templates.clone(),
+            None => default_templates.iter().map(|t| t.to_string()).collect(),
+        };
     generic_text_templates.shuffle(rng);
-    let mut generic_templates_iter = generic_text_templates.iter();
+    let mut generic_templates_iter = generic_text_templates.iter().cycle();
 
-    // Where are the artifacts?
-    let mut artifact_locations = Vec::<(&str, PickupType)>::new();
-    for (_, level) in level_data.iter() {
-        for (room_name, room) in level.rooms.iter() {
+    let mut vague_text_templates: Vec<String> =
+        match artifact_totem_hints.and_then(|hints| hints.vague_templates.as_ref()) {
+            Some(templates) => templates.clone(),
+            None => default_vague_templates
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+        };
+    if progressive {
+        vague_text_templates.shuffle(rng);
+    }
+    let mut vague_templates_iter = vague_text_templates.iter().cycle();
+
+    let fill_template = |template: &str, world: &str, room: &str, pickup: &str| {
+        let highlight = |name: &str| format!("&push;&main-color=#43CD80;{}&pop;", name);
+        format!(
+            "{}\0",
+            template
+                .replace("{room}", &highlight(room))
+                .replace("{world}", &highlight(world))
+                .replace("{pickup}", pickup)
+        )
+    };
+
+    // Where are the artifacts? Sorted so that the same seed gives the same hints
+    let mut artifact_locations = Vec::<(&str, &str, PickupType)>::new();
+    let mut levels: Vec<_> = level_data.iter().collect();
+    levels.sort_by(|a, b| a.0.cmp(b.0));
+    for (level_name, level) in levels {
+        let mut rooms: Vec<_> = level.rooms.iter().collect();
+        rooms.sort_by(|a, b| a.0.cmp(b.0));
+        for (room_name, room) in rooms {
             if room.pickups.is_none() {
                 continue;
             };
@@ -168,7 +366,7 @@ where
                 if pickup_type.kind() >= PickupType::ArtifactOfTruth.kind()
                     && pickup_type.kind() <= PickupType::ArtifactOfNewborn.kind()
                 {
-                    artifact_locations.push(((room_name.as_str()), pickup_type));
+                    artifact_locations.push((level_name.as_str(), room_name.as_str(), pickup_type));
                 }
             }
         }
@@ -181,7 +379,7 @@ where
     //     relies on unsafe code.
     let mut specific_room_templates = [(
         "Artifact Temple",
-        vec!["{pickup} awaits those who truly seek it.\0"],
+        vec!["{pickup} awaits those who truly seek it."],
     )];
     for rt in &mut specific_room_templates {
         rt.1.shuffle(rng);
@@ -201,9 +399,10 @@ where
         String::new(),
         String::new(),
     ];
+    let mut vague_text: [Option<String>; 12] = Default::default();
 
     // Shame there isn't a way to flatten tuples automatically
-    for (room_name, pt) in artifact_locations.iter() {
+    for (world_name, room_name, pt) in artifact_locations.iter() {
         let artifact_id = (pt.kind() - PickupType::ArtifactOfTruth.kind()) as usize;
 
         let artifact_id = match artifact_id {
@@ -234,11 +433,13 @@ where
             .iter_mut()
             .find(|row| &row.0 == room_name)
             .and_then(|row| row.1.pop())
-            .unwrap_or_else(|| generic_templates_iter.next().unwrap());
+            .unwrap_or_else(|| generic_templates_iter.next().unwrap().as_str());
         let pickup_name = pt.name();
-        scan_text[artifact_id] = template
-            .replace("{room}", room_name)
-            .replace("{pickup}", pickup_name);
+        scan_text[artifact_id] = fill_template(template, world_name, room_name, pickup_name);
+        if progressive {
+            let template = vague_templates_iter.next().unwrap();
+            vague_text[artifact_id] = Some(fill_template(template, world_name, "", pickup_name));
+        }
     }
 
     // Set a default value for any artifacts that we didn't find.
@@ -269,13 +470,62 @@ where
             };
 
             scan_text[idx] = format!("{}\0", hint.to_owned());
+            vague_text[idx] = None;
+        }
+    }
+
+    // Let the player know that not every totem needs to be activated
+    if let Some(count) = required_artifact_count {
+        if count < 12 {
+            let requirement = match count {
+                0 => "No Chozo Artifacts are required to open the portal.".to_string(),
+                1 => "Only 1 of the 12 Chozo Artifacts is required to open the portal.".to_string(),
+                _ => format!(
+                    "Only {} of the 12 Chozo Artifacts are required to open the portal.",
+                    count
+                ),
+            };
+            for scan_text in scan_text.iter_mut().chain(vague_text.iter_mut().flatten()) {
+                let text = scan_text.trim_end_matches('\0');
+                *scan_text = format!(
+                    "{}\n\n&push;&main-color=#FF3333;{}&pop;\0",
+                    text, requirement
+                );
+            }
+        }
+    }
+
+    if !progressive {
+        return (scan_text, vec![]);
+    }
+
+    // Following a random order of the totems, the first one gives its exact hint from the start
+    // and each of the others does once the artifact of the one before it is collected. The
+    // artifacts which aren't placed can't reveal anything, so they're left out.
+    let mut order: Vec<usize> = (0..12)
+        .filter(|totem| {
+            artifact_locations
+                .iter()
+                .any(|(_, _, pt)| *pt == ARTIFACT_TOTEM_PICKUPS[*totem])
+        })
+        .collect();
+    order.shuffle(rng);
+    let mut reveals = vec![];
+    for pair in order.windows(2) {
+        let (previous, totem) = (pair[0], pair[1]);
+        if let Some(vague) = vague_text[totem].take() {
+            reveals.push(ArtifactTotemReveal {
+                totem,
+                text: std::mem::replace(&mut scan_text[totem], vague),
+                artifact: ARTIFACT_TOTEM_PICKUPS[previous],
+            });
         }
     }
 
-    scan_text
+    (scan_text, reveals)
 }
 
-fn patch_artifact_totem_scan_strg(
+pub fn patch_artifact_totem_scan_strg(
     res: &mut structs::Resource,
     text: &str,
     version: Version,
@@ -299,6 +549,56 @@ fn patch_save_banner_txtr(res: &mut structs::Resource) -> Result<(), String> {
     Ok(())
 }
 
+fn patch_title_screen<'s>(
+    patcher: &mut PrimePatcher<'_, 's>,
+    title_screen: &'s TitleScreen,
+) -> Result<(), String> {
+    let textures = [
+        (
+            &title_screen.press_start,
+            resource_info!("TXTR_PressStart.TXTR"),
+        ),
+        (
+            &title_screen.nintendo_logo,
+            resource_info!("TXTR_NintendoLogo.TXTR"),
+        ),
+        (
+            &title_screen.retro_logo,
+            resource_info!("TXTR_RetroLogo.TXTR"),
+        ),
+        (
+            &title_screen.dolby_logo,
+            resource_info!("TXTR_DolbyLogo.TXTR"),
+        ),
+    ];
+    for (path, res_info) in textures {
+        if let Some(path) = path {
+            let path = Path::new(path);
+            patcher.add_resource_patch(res_info.into(), move |res| replace_txtr(res, path, true));
+        }
+    }
+
+    // The world billboards shown behind the file select menu
+    for (world, path) in title_screen.billboards.iter().flat_map(|b| b.iter()) {
+        let res_info = match world.to_lowercase().trim() {
+            "tallon overworld" => resource_info!("TXTR_OverworldBillboard.TXTR"),
+            "chozo ruins" => resource_info!("TXTR_ChozoRuinsBillboard.TXTR"),
+            "magmoor caverns" => resource_info!("TXTR_LavaBillboard.TXTR"),
+            "phendrana drifts" => resource_info!("TXTR_PhenDriftsBillboard.TXTR"),
+            "phazon mines" => resource_info!("TXTR_MinesBillboard.TXTR"),
+            "impact crater" => resource_info!("TXTR_CraterBillboard.TXTR"),
+            _ => Err(format!(
+                "Unknown world '{}' in titleScreen.billboards",
+                world
+            ))?,
+        };
+        let path = Path::new(path);
+        patcher.add_resource_patch(res_info.into(), move |res| replace_txtr(res, path, true));
+    }
+
+    Ok(())
+}
+
 fn patch_tournament_winners<'r>(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
@@ -392,7 +692,56 @@ fn remove_door_locks(
     Ok(())
 }
 
-fn patch_morphball_hud(res: &mut structs::Resource) -> Result<(), String> {
+fn patch_helmet_colors(res: &mut structs::Resource, remap: &ColorRemap) -> Result<(), String> {
+    let frme = res.kind.as_frme_mut().unwrap();
+    for widget in frme.widgets.iter_mut() {
+        if !matches!(widget.kind, structs::FrmeWidgetKind::Model(_)) {
+            continue;
+        }
+
+        let new_rgb = remap_color(
+            remap,
+            (widget.color[0] * 255.0) as u8,
+            (widget.color[1] * 255.0) as u8,
+            (widget.color[2] * 255.0) as u8,
+        );
+        widget.color[0] = new_rgb[0] as f32 / 255.0;
+        widget.color[1] = new_rgb[1] as f32 / 255.0;
+        widget.color[2] = new_rgb[2] as f32 / 255.0;
+    }
+
+    Ok(())
+}
+
+// How far a digit of the HUD's ammo counters is from the next one
+const HUD_DIGIT_WIDTH: f32 = 0.325;
+
+// The number of digits the HUD makes room for to show the ammo count of an item, which is at most
+// `max_digits`: any more and the counter would run into the rest of the HUD
+fn hud_digits(
+    config: &PatchConfig,
+    pickup_type: PickupType,
+    default_capacity: u32,
+    max_digits: u32,
+) -> u32 {
+    let max_capacity = config
+        .item_max_capacity
+        .get(&pickup_type)
+        .copied()
+        .unwrap_or(default_capacity);
+    let digits = max_capacity.max(1).to_string().len() as u32;
+    if digits > max_digits {
+        warn!(
+            "The HUD only has room for {} digits of the {} count, a capacity of {} overlaps it",
+            max_digits,
+            pickup_type.name(),
+            max_capacity
+        );
+    }
+    digits.min(max_digits)
+}
+
+fn patch_morphball_hud(res: &mut structs::Resource, power_bomb_digits: u32) -> Result<(), String> {
     let frme = res.kind.as_frme_mut().unwrap();
     let (jpn_font, jpn_point_scale) = if frme.version == 0 {
         (None, None)
@@ -421,7 +770,8 @@ fn patch_morphball_hud(res: &mut structs::Resource) -> Result<(), String> {
     widget.origin[0] -= 0.1;
 
     // We need to shift all of the widgets in the bomb UI left so there's
-    // room for the longer powerbomb ammo counter
+    // room for the longer powerbomb ammo counter (at least 2 digits)
+    let shift = HUD_DIGIT_WIDTH * (power_bomb_digits.max(2) - 1) as f32;
     const BOMB_UI_WIDGET_NAMES: &[&[u8]] = &[
         b"model_bar",
         b"model_bombbrak0",
@@ -436,8 +786,145 @@ fn patch_morphball_hud(res: &mut structs::Resource) -> Result<(), String> {
         if !BOMB_UI_WIDGET_NAMES.contains(&widget.name.to_bytes()) {
             continue;
         }
-        widget.origin[0] -= 0.325;
+        widget.origin[0] -= shift;
+    }
+    Ok(())
+}
+
+// The missile counters of the visor HUDs have room for 3 digits, any more and the counter is
+// moved left and allowed to grow past its box
+fn patch_missile_hud_digits(
+    res: &mut structs::Resource,
+    missile_digits: u32,
+) -> Result<(), String> {
+    let frme = res.kind.as_frme_mut().unwrap();
+    let widget = frme
+        .widgets
+        .iter_mut()
+        .find(|widget| widget.name == b"textpane_missiledigits\0".as_cstr());
+    let widget = match widget {
+        Some(widget) => widget,
+        None => return Ok(()), // this HUD doesn't show missiles
+    };
+    match &mut widget.kind {
+        structs::FrmeWidgetKind::TextPane(textpane) => textpane.word_wrap = 0,
+        _ => Err("Widget \"textpane_missiledigits\" should be a TXPN")?,
+    }
+    widget.origin[0] -= HUD_DIGIT_WIDTH * missile_digits.saturating_sub(3) as f32;
+    Ok(())
+}
+
+// Text pane the rel fills in with the number of collected items. Must be kept in sync with
+// PICKUP_COUNTER_WIDGET in compile_to_ppc/rel_patches/src/lib.rs
+const PICKUP_COUNTER_WIDGET: &str = "textpane_pickupcounter";
+
+fn patch_add_frme_widgets(
+    res: &mut structs::Resource,
+    frme_name: &str,
+    widgets: &[FrmeWidgetConfig],
+) -> Result<(), String> {
+    let frme = res.kind.as_frme_mut().unwrap();
+    let version = frme.version;
+
+    for config in widgets {
+        let name = CString::new(config.name.clone())
+            .map_err(|_| format!("Invalid widget name '{}' in {}", config.name, frme_name))?;
+        let parent = CString::new(
+            config
+                .parent
+                .clone()
+                .unwrap_or_else(|| "kGSYS_HeadWidgetID".to_string()),
+        )
+        .map_err(|_| {
+            format!(
+                "Invalid parent of widget '{}' in {}",
+                config.name, frme_name
+            )
+        })?;
+
+        if frme.widgets.iter().any(|w| *w.name == *name) {
+            Err(format!(
+                "Widget '{}' already exists in {}",
+                config.name, frme_name
+            ))?;
+        }
+        if !frme.widgets.iter().any(|w| *w.name == *parent) {
+            Err(format!(
+                "Parent widget '{}' of '{}' doesn't exist in {}",
+                parent.to_string_lossy(),
+                config.name,
+                frme_name
+            ))?;
+        }
+
+        let kind = match config.kind {
+            FrmeWidgetType::TextPane => {
+                let font_name = config.font.as_deref().unwrap_or("Deface14B_O.FONT");
+                let font: ResId<res_id::FONT> = resource_info_table::font_info(font_name)
+                    .ok_or_else(|| format!("Unknown font '{}' in {}", font_name, frme_name))?
+                    .try_into()
+                    .unwrap();
+                let [x_dim, z_dim] = config.size.unwrap_or([10.0, 2.0]);
+                // Pixel size of the text block, about 20 pixels per frame unit
+                let block_extent = [(x_dim * 20.4).round(), (z_dim * 20.4).round()];
+                let (jpn_font, jpn_point_scale) = if version == 0 {
+                    (None, None)
+                } else {
+                    (
+                        Some(font),
+                        Some([block_extent[0] as u32, block_extent[1] as u32].into()),
+                    )
+                };
+                structs::FrmeWidgetKind::TextPane(structs::TextPaneWidget {
+                    x_dim,
+                    z_dim,
+                    scale_center: [-x_dim / 2.0, 0.0, -z_dim / 2.0].into(),
+                    font,
+                    word_wrap: 0,
+                    horizontal: 1,
+                    justification: 0,
+                    vertical_justification: 0,
+                    fill_color: config.fill_color.unwrap_or([1.0, 1.0, 1.0, 1.0]).into(),
+                    outline_color: config.outline_color.unwrap_or([0.0, 0.0, 0.0, 1.0]).into(),
+                    block_extent: block_extent.into(),
+                    jpn_font,
+                    jpn_point_scale,
+                })
+            }
+            FrmeWidgetType::Model => {
+                let model = config.model.ok_or_else(|| {
+                    format!(
+                        "Model widget '{}' in {} has no model",
+                        config.name, frme_name
+                    )
+                })?;
+                structs::FrmeWidgetKind::Model(structs::ModelWidget {
+                    model: ResId::new(model),
+                    blend_mode: 0,
+                    light_mask: 0,
+                })
+            }
+        };
+
+        frme.widgets.as_mut_vec().push(structs::FrmeWidget {
+            name: Cow::Owned(name),
+            parent: Cow::Owned(parent),
+            use_anim_controller: 0,
+            default_visible: config.visible.unwrap_or(true) as u8,
+            default_active: 1,
+            cull_faces: 0,
+            color: config.color.unwrap_or([1.0, 1.0, 1.0, 1.0]).into(),
+            model_draw_flags: 2,
+            kind,
+            worker_id: None,
+            origin: config.position.into(),
+            basis: [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
+            rotation_center: [0.0, 0.0, 0.0].into(),
+            unknown0: 0,
+            unknown1: 0,
+        });
     }
+
     Ok(())
 }
 
@@ -477,7 +964,10 @@ fn patch_map_door_icon(
     mrea_id: u32,
 ) -> Result<(), String> {
     if door.door_location.is_none() {
-        println!("Warning, no door location to patch map for");
+        warn!(
+            "No door location to patch the map of room 0x{:08X} for",
+            mrea_id
+        );
         return Ok(());
     }
 
@@ -485,17 +975,61 @@ fn patch_map_door_icon(
 
     let door_id = door.door_location.as_ref().unwrap().instance_id;
 
-    let door_icon = mapa
-        .objects
+    let door_icon = find_map_object(mapa, door_id, mrea_id)?;
+    door_icon.type_ = map_object_type;
+
+    Ok(())
+}
+
+fn find_map_object<'a>(
+    mapa: &'a mut structs::Mapa,
+    editor_id: u32,
+    mrea_id: u32,
+) -> Result<&'a mut structs::MapaObject, String> {
+    mapa.objects
         .iter_mut()
-        .find(|obj| obj.editor_id == door_id)
-        .unwrap_or_else(|| {
-            panic!(
-                "Failed to find door 0x{:X} in room 0x{:X}",
-                door_id, mrea_id
+        .find(|obj| obj.editor_id == editor_id)
+        .ok_or_else(|| {
+            format!(
+                "Failed to find map object 0x{:X} in room 0x{:X}",
+                editor_id, mrea_id
             )
-        });
-    door_icon.type_ = map_object_type;
+        })
+}
+
+fn patch_room_map(
+    res: &mut structs::Resource,
+    map_config: &RoomMapConfig,
+    mrea_id: u32,
+) -> Result<(), String> {
+    let mapa = res.kind.as_mapa_mut().unwrap();
+
+    for object_config in map_config.objects.iter().flatten() {
+        let object = find_map_object(mapa, object_config.editor_id, mrea_id)?;
+        if let Some(icon) = object_config.icon {
+            object.type_ = icon as u32;
+            if icon == structs::MapaObjectType::Pickup {
+                object.seed1 = 0;
+            }
+        }
+        if let Some(visibility_mode) = object_config.visibility_mode {
+            object.visibility_mode = visibility_mode as u32;
+        }
+    }
+
+    for icon in map_config.icons.iter().flatten() {
+        if icon.icon == structs::MapaObjectType::Pickup {
+            mapa.add_pickup(icon.editor_id.unwrap_or(0xFFFFFFFF), icon.position, 0);
+            continue;
+        }
+        mapa.add_object(
+            icon.icon,
+            icon.visibility_mode
+                .unwrap_or(MapaObjectVisibilityMode::Always),
+            icon.editor_id.unwrap_or(0xFFFFFFFF),
+            icon.position,
+        );
+    }
 
     Ok(())
 }
@@ -563,18 +1097,29 @@ fn patch_remove_blast_shield(
     Ok(())
 }
 
+// Morph ball door docks are 2 units tall instead of 5
+const MORPH_BALL_DOOR_SIZE: f32 = 0.4;
+
 fn this_near_that(this: [f32; 3], that: [f32; 3]) -> bool {
     f32::abs(this[0] - that[0]) < 2.7
         && f32::abs(this[1] - that[1]) < 2.7
         && f32::abs(this[2] - that[2]) < 2.7
 }
 
+// The damageable trigger of a blast shield with an ammo cost gets a connection which is never
+// sent, which the rel reads the cost from. Must be kept in sync with AMMO_COST_CONNECTION_STATE
+// in compile_to_ppc/rel_patches/src/lib.rs
+pub const AMMO_COST_CONNECTION_STATE: u32 = 0x414D4D4F;
+
+#[allow(clippy::too_many_arguments)]
 fn patch_door<'r>(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     door_loc: ModifiableDoorLocation,
     door_type: Option<DoorType>,
     blast_shield_type: Option<BlastShieldType>,
+    blast_shield_cost: Option<u32>,
+    scan_gate_scan: Option<(ResId<res_id::SCAN>, ResId<res_id::STRG>)>,
     door_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
     door_open_mode: DoorOpenMode,
 ) -> Result<(), String> {
@@ -595,6 +1140,11 @@ fn patch_door<'r>(
         deps.extend_from_slice(&blast_shield_type.dependencies(DO_GIBBS));
     }
 
+    if let Some((scan_id, strg_id)) = scan_gate_scan {
+        deps.push((scan_id.to_u32(), FourCC::from_bytes(b"SCAN")));
+        deps.push((strg_id.to_u32(), FourCC::from_bytes(b"STRG")));
+    }
+
     let blast_shield_can_change_door = door_type.is_some() && blast_shield_type.is_some();
     let door_type_after_open = match door_open_mode {
         DoorOpenMode::Original => None,
@@ -693,8 +1243,7 @@ fn patch_door<'r>(
         special_function_id = area.new_object_id_from_layer_id(0);
 
         /* Add a new layer to this room to put all the blast shield objects onto */
-        area.add_layer(b"Custom Shield Layer\0".as_cstr());
-        blast_shield_layer_idx = area.layer_flags.layer_count as usize - 1;
+        blast_shield_layer_idx = area.add_named_layer("Custom Shield Layer", LayerDefault::Enabled);
 
         sound_id = area.new_object_id_from_layer_id(blast_shield_layer_idx);
         streamed_audio_id = area.new_object_id_from_layer_id(blast_shield_layer_idx);
@@ -720,6 +1269,7 @@ fn patch_door<'r>(
     let scly = area.mrea().scly_section_mut();
     let layers = &mut scly.layers.as_mut_vec();
 
+    let mut is_morphball_door = false;
     if let Some(door_location) = door_loc.door_location {
         let obj = layers[door_location.layer as usize]
             .objects
@@ -728,17 +1278,18 @@ fn patch_door<'r>(
             .find(|obj| obj.instance_id == door_location.instance_id)
             .unwrap_or_else(|| panic!("Failed to find door in room 0x{:X}", mrea_id));
 
-        if obj.property_data.as_door_mut().unwrap().is_morphball_door != 0
-            || obj.instance_id == 0x002C0186
-        {
-            // energy core morph ball door isn't marked as such
-            panic!(
-                "Modifying shield and/or blast shield of mophball door in room 0x{:X} not allowed",
-                mrea_id
-            );
-        }
+        // energy core morph ball door isn't marked as such
+        is_morphball_door = obj.property_data.as_door_mut().unwrap().is_morphball_door != 0
+            || obj.instance_id == 0x002C0186;
     }
 
+    // Morph ball doors are the same as normal doors, just smaller
+    let door_size = if is_morphball_door {
+        MORPH_BALL_DOOR_SIZE
+    } else {
+        1.0
+    };
+
     // Add blast shield
     let position: GenericArray<f32, U3>;
     if blast_shield_type.is_some() {
@@ -762,122 +1313,63 @@ fn patch_door<'r>(
             .and_then(|obj| obj.property_data.as_actor_mut())
             .unwrap();
 
-        let is_vertical = DoorType::from_cmdl(&door_shield.cmdl.to_u32()).is_vertical();
+        // Morph ball doors are placed from their rotation alone, whichever way they face
+        let is_vertical =
+            !is_morphball_door && DoorType::from_cmdl(&door_shield.cmdl.to_u32()).is_vertical();
 
         let blast_shield_type = blast_shield_type.as_ref().unwrap();
 
-        // Calculate placement //
-        let rotation: GenericArray<f32, U3>;
-        // this is actually scan offset
-        let scale: GenericArray<f32, U3>;
-        // this is actually hitbox
+        // Calculate placement from the direction the door faces //
+        let door_rotation = door_loc.door_rotation.ok_or_else(|| {
+            format!(
+                "Door #{} in room 0x{:X} didn't get rotation data dumped",
+                door_loc.dock_number, mrea_id
+            )
+        })?;
 
+        // this is actually scan offset
         let scan_offset: GenericArray<f32, U3> = [0.0, 0.0, 0.0].into();
+        // this is actually hitbox
         let hitbox: GenericArray<f32, U3> = [0.0, 0.0, 0.0].into();
 
-        let door_rotation = door_loc.door_rotation.unwrap();
-        let mut is_ceiling = false;
-        let mut is_floor = false;
+        // The up axis of vertical doors tells ceiling (+Z) and floor (-Z) doors apart
+        let DoorOrientation { forward, up } = door_loc.door_orientation.ok_or_else(|| {
+            format!(
+                "Door #{} in room 0x{:X} didn't get orientation data dumped",
+                door_loc.dock_number, mrea_id
+            )
+        })?;
 
+        let rotation: GenericArray<f32, U3>;
+        let scale: GenericArray<f32, U3>;
         if is_vertical {
-            if door_loc.door_rotation.is_none() {
-                panic!(
-                    "Vertical door in room {:X} didn't get position data dumped",
-                    mrea_id
-                );
-            }
-
-            if mrea_id == 0xFB54A0CB {
-                // hall of the elders
-                scale = [1.6, 1.6, 1.6].into();
-
-                // Floor door
-                is_floor = true;
-                position = [
-                    door_shield.position[0] - 2.0,
-                    door_shield.position[1],
-                    door_shield.position[2] - 0.3,
-                ]
-                .into();
-                rotation = [0.0, 90.0, 0.0].into();
-            } else {
-                scale = [1.6, 1.6, 1.6].into();
-
-                if door_rotation[0] > -90.0 && door_rotation[0] < 90.0 {
-                    // Ceiling door
-                    is_ceiling = true;
-                    position = [
-                        door_shield.position[0] + 2.0,
-                        door_shield.position[1],
-                        door_shield.position[2] + 0.2,
-                    ]
-                    .into();
-                    rotation = [0.0, -90.0, 0.0].into();
-                } else if door_rotation[0] < -90.0 && door_rotation[0] > -270.0 {
-                    // Floor door
-                    is_floor = true;
-                    position = [
-                        door_shield.position[0] - 2.0,
-                        door_shield.position[1],
-                        door_shield.position[2] - 0.2,
-                    ]
-                    .into();
-                    rotation = [0.0, 90.0, 0.0].into();
-                } else {
-                    panic!(
-                        "Unhandled door rotation on vertical door {:?} in room 0x{:X}",
-                        door_rotation, mrea_id
-                    );
-                }
-            }
+            // The blast shield model is always placed in the same orientation, only flipped
+            // between ceiling (facing up) and floor (facing down) doors
+            let up_z = if up[2] >= 0.0 { 1.0 } else { -1.0 };
+            // The floor door of Hall of the Elders sits a bit lower than the others
+            let offset_z = if mrea_id == 0xFB54A0CB { 0.3 } else { 0.2 };
+            scale = [1.6 * door_size, 1.6 * door_size, 1.6 * door_size].into();
+            rotation = [0.0, -90.0 * up_z, 0.0].into();
+            position = [
+                door_shield.position[0] + 2.0 * door_size * up_z,
+                door_shield.position[1],
+                door_shield.position[2] + offset_z * door_size * up_z,
+            ]
+            .into();
         } else {
-            let scale_scale = 1.0;
+            let scale_scale = door_size;
             scale = [1.0 * scale_scale, 1.5 * scale_scale, 1.5 * scale_scale].into();
             rotation = door_rotation.into();
 
-            let door_offset: f32 = -0.05;
-            let door_offset_z: f32 = 1.8017;
+            let door_offset: f32 = -0.05 * door_size;
+            let door_offset_z: f32 = 1.8017 * door_size;
 
-            if door_rotation[2] >= 45.0 && door_rotation[2] < 135.0 {
-                // Leads North
-                position = [
-                    door_shield.position[0],
-                    door_shield.position[1] - door_offset,
-                    door_shield.position[2] - door_offset_z,
-                ]
-                .into();
-            } else if (door_rotation[2] >= 135.0 && door_rotation[2] < 225.0)
-                || (door_rotation[2] < -135.0 && door_rotation[2] > -225.0)
-            {
-                // Leads East
-                position = [
-                    door_shield.position[0] + door_offset,
-                    door_shield.position[1],
-                    door_shield.position[2] - door_offset_z,
-                ]
-                .into();
-            } else if door_rotation[2] >= -135.0 && door_rotation[2] < -45.0 {
-                // Leads South
-                position = [
-                    door_shield.position[0],
-                    door_shield.position[1] + door_offset,
-                    door_shield.position[2] - door_offset_z,
-                ]
-                .into();
-            } else if door_rotation[2] >= -45.0 && door_rotation[2] < 45.0 {
-                // Leads West
-                position = [
-                    door_shield.position[0] - door_offset,
-                    door_shield.position[1],
-                    door_shield.position[2] - door_offset_z,
-                ]
-                .into();
-            } else {
-                panic!(
-                    "Unhandled door rotation on horizontal door {:?} in room 0x{:X}",
-                    door_rotation, mrea_id
-                );
-            }
+            position = [
+                door_shield.position[0] - door_offset * forward[0] - door_offset_z * up[0],
+                door_shield.position[1] - door_offset * forward[1] - door_offset_z * up[1],
+                door_shield.position[2] - door_offset * forward[2] - door_offset_z * up[2],
+            ]
+            .into();
         }
 
         // Create new blast shield actor //
@@ -1070,74 +1562,34 @@ fn patch_door<'r>(
 
         /* Create damageable trigger to actually handle vulnerability, because actor collision extent/offset/rotation is very unreliable */
         let (dt_pos, dt_scale) = {
-            let dt_offset_z = 1.9;
-            let dt_offset = 1.25;
+            let dt_offset_z = 1.9 * door_size;
+            let dt_offset = 1.25 * door_size;
+            let dt_width = 4.0 * door_size;
+            let dt_depth = 0.8;
 
-            if is_ceiling {
-                (
-                    [
-                        position[0] - dt_offset_z,
-                        position[1],
-                        position[2] - dt_offset,
-                    ],
-                    [4.0, 4.0, 0.8],
-                )
-            } else if is_floor {
-                (
-                    [
-                        position[0] + dt_offset_z,
-                        position[1],
-                        position[2] + dt_offset,
-                    ],
-                    [4.0, 4.0, 0.8],
-                )
-            } else if door_rotation[2] >= 45.0 && door_rotation[2] < 135.0 {
-                // Leads North
-                (
-                    [
-                        position[0],
-                        position[1] - dt_offset,
-                        position[2] + dt_offset_z,
-                    ],
-                    [4.0, 0.8, 4.0],
-                )
-            } else if (door_rotation[2] >= 135.0 && door_rotation[2] < 225.0)
-                || (door_rotation[2] < -135.0 && door_rotation[2] > -225.0)
-            {
-                // Leads East
+            if is_vertical {
+                let up_z = if up[2] >= 0.0 { 1.0 } else { -1.0 };
                 (
                     [
-                        position[0] + dt_offset,
+                        position[0] - dt_offset_z * up_z,
                         position[1],
-                        position[2] + dt_offset_z,
+                        position[2] - dt_offset * up_z,
                     ],
-                    [0.8, 4.0, 4.0],
+                    [dt_width, dt_width, dt_depth],
                 )
-            } else if door_rotation[2] >= -135.0 && door_rotation[2] < -45.0 {
-                // Leads South
-                (
-                    [
-                        position[0],
-                        position[1] + dt_offset,
-                        position[2] + dt_offset_z,
-                    ],
-                    [4.0, 0.8, 4.0],
-                )
-            } else if door_rotation[2] >= -45.0 && door_rotation[2] < 45.0 {
-                // Leads West
+            } else {
+                // Damageable triggers can't be rotated, so fit the box around the rotated shield
+                let extent = |axis: usize| {
+                    dt_depth * forward[axis].abs() + dt_width * (1.0 - forward[axis].abs())
+                };
                 (
                     [
-                        position[0] - dt_offset,
-                        position[1],
-                        position[2] + dt_offset_z,
+                        position[0] - dt_offset * forward[0] + dt_offset_z * up[0],
+                        position[1] - dt_offset * forward[1] + dt_offset_z * up[1],
+                        position[2] - dt_offset * forward[2] + dt_offset_z * up[2],
                     ],
-                    [0.8, 4.0, 4.0],
+                    [extent(0), extent(1), extent(2)],
                 )
-            } else {
-                panic!(
-                    "Unhandled door rotation on horizontal door {:?} in room 0x{:X}",
-                    door_rotation, mrea_id
-                );
             }
         };
 
@@ -1159,14 +1611,27 @@ fn patch_door<'r>(
             BlastShieldType::Unchanged => false,
         };
 
+        let mut dt_connections = vec![structs::Connection {
+            state: structs::ConnectionState::DEAD,
+            message: structs::ConnectionMsg::SET_TO_ZERO,
+            target_object_id: relay_id,
+        }];
+
+        if let Some(cost) = blast_shield_cost {
+            let item = match blast_shield_type {
+                BlastShieldType::PowerBomb => PickupType::PowerBomb,
+                _ => PickupType::Missile,
+            };
+            dt_connections.push(structs::Connection {
+                state: structs::ConnectionState(AMMO_COST_CONNECTION_STATE),
+                message: structs::ConnectionMsg(item.kind()),
+                target_object_id: cost,
+            });
+        }
+
         let dt = structs::SclyObject {
             instance_id: dt_id,
-            connections: vec![structs::Connection {
-                state: structs::ConnectionState::DEAD,
-                message: structs::ConnectionMsg::SET_TO_ZERO,
-                target_object_id: relay_id,
-            }]
-            .into(),
+            connections: dt_connections.into(),
             property_data: structs::DamageableTrigger {
                 name: b"mydtrigger\0".as_cstr(),
                 position: dt_pos.into(),
@@ -1191,17 +1656,29 @@ fn patch_door<'r>(
             .into(),
         };
 
-        let poi = structs::SclyObject {
-            instance_id: poi_id,
-            connections: vec![].into(),
+        // Scan gates are removed by finishing their scan instead of by damage
+        let mut poi_connections = vec![];
+        if *blast_shield_type == BlastShieldType::Scan {
+            poi_connections.push(structs::Connection {
+                state: structs::ConnectionState::SCAN_DONE,
+                message: structs::ConnectionMsg::SET_TO_ZERO,
+                target_object_id: relay_id,
+            });
+        }
+
+        let poi = structs::SclyObject {
+            instance_id: poi_id,
+            connections: poi_connections.into(),
             property_data: structs::SclyProperty::PointOfInterest(
                 structs::PointOfInterest {
                     name: b"mypoi\0".as_cstr(),
-                    position: [dt_pos[0], dt_pos[1], dt_pos[2] + 0.5].into(),
+                    position: [dt_pos[0], dt_pos[1], dt_pos[2] + 0.5 * door_size].into(),
                     rotation: [0.0, 0.0, 0.0].into(),
                     active: 0,
                     scan_param: structs::scly_structs::ScannableParameters {
-                        scan: blast_shield_type.scan(),
+                        scan: scan_gate_scan
+                            .map(|(scan_id, _)| scan_id)
+                            .unwrap_or_else(|| blast_shield_type.scan()),
                     },
                     point_size: 0.0,
                 }
@@ -1271,10 +1748,6 @@ fn patch_door<'r>(
                         == door_shield_location.instance_id & 0x00FFFFFF
                         && conn.message == structs::ConnectionMsg::DEACTIVATE
                     {
-                        if obj.property_data.as_door().unwrap().is_morphball_door != 0 {
-                            panic!("Custom Blast Shields cannot be placed on morph ball doors");
-                        }
-
                         // Disable the blast shield via memory relay when the door is opened from the other side
                         obj.connections.as_mut_vec().push(structs::Connection {
                             state: structs::ConnectionState::MAX_REACHED,
@@ -1638,6 +2111,10 @@ fn patch_door<'r>(
                 .and_then(|obj| obj.property_data.as_actor_mut())
                 .unwrap();
             door_shield.cmdl = _door_type.shield_cmdl();
+            // The shield models are all full size
+            if is_morphball_door {
+                door_shield.scale = [door_size, door_size, door_size].into();
+            }
         }
 
         // Add scan point
@@ -1656,7 +2133,6 @@ fn patch_door<'r>(
             let is_floor_door = door.ancs.file_id == 0xf57dd484
                 && door.rotation[0] < -90.0
                 && door.rotation[0] > -270.0;
-            let is_morphball_door = door.is_morphball_door != 0;
 
             if is_ceiling_door {
                 door.scan_offset[0] = 0.0;
@@ -1667,9 +2143,12 @@ fn patch_door<'r>(
                 door.scan_offset[1] = 0.0;
                 door.scan_offset[2] = 2.5;
             } else if is_morphball_door {
-                door.scan_offset[0] = 0.0;
-                door.scan_offset[1] = 0.0;
-                door.scan_offset[2] = 1.0;
+                // The middle of the scaled down shield, whichever way the door faces
+                let up = DoorOrientation::from_rotation(door.rotation.into()).up;
+                let scan_offset_z = 1.8017 * door_size;
+                door.scan_offset[0] = scan_offset_z * up[0];
+                door.scan_offset[1] = scan_offset_z * up[1];
+                door.scan_offset[2] = scan_offset_z * up[2];
             }
 
             door.actor_params.scan_params.scan = _door_type.scan();
@@ -2230,7 +2709,7 @@ fn patch_door<'r>(
 // TODO: factor out shared code with modify_pickups_in_mrea
 #[allow(clippy::too_many_arguments)]
 fn patch_add_item<'r>(
-    _ps: &mut PatcherState,
+    ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     _pickup_idx: usize,
     pickup_config: &PickupConfig,
@@ -2241,9 +2720,12 @@ fn patch_add_item<'r>(
     skip_hudmemos: bool,
     extern_models: &HashMap<String, ExternPickupModel>,
     shuffle_position: bool,
+    shuffle_bounds: Option<&ShuffleBoundsConfig>,
     seed: u64,
     _no_starting_visor: bool,
     version: Version,
+    totem_hint_layers: Option<(u32, u32)>,
+    save_prompt: bool,
 ) -> Result<(), String> {
     let mut rng = StdRng::seed_from_u64(seed);
     let room_id = area.mlvl_area.internal_id;
@@ -2306,13 +2788,8 @@ fn patch_add_item<'r>(
 
     let new_layer_idx = {
         if !respawn {
-            let name = CString::new(format!(
-                "Randomizer - Pickup ({:?})",
-                pickup_model_data.name
-            ))
-            .unwrap();
-            area.add_layer(Cow::Owned(name));
-            area.layer_flags.layer_count as usize - 1
+            let name = format!("Randomizer - Pickup ({:?})", pickup_model_data.name);
+            area.add_named_layer(&name, LayerDefault::Enabled)
         } else {
             0
         }
@@ -2422,7 +2899,7 @@ fn patch_add_item<'r>(
 
     let mut pickup_position = {
         if shuffle_position {
-            get_shuffled_position(area, &mut rng)
+            get_shuffled_position(area, shuffle_bounds, &mut rng)
         } else {
             if pickup_config.position.is_none() {
                 panic!(
@@ -2547,7 +3024,7 @@ fn patch_add_item<'r>(
             name: b"mysound\0".as_cstr(),
             position: pickup_position.into(),
             rotation: [0.0, 0.0, 0.0].into(),
-            sound_id: 117,
+            sound_id: pickup_config.sound_id.unwrap_or(117),
             active: 1,
             max_dist: 50.0,
             dist_comp: 0.2,
@@ -2567,15 +3044,57 @@ fn patch_add_item<'r>(
         })),
     };
 
+    // A custom jingle replaces the default sound, unless a sound is also requested
+    let play_attainment_audio = pickup_config
+        .attainment_audio
+        .as_ref()
+        .map(|audio| audio.enabled())
+        .unwrap_or(true);
+    let attainment_audio_file = pickup_config
+        .attainment_audio
+        .as_ref()
+        .and_then(|audio| audio.file_name());
+    let attainment_streamed_audio = attainment_audio_file.map(|file_name| structs::SclyObject {
+        instance_id: area.new_object_id_from_layer_id(new_layer_idx),
+        connections: vec![].into(),
+        property_data: structs::StreamedAudio {
+            name: b"mystreamedaudio\0".as_cstr(),
+            active: 1,
+            audio_file_name: Cow::Owned(CString::new(file_name).unwrap()),
+            no_stop_on_deactivate: 0,
+            fade_in_time: 0.0,
+            fade_out_time: 0.0,
+            volume: 127,
+            oneshot: 1,
+            is_music: 0,
+        }
+        .into(),
+    });
+
     // Play the sound when item is picked up
-    pickup_obj
-        .connections
-        .as_mut_vec()
-        .push(structs::Connection {
-            state: structs::ConnectionState::ARRIVED,
-            message: structs::ConnectionMsg::PLAY,
-            target_object_id: attainment_audio.instance_id,
-        });
+    if play_attainment_audio {
+        if attainment_streamed_audio.is_none() || pickup_config.sound_id.is_some() {
+            pickup_obj
+                .connections
+                .as_mut_vec()
+                .push(structs::Connection {
+                    state: structs::ConnectionState::ARRIVED,
+                    message: structs::ConnectionMsg::PLAY,
+                    target_object_id: attainment_audio.instance_id,
+                });
+        }
+
+        if let Some(streamed_audio) = attainment_streamed_audio.as_ref() {
+            pickup_obj
+                .connections
+                .as_mut_vec()
+                .push(structs::Connection {
+                    state: structs::ConnectionState::ARRIVED,
+                    message: structs::ConnectionMsg::PLAY,
+                    target_object_id: streamed_audio.instance_id,
+                });
+        }
+    }
 
     // 2022-02-08 - I had to remove this because there's a bug in the vanilla engine where playerhint -> Scan Visor doesn't holster the weapon
     // // If scan visor, and starting visor is none, then switch to combat and back to scan when obtaining scan
@@ -2707,6 +3226,18 @@ fn patch_add_item<'r>(
     if (29..=40).contains(&pickup_kind) {
         special_fn_artifact_layer_change_id = area.new_object_id_from_layer_name("Default");
     }
+    let totem_hint_switch_ids = totem_hint_layers.map(|_| {
+        [
+            area.new_object_id_from_layer_name("Default"),
+            area.new_object_id_from_layer_name("Default"),
+        ]
+    });
+
+    let key_connection = pickup_config
+        .key
+        .as_ref()
+        .map(|key| add_key_memory_relay(ps, area, key));
+    let save_prompt_connection = save_prompt.then(|| add_save_prompt(area, pickup_position));
 
     let scly = area.mrea().scly_section_mut();
     let layers = scly.layers.as_mut_vec();
@@ -2775,6 +3306,13 @@ fn patch_add_item<'r>(
             });
     }
 
+    // If this artifact reveals the exact hint of a totem, switch its hint layers
+    if let (Some(layers_ids), Some(switch_ids)) = (totem_hint_layers, totem_hint_switch_ids) {
+        let (functions, connections) = artifact_totem_hint_switches(switch_ids, layers_ids);
+        layers[new_layer_idx].objects.as_mut_vec().extend(functions);
+        pickup_obj.connections.as_mut_vec().extend(connections);
+    }
+
     if !respawn && new_layer_idx != 0 {
         // Create Special Function to disable layer once item is obtained
         // This is needed because otherwise the item would re-appear every
@@ -2820,6 +3358,16 @@ fn patch_add_item<'r>(
             .push(special_function);
     }
 
+    if let Some(key_connection) = key_connection {
+        pickup_obj.connections.as_mut_vec().push(key_connection);
+    }
+    if let Some(save_prompt_connection) = save_prompt_connection {
+        pickup_obj
+            .connections
+            .as_mut_vec()
+            .push(save_prompt_connection);
+    }
+
     if pickup_config.destination.is_some() {
         pickup_obj
             .connections
@@ -2828,6 +3376,7 @@ fn patch_add_item<'r>(
                 four_ids,
                 layers[new_layer_idx].objects.as_mut_vec(),
                 &pickup_config.destination.clone().unwrap(),
+                structs::ConnectionState::ARRIVED,
                 version,
             ));
     }
@@ -2837,6 +3386,12 @@ fn patch_add_item<'r>(
         .objects
         .as_mut_vec()
         .push(attainment_audio);
+    if let Some(streamed_audio) = attainment_streamed_audio {
+        layers[new_layer_idx]
+            .objects
+            .as_mut_vec()
+            .push(streamed_audio);
+    }
     layers[new_layer_idx].objects.as_mut_vec().push(pickup_obj);
 
     // 2022-02-08 - I had to remove this because there's a bug in the vanilla engine where playerhint -> Scan Visor doesn't holster the weapon
@@ -2853,6 +3408,7 @@ fn add_world_teleporter(
     the_next_four_ids: [u32; 4],
     objects: &mut Vec<structs::SclyObject>,
     destination: &str,
+    trigger_state: structs::ConnectionState,
     version: Version,
 ) -> Vec<structs::Connection> {
     let destination = SpawnRoomData::from_str(destination);
@@ -2952,22 +3508,481 @@ fn add_world_teleporter(
     vec![
         structs::Connection {
             target_object_id: timer_id,
-            state: structs::ConnectionState::ARRIVED,
+            state: trigger_state,
             message: structs::ConnectionMsg::RESET_AND_START,
         },
         structs::Connection {
             target_object_id: hudmemo_id,
-            state: structs::ConnectionState::ARRIVED,
+            state: trigger_state,
             message: structs::ConnectionMsg::SET_TO_ZERO,
         },
         structs::Connection {
             target_object_id: player_hint_id,
-            state: structs::ConnectionState::ARRIVED,
+            state: trigger_state,
             message: structs::ConnectionMsg::INCREMENT,
         },
     ]
 }
 
+/// Adds a memory relay which, once activated, unlocks every door requiring `key` in this world.
+/// Returns the connection a pickup should use to activate it.
+fn add_key_memory_relay(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+    key: &str,
+) -> structs::Connection {
+    let memory_relay_id = area.new_object_id_from_layer_id(0);
+
+    // Doors patched before this pickup are connected now, the rest are connected when they
+    // are patched
+    let key_lock = ps.key_locks.entry(key.to_string()).or_default();
+    key_lock.memory_relay_ids.push(memory_relay_id);
+    let connections: Vec<structs::Connection> = key_lock
+        .locked_objects
+        .iter()
+        .map(|&(target_object_id, message)| structs::Connection {
+            state: structs::ConnectionState::ACTIVE,
+            message,
+            target_object_id,
+        })
+        .collect();
+
+    area.add_memory_relay(structs::SclyObject {
+        instance_id: memory_relay_id,
+        property_data: structs::MemoryRelay {
+            name: b"Randomizer - Key Memory Relay\0".as_cstr(),
+            unknown: 0,
+            active: 0,
+        }
+        .into(),
+        connections: connections.into(),
+    });
+
+    structs::Connection {
+        state: structs::ConnectionState::ARRIVED,
+        message: structs::ConnectionMsg::ACTIVATE,
+        target_object_id: memory_relay_id,
+    }
+}
+
+// Opens the save station prompt shortly after the pickup is collected, once its popup is gone.
+// Loading the save puts Samus at the room's default spawn point, which can be somewhere unsafe or
+// missing altogether, so it's moved to the pickup.
+fn add_save_prompt(area: &mut mlvl_wrapper::MlvlArea, position: [f32; 3]) -> structs::Connection {
+    let timer_id = area.new_object_id_from_layer_id(0);
+    let special_function_id = area.new_object_id_from_layer_id(0);
+    let spawn_point_id = area.new_object_id_from_layer_id(0);
+
+    let scly = area.mrea().scly_section_mut();
+    let default_spawn_point = scly
+        .layers
+        .as_mut_vec()
+        .iter_mut()
+        .flat_map(|layer| layer.objects.as_mut_vec().iter_mut())
+        .filter_map(|obj| obj.property_data.as_spawn_point_mut())
+        .find(|spawn_point| spawn_point.default_spawn != 0);
+    match default_spawn_point {
+        Some(spawn_point) => spawn_point.position = position.into(),
+        None => scly.layers.as_mut_vec()[0]
+            .objects
+            .as_mut_vec()
+            .push(structs::SclyObject {
+                instance_id: spawn_point_id,
+                connections: vec![].into(),
+                property_data: structs::SclyProperty::SpawnPoint(Box::new(structs::SpawnPoint {
+                    name: b"Randomizer - Save Prompt Spawn Point\0".as_cstr(),
+                    position: position.into(),
+                    rotation: [0.0, 0.0, 0.0].into(),
+                    power: 0,
+                    ice: 0,
+                    wave: 0,
+                    plasma: 0,
+                    missiles: 0,
+                    scan_visor: 0,
+                    bombs: 0,
+                    power_bombs: 0,
+                    flamethrower: 0,
+                    thermal_visor: 0,
+                    charge: 0,
+                    super_missile: 0,
+                    grapple: 0,
+                    xray: 0,
+                    ice_spreader: 0,
+                    space_jump: 0,
+                    morph_ball: 0,
+                    combat_visor: 0,
+                    boost_ball: 0,
+                    spider_ball: 0,
+                    power_suit: 0,
+                    gravity_suit: 0,
+                    varia_suit: 0,
+                    phazon_suit: 0,
+                    energy_tanks: 0,
+                    unknown0: 0,
+                    health_refill: 0,
+                    unknown1: 0,
+                    wavebuster: 0,
+                    default_spawn: 1,
+                    active: 1,
+                    morphed: 0,
+                })),
+            }),
+    }
+
+    let objects = scly.layers.as_mut_vec()[0].objects.as_mut_vec();
+    objects.extend_from_slice(&[
+        structs::SclyObject {
+            instance_id: timer_id,
+            property_data: structs::Timer {
+                name: b"Randomizer - Save Prompt Timer\0".as_cstr(),
+                start_time: 1.0,
+                max_random_add: 0.0,
+                looping: 0,
+                start_immediately: 0,
+                active: 1,
+            }
+            .into(),
+            connections: vec![structs::Connection {
+                state: structs::ConnectionState::ZERO,
+                message: structs::ConnectionMsg::ACTION,
+                target_object_id: special_function_id,
+            }]
+            .into(),
+        },
+        structs::SclyObject {
+            instance_id: special_function_id,
+            property_data: structs::SpecialFunction {
+                name: b"Randomizer - Save Prompt\0".as_cstr(),
+                position: [0.0, 0.0, 0.0].into(),
+                rotation: [0.0, 0.0, 0.0].into(),
+                type_: SpecialFunctionType::SaveStation as u32,
+                unknown0: b"\0".as_cstr(),
+                unknown1: 0.0,
+                unknown2: 0.0,
+                unknown3: 0.0,
+                layer_change_room_id: 0xFFFFFFFF,
+                layer_change_layer_id: 0xFFFFFFFF,
+                item_id: 0,
+                unknown4: 1, // active
+                unknown5: 0.0,
+                unknown6: 0xFFFFFFFF,
+                unknown7: 0xFFFFFFFF,
+                unknown8: 0xFFFFFFFF,
+            }
+            .into(),
+            connections: vec![].into(),
+        },
+    ]);
+
+    structs::Connection {
+        state: structs::ConnectionState::ARRIVED,
+        message: structs::ConnectionMsg::RESET_AND_START,
+        target_object_id: timer_id,
+    }
+}
+
+// Locks one side of a door, see the callers for the other side
+fn patch_lock_door_with_key<'r>(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
+    game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+    door_loc: ModifiableDoorLocation,
+    key: &str,
+    (scan_id, strg_id): (ResId<res_id::SCAN>, ResId<res_id::STRG>),
+) -> Result<(), String> {
+    let mrea_id = area.mlvl_area.mrea.to_u32();
+    if door_loc.door_force_locations.is_empty() {
+        Err(format!(
+            "Tried to lock dock #{} in room 0x{:X}, which does not have a door",
+            door_loc.dock_number, mrea_id
+        ))?;
+    }
+    let poi_id = area.new_object_id_from_layer_id(0);
+
+    // The door can't be shot open until the key's memory relay re-activates its force field
+    let scly = area.mrea().scly_section_mut();
+    let layers = scly.layers.as_mut_vec();
+    let mut locked_objects = Vec::new();
+    for door_force_location in door_loc.door_force_locations.iter() {
+        let door_force = layers[door_force_location.layer as usize]
+            .objects
+            .as_mut_vec()
+            .iter_mut()
+            .find(|obj| obj.instance_id == door_force_location.instance_id)
+            .and_then(|obj| obj.property_data.as_damageable_trigger_mut())
+            .ok_or_else(|| {
+                format!(
+                    "Failed to find door force 0x{:X} in room 0x{:X}",
+                    door_force_location.instance_id, mrea_id
+                )
+            })?;
+        door_force.active = 0;
+        locked_objects.push((
+            door_force_location.instance_id,
+            structs::ConnectionMsg::ACTIVATE,
+        ));
+    }
+
+    // Scanning the door tells which key it needs, until it's unlocked
+    patch_add_poi(
+        ps,
+        area,
+        game_resources,
+        scan_id,
+        strg_id,
+        door_loc.dock_position,
+        Some(poi_id),
+        None,
+    )?;
+    locked_objects.push((poi_id, structs::ConnectionMsg::DEACTIVATE));
+
+    let key_lock = ps.key_locks.entry(key.to_string()).or_default();
+    for &sender_id in key_lock.memory_relay_ids.iter() {
+        for &(target_id, message) in locked_objects.iter() {
+            area.memory_relay_conns
+                .as_mut_vec()
+                .push(structs::MemoryRelayConn {
+                    sender_id,
+                    target_id,
+                    message: message.0 as u16,
+                    active: 0,
+                });
+        }
+    }
+    key_lock.locked_objects.extend(locked_objects);
+
+    Ok(())
+}
+
+// The dock on the other side of a door, in the room it leads to in vanilla. Both docks of a door
+// are at the same place.
+fn connected_dock(
+    rooms: &'static [pickup_meta::RoomInfo],
+    room_info: &pickup_meta::RoomInfo,
+    dock_position: [f32; 3],
+) -> Option<(&'static pickup_meta::RoomInfo, &'static DoorLocation)> {
+    rooms
+        .iter()
+        .filter(|other| other.room_id != room_info.room_id)
+        .flat_map(|other| other.door_locations.iter().map(move |dl| (other, dl)))
+        .find(|(_, dl)| (0..3).all(|i| f32::abs(dl.dock_position[i] - dock_position[i]) < 5.0))
+}
+
+fn patch_add_teleporter<'r>(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
+    game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+    config: TeleporterConfig,
+    version: Version,
+) -> Result<(), String> {
+    let layer = config.layer.unwrap_or(0);
+    while area.layer_flags.layer_count <= layer {
+        area.add_layer(b"New Layer\0".as_cstr());
+    }
+
+    if config.pad.unwrap_or(true) {
+        patch_add_platform(
+            ps,
+            area,
+            game_resources,
+            PlatformConfig {
+                id: None,
+                layer: Some(layer),
+                active: config.active,
+                position: config.position,
+                rotation: None,
+                alt_platform: None,
+                platform_type: Some(PlatformType::Metal),
+                xray_only: None,
+                thermal_only: None,
+                waypoints: None,
+                speed: None,
+                pause: None,
+                looping: None,
+                activation_trigger: None,
+            },
+        )?;
+
+        // The hologram of the elevators, over the pad
+        let holobeam: (u32, FourCC) = resource_info!("holobeam.CMDL").into();
+        let textures = referenced_assets(
+            holobeam.1,
+            &crate::ResourceData::new(&game_resources[&holobeam]).decompress(),
+        );
+        let deps_iter = iter::once(holobeam)
+            .chain(textures)
+            .map(|(asset_id, asset_type)| structs::Dependency {
+                asset_id,
+                asset_type,
+            });
+        area.add_dependencies(game_resources, 0, deps_iter);
+
+        let hologram_id = area.new_object_id_from_layer_id(layer as usize);
+        let [x, y, z] = config.position;
+        let scly = area.mrea().scly_section_mut();
+        scly.layers.as_mut_vec()[layer as usize]
+            .objects
+            .as_mut_vec()
+            .push(structs::SclyObject {
+                instance_id: hologram_id,
+                connections: vec![].into(),
+                property_data: structs::SclyProperty::Actor(Box::new(structs::Actor {
+                    name: b"teleporterhologram\0".as_cstr(),
+                    position: [x, y, z + 0.5].into(),
+                    rotation: [0.0, 0.0, 0.0].into(),
+                    scale: [1.0, 1.0, 1.0].into(),
+                    hitbox: [0.0, 0.0, 0.0].into(),
+                    scan_offset: [0.0, 0.0, 0.0].into(),
+                    unknown1: 1.0, // mass
+                    unknown2: 0.0, // momentum
+                    health_info: structs::scly_structs::HealthInfo {
+                        health: 5.0,
+                        knockback_resistance: 1.0,
+                    },
+                    damage_vulnerability: DoorType::Disabled.vulnerability(),
+                    cmdl: ResId::new(holobeam.0),
+                    ancs: structs::scly_structs::AncsProp {
+                        file_id: ResId::invalid(),
+                        node_index: 0,
+                        default_animation: 0xFFFFFFFF,
+                    },
+                    actor_params: structs::scly_structs::ActorParameters {
+                        light_params: structs::scly_structs::LightParameters {
+                            unknown0: 1,
+                            unknown1: 1.0,
+                            shadow_tessellation: 0,
+                            unknown2: 1.0,
+                            unknown3: 20.0,
+                            color: [1.0, 1.0, 1.0, 1.0].into(), // RGBA
+                            unknown4: 1,
+                            world_lighting: 1,
+                            light_recalculation: 1,
+                            unknown5: [0.0, 0.0, 0.0].into(),
+                            unknown6: 4,
+                            unknown7: 4,
+                            unknown8: 0,
+                            light_layer_id: 0,
+                        },
+                        scan_params: structs::scly_structs::ScannableParameters {
+                            scan: ResId::invalid(),
+                        },
+                        xray_cmdl: ResId::invalid(),
+                        xray_cskr: ResId::invalid(),
+                        thermal_cmdl: ResId::invalid(),
+                        thermal_cskr: ResId::invalid(),
+                        unknown0: 1,
+                        unknown1: 1.0,
+                        unknown2: 1.0,
+                        visor_params: structs::scly_structs::VisorParameters {
+                            unknown0: 0,
+                            target_passthrough: 1,
+                            visor_mask: 15, // Visor Flags : Combat|Scan|Thermal|XRay
+                        },
+                        enable_thermal_heat: 0,
+                        unknown3: 0,
+                        unknown4: 0,
+                        unknown5: 1.0,
+                    },
+                    looping: 1,
+                    snow: 1, // immovable
+                    solid: 0,
+                    camera_passthrough: 1,
+                    active: config.active.unwrap_or(true) as u8,
+                    unknown8: 0,
+                    unknown9: 1.0,
+                    unknown10: 0,
+                    unknown11: 0,
+                    unknown12: 0,
+                    unknown13: 0,
+                })),
+            });
+    }
+
+    let layer = layer as usize;
+    let trigger_id = area.new_object_id_from_layer_id(layer);
+    let special_function_id = area.new_object_id_from_layer_id(layer);
+    let four_ids = [
+        area.new_object_id_from_layer_id(layer),
+        area.new_object_id_from_layer_id(layer),
+        area.new_object_id_from_layer_id(layer),
+        area.new_object_id_from_layer_id(layer),
+    ];
+    let active = config.active.unwrap_or(true) as u8;
+    let [x, y, z] = config.position;
+
+    let scly = area.mrea().scly_section_mut();
+    let objects = scly.layers.as_mut_vec()[layer].objects.as_mut_vec();
+
+    // With a requirement, the trigger asks an inventory activator which only passes the
+    // signal along if the player has the item. Otherwise the trigger warps directly.
+    let warp_state = if config.required_item.is_some() {
+        structs::ConnectionState::ZERO
+    } else {
+        structs::ConnectionState::ENTERED
+    };
+    let warp_connections =
+        add_world_teleporter(four_ids, objects, &config.destination, warp_state, version);
+
+    let trigger_connections = match config.required_item {
+        Some(required_item) => {
+            objects.push(structs::SclyObject {
+                instance_id: special_function_id,
+                property_data: structs::SpecialFunction {
+                    name: b"teleporterspecialfunction\0".as_cstr(),
+                    position: config.position.into(),
+                    rotation: [0.0, 0.0, 0.0].into(),
+                    type_: SpecialFunctionType::InventoryActivator as u32,
+                    unknown0: b"\0".as_cstr(),
+                    unknown1: 0.0,
+                    unknown2: 0.0,
+                    unknown3: 0.0,
+                    layer_change_room_id: 0xFFFFFFFF,
+                    layer_change_layer_id: 0xFFFFFFFF,
+                    item_id: required_item.kind(),
+                    unknown4: 1,
+                    unknown5: 0.0,
+                    unknown6: 0xFFFFFFFF,
+                    unknown7: 0xFFFFFFFF,
+                    unknown8: 0xFFFFFFFF,
+                }
+                .into(),
+                connections: warp_connections.into(),
+            });
+
+            vec![structs::Connection {
+                state: structs::ConnectionState::ENTERED,
+                message: structs::ConnectionMsg::ACTION,
+                target_object_id: special_function_id,
+            }]
+        }
+        None => warp_connections,
+    };
+
+    objects.push(structs::SclyObject {
+        instance_id: trigger_id,
+        property_data: structs::Trigger {
+            name: b"teleportertrigger\0".as_cstr(),
+            position: [x, y, z + 2.0].into(),
+            scale: [3.0, 3.0, 4.0].into(),
+            damage_info: structs::scly_structs::DamageInfo {
+                weapon_type: 0,
+                damage: 0.0,
+                radius: 0.0,
+                knockback_power: 0.0,
+            },
+            force: [0.0, 0.0, 0.0].into(),
+            flags: 1, // detect player
+            active,
+            deactivate_on_enter: 0,
+            deactivate_on_exit: 0,
+        }
+        .into(),
+        connections: trigger_connections.into(),
+    });
+
+    Ok(())
+}
+
 fn is_area_damage_special_function(obj: &structs::SclyObject) -> bool {
     let special_function = obj.property_data.as_special_function();
     special_function
@@ -3459,6 +4474,7 @@ fn patch_submerge_room<'r>(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+    display_fluid_surface: bool,
 ) -> Result<(), String> {
     let water_type = WaterType::Normal;
 
@@ -3483,6 +4499,7 @@ fn patch_submerge_room<'r>(
     ]
     .into();
     water.position = room_origin.into();
+    water.display_fluid_surface = display_fluid_surface as u8;
 
     // add water to area //
     let scly = area.mrea().scly_section_mut();
@@ -3492,6 +4509,76 @@ fn patch_submerge_room<'r>(
     Ok(())
 }
 
+fn patch_room_physics(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+    config: RoomPhysicsConfig,
+    global_gravity: f32,
+) -> Result<(), String> {
+    // Stop vanilla water from putting the player into fluid physics
+    if config.underwater_physics == Some(false) {
+        let scly = area.mrea().scly_section_mut();
+        for layer in scly.layers.as_mut_vec().iter_mut() {
+            for obj in layer.objects.as_mut_vec().iter_mut() {
+                if let Some(water) = obj.property_data.as_water_mut() {
+                    water.unknown2 &= !(0x1 | 0x1000 | 0x10000); // detect player/morphed/unmorphed
+                }
+            }
+        }
+    }
+
+    let gravity_scale = match config.gravity_scale {
+        Some(gravity_scale) => gravity_scale,
+        None => return Ok(()),
+    };
+
+    if gravity_scale < 0.0 {
+        Err(format!(
+            "Room gravity scale must not be negative (got {})",
+            gravity_scale
+        ))?;
+    }
+
+    // The trigger applies (force * mass), so counteract the difference between normal
+    // gravity acceleration and the requested one
+    const NORMAL_GRAV_ACCEL: f32 = 35.0;
+    let force = NORMAL_GRAV_ACCEL * global_gravity * (1.0 - gravity_scale);
+
+    let (_, _, bounding_box_extent, room_origin) = derrive_bounding_box_measurements(area);
+    let trigger_id = area.new_object_id_from_layer_id(0);
+
+    let scly = area.mrea().scly_section_mut();
+    let layer = &mut scly.layers.as_mut_vec()[0];
+    layer.objects.as_mut_vec().push(structs::SclyObject {
+        instance_id: trigger_id,
+        property_data: structs::Trigger {
+            name: b"gravitytrigger\0".as_cstr(),
+            position: room_origin.into(),
+            scale: [
+                bounding_box_extent[0] * 2.0, // half-extent into full-extent
+                bounding_box_extent[1] * 2.0,
+                bounding_box_extent[2] * 2.0,
+            ]
+            .into(),
+            damage_info: structs::scly_structs::DamageInfo {
+                weapon_type: 0,
+                damage: 0.0,
+                radius: 0.0,
+                knockback_power: 0.0,
+            },
+            force: [0.0, 0.0, force].into(),
+            flags: 1, // detect player
+            active: 1,
+            deactivate_on_enter: 0,
+            deactivate_on_exit: 0,
+        }
+        .into(),
+        connections: vec![].into(),
+    });
+
+    Ok(())
+}
+
 fn patch_remove_tangle_weed_scan_point(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
@@ -3565,6 +4652,50 @@ fn patch_add_poi<'r>(
     Ok(())
 }
 
+// Places the seed info scan in front of the player's spawn point, once scanned it can be read from
+// the logbook anywhere
+fn patch_add_seed_info_poi<'r>(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
+    game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+) -> Result<(), String> {
+    // default spawn, position, yaw in degrees
+    let mut spawn_points: Vec<(bool, [f32; 3], f32)> = Vec::new();
+    let scly = area.mrea().scly_section();
+    for layer in scly.layers.iter() {
+        for obj in layer.objects.iter() {
+            if let Some(sp) = obj.property_data.as_spawn_point() {
+                let position = [sp.position[0], sp.position[1], sp.position[2]];
+                spawn_points.push((sp.default_spawn != 0, position, sp.rotation[2]));
+            }
+        }
+    }
+    let (_, spawn_position, yaw) = spawn_points
+        .iter()
+        .find(|(default_spawn, _, _)| *default_spawn)
+        .or_else(|| spawn_points.first())
+        .copied()
+        .ok_or_else(|| "The starting room has no spawn point".to_string())?;
+
+    let yaw = yaw.to_radians();
+    let position = [
+        spawn_position[0] - 3.0 * yaw.sin(),
+        spawn_position[1] + 3.0 * yaw.cos(),
+        spawn_position[2] + 2.5,
+    ];
+
+    patch_add_poi(
+        ps,
+        area,
+        game_resources,
+        custom_asset_ids::SEED_INFO_SCAN,
+        custom_asset_ids::SEED_INFO_STRG,
+        position,
+        None,
+        None,
+    )
+}
+
 fn patch_add_scan_actor<'r>(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
@@ -3692,8 +4823,12 @@ where
     closest
 }
 
+const SHUFFLED_POSITION_ATTEMPTS: usize = 32;
+const SHUFFLED_PICKUP_HEIGHT: f32 = 1.0; // above the floor
+
 fn get_shuffled_position<R>(
     area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+    shuffle_bounds: Option<&ShuffleBoundsConfig>,
     rng: &mut R,
 ) -> [f32; 3]
 where
@@ -3703,66 +4838,243 @@ where
 
     // xmin, ymin, zmin,
     // xmax, ymax, zmax,
-    let mut bounding_boxes: Vec<[f32; 6]> = Vec::new();
-    {
-        let (bounding_box_min, bounding_box_max, _, _) = derrive_bounding_box_measurements(area);
-        bounding_boxes.push([
-            bounding_box_min[0],
-            bounding_box_min[1],
-            bounding_box_min[2],
-            bounding_box_max[0],
-            bounding_box_max[1],
-            bounding_box_max[2],
-        ]);
-    }
-
-    if mrea_id == 0x2398E906 {
-        // Artifact Temple
-        bounding_boxes.clear();
-        bounding_boxes.push([-410.0, 20.0, -40.0, -335.0, 69.0, -17.0]);
-        bounding_boxes.push([-411.429, 67.9626, -14.8928, -370.429, 93.9626, -9.8928]);
-    } else if mrea_id == 0x4148F7B0 {
-        // burn dome
-        bounding_boxes.clear();
-        bounding_boxes.push([565.7892, -27.4683, 30.6111, 589.7892, 0.5317, 42.6111]);
-        bounding_boxes.push([578.9656, 35.3132, 31.0428, 598.9656, 44.3132, 37.0428]);
-        bounding_boxes.push([588.6971, 9.1298, 29.8123, 589.6971, 49.1298, 31.8123]);
-    }
+    let bounding_boxes: Vec<[f32; 6]> = match shuffle_bounds.and_then(|x| x.boxes.clone()) {
+        Some(boxes) if !boxes.is_empty() => boxes,
+        _ => {
+            let (bounding_box_min, bounding_box_max, _, _) =
+                derrive_bounding_box_measurements(area);
+            vec![[
+                bounding_box_min[0],
+                bounding_box_min[1],
+                bounding_box_min[2],
+                bounding_box_max[0],
+                bounding_box_max[1],
+                bounding_box_max[2],
+            ]]
+        }
+    };
 
     let mut offset_xy = 0.0;
     let mut offset_max_z = 0.0;
-    if [
-        0xC44E7A07, // landing site
-        0xB2701146, // alcove
-        0xB9ABCD56, // fcs
-        0x9A0A03EB, // sunchamber
-        0xFB54A0CB, // hote
-        0xBAD9EDBF, // Triclops pit
-        0x3953C353, // Elite Quarters
-        0x70181194, // Quarantine Cave
-        0xC7E821BA, // ttb
-        0x4148F7B0, // burn dome
-        0x43E4CC25, // hydra
-        0x21B4BFF6,
-    ]
-    .contains(&mrea_id)
-    {
+    if shuffle_bounds.and_then(|x| x.avoid_edges).unwrap_or(false) {
         offset_xy = 0.1;
         offset_max_z = -0.3;
     }
 
-    // Pick the relative position inside the bounding box
-    let x_factor: f32 = gen_n_pick_closest(2, rng, 0.15 + offset_xy, 0.85 - offset_xy, 0.5);
-    let y_factor: f32 = gen_n_pick_closest(2, rng, 0.15 + offset_xy, 0.85 - offset_xy, 0.5);
-    let z_factor: f32 = gen_n_pick_closest(2, rng, 0.1, 0.8 + offset_max_z, 0.35);
+    let pick_position = |rng: &mut R| {
+        // Pick the relative position inside the bounding box
+        let x_factor: f32 = gen_n_pick_closest(2, rng, 0.15 + offset_xy, 0.85 - offset_xy, 0.5);
+        let y_factor: f32 = gen_n_pick_closest(2, rng, 0.15 + offset_xy, 0.85 - offset_xy, 0.5);
+        let z_factor: f32 = gen_n_pick_closest(2, rng, 0.1, 0.8 + offset_max_z, 0.35);
 
-    // Pick a bounding box if multiple are available
-    let bounding_box = *bounding_boxes.choose(rng).unwrap();
-    [
-        bounding_box[0] + (bounding_box[3] - bounding_box[0]) * x_factor,
-        bounding_box[1] + (bounding_box[4] - bounding_box[1]) * y_factor,
-        bounding_box[2] + (bounding_box[5] - bounding_box[2]) * z_factor,
-    ]
+        // Pick a bounding box if multiple are available
+        let bounding_box = *bounding_boxes.choose(rng).unwrap();
+        [
+            bounding_box[0] + (bounding_box[3] - bounding_box[0]) * x_factor,
+            bounding_box[1] + (bounding_box[4] - bounding_box[1]) * y_factor,
+            bounding_box[2] + (bounding_box[5] - bounding_box[2]) * z_factor,
+        ]
+    };
+
+    // Drop the position onto the floor under it, trying others if it's inside a wall or over a
+    // pit. The bounding box is all there is to go by if the collision can't be read.
+    let collision = match AreaCollision::from_area(area) {
+        Ok(collision) => collision,
+        Err(e) => {
+            warn!(
+                "Failed to read the collision of room 0x{:08X}, shuffled pickups may end up inside walls: {}",
+                mrea_id, e
+            );
+            return pick_position(rng);
+        }
+    };
+    let mut position = [0.0; 3];
+    for _ in 0..SHUFFLED_POSITION_ATTEMPTS {
+        position = pick_position(rng);
+        if let Some(snapped) = collision.snap_to_floor(position, SHUFFLED_PICKUP_HEIGHT) {
+            return snapped;
+        }
+    }
+    position
+}
+
+// The rooms hazards can be placed in, with the kind of hazard fitting each. Acid rain only falls
+// outdoors in Tallon Overworld, the only world with the rain particle in its pak.
+const HAZARD_ROOMS: &[(&str, &str, HazardKind)] = &[
+    ("Tallon Overworld", "Landing Site", HazardKind::AcidRain),
+    ("Tallon Overworld", "Gully", HazardKind::AcidRain),
+    ("Tallon Overworld", "Alcove", HazardKind::AcidRain),
+    ("Tallon Overworld", "Tallon Canyon", HazardKind::AcidRain),
+    (
+        "Tallon Overworld",
+        "Frigate Crash Site",
+        HazardKind::AcidRain,
+    ),
+    ("Tallon Overworld", "Root Cave", HazardKind::AcidRain),
+    (
+        "Tallon Overworld",
+        "Overgrown Cavern",
+        HazardKind::SporeCloud,
+    ),
+    ("Tallon Overworld", "Root Tunnel", HazardKind::SporeCloud),
+    ("Tallon Overworld", "Arbor Chamber", HazardKind::SporeCloud),
+    (
+        "Tallon Overworld",
+        "Great Tree Hall",
+        HazardKind::SporeCloud,
+    ),
+    (
+        "Tallon Overworld",
+        "Life Grove Tunnel",
+        HazardKind::SporeCloud,
+    ),
+    ("Chozo Ruins", "Main Plaza", HazardKind::SporeCloud),
+    ("Chozo Ruins", "Ruined Fountain", HazardKind::SporeCloud),
+    ("Chozo Ruins", "Ruined Shrine", HazardKind::SporeCloud),
+    ("Chozo Ruins", "Ruined Nursery", HazardKind::SporeCloud),
+    ("Chozo Ruins", "Arboretum", HazardKind::SporeCloud),
+    ("Phazon Mines", "Fungal Hall Access", HazardKind::SporeCloud),
+    ("Phazon Mines", "Fungal Hall A", HazardKind::SporeCloud),
+    ("Phazon Mines", "Fungal Hall B", HazardKind::SporeCloud),
+    ("Impact Crater", "Crater Tunnel A", HazardKind::SporeCloud),
+    ("Impact Crater", "Phazon Core", HazardKind::SporeCloud),
+    (
+        "Impact Crater",
+        "Crater Missile Station",
+        HazardKind::SporeCloud,
+    ),
+    ("Impact Crater", "Crater Tunnel B", HazardKind::SporeCloud),
+];
+
+// Damaging volumes resting on the floor of the room, each with a particle effect to show where
+// it is. The volumes are placed like shuffled pickups, so they don't end up inside walls.
+fn patch_add_hazards(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+    kind: HazardKind,
+    hazards: &HazardsConfig,
+    seed: u64,
+) -> Result<(), String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let size = hazards.size.unwrap_or(6.0);
+    let part = match kind {
+        HazardKind::AcidRain => resource_info!("rain1.PART").res_id,
+        HazardKind::SporeCloud => resource_info!("BaseFog.PART").res_id,
+    };
+
+    for _ in 0..hazards.hazards_per_room.unwrap_or(3) {
+        let position = get_shuffled_position(area, None, &mut rng);
+        let floor_z = position[2] - SHUFFLED_PICKUP_HEIGHT;
+        let center = [position[0], position[1], floor_z + size / 2.0];
+
+        patch_add_trigger(
+            ps,
+            area,
+            TriggerConfig {
+                id: None,
+                layer: None,
+                active: Some(true),
+                position: Some(center),
+                scale: Some([size, size, size]),
+                force: None,
+                damage_type: Some(DamageType::PoisonWater),
+                damage_amount: Some(hazards.damage_per_sec.unwrap_or(5.0)),
+                flags: None,
+                deactivate_on_enter: None,
+                deactivate_on_exit: None,
+            },
+        )?;
+
+        // Rain falls from the top of the volume, spores float in the middle of it
+        let effect_position = match kind {
+            HazardKind::AcidRain => [center[0], center[1], floor_z + size],
+            HazardKind::SporeCloud => center,
+        };
+        patch_add_effect(
+            ps,
+            area,
+            EffectConfig {
+                id: None,
+                layer: None,
+                active: Some(true),
+                position: Some(effect_position),
+                rotation: None,
+                scale: None,
+                part: Some(part),
+                hot_in_thermal: None,
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+// Makes the Chozo Ghost and Space Pirate ambushes of a room happen on every visit. Their layers
+// are normally turned off by a layer switch of the room once the fight is over, so nothing sends
+// to those switches anymore, and the memory relays of the ambush become plain relays so that
+// they don't remember how the fight ended.
+fn patch_respawn_enemy_layers(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+) -> Result<(), String> {
+    let room_id = area.mlvl_area.internal_id;
+    let layers = area.mrea().scly_section_mut().layers.as_mut_vec();
+
+    // Layers with pickups are left alone, the pickups would respawn with the enemies
+    let ambush_layers: Vec<u32> = layers
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, layer)| {
+            let objects = || layer.objects.iter().map(|obj| &obj.property_data);
+            objects()
+                .any(|obj| obj.is_chozo_ghost() || obj.is_space_pirate() || obj.is_flying_pirate())
+                && !objects().any(|obj| obj.is_pickup())
+        })
+        .map(|(i, _)| i as u32)
+        .collect();
+
+    let mut switch_ids = vec![];
+    let mut respawned_layers = vec![];
+    for layer in layers.iter() {
+        for obj in layer.objects.iter() {
+            let special_function = match obj.property_data.as_special_function() {
+                Some(special_function) => special_function,
+                None => continue,
+            };
+            if special_function.type_ == SpecialFunctionType::ScriptLayerController as u32
+                && special_function.layer_change_room_id == room_id
+                && ambush_layers.contains(&special_function.layer_change_layer_id)
+            {
+                switch_ids.push(obj.instance_id & 0x00FFFFFF);
+                respawned_layers.push(special_function.layer_change_layer_id as usize);
+            }
+        }
+    }
+
+    for layer in layers.iter_mut() {
+        for obj in layer.objects.as_mut_vec().iter_mut() {
+            obj.connections.as_mut_vec().retain(|conn| {
+                conn.message != structs::ConnectionMsg::DECREMENT
+                    || !switch_ids.contains(&(conn.target_object_id & 0x00FFFFFF))
+            });
+        }
+    }
+
+    for layer_idx in respawned_layers {
+        for obj in layers[layer_idx].objects.as_mut_vec().iter_mut() {
+            let relay = match obj.property_data.as_memory_relay() {
+                Some(memory_relay) => structs::Relay {
+                    name: memory_relay.name.clone(),
+                    active: memory_relay.active,
+                },
+                None => continue,
+            };
+            obj.property_data = relay.into();
+        }
+    }
+
+    Ok(())
 }
 
 fn set_room_map_default_state(
@@ -3801,35 +5113,333 @@ fn add_player_freeze_assets<'r>(
     Ok(())
 }
 
+// Named assets the game loads for each beam, indexed by `Beam`. The projectiles come first.
+const BEAM_ROOT_ASSETS: [&[ResourceInfo]; 4] = [
+    &[
+        resource_info!("PowerBeam.WPSC"),
+        resource_info!("PowerBall.WPSC"),
+        resource_info!("PowerMuzzle.PART"),
+        resource_info!("PowerAuxMuzzle.PART"),
+        resource_info!("PowerCharge.PART"),
+        resource_info!("Power2nd_1.PART"),
+    ],
+    &[
+        resource_info!("IceBeam.WPSC"),
+        resource_info!("IceBall.WPSC"),
+        resource_info!("IceMuzzle.PART"),
+        resource_info!("IceAuxMuzzle.PART"),
+        resource_info!("IceCharge.PART"),
+        resource_info!("IceXfer.PART"),
+        resource_info!("IceSmoke.PART"),
+        resource_info!("Ice2nd_1.PART"),
+        resource_info!("Ice2nd_2.PART"),
+    ],
+    &[
+        resource_info!("WaveBeam.WPSC"),
+        resource_info!("WaveBall.WPSC"),
+        resource_info!("WaveAuxMuzzle.PART"),
+        resource_info!("WaveCharge.PART"),
+        resource_info!("WaveXfer.PART"),
+        resource_info!("Wave2nd_1.ELSC"),
+        resource_info!("Wave2nd_2.ELSC"),
+        resource_info!("Wave2nd_3.PART"),
+    ],
+    &[
+        resource_info!("PlasmaBeam.WPSC"),
+        resource_info!("PlasmaBall.WPSC"),
+        resource_info!("PlasmaMuzzle.PART"),
+        resource_info!("PlasmaCharge.PART"),
+        resource_info!("Plasma2nd_1.PART"),
+    ],
+];
+
+// The beam's assets are preloaded from these when switching to it
+const BEAM_DGRPS: [ResourceInfo; 4] = [
+    resource_info!("Power_DGRP.DGRP"),
+    resource_info!("Ice_DGRP.DGRP"),
+    resource_info!("Wave_DGRP.DGRP"),
+    resource_info!("Plasma_DGRP.DGRP"),
+];
+
+const PARTICLE_FOURCCS: [&[u8; 4]; 6] = [b"PART", b"ELSC", b"SWHC", b"WPSC", b"CRSC", b"DPSC"];
+
+fn is_particle(fourcc: FourCC) -> bool {
+    PARTICLE_FOURCCS.iter().any(|p| fourcc == (*p).into())
+}
+
+fn resource_bytes(res: &structs::Resource) -> Result<Vec<u8>, String> {
+    let res_data = match &res.kind {
+        structs::ResourceKind::Unknown(_, _) => crate::ResourceData::new(res),
+        structs::ResourceKind::External(_, _) => crate::ResourceData::new_external(res),
+        _ => return Err(format!("Unsupported resource kind for 0x{:X}", res.file_id)),
+    };
+    Ok(res_data.decompress().into_owned())
+}
+
+// The references of a particle file to assets of the pak, as the offsets of their ids
+fn particle_asset_refs(data: &[u8], kinds: &HashMap<u32, FourCC>) -> Result<Vec<usize>, String> {
+    Ok(particle_elements(data)?
+        .asset_refs
+        .into_iter()
+        .filter(|&i| kinds.contains_key(&u32::from_be_bytes(data[i..i + 4].try_into().unwrap())))
+        .collect())
+}
+
+fn remap_particle_colors(data: &mut [u8], remap: &ColorRemap) -> Result<(), String> {
+    for offsets in particle_elements(data)?.colors {
+        let rgb = offsets.map(|o| f32::from_be_bytes(data[o..o + 4].try_into().unwrap()));
+        for (o, c) in offsets.iter().zip(remap_rgb(remap, rgb)) {
+            data[*o..*o + 4].copy_from_slice(&c.to_be_bytes());
+        }
+    }
+    Ok(())
+}
+
+// A DGRP is a count followed by (fourcc, id) pairs
+fn add_dgrp_dependencies(data: &mut Vec<u8>, deps: &[(FourCC, u32)]) {
+    let count = u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize;
+    let existing: HashSet<u32> = (0..count)
+        .map(|i| u32::from_be_bytes(data[8 + i * 8..12 + i * 8].try_into().unwrap()))
+        .collect();
+
+    // Drop the padding, it is added back when the pak is written
+    data.truncate(4 + count * 8);
+    let mut added = 0;
+    for (fourcc, id) in deps {
+        if existing.contains(id) {
+            continue;
+        }
+        data.extend_from_slice(fourcc.as_bytes());
+        data.extend_from_slice(&id.to_be_bytes());
+        added += 1;
+    }
+    data[0..4].copy_from_slice(&((count + added) as u32).to_be_bytes());
+}
+
+// Recolors and/or swaps the projectiles of each beam. The named assets of a beam are edited in
+// place, but everything they reference may be shared with other weapons, so those are copied
+// under new ids before being recolored.
+fn patch_beam_cosmetics<'r>(
+    file: &mut structs::FstEntryFile<'r>,
+    remaps: [Option<ColorRemap>; 4],
+    projectiles: [Option<Beam>; 4],
+    asset_cache: &AssetCache,
+) -> Result<(), String> {
+    let pak = match file {
+        structs::FstEntryFile::Pak(pak) => pak,
+        _ => unreachable!(),
+    };
+
+    let mut kinds = HashMap::new();
+    let mut vanilla = HashMap::new();
+    for res in pak.resources.iter() {
+        kinds.insert(res.file_id, res.fourcc());
+        if is_particle(res.fourcc()) || res.fourcc() == b"DGRP".into() {
+            vanilla.insert(res.file_id, resource_bytes(&res)?);
+        }
+    }
+
+    // Assets are always read from here, so that one beam's edits don't leak into another's
+    let mut sources = vanilla.clone();
+    for (beam, projectile) in projectiles.iter().enumerate() {
+        if let Some(projectile) = projectile {
+            for i in 0..2 {
+                let data = vanilla[&BEAM_ROOT_ASSETS[*projectile as usize][i].res_id].clone();
+                sources.insert(BEAM_ROOT_ASSETS[beam][i].res_id, data);
+            }
+        }
+    }
+
+    let read_id = |data: &[u8], i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+
+    let mut edited: HashMap<u32, Vec<u8>> = HashMap::new();
+    let mut new_resources = vec![];
+    let mut texture_copies: HashMap<u32, Vec<(u32, ColorRemap)>> = HashMap::new();
+    let mut next_id = custom_asset_ids::BEAM_COSMETIC_ASSETS.to_u32();
+    for (beam, roots) in BEAM_ROOT_ASSETS.iter().enumerate() {
+        if remaps[beam].is_none() && projectiles[beam].is_none() {
+            continue;
+        }
+
+        let root_ids: Vec<u32> = roots.iter().map(|res| res.res_id).collect();
+        let mut reachable = root_ids.clone();
+        let mut i = 0;
+        while i < reachable.len() {
+            if let Some(data) = sources.get(&reachable[i]) {
+                for offset in particle_asset_refs(data, &kinds)? {
+                    let id = read_id(data, offset);
+                    if !reachable.contains(&id) {
+                        reachable.push(id);
+                    }
+                }
+            }
+            i += 1;
+        }
+
+        let mut new_ids = HashMap::new();
+        if let Some(remap) = remaps[beam] {
+            for id in reachable.iter().filter(|id| !root_ids.contains(id)) {
+                let kind = kinds[id];
+                if is_particle(kind) || kind == b"TXTR".into() {
+                    new_ids.insert(*id, next_id);
+                    next_id += 1;
+                }
+            }
+            if next_id > custom_asset_ids::EXTRA_IDS_START.to_u32() {
+                Err("Too many beam particle assets to recolor")?;
+            }
+
+            for id in reachable.iter() {
+                let kind = kinds[id];
+                if kind == b"TXTR".into() {
+                    texture_copies
+                        .entry(*id)
+                        .or_default()
+                        .push((new_ids[id], remap));
+                    continue;
+                }
+
+                let mut data = match sources.get(id) {
+                    Some(data) if is_particle(kind) => data.clone(),
+                    _ => continue,
+                };
+                for offset in particle_asset_refs(&data, &kinds)? {
+                    if let Some(new_id) = new_ids.get(&read_id(&data, offset)) {
+                        data[offset..offset + 4].copy_from_slice(&new_id.to_be_bytes());
+                    }
+                }
+                remap_particle_colors(&mut data, &remap)?;
+
+                match new_ids.get(id) {
+                    Some(new_id) => new_resources.push(build_resource_raw(
+                        *new_id,
+                        structs::ResourceKind::External(data, kind),
+                    )),
+                    None => {
+                        edited.insert(*id, data);
+                    }
+                }
+            }
+        } else {
+            for id in root_ids.iter().take(2) {
+                edited.insert(*id, sources[id].clone());
+            }
+        }
+
+        // Make sure the swapped projectile and the copies are loaded along with the beam
+        let deps: Vec<(FourCC, u32)> = reachable
+            .iter()
+            .map(|id| (kinds[id], *new_ids.get(id).unwrap_or(id)))
+            .collect();
+        let dgrp_id = BEAM_DGRPS[beam].res_id;
+        add_dgrp_dependencies(
+            edited
+                .entry(dgrp_id)
+                .or_insert_with(|| vanilla[&dgrp_id].clone()),
+            &deps,
+        );
+    }
+
+    let mut cursor = pak.resources.cursor();
+    loop {
+        let id = match cursor.peek() {
+            Some(res) => res.file_id,
+            None => break,
+        };
+        if let Some(data) = edited.remove(&id) {
+            let res = cursor.value().unwrap();
+            res.kind = structs::ResourceKind::External(data, res.fourcc());
+            res.compressed = false;
+        } else if let Some(copies) = texture_copies.get(&id) {
+            let txtr = cursor.peek().unwrap().into_owned();
+            for (new_id, remap) in copies {
+                let mut copy = txtr.clone();
+                copy.file_id = *new_id;
+                remap_txtr(&mut copy, remap, asset_cache)?;
+                new_resources.push(copy);
+            }
+        }
+        cursor.next();
+    }
+    cursor.insert_after(new_resources.into_iter());
+
+    Ok(())
+}
+
+fn patch_agsc_samples(
+    res: &mut structs::Resource,
+    samples: &HashMap<u16, DspSample>,
+) -> Result<(), String> {
+    let bytes = replace_agsc_samples(&resource_bytes(res)?, samples)?;
+    res.kind = structs::ResourceKind::External(bytes, b"AGSC".into());
+    res.compressed = false;
+    Ok(())
+}
+
 fn add_map_pickup_icon_txtr(file: &mut structs::FstEntryFile) -> Result<(), String> {
     let pak = match file {
         structs::FstEntryFile::Pak(pak) => pak,
         _ => unreachable!(),
     };
 
-    const TXTR_BYTES: &[u8] = include_bytes!("../extra_assets/map_pickupdot.txtr");
+    const TXTRS: &[(ResId<res_id::TXTR>, &[u8])] = &[
+        (
+            custom_asset_ids::MAP_PICKUP_ICON_MAJOR_TXTR,
+            include_bytes!("../extra_assets/map_pickupdot.txtr"),
+        ),
+        (
+            custom_asset_ids::MAP_PICKUP_ICON_EXPANSION_TXTR,
+            include_bytes!("../extra_assets/map_pickupdot_expansion.txtr"),
+        ),
+        (
+            custom_asset_ids::MAP_PICKUP_ICON_ARTIFACT_TXTR,
+            include_bytes!("../extra_assets/map_pickupdot_artifact.txtr"),
+        ),
+        (
+            custom_asset_ids::MAP_PICKUP_ICON_NOTHING_TXTR,
+            include_bytes!("../extra_assets/map_pickupdot_nothing.txtr"),
+        ),
+        (
+            custom_asset_ids::MAP_PICKUP_ICON_COLLECTED_TXTR,
+            include_bytes!("../extra_assets/map_pickupdot_collected.txtr"),
+        ),
+        (
+            custom_asset_ids::MAP_PICKUP_ICON_EXPANSION_COLLECTED_TXTR,
+            include_bytes!("../extra_assets/map_pickupdot_expansion_collected.txtr"),
+        ),
+        (
+            custom_asset_ids::MAP_PICKUP_ICON_ARTIFACT_COLLECTED_TXTR,
+            include_bytes!("../extra_assets/map_pickupdot_artifact_collected.txtr"),
+        ),
+        (
+            custom_asset_ids::MAP_PICKUP_ICON_NOTHING_COLLECTED_TXTR,
+            include_bytes!("../extra_assets/map_pickupdot_nothing_collected.txtr"),
+        ),
+    ];
 
     // append at the end of the pak
     let mut cursor = pak.resources.cursor();
     while cursor.cursor_advancer().peek().is_some() {}
-    let mut res = crate::custom_assets::build_resource_raw(
-        custom_asset_ids::MAP_PICKUP_ICON_TXTR.into(),
-        structs::ResourceKind::Unknown(Reader::new(TXTR_BYTES), b"TXTR".into()),
-    );
-    res.compressed = false;
-    cursor.insert_after(iter::once(res));
+    for (txtr_id, txtr_bytes) in TXTRS.iter() {
+        let mut res = crate::custom_assets::build_resource_raw(
+            (*txtr_id).into(),
+            structs::ResourceKind::Unknown(Reader::new(txtr_bytes), b"TXTR".into()),
+        );
+        res.compressed = false;
+        cursor.insert_after(iter::once(res));
+    }
     Ok(())
 }
 
 fn add_pickups_to_mapa(
     res: &mut structs::Resource,
-    show_icon: bool,
+    map_icon: Option<u32>,
     memory_relay: pickup_meta::ScriptObjectLocation,
     pickup_position: [f32; 3],
 ) -> Result<(), String> {
     let mapa = res.kind.as_mapa_mut().unwrap();
-    if show_icon {
-        mapa.add_pickup(memory_relay.instance_id, pickup_position);
+    if let Some(map_icon) = map_icon {
+        mapa.add_pickup(memory_relay.instance_id, pickup_position, map_icon);
     }
 
     Ok(())
@@ -3837,7 +5447,7 @@ fn add_pickups_to_mapa(
 
 #[allow(clippy::too_many_arguments)]
 fn modify_pickups_in_mrea<'r>(
-    _ps: &mut PatcherState,
+    ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     _pickup_idx: usize,
     pickup_config: &PickupConfig,
@@ -3851,12 +5461,18 @@ fn modify_pickups_in_mrea<'r>(
     qol_pickup_scans: bool,
     extern_models: &HashMap<String, ExternPickupModel>,
     shuffle_position: bool,
+    shuffle_bounds: Option<&ShuffleBoundsConfig>,
     seed: u64,
     _no_starting_visor: bool,
     version: Version,
     force_vanilla_layout: bool,
+    fix_chapel_item: bool,
+    pickups_cosmetics: Option<&PickupsCosmeticsConfig>,
+    totem_hint_layers: Option<(u32, u32)>,
+    save_prompt: bool,
 ) -> Result<(), String> {
     let mrea_id = area.mlvl_area.mrea.to_u32();
+    let fix_chapel_item = fix_chapel_item && mrea_id == 0x40C548E9;
 
     let mut pickup_config = pickup_config.clone();
 
@@ -3894,21 +5510,18 @@ fn modify_pickups_in_mrea<'r>(
     let mut auto_respawn_special_function_id = 0;
     let mut auto_respawn_timer_id = 0;
     let mut chapel_repo_despawn_timer_id = 0;
-    if respawn || mrea_id == 0x40C548E9 {
-        auto_respawn_layer_idx = area.layer_flags.layer_count as usize;
+    if respawn || fix_chapel_item {
+        auto_respawn_layer_idx = area.add_named_layer("auto-respawn layer", LayerDefault::Disabled);
         auto_respawn_special_function_id = area.new_object_id_from_layer_id(0);
 
         // Fix chapel IS
-        if mrea_id == 0x40C548E9 {
+        if fix_chapel_item {
             chapel_repo_despawn_timer_id = area.new_object_id_from_layer_id(auto_respawn_layer_idx);
         }
 
         if respawn {
             auto_respawn_timer_id = area.new_object_id_from_layer_id(auto_respawn_layer_idx);
         }
-
-        area.add_layer(b"auto-respawn layer\0".as_cstr());
-        area.layer_flags.flags &= !(1 << auto_respawn_layer_idx); // layer disabled by default
     }
 
     let jumbo_poi = shuffle_position || *pickup_config.jumbo_scan.as_ref().unwrap_or(&false);
@@ -3916,15 +5529,14 @@ fn modify_pickups_in_mrea<'r>(
     let mut jumbo_poi_special_function_id = 0;
     let mut jumbo_poi_id = 0;
     if jumbo_poi {
-        jumbo_poi_layer_idx = area.layer_flags.layer_count as usize;
+        jumbo_poi_layer_idx = area.add_named_layer("jumbo poi layer", LayerDefault::Enabled);
         jumbo_poi_special_function_id = area.new_object_id_from_layer_id(0);
         jumbo_poi_id = area.new_object_id_from_layer_id(jumbo_poi_layer_idx);
-        area.add_layer(b"jumbo poi layer\0".as_cstr());
     }
 
     let mut position_override: Option<[f32; 3]> = None;
     if shuffle_position {
-        position_override = Some(get_shuffled_position(area, &mut rng));
+        position_override = Some(get_shuffled_position(area, shuffle_bounds, &mut rng));
     }
 
     // Pickup to use for game functionality //
@@ -4065,9 +5677,15 @@ fn modify_pickups_in_mrea<'r>(
     if (29..=40).contains(&pickup_kind) {
         special_fn_artifact_layer_change_id = area.new_object_id_from_layer_name("Default");
     }
+    let totem_hint_switch_ids = totem_hint_layers.map(|_| {
+        [
+            area.new_object_id_from_layer_name("Default"),
+            area.new_object_id_from_layer_name("Default"),
+        ]
+    });
 
     // Fix chapel IS
-    if mrea_id == 0x40C548E9 {
+    if fix_chapel_item {
         trigger_id = area.new_object_id_from_layer_name("Default");
     }
 
@@ -4091,6 +5709,21 @@ fn modify_pickups_in_mrea<'r>(
         area.new_object_id_from_layer_id(0),
     ];
 
+    let key_connection = pickup_config
+        .key
+        .as_ref()
+        .map(|key| add_key_memory_relay(ps, area, key));
+    let save_prompt_connection = save_prompt.then(|| {
+        let position = position_override
+            .or(pickup_config.position)
+            .unwrap_or(pickup_location.position);
+        add_save_prompt(area, position)
+    });
+
+    let sound = pickup_config
+        .sound_id
+        .map(|sound_id| (sound_id, area.new_object_id_from_layer_id(0)));
+
     let scly = area.mrea().scly_section_mut();
     let layers = scly.layers.as_mut_vec();
 
@@ -4100,11 +5733,59 @@ fn modify_pickups_in_mrea<'r>(
             four_ids,
             layers[0].objects.as_mut_vec(),
             &pickup_config.destination.clone().unwrap(),
+            structs::ConnectionState::ARRIVED,
             version,
         );
     }
 
     let mut additional_connections = Vec::new();
+    if let Some(key_connection) = key_connection {
+        additional_connections.push(key_connection);
+    }
+    if let Some(save_prompt_connection) = save_prompt_connection {
+        additional_connections.push(save_prompt_connection);
+    }
+
+    // Vanilla pickups only have a jingle, add a sound effect if one is requested
+    let play_attainment_audio = pickup_config
+        .attainment_audio
+        .as_ref()
+        .map(|audio| audio.enabled())
+        .unwrap_or(true);
+    if let Some((sound_id, sound_obj_id)) = sound.filter(|_| play_attainment_audio) {
+        layers[0].objects.as_mut_vec().push(structs::SclyObject {
+            instance_id: sound_obj_id,
+            connections: vec![].into(),
+            property_data: structs::Sound {
+                name: b"mysound\0".as_cstr(),
+                position: [0.0, 0.0, 0.0].into(),
+                rotation: [0.0, 0.0, 0.0].into(),
+                sound_id,
+                active: 1,
+                max_dist: 50.0,
+                dist_comp: 0.2,
+                start_delay: 0.0,
+                min_volume: 20,
+                volume: 127,
+                priority: 127,
+                pan: 64,
+                loops: 0,
+                non_emitter: 1,
+                auto_start: 0,
+                occlusion_test: 0,
+                acoustics: 0,
+                world_sfx: 0,
+                allow_duplicates: 0,
+                pitch: 0,
+            }
+            .into(),
+        });
+        additional_connections.push(structs::Connection {
+            state: structs::ConnectionState::ARRIVED,
+            message: structs::ConnectionMsg::PLAY,
+            target_object_id: sound_obj_id,
+        });
+    }
 
     // 2022-02-08 - I had to remove this because there's a bug in the vanilla engine where playerhint -> Scan Visor doesn't holster the weapon
     // if pickup_type == PickupType::ScanVisor && no_starting_visor {
@@ -4239,6 +5920,13 @@ fn modify_pickups_in_mrea<'r>(
         });
     }
 
+    // If this artifact reveals the exact hint of a totem, switch its hint layers
+    if let (Some(layers_ids), Some(switch_ids)) = (totem_hint_layers, totem_hint_switch_ids) {
+        let (functions, connections) = artifact_totem_hint_switches(switch_ids, layers_ids);
+        layers[0].objects.as_mut_vec().extend(functions);
+        additional_connections.extend(connections);
+    }
+
     // If this is an ice trap, insert a special function to freeze the player on picking up
     // Extra dependencies for the freeze effect
     // steamTxtr -> "Frost1TXTR.TXTR"
@@ -4260,7 +5948,7 @@ fn modify_pickups_in_mrea<'r>(
         });
     }
 
-    if respawn || mrea_id == 0x40C548E9 {
+    if respawn || fix_chapel_item {
         if auto_respawn_timer_id != 0 {
             let timer = structs::SclyObject {
                 instance_id: auto_respawn_timer_id,
@@ -4336,7 +6024,7 @@ fn modify_pickups_in_mrea<'r>(
     }
 
     // Fix chapel IS
-    if mrea_id == 0x40C548E9 {
+    if fix_chapel_item {
         // additional_connections.push(
         //     structs::Connection {
         //         state: structs::ConnectionState::ARRIVED,
@@ -4475,6 +6163,8 @@ fn modify_pickups_in_mrea<'r>(
                 &pickup_config,
                 scan_id,
                 position_override,
+                pickups_cosmetics.and_then(|x| x.jitter(pickup_type)),
+                &mut rng,
             );
 
             if !additional_connections.is_empty() {
@@ -4582,7 +6272,11 @@ fn modify_pickups_in_mrea<'r>(
         .iter_mut()
         .find(|obj| obj.instance_id == location.instance_id)
         .unwrap();
-    update_attainment_audio(attainment_audio, pickup_type);
+    update_attainment_audio(
+        attainment_audio,
+        pickup_type,
+        pickup_config.attainment_audio.as_ref(),
+    );
 
     Ok(())
 }
@@ -4696,14 +6390,35 @@ fn place_floaty_contraption(
     });
 }
 
-fn update_pickup(
+fn update_pickup<R>(
     pickup_obj: &mut structs::SclyObject,
     pickup_type: PickupType,
-    pickup_model_data: structs::Pickup,
+    mut pickup_model_data: structs::Pickup,
     pickup_config: &PickupConfig,
     scan_id: ResId<res_id::SCAN>,
     position_override: Option<[f32; 3]>,
-) -> ([f32; 3], ResId<res_id::SCAN>) {
+    jitter: Option<PickupJitterConfig>,
+    rng: &mut R,
+) -> ([f32; 3], ResId<res_id::SCAN>)
+where
+    R: Rng,
+{
+    // Jittered before the model is centered on the original, so that it stays centered. The
+    // hitbox is the original's either way.
+    if let Some(jitter) = jitter {
+        let rotation = jitter.rotation.unwrap_or(0.0);
+        if rotation > 0.0 {
+            pickup_model_data.rotation[2] += rng.gen_range(-rotation, rotation);
+        }
+        let scale = jitter.scale.unwrap_or(0.0);
+        if scale > 0.0 {
+            let factor = 1.0 + rng.gen_range(-scale, scale);
+            pickup_model_data.scale[0] *= factor;
+            pickup_model_data.scale[1] *= factor;
+            pickup_model_data.scale[2] *= factor;
+        }
+    }
+
     let pickup = pickup_obj.property_data.as_pickup_mut().unwrap();
     let mut original_pickup = pickup.clone();
 
@@ -4849,13 +6564,29 @@ fn update_hudmemo(
     }
 }
 
-fn update_attainment_audio(attainment_audio: &mut structs::SclyObject, pickup_type: PickupType) {
+fn update_attainment_audio(
+    attainment_audio: &mut structs::SclyObject,
+    pickup_type: PickupType,
+    audio_override: Option<&AttainmentAudio>,
+) {
     let attainment_audio = attainment_audio
         .property_data
         .as_streamed_audio_mut()
         .unwrap();
-    let bytes = pickup_type.attainment_audio_file_name().as_bytes();
-    attainment_audio.audio_file_name = bytes.as_cstr();
+    match audio_override.and_then(|audio| audio.file_name()) {
+        Some(file_name) => {
+            attainment_audio.audio_file_name = Cow::Owned(CString::new(file_name).unwrap());
+        }
+        None => {
+            let bytes = pickup_type.attainment_audio_file_name().as_bytes();
+            attainment_audio.audio_file_name = bytes.as_cstr();
+        }
+    }
+
+    if !audio_override.map(|audio| audio.enabled()).unwrap_or(true) {
+        attainment_audio.active = 0;
+        attainment_audio.volume = 0;
+    }
 }
 
 fn calculate_center(
@@ -4971,12 +6702,121 @@ fn patch_elevator_actor_size(
     Ok(())
 }
 
-fn make_elevators_patch(
-    patcher: &mut PrimePatcher<'_, '_>,
+fn patch_sound_overrides(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+    sound_overrides: &HashMap<u32, SoundOverride>,
+) -> Result<(), String> {
+    let scly = area.mrea().scly_section_mut();
+    for layer in scly.layers.as_mut_vec().iter_mut() {
+        for obj in layer.objects.as_mut_vec().iter_mut() {
+            if !obj.property_data.is_sound() {
+                continue;
+            }
+            let sound = obj.property_data.as_sound_mut().unwrap();
+            let sound_override = match sound_overrides.get(&sound.sound_id) {
+                Some(sound_override) => sound_override,
+                None => continue,
+            };
+            if sound_override.mute.unwrap_or(false) {
+                sound.volume = 0;
+                sound.min_volume = 0;
+                continue;
+            }
+            if let Some(volume) = sound_override.volume {
+                sound.volume = volume;
+                sound.min_volume = sound.min_volume.min(volume);
+            }
+            if let Some(pitch) = sound_override.pitch {
+                sound.pitch = pitch as u32;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Plays `file_name` whenever the elevator's "access granted" memo is shown
+fn patch_elevator_audio(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+    hologram_strg: u32,
+    file_name: &str,
+) -> Result<(), String> {
+    let streamed_audio_id = area.new_object_id_from_layer_id(0);
+    let scly = area.mrea().scly_section_mut();
+
+    let memo_ids: Vec<u32> = scly
+        .layers
+        .iter()
+        .flat_map(|layer| layer.objects.iter())
+        .filter(|obj| {
+            obj.property_data
+                .as_hud_memo()
+                .map(|memo| memo.strg == hologram_strg)
+                .unwrap_or(false)
+        })
+        .map(|obj| obj.instance_id)
+        .collect();
+    if memo_ids.is_empty() {
+        Err(format!(
+            "Failed to find the memo of elevator hologram STRG 0x{:08X}",
+            hologram_strg
+        ))?;
+    }
+
+    // Whatever shows the memo also plays the audio
+    for layer in scly.layers.as_mut_vec() {
+        for obj in layer.objects.as_mut_vec() {
+            let plays: Vec<structs::Connection> = obj
+                .connections
+                .iter()
+                .filter(|conn| memo_ids.contains(&conn.target_object_id))
+                .map(|conn| structs::Connection {
+                    state: conn.state,
+                    message: structs::ConnectionMsg::PLAY,
+                    target_object_id: streamed_audio_id,
+                })
+                .collect();
+            obj.connections.as_mut_vec().extend(plays);
+        }
+    }
+
+    scly.layers.as_mut_vec()[0]
+        .objects
+        .as_mut_vec()
+        .push(structs::SclyObject {
+            instance_id: streamed_audio_id,
+            connections: vec![].into(),
+            property_data: structs::StreamedAudio {
+                name: b"elevator audio\0".as_cstr(),
+                active: 1,
+                audio_file_name: Cow::Owned(CString::new(file_name).unwrap()),
+                no_stop_on_deactivate: 0,
+                fade_in_time: 0.0,
+                fade_out_time: 0.0,
+                volume: 127,
+                oneshot: 1,
+                is_music: 0,
+            }
+            .into(),
+        });
+
+    Ok(())
+}
+
+// holobeam.CMDL, the hologram of every elevator
+const ELEVATOR_HOLOGRAM_CMDL: u32 = 0xD87334FA;
+
+fn make_elevators_patch<'r, 's>(
+    patcher: &mut PrimePatcher<'_, 's>,
+    game_resources: &'s HashMap<(u32, FourCC), structs::Resource<'r>>,
     level_data: &HashMap<String, LevelConfig>,
     auto_enabled_elevators: bool,
     player_size: f32,
     force_vanilla_layout: bool,
+    elevator_holograms: Option<&HashMap<String, ElevatorHologramCosmetics>>,
+    elevator_audio: &HashMap<u32, AttainmentAudio>,
     version: Version,
 ) -> (bool, bool) {
     for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
@@ -4988,10 +6828,32 @@ fn make_elevators_patch(
         }
     }
 
+    for elv in Elevator::iter() {
+        let file_name = match elevator_audio
+            .get(&elv.mrea)
+            .and_then(|audio| audio.file_name())
+        {
+            Some(file_name) => file_name,
+            None => continue,
+        };
+        if elv.hologram_strg == 0xFFFFFFFF {
+            continue; // no hologram
+        }
+        patcher.add_scly_patch((elv.pak_name.as_bytes(), elv.mrea), move |ps, area| {
+            patch_elevator_audio(ps, area, elv.hologram_strg, &file_name)
+        });
+    }
+
     if force_vanilla_layout {
         return (false, false);
     }
 
+    let hologram_worlds: Vec<World> = elevator_holograms
+        .iter()
+        .flat_map(|holograms| holograms.keys())
+        .map(|world_name| World::from_json_key(world_name))
+        .collect();
+
     let mut skip_frigate = true;
     let mut skip_ending_cinematic = false;
     for (_, level) in level_data.iter() {
@@ -5019,6 +6881,25 @@ fn make_elevators_patch(
                 skip_ending_cinematic = true;
             }
 
+            // The copy of the hologram made for the destination world, and the assets it needs
+            let hologram = World::iter()
+                .find(|world| world.mlvl() == dest.mlvl && hologram_worlds.contains(world))
+                .map(|world| {
+                    let cmdl = elevator_hologram_cmdl(world);
+                    let res = &game_resources[&(cmdl.to_u32(), FourCC::from_bytes(b"CMDL"))];
+                    let bytes = crate::ResourceData::new_external(res)
+                        .decompress()
+                        .into_owned();
+                    let deps: Vec<structs::Dependency> = iter::once((cmdl.to_u32(), res.fourcc()))
+                        .chain(referenced_assets(res.fourcc(), &bytes))
+                        .map(|(asset_id, asset_type)| structs::Dependency {
+                            asset_id,
+                            asset_type,
+                        })
+                        .collect();
+                    (cmdl, deps)
+                });
+
             patcher.add_scly_patch((elv.pak_name.as_bytes(), elv.mrea), move |_ps, area| {
                 let mut timer_id = 0;
                 if auto_enabled_elevators {
@@ -5076,6 +6957,24 @@ fn make_elevators_patch(
                     }
                 }
 
+                if let Some((cmdl, deps)) = hologram.as_ref() {
+                    let mut hologram_layers = vec![];
+                    let scly = area.mrea().scly_section_mut();
+                    for (i, layer) in scly.layers.iter_mut().enumerate() {
+                        for obj in layer.objects.iter_mut() {
+                            if let Some(actor) = obj.property_data.as_actor_mut() {
+                                if actor.cmdl == ELEVATOR_HOLOGRAM_CMDL {
+                                    actor.cmdl = *cmdl;
+                                    hologram_layers.push(i);
+                                }
+                            }
+                        }
+                    }
+                    for layer in hologram_layers {
+                        area.add_dependencies(game_resources, layer, deps.iter().cloned());
+                    }
+                }
+
                 Ok(())
             });
 
@@ -5672,6 +7571,37 @@ fn patch_edit_fog(
     Ok(())
 }
 
+fn fog_shuffle_palette(colorblind_mode: ColorblindMode) -> &'static [[f32; 3]] {
+    // Each preset sticks to hues which stay distinct from one another (and from the
+    // recolored doors) for that kind of color vision
+    match colorblind_mode {
+        ColorblindMode::None => &[
+            [0.8, 0.8, 0.9],
+            [0.9, 0.5, 0.3],
+            [0.4, 0.8, 0.4],
+            [0.3, 0.5, 0.9],
+            [0.7, 0.3, 0.8],
+            [0.9, 0.8, 0.4],
+            [0.3, 0.8, 0.8],
+            [0.8, 0.3, 0.3],
+        ],
+        ColorblindMode::Protanopia | ColorblindMode::Deuteranopia => &[
+            [0.8, 0.8, 0.9],
+            [0.3, 0.5, 0.9],
+            [0.2, 0.3, 0.7],
+            [0.9, 0.8, 0.4],
+            [0.6, 0.5, 0.2],
+        ],
+        ColorblindMode::Tritanopia => &[
+            [0.8, 0.8, 0.9],
+            [0.9, 0.4, 0.4],
+            [0.7, 0.2, 0.3],
+            [0.3, 0.8, 0.8],
+            [0.2, 0.5, 0.5],
+        ],
+    }
+}
+
 fn local_to_global_tranform(tranformation_matrix: [f32; 12], coordinates: [f32; 3]) -> [f32; 3] {
     [
         coordinates[0] * tranformation_matrix[0]
@@ -5844,11 +7774,14 @@ fn patch_visible_aether_boundaries<'r>(
             None,
             position,
             scale,
-            AETHER_BOUNDARY_TEXTURE,
+            AETHER_BOUNDARY_TEXTURE.cmdl(),
             0,
             None,
             true,
             true,
+            DoorType::Disabled.vulnerability(),
+            5.0,
+            vec![],
         );
     }
 
@@ -5901,6 +7834,146 @@ fn patch_ambient_lighting(
     Ok(())
 }
 
+// Makes pickups and doors show up in the Thermal Visor so that they can be found in the dark
+fn patch_thermal_heat_pickups_and_doors(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+) -> Result<(), String> {
+    let scly = area.mrea().scly_section_mut();
+    for layer in scly.layers.as_mut_vec().iter_mut() {
+        for obj in layer.objects.as_mut_vec().iter_mut() {
+            if let Some(pickup) = obj.property_data.as_pickup_mut() {
+                pickup.actor_params.enable_thermal_heat = 1;
+            } else if let Some(door) = obj.property_data.as_door_mut() {
+                door.actor_params.enable_thermal_heat = 1;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn patch_room_lighting(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+    config: LightingConfig,
+) -> Result<(), String> {
+    let mrea_id = area.mlvl_area.mrea.to_u32();
+
+    if let Some(color) = config.ambient_color {
+        let lights = area.mrea().lights_section_mut();
+        let lights = lights.light_layers.as_mut_vec();
+
+        let mut any = false;
+        for light in lights.iter_mut() {
+            if light.light_type != 0x0 {
+                // local ambient
+                continue;
+            }
+
+            light.color = color.into();
+            any = true;
+        }
+
+        if !any {
+            lights.push(LightLayer {
+                light_type: 0, // local ambient
+                color: color.into(),
+                position: [0.0, 0.0, 0.0].into(),
+                direction: [0.0, -1.0, 0.0].into(),
+                brightness: 1.0,
+                spot_cutoff: 0.0,
+                unknown0: 0.0,
+                unknown1: 0,
+                unknown2: 0.0,
+                falloff_type: 0, // constant
+                unknown3: 0.0,
+            });
+        }
+    }
+
+    if let Some(scale) = config.brightness_scale {
+        let lights = area.mrea().lights_section_mut();
+        for light in lights.light_layers.as_mut_vec().iter_mut() {
+            light.brightness *= scale;
+        }
+    }
+
+    if config.world_lighting.is_none() && config.light_params.is_none() {
+        return Ok(());
+    }
+
+    let light_params_configs = config.light_params.unwrap_or_default();
+    let mut found = HashSet::new();
+
+    let scly = area.mrea().scly_section_mut();
+    for layer in scly.layers.as_mut_vec().iter_mut() {
+        for obj in layer.objects.as_mut_vec().iter_mut() {
+            let obj_config = light_params_configs
+                .iter()
+                .find(|(id, _)| *id & 0x00FFFFFF == obj.instance_id & 0x00FFFFFF);
+
+            if let Some((id, _)) = obj_config {
+                if !obj.property_data.supports_light_params() {
+                    Err(format!(
+                        "Object 0x{:X} in room 0x{:X} does not support light params",
+                        id, mrea_id
+                    ))?;
+                }
+
+                found.insert(*id);
+            } else if config.world_lighting.is_none() || !obj.property_data.supports_light_params()
+            {
+                continue;
+            }
+
+            let mut light_params = obj.property_data.get_light_params();
+            for params in light_params.iter_mut() {
+                if let Some(world_lighting) = config.world_lighting {
+                    params.world_lighting = world_lighting as u32;
+                }
+
+                let obj_config = match obj_config {
+                    Some((_, obj_config)) => obj_config,
+                    None => continue,
+                };
+
+                if let Some(color) = obj_config.ambient_color {
+                    params.color = color.into();
+                }
+
+                if let Some(world_lighting) = obj_config.world_lighting {
+                    params.world_lighting = world_lighting as u32;
+                }
+
+                if let Some(light_layer) = obj_config.light_layer {
+                    params.light_layer_id = light_layer;
+                }
+
+                if let Some(max_dynamic_lights) = obj_config.max_dynamic_lights {
+                    params.unknown6 = max_dynamic_lights;
+                }
+
+                if let Some(max_area_lights) = obj_config.max_area_lights {
+                    params.unknown7 = max_area_lights;
+                }
+            }
+            obj.property_data.set_light_params(light_params);
+        }
+    }
+
+    for id in light_params_configs.keys() {
+        if !found.contains(id) {
+            Err(format!(
+                "Could not find object 0x{:X} in room 0x{:X}",
+                id, mrea_id
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
 // fn patch_add_orange_light<'r>(
 //     ps: &mut PatcherState,
 //     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
@@ -6418,6 +8491,140 @@ fn fix_artifact_of_truth_requirements(
     Ok(())
 }
 
+// The layers of the totems' hints are added right after the one of
+// fix_artifact_of_truth_requirements, so that the artifacts' layer switches know their ids
+const ARTIFACT_TOTEM_HINT_LAYERS: u32 = ARTIFACT_OF_TRUTH_REQ_LAYER + 1;
+
+fn artifact_totem_hint_layers(reveal_idx: usize) -> (u32, u32) {
+    let vague_layer = ARTIFACT_TOTEM_HINT_LAYERS + 2 * reveal_idx as u32;
+    (vague_layer, vague_layer + 1)
+}
+
+fn patch_artifact_totem_hint_layers<'r>(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
+    game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+    reveals: &[ArtifactTotemReveal],
+) -> Result<(), String> {
+    assert!(ARTIFACT_TOTEM_HINT_LAYERS == area.layer_flags.layer_count);
+
+    for (i, reveal) in reveals.iter().enumerate() {
+        let scan = ARTIFACT_TOTEM_SCANS[reveal.totem].res_id;
+        let exact_scan = ResId::<res_id::SCAN>::new(
+            custom_asset_ids::ARTIFACT_TOTEM_HINT_SCANS.to_u32() + reveal.totem as u32,
+        );
+        let exact_strg = ResId::<res_id::STRG>::new(
+            custom_asset_ids::ARTIFACT_TOTEM_HINT_STRGS.to_u32() + reveal.totem as u32,
+        );
+
+        // The vague hint is shown until the artifact's layer switch turns its layer off and
+        // the one of the exact hint on
+        let (vague_layer, exact_layer) = artifact_totem_hint_layers(i);
+        area.add_layer(b"Randomizer - Vague Totem Hint\0".as_cstr());
+        area.add_layer(b"Randomizer - Exact Totem Hint\0".as_cstr());
+        area.layer_flags.flags &= !(1 << exact_layer);
+        let scan_dep: structs::Dependency = exact_scan.into();
+        area.add_dependencies(game_resources, exact_layer as usize, iter::once(scan_dep));
+        let strg_dep: structs::Dependency = exact_strg.into();
+        area.add_dependencies(game_resources, exact_layer as usize, iter::once(strg_dep));
+
+        // The objects with the totem's scan are moved to the vague layer, and copied to the
+        // exact one with the new scan
+        let mut moved_ids = vec![];
+        for layer in area.mrea().scly_section().layers.iter() {
+            for obj in layer.objects.iter() {
+                if obj
+                    .property_data
+                    .get_asset_ids()
+                    .contains(&(scan, FourCC::from_bytes(b"SCAN")))
+                {
+                    moved_ids.push(obj.instance_id);
+                }
+            }
+        }
+        if moved_ids.is_empty() {
+            Err(format!(
+                "Failed to find the scan of the {} totem in Artifact Temple",
+                reveal.artifact.name()
+            ))?;
+        }
+        let new_ids: Vec<(u32, u32, u32)> = moved_ids
+            .iter()
+            .map(|id| {
+                (
+                    *id,
+                    area.new_object_id_from_layer_id(vague_layer as usize),
+                    area.new_object_id_from_layer_id(exact_layer as usize),
+                )
+            })
+            .collect();
+
+        let layers = area.mrea().scly_section_mut().layers.as_mut_vec();
+        for (old_id, vague_id, exact_id) in new_ids.iter().copied() {
+            let mut obj = None;
+            for layer in layers.iter_mut() {
+                let objects = layer.objects.as_mut_vec();
+                if let Some(idx) = objects.iter().position(|obj| obj.instance_id == old_id) {
+                    obj = Some(objects.remove(idx));
+                    break;
+                }
+            }
+            let mut vague_obj = obj.unwrap();
+            vague_obj.instance_id = vague_id;
+
+            let mut exact_obj = vague_obj.clone();
+            exact_obj.instance_id = exact_id;
+            if let Some(poi) = exact_obj.property_data.as_point_of_interest_mut() {
+                poi.scan_param.scan = exact_scan;
+            } else if let Some(actor) = exact_obj.property_data.as_actor_mut() {
+                actor.actor_params.scan_params.scan = exact_scan;
+            } else {
+                Err(format!(
+                    "Unexpected object type 0x{:X} with the scan of the {} totem",
+                    exact_obj.property_data.object_type(),
+                    reveal.artifact.name()
+                ))?;
+            }
+
+            layers[vague_layer as usize]
+                .objects
+                .as_mut_vec()
+                .push(vague_obj);
+            layers[exact_layer as usize]
+                .objects
+                .as_mut_vec()
+                .push(exact_obj);
+        }
+
+        // Whatever activated or deactivated the moved objects now does it to both copies, only
+        // the one in the enabled layer is loaded
+        for layer in layers.iter_mut() {
+            for obj in layer.objects.as_mut_vec().iter_mut() {
+                let connections = obj.connections.as_mut_vec();
+                let mut retargeted = Vec::with_capacity(connections.len());
+                for conn in connections.drain(..) {
+                    match new_ids.iter().find(|(old_id, _, _)| {
+                        old_id & 0x00FFFFFF == conn.target_object_id & 0x00FFFFFF
+                    }) {
+                        Some((_, vague_id, exact_id)) => {
+                            for target_object_id in [*vague_id, *exact_id] {
+                                retargeted.push(structs::Connection {
+                                    target_object_id,
+                                    ..conn
+                                });
+                            }
+                        }
+                        None => retargeted.push(conn),
+                    }
+                }
+                *connections = retargeted;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn patch_artifact_hint_availability(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
@@ -6791,57 +8998,6 @@ fn patch_research_lab_aether_exploding_wall(
     Ok(())
 }
 
-fn patch_research_lab_aether_exploding_wall_2(
-    _ps: &mut PatcherState,
-    area: &mut mlvl_wrapper::MlvlArea,
-) -> Result<(), String> {
-    let scly = area.mrea().scly_section_mut();
-    let layer = &mut scly.layers.as_mut_vec()[1];
-
-    // break wall via trigger in lower area instead of relying on gameplay
-    let trigger = layer
-        .objects
-        .iter_mut()
-        .find(|obj| obj.instance_id & 0x00FFFFFF == 0x00330219)
-        .unwrap();
-    trigger.connections.as_mut_vec().push(structs::Connection {
-        state: structs::ConnectionState::ENTERED,
-        message: structs::ConnectionMsg::RESET_AND_START,
-        target_object_id: 0x0033005D, // Timer to break wall
-    });
-
-    trigger.connections.as_mut_vec().push(structs::Connection {
-        state: structs::ConnectionState::ENTERED,
-        message: structs::ConnectionMsg::DEACTIVATE,
-        target_object_id: 0x0033007C, // Edward
-    });
-
-    Ok(())
-}
-
-fn patch_observatory_2nd_pass_solvablility(
-    _ps: &mut PatcherState,
-    area: &mut mlvl_wrapper::MlvlArea,
-) -> Result<(), String> {
-    let scly = area.mrea().scly_section_mut();
-    let layer = &mut scly.layers.as_mut_vec()[2];
-
-    let iter = layer
-        .objects
-        .as_mut_vec()
-        .iter_mut()
-        .filter(|obj| obj.instance_id == 0x81E0460 || obj.instance_id == 0x81E0461);
-    for obj in iter {
-        obj.connections.as_mut_vec().push(structs::Connection {
-            state: structs::ConnectionState::DEATH_RATTLE,
-            message: structs::ConnectionMsg::INCREMENT,
-            target_object_id: 0x1E02EA, // Counter - dead pirates active panel
-        });
-    }
-
-    Ok(())
-}
-
 fn patch_observatory_1st_pass_softlock(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
@@ -7390,6 +9546,30 @@ fn patch_cutscene_force_phazon_suit(
     Ok(())
 }
 
+// The suit normally comes from a model-less pickup which the end of Omega Pirate's death cutscene
+// activates, on top of the item of the room. Removing it leaves the Phazon Suit to wherever the
+// layout placed it, where it's awarded like any other suit.
+fn patch_remove_scripted_phazon_suit(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+) -> Result<(), String> {
+    const SCRIPTED_PHAZON_SUIT: u32 = 0x001A04C5;
+
+    let scly = area.mrea().scly_section_mut();
+    let layers = &mut scly.layers.as_mut_vec();
+    for layer in layers.iter_mut() {
+        let objects = layer.objects.as_mut_vec();
+        objects.retain(|obj| obj.instance_id & 0x00FFFFFF != SCRIPTED_PHAZON_SUIT);
+        for obj in objects.iter_mut() {
+            obj.connections
+                .as_mut_vec()
+                .retain(|conn| conn.target_object_id & 0x00FFFFFF != SCRIPTED_PHAZON_SUIT);
+        }
+    }
+
+    Ok(())
+}
+
 // for some reason this function is vitial to everything working
 // it must get called every time we patch
 fn patch_remove_otrs(
@@ -7520,8 +9700,10 @@ fn patch_move_objects(
 
     let scly = area.mrea().scly_section_mut();
 
-    // Move objects
-    for (obj_id, layer_id) in layer_objs.iter() {
+    // Move objects, sorted so that they always end up in the same order
+    let mut layer_objs: Vec<_> = layer_objs.iter().collect();
+    layer_objs.sort_by_key(|(obj_id, _)| **obj_id);
+    for (obj_id, layer_id) in layer_objs {
         let obj_id = obj_id & 0x00FFFFFF;
         let layer_id = *layer_id as usize;
 
@@ -8110,67 +10292,6 @@ fn patch_geothermal_core_destructible_rock_pal(
     Ok(())
 }
 
-fn patch_ore_processing_door_lock_0_02(
-    _ps: &mut PatcherState,
-    area: &mut mlvl_wrapper::MlvlArea,
-) -> Result<(), String> {
-    let scly = area.mrea().scly_section_mut();
-    let layer = &mut scly.layers.as_mut_vec()[0];
-
-    let actor_door_lock_obj_id = 0x6036A;
-    let pb_inv_check_timer_obj_id = 0x6036C;
-    let pb_inv_check_spec_func_obj_id = 0x60368;
-
-    layer.objects.as_mut_vec().retain(|obj| {
-        obj.instance_id != actor_door_lock_obj_id
-            && obj.instance_id != pb_inv_check_timer_obj_id
-            && obj.instance_id != pb_inv_check_spec_func_obj_id
-    });
-
-    Ok(())
-}
-
-fn patch_ore_processing_destructible_rock_pal(
-    _ps: &mut PatcherState,
-    area: &mut mlvl_wrapper::MlvlArea,
-) -> Result<(), String> {
-    let scly = area.mrea().scly_section_mut();
-    let layer = &mut scly.layers.as_mut_vec()[0];
-
-    let platform_obj_id = 0x60372;
-    let scan_target_platform_obj_id = 0x60378;
-    let actor_blocker_collision_id = 0x60379;
-
-    let platform_obj = layer
-        .objects
-        .as_mut_vec()
-        .iter_mut()
-        .find(|obj| obj.instance_id == platform_obj_id)
-        .and_then(|obj| obj.property_data.as_platform_mut())
-        .unwrap();
-    platform_obj.active = 0;
-
-    let scan_target_platform_obj = layer
-        .objects
-        .as_mut_vec()
-        .iter_mut()
-        .find(|obj| obj.instance_id == scan_target_platform_obj_id)
-        .and_then(|obj| obj.property_data.as_point_of_interest_mut())
-        .unwrap();
-    scan_target_platform_obj.active = 0;
-
-    let actor_blocker_collision_obj = layer
-        .objects
-        .as_mut_vec()
-        .iter_mut()
-        .find(|obj| obj.instance_id == actor_blocker_collision_id)
-        .and_then(|obj| obj.property_data.as_actor_mut())
-        .unwrap();
-    actor_blocker_collision_obj.active = 0;
-
-    Ok(())
-}
-
 fn patch_add_pb_refill(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
@@ -8576,12 +10697,6 @@ fn patch_remove_cutscenes(
                 // kill puffer trigger
                 // the puffers will increment the counter instead of me, the kill trigger
                 obj.connections.as_mut_vec().retain(|_conn| false);
-            } else if obj_id == 0x001B065F {
-                // central dynamo collision blocker
-                // the power bomb rock collision should not extend beyond the door
-                let actor = obj.property_data.as_actor_mut().unwrap();
-                actor.hitbox[1] = 0.4;
-                actor.position[1] -= 0.8;
             } else if obj_id == 0x0002023E {
                 // main plaza turn crane left relay
                 // snap the crane immediately so fast players don't fall through the intangible animation
@@ -8681,25 +10796,6 @@ fn patch_remove_cutscenes(
     Ok(())
 }
 
-/**
- * Patch is actually for QAA
- *
- */
-fn patch_fix_central_dynamo_crash(
-    _ps: &mut PatcherState,
-    area: &mut mlvl_wrapper::MlvlArea,
-) -> Result<(), String> {
-    let scly = area.mrea().scly_section_mut();
-    for layer in scly.layers.as_mut_vec() {
-        layer
-            .objects
-            .as_mut_vec()
-            .retain(|obj| ![0x45].contains(&obj.property_data.object_type()));
-    }
-
-    Ok(())
-}
-
 fn patch_main_quarry_door_lock_pal(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
@@ -8721,26 +10817,6 @@ fn patch_main_quarry_door_lock_pal(
     Ok(())
 }
 
-fn patch_mines_security_station_soft_lock(
-    _ps: &mut PatcherState,
-    area: &mut mlvl_wrapper::MlvlArea,
-) -> Result<(), String> {
-    let scly = area.mrea().scly_section_mut();
-    for layer in scly.layers.as_mut_vec().iter_mut() {
-        for obj in layer.objects.as_mut_vec().iter_mut() {
-            if (obj.instance_id & 0x00FFFFFF) != 0x0007033F {
-                continue;
-            }
-            let trigger = obj.property_data.as_trigger_mut().unwrap();
-            trigger.scale[0] = 50.0;
-            trigger.scale[1] = 100.0;
-            trigger.scale[2] = 40.0;
-        }
-    }
-
-    Ok(())
-}
-
 fn patch_research_core_access_soft_lock(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
@@ -8789,39 +10865,6 @@ fn patch_research_core_access_soft_lock(
     Ok(())
 }
 
-fn patch_hive_totem_softlock(
-    _ps: &mut PatcherState,
-    area: &mut mlvl_wrapper::MlvlArea,
-) -> Result<(), String> {
-    let scly = area.mrea().scly_section_mut();
-    let layer = &mut scly.layers.as_mut_vec()[0];
-    let trigger = layer
-        .objects
-        .as_mut_vec()
-        .iter_mut()
-        .find(|obj| obj.instance_id & 0x00FFFFFF == 0x002400CA)
-        .unwrap();
-    trigger.property_data.as_trigger_mut().unwrap().scale[1] = 60.0;
-
-    Ok(())
-}
-
-fn patch_gravity_chamber_stalactite_grapple_point(
-    _ps: &mut PatcherState,
-    area: &mut mlvl_wrapper::MlvlArea,
-) -> Result<(), String> {
-    let scly = area.mrea().scly_section_mut();
-    let layer = &mut scly.layers.as_mut_vec()[0];
-
-    // Remove the object that turns off the stalactites layer
-    layer
-        .objects
-        .as_mut_vec()
-        .retain(|obj| obj.instance_id != 3473722);
-
-    Ok(())
-}
-
 fn patch_heat_damage_per_sec(patcher: &mut PrimePatcher<'_, '_>, heat_damage_per_sec: f32) {
     const HEATED_ROOMS: &[ResourceInfo] = &[
         resource_info!("06_grapplegallery.MREA"),
@@ -8887,6 +10930,22 @@ fn patch_poison_damage_per_sec(patcher: &mut PrimePatcher<'_, '_>, poison_damage
     }
 }
 
+fn patch_remove_lava_surface_damage(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+) -> Result<(), String> {
+    let scly = area.mrea().scly_section_mut();
+    for layer in scly.layers.as_mut_vec() {
+        layer
+            .objects
+            .iter_mut()
+            .filter_map(|obj| obj.property_data.as_water_mut())
+            .filter(|water| water.damage_info.weapon_type == 11) // Is Lava
+            .for_each(|water| water.damage_info.damage = 0.0);
+    }
+    Ok(())
+}
+
 fn patch_save_station_for_warp_to_start<'r>(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
@@ -9255,9 +11314,14 @@ fn patch_credits(
 
         for pickup_type in PICKUPS_TO_PRINT {
             let room_name = {
+                // Sorted so that a duplicated item always lists the same room
                 let mut _room_name = String::new();
-                for (_, level) in level_data.iter() {
-                    for (room_name, room) in level.rooms.iter() {
+                let mut levels: Vec<_> = level_data.iter().collect();
+                levels.sort_by(|a, b| a.0.cmp(b.0));
+                for (_, level) in levels {
+                    let mut rooms: Vec<_> = level.rooms.iter().collect();
+                    rooms.sort_by(|a, b| a.0.cmp(b.0));
+                    for (room_name, room) in rooms {
                         if room.pickups.is_none() {
                             continue;
                         };
@@ -9467,7 +11531,34 @@ fn patch_starting_pickups<'r>(
 }
 
 include!("../compile_to_ppc/patches_config.rs");
-fn create_rel_config_file(spawn_room: SpawnRoomData, quickplay: bool) -> Vec<u8> {
+fn create_rel_config_file(
+    spawn_room: SpawnRoomData,
+    quickplay: bool,
+    timer_config: &SplitTimerConfig,
+    ghost_recording_address: Option<u32>,
+    ghost_memory_card_dump: bool,
+    tracker_address: Option<u32>,
+    integrity_regions: [[u32; 3]; 8],
+    progressive_chains: &[Vec<PickupType>],
+    blast_shield_costs: bool,
+    pickup_counter: Option<&PickupCounterConfig>,
+    starting_presets: &[StartingPreset],
+    debug_features: bool,
+    version: Version,
+) -> Vec<u8> {
+    let mut chains = [[0xFF; 8]; 8];
+    for (chain, items) in chains.iter_mut().zip(progressive_chains) {
+        for (item, pickup_type) in chain.iter_mut().zip(items) {
+            *item = pickup_type.kind() as u8;
+        }
+    }
+
+    let mut presets = [[0xFFFFFFFF; 2]; 3];
+    for preset in starting_presets {
+        let room = SpawnRoomData::from_str(&preset.starting_room);
+        presets[preset.file as usize] = [room.mlvl, room.mrea];
+    }
+
     let config = RelConfig {
         quickplay_mlvl: if quickplay {
             spawn_room.mlvl
@@ -9479,12 +11570,251 @@ fn create_rel_config_file(spawn_room: SpawnRoomData, quickplay: bool) -> Vec<u8>
         } else {
             0xFFFFFFFF
         },
+        show_igt: timer_config.show_igt.unwrap_or(false),
+        show_room_timer: timer_config.show_room_timer.unwrap_or(false),
+        splits_address: timer_config.splits_address.unwrap_or(0),
+        ghost_address: ghost_recording_address.unwrap_or(0),
+        ghost_card_functions: if ghost_memory_card_dump {
+            card_function_addresses(version)
+        } else {
+            [0; 7]
+        },
+        tracker_address: tracker_address.unwrap_or(0),
+        integrity_regions,
+        progressive_hook_address: if progressive_chains.is_empty() {
+            0
+        } else {
+            rel_hook_address(version, PROGRESSIVE_ITEM_HOOK)
+        },
+        progressive_chains: chains,
+        ammo_cost_hook_address: if blast_shield_costs {
+            rel_hook_address(version, AMMO_COST_HOOK)
+        } else {
+            0
+        },
+        pickup_counter_hook_address: if pickup_counter.is_some() {
+            rel_hook_address(version, PICKUP_COUNTER_HOOK)
+        } else {
+            0
+        },
+        pickup_counter_total: pickup_counter
+            .map(|counter| counter.total.unwrap_or(100).max(1))
+            .unwrap_or(0),
+        pickup_counter_percentage: pickup_counter
+            .and_then(|counter| counter.percentage)
+            .unwrap_or(false),
+        starting_preset_hook_address: if starting_presets.is_empty() {
+            0
+        } else {
+            rel_hook_address(version, STARTING_PRESET_HOOK)
+        },
+        starting_mlvl: spawn_room.mlvl,
+        starting_presets: presets,
+        debug_hook_address: if debug_features {
+            rel_hook_address(version, DEBUG_FEATURES_HOOK)
+        } else {
+            0
+        },
     };
     let mut buf = vec![0; mem::size_of::<RelConfig>()];
     ssmarshal::serialize(&mut buf, &config).unwrap();
     buf
 }
 
+// The memory card functions the rel dumps the ghost with, in the order of
+// CardFunctions::from_addresses. They're only known for some versions, 0s for the others.
+fn card_function_addresses(version: Version) -> [u32; 7] {
+    let dol_version = match version.dol_version() {
+        Some(dol_version) => dol_version,
+        None => return [0; 7],
+    };
+    let addresses = [
+        mp1_symbol!("CARDMountAsync").addr(dol_version),
+        mp1_symbol!("CARDUnmount").addr(dol_version),
+        mp1_symbol!("CARDOpen").addr(dol_version),
+        mp1_symbol!("CARDCreateAsync").addr(dol_version),
+        mp1_symbol!("CARDWriteAsync").addr(dol_version),
+        mp1_symbol!("CARDClose").addr(dol_version),
+        mp1_symbol!("CARDGetResultCode").addr(dol_version),
+    ];
+    let mut res = [0; 7];
+    for (res, addr) in res.iter_mut().zip(addresses) {
+        match addr {
+            Some(addr) => *res = addr,
+            None => return [0; 7],
+        }
+    }
+    res
+}
+
+macro_rules! symbol_addr {
+    ($sym:tt, $version:expr) => {{
+        let version: Version = $version;
+        let dol_version = version
+            .dol_version()
+            .unwrap_or_else(|| panic!("There is no symbol table for version {}", version));
+        mp1_symbol!($sym)
+            .addr(dol_version)
+            .unwrap_or_else(|| panic!("Symbol {} unknown for version {}", $sym, version))
+    }};
+}
+
+fn rel_loader_bytes_and_map(version: Version) -> (&'static [u8], &'static str) {
+    match version {
+        Version::NtscU0_00 => (rel_files::REL_LOADER_100, rel_files::REL_LOADER_100_MAP),
+        Version::NtscU0_01 => (rel_files::REL_LOADER_101, rel_files::REL_LOADER_101_MAP),
+        Version::NtscU0_02 => (rel_files::REL_LOADER_102, rel_files::REL_LOADER_102_MAP),
+        Version::NtscK => (rel_files::REL_LOADER_KOR, rel_files::REL_LOADER_KOR_MAP),
+        Version::NtscJ => (rel_files::REL_LOADER_JPN, rel_files::REL_LOADER_JPN_MAP),
+        Version::Pal => (rel_files::REL_LOADER_PAL, rel_files::REL_LOADER_PAL_MAP),
+        Version::NtscUTrilogy => unreachable!(),
+        Version::NtscJTrilogy => unreachable!(),
+        Version::PalTrilogy => unreachable!(),
+    }
+}
+
+// The function pointers the dol's hooks call through are placed right after the rel loader, so
+// that their addresses are known before the dol is patched. The rel sets them once it's loaded
+const PROGRESSIVE_ITEM_HOOK: u32 = 0;
+const AMMO_COST_HOOK: u32 = 1;
+const PICKUP_COUNTER_HOOK: u32 = 2;
+const STARTING_PRESET_HOOK: u32 = 3;
+const DEBUG_FEATURES_HOOK: u32 = 4;
+const REL_HOOK_COUNT: u32 = 5;
+
+fn rel_hook_address(version: Version, hook: u32) -> u32 {
+    let (rel_loader_bytes, _) = rel_loader_bytes_and_map(version);
+    symbol_addr!("OSArenaHi", version) + ((rel_loader_bytes.len() as u32 + 3) & !3) + hook * 4
+}
+
+// How much of each critical function the rel hashes at boot to check it wasn't tampered with
+const INTEGRITY_REGION_LEN: u32 = 0x80;
+
+// The bytes of the dol loaded at `addr`
+fn dol_bytes_at(dol: &[u8], addr: u32, len: u32) -> Option<&[u8]> {
+    let header = |i: usize| {
+        dol.get(i * 4..i * 4 + 4)
+            .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+    };
+    (0..18).find_map(|i| {
+        let (offset, load_addr, size) = (header(i)?, header(18 + i)?, header(36 + i)?);
+        if addr < load_addr || addr + len > load_addr + size {
+            return None;
+        }
+        let start = (offset + addr - load_addr) as usize;
+        dol.get(start..start + len as usize)
+    })
+}
+
+// The address and bytes of the regions of the patched dol the rel checks at boot. These are the
+// functions cheats usually go for, which neither the rel's own patches (see patch_fn in
+// compile_to_ppc/rel_patches) nor Nintendont's patch in memory.
+fn integrity_region_bytes(
+    gc_disc: &mut structs::GcDisc,
+    version: Version,
+) -> Result<Vec<(u32, Vec<u8>)>, String> {
+    let addrs = [
+        symbol_addr!(
+            "IncrPickUp__12CPlayerStateFQ212CPlayerState9EItemTypei",
+            version
+        ),
+        symbol_addr!(
+            "HasPowerUp__12CPlayerStateCFQ212CPlayerState9EItemType",
+            version
+        ),
+        symbol_addr!(
+            "ApplyLocalDamage__13CStateManagerFRC9CVector3fRC9CVector3fR6CActorfRC11CWeaponMode",
+            version
+        ),
+        symbol_addr!("UpdatePhazonDamage__7CPlayerFfR13CStateManager", version),
+        symbol_addr!("BombJump__7CPlayerFRC9CVector3fR13CStateManager", version),
+        symbol_addr!(
+            "SidewaysDashAllowed__7CPlayerCFffRC11CFinalInputR13CStateManager",
+            version
+        ),
+        symbol_addr!("CPlayerState_PowerUpMaxValues", version),
+    ];
+
+    let file = gc_disc
+        .file_system_root
+        .dir_files_iter_mut()
+        .find(|(name, _)| &name[..] == b"default.dol")
+        .and_then(|(_, entry)| entry.file_mut())
+        .ok_or("Failed to find default.dol")?;
+    let mut dol = vec![];
+    match file {
+        structs::FstEntryFile::ExternalFile(f) => {
+            f.with_read(&mut |r| io::copy(r, &mut dol))
+                .map_err(|e| format!("Failed to read the patched dol: {}", e))?;
+        }
+        structs::FstEntryFile::Unknown(reader) => dol.extend_from_slice(&reader[..]),
+        _ => Err("default.dol isn't a dol")?,
+    }
+
+    addrs
+        .into_iter()
+        .map(|addr| {
+            dol_bytes_at(&dol, addr, INTEGRITY_REGION_LEN)
+                .map(|bytes| (addr, bytes.to_vec()))
+                .ok_or_else(|| format!("0x{:08X} isn't in the dol", addr))
+        })
+        .collect()
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
+
+// The regions the rel checks at boot, as (address, length, CRC32)
+fn integrity_regions(
+    gc_disc: &mut structs::GcDisc,
+    version: Version,
+) -> Result<[[u32; 3]; 8], String> {
+    let mut regions = [[0; 3]; 8];
+    for (region, (addr, bytes)) in regions
+        .iter_mut()
+        .zip(integrity_region_bytes(gc_disc, version)?)
+    {
+        *region = [addr, INTEGRITY_REGION_LEN, crc32(&bytes)];
+    }
+    Ok(regions)
+}
+
+// The CRC32s of the config, the checked code and the rel which the rel publishes in the tracker
+// region. Unlike the regions in rel_config.bin, they aren't on the disc: organizers compare them
+// with those printed when the seed was patched.
+fn integrity_signature(
+    gc_disc: &mut structs::GcDisc,
+    version: Version,
+    config_json: &[u8],
+    rel: &[u8],
+) -> Result<[u32; 3], String> {
+    let mut code = flate2::Crc::new();
+    for (_, bytes) in integrity_region_bytes(gc_disc, version)? {
+        code.update(&bytes);
+    }
+    Ok([crc32(config_json), code.sum(), crc32(rel)])
+}
+
+fn pickup_counter_config(config: &PatchConfig) -> Option<&PickupCounterConfig> {
+    config
+        .cosmetics
+        .hud
+        .as_ref()
+        .and_then(|hud| hud.pickup_counter.as_ref())
+}
+
+fn has_blast_shield_costs(config: &PatchConfig) -> bool {
+    config
+        .level_data
+        .values()
+        .flat_map(|level| level.rooms.values())
+        .flat_map(|room| room.doors.iter().flat_map(|doors| doors.values()))
+        .any(|door| door.blast_shield_cost.is_some())
+}
+
 #[allow(clippy::too_many_arguments)]
 fn patch_dol(
     file: &mut structs::FstEntryFile,
@@ -9498,7 +11828,15 @@ fn patch_dol(
     enable_ice_traps: bool,
     uuid: Option<[u8; 16]>,
     shoot_in_grapple: bool,
+    starting_scans: &[u32],
 ) -> Result<(), String> {
+    let suit_color_remaps = config
+        .cosmetics
+        .suits
+        .as_ref()
+        .map(|suits| suits.color_remaps())
+        .unwrap_or_default();
+
     if version == Version::NtscUTrilogy
         || version == Version::NtscJTrilogy
         || version == Version::PalTrilogy
@@ -9506,24 +11844,6 @@ fn patch_dol(
         return Ok(());
     }
 
-    macro_rules! symbol_addr {
-        ($sym:tt, $version:expr) => {{
-            let s = mp1_symbol!($sym);
-            match &$version {
-                Version::NtscU0_00 => s.addr_0_00,
-                Version::NtscU0_01 => s.addr_0_01,
-                Version::NtscU0_02 => s.addr_0_02,
-                Version::NtscK => s.addr_kor,
-                Version::NtscJ => s.addr_jpn,
-                Version::Pal => s.addr_pal,
-                Version::NtscUTrilogy => unreachable!(),
-                Version::NtscJTrilogy => unreachable!(),
-                Version::PalTrilogy => unreachable!(),
-            }
-            .unwrap_or_else(|| panic!("Symbol {} unknown for version {}", $sym, $version))
-        }};
-    }
-
     // new text section for code caves or rel loader
     // skip 0x103c0 bytes after toc register
     let new_text_section_start = symbol_addr!("OSArenaHi", version);
@@ -9541,15 +11861,7 @@ fn patch_dol(
         let uuid = uuid.unwrap();
 
         // e.g. "!#$MetroidBuildInfo!#$ Build v1.088 10/29/2002 2:21:25"
-        let build_info_address: u32 = match version {
-            Version::NtscU0_00 => 0x803cc588,
-            Version::NtscU0_01 => 0x803cc768,
-            Version::NtscU0_02 => 0x803cd648,
-            Version::NtscK => 0x803cc688,
-            Version::NtscJ => 0x803b86cc,
-            Version::Pal => 0x803b6924,
-            _ => panic!("This version of the game does not support etching a UUID into the dol"),
-        };
+        let build_info_address = symbol_addr!("aMetroidBuildInfo", version);
 
         // Leave the start alone for easier pattern matching
         let build_info_address = build_info_address + "!#$Met".len() as u32;
@@ -9576,13 +11888,8 @@ fn patch_dol(
     }
 
     if config.difficulty_behavior != DifficultyBehavior::Either {
-        let only_one_option_jump_offset = if version == Version::Pal || version == Version::NtscJ {
-            0x210
-        } else {
-            0x1f8
-        };
         let only_one_option_patch = ppcasm!(symbol_addr!("ActivateNewGamePopup__19SNewFileSelectFrameFv", version) + 0x110, {
-            b   { symbol_addr!("ActivateNewGamePopup__19SNewFileSelectFrameFv", version) + only_one_option_jump_offset };
+            b   { symbol_addr!("ActivateNewGamePopupSkipTarget", version) };
         });
         dol_patcher.ppcasm_patch(&only_one_option_patch)?;
     }
@@ -9626,15 +11933,10 @@ fn patch_dol(
 
         // Never hide the escape sequence timer
         // NTSC-U (0x80066e78 - 0x80066380)
-        let patch_offset = if version == Version::Pal || version == Version::NtscJ {
-            0xb84
-        } else {
-            0xaf8
-        };
-        let remove_escape_sequence_rumble_patch = ppcasm!(
-            symbol_addr!("Update__9CSamusHudFfRC13CStateManagerUibb", version) + patch_offset,
-            { nop }
-        );
+        let remove_escape_sequence_rumble_patch =
+            ppcasm!(symbol_addr!("SamusHudHideEscapeTimerBranch", version), {
+                nop;
+            });
         dol_patcher.ppcasm_patch(&remove_escape_sequence_rumble_patch)?;
     }
     // byte pattern to find GetIsFusionEnabled__12CPlayerStateFv
@@ -9676,8 +11978,8 @@ fn patch_dol(
             symbol_addr!("BallGlowColors", version),
             colors.clone().into(),
         )?;
-    } else if config.suit_colors.is_some() {
-        let suit_colors = config.suit_colors.as_ref().unwrap();
+    } else if config.suit_colors.is_some() || suit_color_remaps.iter().any(Option::is_some) {
+        let suit_colors = config.suit_colors.clone().unwrap_or_default();
         let mut colors: Vec<Vec<u8>> = vec![
             vec![
                 0xc2, 0x7e, 0x10, 0x66, 0xc4, 0xff, 0x60, 0xff, 0x90, 0x33, 0x33, 0xff, 0xff, 0x80,
@@ -9723,20 +12025,33 @@ fn patch_dol(
                     0
                 };
 
-                let angle = angle % 360;
-                if angle == 0 {
-                    continue;
-                }
-                let matrix = huerotate_matrix(angle as f32);
-
                 let r_idx = j * 3;
                 let g_idx = r_idx + 1;
                 let b_idx = r_idx + 2;
 
-                let new_rgb = huerotate_color(matrix, color[r_idx], color[g_idx], color[b_idx]);
-                color[r_idx] = new_rgb[0];
-                color[g_idx] = new_rgb[1];
-                color[b_idx] = new_rgb[2];
+                let angle = angle % 360;
+                if angle != 0 {
+                    let matrix = huerotate_matrix(angle as f32);
+                    let new_rgb = huerotate_color(matrix, color[r_idx], color[g_idx], color[b_idx]);
+                    color[r_idx] = new_rgb[0];
+                    color[g_idx] = new_rgb[1];
+                    color[b_idx] = new_rgb[2];
+                }
+
+                // Same suit order as above: power, varia (x2), gravity, phazon
+                let remap = match j {
+                    0 => suit_color_remaps[0],
+                    1 | 2 => suit_color_remaps[1],
+                    3 => suit_color_remaps[2],
+                    4 => suit_color_remaps[3],
+                    _ => None,
+                };
+                if let Some(remap) = remap {
+                    let new_rgb = remap_color(&remap, color[r_idx], color[g_idx], color[b_idx]);
+                    color[r_idx] = new_rgb[0];
+                    color[g_idx] = new_rgb[1];
+                    color[b_idx] = new_rgb[2];
+                }
             }
         }
 
@@ -9853,117 +12168,58 @@ fn patch_dol(
             // }
 
             // spawn with weapon holstered instead of drawn
-            let patch_offset = if version == Version::Pal || version == Version::NtscJ {
-                0x3bc
-            } else {
-                0x434
-            };
-            let default_visor_patch = ppcasm!(symbol_addr!("__ct__7CPlayerF9TUniqueIdRC12CTransform4fRC6CAABoxUi9CVector3fffffRC13CMaterialList", version) + patch_offset, {
+            let default_visor_patch = ppcasm!(symbol_addr!("PlayerCtorSetGunHolsterState", version), {
                     li      r0, 0; // r0 = holstered
             });
             dol_patcher.ppcasm_patch(&default_visor_patch)?;
 
             // stop gun from being drawn after unmorphing
-            let (patch_offset, patch_offset2) =
-                if version == Version::Pal || version == Version::NtscJ {
-                    (0x79c, 0x7a8)
-                } else {
-                    (0x7c8, 0x7d4)
-                };
-            let default_visor_patch = ppcasm!(
-                symbol_addr!(
-                    "TransitionFromMorphBallState__7CPlayerFR13CStateManager",
-                    version
-                ) + patch_offset,
-                {
+            let default_visor_patch =
+                ppcasm!(symbol_addr!("TransitionFromMorphBallDrawGun1", version), {
                     nop;
-                }
-            );
+                });
             dol_patcher.ppcasm_patch(&default_visor_patch)?;
-            let default_visor_patch = ppcasm!(
-                symbol_addr!(
-                    "TransitionFromMorphBallState__7CPlayerFR13CStateManager",
-                    version
-                ) + patch_offset2,
-                {
+            let default_visor_patch =
+                ppcasm!(symbol_addr!("TransitionFromMorphBallDrawGun2", version), {
                     nop;
-                }
-            );
+                });
             dol_patcher.ppcasm_patch(&default_visor_patch)?;
 
             // stop gun from being drawn after unmorphing
-            let (patch_offset, patch_offset2) =
-                if version == Version::Pal || version == Version::NtscJ {
-                    (0x14c, 0x158)
-                } else {
-                    (0x1a4, 0x1b0)
-                };
-            let default_visor_patch = ppcasm!(
-                symbol_addr!("LeaveMorphBallState__7CPlayerFR13CStateManager", version)
-                    + patch_offset,
-                {
-                    nop;
-                }
-            );
+            let default_visor_patch = ppcasm!(symbol_addr!("LeaveMorphBallDrawGun1", version), {
+                nop;
+            });
             dol_patcher.ppcasm_patch(&default_visor_patch)?;
-            let default_visor_patch = ppcasm!(
-                symbol_addr!("LeaveMorphBallState__7CPlayerFR13CStateManager", version)
-                    + patch_offset2,
-                {
-                    nop;
-                }
-            );
+            let default_visor_patch = ppcasm!(symbol_addr!("LeaveMorphBallDrawGun2", version), {
+                nop;
+            });
             dol_patcher.ppcasm_patch(&default_visor_patch)?;
 
             // do not change visors after unmorphing
-            let patch_offset = if version == Version::Pal || version == Version::NtscJ {
-                0xb0
-            } else {
-                0x108
-            };
-            let default_visor_patch = ppcasm!(
-                symbol_addr!("EnterMorphBallState__7CPlayerFR13CStateManager", version)
-                    + patch_offset,
-                {
+            let default_visor_patch =
+                ppcasm!(symbol_addr!("EnterMorphBallChangeVisor", version), {
                     nop;
                     nop;
                     nop;
-                }
-            );
+                });
             dol_patcher.ppcasm_patch(&default_visor_patch)?;
         } else {
-            let (patch_offset, patch_offset2) =
-                if version == Version::Pal || version == Version::NtscJ {
-                    (0xdc, 0xf0)
-                } else {
-                    (0xe8, 0xfc)
-                };
-
             // When pressing a or y in in scan visor, check for and switch to default visor instead of combat
-            let default_visor_patch = ppcasm!(symbol_addr!("UpdateVisorState__7CPlayerFRC11CFinalInputfR13CStateManager", version) + patch_offset, {
+            let default_visor_patch = ppcasm!(symbol_addr!("UpdateVisorStateCheckCombatVisor", version), {
                     li r4, visor_item;
             });
             dol_patcher.ppcasm_patch(&default_visor_patch)?;
-            let default_visor_patch = ppcasm!(symbol_addr!("UpdateVisorState__7CPlayerFRC11CFinalInputfR13CStateManager", version) + patch_offset2, {
+            let default_visor_patch = ppcasm!(symbol_addr!("UpdateVisorStateSwitchToCombatVisor", version), {
                     li r4, visor;
             });
             dol_patcher.ppcasm_patch(&default_visor_patch)?;
 
-            let patch_offset = if version == Version::Pal || version == Version::NtscJ {
-                0xb0
-            } else {
-                0x108
-            };
-
-            let default_visor_patch = ppcasm!(
-                symbol_addr!("EnterMorphBallState__7CPlayerFR13CStateManager", version)
-                    + patch_offset,
-                {
+            let default_visor_patch =
+                ppcasm!(symbol_addr!("EnterMorphBallChangeVisor", version), {
                     nop;
                     nop;
                     nop;
-                }
-            );
+                });
             dol_patcher.ppcasm_patch(&default_visor_patch)?;
         }
     }
@@ -9992,20 +12248,9 @@ fn patch_dol(
     // (0x8017a998 - 0x8017A668)
     // byte pattern : 40820178 7f83e378 7fc4f378 4b
     if shoot_in_grapple {
-        let shoot_in_grapple_offset = if [Version::NtscJ, Version::Pal].contains(&version) {
-            0x324
-        } else {
-            0x330
-        };
-        let patch = ppcasm!(
-            symbol_addr!(
-                "UpdateGrappleState__7CPlayerFRC11CFinalInputR13CStateManager",
-                version
-            ) + shoot_in_grapple_offset,
-            {
-                nop;
-            }
-        );
+        let patch = ppcasm!(symbol_addr!("UpdateGrappleStateHolsterGun", version), {
+            nop;
+        });
         dol_patcher.ppcasm_patch(&patch)?;
     }
 
@@ -10590,7 +12835,9 @@ fn patch_dol(
         });
         dol_patcher.ppcasm_patch(&linear_phazon_damage_patch)?;
 
-        if config.phazon_damage_modifier == PhazonDamageModifier::Linear {
+        if [PhazonDamageModifier::Linear, PhazonDamageModifier::None]
+            .contains(&config.phazon_damage_modifier)
+        {
             let remove_phazon_damage_delay_offset =
                 if version == Version::Pal && version == Version::NtscJ {
                     0x534
@@ -10610,41 +12857,7 @@ fn patch_dol(
     }
 
     // Add rel loader to the binary
-    let (rel_loader_bytes, rel_loader_map_str) = match version {
-        Version::NtscU0_00 => {
-            let loader_bytes = rel_files::REL_LOADER_100;
-            let map_str = rel_files::REL_LOADER_100_MAP;
-            (loader_bytes, map_str)
-        }
-        Version::NtscU0_01 => {
-            let loader_bytes = rel_files::REL_LOADER_101;
-            let map_str = rel_files::REL_LOADER_101_MAP;
-            (loader_bytes, map_str)
-        }
-        Version::NtscU0_02 => {
-            let loader_bytes = rel_files::REL_LOADER_102;
-            let map_str = rel_files::REL_LOADER_102_MAP;
-            (loader_bytes, map_str)
-        }
-        Version::NtscK => {
-            let loader_bytes = rel_files::REL_LOADER_KOR;
-            let map_str = rel_files::REL_LOADER_KOR_MAP;
-            (loader_bytes, map_str)
-        }
-        Version::NtscJ => {
-            let loader_bytes = rel_files::REL_LOADER_JPN;
-            let map_str = rel_files::REL_LOADER_JPN_MAP;
-            (loader_bytes, map_str)
-        }
-        Version::Pal => {
-            let loader_bytes = rel_files::REL_LOADER_PAL;
-            let map_str = rel_files::REL_LOADER_PAL_MAP;
-            (loader_bytes, map_str)
-        }
-        Version::NtscUTrilogy => unreachable!(),
-        Version::NtscJTrilogy => unreachable!(),
-        Version::PalTrilogy => unreachable!(),
-    };
+    let (rel_loader_bytes, rel_loader_map_str) = rel_loader_bytes_and_map(version);
 
     let mut rel_loader = rel_loader_bytes.to_vec();
     let rel_loader_padding_size = ((rel_loader.len() + 3) & !3) - rel_loader.len();
@@ -10665,6 +12878,271 @@ fn patch_dol(
 
     new_text_section_end += rel_loader_size;
 
+    let rel_hooks_addr = new_text_section_end;
+    assert_eq!(rel_hooks_addr, rel_hook_address(version, 0));
+    new_text_section.extend([0; REL_HOOK_COUNT as usize * 4]);
+    new_text_section_end += REL_HOOK_COUNT * 4;
+
+    if !config.progressive_chains.is_empty() {
+        // Call through a function pointer set by the rel before IncrPickUp runs, so that it can
+        // replace progressive pickups with the next item in their chain
+        let hook_ptr = rel_hook_address(version, PROGRESSIVE_ITEM_HOOK);
+        let hook_addr = new_text_section_end;
+
+        let incr_pickup_addr = symbol_addr!(
+            "IncrPickUp__12CPlayerStateFQ212CPlayerState9EItemTypei",
+            version
+        );
+        let original_instr = dol_patcher.read_original_u32(incr_pickup_addr)?;
+        if original_instr >> 26 == 18 {
+            Err("Cannot relocate a branch out of CPlayerState::IncrPickUp".to_string())?;
+        }
+
+        #[rustfmt::skip]
+        let incr_pickup_patch = ppcasm!(incr_pickup_addr, {
+            b          { hook_addr };
+        });
+        dol_patcher.ppcasm_patch(&incr_pickup_patch)?;
+
+        let progressive_item_hook_patch = ppcasm!(hook_addr, {
+                lis        r12, { hook_ptr }@h;
+                addi       r12, r12, { hook_ptr }@l;
+                lwz        r12, 0(r12);
+                cmpwi      r12, 0;
+                beq        original;
+                stwu       r1, -0x20(r1);
+                mflr       r0;
+                stw        r0, 0x24(r1);
+                stw        r3, 0x8(r1);
+                stw        r5, 0xc(r1);
+                mtctr      r12;
+                bctrl;
+                mr         r4, r3;
+                lwz        r3, 0x8(r1);
+                lwz        r5, 0xc(r1);
+                lwz        r0, 0x24(r1);
+                mtlr       r0;
+                addi       r1, r1, 0x20;
+            original:
+                .long      original_instr;
+                b          { incr_pickup_addr + 4 };
+        });
+
+        new_text_section_end += progressive_item_hook_patch.encoded_bytes().len() as u32;
+        new_text_section.extend(progressive_item_hook_patch.encoded_bytes());
+    }
+
+    if has_blast_shield_costs(config) {
+        // Call through a function pointer set by the rel before ApplyLocalDamage runs, so that it
+        // can take ammo for costed blast shields and replace the damage when it can't be paid
+        let hook_ptr = rel_hook_address(version, AMMO_COST_HOOK);
+        let hook_addr = new_text_section_end;
+
+        let apply_local_damage_addr = symbol_addr!(
+            "ApplyLocalDamage__13CStateManagerFRC9CVector3fRC9CVector3fR6CActorfRC11CWeaponMode",
+            version
+        );
+        let original_instr = dol_patcher.read_original_u32(apply_local_damage_addr)?;
+        if original_instr >> 26 == 18 {
+            Err("Cannot relocate a branch out of CStateManager::ApplyLocalDamage".to_string())?;
+        }
+
+        #[rustfmt::skip]
+        let apply_local_damage_patch = ppcasm!(apply_local_damage_addr, {
+            b          { hook_addr };
+        });
+        dol_patcher.ppcasm_patch(&apply_local_damage_patch)?;
+
+        // The damage is passed in f1, which is also where the new damage is returned
+        let ammo_cost_hook_patch = ppcasm!(hook_addr, {
+                lis        r12, { hook_ptr }@h;
+                addi       r12, r12, { hook_ptr }@l;
+                lwz        r12, 0(r12);
+                cmpwi      r12, 0;
+                beq        original;
+                stwu       r1, -0x20(r1);
+                mflr       r0;
+                stw        r0, 0x24(r1);
+                stw        r3, 0x8(r1);
+                stw        r4, 0xc(r1);
+                stw        r5, 0x10(r1);
+                stw        r6, 0x14(r1);
+                stw        r7, 0x18(r1);
+                mtctr      r12;
+                bctrl;
+                lwz        r3, 0x8(r1);
+                lwz        r4, 0xc(r1);
+                lwz        r5, 0x10(r1);
+                lwz        r6, 0x14(r1);
+                lwz        r7, 0x18(r1);
+                lwz        r0, 0x24(r1);
+                mtlr       r0;
+                addi       r1, r1, 0x20;
+            original:
+                .long      original_instr;
+                b          { apply_local_damage_addr + 4 };
+        });
+
+        new_text_section_end += ammo_cost_hook_patch.encoded_bytes().len() as u32;
+        new_text_section.extend(ammo_cost_hook_patch.encoded_bytes());
+    }
+
+    if pickup_counter_config(config).is_some() {
+        // Call through a function pointer set by the rel at the start of CSamusHud::Update, so
+        // that it can refresh the pickup counter in the base HUD
+        let hook_ptr = rel_hook_address(version, PICKUP_COUNTER_HOOK);
+        let hook_addr = new_text_section_end;
+
+        let samus_hud_update_addr =
+            symbol_addr!("Update__9CSamusHudFfRC13CStateManagerUibb", version);
+        let original_instr = dol_patcher.read_original_u32(samus_hud_update_addr)?;
+        if original_instr >> 26 == 18 {
+            Err("Cannot relocate a branch out of CSamusHud::Update".to_string())?;
+        }
+
+        #[rustfmt::skip]
+        let samus_hud_update_patch = ppcasm!(samus_hud_update_addr, {
+            b          { hook_addr };
+        });
+        dol_patcher.ppcasm_patch(&samus_hud_update_patch)?;
+
+        // The frame time is passed in f1, which has to survive the call as well
+        let pickup_counter_hook_patch = ppcasm!(hook_addr, {
+                lis        r12, { hook_ptr }@h;
+                addi       r12, r12, { hook_ptr }@l;
+                lwz        r12, 0(r12);
+                cmpwi      r12, 0;
+                beq        original;
+                stwu       r1, -0x30(r1);
+                mflr       r0;
+                stw        r0, 0x34(r1);
+                stw        r3, 0x8(r1);
+                stw        r4, 0xc(r1);
+                stw        r5, 0x10(r1);
+                stw        r6, 0x14(r1);
+                stw        r7, 0x18(r1);
+                stfd       f1, 0x20(r1);
+                mtctr      r12;
+                bctrl;
+                lwz        r3, 0x8(r1);
+                lwz        r4, 0xc(r1);
+                lwz        r5, 0x10(r1);
+                lwz        r6, 0x14(r1);
+                lwz        r7, 0x18(r1);
+                lfd        f1, 0x20(r1);
+                lwz        r0, 0x34(r1);
+                mtlr       r0;
+                addi       r1, r1, 0x30;
+            original:
+                .long      original_instr;
+                b          { samus_hud_update_addr + 4 };
+        });
+
+        new_text_section_end += pickup_counter_hook_patch.encoded_bytes().len() as u32;
+        new_text_section.extend(pickup_counter_hook_patch.encoded_bytes());
+    }
+
+    if !config.starting_presets.is_empty() {
+        // Call through a function pointer set by the rel at the start of
+        // CGameState::SetCurrentWorldId, so that it can send a new game to the starting preset of
+        // the selected save file
+        let hook_ptr = rel_hook_address(version, STARTING_PRESET_HOOK);
+        let hook_addr = new_text_section_end;
+
+        let set_current_world_id_addr = symbol_addr!("SetCurrentWorldId__10CGameStateFUi", version);
+        let original_instr = dol_patcher.read_original_u32(set_current_world_id_addr)?;
+        if original_instr >> 26 == 18 {
+            Err("Cannot relocate a branch out of CGameState::SetCurrentWorldId".to_string())?;
+        }
+
+        #[rustfmt::skip]
+        let set_current_world_id_patch = ppcasm!(set_current_world_id_addr, {
+            b          { hook_addr };
+        });
+        dol_patcher.ppcasm_patch(&set_current_world_id_patch)?;
+
+        // The hook returns the world to switch to, which replaces the one passed in r4
+        let starting_preset_hook_patch = ppcasm!(hook_addr, {
+                lis        r12, { hook_ptr }@h;
+                addi       r12, r12, { hook_ptr }@l;
+                lwz        r12, 0(r12);
+                cmpwi      r12, 0;
+                beq        original;
+                stwu       r1, -0x20(r1);
+                mflr       r0;
+                stw        r0, 0x24(r1);
+                stw        r3, 0x8(r1);
+                mtctr      r12;
+                bctrl;
+                mr         r4, r3;
+                lwz        r3, 0x8(r1);
+                lwz        r0, 0x24(r1);
+                mtlr       r0;
+                addi       r1, r1, 0x20;
+            original:
+                .long      original_instr;
+                b          { set_current_world_id_addr + 4 };
+        });
+
+        new_text_section_end += starting_preset_hook_patch.encoded_bytes().len() as u32;
+        new_text_section.extend(starting_preset_hook_patch.encoded_bytes());
+    }
+
+    if config.debug_features {
+        // Call through a function pointer set by the rel at the start of
+        // CPlayer::UpdateVisorState, so that it can read the controller combos of the debug
+        // features and move the player
+        let hook_ptr = rel_hook_address(version, DEBUG_FEATURES_HOOK);
+        let hook_addr = new_text_section_end;
+
+        let update_visor_state_addr = symbol_addr!(
+            "UpdateVisorState__7CPlayerFRC11CFinalInputfR13CStateManager",
+            version
+        );
+        let original_instr = dol_patcher.read_original_u32(update_visor_state_addr)?;
+        if original_instr >> 26 == 18 {
+            Err("Cannot relocate a branch out of CPlayer::UpdateVisorState".to_string())?;
+        }
+
+        #[rustfmt::skip]
+        let update_visor_state_patch = ppcasm!(update_visor_state_addr, {
+            b          { hook_addr };
+        });
+        dol_patcher.ppcasm_patch(&update_visor_state_patch)?;
+
+        // The player, the input and the state manager are passed on in r3, r4 and r5, the frame
+        // time in f1 has to survive the call
+        let debug_features_hook_patch = ppcasm!(hook_addr, {
+                lis        r12, { hook_ptr }@h;
+                addi       r12, r12, { hook_ptr }@l;
+                lwz        r12, 0(r12);
+                cmpwi      r12, 0;
+                beq        original;
+                stwu       r1, -0x20(r1);
+                mflr       r0;
+                stw        r0, 0x24(r1);
+                stw        r3, 0x8(r1);
+                stw        r4, 0xc(r1);
+                stw        r5, 0x10(r1);
+                stfd       f1, 0x18(r1);
+                mtctr      r12;
+                bctrl;
+                lwz        r3, 0x8(r1);
+                lwz        r4, 0xc(r1);
+                lwz        r5, 0x10(r1);
+                lfd        f1, 0x18(r1);
+                lwz        r0, 0x24(r1);
+                mtlr       r0;
+                addi       r1, r1, 0x20;
+            original:
+                .long      original_instr;
+                b          { update_visor_state_addr + 4 };
+        });
+
+        new_text_section_end += debug_features_hook_patch.encoded_bytes().len() as u32;
+        new_text_section.extend(debug_features_hook_patch.encoded_bytes());
+    }
+
     // bool __thiscall CGameState::IsMemoryRelayActive(uint object_id, uint mlvl_id)
     let is_memory_relay_active_func = new_text_section_end;
     let is_memory_relay_active_func_patch = ppcasm!(is_memory_relay_active_func, {
@@ -10740,6 +13218,31 @@ fn patch_dol(
         -0x5eb4
     };
 
+    // Run when the pickup was collected, the icon is either faded as usual or swapped for its
+    // crossed out variant. The TXTR id is in r0 on NTSC-J/PAL and in r6 otherwise.
+    let collected_icon_instr = {
+        let collected_offset = custom_asset_ids::MAP_PICKUP_ICON_COLLECTED_TXTR.to_u32()
+            - custom_asset_ids::MAP_PICKUP_ICON_MAJOR_TXTR.to_u32();
+        let instr = if !config.map_collected_pickup_icons {
+            ppcasm!(0, {
+                fmr          f30, f14;
+            })
+            .encoded_bytes()
+        } else if version == Version::NtscJ || version == Version::Pal {
+            // addic because addi would read r0 as 0
+            ppcasm!(0, {
+                addic        r0, r0, { collected_offset };
+            })
+            .encoded_bytes()
+        } else {
+            ppcasm!(0, {
+                addi         r6, r6, { collected_offset };
+            })
+            .encoded_bytes()
+        };
+        u32::from_be_bytes(instr[..4].try_into().unwrap())
+    };
+
     if version == Version::NtscJ || version == Version::Pal {
         let set_pickup_icon_txtr_patch = ppcasm!(new_text_section_end, {
             lwz          r3, 0x08(r18);
@@ -10755,15 +13258,18 @@ fn patch_dol(
             lwz          r4, 0x0c(r4);
 
             bl           { is_memory_relay_active_func };
-            lis          r31, { custom_asset_ids::MAP_PICKUP_ICON_TXTR.to_u32() }@h;
-            addi         r31, r31, { custom_asset_ids::MAP_PICKUP_ICON_TXTR.to_u32() }@l;
-            mr           r0, r31;
+            lis          r31, { custom_asset_ids::MAP_PICKUP_ICON_MAJOR_TXTR.to_u32() }@h;
+            addi         r31, r31, { custom_asset_ids::MAP_PICKUP_ICON_MAJOR_TXTR.to_u32() }@l;
+
+            // the icon variant is stored in the unused field after the editor ID
+            lwz          r0, 0x0c(r18);
+            add          r0, r0, r31;
             cmpwi        r3, 0;
             lis          r31, 0xffff;
             ori          r31, r31, 0xffff;
             lwz          r3, { off }(r13);
-            beq          { new_text_section_end + 0x4c };
-            fmr          f30, f14;
+            beq          { new_text_section_end + 0x50 };
+            .long        collected_icon_instr;
             b            { symbol_addr!("Draw__15CMappableObjectCFiRC13CMapWorldInfofb", version) + 0x284 };
         });
 
@@ -10784,11 +13290,15 @@ fn patch_dol(
 
             bl           { is_memory_relay_active_func };
             cmpwi        r3, 0;
+
+            // the icon variant is stored in the unused field after the editor ID
+            lwz          r6, 0x0c(r18);
+            lis          r3, { custom_asset_ids::MAP_PICKUP_ICON_MAJOR_TXTR.to_u32() }@h;
+            addi         r3, r3, { custom_asset_ids::MAP_PICKUP_ICON_MAJOR_TXTR.to_u32() }@l;
+            add          r6, r6, r3;
             lwz          r3, { off }(r13);
-            lis          r6, { custom_asset_ids::MAP_PICKUP_ICON_TXTR.to_u32() }@h;
-            addi         r6, r6, { custom_asset_ids::MAP_PICKUP_ICON_TXTR.to_u32() }@l;
-            beq          { new_text_section_end + 0x3c };
-            fmr          f30, f14;
+            beq          { new_text_section_end + 0x44 };
+            .long        collected_icon_instr;
             b            { symbol_addr!("Draw__15CMappableObjectCFiRC13CMapWorldInfofb", version) + 0x298 };
         });
 
@@ -11263,6 +13773,273 @@ fn patch_dol(
         new_text_section.extend(restore_original_check_code_cave_patch.encoded_bytes());
     }
 
+    if version == Version::Pal && config.pal_timing != PalTiming::Default {
+        // VIInit picks the TV format from the OS globals, overwrite it before the game gets the
+        // chance to initialize video
+        let tv_format: u32 = match config.pal_timing {
+            PalTiming::Ntsc => 0,  // VI_NTSC
+            PalTiming::Pal60 => 5, // VI_EURGB60
+            PalTiming::Default => unreachable!(),
+        };
+        let main_ctor_addr = symbol_addr!("__ct__5CMainFv", version);
+
+        let original_instr = dol_patcher.read_original_u32(main_ctor_addr)?;
+        if original_instr >> 26 == 18 {
+            Err("Cannot relocate a branch out of the CMain constructor".to_string())?;
+        }
+
+        #[rustfmt::skip]
+        let main_ctor_patch = ppcasm!(main_ctor_addr, {
+            b          { new_text_section_end };
+        });
+        dol_patcher.ppcasm_patch(&main_ctor_patch)?;
+
+        let force_tv_format_patch = ppcasm!(new_text_section_end, {
+                lis        r11, 0x8000;
+                li         r12, { tv_format };
+                stw        r12, 0xcc(r11);
+                .long      original_instr;
+                b          { main_ctor_addr + 4 };
+        });
+
+        new_text_section_end += force_tv_format_patch.encoded_bytes().len() as u32;
+        new_text_section.extend(force_tv_format_patch.encoded_bytes());
+    }
+
+    if config.display.widescreen.unwrap_or(false) {
+        // Every game camera passes its aspect ratio through the CGameCamera constructor, which
+        // the camera uses both for its projection and the frustum objects are culled against.
+        // Widening it there keeps the two in sync.
+        let game_camera_ctor_addr = match version {
+            Version::NtscU0_00 | Version::NtscU0_02 => symbol_addr!("__ct__11CGameCameraF9TUniqueIdbRCQ24rstl66basic_string<c,Q24rstl14char_traits<c>,Q24rstl17rmemory_allocator>RC11CEntityInfoRC12CTransform4fffff9TUniqueIdbi", version),
+            _ => Err(format!("Widescreen is not supported for version {}", version))?,
+        };
+
+        let original_instr = dol_patcher.read_original_u32(game_camera_ctor_addr)?;
+        if original_instr >> 26 == 18 {
+            Err("Cannot relocate a branch out of the CGameCamera constructor".to_string())?;
+        }
+
+        #[rustfmt::skip]
+        let game_camera_ctor_patch = ppcasm!(game_camera_ctor_addr, {
+            b          { new_text_section_end };
+        });
+        dol_patcher.ppcasm_patch(&game_camera_ctor_patch)?;
+
+        let widescreen_aspect_patch = ppcasm!(new_text_section_end, {
+                lis        r12, data@h;
+                addi       r12, r12, data@l;
+                lfs        f0, 0(r12);
+                fmuls      f4, f4, f0;
+                .long      original_instr;
+                b          { game_camera_ctor_addr + 4 };
+            data:
+                .float     { 4.0f32 / 3.0 };
+        });
+
+        new_text_section_end += widescreen_aspect_patch.encoded_bytes().len() as u32;
+        new_text_section.extend(widescreen_aspect_patch.encoded_bytes());
+    }
+
+    if let Some(threshold) = config.cosmetics.low_energy_alarm_threshold {
+        // CPlayer::IsEnergyLow decides both when the alarm sounds and when the HUD flashes,
+        // replace its 30 (100 with 4 or more energy tanks) with the configured threshold
+        let is_energy_low_addr = match version {
+            Version::NtscU0_00 | Version::NtscU0_02 | Version::Pal => {
+                symbol_addr!("IsEnergyLow__7CPlayerCFRC13CStateManager", version)
+            }
+            _ => Err(format!(
+                "The low energy alarm threshold is not supported for version {}",
+                version
+            ))?,
+        };
+
+        #[rustfmt::skip]
+        let is_energy_low_patch = ppcasm!(is_energy_low_addr, {
+            b          { new_text_section_end };
+        });
+        dol_patcher.ppcasm_patch(&is_energy_low_patch)?;
+
+        let low_energy_threshold_patch = ppcasm!(new_text_section_end, {
+                lwz        r5, 0x8b8(r4);
+                lwz        r5, 0x0(r5);
+                lfs        f1, 0x0c(r5);
+                lis        r6, data@h;
+                addi       r6, r6, data@l;
+                lfs        f0, 0(r6);
+                li         r3, 1;
+                fcmpu      cr0, f1, f0;
+                blt        { new_text_section_end + 0x28 };
+                li         r3, 0;
+                blr;
+            data:
+                .float     { threshold };
+        });
+
+        new_text_section_end += low_energy_threshold_patch.encoded_bytes().len() as u32;
+        new_text_section.extend(low_energy_threshold_patch.encoded_bytes());
+    }
+
+    if !starting_scans.is_empty() {
+        // A new game's scan times are all set to 0 in CPlayerState::InitializeScanTimes, set those
+        // of the starting logbook to 1 (fully scanned) once it's done. Saved games read their scan
+        // times from the memory card instead.
+        let (initialize_scan_times_addr, set_scan_time_addr) = match version {
+            Version::NtscU0_00 | Version::NtscU0_02 => (
+                symbol_addr!("InitializeScanTimes__12CPlayerStateFv", version),
+                symbol_addr!("SetScanTime__12CPlayerStateFUif", version),
+            ),
+            _ => Err(format!(
+                "The starting logbook is not supported for version {}",
+                version
+            ))?,
+        };
+
+        let original_instr = dol_patcher.read_original_u32(initialize_scan_times_addr)?;
+        if original_instr >> 26 == 18 {
+            Err("Cannot relocate a branch out of CPlayerState::InitializeScanTimes".to_string())?;
+        }
+
+        // 1.0, followed by the scan ids
+        let scan_table_addr = new_text_section_end;
+        let scan_table_end = 4 * (starting_scans.len() as u32 + 1);
+        new_text_section.extend(1.0f32.to_be_bytes());
+        for scan_id in starting_scans {
+            new_text_section.extend(scan_id.to_be_bytes());
+        }
+        new_text_section_end += scan_table_end;
+
+        #[rustfmt::skip]
+        let initialize_scan_times_patch = ppcasm!(initialize_scan_times_addr, {
+            b          { new_text_section_end };
+        });
+        dol_patcher.ppcasm_patch(&initialize_scan_times_patch)?;
+
+        let starting_logbook_patch = ppcasm!(new_text_section_end, {
+                stwu       r1, -0x20(r1);
+                mflr       r0;
+                stw        r0, 0x24(r1);
+                stw        r29, 0x14(r1);
+                stw        r30, 0x18(r1);
+                stw        r31, 0x1c(r1);
+                mr         r31, r3;
+                bl         original;
+                lis        r30, { scan_table_addr }@h;
+                addi       r30, r30, { scan_table_addr }@l;
+                li         r29, 4;
+            next_scan:
+                mr         r3, r31;
+                lwzx       r4, r30, r29;
+                lfs        f1, 0(r30);
+                bl         { set_scan_time_addr };
+                addi       r29, r29, 4;
+                cmpwi      r29, { scan_table_end };
+                blt        next_scan;
+                lwz        r29, 0x14(r1);
+                lwz        r30, 0x18(r1);
+                lwz        r31, 0x1c(r1);
+                lwz        r0, 0x24(r1);
+                mtlr       r0;
+                addi       r1, r1, 0x20;
+                blr;
+            original:
+                .long      original_instr;
+                b          { initialize_scan_times_addr + 4 };
+        });
+
+        new_text_section_end += starting_logbook_patch.encoded_bytes().len() as u32;
+        new_text_section.extend(starting_logbook_patch.encoded_bytes());
+    }
+
+    let save_icon_count = config
+        .game_banner
+        .save_icons
+        .as_ref()
+        .map_or(0, |icons| icons.len());
+    if save_icon_count > 2 || config.game_banner.save_icon_speed.is_some() {
+        // CMemoryCardDriver::InitializeFileInfo locks TXTR_SaveIcon0 then TXTR_SaveIcon1 with the
+        // middle speed (8 frames per icon). Set the speed of every icon, and lock the extra frames
+        // right after TXTR_SaveIcon1.
+        let lock_icon_token_addr = match version {
+            Version::NtscU0_00 | Version::NtscU0_02 | Version::Pal => {
+                symbol_addr!("LockIconToken__13CCardFileInfoFiiR11CSimplePool", version)
+            }
+            _ => Err(format!(
+                "More than 2 save icons and the save icon speed are not supported for version {}",
+                version
+            ))?,
+        };
+        let speed = config.game_banner.save_icon_speed.unwrap_or(2);
+        let last_icon_id = resource_info!("TXTR_SaveIcon1.TXTR").res_id;
+
+        let original_instr = dol_patcher.read_original_u32(lock_icon_token_addr)?;
+        if original_instr >> 26 == 18 {
+            Err("Cannot relocate a branch out of CCardFileInfo::LockIconToken".to_string())?;
+        }
+
+        // The ids of the extra frames, ending with 0
+        let extra_icon_count = save_icon_count.saturating_sub(2) as u32;
+        let icon_table_addr = new_text_section_end;
+        for i in 0..extra_icon_count {
+            let icon_id = custom_asset_ids::SAVE_ICON_TXTRS.to_u32() + i;
+            new_text_section.extend(icon_id.to_be_bytes());
+        }
+        new_text_section.extend(0u32.to_be_bytes());
+        new_text_section_end += 4 * (extra_icon_count + 1);
+
+        #[rustfmt::skip]
+        let lock_icon_token_patch = ppcasm!(lock_icon_token_addr, {
+            b          { new_text_section_end };
+        });
+        dol_patcher.ppcasm_patch(&lock_icon_token_patch)?;
+
+        let save_icon_patch = ppcasm!(new_text_section_end, {
+                stwu       r1, -0x20(r1);
+                mflr       r0;
+                stw        r0, 0x24(r1);
+                stw        r28, 0x10(r1);
+                stw        r29, 0x14(r1);
+                stw        r30, 0x18(r1);
+                stw        r31, 0x1c(r1);
+                mr         r31, r3;
+                mr         r30, r4;
+                mr         r29, r6;
+                li         r5, { speed };
+                bl         original;
+                lis        r3, { last_icon_id }@h;
+                addi       r3, r3, { last_icon_id }@l;
+                cmplw      r30, r3;
+                bne        done;
+                lis        r28, { icon_table_addr }@h;
+                addi       r28, r28, { icon_table_addr }@l;
+            next_icon:
+                lwz        r4, 0(r28);
+                cmpwi      r4, 0;
+                beq        done;
+                mr         r3, r31;
+                li         r5, { speed };
+                mr         r6, r29;
+                bl         original;
+                addi       r28, r28, 4;
+                b          next_icon;
+            done:
+                lwz        r28, 0x10(r1);
+                lwz        r29, 0x14(r1);
+                lwz        r30, 0x18(r1);
+                lwz        r31, 0x1c(r1);
+                lwz        r0, 0x24(r1);
+                mtlr       r0;
+                addi       r1, r1, 0x20;
+                blr;
+            original:
+                .long      original_instr;
+                b          { lock_icon_token_addr + 4 };
+        });
+
+        new_text_section_end += save_icon_patch.encoded_bytes().len() as u32;
+        new_text_section.extend(save_icon_patch.encoded_bytes());
+    }
+
     let bytes_needed = ((new_text_section.len() + 31) & !31) - new_text_section.len();
     new_text_section.extend([0; 32][..bytes_needed].iter().copied());
     dol_patcher.add_text_segment(new_text_section_start, Cow::Owned(new_text_section))?;
@@ -11329,7 +14106,20 @@ fn patch_ctwk_game(res: &mut structs::Resource, ctwk_config: &CtwkConfig) -> Res
     Ok(())
 }
 
-fn patch_ctwk_player(res: &mut structs::Resource, ctwk_config: &CtwkConfig) -> Result<(), String> {
+// The time a frame takes, which is longer on a PAL disc left to output 50Hz video
+fn frame_time(version: Version, pal_timing: PalTiming) -> f32 {
+    if version == Version::Pal && pal_timing == PalTiming::Default {
+        1.0 / 50.0
+    } else {
+        1.0 / 60.0
+    }
+}
+
+fn patch_ctwk_player(
+    res: &mut structs::Resource,
+    ctwk_config: &CtwkConfig,
+    frame_time: f32,
+) -> Result<(), String> {
     let mut ctwk = res.kind.as_ctwk_mut().unwrap();
     let ctwk_player = match &mut ctwk {
         structs::Ctwk::Player(i) => i,
@@ -11342,6 +14132,14 @@ fn patch_ctwk_player(res: &mut structs::Resource, ctwk_config: &CtwkConfig) -> R
         ctwk_player.player_xy_half_extent *= player_size;
         ctwk_player.step_up_height *= player_size;
         ctwk_player.step_down_height *= player_size;
+
+        if ctwk_config.player_size_physics.unwrap_or(false) {
+            ctwk_player.eye_offset *= player_size;
+            ctwk_player.player_ball_half_extent *= player_size;
+            ctwk_player.bomb_jump_height *= player_size;
+            ctwk_player.bomb_jump_radius *= player_size;
+            ctwk_player.aim_box_height *= player_size;
+        }
     }
 
     if ctwk_config.step_up_height.is_some() {
@@ -11414,7 +14212,7 @@ fn patch_ctwk_player(res: &mut structs::Resource, ctwk_config: &CtwkConfig) -> R
     }
 
     if ctwk_config.coyote_frames.is_some() {
-        ctwk_player.allowed_ledge_time = (ctwk_config.coyote_frames.unwrap() as f32) * (1.0 / 60.0);
+        ctwk_player.allowed_ledge_time = (ctwk_config.coyote_frames.unwrap() as f32) * frame_time;
     }
 
     if ctwk_config.move_during_free_look.unwrap_or(false) {
@@ -11532,6 +14330,61 @@ fn patch_ctwk_player(res: &mut structs::Resource, ctwk_config: &CtwkConfig) -> R
         // there might be others
     }
 
+    if let Some(vertical_jump_accel) = ctwk_config.vertical_jump_accel {
+        ctwk_player.vertical_jump_accel *= vertical_jump_accel;
+    }
+    if let Some(horizontal_jump_accel) = ctwk_config.horizontal_jump_accel {
+        ctwk_player.horizontal_jump_accel *= horizontal_jump_accel;
+    }
+
+    // The 8 entry arrays are indexed by surface restraint:
+    // 0 - Normal, 1 - Air, 2 - Ice, 3 - Organic, 4 - Water, 5 - Lava, 6 - Phazon, 7 - Shrubbery
+    if let Some(air_acceleration) = ctwk_config.air_acceleration {
+        ctwk_player.max_translational_acceleration[1] *= air_acceleration;
+    }
+    if let Some(air_friction) = ctwk_config.air_friction {
+        ctwk_player.translation_friction[1] *= air_friction;
+    }
+    if let Some(air_max_speed) = ctwk_config.air_max_speed {
+        ctwk_player.translation_max_speed[1] *= air_max_speed;
+    }
+    if let Some(water_acceleration) = ctwk_config.water_acceleration {
+        ctwk_player.max_translational_acceleration[4] *= water_acceleration;
+    }
+    if let Some(water_max_speed) = ctwk_config.water_max_speed {
+        ctwk_player.translation_max_speed[4] *= water_max_speed;
+    }
+
+    if let Some(fluid_gravity) = ctwk_config.fluid_gravity {
+        ctwk_player.fluid_grav_accel *= fluid_gravity;
+    }
+    if let Some(water_jump_factor) = ctwk_config.water_jump_factor {
+        ctwk_player.water_jump_factor *= water_jump_factor;
+    }
+    if let Some(water_ball_jump_factor) = ctwk_config.water_ball_jump_factor {
+        ctwk_player.water_ball_jump_factor *= water_ball_jump_factor;
+    }
+    if let Some(lava_jump_factor) = ctwk_config.lava_jump_factor {
+        ctwk_player.lava_jump_factor *= lava_jump_factor;
+    }
+    if let Some(lava_ball_jump_factor) = ctwk_config.lava_ball_jump_factor {
+        ctwk_player.lava_ball_jump_factor *= lava_ball_jump_factor;
+    }
+    if let Some(phazon_jump_factor) = ctwk_config.phazon_jump_factor {
+        ctwk_player.phazon_jump_factor *= phazon_jump_factor;
+    }
+    if let Some(phazon_ball_jump_factor) = ctwk_config.phazon_ball_jump_factor {
+        ctwk_player.phazon_ball_jump_factor *= phazon_ball_jump_factor;
+    }
+
+    if let Some(grapple_jump_force) = ctwk_config.grapple_jump_force {
+        ctwk_player.grapple_jump_force *= grapple_jump_force;
+    }
+    if let Some(grapple_pull_speed) = ctwk_config.grapple_pull_speed {
+        ctwk_player.grapple_pull_speed_min *= grapple_pull_speed;
+        ctwk_player.grapple_pull_speed_max *= grapple_pull_speed;
+    }
+
     Ok(())
 }
 
@@ -11562,12 +14415,47 @@ fn patch_ctwk_player_gun(
         }
     }
 
-    if ctwk_config.gun_cooldown.is_some() {
-        let gun_cooldown = ctwk_config.gun_cooldown.unwrap();
-        for i in 0..ctwk_player_gun.beams.len() {
-            ctwk_player_gun.beams[i].cool_down *= gun_cooldown;
-        }
+    if ctwk_config.gun_cooldown.is_some() {
+        let gun_cooldown = ctwk_config.gun_cooldown.unwrap();
+        for i in 0..ctwk_player_gun.beams.len() {
+            ctwk_player_gun.beams[i].cool_down *= gun_cooldown;
+        }
+    }
+
+    if let Some(missile_damage) = ctwk_config.missile_damage {
+        ctwk_player_gun.missile.damage *= missile_damage;
+    }
+
+    if let Some(charged_beam_damage) = ctwk_config.charged_beam_damage {
+        for i in 0..ctwk_player_gun.beams.len() {
+            ctwk_player_gun.beams[i].charged.damage *= charged_beam_damage;
+        }
+    }
+
+    if let Some(bomb_damage) = ctwk_config.bomb_damage {
+        ctwk_player_gun.bomb.damage *= bomb_damage;
+    }
+
+    if let Some(power_bomb_damage) = ctwk_config.power_bomb_damage {
+        ctwk_player_gun.power_bomb.damage *= power_bomb_damage;
+    }
+
+    if let Some(bomb_fuse_time) = ctwk_config.bomb_fuse_time {
+        ctwk_player_gun.bomb_fuse_time *= bomb_fuse_time;
+    }
+
+    if let Some(bomb_drop_delay_time) = ctwk_config.bomb_drop_delay_time {
+        ctwk_player_gun.bomb_drop_delay_time *= bomb_drop_delay_time;
     }
+
+    if let Some(beam_switch_time) = ctwk_config.beam_switch_time {
+        ctwk_player_gun.gun_transform_time *= beam_switch_time;
+    }
+
+    if let Some(gun_holster_time) = ctwk_config.gun_holster_time {
+        ctwk_player_gun.gun_holster_time *= gun_holster_time;
+    }
+
     Ok(())
 }
 
@@ -11579,6 +14467,36 @@ fn patch_ctwk_ball(res: &mut structs::Resource, ctwk_config: &CtwkConfig) -> Res
         _ => panic!("Failed to map res=0x{:X} as CtwkBall", res.file_id),
     };
 
+    if ctwk_config.player_size_physics.unwrap_or(false) {
+        if let Some(player_size) = ctwk_config.player_size {
+            ctwk_ball.conservative_door_cam_distance *= player_size;
+
+            // The distances of the morph ball camera, so that it follows as far behind a tiny or
+            // giant ball as it does a regular one
+            for offset in [
+                &mut ctwk_ball.ball_camera_offset,
+                &mut ctwk_ball.ball_camera_chase_look_at_offset,
+                &mut ctwk_ball.ball_camera_boost_look_at_offset,
+            ] {
+                for x in offset.iter_mut() {
+                    *x *= player_size;
+                }
+            }
+            for distance in [
+                &mut ctwk_ball.ball_camera_min_speed_distance,
+                &mut ctwk_ball.ball_camera_max_speed_distance,
+                &mut ctwk_ball.ball_camera_backwards_distance,
+                &mut ctwk_ball.ball_camera_chase_elevation,
+                &mut ctwk_ball.ball_camera_chase_distance,
+                &mut ctwk_ball.ball_camera_boost_elevation,
+                &mut ctwk_ball.ball_camera_boost_distance,
+                &mut ctwk_ball.ball_camera_control_distance,
+            ] {
+                *distance *= player_size;
+            }
+        }
+    }
+
     if ctwk_config.max_translation_accel.is_some() {
         ctwk_ball.max_translation_accel[0] *= ctwk_config.max_translation_accel.unwrap();
         ctwk_ball.max_translation_accel[1] *= ctwk_config.max_translation_accel.unwrap();
@@ -11653,6 +14571,15 @@ fn patch_ctwk_ball(res: &mut structs::Resource, ctwk_config: &CtwkConfig) -> Res
     if ctwk_config.boost_incremental_speed2.is_some() {
         ctwk_ball.boost_incremental_speed2 *= ctwk_config.boost_incremental_speed2.unwrap();
     }
+    if let Some(ball_air_acceleration) = ctwk_config.ball_air_acceleration {
+        ctwk_ball.max_translation_accel[1] *= ball_air_acceleration;
+    }
+    if let Some(ball_water_acceleration) = ctwk_config.ball_water_acceleration {
+        ctwk_ball.max_translation_accel[4] *= ball_water_acceleration;
+    }
+    if let Some(ball_door_camera_distance) = ctwk_config.ball_door_camera_distance {
+        ctwk_ball.conservative_door_cam_distance *= ball_door_camera_distance;
+    }
 
     Ok(())
 }
@@ -11660,6 +14587,7 @@ fn patch_ctwk_ball(res: &mut structs::Resource, ctwk_config: &CtwkConfig) -> Res
 fn patch_subchamber_five_essence_permadeath(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+    skip_exo: bool,
 ) -> Result<(), String> {
     let mrea_id = area.mlvl_area.mrea.to_u32();
     let layer_count = area.mrea().scly_section_mut().layers.len();
@@ -11671,7 +14599,12 @@ fn patch_subchamber_five_essence_permadeath(
         );
     }
     area.add_layer(b"Disable Bosses Layer\0".as_cstr());
-    area.layer_flags.flags &= !(1 << disable_bosses_layer_num);
+    if skip_exo {
+        // Always push the player into the hole, regardless of whether exo is dead
+        area.layer_flags.flags |= 1 << disable_bosses_layer_num;
+    } else {
+        area.layer_flags.flags &= !(1 << disable_bosses_layer_num);
+    }
 
     let spawn_point_id = area.new_object_id_from_layer_id(disable_bosses_layer_num);
     let player_hint_id = area.new_object_id_from_layer_id(disable_bosses_layer_num);
@@ -11863,6 +14796,142 @@ fn patch_subchamber_five_essence_permadeath(
     Ok(())
 }
 
+fn patch_skip_exo<'r>(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
+    game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+    bosses_moved: bool,
+) -> Result<(), String> {
+    if !bosses_moved {
+        patch_final_boss_permadeath(ps, area, game_resources)?;
+    }
+
+    // The exo objects now live on layer #1, which would otherwise be disabled once exo dies
+    area.layer_flags.flags &= !(1 << 1);
+
+    Ok(())
+}
+
+fn patch_lair_skip_essence(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+) -> Result<(), String> {
+    let trigger_id = area.new_object_id_from_layer_name("Default");
+    let timer_id = area.new_object_id_from_layer_name("Default");
+    let player_hint_id = area.new_object_id_from_layer_name("Default");
+    let (_, _, room_extent, room_origin) = derrive_bounding_box_measurements(area);
+    let layers = area.mrea().scly_section_mut().layers.as_mut_vec();
+
+    // This is the same transporter used by the "Essence Dead Cutscene" destination
+    let world_transporter_id = layers
+        .iter()
+        .flat_map(|layer| layer.objects.iter())
+        .find(|obj| obj.property_data.is_world_transporter())
+        .map(|obj| obj.instance_id)
+        .ok_or("Could not find the world transporter in Metroid Prime Lair")?;
+
+    // Send the player onwards as soon as they land in the arena, wherever that is in the room
+    layers[0].objects.as_mut_vec().push(structs::SclyObject {
+        instance_id: trigger_id,
+        property_data: structs::Trigger {
+            name: b"skip essence trigger\0".as_cstr(),
+            position: room_origin.into(),
+            scale: [
+                room_extent[0] * 2.0,
+                room_extent[1] * 2.0,
+                room_extent[2] * 2.0,
+            ]
+            .into(),
+            damage_info: structs::scly_structs::DamageInfo {
+                weapon_type: 0,
+                damage: 0.0,
+                radius: 0.0,
+                knockback_power: 0.0,
+            },
+            force: [0.0, 0.0, 0.0].into(),
+            flags: 1,
+            active: 1,
+            deactivate_on_enter: 1,
+            deactivate_on_exit: 0,
+        }
+        .into(),
+        connections: vec![
+            structs::Connection {
+                state: structs::ConnectionState::ENTERED,
+                message: structs::ConnectionMsg::INCREMENT,
+                target_object_id: player_hint_id,
+            },
+            structs::Connection {
+                state: structs::ConnectionState::ENTERED,
+                message: structs::ConnectionMsg::RESET_AND_START,
+                target_object_id: timer_id,
+            },
+        ]
+        .into(),
+    });
+
+    // Add timer to delay warp (can crash if player warps too quickly)
+    layers[0].objects.as_mut_vec().push(structs::SclyObject {
+        instance_id: timer_id,
+        property_data: structs::Timer {
+            name: b"skip essence delay\0".as_cstr(),
+            start_time: 1.0,
+            max_random_add: 0.0,
+            looping: 0,
+            start_immediately: 0,
+            active: 1,
+        }
+        .into(),
+        connections: vec![
+            structs::Connection {
+                state: structs::ConnectionState::ZERO,
+                message: structs::ConnectionMsg::ACTIVATE,
+                target_object_id: world_transporter_id,
+            },
+            structs::Connection {
+                state: structs::ConnectionState::ZERO,
+                message: structs::ConnectionMsg::SET_TO_ZERO,
+                target_object_id: world_transporter_id,
+            },
+        ]
+        .into(),
+    });
+
+    // Stop the player from moving
+    layers[0].objects.as_mut_vec().push(structs::SclyObject {
+        instance_id: player_hint_id,
+        property_data: structs::PlayerHint {
+            name: b"skip essence playerhint\0".as_cstr(),
+
+            position: [0.0, 0.0, 0.0].into(),
+            rotation: [0.0, 0.0, 0.0].into(),
+            active: 1,
+            data: structs::PlayerHintStruct {
+                unknown1: 0,
+                unknown2: 0,
+                extend_target_distance: 0,
+                unknown4: 0,
+                unknown5: 0,
+                disable_unmorph: 1,
+                disable_morph: 1,
+                disable_controls: 1,
+                disable_boost: 1,
+                activate_visor_combat: 0,
+                activate_visor_scan: 0,
+                activate_visor_thermal: 0,
+                activate_visor_xray: 0,
+                unknown6: 0,
+                face_object_on_unmorph: 0,
+            },
+            priority: 10,
+        }
+        .into(),
+        connections: vec![].into(),
+    });
+
+    Ok(())
+}
+
 fn patch_fix_aether_lab_entryway_broken_load(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
@@ -12423,54 +15492,75 @@ fn patch_final_boss_permadeath<'r>(
     Ok(())
 }
 
-fn patch_combat_hud_color(
-    res: &mut structs::Resource,
-    ctwk_config: &CtwkConfig,
-) -> Result<(), String> {
-    if ctwk_config.hud_color.is_none() {
-        return Ok(());
-    }
+fn patch_hud_widescreen(res: &mut structs::Resource) -> Result<(), String> {
+    // The 3D HUD elements are drawn through the FRME's own cameras rather than a CGameCamera,
+    // so they need to be widened separately
+    const ASPECT_SCALE: f32 = 4.0 / 3.0;
 
-    let mut new_color: [f32; 3] = *ctwk_config.hud_color.as_ref().unwrap();
-    let mut max_new = new_color[0];
-    if new_color[1] > max_new {
-        max_new = new_color[1];
+    let frme = res.kind.as_frme_mut().unwrap();
+    for widget in frme.widgets.as_mut_vec().iter_mut() {
+        let camera = match &mut widget.kind {
+            structs::FrmeWidgetKind::Camera(camera) => camera,
+            _ => continue,
+        };
+        if let Some(perspective) = camera.perspective_projection.as_mut() {
+            // fov, aspect, znear, zfar
+            perspective[1] *= ASPECT_SCALE;
+        }
+        if let Some(orthographic) = camera.orthographic_projection.as_mut() {
+            // left, right, top, bottom, znear, zfar
+            orthographic[0] *= ASPECT_SCALE;
+            orthographic[1] *= ASPECT_SCALE;
+        }
     }
-    if new_color[2] > max_new {
-        max_new = new_color[2];
+
+    Ok(())
+}
+
+// Scales `new_color` up or down to approximate the brightness of `old_color`, preserving alpha.
+// Returns None for black/white/gray colors, which shouldn't be tinted.
+fn tint_hud_color(old_color: &[f32], new_color: [f32; 3]) -> Option<[f32; 4]> {
+    if old_color[0] - old_color[1] > -0.1
+        && old_color[0] - old_color[1] < 0.1
+        && old_color[0] - old_color[2] > -0.1
+        && old_color[0] - old_color[2] < 0.1
+        && old_color[1] - old_color[2] > -0.1
+        && old_color[1] - old_color[2] < 0.1
+    {
+        return None;
     }
+
+    let mut new_color = new_color;
+    let mut max_new = new_color[0].max(new_color[1]).max(new_color[2]);
     if max_new < 0.0001 {
         new_color = [1.0, 1.0, 1.0];
+        max_new = 1.0;
     }
 
+    let max_original = old_color[0].max(old_color[1]).max(old_color[2]);
+    let scale = max_original / max_new;
+    Some([
+        new_color[0] * scale,
+        new_color[1] * scale,
+        new_color[2] * scale,
+        old_color[3],
+    ])
+}
+
+fn patch_combat_hud_color(
+    res: &mut structs::Resource,
+    hud_color: Option<[f32; 3]>,
+) -> Result<(), String> {
+    let new_color = match hud_color {
+        Some(hud_color) => hud_color,
+        None => return Ok(()),
+    };
+
     let frme = res.kind.as_frme_mut().unwrap();
     for widget in frme.widgets.as_mut_vec().iter_mut() {
-        let old_color = widget.color;
-        if old_color[0] - old_color[1] > -0.1
-            && old_color[0] - old_color[1] < 0.1
-            && old_color[0] - old_color[2] > -0.1
-            && old_color[0] - old_color[2] < 0.1
-            && old_color[1] - old_color[2] > -0.1
-            && old_color[1] - old_color[2] < 0.1
-        {
-            continue;
+        if let Some(new_color_scaled) = tint_hud_color(&widget.color, new_color) {
+            widget.color = new_color_scaled.into();
         }
-
-        let mut max_original = old_color[0];
-        if old_color[1] > max_original {
-            max_original = old_color[1];
-        }
-        if old_color[2] > max_original {
-            max_original = old_color[2];
-        }
-        let scale = max_original / max_new;
-        let new_color_scaled = [
-            new_color[0] * scale,
-            new_color[1] * scale,
-            new_color[2] * scale,
-            old_color[3],
-        ];
-        widget.color = new_color_scaled.into();
     }
 
     Ok(())
@@ -12479,15 +15569,18 @@ fn patch_combat_hud_color(
 fn patch_ctwk_gui_colors(
     res: &mut structs::Resource,
     ctwk_config: &CtwkConfig,
+    hud: Option<&HudCosmeticsConfig>,
 ) -> Result<(), String> {
+    let hud = hud.cloned().unwrap_or_default();
+
     let mut ctwk = res.kind.as_ctwk_mut().unwrap();
     let ctwk_gui_colors = match &mut ctwk {
         structs::Ctwk::GuiColors(i) => i,
         _ => panic!("Failed to map res=0x{:X} as CtwkGuiColors", res.file_id),
     };
 
-    if ctwk_config.hud_color.is_some() {
-        let mut new_color = ctwk_config.hud_color.unwrap();
+    if let Some(hud_color) = hud.hud_color.or(ctwk_config.hud_color) {
+        let mut new_color = hud_color;
         let mut max_new = new_color[0];
         if new_color[1] > max_new {
             max_new = new_color[1];
@@ -12558,37 +15651,33 @@ fn patch_ctwk_gui_colors(
         }
 
         for i in 0..5 {
-            let i = i as usize;
             for j in 0..7 {
-                let j = j as usize;
                 let old_color = ctwk_gui_colors.visor_colors[i][j];
-                if old_color[0] - old_color[1] > -0.1
-                    && old_color[0] - old_color[1] < 0.1
-                    && old_color[0] - old_color[2] > -0.1
-                    && old_color[0] - old_color[2] < 0.1
-                    && old_color[1] - old_color[2] > -0.1
-                    && old_color[1] - old_color[2] < 0.1
-                {
-                    continue;
+                if let Some(new_color_scaled) = tint_hud_color(&old_color, new_color) {
+                    ctwk_gui_colors.visor_colors[i][j] = new_color_scaled.into();
                 }
+            }
+        }
+    }
 
-                let mut max_original = old_color[0];
-                if old_color[1] > max_original {
-                    max_original = old_color[1];
-                }
-                if old_color[2] > max_original {
-                    max_original = old_color[2];
-                }
-                let scale = max_original / max_new;
-
-                // Scale new color up or down to approximate original, preserve alpha
-                ctwk_gui_colors.visor_colors[i][j] = [
-                    new_color[0] * scale,
-                    new_color[1] * scale,
-                    new_color[2] * scale,
-                    old_color[3],
-                ]
-                .into();
+    // Energy bar, energy tank and energy digit colors for each visor
+    for visor in [
+        HudVisor::Combat,
+        HudVisor::Scan,
+        HudVisor::XRay,
+        HudVisor::Thermal,
+        HudVisor::MorphBall,
+    ] {
+        let energy_bar_color = match hud.visor_energy_bar_color(visor) {
+            Some(energy_bar_color) => energy_bar_color,
+            None => continue,
+        };
+
+        let i = visor as usize;
+        for j in 0..7 {
+            let old_color = ctwk_gui_colors.visor_colors[i][j];
+            if let Some(new_color_scaled) = tint_hud_color(&old_color, energy_bar_color) {
+                ctwk_gui_colors.visor_colors[i][j] = new_color_scaled.into();
             }
         }
     }
@@ -13701,29 +16790,173 @@ fn patch_pq_scale(
         }
     }
 
-    Ok(())
-}
-
-fn patch_thardus_scale(
-    _ps: &mut PatcherState,
-    area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
-    scale: f32,
-) -> Result<(), String> {
+    Ok(())
+}
+
+fn patch_thardus_scale(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+    scale: f32,
+) -> Result<(), String> {
+    let scly = area.mrea().scly_section_mut();
+    for layer in scly.layers.as_mut_vec().iter_mut() {
+        for obj in layer.objects.as_mut_vec().iter_mut() {
+            if obj.property_data.is_thardus() {
+                let boss = obj.property_data.as_thardus_mut().unwrap();
+                boss.scale[0] *= scale;
+                boss.scale[1] *= scale;
+                boss.scale[2] *= scale;
+            } else if obj.property_data.is_platform()
+                && [].contains(&(obj.instance_id & 0x00FFFFFF))
+            {
+                let boss = obj.property_data.as_platform_mut().unwrap();
+                boss.scale[0] *= scale;
+                boss.scale[1] *= scale;
+                boss.scale[2] *= scale;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn patch_essence_health(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+    health: f32,
+) -> Result<(), String> {
+    let scly = area.mrea().scly_section_mut();
+    let layer = &mut scly.layers.as_mut_vec()[0];
+    layer
+        .objects
+        .iter_mut()
+        .filter_map(|obj| obj.property_data.as_metroidprimestage2_mut())
+        .for_each(|mps2| mps2.patterned_info.health_info.health = health);
+
+    Ok(())
+}
+
+fn patch_boss_health_multiplier(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
+    boss_name: &str,
+    version: Version,
+    multiplier: f32,
+) -> Result<(), String> {
+    // PAL and later versions use a different Ridley layout with the same object type
+    let ridley_v2 = [
+        Version::Pal,
+        Version::NtscJ,
+        Version::PalTrilogy,
+        Version::NtscUTrilogy,
+        Version::NtscJTrilogy,
+    ]
+    .contains(&version);
+
+    let is_boss = |obj: &structs::SclyObject| -> bool {
+        let property_data = &obj.property_data;
+        match boss_name {
+            "parasitequeen" => property_data.is_new_intro_boss(),
+            "idrone" | "incineratordrone" | "zoid" => property_data.is_actor_contraption(),
+            "flaahgra" => property_data.is_flaahgra(),
+            "adultsheegoth" => property_data.is_ice_sheegoth(),
+            "thardus" => property_data.is_thardus(),
+            "elitepirate1" | "elitepirate2" | "elitepirate3" | "phazonelite" => {
+                property_data.is_elite_pirate()
+            }
+            "omegapirate" => property_data.is_omega_pirate(),
+            "ridley" | "metaridley" => property_data.is_ridley_v1() || property_data.is_ridley_v2(),
+            "exo" | "metroidprime" | "metroidprimeexoskeleton" => {
+                property_data.is_metroidprimestage1()
+            }
+            "essence" | "metroidprimeessence" => property_data.is_metroidprimestage2(),
+            // The ambush it's a part of has regular beetles as well
+            "platedbeetle" => property_data.as_beetle().is_some_and(|beetle| {
+                beetle
+                    .name
+                    .to_str()
+                    .unwrap()
+                    .to_lowercase()
+                    .contains("garbeetle")
+            }),
+            "cloakeddrone" => property_data.is_drone(),
+            _ => false,
+        }
+    };
+
+    // Omega Pirate's armor plates have to be destroyed before each of its vulnerable phases.
+    // Flaahgra's health is restored whenever it gets back up, so its own health already covers
+    // every phase.
+    let is_phase_object = |obj: &structs::SclyObject| -> bool {
+        boss_name == "omegapirate"
+            && obj.property_data.as_platform().is_some_and(|platform| {
+                platform
+                    .name
+                    .to_str()
+                    .unwrap()
+                    .to_lowercase()
+                    .contains("armor")
+            })
+    };
+
+    let scly = area.mrea().scly_section();
+    let mut boss_ids = vec![];
+    for layer in scly.layers.iter() {
+        for obj in layer.objects.iter() {
+            if is_boss(&obj) || is_phase_object(&obj) {
+                boss_ids.push(obj.instance_id);
+            }
+        }
+    }
+    if boss_ids.is_empty() {
+        Err(format!(
+            "Could not find the {} to scale the health of in room 0x{:08X}",
+            boss_name,
+            area.mlvl_area.mrea.to_u32()
+        ))?;
+    }
+
     let scly = area.mrea().scly_section_mut();
     for layer in scly.layers.as_mut_vec().iter_mut() {
         for obj in layer.objects.as_mut_vec().iter_mut() {
-            if obj.property_data.is_thardus() {
-                let boss = obj.property_data.as_thardus_mut().unwrap();
-                boss.scale[0] *= scale;
-                boss.scale[1] *= scale;
-                boss.scale[2] *= scale;
-            } else if obj.property_data.is_platform()
-                && [].contains(&(obj.instance_id & 0x00FFFFFF))
-            {
-                let boss = obj.property_data.as_platform_mut().unwrap();
-                boss.scale[0] *= scale;
-                boss.scale[1] *= scale;
-                boss.scale[2] *= scale;
+            if !boss_ids.contains(&obj.instance_id) {
+                continue;
+            }
+            let property_data = &mut obj.property_data;
+            if property_data.is_ridley_v1() || property_data.is_ridley_v2() {
+                if ridley_v2 {
+                    let boss = property_data.as_ridley_v2_mut().unwrap();
+                    boss.patterned_info.health_info.health *= multiplier;
+                } else {
+                    let boss = property_data.as_ridley_v1_mut().unwrap();
+                    boss.patterned_info.health_info.health *= multiplier;
+                }
+            } else if let Some(boss) = property_data.as_new_intro_boss_mut() {
+                boss.patterned_info.health_info.health *= multiplier;
+            } else if let Some(boss) = property_data.as_actor_contraption_mut() {
+                boss.health_info.health *= multiplier;
+            } else if let Some(boss) = property_data.as_flaahgra_mut() {
+                boss.patterned_info.health_info.health *= multiplier;
+            } else if let Some(boss) = property_data.as_ice_sheegoth_mut() {
+                boss.patterned_info.health_info.health *= multiplier;
+            } else if let Some(boss) = property_data.as_thardus_mut() {
+                boss.patterned_info.health_info.health *= multiplier;
+            } else if let Some(boss) = property_data.as_elite_pirate_mut() {
+                boss.patterned_info.health_info.health *= multiplier;
+            } else if let Some(boss) = property_data.as_omega_pirate_mut() {
+                boss.patterned_info.health_info.health *= multiplier;
+            } else if let Some(armor) = property_data.as_platform_mut() {
+                armor.health_info.health *= multiplier;
+            } else if let Some(boss) = property_data.as_metroidprimestage1_mut() {
+                boss.exo_struct_b.patterned_info.health_info.health *= multiplier;
+                boss.health_info1.health *= multiplier;
+                boss.health_info2.health *= multiplier;
+            } else if let Some(boss) = property_data.as_metroidprimestage2_mut() {
+                boss.patterned_info.health_info.health *= multiplier;
+            } else if let Some(boss) = property_data.as_beetle_mut() {
+                boss.patterned_info.health_info.health *= multiplier;
+            } else if let Some(boss) = property_data.as_drone_mut() {
+                boss.patterned_info.health_info.health *= multiplier;
             }
         }
     }
@@ -13731,18 +16964,31 @@ fn patch_thardus_scale(
     Ok(())
 }
 
-fn patch_essence_health(
+fn patch_omega_pirate_weak_armor(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'_, '_, '_, '_>,
-    health: f32,
 ) -> Result<(), String> {
     let scly = area.mrea().scly_section_mut();
-    let layer = &mut scly.layers.as_mut_vec()[0];
-    layer
-        .objects
-        .iter_mut()
-        .filter_map(|obj| obj.property_data.as_metroidprimestage2_mut())
-        .for_each(|mps2| mps2.patterned_info.health_info.health = health);
+    for layer in scly.layers.as_mut_vec().iter_mut() {
+        for obj in layer.objects.as_mut_vec().iter_mut() {
+            if let Some(platform) = obj.property_data.as_platform_mut() {
+                if !platform
+                    .name
+                    .to_str()
+                    .ok()
+                    .unwrap()
+                    .to_string()
+                    .to_lowercase()
+                    .contains("armor")
+                {
+                    continue;
+                }
+
+                // Each armor plate breaks in a single hit
+                platform.health_info.health = 1.0;
+            }
+        }
+    }
 
     Ok(())
 }
@@ -13835,8 +17081,15 @@ fn patch_bnr(file: &mut structs::FstEntryFile, banner: &GameBanner) -> Result<()
         _ => panic!(),
     };
 
-    bnr.pixels
-        .clone_from_slice(include_bytes!("../extra_assets/banner_image.bin"));
+    if let Some(path) = &banner.image {
+        let (pixels, width, height) = read_texture_file(Path::new(path))?;
+        let pixels = resize_rgba(pixels, width, height, 96, 32);
+        bnr.pixels
+            .clone_from_slice(&encode_rgb5a3_image(&pixels[..], 96, 32));
+    } else {
+        bnr.pixels
+            .clone_from_slice(include_bytes!("../extra_assets/banner_image.bin"));
+    }
 
     fn write_encoded_str(field: &str, s: &Option<String>, slice: &mut [u8]) -> Result<(), String> {
         if let Some(s) = s {
@@ -13857,50 +17110,117 @@ fn patch_bnr(file: &mut structs::FstEntryFile, banner: &GameBanner) -> Result<()
         Ok(())
     }
 
-    write_encoded_str(
-        "game_name",
+    fn write_fields(
+        fields: &mut structs::BnrMetadata,
+        game_name: &Option<String>,
+        game_name_full: &Option<String>,
+        developer: &Option<String>,
+        developer_full: &Option<String>,
+        description: &Option<String>,
+    ) -> Result<(), String> {
+        write_encoded_str("game_name", game_name, &mut fields.game_name)?;
+        write_encoded_str("developer", developer, &mut fields.developer)?;
+        write_encoded_str("game_name_full", game_name_full, &mut fields.game_name_full)?;
+        write_encoded_str("developer_full", developer_full, &mut fields.developer_full)?;
+        write_encoded_str("description", description, &mut fields.description)?;
+        Ok(())
+    }
+
+    write_fields(
+        &mut bnr.english_fields,
         &banner.game_name,
-        &mut bnr.english_fields.game_name,
-    )?;
-    write_encoded_str(
-        "developer",
-        &banner.developer,
-        &mut bnr.english_fields.developer,
-    )?;
-    write_encoded_str(
-        "game_name_full",
         &banner.game_name_full,
-        &mut bnr.english_fields.game_name_full,
-    )?;
-    write_encoded_str(
-        "developer_full",
+        &banner.developer,
         &banner.developer_full,
-        &mut bnr.english_fields.developer_full,
-    )?;
-    write_encoded_str(
-        "description",
         &banner.description,
-        &mut bnr.english_fields.description,
     )?;
 
+    // Only PAL discs (BNR2) have text for languages other than English
+    for (language, text) in banner.languages.iter().flat_map(|l| l.iter()) {
+        let index = crate::patch_config::banner_language_index(language)
+            .ok_or_else(|| format!("Unknown language '{}' in gameBanner.languages", language))?;
+        let other_lang_fields = bnr
+            .other_lang_fields
+            .as_mut()
+            .ok_or("gameBanner.languages is only supported for PAL Metroid Prime")?;
+        write_fields(
+            &mut other_lang_fields[index],
+            &text.game_name,
+            &text.game_name_full,
+            &text.developer,
+            &text.developer_full,
+            &text.description,
+        )?;
+    }
+
     Ok(())
 }
 
+// The memory card banner and icon animation of the save file. The game plays the icon frames
+// TXTR_SaveIcon0 and TXTR_SaveIcon1 in a loop, the DOL patch adds the frames after those.
+fn patch_save_file_textures<'s>(patcher: &mut PrimePatcher<'_, 's>, banner: &'s GameBanner) {
+    if let Some(path) = &banner.save_banner {
+        let path = Path::new(path);
+        patcher.add_resource_patch(resource_info!("TXTR_SaveBanner.TXTR").into(), move |res| {
+            replace_txtr(res, path, true)
+        });
+    } else {
+        patcher.add_resource_patch(
+            resource_info!("TXTR_SaveBanner.TXTR").into(),
+            patch_save_banner_txtr,
+        );
+    }
+
+    let save_icons = banner.save_icons.as_deref().unwrap_or(&[]);
+    let frames = [
+        resource_info!("TXTR_SaveIcon0.TXTR"),
+        resource_info!("TXTR_SaveIcon1.TXTR"),
+    ];
+    for (i, res_info) in frames.iter().enumerate() {
+        // A single frame is used for both, making the icon static
+        if let Some(path) = save_icons.get(i).or(save_icons.first()) {
+            let path = Path::new(path);
+            patcher
+                .add_resource_patch((*res_info).into(), move |res| replace_txtr(res, path, true));
+        }
+    }
+
+    // The other frames are copies of the first, which keep its format and size
+    if save_icons.len() > 2 {
+        patcher.add_file_patch(b"NoARAM.pak", move |file| {
+            let pak = match file {
+                structs::FstEntryFile::Pak(pak) => pak,
+                _ => unreachable!(),
+            };
+            let save_icon = pak
+                .resources
+                .iter()
+                .find(|res| res.file_id == frames[0].res_id)
+                .ok_or("Could not find TXTR_SaveIcon0 in NoARAM.pak")?
+                .into_owned();
+
+            let mut cursor = pak.resources.cursor();
+            while cursor.cursor_advancer().peek().is_some() {}
+            for (i, path) in save_icons.iter().enumerate().skip(2) {
+                let mut frame = save_icon.clone();
+                frame.file_id = custom_asset_ids::SAVE_ICON_TXTRS.to_u32() + i as u32 - 2;
+                replace_txtr(&mut frame, Path::new(path), true)?;
+                cursor.insert_after(iter::once(frame));
+            }
+            Ok(())
+        });
+    }
+}
+
 fn patch_qol_game_breaking(
     patcher: &mut PrimePatcher,
     version: Version,
     _force_vanilla_layout: bool,
     small_samus: bool,
+    softlock_fixes: &HashMap<SoftlockFix, bool>,
+    force_phazon_suit_cutscene: bool,
 ) {
     // Crashes
-    patcher.add_scly_patch(
-        resource_info!("00j_mines_connect.MREA").into(),
-        patch_fix_central_dynamo_crash,
-    );
-    patcher.add_scly_patch(
-        resource_info!("05_under_intro_zoo.MREA").into(), // stop crashing from too much stuff in biohazard containment + our patches
-        patch_fix_central_dynamo_crash,
-    );
     patcher.add_scly_patch(
         resource_info!("00p_mines_connect.MREA").into(),
         patch_fix_pca_crash,
@@ -13951,10 +17271,12 @@ fn patch_qol_game_breaking(
         );
     }
     // EQ Cutscene always Phazon Suit (avoids multiworld crash when player receives a suit during the fight)
-    patcher.add_scly_patch(
-        resource_info!("12_mines_eliteboss.MREA").into(),
-        move |ps, area| patch_cutscene_force_phazon_suit(ps, area),
-    );
+    if force_phazon_suit_cutscene {
+        patcher.add_scly_patch(
+            resource_info!("12_mines_eliteboss.MREA").into(),
+            move |ps, area| patch_cutscene_force_phazon_suit(ps, area),
+        );
+    }
     patcher.add_scly_patch(
         resource_info!("12_mines_eliteboss.MREA").into(),
         move |ps, area| patch_op_death_pickup_spawn(ps, area),
@@ -13991,10 +17313,6 @@ fn patch_qol_game_breaking(
             resource_info!("19_hive_totem.MREA").into(),
             patch_hive_totem_boss_trigger_0_02,
         );
-        patcher.add_scly_patch(
-            resource_info!("04_mines_pillar.MREA").into(),
-            patch_ore_processing_door_lock_0_02,
-        );
     }
     if version == Version::Pal
         || version == Version::NtscJ
@@ -14002,10 +17320,6 @@ fn patch_qol_game_breaking(
         || version == Version::NtscJTrilogy
         || version == Version::PalTrilogy
     {
-        patcher.add_scly_patch(
-            resource_info!("04_mines_pillar.MREA").into(),
-            patch_ore_processing_destructible_rock_pal,
-        );
         patcher.add_scly_patch(
             resource_info!("13_over_burningeffigy.MREA").into(),
             patch_geothermal_core_destructible_rock_pal,
@@ -14019,42 +17333,29 @@ fn patch_qol_game_breaking(
     }
 
     // softlocks
-    patcher.add_scly_patch(
-        resource_info!("22_Flaahgra.MREA").into(),
-        patch_sunchamber_prevent_wild_before_flaahgra,
-    );
-    patcher.add_scly_patch(
-        resource_info!("0v_connect_tunnel.MREA").into(),
-        patch_sun_tower_prevent_wild_before_flaahgra,
-    );
-    patcher.add_scly_patch(
-        resource_info!("13_ice_vault.MREA").into(),
-        patch_research_lab_aether_exploding_wall, // Remove wall when dark labs is activated
-    );
-    patcher.add_scly_patch(
-        resource_info!("12_ice_research_b.MREA").into(),
-        patch_research_lab_aether_exploding_wall_2, // Remove AI jank factor from persuading Edward to jump through glass when doing backwards aether
-    );
-    patcher.add_scly_patch(
-        resource_info!("11_ice_observatory.MREA").into(),
-        patch_observatory_2nd_pass_solvablility,
-    );
-    patcher.add_scly_patch(
-        resource_info!("11_ice_observatory.MREA").into(),
-        patch_observatory_1st_pass_softlock,
-    );
-    patcher.add_scly_patch(
-        resource_info!("02_mines_shotemup.MREA").into(),
-        patch_mines_security_station_soft_lock,
-    );
-    patcher.add_scly_patch(
-        resource_info!("18_ice_gravity_chamber.MREA").into(),
-        patch_gravity_chamber_stalactite_grapple_point,
-    );
-    patcher.add_scly_patch(
-        resource_info!("19_hive_totem.MREA").into(),
-        patch_hive_totem_softlock,
-    );
+    if SoftlockFix::SunchamberWildBeforeFlaahgra.is_enabled(softlock_fixes) {
+        patcher.add_scly_patch(
+            resource_info!("22_Flaahgra.MREA").into(),
+            patch_sunchamber_prevent_wild_before_flaahgra,
+        );
+        patcher.add_scly_patch(
+            resource_info!("0v_connect_tunnel.MREA").into(),
+            patch_sun_tower_prevent_wild_before_flaahgra,
+        );
+    }
+    if SoftlockFix::ResearchLabAetherWall.is_enabled(softlock_fixes) {
+        patcher.add_scly_patch(
+            resource_info!("13_ice_vault.MREA").into(),
+            patch_research_lab_aether_exploding_wall, // Remove wall when dark labs is activated
+        );
+    }
+    if SoftlockFix::Observatory1stPass.is_enabled(softlock_fixes) {
+        patcher.add_scly_patch(
+            resource_info!("11_ice_observatory.MREA").into(),
+            patch_observatory_1st_pass_softlock,
+        );
+    }
+    patch_softlock_fixes(patcher, version, softlock_fixes);
 
     // Elite Research
     // Platforms
@@ -14331,11 +17632,6 @@ fn patch_qol_cosmetic(patcher: &mut PrimePatcher, skip_ending_cinematic: bool, q
         }
     }
 
-    patcher.add_resource_patch(
-        resource_info!("FRME_BallHud.FRME").into(),
-        patch_morphball_hud,
-    );
-
     if skip_ending_cinematic {
         patcher.add_scly_patch(
             resource_info!("01_endcinema.MREA").into(),
@@ -14351,12 +17647,62 @@ fn patch_qol_cosmetic(patcher: &mut PrimePatcher, skip_ending_cinematic: bool, q
     // not shown here - hudmemos are nonmodal and item aquisition cutscenes are removed
 }
 
-fn patch_qol_competitive_cutscenes(
-    patcher: &mut PrimePatcher,
+// Per-room overrides of which cutscenes are skipped, see `PatchConfig::cutscene_overrides`
+struct CutsceneSkips<'a> {
+    overrides: &'a HashMap<u32, CutsceneOverride>,
+    patched_rooms: HashSet<u32>,
+}
+
+impl CutsceneSkips<'_> {
+    // Registers a cutscene skip for a room unless its cutscenes are configured to be kept
+    fn add_patch<'r, 's, F>(
+        &mut self,
+        patcher: &mut PrimePatcher<'r, 's>,
+        (pak_name, room_id): (&'s [u8], u32),
+        f: F,
+    ) where
+        F: FnMut(
+                &mut PatcherState,
+                &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
+            ) -> Result<(), String>
+            + 's,
+    {
+        if self.overrides.get(&room_id) == Some(&CutsceneOverride::Keep) {
+            return;
+        }
+        self.patched_rooms.insert(room_id);
+        patcher.add_scly_patch((pak_name, room_id), f);
+    }
+
+    // Skips the cutscenes of the rooms explicitly configured to be skipped which aren't
+    // already handled by the selected cutscene mode
+    fn add_remaining_patches(&mut self, patcher: &mut PrimePatcher, elevators_patched: bool) {
+        for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
+            for room_info in rooms.iter() {
+                let room_id = room_info.room_id.to_u32();
+                if self.overrides.get(&room_id) != Some(&CutsceneOverride::Skip)
+                    || self.patched_rooms.contains(&room_id)
+                    || (elevators_patched && is_elevator(room_id))
+                {
+                    continue;
+                }
+                self.patched_rooms.insert(room_id);
+                patcher.add_scly_patch((pak_name.as_bytes(), room_id), move |ps, area| {
+                    patch_remove_cutscenes(ps, area, vec![], vec![], false)
+                });
+            }
+        }
+    }
+}
+
+fn patch_qol_competitive_cutscenes<'r, 's>(
+    patcher: &mut PrimePatcher<'r, 's>,
+    cutscenes: &mut CutsceneSkips,
     version: Version,
     _skip_frigate: bool,
 ) {
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("01_mines_mainplaza.MREA").into(), // main quarry (just pirate booty)
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14373,7 +17719,8 @@ fn patch_qol_competitive_cutscenes(
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("08_courtyard.MREA").into(), // Arboretum
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14385,29 +17732,35 @@ fn patch_qol_competitive_cutscenes(
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("10_over_1alavaarea.MREA").into(), // magmoor workstation
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![0x00170153], false), // skip patching 1st cutscene (special floaty case)
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("05_over_xray.MREA").into(), // life grove (competitive only - watch raise post cutscenes)
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![0x002A01D0], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("12_ice_research_b.MREA").into(),
         move |ps, area| patch_lab_aether_cutscene_trigger(ps, area, version),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("00j_over_hall.MREA").into(), // temple security station
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("15_ice_cave_a.MREA").into(), // frost cave
         move |ps, area| {
             patch_remove_cutscenes(ps, area, vec![0x0029006C, 0x0029006B], vec![], false)
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("15_energycores.MREA").into(), // energy core
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14426,33 +17779,40 @@ fn patch_qol_competitive_cutscenes(
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("07_under_intro_reactor.MREA").into(), // reactor core
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("06_under_intro_freight.MREA").into(), // cargo freight lift
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x001B0100], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("05_under_intro_zoo.MREA").into(), // biohazard containment
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x001E028A], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("05_under_intro_specimen_chamber.MREA").into(), // biotech research area 1
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x002000DB], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("04_maproom_d.MREA").into(), // vault
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("0v_connect_tunnel.MREA").into(), // sun tower
         move |ps, area| {
             patch_remove_cutscenes(ps, area, vec![0x001D00E5, 0x001D00E8], vec![], false)
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("07_ruinedroof.MREA").into(), // training chamber
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14464,17 +17824,20 @@ fn patch_qol_competitive_cutscenes(
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("11_wateryhall.MREA").into(), // watery hall
         move |ps, area| {
             patch_remove_cutscenes(ps, area, vec![0x0029280A, 0x002927FD], vec![], false)
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("18_halfpipe.MREA").into(), // crossway
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("13_over_burningeffigy.MREA").into(), // geothermal core
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14486,7 +17849,8 @@ fn patch_qol_competitive_cutscenes(
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("06_ice_temple.MREA").into(), // chozo ice temple
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14498,32 +17862,42 @@ fn patch_qol_competitive_cutscenes(
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("04_ice_boost_canyon.MREA").into(), // Phendrana canyon
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("05_ice_shorelines.MREA").into(), // ruined courtyard
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("13_ice_vault.MREA").into(), // research core
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("03_mines.MREA").into(), // elite research (keep phazon elite cutscene)
         move |ps, area| {
             patch_remove_cutscenes(ps, area, vec![], vec![0x000D04C8, 0x000D01CF], true)
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("02_mines_shotemup.MREA").into(), // mine security station
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x00070513], vec![], true),
     );
 }
 
-fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
-    patcher.add_scly_patch(
+fn patch_qol_minor_cutscenes<'r, 's>(
+    patcher: &mut PrimePatcher<'r, 's>,
+    cutscenes: &mut CutsceneSkips,
+    version: Version,
+) {
+    cutscenes.add_patch(
+        patcher,
         resource_info!("08_courtyard.MREA").into(), // Arboretum
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14535,7 +17909,8 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("08_mines.MREA").into(), // MQA (just first cutscene)
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14547,21 +17922,25 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("12_ice_research_b.MREA").into(),
         move |ps, area| patch_lab_aether_cutscene_trigger(ps, area, version),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("00j_over_hall.MREA").into(), // temple security station
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("15_ice_cave_a.MREA").into(), // frost cave
         move |ps, area| {
             patch_remove_cutscenes(ps, area, vec![0x0029006C, 0x0029006B], vec![], false)
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("15_energycores.MREA").into(), // energy core
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14580,35 +17959,43 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("10_over_1alavaarea.MREA").into(), // magmoor workstation
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![0x00170153], false), // skip patching 1st cutscene (special floaty case)
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("07_under_intro_reactor.MREA").into(), // reactor core
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("06_under_intro_freight.MREA").into(), // cargo freight lift
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x001B0100], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("05_under_intro_zoo.MREA").into(), // biohazard containment
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x001E028A], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("05_under_intro_specimen_chamber.MREA").into(), // biotech research area 1
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x002000DB], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("05_over_xray.MREA").into(), // life grove
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("01_mainplaza.MREA").into(), // main plaza
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("01_mines_mainplaza.MREA").into(), // main quarry
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14620,21 +18007,25 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("11_over_muddywaters_b.MREA").into(), // lava lake
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("04_maproom_d.MREA").into(), // vault
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("0v_connect_tunnel.MREA").into(), // sun tower
         move |ps, area| {
             patch_remove_cutscenes(ps, area, vec![0x001D00E5, 0x001D00E8], vec![], false)
         }, // Open gate faster
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("07_ruinedroof.MREA").into(), // training chamber
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14646,17 +18037,20 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("11_wateryhall.MREA").into(), // watery hall
         move |ps, area| {
             patch_remove_cutscenes(ps, area, vec![0x0029280A, 0x002927FD], vec![], false)
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("18_halfpipe.MREA").into(), // crossway
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("17_chozo_bowling.MREA").into(), // hall of the elders
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14675,7 +18069,8 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("13_over_burningeffigy.MREA").into(), // geothermal core
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14687,7 +18082,8 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("00h_mines_connect.MREA").into(), // vent shaft
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14699,7 +18095,8 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("06_ice_temple.MREA").into(), // chozo ice temple
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14711,21 +18108,25 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("04_ice_boost_canyon.MREA").into(), // Phendrana canyon
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("05_ice_shorelines.MREA").into(), // ruined courtyard
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("11_ice_observatory.MREA").into(), // Observatory
         move |ps, area| {
             patch_remove_cutscenes(ps, area, vec![0x001E0042, 0x001E000E], vec![], false)
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("08_ice_ridley.MREA").into(), // control tower
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14737,21 +18138,25 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("13_ice_vault.MREA").into(), // research core
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("03_mines.MREA").into(), // elite research (keep phazon elite cutscene)
         move |ps, area| {
             patch_remove_cutscenes(ps, area, vec![], vec![0x000D04C8, 0x000D01CF], true)
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("06_mines_elitebustout.MREA").into(), // omega reserach
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("07_mines_electric.MREA").into(), // central dynamo
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14763,11 +18168,13 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("02_mines_shotemup.MREA").into(), // mine security station
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x00070513], vec![], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("01_ice_plaza.MREA").into(), // phendrana shorelines
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14781,9 +18188,24 @@ fn patch_qol_minor_cutscenes(patcher: &mut PrimePatcher, version: Version) {
     );
 }
 
+// Skips the major cutscenes of every room, without per-room overrides
 pub fn patch_qol_major_cutscenes(patcher: &mut PrimePatcher, shuffle_pickup_position: bool) {
+    let overrides = HashMap::new();
+    let mut cutscenes = CutsceneSkips {
+        overrides: &overrides,
+        patched_rooms: HashSet::new(),
+    };
+    patch_qol_major_cutscene_skips(patcher, &mut cutscenes, shuffle_pickup_position);
+}
+
+fn patch_qol_major_cutscene_skips<'r, 's>(
+    patcher: &mut PrimePatcher<'r, 's>,
+    cutscenes: &mut CutsceneSkips,
+    shuffle_pickup_position: bool,
+) {
     if !shuffle_pickup_position {
-        patcher.add_scly_patch(
+        cutscenes.add_patch(
+            patcher,
             resource_info!("07_ice_chapel.MREA").into(), // chapel of the elders
             move |ps, area| {
                 patch_remove_cutscenes(
@@ -14797,7 +18219,8 @@ pub fn patch_qol_major_cutscenes(patcher: &mut PrimePatcher, shuffle_pickup_posi
         );
     }
 
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("08_courtyard.MREA").into(), // Arboretum
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14809,12 +18232,14 @@ pub fn patch_qol_major_cutscenes(patcher: &mut PrimePatcher, shuffle_pickup_posi
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("01_endcinema.MREA").into(), // Impact Crater Escape Cinema (cause why not)
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], true),
     );
     // +Ghost death cutscene
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("17_chozo_bowling.MREA").into(), // hall of the elders
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14831,11 +18256,13 @@ pub fn patch_qol_major_cutscenes(patcher: &mut PrimePatcher, shuffle_pickup_posi
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("01_ice_plaza.MREA").into(), // phendrana shorelines
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x00020203], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("07_stonehenge.MREA").into(), // artifact temple
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14855,23 +18282,28 @@ pub fn patch_qol_major_cutscenes(patcher: &mut PrimePatcher, shuffle_pickup_posi
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("03_mines.MREA").into(), // elite research
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x000D01A9], vec![], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("19_hive_totem.MREA").into(), // hive totem
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("1a_morphball_shrine.MREA").into(), // ruined shrine
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("03_monkey_lower.MREA").into(), // burn dome
         move |ps, area| patch_remove_cutscenes(ps, area, vec![0x0030017B], vec![], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("22_Flaahgra.MREA").into(), // sunchamber
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14893,7 +18325,8 @@ pub fn patch_qol_major_cutscenes(patcher: &mut PrimePatcher, shuffle_pickup_posi
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("09_ice_lobby.MREA").into(), // research entrance
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14905,15 +18338,18 @@ pub fn patch_qol_major_cutscenes(patcher: &mut PrimePatcher, shuffle_pickup_posi
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("19_ice_thardus.MREA").into(), // Quarantine Cave
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("05_mines_forcefields.MREA").into(), // elite control
         move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], true),
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("08_mines.MREA").into(), // MQA
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14930,7 +18366,8 @@ pub fn patch_qol_major_cutscenes(patcher: &mut PrimePatcher, shuffle_pickup_posi
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("12_mines_eliteboss.MREA").into(), // elite quarters
         move |ps, area| {
             patch_remove_cutscenes(
@@ -14947,7 +18384,8 @@ pub fn patch_qol_major_cutscenes(patcher: &mut PrimePatcher, shuffle_pickup_posi
             )
         },
     );
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         // phazon infusion chamber
         resource_info!("03a_crater.MREA").into(),
         move |ps, area| {
@@ -14967,22 +18405,31 @@ pub fn patch_qol_major_cutscenes(patcher: &mut PrimePatcher, shuffle_pickup_posi
     );
 
     // subchambers 1-4 (see special handling for exo aggro)
-    patcher.add_scly_patch(resource_info!("03b_crater.MREA").into(), move |ps, area| {
-        patch_remove_cutscenes(ps, area, vec![], vec![], false)
-    });
-    patcher.add_scly_patch(resource_info!("03c_crater.MREA").into(), move |ps, area| {
-        patch_remove_cutscenes(ps, area, vec![], vec![], false)
-    });
-    patcher.add_scly_patch(resource_info!("03d_crater.MREA").into(), move |ps, area| {
-        patch_remove_cutscenes(ps, area, vec![], vec![], false)
-    });
-    patcher.add_scly_patch(resource_info!("03e_crater.MREA").into(), move |ps, area| {
-        patch_remove_cutscenes(ps, area, vec![], vec![], true)
-    });
+    cutscenes.add_patch(
+        patcher,
+        resource_info!("03b_crater.MREA").into(),
+        move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
+    );
+    cutscenes.add_patch(
+        patcher,
+        resource_info!("03c_crater.MREA").into(),
+        move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
+    );
+    cutscenes.add_patch(
+        patcher,
+        resource_info!("03d_crater.MREA").into(),
+        move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
+    );
+    cutscenes.add_patch(
+        patcher,
+        resource_info!("03e_crater.MREA").into(),
+        move |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], true),
+    );
 
     // play subchamber 5 cutscene normally (players can't natrually pass through the ceiling of prime's lair)
 
-    patcher.add_scly_patch(
+    cutscenes.add_patch(
+        patcher,
         resource_info!("03f_crater.MREA").into(), // metroid prime lair
         move |ps, area| {
             patch_remove_cutscenes(
@@ -15161,7 +18608,16 @@ pub fn patch_iso<T>(config: PatchConfig, mut pn: T) -> Result<(), String>
 where
     T: structs::ProgressNotifier,
 {
+    patch_log::init(config.log_level, config.log_file.as_deref())?;
+    if let InputIso::Buffered(_, Some(e)) = &config.input_iso {
+        warn!(
+            "Failed to memory-map the input ISO, it was read into memory instead: {}",
+            e
+        );
+    }
     let start_time = Instant::now();
+    version_compat::check_version_support(&config)?;
+
     let mut audio_override_patches: Vec<AudioOverridePatch> = Vec::new();
     for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
         let world = World::from_pak(pak_name).unwrap();
@@ -15203,8 +18659,57 @@ where
             }
         }
     }
-    let audio_override_patches = &audio_override_patches;
+    let audio_override_patches = &audio_override_patches;
+
+    // Pickup jingles and elevator audio which point at local .dsp files need to be copied onto
+    // the disc
+    let pickup_audio = config
+        .level_data
+        .values()
+        .flat_map(|level| level.rooms.values())
+        .flat_map(|room| room.pickups.iter().flatten())
+        .filter_map(|pickup| pickup.attainment_audio.as_ref());
+    let mut extern_attainment_audio: Vec<(String, PathBuf, Vec<u8>)> = Vec::new();
+    for audio in pickup_audio.chain(config.elevator_audio.values()) {
+        let file = match audio {
+            AttainmentAudio::File(file) => file,
+            _ => continue,
+        };
+        // Names without an extension (e.g. "itm_x_short_02") are audio files on the disc
+        if file.to_lowercase().starts_with("/audio/") || Path::new(file).extension().is_none() {
+            continue;
+        }
+
+        let path = match config.extern_assets_dir.as_ref() {
+            Some(dir) if !Path::new(file).exists() => Path::new(dir).join(file),
+            _ => Path::new(file).to_path_buf(),
+        };
+        if !path.is_file() {
+            Err(format!("Could not find the audio file {}", path.display()))?;
+        }
+
+        // Every file is copied to /audio/ under its own name
+        let file_name = audio.file_name().unwrap();
+        if let Some((_, other_path, _)) = extern_attainment_audio
+            .iter()
+            .find(|(n, _, _)| n.to_lowercase() == file_name.to_lowercase())
+        {
+            if *other_path != path {
+                Err(format!(
+                    "The audio files {} and {} have the same name, rename one of them",
+                    other_path.display(),
+                    path.display()
+                ))?;
+            }
+            continue;
+        }
+
+        let bytes =
+            fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        extern_attainment_audio.push((file_name, path, bytes));
+    }
 
+    pn.notify_stage(structs::ProgressStage::Extract);
     let mut ct = Vec::new();
     let mut reader = Reader::new(&config.input_iso[..]);
     let mut gc_disc: structs::GcDisc = reader.read(());
@@ -15216,6 +18721,19 @@ where
         ))?
     }
 
+    for (file_name, _, bytes) in extern_attainment_audio.iter() {
+        if gc_disc.find_file(file_name).is_some() {
+            Err(format!(
+                "Cannot add attainment audio {} because a file with that name already exists",
+                file_name
+            ))?;
+        }
+        gc_disc.add_file(
+            &format!("Audio/{}", &file_name["/audio/".len()..]),
+            structs::FstEntryFile::Unknown(Reader::new(bytes)),
+        )?;
+    }
+
     if config.run_mode == RunMode::ExportLogbook {
         export_logbook(&mut gc_disc, &config)?;
         return Ok(());
@@ -15224,18 +18742,38 @@ where
         return Ok(());
     }
 
-    build_and_run_patches(&mut gc_disc, &config, audio_override_patches)?;
+    build_and_run_patches(&mut gc_disc, &config, audio_override_patches, &mut pn)?;
 
-    println!("Created patches in {:?}", start_time.elapsed());
+    info!("Created patches in {:?}", start_time.elapsed());
 
     {
-        let json_string = serde_json::to_string(&config)
+        // Going through a Value sorts the keys of the config's maps, so that the same config
+        // always produces the same file
+        let json_string = serde_json::to_value(&config)
+            .and_then(|value| serde_json::to_string(&value))
             .map_err(|e| format!("Failed to serialize patch config: {}", e))?;
         writeln!(ct, "{}", json_string).unwrap();
         gc_disc.add_file(
             "randomprime.json",
             structs::FstEntryFile::Unknown(Reader::new(&ct)),
         )?;
+
+        if config.save_protection == SaveProtection::PerLayout {
+            let maker_code = layout_maker_code(&json_string, &config.level_data)?;
+            info!(
+                "Changed the game ID to {} to keep the saves of this layout apart",
+                String::from_utf8_lossy(&[
+                    gc_disc.header.console_id,
+                    gc_disc.header.game_code[0],
+                    gc_disc.header.game_code[1],
+                    gc_disc.header.country_code,
+                    maker_code[0],
+                    maker_code[1],
+                ])
+            );
+            gc_disc.header.maker_code[0] = maker_code[0];
+            gc_disc.header.maker_code[1] = maker_code[1];
+        }
     }
 
     let patches_rel_bytes = match config.version {
@@ -15254,20 +18792,79 @@ where
             "patches.rel",
             structs::FstEntryFile::Unknown(Reader::new(patches_rel_bytes)),
         )?;
+
+        if config.integrity_check {
+            let [config_crc, code_crc, rel_crc] =
+                integrity_signature(&mut gc_disc, config.version, &ct, patches_rel_bytes)?;
+            info!(
+                "Integrity signature (config, code, rel): {:08X} {:08X} {:08X}",
+                config_crc, code_crc, rel_crc
+            );
+        }
+    }
+
+    if config.fast_disc_layout {
+        gc_disc.file_layout = Some(structs::FileLayout {
+            access_order: DISC_ACCESS_ORDER.iter().map(|name| name.to_vec()).collect(),
+            pak_alignment: 0x8000,
+        });
     }
 
+    let journal_path = config.output_iso_journal.as_deref();
+    let sha256_path = config.output_iso_sha256.as_deref();
+    let patch_manifest_path = config.output_iso_patch_manifest.as_deref();
+    let mut written_file = match sha256_path.or(patch_manifest_path) {
+        Some(_) => Some(
+            config
+                .output_iso
+                .try_clone()
+                .map_err(|e| format!("Failed to prepare output file for hashing: {}", e))?,
+        ),
+        None => None,
+    };
     match config.iso_format {
-        IsoFormat::Iso => {
+        IsoFormat::Iso if config.output_iso_manifest.is_some() => {
+            pn.notify_stage(structs::ProgressStage::Write);
+            let manifest_path = config.output_iso_manifest.as_deref().unwrap();
+            let previous_manifest: Option<structs::DiscManifest> = fs::read(manifest_path)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+            // If writing gets interrupted the manifest won't match what's on disc anymore
+            if previous_manifest.is_some() {
+                fs::remove_file(manifest_path)
+                    .map_err(|e| format!("Failed to remove {}: {}", manifest_path, e))?;
+            }
+
             let mut file = config.output_iso;
             file.set_len(structs::GC_DISC_LENGTH as u64)
                 .map_err(|e| format!("Failed to resize output file: {}", e))?;
+            let manifest = gc_disc
+                .write_in_place(&mut file, &mut pn, previous_manifest.as_ref())
+                .map_err(|e| format!("Error writing output file: {}", e))?;
+            pn.notify_flushing_to_disk();
+
+            let manifest_json = serde_json::to_string(&manifest)
+                .map_err(|e| format!("Failed to serialize disc manifest: {}", e))?;
+            fs::write(manifest_path, manifest_json)
+                .map_err(|e| format!("Failed to write {}: {}", manifest_path, e))?;
+        }
+        IsoFormat::Iso => {
+            pn.notify_stage(structs::ProgressStage::Write);
+            let file = config.output_iso;
+            file.set_len(structs::GC_DISC_LENGTH as u64)
+                .map_err(|e| format!("Failed to resize output file: {}", e))?;
+            let mut writer = ResumableWriter::new(file, journal_path)
+                .map_err(|e| format!("Failed to prepare output file for writing: {}", e))?;
             gc_disc
-                .write(&mut file, &mut pn)
+                .write(&mut writer, &mut pn)
                 .map_err(|e| format!("Error writing output file: {}", e))?;
             pn.notify_flushing_to_disk();
         }
         IsoFormat::Gcz => {
-            let mut gcz_writer = GczWriter::new(config.output_iso, structs::GC_DISC_LENGTH as u64)
+            pn.notify_stage(structs::ProgressStage::Compress);
+            let writer = ResumableWriter::new(config.output_iso, journal_path)
+                .map_err(|e| format!("Failed to prepare output file for writing: {}", e))?;
+            let mut gcz_writer = GczWriter::new(writer, structs::GC_DISC_LENGTH as u64)
                 .map_err(|e| format!("Failed to prepare output file for writing: {}", e))?;
             gc_disc
                 .write(&mut *gcz_writer, &mut pn)
@@ -15275,17 +18872,93 @@ where
             pn.notify_flushing_to_disk();
         }
         IsoFormat::Ciso => {
-            let mut ciso_writer = CisoWriter::new(config.output_iso)
+            pn.notify_stage(structs::ProgressStage::Write);
+            let writer = ResumableWriter::new(config.output_iso, journal_path)
+                .map_err(|e| format!("Failed to prepare output file for writing: {}", e))?;
+            let mut ciso_writer = CisoWriter::new(writer)
                 .map_err(|e| format!("Failed to prepare output file for writing: {}", e))?;
             gc_disc
                 .write(&mut ciso_writer, &mut pn)
                 .map_err(|e| format!("Error writing output file: {}", e))?;
             pn.notify_flushing_to_disk();
         }
+        IsoFormat::Bps => {
+            pn.notify_stage(structs::ProgressStage::Write);
+            let out = io::BufWriter::new(config.output_iso);
+            let mut bps_writer =
+                BpsWriter::new(out, &config.input_iso[..], structs::GC_DISC_LENGTH)
+                    .map_err(|e| format!("Failed to prepare output file for writing: {}", e))?;
+            gc_disc
+                .write(&mut bps_writer, &mut pn)
+                .map_err(|e| format!("Error writing output file: {}", e))?;
+            bps_writer
+                .finish()
+                .map_err(|e| format!("Error writing output file: {}", e))?;
+            pn.notify_flushing_to_disk();
+        }
     };
+    if let Some(journal_path) = journal_path {
+        resumable_writer::remove_journal(journal_path)
+            .map_err(|e| format!("Failed to remove {}: {}", journal_path, e))?;
+    }
+    if let (Some(sha256_path), Some(file)) = (sha256_path, written_file.as_mut()) {
+        let digest =
+            sha256::hash_file(file).map_err(|e| format!("Failed to hash output file: {}", e))?;
+        let digest = sha256::to_hex(&digest);
+        info!("SHA-256: {}", digest);
+
+        let output_name = Path::new(sha256_path.trim_end_matches(".sha256"))
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        fs::write(sha256_path, format!("{}  {}\n", digest, output_name))
+            .map_err(|e| format!("Failed to write {}: {}", sha256_path, e))?;
+    }
+    if let (Some(manifest_path), Some(file)) = (patch_manifest_path, written_file.as_ref()) {
+        let iso = unsafe { memmap::Mmap::map(file) }
+            .map_err(|e| format!("Failed to read back output file: {}", e))?;
+        let manifest = PatchManifest::from_iso(&iso)?;
+        let manifest_json = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize patch manifest: {}", e))?;
+        fs::write(manifest_path, manifest_json)
+            .map_err(|e| format!("Failed to write {}: {}", manifest_path, e))?;
+    }
     Ok(())
 }
 
+// Roughly the order the files are read in during a playthrough: boot, the front end, then each
+// world in the order they are usually visited
+const DISC_ACCESS_ORDER: &[&[u8]] = &[
+    b"default.dol",
+    b"patches.rel",
+    b"rel_config.bin",
+    b"AudioGrp.pak",
+    b"GGuiSys.pak",
+    b"NoARAM.pak",
+    b"MiscData.pak",
+    b"SamusGun.pak",
+    b"SamGunFx.pak",
+    b"TestAnim.pak",
+    b"Tweaks.pak",
+    b"00_first_start.thp",
+    b"01_startloop.thp",
+    b"02_start_fileselect_A.thp",
+    b"02_start_fileselect_B.thp",
+    b"02_start_fileselect_C.thp",
+    b"03_fileselectloop.thp",
+    b"04_fileselect_playgame_A.thp",
+    b"04_fileselect_playgame_B.thp",
+    b"04_fileselect_playgame_C.thp",
+    b"Metroid1.pak", // Frigate Orpheon
+    b"Metroid4.pak", // Tallon Overworld
+    b"Metroid2.pak", // Chozo Ruins
+    b"Metroid6.pak", // Magmoor Caverns
+    b"Metroid3.pak", // Phendrana Drifts
+    b"Metroid5.pak", // Phazon Mines
+    b"Metroid7.pak", // Impact Crater
+    b"Metroid8.pak", // End cinema
+];
+
 fn export_logbook(gc_disc: &mut structs::GcDisc, config: &PatchConfig) -> Result<(), String> {
     let filenames = [
         "AudioGrp.pak",
@@ -15397,7 +19070,8 @@ fn export_assets(gc_disc: &mut structs::GcDisc, config: &PatchConfig) -> Result<
         }
     }
 
-    let (_, _, _, _, _, _, _, _, custom_assets) = collect_game_resources(gc_disc, None, config)?;
+    let (_, _, _, _, _, _, _, _, custom_assets) =
+        collect_game_resources(gc_disc, None, &[], config)?;
 
     for resource in custom_assets {
         let mut bytes = vec![];
@@ -15411,13 +19085,20 @@ fn export_assets(gc_disc: &mut structs::GcDisc, config: &PatchConfig) -> Result<
     Ok(())
 }
 
-fn build_and_run_patches<'r>(
+fn build_and_run_patches<'r, T>(
     gc_disc: &mut structs::GcDisc<'r>,
     config: &PatchConfig,
     audio_override_patches: &'r Vec<AudioOverridePatch>,
-) -> Result<(), String> {
-    let morph_ball_size = config.ctwk_config.morph_ball_size.unwrap_or(1.0);
+    pn: &mut T,
+) -> Result<(), String>
+where
+    T: structs::ProgressNotifier,
+{
     let player_size = config.ctwk_config.player_size.unwrap_or(1.0);
+    let mut morph_ball_size = config.ctwk_config.morph_ball_size.unwrap_or(1.0);
+    if config.ctwk_config.player_size_physics.unwrap_or(false) {
+        morph_ball_size *= player_size;
+    }
 
     let remove_ball_color = morph_ball_size < 0.999;
     let remove_control_disabler = player_size < 0.999 || morph_ball_size < 0.999;
@@ -15511,6 +19192,10 @@ fn build_and_run_patches<'r>(
             items.push(PickupType::EnergyTank);
 
             for room_info in rooms.iter() {
+                if config.exclude_rooms.contains(&room_info.room_id.to_u32()) {
+                    continue;
+                }
+
                 let key = room_info.name().trim();
                 if !level.rooms.contains_key(key) {
                     level.rooms.insert(key.to_string(), RoomConfig::default());
@@ -15537,21 +19222,200 @@ fn build_and_run_patches<'r>(
                         model: None,
                         scan_text: None,
                         hudmemo_text: None,
+                        scan_text_translations: None,
+                        hudmemo_text_translations: None,
                         respawn: None,
                         position: None,
                         modal_hudmemo: None,
+                        attainment_audio: None,
+                        sound_id: None,
                         jumbo_scan: None,
                         destination: None,
                         show_icon: None,
                         invisible_and_silent: None,
                         thermal_only: None,
                         scale: None,
+                        key: None,
                     }]);
                 }
             }
         }
     }
 
+    if config.fog_shuffle {
+        let palette = fog_shuffle_palette(config.colorblind_mode);
+
+        for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
+            let world = World::from_pak(pak_name).unwrap();
+
+            if !level_data.contains_key(world.to_json_key()) {
+                level_data.insert(
+                    world.to_json_key().to_string(),
+                    LevelConfig {
+                        transports: HashMap::new(),
+                        rooms: HashMap::new(),
+                    },
+                );
+            }
+
+            let level = level_data.get_mut(world.to_json_key()).unwrap();
+
+            for room_info in rooms.iter() {
+                if config.exclude_rooms.contains(&room_info.room_id.to_u32()) {
+                    continue;
+                }
+
+                let key = room_info.name().trim();
+                if !level.rooms.contains_key(key) {
+                    level.rooms.insert(key.to_string(), RoomConfig::default());
+                }
+
+                // Explicit fog edits always win over the shuffle
+                let room = level.rooms.get_mut(key).unwrap();
+                if room.fog.is_some() {
+                    continue;
+                }
+
+                let base = palette.choose(&mut rng).unwrap();
+                let mut color = [0.0, 0.0, 0.0, 0.0];
+                for i in 0..3 {
+                    color[i] = (base[i] + rng.gen_range(-0.05, 0.05)).clamp(0.0, 1.0);
+                }
+
+                let near = rng.gen_range(10.0, 30.0);
+                let far = near + rng.gen_range(20.0, 60.0);
+
+                room.fog = Some(FogConfig {
+                    id: None,
+                    layer: None,
+                    active: None,
+                    mode: Some(1),
+                    explicit: None,
+                    color: Some(color),
+                    range: Some([near, far]),
+                    color_delta: None,
+                    range_delta: None,
+                });
+            }
+        }
+    }
+
+    // Rooms which go dark in the blackout challenge mode
+    let mut blackout_rooms = HashSet::new();
+    if let Some(blackout) = config.blackout.as_ref() {
+        // Boss fights are left alone, fighting them in the dark isn't fun
+        let boss_rooms = [
+            resource_info!("07_intro_reactor.MREA").res_id,
+            resource_info!("22_Flaahgra.MREA").res_id,
+            resource_info!("07_ice_chapel.MREA").res_id,
+            resource_info!("19_ice_thardus.MREA").res_id,
+            resource_info!("12_mines_eliteboss.MREA").res_id,
+            resource_info!("07_stonehenge.MREA").res_id,
+            resource_info!("03a_crater.MREA").res_id,
+            resource_info!("03b_crater.MREA").res_id,
+            resource_info!("03c_crater.MREA").res_id,
+            resource_info!("03d_crater.MREA").res_id,
+            resource_info!("03e_crater.MREA").res_id,
+            resource_info!("03f_crater.MREA").res_id,
+        ];
+
+        let mut candidates = Vec::new();
+        for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
+            let world = World::from_pak(pak_name).unwrap();
+
+            for room_info in rooms.iter() {
+                if boss_rooms.contains(&room_info.room_id.to_u32())
+                    || config.exclude_rooms.contains(&room_info.room_id.to_u32())
+                {
+                    continue;
+                }
+
+                // Explicit lighting edits always win over the blackout
+                let explicit_lighting = level_data
+                    .get(world.to_json_key())
+                    .and_then(|level| level.rooms.get(room_info.name().trim()))
+                    .map(|room| room.lighting.is_some())
+                    .unwrap_or(false);
+                if explicit_lighting {
+                    continue;
+                }
+
+                candidates.push((world, room_info));
+            }
+        }
+
+        candidates.shuffle(&mut rng);
+        candidates.truncate(blackout.room_count.unwrap_or(20) as usize);
+
+        for (world, room_info) in candidates {
+            let level = level_data
+                .entry(world.to_json_key().to_string())
+                .or_default();
+            let room = level
+                .rooms
+                .entry(room_info.name().trim().to_string())
+                .or_default();
+
+            room.lighting = Some(LightingConfig {
+                ambient_color: None,
+                brightness_scale: Some(blackout.brightness.unwrap_or(0.1)),
+                world_lighting: None,
+                light_params: None,
+            });
+
+            if blackout.thermal_visor_buff.unwrap_or(false) {
+                blackout_rooms.insert(room_info.room_id.to_u32());
+            }
+        }
+    }
+
+    // Rooms hazards are placed in, with the kind of hazard and the seed placing them
+    let mut hazard_rooms = HashMap::new();
+    if let Some(hazards) = config.hazards.as_ref() {
+        let kinds = hazards
+            .kinds
+            .clone()
+            .unwrap_or_else(|| vec![HazardKind::AcidRain, HazardKind::SporeCloud]);
+        let excluded: Vec<(&str, &str)> = hazards
+            .exclude_rooms
+            .iter()
+            .flatten()
+            .filter_map(|room| room.split_once(':'))
+            .map(|(world, room)| (world.trim(), room.trim()))
+            .collect();
+
+        let mut candidates = Vec::new();
+        for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
+            let world = World::from_pak(pak_name).unwrap();
+
+            for room_info in rooms.iter() {
+                let room_name = room_info.name().trim();
+                let kind = HAZARD_ROOMS
+                    .iter()
+                    .find(|(w, r, _)| *w == world.to_json_key() && *r == room_name)
+                    .map(|(_, _, kind)| *kind);
+                let kind = match kind {
+                    Some(kind) if kinds.contains(&kind) => kind,
+                    _ => continue,
+                };
+                if config.exclude_rooms.contains(&room_info.room_id.to_u32())
+                    || excluded.contains(&(world.to_json_key(), room_name))
+                {
+                    continue;
+                }
+
+                candidates.push((room_info.room_id.to_u32(), kind));
+            }
+        }
+
+        candidates.shuffle(&mut rng);
+        candidates.truncate(hazards.room_count.unwrap_or(10) as usize);
+
+        for (room_id, kind) in candidates {
+            hazard_rooms.insert(room_id, (kind, rng.gen::<u64>()));
+        }
+    }
+
     let frigate_done_room = {
         let mut destination_name = "Tallon:Landing Site";
         let frigate_level = level_data.get(World::FrigateOrpheon.to_json_key());
@@ -15583,13 +19447,27 @@ fn build_and_run_patches<'r>(
         destination
     };
 
-    let artifact_totem_strings = build_artifact_temple_totem_scan_strings(
+    let (artifact_totem_strings, artifact_totem_reveals) = build_artifact_temple_totem_scan_strings(
         &level_data,
         &mut rng,
         config.artifact_hints.clone(),
+        // The totems' strings aren't patched with the vanilla layout
+        config
+            .artifact_totem_hints
+            .as_ref()
+            .filter(|_| !config.force_vanilla_layout),
+        config.required_artifact_count,
     );
 
-    let show_starting_memo = config.starting_memo.is_some();
+    // The hint layers of Artifact Temple switched by each artifact which reveals a totem's hint
+    let totem_hint_layers: HashMap<u32, (u32, u32)> = artifact_totem_reveals
+        .iter()
+        .enumerate()
+        .map(|(i, reveal)| (reveal.artifact.kind(), artifact_totem_hint_layers(i)))
+        .collect();
+    let totem_hint_layers = &totem_hint_layers;
+
+    let show_starting_memo = config.starting_memo.is_some() || config.loadout_memo;
 
     let starting_memo = {
         if config.starting_memo.is_some() {
@@ -15599,6 +19477,7 @@ fn build_and_run_patches<'r>(
         }
     };
 
+    pn.notify_stage(structs::ProgressStage::BuildAssets);
     let (
         game_resources,
         pickup_hudmemos,
@@ -15609,7 +19488,7 @@ fn build_and_run_patches<'r>(
         savw_scan_logbook_category,
         extern_models,
         _,
-    ) = collect_game_resources(gc_disc, starting_memo, config)?;
+    ) = collect_game_resources(gc_disc, starting_memo, &artifact_totem_reveals, config)?;
 
     let extern_models = &extern_models;
     let game_resources = &game_resources;
@@ -15623,6 +19502,9 @@ fn build_and_run_patches<'r>(
     let local_savw_scans_to_add = &local_savw_scans_to_add;
     let savw_scan_logbook_category = &savw_scan_logbook_category;
 
+    let starting_scans = logbook_scans(gc_disc, &config.starting_logbook);
+    let starting_scans = &starting_scans;
+
     let missile_station_refill_strings =
         vec!["&just=center;Ammunition fully replenished.".to_string()];
     let missile_station_refill_strings = &missile_station_refill_strings;
@@ -15742,7 +19624,12 @@ fn build_and_run_patches<'r>(
     );
     let file_select_play_game_fmv = gc_disc.find_file(&n).unwrap().file().unwrap().clone();
 
+    let asset_cache = AssetCache::new(&config.cache_dir);
     let mut patcher = PrimePatcher::new();
+    patcher.set_excluded_rooms(&config.exclude_rooms);
+    patcher.set_validate_areas(config.validate_output);
+    patcher.set_build_area_dependencies(config.auto_dependencies);
+    patcher.set_prune_area_dependencies(config.prune_dependencies);
 
     // Add the freeze effect assets required by CPlayer::Freeze()
     if config.enable_ice_traps {
@@ -15754,6 +19641,17 @@ fn build_and_run_patches<'r>(
     // Add the pickup icon
     patcher.add_file_patch(b"GGuiSys.pak", |file| add_map_pickup_icon_txtr(file));
 
+    if let Some(beams) = config.cosmetics.beams.as_ref() {
+        let remaps = beams.color_remaps(config.seed);
+        let projectiles = beams.projectiles();
+        if remaps.iter().any(Option::is_some) || projectiles.iter().any(Option::is_some) {
+            let asset_cache = asset_cache.clone();
+            patcher.add_file_patch(b"SamGunFx.pak", move |file| {
+                patch_beam_cosmetics(file, remaps, projectiles, &asset_cache)
+            });
+        }
+    }
+
     patcher.add_file_patch(b"opening.bnr", |file| patch_bnr(file, &config.game_banner));
 
     if let Some(flaahgra_music_files) = &config.flaahgra_music_files {
@@ -15774,7 +19672,7 @@ fn build_and_run_patches<'r>(
         );
         patcher.add_resource_patch(
             (&[b"Tweaks.Pak"], 0x26F1E0C1, FourCC::from_bytes(b"CTWK")), // Player.CTWK
-            |res| patch_ctwk_player(res, &config.ctwk_config),
+            |res| patch_ctwk_player(res, &config.ctwk_config, 1.0 / 60.0),
         );
         patcher.add_resource_patch(
             (&[b"Tweaks.Pak"], 0x8D698EC0, FourCC::from_bytes(b"CTWK")), // PlayerGun.CTWK
@@ -15786,14 +19684,18 @@ fn build_and_run_patches<'r>(
         );
         patcher.add_resource_patch(
             (&[b"Tweaks.Pak"], 0x2DFB63BB, FourCC::from_bytes(b"CTWK")), // GuiColors.CTWK
-            |res| patch_ctwk_gui_colors(res, &config.ctwk_config),
+            |res| patch_ctwk_gui_colors(res, &config.ctwk_config, config.cosmetics.hud.as_ref()),
         );
     } else {
         patcher.add_resource_patch(resource_info!("Game.CTWK").into(), |res| {
             patch_ctwk_game(res, &config.ctwk_config)
         });
         patcher.add_resource_patch(resource_info!("Player.CTWK").into(), |res| {
-            patch_ctwk_player(res, &config.ctwk_config)
+            patch_ctwk_player(
+                res,
+                &config.ctwk_config,
+                frame_time(config.version, config.pal_timing),
+            )
         });
         patcher.add_resource_patch(resource_info!("PlayerGun.CTWK").into(), |res| {
             patch_ctwk_player_gun(res, &config.ctwk_config)
@@ -15802,7 +19704,7 @@ fn build_and_run_patches<'r>(
             patch_ctwk_ball(res, &config.ctwk_config)
         });
         patcher.add_resource_patch(resource_info!("GuiColors.CTWK").into(), |res| {
-            patch_ctwk_gui_colors(res, &config.ctwk_config)
+            patch_ctwk_gui_colors(res, &config.ctwk_config, config.cosmetics.hud.as_ref())
         });
 
         /* TODO: add more tweaks
@@ -15824,26 +19726,88 @@ fn build_and_run_patches<'r>(
         */
     }
 
-    patcher.add_resource_patch(resource_info!("FRME_CombatHud.FRME").into(), move |res| {
-        patch_combat_hud_color(res, &config.ctwk_config)
-    });
-    patcher.add_resource_patch(resource_info!("FRME_ScanHudFlat.FRME").into(), move |res| {
-        patch_combat_hud_color(res, &config.ctwk_config)
-    });
-    patcher.add_resource_patch(resource_info!("FRME_ScanHud.FRME").into(), move |res| {
-        patch_combat_hud_color(res, &config.ctwk_config)
-    });
+    // The X-Ray and morph ball HUDs are only tinted if they are configured explicitly
+    let hud_cosmetics = config.cosmetics.hud.clone().unwrap_or_default();
+    let hud_color = hud_cosmetics.hud_color.or(config.ctwk_config.hud_color);
+    for (frme, visor) in [
+        (resource_info!("FRME_CombatHud.FRME"), HudVisor::Combat),
+        (resource_info!("FRME_ScanHudFlat.FRME"), HudVisor::Scan),
+        (resource_info!("FRME_ScanHud.FRME"), HudVisor::Scan),
+        (resource_info!("FRME_ThermalHud.FRME"), HudVisor::Thermal),
+        (resource_info!("FRME_XRayHudNew.FRME"), HudVisor::XRay),
+        (resource_info!("FRME_BallHud.FRME"), HudVisor::MorphBall),
+    ] {
+        let mut tint = hud_cosmetics.visor_tint(visor);
+        if !matches!(visor, HudVisor::XRay | HudVisor::MorphBall) {
+            tint = tint.or(hud_color);
+        }
+        patcher.add_resource_patch(frme.into(), move |res| patch_combat_hud_color(res, tint));
+    }
     patcher.add_resource_patch(resource_info!("FRME_MapScreen.FRME").into(), move |res| {
-        patch_combat_hud_color(res, &config.ctwk_config)
-    });
-    patcher.add_resource_patch(resource_info!("FRME_ThermalHud.FRME").into(), move |res| {
-        patch_combat_hud_color(res, &config.ctwk_config)
+        patch_combat_hud_color(res, hud_color)
     });
 
+    // The power bomb counter needs room for a second digit as soon as the capacity is 10, which
+    // the quality of life changes always make room for
+    let power_bomb_digits = hud_digits(config, PickupType::PowerBomb, 99, 3);
+    if config.qol_cosmetic || power_bomb_digits > 1 {
+        patcher.add_resource_patch(resource_info!("FRME_BallHud.FRME").into(), move |res| {
+            patch_morphball_hud(res, power_bomb_digits)
+        });
+    }
+
+    let missile_digits = hud_digits(config, PickupType::Missile, 999, 4);
+    if missile_digits > 3 {
+        for frme in [
+            resource_info!("FRME_CombatHud.FRME"),
+            resource_info!("FRME_ScanHudFlat.FRME"),
+            resource_info!("FRME_ScanHud.FRME"),
+            resource_info!("FRME_ThermalHud.FRME"),
+            resource_info!("FRME_XRayHudNew.FRME"),
+        ] {
+            patcher.add_resource_patch(frme.into(), move |res| {
+                patch_missile_hud_digits(res, missile_digits)
+            });
+        }
+    }
+
+    if config.display.widescreen.unwrap_or(false) {
+        patcher.add_resource_patch(
+            resource_info!("FRME_CombatHud.FRME").into(),
+            patch_hud_widescreen,
+        );
+        patcher.add_resource_patch(
+            resource_info!("FRME_ScanHudFlat.FRME").into(),
+            patch_hud_widescreen,
+        );
+        patcher.add_resource_patch(
+            resource_info!("FRME_ScanHud.FRME").into(),
+            patch_hud_widescreen,
+        );
+        patcher.add_resource_patch(
+            resource_info!("FRME_ThermalHud.FRME").into(),
+            patch_hud_widescreen,
+        );
+        patcher.add_resource_patch(
+            resource_info!("FRME_BallHud.FRME").into(),
+            patch_hud_widescreen,
+        );
+    }
+
     patcher.add_scly_patch(resource_info!("07_stonehenge.MREA").into(), |ps, area| {
         fix_artifact_of_truth_requirements(ps, area, config)
     });
 
+    if !artifact_totem_reveals.is_empty() {
+        let artifact_totem_reveals = &artifact_totem_reveals;
+        patcher.add_scly_patch(
+            resource_info!("07_stonehenge.MREA").into(),
+            move |ps, area| {
+                patch_artifact_totem_hint_layers(ps, area, game_resources, artifact_totem_reveals)
+            },
+        );
+    }
+
     if config.skip_ridley {
         patcher.add_scly_patch(
             resource_info!("07_stonehenge.MREA").into(),
@@ -15860,6 +19824,7 @@ fn build_and_run_patches<'r>(
     }
 
     // Add hard-coded POI
+    patcher.set_skip_excluded_rooms(true);
     if config.qol_pickup_scans {
         patcher.add_scly_patch(
             resource_info!("01_over_mainplaza.MREA").into(), // Tallon Landing Site - Behind ship item
@@ -15883,6 +19848,7 @@ fn build_and_run_patches<'r>(
             },
         );
     }
+    patcher.set_skip_excluded_rooms(false);
     patcher.add_scly_patch(
         resource_info!("06_under_intro_freight.MREA").into(),
         move |ps, area| {
@@ -15938,6 +19904,13 @@ fn build_and_run_patches<'r>(
         });
     }
 
+    if config.skip_essence {
+        patcher.add_scly_patch(
+            resource_info!("03f_crater.MREA").into(), // lair
+            patch_lair_skip_essence,
+        );
+    }
+
     if config.qol_game_breaking {
         patcher.add_scly_patch(
             resource_info!("07_intro_reactor.MREA").into(),
@@ -15962,7 +19935,7 @@ fn build_and_run_patches<'r>(
             });
             patcher.add_scly_patch(
                 resource_info!("03e_f_crater.MREA").into(), // subchamber five
-                move |ps, area| patch_subchamber_five_essence_permadeath(ps, area),
+                move |ps, area| patch_subchamber_five_essence_permadeath(ps, area, config.skip_exo),
             );
             patcher.add_scly_patch(
                 resource_info!("03f_crater.MREA").into(), // lair
@@ -15983,6 +19956,32 @@ fn build_and_run_patches<'r>(
         }
     }
 
+    if config.skip_exo {
+        // Re-use the permadeath layers, but start with the bosses already gone
+        let bosses_moved = config.qol_game_breaking && boss_permadeath;
+        patcher.add_scly_patch(resource_info!("03a_crater.MREA").into(), move |ps, area| {
+            patch_skip_exo(ps, area, game_resources, bosses_moved)
+        });
+        patcher.add_scly_patch(resource_info!("03b_crater.MREA").into(), move |ps, area| {
+            patch_skip_exo(ps, area, game_resources, bosses_moved)
+        });
+        patcher.add_scly_patch(resource_info!("03c_crater.MREA").into(), move |ps, area| {
+            patch_skip_exo(ps, area, game_resources, bosses_moved)
+        });
+        patcher.add_scly_patch(resource_info!("03d_crater.MREA").into(), move |ps, area| {
+            patch_skip_exo(ps, area, game_resources, bosses_moved)
+        });
+        patcher.add_scly_patch(resource_info!("03e_crater.MREA").into(), move |ps, area| {
+            patch_skip_exo(ps, area, game_resources, bosses_moved)
+        });
+        if !bosses_moved {
+            patcher.add_scly_patch(
+                resource_info!("03e_f_crater.MREA").into(), // subchamber five
+                move |ps, area| patch_subchamber_five_essence_permadeath(ps, area, true),
+            );
+        }
+    }
+
     // Patch pickups
     let mut seed: u64 = 1;
     for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
@@ -15990,6 +19989,7 @@ fn build_and_run_patches<'r>(
 
         for room_info in rooms.iter() {
             let room_idx = room_info.index();
+            let excluded = config.exclude_rooms.contains(&room_info.room_id.to_u32());
 
             if remove_control_disabler {
                 patcher.add_scly_patch(
@@ -16029,6 +20029,7 @@ fn build_and_run_patches<'r>(
             {
                 // this is a hack because something is getting messed up with the MREA objects if this patch never gets used
                 let remove_otrs = config.qol_cosmetic
+                    && !excluded
                     && !(config.shuffle_pickup_position
                         && room_info.room_id.to_u32() == 0x40C548E9)
                     && !config.force_vanilla_layout;
@@ -16043,6 +20044,8 @@ fn build_and_run_patches<'r>(
 
             if config.qol_cutscenes == CutsceneMode::Major
                 && is_elevator(room_info.room_id.to_u32())
+                && config.cutscene_overrides.get(&room_info.room_id.to_u32())
+                    != Some(&CutsceneOverride::Keep)
             {
                 patcher.add_scly_patch(
                     (pak_name.as_bytes(), room_info.room_id.to_u32()),
@@ -16052,11 +20055,27 @@ fn build_and_run_patches<'r>(
 
             let map_default_state = {
                 let mut map_default_state = config.map_default_state;
+                if config
+                    .starting_maps
+                    .iter()
+                    .any(|x| x == world.to_json_key())
+                    && map_default_state != MapaObjectVisibilityMode::Never
+                {
+                    map_default_state = MapaObjectVisibilityMode::Always;
+                }
+                let room_key = format!("{}:{}", world.to_json_key(), room_info.name().trim());
+                if config.starting_visited_rooms.contains(&room_key) {
+                    map_default_state = MapaObjectVisibilityMode::Always;
+                }
                 if let Some(level) = level_data.get(world.to_json_key()) {
                     if let Some(room) = level.rooms.get(room_info.name().trim()) {
                         if let Some(state) = room.map_default_state {
                             map_default_state = state;
                         }
+                        if let Some(state) = room.map_config.as_ref().and_then(|x| x.default_state)
+                        {
+                            map_default_state = state;
+                        }
                     }
                 }
                 map_default_state
@@ -16070,11 +20089,31 @@ fn build_and_run_patches<'r>(
                 move |res| set_room_map_default_state(res, map_default_state),
             );
 
+            let map_config = level_data
+                .get(world.to_json_key())
+                .and_then(|level| level.rooms.get(room_info.name().trim()))
+                .and_then(|room| room.map_config.as_ref());
+            if let Some(map_config) = map_config {
+                patcher.add_resource_patch(
+                    (
+                        &[pak_name.as_bytes()],
+                        room_info.mapa_id.to_u32(),
+                        reader_writer::FourCC::from_bytes(b"MAPA"),
+                    ),
+                    move |res| patch_room_map(res, map_config, room_info.room_id.to_u32()),
+                );
+            }
+
+            let shuffle_bounds = level_data
+                .get(world.to_json_key())
+                .and_then(|level| level.rooms.get(room_info.name().trim()))
+                .and_then(|room| room.shuffle_bounds.as_ref());
+
             // Get list of patches specified for this room
             let (pickups, scans, doors, hudmemos) = {
                 let mut _pickups = Vec::new();
                 let mut _scans = Vec::new();
-                let mut _doors = HashMap::<u32, DoorConfig>::new();
+                let mut _doors = Vec::<(u32, DoorConfig)>::new();
                 let mut _hudmemos = Vec::new();
 
                 let level = level_data.get(world.to_json_key());
@@ -16091,7 +20130,9 @@ fn build_and_run_patches<'r>(
                         }
 
                         if room.doors.is_some() {
-                            _doors = room.doors.clone().unwrap();
+                            // Sorted to match the order the door scans were created in
+                            _doors = room.doors.clone().unwrap().into_iter().collect();
+                            _doors.sort_by_key(|(dock_num, _)| *dock_num);
                         }
 
                         if room.hudmemos.is_some() {
@@ -16164,6 +20205,34 @@ fn build_and_run_patches<'r>(
                             }
                         }
 
+                        if let Some(stations) = room.stations.as_ref() {
+                            for config in stations {
+                                patcher.add_scly_patch(
+                                    (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                                    move |ps, area| {
+                                        patch_add_station(ps, area, game_resources, config.clone())
+                                    },
+                                );
+                            }
+                        }
+
+                        if let Some(teleporters) = room.teleporters.as_ref() {
+                            for teleporter in teleporters {
+                                patcher.add_scly_patch(
+                                    (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                                    move |ps, area| {
+                                        patch_add_teleporter(
+                                            ps,
+                                            area,
+                                            game_resources,
+                                            teleporter.clone(),
+                                            config.version,
+                                        )
+                                    },
+                                );
+                            }
+                        }
+
                         if room.relays.is_some() {
                             for relay_config in room.relays.as_ref().unwrap() {
                                 patcher.add_scly_patch(
@@ -16235,6 +20304,56 @@ fn build_and_run_patches<'r>(
                             }
                         }
 
+                        if let Some(objectives) = room.objectives.as_ref() {
+                            for config in objectives {
+                                // Opening a door is what its force field does when shot
+                                let mut door_connections = Vec::new();
+                                for dock_num in config.open_doors.iter().flatten() {
+                                    let door_location = room_info
+                                        .door_locations
+                                        .iter()
+                                        .find(|dl| dl.dock_number == *dock_num)
+                                        .and_then(|dl| dl.door_location.map(|door| (dl, door)));
+                                    let (door_location, door) = door_location.ok_or_else(|| {
+                                        format!(
+                                            "Tried to open dock #{} in {}, which does not have a door",
+                                            dock_num,
+                                            room_info.name()
+                                        )
+                                    })?;
+
+                                    door_connections.push(structs::Connection {
+                                        state: structs::ConnectionState::ZERO,
+                                        message: structs::ConnectionMsg::SET_TO_ZERO,
+                                        target_object_id: door.instance_id,
+                                    });
+                                    for location in door_location
+                                        .door_force_locations
+                                        .iter()
+                                        .chain(door_location.door_shield_locations.iter())
+                                    {
+                                        door_connections.push(structs::Connection {
+                                            state: structs::ConnectionState::ZERO,
+                                            message: structs::ConnectionMsg::DEACTIVATE,
+                                            target_object_id: location.instance_id,
+                                        });
+                                    }
+                                }
+
+                                patcher.add_scly_patch(
+                                    (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                                    move |ps, area| {
+                                        patch_add_objective(
+                                            ps,
+                                            area,
+                                            config.clone(),
+                                            door_connections.clone(),
+                                        )
+                                    },
+                                );
+                            }
+                        }
+
                         if let Some(switches) = room.switches.as_ref() {
                             for config in switches {
                                 patcher.add_scly_patch(
@@ -16350,6 +20469,15 @@ fn build_and_run_patches<'r>(
                             }
                         }
 
+                        if let Some(effects) = room.effects.as_ref() {
+                            for config in effects {
+                                patcher.add_scly_patch(
+                                    (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                                    move |ps, area| patch_add_effect(ps, area, config.clone()),
+                                );
+                            }
+                        }
+
                         if room.streamed_audios.is_some() {
                             for config in room.streamed_audios.as_ref().unwrap() {
                                 patcher.add_scly_patch(
@@ -16573,18 +20701,46 @@ fn build_and_run_patches<'r>(
                             );
                         }
 
+                        if let Some(lighting) = room.lighting.as_ref() {
+                            patcher.add_scly_patch(
+                                (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                                move |ps, area| patch_room_lighting(ps, area, lighting.clone()),
+                            );
+                        }
+
                         let submerge = room.submerge.unwrap_or(false);
                         if room.remove_water.unwrap_or(false) || submerge {
                             patcher.add_scly_patch(
                                 (pak_name.as_bytes(), room_info.room_id.to_u32()),
-                                move |_ps, area| patch_remove_water(_ps, area, submerge),
+                                move |_ps, area| patch_remove_water(_ps, area, submerge),
+                            );
+                        }
+
+                        if submerge {
+                            patcher.add_scly_patch(
+                                (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                                move |_ps, area| {
+                                    patch_submerge_room(_ps, area, game_resources, true)
+                                },
                             );
                         }
 
-                        if submerge {
+                        if let Some(physics) = room.physics.as_ref() {
+                            if physics.underwater_physics == Some(true) && !submerge {
+                                patcher.add_scly_patch(
+                                    (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                                    move |_ps, area| {
+                                        patch_submerge_room(_ps, area, game_resources, false)
+                                    },
+                                );
+                            }
+
+                            let global_gravity = config.ctwk_config.gravity.unwrap_or(1.0);
                             patcher.add_scly_patch(
                                 (pak_name.as_bytes(), room_info.room_id.to_u32()),
-                                move |_ps, area| patch_submerge_room(_ps, area, game_resources),
+                                move |ps, area| {
+                                    patch_room_physics(ps, area, physics.clone(), global_gravity)
+                                },
                             );
                         }
 
@@ -16608,7 +20764,7 @@ fn build_and_run_patches<'r>(
             let mut idx = 0;
             let pickups_config_len = pickups.len();
             for pickup_location in room_info.pickup_locations.iter() {
-                let pickup = {
+                let mut pickup = {
                     if idx >= pickups_config_len {
                         PickupConfig {
                             id: None,
@@ -16617,22 +20773,30 @@ fn build_and_run_patches<'r>(
                             max_increase: Some(0),
                             position: None,
                             hudmemo_text: None,
+                            scan_text_translations: None,
+                            hudmemo_text_translations: None,
                             scan_text: None,
                             model: None,
                             respawn: None,
                             modal_hudmemo: None,
+                            attainment_audio: None,
+                            sound_id: None,
                             jumbo_scan: None,
                             destination: None,
                             show_icon: None,
                             invisible_and_silent: None,
                             thermal_only: None,
                             scale: None,
+                            key: None,
                         }
                     } else {
                         pickups[idx].clone() // TODO: cloning is suboptimal
                     }
                 };
-                let show_icon = pickup.show_icon.unwrap_or(false);
+                let map_icon = pickup
+                    .show_icon
+                    .unwrap_or_default()
+                    .texture_index(PickupType::from_str(&pickup.pickup_type));
 
                 let key = PickupHashKey {
                     level_id: world.mlvl(),
@@ -16640,6 +20804,21 @@ fn build_and_run_patches<'r>(
                     pickup_idx: idx as u32,
                 };
 
+                let item_acquisition = config
+                    .item_acquisition
+                    .get(&PickupType::from_str(&pickup.pickup_type))
+                    .copied()
+                    .unwrap_or_default();
+                if pickup.modal_hudmemo.is_none() {
+                    pickup.modal_hudmemo = item_acquisition.modal_hudmemo;
+                }
+                if pickup.attainment_audio.is_none() {
+                    pickup.attainment_audio = item_acquisition
+                        .attainment_audio
+                        .map(AttainmentAudio::Enabled);
+                }
+                let save_prompt = item_acquisition.save_prompt.unwrap_or(false);
+
                 let skip_hudmemos = {
                     let modal_hudmemo = pickup.modal_hudmemo.as_ref();
 
@@ -16665,6 +20844,10 @@ fn build_and_run_patches<'r>(
                     panic!("EnableIceTraps must be true if you are placing Ice Trap pickups");
                 }
 
+                let totem_hint_layers = totem_hint_layers
+                    .get(&PickupType::from_str(&pickup.pickup_type).kind())
+                    .copied();
+
                 // modify pickup, connections, hudmemo etc.
                 patcher.add_scly_patch(
                     (pak_name.as_bytes(), room_info.room_id.to_u32()),
@@ -16681,9 +20864,10 @@ fn build_and_run_patches<'r>(
                             key,
                             skip_hudmemos,
                             hudmemo_delay,
-                            config.qol_pickup_scans,
+                            config.qol_pickup_scans && !excluded,
                             extern_models,
-                            config.shuffle_pickup_position,
+                            config.shuffle_pickup_position && !excluded,
+                            shuffle_bounds,
                             config.seed + seed,
                             !config.starting_items.combat_visor
                                 && !config.starting_items.scan_visor
@@ -16691,6 +20875,10 @@ fn build_and_run_patches<'r>(
                                 && !config.starting_items.xray,
                             config.version,
                             config.force_vanilla_layout,
+                            SoftlockFix::ChapelOfTheEldersItem.is_enabled(&config.softlock_fixes),
+                            config.cosmetics.pickups.as_ref(),
+                            totem_hint_layers,
+                            save_prompt,
                         )
                     },
                 );
@@ -16704,7 +20892,7 @@ fn build_and_run_patches<'r>(
                     move |res| {
                         add_pickups_to_mapa(
                             res,
-                            show_icon,
+                            map_icon,
                             pickup_location.memory_relay,
                             pickup_location.position,
                         )
@@ -16717,14 +20905,19 @@ fn build_and_run_patches<'r>(
 
             // Patch extra item locations
             while idx < pickups_config_len {
-                let pickup = pickups[idx].clone(); // TODO: cloning is suboptimal
-                let show_icon = pickup.show_icon.unwrap_or(false);
-                let position = pickup.position.unwrap_or_else(|| {
-                    panic!(
-                        "Additional pickup in room 0x{} is missing required \"position\" property",
-                        room_info.room_id.to_u32()
+                let mut pickup = pickups[idx].clone(); // TODO: cloning is suboptimal
+                let map_icon = pickup
+                    .show_icon
+                    .unwrap_or_default()
+                    .texture_index(PickupType::from_str(&pickup.pickup_type));
+                // Frigate Orpheon and Impact Crater have no pickup locations, so all of their
+                // pickups end up here
+                let position = pickup.position.ok_or_else(|| {
+                    format!(
+                        "Additional pickup in {} is missing required \"position\" property",
+                        room_info.name()
                     )
-                });
+                })?;
 
                 // doesn't count the original pickups in the indexing
                 let custom_pickup_idx = idx - room_info.pickup_locations.len();
@@ -16735,6 +20928,21 @@ fn build_and_run_patches<'r>(
                     pickup_idx: idx as u32,
                 };
 
+                let item_acquisition = config
+                    .item_acquisition
+                    .get(&PickupType::from_str(&pickup.pickup_type))
+                    .copied()
+                    .unwrap_or_default();
+                if pickup.modal_hudmemo.is_none() {
+                    pickup.modal_hudmemo = item_acquisition.modal_hudmemo;
+                }
+                if pickup.attainment_audio.is_none() {
+                    pickup.attainment_audio = item_acquisition
+                        .attainment_audio
+                        .map(AttainmentAudio::Enabled);
+                }
+                let save_prompt = item_acquisition.save_prompt.unwrap_or(false);
+
                 let skip_hudmemos = {
                     if config.qol_cosmetic {
                         !(pickup.modal_hudmemo.unwrap_or(false))
@@ -16749,6 +20957,10 @@ fn build_and_run_patches<'r>(
                     panic!("EnableIceTraps must be true if you are placing Ice Trap pickups");
                 }
 
+                let totem_hint_layers = totem_hint_layers
+                    .get(&PickupType::from_str(&pickup.pickup_type).kind())
+                    .copied();
+
                 patcher.add_scly_patch(
                     (pak_name.as_bytes(), room_info.room_id.to_u32()),
                     move |_ps, area| {
@@ -16763,13 +20975,16 @@ fn build_and_run_patches<'r>(
                             key,
                             skip_hudmemos,
                             extern_models,
-                            config.shuffle_pickup_pos_all_rooms,
+                            config.shuffle_pickup_pos_all_rooms && !excluded,
+                            shuffle_bounds,
                             config.seed,
                             !config.starting_items.combat_visor
                                 && !config.starting_items.scan_visor
                                 && !config.starting_items.thermal_visor
                                 && !config.starting_items.xray,
                             config.version,
+                            totem_hint_layers,
+                            save_prompt,
                         )
                     },
                 );
@@ -16786,7 +21001,7 @@ fn build_and_run_patches<'r>(
                     move |res| {
                         add_pickups_to_mapa(
                             res,
-                            show_icon,
+                            map_icon,
                             pickup_meta::ScriptObjectLocation {
                                 layer: 0,
                                 instance_id: ((room_idx as u32) >> 16)
@@ -16849,6 +21064,30 @@ fn build_and_run_patches<'r>(
 
             // Edit doors
             for (dock_num, door_config) in doors {
+                // Get the resource info for the premade scan with this door's scan gate text
+                let scan_gate_scan = if door_config.scan_text.is_some() {
+                    let key = PickupHashKey {
+                        level_id: world.mlvl(),
+                        room_id: room_info.room_id.to_u32(),
+                        pickup_idx: idx as u32,
+                    };
+                    idx += 1;
+                    Some(*extra_scans.get(&key).unwrap())
+                } else {
+                    None
+                };
+                let key_lock_scan = if door_config.key.is_some() {
+                    let key = PickupHashKey {
+                        level_id: world.mlvl(),
+                        room_id: room_info.room_id.to_u32(),
+                        pickup_idx: idx as u32,
+                    };
+                    idx += 1;
+                    Some(*extra_scans.get(&key).unwrap())
+                } else {
+                    None
+                };
+
                 let is_vertical_dock = [
                     (0x11BD63B7, 0), // Tower Chamber
                     (0x0D72F1F7, 1), // Tower of Light
@@ -16876,42 +21115,61 @@ fn build_and_run_patches<'r>(
                         continue;
                     }
 
-                    let mut local_dl: ModifiableDoorLocation = (*dl).into();
+                    let local_dl = version_door_location(config.version, room_info, dl);
 
-                    let mrea_id = room_info.room_id.to_u32();
+                    let door_location = local_dl.clone();
+                    maybe_door_location = Some(door_location.clone());
 
-                    // Some doors have their object IDs changed in non NTSC-U versions
-                    // NTSC-K is based on NTSC-U and shouldn't be part of those changes
-                    if [Version::Pal, Version::NtscJ, Version::NtscJTrilogy, Version::NtscUTrilogy, Version::PalTrilogy].contains(&config.version) {
-                        // Tallon Overworld - Temple Security Station
-                        if mrea_id == 0xBDB1FCAC
-                            && local_dl.door_location.unwrap().instance_id == 0x00070055
-                        {
-                            local_dl.door_location = Some(ScriptObjectLocation {
-                                layer: 0,
-                                instance_id: 0x000700a5,
-                            });
-                            local_dl.door_force_locations = Box::new([ScriptObjectLocation {
-                                layer: 0,
-                                instance_id: 0x000700a6,
-                            }]);
-                            local_dl.door_shield_locations = Box::new([ScriptObjectLocation {
-                                layer: 0,
-                                instance_id: 0x000700a8,
-                            }]);
+                    // Both sides of the door are locked, the scan says which key opens it
+                    if let Some(key) = door_config.key.clone() {
+                        let lock_scan = key_lock_scan.unwrap();
+                        let (other_room, other_dl) =
+                            connected_dock(rooms, room_info, local_dl.dock_position).ok_or_else(
+                                || {
+                                    format!(
+                                        "Could not find the other side of dock #{} in {}",
+                                        dock_num,
+                                        room_info.name()
+                                    )
+                                },
+                            )?;
+                        let locked_docks = [
+                            (room_info, local_dl.clone()),
+                            (
+                                other_room,
+                                version_door_location(config.version, other_room, other_dl),
+                            ),
+                        ];
+                        for (room, dl) in locked_docks {
+                            let key = key.clone();
+                            patcher.add_scly_patch(
+                                (pak_name.as_bytes(), room.room_id.to_u32()),
+                                move |ps, area| {
+                                    patch_lock_door_with_key(
+                                        ps,
+                                        area,
+                                        game_resources,
+                                        dl.clone(),
+                                        &key,
+                                        lock_scan,
+                                    )
+                                },
+                            );
                         }
                     }
 
-                    let door_location = local_dl.clone();
-                    maybe_door_location = Some(door_location.clone());
-
                     if door_config.shield_type.is_none() && door_config.blast_shield_type.is_none()
                     {
                         break;
                     }
 
+                    // e.g. the Impact Crater subchambers, which Samus falls through
                     if local_dl.door_location.is_none() {
-                        panic!("Tried to modify shield of door in {} on a dock which does not have a door", room_info.name());
+                        Err(format!(
+                            "Tried to modify the shield of dock #{} in {}, which does not have a door",
+                            dock_num,
+                            room_info.name()
+                        ))?;
                     }
 
                     // Patch door color and blast shield //
@@ -16958,6 +21216,8 @@ fn build_and_run_patches<'r>(
                         break;
                     }
 
+                    let blast_shield_cost = door_config.blast_shield_cost;
+
                     patcher.add_scly_patch(
                         (pak_name.as_bytes(), room_info.room_id.to_u32()),
                         move |ps, area| {
@@ -16967,6 +21227,8 @@ fn build_and_run_patches<'r>(
                                 local_dl.clone(),
                                 door_type,
                                 blast_shield_type,
+                                blast_shield_cost,
+                                scan_gate_scan,
                                 game_resources,
                                 config.door_open_mode,
                             )
@@ -17007,18 +21269,22 @@ fn build_and_run_patches<'r>(
                 }
 
                 if maybe_door_location.is_none() {
-                    panic!(
+                    Err(format!(
                         "Could not find dock #{} in '{}'",
                         dock_num,
                         room_info.name()
-                    );
+                    ))?;
                 }
                 let door_location = maybe_door_location.unwrap();
 
                 // If specified, patch this door's connection
                 if door_config.destination.is_some() {
                     if door_location.door_location.is_none() {
-                        panic!("Tried to shuffle door destination in {} on a dock which does not have a door", room_info.name());
+                        Err(format!(
+                            "Tried to shuffle the destination of dock #{} in {}, which does not have a door",
+                            dock_num,
+                            room_info.name()
+                        ))?;
                     }
 
                     // Get the resource info for premade scan point with destination info
@@ -17174,31 +21440,147 @@ fn build_and_run_patches<'r>(
         }
     }
 
+    for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
+        for room_info in rooms.iter() {
+            if blackout_rooms.contains(&room_info.room_id.to_u32()) {
+                patcher.add_scly_patch(
+                    (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                    patch_thermal_heat_pickups_and_doors,
+                );
+            }
+
+            let world = World::from_pak(pak_name).unwrap();
+            if let Some(respawn) = config.respawn_enemies.get(world.to_json_key()) {
+                let excluded = config.exclude_rooms.contains(&room_info.room_id.to_u32())
+                    || respawn
+                        .exclude_rooms
+                        .iter()
+                        .flatten()
+                        .any(|room| room.trim() == room_info.name().trim());
+                if !excluded && rng.gen::<f32>() < respawn.intensity.unwrap_or(1.0) {
+                    patcher.add_scly_patch(
+                        (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                        patch_respawn_enemy_layers,
+                    );
+                }
+            }
+
+            if let Some(&(kind, seed)) = hazard_rooms.get(&room_info.room_id.to_u32()) {
+                let hazards = config.hazards.as_ref().unwrap();
+                patcher.add_scly_patch(
+                    (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                    move |ps, area| patch_add_hazards(ps, area, kind, hazards, seed),
+                );
+            }
+        }
+    }
+
     let (skip_frigate, skip_ending_cinematic) = make_elevators_patch(
         &mut patcher,
+        game_resources,
         &level_data,
         config.auto_enabled_elevators,
         player_size,
         config.force_vanilla_layout,
+        config.cosmetics.elevator_holograms.as_ref(),
+        &config.elevator_audio,
         config.version,
     );
     let skip_frigate = skip_frigate && starting_room.mlvl != World::FrigateOrpheon.mlvl();
+    let skip_ending_cinematic = config
+        .skip_ending_cinematic
+        .unwrap_or(skip_ending_cinematic);
+
+    // The Frigate has no pickup locations of its own, its pickups are all extra pickups. They
+    // would silently disappear with the rest of the Frigate if it can't be reached.
+    let frigate_has_pickups = level_data
+        .get(World::FrigateOrpheon.to_json_key())
+        .iter()
+        .flat_map(|level| level.rooms.values())
+        .any(|room| room.pickups.as_ref().map_or(false, |p| !p.is_empty()));
+    if skip_frigate && frigate_has_pickups {
+        Err(concat!(
+            "Pickups are placed in Frigate Orpheon, but neither the starting room nor any ",
+            "elevator leads there"
+        ))?;
+    }
+
+    // The item loss resets the inventory to the item loss items, which would take away what the
+    // Frigate's pickups gave. The item loss gives those items instead, whether or not Samus found
+    // them, so that they can't be lost. The artifacts aren't reset.
+    let mut item_loss_items = config.item_loss_items.clone();
+    if !config.disable_item_loss {
+        let frigate_pickups = level_data
+            .get(World::FrigateOrpheon.to_json_key())
+            .into_iter()
+            .flat_map(|level| level.rooms.values())
+            .flat_map(|room| room.pickups.iter().flatten());
+        for pickup in frigate_pickups {
+            let pickup_type = PickupType::from_str(&pickup.pickup_type);
+            let amount = pickup.max_increase.or(pickup.curr_increase).unwrap_or(
+                if pickup_type == PickupType::Missile {
+                    5
+                } else {
+                    1
+                },
+            );
+            item_loss_items.add_pickup(pickup_type, amount);
+        }
+    }
 
+    if config.no_lava_surface_damage {
+        for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
+            for room_info in rooms.iter() {
+                patcher.add_scly_patch(
+                    (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                    patch_remove_lava_surface_damage,
+                );
+            }
+        }
+    }
+
+    if !config.sound_overrides.is_empty() {
+        for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
+            for room_info in rooms.iter() {
+                patcher.add_scly_patch(
+                    (pak_name.as_bytes(), room_info.room_id.to_u32()),
+                    move |ps, area| patch_sound_overrides(ps, area, &config.sound_overrides),
+                );
+            }
+        }
+    }
+
+    let mut cutscenes = CutsceneSkips {
+        overrides: &config.cutscene_overrides,
+        patched_rooms: HashSet::new(),
+    };
+    patcher.set_skip_excluded_rooms(true);
     match config.qol_cutscenes {
         CutsceneMode::Original => {}
         CutsceneMode::Skippable => {}
         CutsceneMode::SkippableCompetitive => {}
         CutsceneMode::Competitive => {
-            patch_qol_competitive_cutscenes(&mut patcher, config.version, skip_frigate);
+            patch_qol_competitive_cutscenes(
+                &mut patcher,
+                &mut cutscenes,
+                config.version,
+                skip_frigate,
+            );
         }
         CutsceneMode::Minor => {
-            patch_qol_minor_cutscenes(&mut patcher, config.version);
+            patch_qol_minor_cutscenes(&mut patcher, &mut cutscenes, config.version);
         }
         CutsceneMode::Major => {
-            patch_qol_minor_cutscenes(&mut patcher, config.version);
-            patch_qol_major_cutscenes(&mut patcher, config.shuffle_pickup_position);
+            patch_qol_minor_cutscenes(&mut patcher, &mut cutscenes, config.version);
+            patch_qol_major_cutscene_skips(
+                &mut patcher,
+                &mut cutscenes,
+                config.shuffle_pickup_position,
+            );
         }
     }
+    patcher.set_skip_excluded_rooms(false);
+    cutscenes.add_remaining_patches(&mut patcher, config.qol_cutscenes == CutsceneMode::Major);
 
     let mut smoother_teleports = false;
     for (_, level) in level_data.iter() {
@@ -17235,6 +21617,7 @@ fn build_and_run_patches<'r>(
                 config.enable_ice_traps,
                 config.uuid,
                 config.shoot_in_grapple,
+                starting_scans,
             )
         });
 
@@ -17256,12 +21639,11 @@ fn build_and_run_patches<'r>(
                 config.enable_ice_traps,
                 config.uuid,
                 config.shoot_in_grapple,
+                starting_scans,
             )
         });
     }
 
-    let rel_config = create_rel_config_file(starting_room, config.quickplay);
-
     if skip_frigate {
         // remove frigate data to save time/space
         patcher.add_file_patch(b"Metroid1.pak", empty_frigate_pak);
@@ -17304,26 +21686,7 @@ fn build_and_run_patches<'r>(
         );
     }
 
-    gc_disc.add_file(
-        "rel_config.bin",
-        structs::FstEntryFile::ExternalFile(Box::new(rel_config)),
-    )?;
-
     if !config.force_vanilla_layout {
-        const ARTIFACT_TOTEM_SCAN_STRGS: &[ResourceInfo] = &[
-            resource_info!("07_Over_Stonehenge Totem 5.STRG"), // Lifegiver
-            resource_info!("07_Over_Stonehenge Totem 4.STRG"), // Wild
-            resource_info!("07_Over_Stonehenge Totem 10.STRG"), // World
-            resource_info!("07_Over_Stonehenge Totem 9.STRG"), // Sun
-            resource_info!("07_Over_Stonehenge Totem 3.STRG"), // Elder
-            resource_info!("07_Over_Stonehenge Totem 11.STRG"), // Spirit
-            resource_info!("07_Over_Stonehenge Totem 1.STRG"), // Truth
-            resource_info!("07_Over_Stonehenge Totem 7.STRG"), // Chozo
-            resource_info!("07_Over_Stonehenge Totem 6.STRG"), // Warrior
-            resource_info!("07_Over_Stonehenge Totem 12.STRG"), // Newborn
-            resource_info!("07_Over_Stonehenge Totem 8.STRG"), // Nature
-            resource_info!("07_Over_Stonehenge Totem 2.STRG"), // Strength
-        ];
         for (res_info, strg_text) in ARTIFACT_TOTEM_SCAN_STRGS
             .iter()
             .zip(artifact_totem_strings.iter())
@@ -17359,17 +21722,17 @@ fn build_and_run_patches<'r>(
         patch_required_artifact_count(&mut patcher, config.required_artifact_count.unwrap());
     }
 
-    patcher.add_resource_patch(
-        resource_info!("TXTR_SaveBanner.TXTR").into(),
-        patch_save_banner_txtr,
-    );
+    patch_save_file_textures(&mut patcher, &config.game_banner);
+    patch_title_screen(&mut patcher, &config.title_screen)?;
 
     if config.patch_power_conduits {
         patch_power_conduits(&mut patcher);
     }
 
     if config.qol_general {
+        patcher.set_skip_excluded_rooms(true);
         patch_weaken_conduits(&mut patcher);
+        patcher.set_skip_excluded_rooms(false);
     }
 
     if config.remove_mine_security_station_locks {
@@ -17644,6 +22007,27 @@ fn build_and_run_patches<'r>(
         },
     );
 
+    for preset in config.starting_presets.iter() {
+        let preset_room = SpawnRoomData::from_str(&preset.starting_room);
+        let starting_items = preset
+            .starting_items
+            .as_ref()
+            .unwrap_or(&config.starting_items);
+        patcher.add_scly_patch(
+            (preset_room.pak_name.as_bytes(), preset_room.mrea),
+            move |ps, area| {
+                patch_starting_pickups(ps, area, starting_items, false, game_resources, 0)
+            },
+        );
+    }
+
+    if config.seed_info.is_some() {
+        patcher.add_scly_patch(
+            (starting_room.pak_name.as_bytes(), starting_room.mrea),
+            move |ps, area| patch_add_seed_info_poi(ps, area, game_resources),
+        );
+    }
+
     if !skip_frigate {
         patcher.add_scly_patch(
             resource_info!("02_intro_elevator.MREA").into(),
@@ -17651,7 +22035,7 @@ fn build_and_run_patches<'r>(
                 patch_starting_pickups(
                     ps,
                     area,
-                    &config.item_loss_items,
+                    &item_loss_items,
                     false,
                     game_resources,
                     0x00050002, // default spawn in item loss elevator
@@ -17676,7 +22060,9 @@ fn build_and_run_patches<'r>(
             )
         });
 
-        if !config.force_vanilla_layout {
+        if !config.force_vanilla_layout
+            && SoftlockFix::PostParasiteQueenFrigate.is_enabled(&config.softlock_fixes)
+        {
             // Patch frigate so that it can be explored any direction without crashing or soft-locking
             patcher.add_scly_patch(
                 resource_info!("01_intro_hanger_connect.MREA").into(),
@@ -17719,6 +22105,11 @@ fn build_and_run_patches<'r>(
                             thermal_only: None,
                             layer: None,
                             active: None,
+                            waypoints: None,
+                            speed: None,
+                            pause: None,
+                            looping: None,
+                            activation_trigger: None,
                         },
                     )
                 },
@@ -17740,6 +22131,11 @@ fn build_and_run_patches<'r>(
                             thermal_only: None,
                             layer: None,
                             active: None,
+                            waypoints: None,
+                            speed: None,
+                            pause: None,
+                            looping: None,
+                            activation_trigger: None,
                         },
                     )
                 },
@@ -17761,6 +22157,11 @@ fn build_and_run_patches<'r>(
                             thermal_only: None,
                             layer: None,
                             active: None,
+                            waypoints: None,
+                            speed: None,
+                            pause: None,
+                            looping: None,
+                            activation_trigger: None,
                         },
                     )
                 },
@@ -17782,6 +22183,11 @@ fn build_and_run_patches<'r>(
                             thermal_only: None,
                             layer: None,
                             active: None,
+                            waypoints: None,
+                            speed: None,
+                            pause: None,
+                            looping: None,
+                            activation_trigger: None,
                         },
                     )
                 },
@@ -17910,12 +22316,17 @@ fn build_and_run_patches<'r>(
         });
     }
 
+    // Crash and softlock fixes apply to excluded rooms as well
     if config.qol_game_breaking {
         patch_qol_game_breaking(
             &mut patcher,
             config.version,
             config.force_vanilla_layout,
             player_size < 0.9,
+            &config.softlock_fixes,
+            // Samus shouldn't come out of the cutscene in a suit she didn't get, unless a suit
+            // could be received from another world during the fight
+            !config.phazon_suit_from_pickup || config.multiworld_dol_patches,
         );
 
         patcher.add_scly_patch(resource_info!("03_mines.MREA").into(), move |ps, area| {
@@ -17930,6 +22341,13 @@ fn build_and_run_patches<'r>(
         }
     }
 
+    if config.phazon_suit_from_pickup {
+        patcher.add_scly_patch(
+            resource_info!("12_mines_eliteboss.MREA").into(), // Elite Quarters
+            patch_remove_scripted_phazon_suit,
+        );
+    }
+
     // not only is this game-breaking, but it's nonsensical and counterintuitive, always fix //
     patcher.add_scly_patch(
         resource_info!("00i_mines_connect.MREA").into(), // Dynamo Access (Mines)
@@ -17940,6 +22358,7 @@ fn build_and_run_patches<'r>(
         move |ps, area| patch_spawn_point_position(ps, area, [0.0, 0.0, 0.0], true, false, false),
     );
 
+    patcher.set_skip_excluded_rooms(true);
     if config.qol_cosmetic {
         patch_qol_cosmetic(&mut patcher, skip_ending_cinematic, config.quickpatch);
 
@@ -17968,6 +22387,47 @@ fn build_and_run_patches<'r>(
     if !config.force_vanilla_layout {
         patch_qol_logical(&mut patcher, config, config.version);
     }
+    patcher.set_skip_excluded_rooms(false);
+
+    // Asking for it explicitly doesn't depend on the cosmetic QoL
+    if config.skip_ending_cinematic == Some(true) && !config.qol_cosmetic {
+        patcher.add_scly_patch(
+            resource_info!("01_endcinema.MREA").into(),
+            patch_ending_scene_straight_to_credits,
+        );
+    }
+
+    // The rel writes the counter into this pane whenever pickupCounter is set, so it's added
+    // whether or not the cosmetic QoL is
+    if let Some(pickup_counter) = pickup_counter_config(config) {
+        let widget = FrmeWidgetConfig {
+            name: PICKUP_COUNTER_WIDGET.to_string(),
+            parent: None,
+            kind: FrmeWidgetType::TextPane,
+            position: pickup_counter.position.unwrap_or([-5.0, 0.0, 4.2]),
+            color: None,
+            visible: None,
+            font: Some("Deface13B.FONT".to_string()),
+            size: Some([6.0, 0.8]),
+            fill_color: pickup_counter.color,
+            outline_color: None,
+            model: None,
+        };
+        patcher.add_resource_patch(resource_info!("FRME_BaseHud.FRME").into(), move |res| {
+            patch_add_frme_widgets(res, "FRME_BaseHud", std::slice::from_ref(&widget))
+        });
+    }
+
+    // Sorted so that errors are reported in a consistent order
+    let mut frme_widgets: Vec<_> = config.frme_widgets.iter().collect();
+    frme_widgets.sort_by(|a, b| a.0.cmp(b.0));
+    for (frme_name, widgets) in frme_widgets {
+        let info = resource_info_table::frame_info(frme_name)
+            .ok_or_else(|| format!("Unknown frame '{}' in frmeWidgets", frme_name))?;
+        patcher.add_resource_patch(info.into(), move |res| {
+            patch_add_frme_widgets(res, frme_name, widgets)
+        });
+    }
 
     for (_boss_name, scale) in config.boss_sizes.iter() {
         let boss_name = _boss_name.to_lowercase().replace([' ', '_'], "");
@@ -18091,6 +22551,77 @@ fn build_and_run_patches<'r>(
         }
     }
 
+    for (_boss_name, boss_config) in config.bosses.iter() {
+        let boss_name = _boss_name.to_lowercase().replace([' ', '_'], "");
+        let health_multiplier = boss_config.health_multiplier.unwrap_or(1.0);
+        let skip_phases = boss_config.skip_phases.unwrap_or(false);
+
+        let rooms: Vec<ResourceInfo> = if boss_name == "parasitequeen" {
+            if skip_frigate {
+                vec![]
+            } else {
+                vec![resource_info!("07_intro_reactor.MREA")]
+            }
+        } else if boss_name == "idrone" || boss_name == "incineratordrone" || boss_name == "zoid" {
+            vec![resource_info!("03_monkey_lower.MREA")]
+        } else if boss_name == "flaahgra" {
+            vec![resource_info!("22_Flaahgra.MREA")]
+        } else if boss_name == "adultsheegoth" {
+            vec![resource_info!("07_ice_chapel.MREA")]
+        } else if boss_name == "thardus" {
+            vec![resource_info!("19_ice_thardus.MREA")]
+        } else if boss_name == "elitepirate1" {
+            vec![resource_info!("05_mines_forcefields.MREA")]
+        } else if boss_name == "elitepirate2" {
+            vec![resource_info!("00i_mines_connect.MREA")]
+        } else if boss_name == "elitepirate3" {
+            vec![resource_info!("06_mines_elitebustout.MREA")]
+        } else if boss_name == "phazonelite" {
+            vec![resource_info!("03_mines.MREA")]
+        } else if boss_name == "omegapirate" {
+            vec![resource_info!("12_mines_eliteboss.MREA")]
+        } else if boss_name == "ridley" || boss_name == "metaridley" {
+            vec![resource_info!("07_stonehenge.MREA")]
+        } else if boss_name == "exo"
+            || boss_name == "metroidprime"
+            || boss_name == "metroidprimeexoskeleton"
+        {
+            vec![
+                resource_info!("03a_crater.MREA"),
+                resource_info!("03b_crater.MREA"),
+                resource_info!("03c_crater.MREA"),
+                resource_info!("03d_crater.MREA"),
+                resource_info!("03e_crater.MREA"),
+            ]
+        } else if boss_name == "essence" || boss_name == "metroidprimeessence" {
+            vec![resource_info!("03f_crater.MREA")]
+        } else if boss_name == "platedbeetle" {
+            vec![resource_info!("1a_morphball_shrine.MREA")]
+        } else if boss_name == "cloakeddrone" {
+            vec![resource_info!("07_mines_electric.MREA")]
+        } else {
+            Err(format!("Unknown boss '{}' in bosses", _boss_name))?
+        };
+
+        for room in rooms {
+            if health_multiplier != 1.0 {
+                let boss_name = boss_name.clone();
+                patcher.add_scly_patch(room.into(), move |_ps, area| {
+                    patch_boss_health_multiplier(
+                        _ps,
+                        area,
+                        &boss_name,
+                        config.version,
+                        health_multiplier,
+                    )
+                });
+            }
+            if skip_phases {
+                patcher.add_scly_patch(room.into(), patch_omega_pirate_weak_armor);
+            }
+        }
+    }
+
     // Edit Strings
     let paks = [
         "AudioGrp.pak",
@@ -18212,6 +22743,36 @@ fn build_and_run_patches<'r>(
                 patch_remove_ids(ps, area, ids.clone())
             });
         }
+
+        if config.visualize_volumes {
+            // Added triggers are looked up by id once every other patch of the room is done, so
+            // that the boxes match any later edit. Those without an id are where they were added.
+            let mut ids = vec![];
+            let mut volumes = vec![];
+            for trigger in room_config.triggers.iter().flatten() {
+                match trigger.id {
+                    Some(id) => ids.push(id),
+                    None => volumes.push((
+                        trigger.position.unwrap_or([0.0, 0.0, 0.0]),
+                        trigger.scale.unwrap_or([5.0, 5.0, 5.0]),
+                    )),
+                }
+            }
+            ids.extend(
+                room_config
+                    .bomb_slots
+                    .iter()
+                    .flatten()
+                    .map(|bomb_slot| bomb_slot.damageable_trigger_id),
+            );
+            ids.extend(room_config.edit_objs.iter().flat_map(|objs| objs.keys()));
+
+            if !ids.is_empty() || !volumes.is_empty() {
+                patcher.add_scly_patch(*room, move |ps, area| {
+                    patch_visualize_volumes(ps, area, game_resources, &ids, &volumes)
+                });
+            }
+        }
     }
 
     if config.disable_item_loss && !skip_frigate {
@@ -18248,7 +22809,7 @@ fn build_and_run_patches<'r>(
             match fs::create_dir(&config.cache_dir) {
                 Ok(()) => {}
                 Err(error) => {
-                    println!(
+                    warn!(
                         "Failed to create cache dir for optimal suit rotation: {}",
                         error
                     );
@@ -18269,7 +22830,7 @@ fn build_and_run_patches<'r>(
                     Ok(()) => {}
                     Err(error) => {
                         if !complained {
-                            println!(
+                            warn!(
                                 "Failed to create cache subdir for optimal suit rotation: {}",
                                 error
                             );
@@ -18327,7 +22888,7 @@ fn build_and_run_patches<'r>(
                                         Ok(()) => {},
                                         Err(error) => {
                                             if !complained {
-                                                println!("Failed to write cache file for optimal suit rotation: {}", error);
+                                                warn!("Failed to write cache file for optimal suit rotation: {}", error);
                                                 complained = true;
                                             }
                                         },
@@ -18335,7 +22896,7 @@ fn build_and_run_patches<'r>(
                                 },
                                 Err(error) => {
                                     if !complained {
-                                        println!("Failed to create cache file for optimal suit rotation: {}", error);
+                                        warn!("Failed to create cache file for optimal suit rotation: {}", error);
                                         complained = true;
                                     }
                                 },
@@ -18354,6 +22915,100 @@ fn build_and_run_patches<'r>(
         }
     }
 
+    if let Some(suits) = config.cosmetics.suits.as_ref() {
+        let suit_textures = [
+            POWER_SUIT_TEXTURES,
+            VARIA_SUIT_TEXTURES,
+            GRAVITY_SUIT_TEXTURES,
+            PHAZON_SUIT_TEXTURES,
+        ];
+        let remaps = suits.color_remaps();
+
+        for (textures, remap) in suit_textures.into_iter().zip(remaps) {
+            let remap = match remap {
+                Some(remap) => remap,
+                None => continue,
+            };
+            for texture in textures {
+                let asset_cache = asset_cache.clone();
+                patcher.add_resource_patch((*texture).into(), move |res| {
+                    remap_txtr(res, &remap, &asset_cache)
+                });
+            }
+        }
+
+        // The helmet is shared by every suit, so it follows the power suit
+        if let Some(remap) = remaps[0] {
+            patcher.add_resource_patch(resource_info!("FRME_Helmet.FRME").into(), move |res| {
+                patch_helmet_colors(res, &remap)
+            });
+        }
+    }
+
+    if let Some(sfx) = config.cosmetics.sfx.as_ref() {
+        // Sorted so that the same seed picks the same samples
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut groups: Vec<_> = sfx.iter().collect();
+        groups.sort_by(|a, b| a.0.cmp(b.0));
+        for (group, samples) in groups {
+            let info = resource_info_table::audio_group_info(group)
+                .ok_or_else(|| format!("Unknown audio group '{}' in cosmetics.sfx", group))?;
+
+            let mut samples: Vec<_> = samples.iter().collect();
+            samples.sort_by(|a, b| a.0.cmp(b.0));
+            let mut dsp_samples = HashMap::new();
+            for (sample_id, paths) in samples {
+                let id = match sample_id.strip_prefix("0x") {
+                    Some(hex) => u16::from_str_radix(hex, 16),
+                    None => sample_id.parse(),
+                }
+                .map_err(|_| format!("Invalid sample id '{}' in {}", sample_id, group))?;
+                let path = paths.choose(&mut rng).ok_or_else(|| {
+                    format!("No replacement for sample {} in {}", sample_id, group)
+                })?;
+                dsp_samples.insert(id, DspSample::read(Path::new(path))?);
+            }
+
+            patcher.add_resource_patch(info.into(), move |res| {
+                patch_agsc_samples(res, &dsp_samples)
+            });
+        }
+    }
+
+    if config.colorblind_mode != ColorblindMode::None {
+        let paks: Vec<&[u8]> = pickup_meta::ROOM_INFO
+            .iter()
+            .map(|(pak_name, _)| pak_name.as_bytes())
+            .collect();
+
+        for (txtr_id, angle) in colorblind_txtr_hue_degs(config.colorblind_mode) {
+            let matrix = huerotate_matrix(angle as f32);
+            let asset_cache = asset_cache.clone();
+            patcher.add_resource_patch((&paks, txtr_id, FourCC::from_bytes(b"TXTR")), move |res| {
+                // Copies from the game resource pool have already been recolored
+                if let structs::ResourceKind::External(_, _) = res.kind {
+                    return Ok(());
+                }
+                huerotate_txtr(res, matrix, &asset_cache)
+            });
+        }
+    }
+
+    if !config.texture_overrides.is_empty() {
+        let paks: Vec<&[u8]> = pickup_meta::ROOM_INFO
+            .iter()
+            .map(|(pak_name, _)| pak_name.as_bytes())
+            .collect();
+
+        for (txtr_id, path) in config.texture_overrides.iter() {
+            let path = Path::new(path);
+            patcher
+                .add_resource_patch((&paks, *txtr_id, FourCC::from_bytes(b"TXTR")), move |res| {
+                    replace_txtr(res, path, false)
+                });
+        }
+    }
+
     if config.warp_to_start {
         const SAVE_STATIONS_ROOMS: &[ResourceInfo] = &[
             // Space Pirate Frigate
@@ -18400,7 +23055,37 @@ fn build_and_run_patches<'r>(
         );
     }
 
-    patcher.run(gc_disc)?;
+    patcher.run(gc_disc, pn)?;
+
+    // Written once the dol is patched, as the regions the rel checks are hashed from it
+    let integrity_regions = if config.integrity_check {
+        integrity_regions(gc_disc, config.version)?
+    } else {
+        [[0; 3]; 8]
+    };
+    let rel_config = create_rel_config_file(
+        starting_room,
+        config.quickplay,
+        &config.timer_config,
+        config.ghost_recording_address,
+        config.ghost_memory_card_dump,
+        config.tracker_address,
+        integrity_regions,
+        &config.progressive_chains,
+        has_blast_shield_costs(&config),
+        pickup_counter_config(&config),
+        &config.starting_presets,
+        config.debug_features,
+        config.version,
+    );
+    gc_disc.add_file(
+        "rel_config.bin",
+        structs::FstEntryFile::ExternalFile(Box::new(rel_config)),
+    )?;
+
+    if config.optimize_paks {
+        pak_optimizer::optimize_paks(gc_disc)?;
+    }
 
     Ok(())
 }
@@ -18579,6 +23264,33 @@ fn patch_maze_seeds(res: &mut structs::Resource, seeds: Vec<u32>) -> Result<(),
     Ok(())
 }
 
+// Memory card saves belong to the game ID of the disc that made them, so a maker code derived from
+// the layout keeps discs with different layouts from loading each other's saves, whose world
+// state wouldn't match their rooms. The first character is always a letter, so it never matches
+// Nintendo's "01".
+fn layout_maker_code(
+    config_json: &str,
+    level_data: &HashMap<String, LevelConfig>,
+) -> Result<[u8; 2], String> {
+    const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    const ALPHANUMERICS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+    // The level data is left out of randomprime.json, but it's most of what makes up a layout
+    let level_data_json = serde_json::to_value(level_data)
+        .and_then(|value| serde_json::to_string(&value))
+        .map_err(|e| format!("Failed to serialize level data: {}", e))?;
+
+    let mut sha = sha256::Sha256::new();
+    sha.update(config_json.as_bytes());
+    sha.update(level_data_json.as_bytes());
+    let digest = sha.finish();
+
+    Ok([
+        LETTERS[digest[0] as usize % LETTERS.len()],
+        ALPHANUMERICS[digest[1] as usize % ALPHANUMERICS.len()],
+    ])
+}
+
 /* For mipmapcache */
 fn calculate_hash<T: Hash>(t: &T) -> u64 {
     let mut s = DefaultHasher::new();
@@ -18885,3 +23597,43 @@ fn patch_elite_research_door_lock<'r>(
 
     Ok(())
 }
+
+#[test]
+#[ignore] // needs the fixtures, see scly_snapshot.rs
+fn test_snapshot_remove_cutscenes() {
+    crate::scly_snapshot::check_snapshot(
+        "remove_cutscenes_sunchamber",
+        resource_info!("22_Flaahgra.MREA"),
+        |ps, area| patch_remove_cutscenes(ps, area, vec![], vec![], false),
+    );
+}
+
+#[test]
+#[ignore] // needs the fixtures, see scly_snapshot.rs
+fn test_snapshot_remove_blast_shields() {
+    crate::scly_snapshot::check_snapshot(
+        "remove_blast_shields_elite_research",
+        resource_info!("03_mines.MREA"),
+        patch_remove_blast_shields,
+    );
+}
+
+#[test]
+#[ignore] // needs the fixtures, see scly_snapshot.rs
+fn test_snapshot_anti_oob() {
+    crate::scly_snapshot::check_snapshot(
+        "anti_oob_landing_site",
+        resource_info!("01_over_mainplaza.MREA"),
+        patch_anti_oob,
+    );
+}
+
+#[test]
+#[ignore] // needs the fixtures, see scly_snapshot.rs
+fn test_snapshot_remove_control_disabler() {
+    crate::scly_snapshot::check_snapshot(
+        "remove_control_disabler_reactor_core",
+        resource_info!("07_intro_reactor.MREA"),
+        patch_remove_control_disabler,
+    );
+}
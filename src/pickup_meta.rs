@@ -590,10 +590,36 @@ pub struct PickupLocation {
     pub position: [f32; 3],
 }
 
+// The forward and up axes of a door, generated by resource_tracing from the door's rotation
+#[derive(Clone, Copy, Debug)]
+pub struct DoorOrientation {
+    pub forward: [f32; 3],
+    pub up: [f32; 3],
+}
+
+impl DoorOrientation {
+    pub fn from_rotation(rotation: [f32; 3]) -> Self {
+        // Rotations are applied Z * Y * X. Keep the table compact, the shields don't need more
+        // precision than this
+        let [x, y, z] = rotation.map(f32::to_radians);
+        let round = |v: f32| (v * 10000.0).round() / 10000.0 + 0.0;
+        DoorOrientation {
+            forward: [z.cos() * y.cos(), z.sin() * y.cos(), -y.sin()].map(round),
+            up: [
+                x.cos() * y.sin() * z.cos() + x.sin() * z.sin(),
+                x.cos() * y.sin() * z.sin() - x.sin() * z.cos(),
+                x.cos() * y.cos(),
+            ]
+            .map(round),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct DoorLocation {
     pub door_location: Option<ScriptObjectLocation>,
     pub door_rotation: Option<[f32; 3]>,
+    pub door_orientation: Option<DoorOrientation>,
     pub door_force_locations: &'static [ScriptObjectLocation],
     pub door_shield_locations: &'static [ScriptObjectLocation],
     pub dock_number: u32,
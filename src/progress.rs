@@ -0,0 +1,100 @@
+use std::time::{Duration, Instant};
+
+use reader_writer::CStr;
+use structs::{ProgressNotifier, ProgressStage};
+
+/// Prints the progress of the patcher to stdout, including an estimate of the time left while
+/// writing the output disc.
+pub struct ConsoleProgressNotifier {
+    total_size: usize,
+    bytes_so_far: usize,
+    write_start: Option<Instant>,
+    quiet: bool,
+}
+
+impl ConsoleProgressNotifier {
+    pub fn new(quiet: bool) -> ConsoleProgressNotifier {
+        ConsoleProgressNotifier {
+            total_size: 0,
+            bytes_so_far: 0,
+            write_start: None,
+            quiet,
+        }
+    }
+
+    fn percent(&self) -> f64 {
+        if self.total_size == 0 {
+            return 0.;
+        }
+        (self.bytes_so_far as f64 / self.total_size as f64 * 100.).min(100.)
+    }
+
+    /// Extrapolates the time left from the write speed so far
+    fn eta(&self) -> Option<Duration> {
+        let elapsed = self.write_start?.elapsed();
+        if self.bytes_so_far == 0 || self.bytes_so_far >= self.total_size {
+            return None;
+        }
+        let bytes_left = (self.total_size - self.bytes_so_far) as f64;
+        Some(elapsed.mul_f64(bytes_left / self.bytes_so_far as f64))
+    }
+}
+
+impl ProgressNotifier for ConsoleProgressNotifier {
+    fn notify_total_bytes(&mut self, total_size: usize) {
+        self.total_size = total_size;
+        self.bytes_so_far = 0;
+        self.write_start = Some(Instant::now());
+    }
+
+    fn notify_writing_file(&mut self, file_name: &CStr, _file_bytes: usize) {
+        if self.quiet {
+            return;
+        }
+        match self.eta() {
+            Some(eta) => println!(
+                "{:02.0}% -- Writing file {:?} ({}s left)",
+                self.percent(),
+                file_name,
+                eta.as_secs()
+            ),
+            None => println!("{:02.0}% -- Writing file {:?}", self.percent(), file_name),
+        }
+    }
+
+    fn notify_writing_header(&mut self) {
+        if self.quiet {
+            return;
+        }
+        println!("{:02.0}% -- Writing ISO header", self.percent());
+    }
+
+    fn notify_flushing_to_disk(&mut self) {
+        if self.quiet {
+            return;
+        }
+        println!("Flushing written data to the disk...");
+    }
+
+    fn notify_stage(&mut self, stage: ProgressStage) {
+        if self.quiet {
+            return;
+        }
+        match stage {
+            ProgressStage::Extract => println!("Reading input ISO..."),
+            ProgressStage::BuildAssets => println!("Building custom assets..."),
+            ProgressStage::PatchPak { name, index, count } => println!(
+                "[{}/{}] Patching {}",
+                index + 1,
+                count,
+                String::from_utf8_lossy(name)
+            ),
+            ProgressStage::Compress => println!("Compressing and writing output ISO..."),
+            ProgressStage::Write => println!("Writing output ISO..."),
+        }
+    }
+
+    fn notify_bytes_written(&mut self, bytes: usize) {
+        self.bytes_so_far += bytes;
+    }
+}
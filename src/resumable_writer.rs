@@ -0,0 +1,278 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, Write},
+};
+
+use adler32::adler32;
+use flate2::Crc;
+use log::error;
+use reader_writer::byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+// Journal layout: the magic and chunk size, followed by one (chunk index, adler32, crc32) entry
+// per chunk that was written out. A later entry for the same chunk replaces the earlier one.
+const JOURNAL_MAGIC: &[u8; 4] = b"RPJ1";
+const CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct ChunkChecksum {
+    adler: u32,
+    crc: u32,
+}
+
+impl ChunkChecksum {
+    fn new(bytes: &[u8]) -> io::Result<ChunkChecksum> {
+        let mut crc = Crc::new();
+        crc.update(bytes);
+        Ok(ChunkChecksum {
+            adler: adler32(bytes)?,
+            crc: crc.sum(),
+        })
+    }
+}
+
+struct Journal {
+    file: File,
+    chunks: HashMap<u32, ChunkChecksum>,
+}
+
+// The checksum of a whole chunk of `file`, or None if the file ends before it does
+fn chunk_checksum_on_disk<F: Read + Seek>(
+    file: &mut F,
+    index: u32,
+) -> io::Result<Option<ChunkChecksum>> {
+    file.seek(io::SeekFrom::Start(index as u64 * CHUNK_SIZE))?;
+    let mut bytes = Vec::with_capacity(CHUNK_SIZE as usize);
+    file.by_ref().take(CHUNK_SIZE).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != CHUNK_SIZE {
+        return Ok(None);
+    }
+    ChunkChecksum::new(&bytes).map(Some)
+}
+
+impl Journal {
+    /// Only the entries whose chunk is still in `output` as journaled are kept, the file could
+    /// have been modified or truncated since the journal was written
+    fn open<F: Read + Seek>(path: &str, output: &mut F) -> io::Result<Journal> {
+        let mut chunks = HashMap::new();
+        if let Ok(mut file) = File::open(path) {
+            let mut bytes = vec![];
+            file.read_to_end(&mut bytes)?;
+            let chunk_size = bytes
+                .get(4..8)
+                .map(|mut b| b.read_u32::<LittleEndian>())
+                .transpose()?;
+            // An entry cut short by the interruption is simply ignored
+            if bytes.starts_with(JOURNAL_MAGIC) && chunk_size == Some(CHUNK_SIZE as u32) {
+                for mut entry in bytes[8..].chunks_exact(12) {
+                    let index = entry.read_u32::<LittleEndian>()?;
+                    let adler = entry.read_u32::<LittleEndian>()?;
+                    let crc = entry.read_u32::<LittleEndian>()?;
+                    chunks.insert(index, ChunkChecksum { adler, crc });
+                }
+            }
+        }
+        let mut verified = HashMap::new();
+        for (index, checksum) in chunks {
+            if chunk_checksum_on_disk(output, index)? == Some(checksum) {
+                verified.insert(index, checksum);
+            }
+        }
+        let chunks = verified;
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.write_all(JOURNAL_MAGIC)?;
+        file.write_u32::<LittleEndian>(CHUNK_SIZE as u32)?;
+        for (index, checksum) in chunks.iter() {
+            file.write_u32::<LittleEndian>(*index)?;
+            file.write_u32::<LittleEndian>(checksum.adler)?;
+            file.write_u32::<LittleEndian>(checksum.crc)?;
+        }
+        file.sync_data()?;
+
+        Ok(Journal { file, chunks })
+    }
+
+    fn record(&mut self, index: u32, checksum: ChunkChecksum) -> io::Result<()> {
+        self.file.write_u32::<LittleEndian>(index)?;
+        self.file.write_u32::<LittleEndian>(checksum.adler)?;
+        self.file.write_u32::<LittleEndian>(checksum.crc)?;
+        self.file.sync_data()?;
+        self.chunks.insert(index, checksum);
+        Ok(())
+    }
+}
+
+/// Call once the whole disc has been written successfully, the journal is no longer needed.
+pub fn remove_journal(journal_path: &str) -> io::Result<()> {
+    match fs::remove_file(journal_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Writes the output disc in checksummed chunks, keeping a journal of the chunks that made it to
+/// the file. When a previous run was interrupted, the journal's chunks are first checked against
+/// the file, then those whose checksum still matches are skipped instead of being written again.
+///
+/// This only saves writing: resuming is still a full re-run, which patches and checksums the whole
+/// disc again.
+///
+/// Only whole chunks written sequentially are journaled. Anything written after seeking into the
+/// middle of a chunk (such as the header of a GCZ/CISO file) is written straight through.
+pub struct ResumableWriter<W: Read + Write + Seek> {
+    file: W,
+    journal: Option<Journal>,
+    pos: u64,
+    chunk_buf: Vec<u8>,
+}
+
+impl<W: Read + Write + Seek> ResumableWriter<W> {
+    /// Without a journal path every write goes straight to `file`.
+    pub fn new(mut file: W, journal_path: Option<&str>) -> io::Result<ResumableWriter<W>> {
+        let pos = file.stream_position()?;
+        let journal = journal_path
+            .map(|path| Journal::open(path, &mut file))
+            .transpose()?;
+        file.seek(io::SeekFrom::Start(pos))?;
+        Ok(ResumableWriter {
+            file,
+            journal,
+            pos,
+            chunk_buf: Vec::new(),
+        })
+    }
+
+    fn chunk_start(&self) -> u64 {
+        self.pos - self.chunk_buf.len() as u64
+    }
+
+    fn commit_chunk(&mut self) -> io::Result<()> {
+        let journal = self.journal.as_mut().unwrap();
+        let index = (self.chunk_start() / CHUNK_SIZE) as u32;
+        let checksum = ChunkChecksum::new(&self.chunk_buf)?;
+        if journal.chunks.get(&index) == Some(&checksum) {
+            self.file
+                .seek(io::SeekFrom::Current(self.chunk_buf.len() as i64))?;
+        } else {
+            self.file.write_all(&self.chunk_buf)?;
+            self.file.flush()?;
+            journal.record(index, checksum)?;
+        }
+        self.chunk_buf.clear();
+        Ok(())
+    }
+
+    /// Writes out a partially filled chunk without journaling it
+    fn flush_partial_chunk(&mut self) -> io::Result<()> {
+        self.file.write_all(&self.chunk_buf)?;
+        self.chunk_buf.clear();
+        Ok(())
+    }
+}
+
+impl<W: Read + Write + Seek> Write for ResumableWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_all(buf).map(|()| buf.len())
+    }
+
+    fn write_all(&mut self, mut buf: &[u8]) -> io::Result<()> {
+        if self.journal.is_none() {
+            self.file.write_all(buf)?;
+            self.pos += buf.len() as u64;
+            return Ok(());
+        }
+
+        while !buf.is_empty() {
+            let next_boundary = (self.pos / CHUNK_SIZE + 1) * CHUNK_SIZE;
+            let l = std::cmp::min(next_boundary - self.pos, buf.len() as u64) as usize;
+            let (left_buf, right_buf) = buf.split_at(l);
+
+            if self.chunk_start() % CHUNK_SIZE == 0 {
+                self.chunk_buf.extend_from_slice(left_buf);
+            } else {
+                // Catch up to the next chunk boundary before journaling again
+                self.file.write_all(left_buf)?;
+            }
+            self.pos += l as u64;
+
+            if self.pos == next_boundary && !self.chunk_buf.is_empty() {
+                self.commit_chunk()?;
+            }
+            buf = right_buf;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.journal.is_some() {
+            self.flush_partial_chunk()?;
+        }
+        self.file.flush()
+    }
+}
+
+impl<W: Read + Write + Seek> Seek for ResumableWriter<W> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            io::SeekFrom::Current(0) => return Ok(self.pos),
+            io::SeekFrom::Current(offset) => self.pos as i64 + offset,
+            io::SeekFrom::Start(offset) => offset as i64,
+            io::SeekFrom::End(_) => {
+                self.flush_partial_chunk()?;
+                self.pos = self.file.seek(pos)?;
+                return Ok(self.pos);
+            }
+        };
+        if target < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Seek to a negative position",
+            ));
+        }
+        if target as u64 == self.pos {
+            return Ok(self.pos);
+        }
+
+        self.flush_partial_chunk()?;
+        self.pos = self.file.seek(io::SeekFrom::Start(target as u64))?;
+        Ok(self.pos)
+    }
+}
+
+impl<W: Read + Write + Seek> Drop for ResumableWriter<W> {
+    fn drop(&mut self) {
+        // We really don't want to panic from a destructor, so just write a warning instead
+        if let Err(e) = self.flush_partial_chunk() {
+            error!("Error closing ResumableWriter: {}", e);
+        }
+    }
+}
+
+#[test]
+fn test_resume_rewrites_chunks_modified_on_disk() {
+    let journal_path = std::env::temp_dir().join("randomprime_test_resume.journal");
+    let journal_path = journal_path.to_str().unwrap();
+    let _ = remove_journal(journal_path);
+
+    let data = (0..2 * CHUNK_SIZE).map(|i| i as u8).collect::<Vec<_>>();
+    let mut writer = ResumableWriter::new(io::Cursor::new(vec![]), Some(journal_path)).unwrap();
+    writer.write_all(&data).unwrap();
+    writer.flush().unwrap();
+    let mut output = std::mem::take(writer.file.get_mut());
+    drop(writer);
+
+    // The second chunk was modified after the journal recorded it
+    output[CHUNK_SIZE as usize] ^= 0xFF;
+    let mut writer = ResumableWriter::new(io::Cursor::new(output), Some(journal_path)).unwrap();
+    writer.write_all(&data).unwrap();
+    writer.flush().unwrap();
+    assert!(writer.file.get_ref()[..] == data[..]);
+    drop(writer);
+
+    remove_journal(journal_path).unwrap();
+}
@@ -0,0 +1,330 @@
+use std::{borrow::Cow, ffi::CString};
+
+use reader_writer::{Reader, Writable};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    mlvl_wrapper::{MlvlArea, MlvlEditor},
+    patch_config::{ConnectionMsg, ConnectionState},
+    room_lookup::{ROOM_BY_MREA, ROOM_BY_NAME},
+    sha256::to_hex,
+};
+
+/// The scripting of a room, as written by the `extract-room` subcommand. Objects keep their
+/// properties as the raw bytes of the SCLY, except for the position, rotation and scale which
+/// are decoded so that they can be edited. `typeName` is informational and not read back.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RoomDump {
+    pub room: String, // e.g. "Chozo Ruins:Hive Totem"
+    pub mrea_id: u32,
+    pub layers: Vec<LayerDump>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct LayerDump {
+    pub name: String,
+    pub active: bool,
+    pub objects: Vec<ObjectDump>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ObjectDump {
+    pub id: u32,
+    pub object_type: u8,
+    pub type_name: Option<String>,
+    pub position: Option<[f32; 3]>,
+    pub rotation: Option<[f32; 3]>,
+    pub scale: Option<[f32; 3]>,
+    pub connections: Vec<ConnectionDump>,
+    pub data: String, // hex
+}
+
+/// A connection of a dumped object, in the format of `addConnections` except that the states
+/// and messages without a name in the config (e.g. the ammo cost of a blast shield) are kept as
+/// their raw value
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ConnectionDump {
+    pub sender_id: u32,
+    pub target_id: u32,
+    pub state: ConnectionValue<ConnectionState>,
+    pub message: ConnectionValue<ConnectionMsg>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ConnectionValue<T> {
+    Named(T),
+    Raw(u32),
+}
+
+impl<T> ConnectionValue<T> {
+    fn new(value: u32, from_u32: impl Fn(u32) -> Option<T>) -> Self {
+        match from_u32(value) {
+            Some(named) => ConnectionValue::Named(named),
+            None => ConnectionValue::Raw(value),
+        }
+    }
+
+    fn value(&self, to_u32: impl Fn(&T) -> u32) -> u32 {
+        match self {
+            ConnectionValue::Named(named) => to_u32(named),
+            ConnectionValue::Raw(value) => *value,
+        }
+    }
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if hex.len() % 2 != 0 {
+        Err(format!("Odd number of digits in hex data '{}'", hex))?;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| format!("Invalid hex data '{}'", hex))
+        })
+        .collect()
+}
+
+/// Runs `f` on the room `mrea_id` of a pak, writing the changes it made to the MLVL back to the
+/// pak
+pub fn edit_room<'r, F, T>(pak: &mut structs::Pak<'r>, mrea_id: u32, f: F) -> Result<T, String>
+where
+    F: FnOnce(&mut MlvlArea<'r, '_, '_, '_>) -> Result<T, String>,
+{
+    let mlvl = pak
+        .resources
+        .iter()
+        .find(|res| res.fourcc() == b"MLVL".into())
+        .ok_or_else(|| "The pak has no MLVL".to_string())?
+        .kind
+        .as_mlvl()
+        .unwrap()
+        .into_owned();
+    let mut mlvl_editor = MlvlEditor::new(mlvl);
+
+    let result = {
+        let mut cursor = pak.resources.cursor();
+        loop {
+            let found = match cursor.peek() {
+                Some(res) => res.fourcc() == b"MREA".into() && res.file_id == mrea_id,
+                None => Err(format!("The pak has no room 0x{:08X}", mrea_id))?,
+            };
+            if found {
+                break;
+            }
+            cursor.next();
+        }
+        let mut area = mlvl_editor.get_area(&mut cursor);
+        f(&mut area)?
+    };
+
+    let mut cursor = pak.resources.cursor();
+    while cursor.peek().unwrap().fourcc() != b"MLVL".into() {
+        cursor.next();
+    }
+    cursor.value().unwrap().kind = structs::ResourceKind::Mlvl(mlvl_editor.mlvl);
+
+    Ok(result)
+}
+
+fn lookup_room(room: &str) -> Result<u32, String> {
+    let (region_name, room_name) = room
+        .split_once(':')
+        .ok_or_else(|| format!("Expected 'World:Room', not '{}'", room))?;
+    ROOM_BY_NAME
+        .get(&(region_name.trim().to_string(), room_name.trim().to_string()))
+        .map(|room_lookup| room_lookup.mrea_id)
+        .ok_or_else(|| format!("Unknown room '{}'", room))
+}
+
+/// Dumps the layers and objects of a room of a pak
+pub fn dump_room(pak_bytes: &[u8], room: &str) -> Result<RoomDump, String> {
+    let mrea_id = lookup_room(room)?;
+    let mut pak: structs::Pak = Reader::new(pak_bytes).read(());
+
+    edit_room(&mut pak, mrea_id, |area| {
+        let mut layers = vec![];
+        for (i, name) in area.layer_names.iter().enumerate() {
+            layers.push(LayerDump {
+                name: name.to_string_lossy().into_owned(),
+                active: area.layer_flags.flags & (1 << i) != 0,
+                objects: vec![],
+            });
+        }
+
+        let scly = area.mrea().scly_section_mut();
+        for (layer, scly_layer) in layers.iter_mut().zip(scly.layers.as_mut_vec().iter_mut()) {
+            for obj in scly_layer.objects.as_mut_vec().iter_mut() {
+                let mut data = vec![];
+                obj.property_data.write_to(&mut data).unwrap();
+
+                let connections = obj
+                    .connections
+                    .iter()
+                    .map(|conn| ConnectionDump {
+                        sender_id: obj.instance_id,
+                        target_id: conn.target_object_id,
+                        state: ConnectionValue::new(conn.state.0, ConnectionState::from_u32),
+                        message: ConnectionValue::new(conn.message.0, ConnectionMsg::from_u32),
+                    })
+                    .collect();
+
+                let property_data = &mut obj.property_data;
+                layer.objects.push(ObjectDump {
+                    id: obj.instance_id,
+                    object_type: property_data.object_type(),
+                    type_name: property_data.object_type_name().map(str::to_string),
+                    position: property_data
+                        .supports_position()
+                        .then(|| property_data.get_position()),
+                    rotation: property_data
+                        .supports_rotation()
+                        .then(|| property_data.get_rotation()),
+                    scale: property_data
+                        .supports_scale()
+                        .then(|| property_data.get_scale()),
+                    connections,
+                    data: to_hex(&data),
+                });
+            }
+        }
+
+        Ok(RoomDump {
+            room: room.to_string(),
+            mrea_id,
+            layers,
+        })
+    })
+}
+
+/// Replaces the layers and objects of a room of a pak with a dump, which may have been edited.
+/// Returns the new pak.
+pub fn import_room(pak_bytes: &[u8], dump: &RoomDump) -> Result<Vec<u8>, String> {
+    let mrea_id = lookup_room(&dump.room)?;
+    if mrea_id != dump.mrea_id {
+        Err(format!(
+            "The dump is of room 0x{:08X} ({}), not {}",
+            dump.mrea_id,
+            ROOM_BY_MREA
+                .get(&dump.mrea_id)
+                .map_or("unknown", |room| room.room_name),
+            dump.room
+        ))?;
+    }
+
+    let mut ids = vec![];
+    let mut datas = vec![];
+    for obj in dump.layers.iter().flat_map(|layer| layer.objects.iter()) {
+        if ids.contains(&obj.id) {
+            Err(format!(
+                "Object 0x{:08X} is in the dump more than once",
+                obj.id
+            ))?;
+        }
+        ids.push(obj.id);
+        datas.push(from_hex(&obj.data)?);
+    }
+
+    let mut pak: structs::Pak = Reader::new(pak_bytes).read(());
+    edit_room(&mut pak, mrea_id, |area| {
+        let layer_count = area.layer_names.len();
+        if dump.layers.len() < layer_count {
+            Err(format!(
+                "The room has {} layers, they can't be removed",
+                layer_count
+            ))?;
+        }
+        for (i, layer) in dump.layers.iter().enumerate() {
+            let name = Cow::Owned(
+                CString::new(layer.name.clone())
+                    .map_err(|_| format!("Invalid layer name '{}'", layer.name))?,
+            );
+            if i < layer_count {
+                area.layer_names[i] = name;
+            } else {
+                area.add_layer(name);
+            }
+            if layer.active {
+                area.layer_flags.flags |= 1 << i;
+            } else {
+                area.layer_flags.flags &= !(1 << i);
+            }
+        }
+
+        let mut datas = datas.iter();
+        let scly = area.mrea().scly_section_mut();
+        for (layer, scly_layer) in dump.layers.iter().zip(scly.layers.as_mut_vec().iter_mut()) {
+            let mut objects = vec![];
+            for obj in layer.objects.iter() {
+                let mut property_data = structs::SclyProperty::Unknown {
+                    object_type: obj.object_type,
+                    data: Reader::new(datas.next().unwrap()),
+                };
+                if let Some(position) = obj.position {
+                    property_data.set_position(position);
+                }
+                if let Some(rotation) = obj.rotation {
+                    property_data.set_rotation(rotation);
+                }
+                if let Some(scale) = obj.scale {
+                    property_data.set_scale(scale);
+                }
+
+                let connections: Vec<_> = obj
+                    .connections
+                    .iter()
+                    .map(|conn| structs::Connection {
+                        state: structs::ConnectionState(conn.state.value(|s| *s as u32)),
+                        message: structs::ConnectionMsg(conn.message.value(|m| *m as u32)),
+                        target_object_id: conn.target_id,
+                    })
+                    .collect();
+
+                objects.push(structs::SclyObject {
+                    instance_id: obj.id,
+                    connections: connections.into(),
+                    property_data,
+                });
+            }
+            scly_layer.objects = objects.into();
+        }
+
+        Ok(())
+    })?;
+
+    let mut bytes = vec![];
+    pak.write_to(&mut bytes)
+        .map_err(|e| format!("Failed to write the pak: {}", e))?;
+    Ok(bytes)
+}
+
+#[test]
+fn test_connection_dump_round_trip() {
+    // The ammo cost connection of a blast shield, whose state has no name
+    let json = r#"{"senderId":1,"targetId":10,"state":1095585103,"message":"DEACTIVATE"}"#;
+    let conn: ConnectionDump = serde_json::from_str(json).unwrap();
+    assert_eq!(conn.state, ConnectionValue::Raw(0x414D4D4F));
+    assert_eq!(
+        conn.message,
+        ConnectionValue::Named(ConnectionMsg::DEACTIVATE)
+    );
+    assert_eq!(serde_json::to_string(&conn).unwrap(), json);
+
+    assert_eq!(
+        ConnectionValue::new(0xE, ConnectionState::from_u32),
+        ConnectionValue::Named(ConnectionState::DEAD)
+    );
+    assert_eq!(
+        ConnectionValue::new(0xB, ConnectionState::from_u32),
+        ConnectionValue::Raw(0xB)
+    );
+    assert_eq!(
+        ConnectionValue::new(0xFFFFFFFF, ConnectionMsg::from_u32).value(|m| *m as u32),
+        0xFFFFFFFF
+    );
+}
@@ -0,0 +1,202 @@
+// Snapshot tests of the SCLY patches. A fixture is a pak holding a single room, i.e. its MREA and
+// the MLVL of its world, extracted from a disc with the ignored `extract_scly_fixtures` test. A
+// patch is run on the room of a fixture and the resulting objects are rendered as text, which is
+// compared against the snapshot under tests/snapshots.
+//
+// The fixtures are game data, so they aren't committed and the snapshot tests are ignored by
+// default. Extract the fixtures with
+//     RANDOMPRIME_FIXTURE_ISO=<NTSC 0-00 ISO> cargo test extract_scly_fixtures -- --ignored
+// and then run the tests with `cargo test snapshot -- --ignored`. A missing fixture or snapshot
+// fails the test; `UPDATE_SNAPSHOTS=1` writes the snapshots, e.g. after an intended change.
+
+use std::{
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::info;
+use reader_writer::{Reader, Writable};
+use resource_info_table::{resource_info, ResourceInfo};
+
+use crate::{
+    mlvl_wrapper::MlvlArea, patcher::PatcherState, room_dump::edit_room, sha1::sha1,
+    sha256::to_hex, GcDiscLookupExtensions,
+};
+
+/// The rooms extracted as fixtures
+pub fn fixture_rooms() -> Vec<ResourceInfo> {
+    vec![
+        resource_info!("01_over_mainplaza.MREA"), // Landing Site
+        resource_info!("07_intro_reactor.MREA"),  // Reactor Core (Frigate)
+        resource_info!("22_Flaahgra.MREA"),       // Sunchamber
+        resource_info!("03_mines.MREA"),          // Elite Research
+    ]
+}
+
+fn fixture_path(room: &ResourceInfo) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{}.pak", room.long_name))
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.txt", name))
+}
+
+/// Builds a pak holding only the MLVL and the MREA of `room` from the disc
+pub fn extract_fixture(gc_disc: &structs::GcDisc, room: &ResourceInfo) -> Result<Vec<u8>, String> {
+    let pak_name = String::from_utf8_lossy(room.paks[0]);
+    let reader = match gc_disc.find_file(&pak_name).and_then(|entry| entry.file()) {
+        Some(structs::FstEntryFile::Unknown(reader)) => reader.clone(),
+        _ => Err(format!("Failed to find {} on the disc", pak_name))?,
+    };
+
+    let mut pak: structs::Pak = reader.read(());
+    let resources: Vec<_> = pak
+        .resources
+        .iter()
+        .filter(|res| {
+            res.fourcc() == b"MLVL".into()
+                || (res.fourcc() == b"MREA".into() && res.file_id == room.res_id)
+        })
+        .map(|res| res.into_owned())
+        .collect();
+    if resources.len() != 2 {
+        Err(format!("Failed to find {} in {}", room.long_name, pak_name))?;
+    }
+    pak.resources = resources.into_iter().collect();
+
+    let mut bytes = vec![];
+    pak.write_to(&mut bytes)
+        .map_err(|e| format!("Failed to write fixture: {}", e))?;
+    Ok(bytes)
+}
+
+/// Renders the layers and objects of a room, one object per line followed by its connections.
+/// The properties of an object are summarized by a hash, plus its position if it has one.
+pub fn render_area(area: &mut MlvlArea) -> String {
+    let mut out = String::new();
+    for (i, name) in area.layer_names.iter().enumerate() {
+        let active = area.layer_flags.flags & (1 << i) != 0;
+        writeln!(
+            out,
+            "layer {} {:?} active={}",
+            i,
+            name.to_string_lossy(),
+            active
+        )
+        .unwrap();
+    }
+
+    let scly = area.mrea().scly_section_mut();
+    for (i, layer) in scly.layers.as_mut_vec().iter_mut().enumerate() {
+        writeln!(out, "\n[layer {}]", i).unwrap();
+        for obj in layer.objects.as_mut_vec().iter_mut() {
+            let mut props = vec![];
+            obj.property_data.write_to(&mut props).unwrap();
+            write!(
+                out,
+                "0x{:08X} type=0x{:02X} props={}",
+                obj.instance_id,
+                obj.property_data.object_type(),
+                to_hex(&sha1(&props)[..4])
+            )
+            .unwrap();
+            if obj.property_data.supports_position() {
+                let [x, y, z] = obj.property_data.get_position();
+                write!(out, " pos=({:.3}, {:.3}, {:.3})", x, y, z).unwrap();
+            }
+            out.push('\n');
+
+            for conn in obj.connections.iter() {
+                writeln!(
+                    out,
+                    "    {:?} {:?} -> 0x{:08X}",
+                    conn.state, conn.message, conn.target_object_id
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    out
+}
+
+/// Runs a SCLY patch on the room `room_id` of a fixture and renders the result
+pub fn run_scly_patch<F>(fixture: &[u8], room_id: u32, f: F) -> Result<String, String>
+where
+    F: FnOnce(&mut PatcherState, &mut MlvlArea) -> Result<(), String>,
+{
+    let mut pak: structs::Pak = Reader::new(fixture).read(());
+    edit_room(&mut pak, room_id, |area| {
+        let mut patcher_state = PatcherState::default();
+        f(&mut patcher_state, area)?;
+        Ok(render_area(area))
+    })
+}
+
+/// Runs `f` on the fixture of `room` and compares the result with the snapshot `name`, or writes
+/// the snapshot if `UPDATE_SNAPSHOTS=1`
+pub fn check_snapshot<F>(name: &str, room: ResourceInfo, f: F)
+where
+    F: FnOnce(&mut PatcherState, &mut MlvlArea) -> Result<(), String>,
+{
+    let fixture = fs::read(fixture_path(&room)).unwrap_or_else(|_| {
+        panic!(
+            "{} hasn't been extracted, see the top of scly_snapshot.rs",
+            room.long_name
+        )
+    });
+    let actual = run_scly_patch(&fixture, room.res_id, f).unwrap();
+
+    let path = snapshot_path(name);
+    if env::var("UPDATE_SNAPSHOTS").is_ok_and(|v| v == "1") {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        info!("Wrote the snapshot {}", name);
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("{} has no snapshot, write it with UPDATE_SNAPSHOTS=1", name));
+
+    if let Some((line, (expected, actual))) = expected
+        .lines()
+        .zip(actual.lines())
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual)
+    {
+        panic!(
+            "{} doesn't match its snapshot at line {}:\n  expected: {}\n    actual: {}",
+            name,
+            line + 1,
+            expected,
+            actual
+        );
+    }
+    assert_eq!(
+        expected.lines().count(),
+        actual.lines().count(),
+        "{} doesn't have as many lines as its snapshot",
+        name
+    );
+}
+
+#[test]
+#[ignore]
+fn extract_scly_fixtures() {
+    let iso_path = env::var("RANDOMPRIME_FIXTURE_ISO")
+        .expect("RANDOMPRIME_FIXTURE_ISO must be the path of the ISO to extract the fixtures from");
+    let file = fs::File::open(&iso_path).unwrap();
+    let iso = unsafe { memmap::Mmap::map(&file) }.unwrap();
+    let gc_disc: structs::GcDisc = Reader::new(&iso[..]).read(());
+
+    for room in fixture_rooms() {
+        let path = fixture_path(&room);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, extract_fixture(&gc_disc, &room).unwrap()).unwrap();
+    }
+}
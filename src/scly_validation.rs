@@ -0,0 +1,187 @@
+// Checks of the scripting of an area which catch patches that would crash the game, or silently
+// break a room, when it's loaded. The vanilla rooms aren't perfectly consistent themselves (e.g.
+// some connections target objects which don't exist), so the patcher only reports the problems
+// a room didn't already have before it was patched.
+
+use std::collections::HashMap;
+
+use reader_writer::LazyArray;
+use structs::{ConnectionState, SclyLayer};
+
+use crate::{
+    mlvl_wrapper::MlvlArea, patches::AMMO_COST_CONNECTION_STATE, room_lookup::ROOM_BY_MREA,
+};
+
+// The engine only tells objects apart by the area and index parts of their ids
+const ID_MASK: u32 = 0x00FFFFFF;
+
+/// Lists the problems with the scripting of an area, as messages which stay the same as long as
+/// the problem does
+pub fn area_problems(area: &mut MlvlArea) -> Vec<String> {
+    let mut problems = vec![];
+
+    let layer_count = area.layer_flags.layer_count as usize;
+    let layer_name_count = area.layer_names.len();
+    let dependency_layer_count = area.mlvl_area.dependencies.deps.len().saturating_sub(1);
+    let scly = area.mrea().scly_section();
+    let scly_layer_count = scly.layers.len();
+
+    if layer_count > 64 {
+        problems.push(format!(
+            "The area has {} layers, at most 64 are allowed",
+            layer_count
+        ));
+    }
+    for (what, count) in [
+        ("layer names", layer_name_count),
+        ("SCLY layers", scly_layer_count),
+        ("dependency layers", dependency_layer_count),
+    ] {
+        if count != layer_count {
+            problems.push(format!(
+                "The area has {} layers, but {} {}",
+                layer_count, count, what
+            ));
+        }
+    }
+
+    problems.extend(object_problems(&scly.layers));
+
+    problems.sort();
+    problems.dedup();
+    problems
+}
+
+// The problems with the objects of the layers and their connections
+fn object_problems(layers: &LazyArray<SclyLayer>) -> Vec<String> {
+    let mut problems = vec![];
+
+    let mut layers_by_id: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (i, layer) in layers.iter().enumerate() {
+        for obj in layer.objects.iter() {
+            layers_by_id
+                .entry(obj.instance_id & ID_MASK)
+                .or_default()
+                .push(i);
+
+            let id_layer = (obj.instance_id >> 26) as usize;
+            if id_layer != i {
+                problems.push(format!(
+                    "Object 0x{:08X} is in layer {}, but its id is of layer {}",
+                    obj.instance_id, i, id_layer
+                ));
+            }
+        }
+    }
+
+    for (id, id_layers) in layers_by_id.iter() {
+        if id_layers.len() > 1 {
+            problems.push(format!(
+                "Object id 0x{:06X} is used {} times, in layers {:?}",
+                id,
+                id_layers.len(),
+                id_layers
+            ));
+        }
+    }
+
+    for layer in layers.iter() {
+        for obj in layer.objects.iter() {
+            for conn in obj.connections.iter() {
+                // Its target is the ammo cost of a blast shield, not an object
+                if conn.state == ConnectionState(AMMO_COST_CONNECTION_STATE) {
+                    continue;
+                }
+                if !layers_by_id.contains_key(&(conn.target_object_id & ID_MASK)) {
+                    problems.push(format!(
+                        "Object 0x{:08X} has a connection ({:?} {:?}) to 0x{:08X}, which doesn't exist",
+                        obj.instance_id, conn.state, conn.message, conn.target_object_id
+                    ));
+                }
+            }
+        }
+    }
+
+    problems
+}
+
+/// Fails with every problem of `area` which isn't in `baseline`, the problems it had before it
+/// was patched
+pub fn check_area(area: &mut MlvlArea, baseline: &[String]) -> Result<(), String> {
+    let problems: Vec<String> = area_problems(area)
+        .into_iter()
+        .filter(|problem| !baseline.contains(problem))
+        .collect();
+    if problems.is_empty() {
+        return Ok(());
+    }
+
+    let mrea_id = area.mrea_file_id();
+    let room = match ROOM_BY_MREA.get(&mrea_id) {
+        Some(room) => format!("{}:{}", room.region_name, room.room_name),
+        None => format!("0x{:08X}", mrea_id),
+    };
+    Err(format!(
+        "The patched scripting of {} is invalid:\n  {}",
+        room,
+        problems.join("\n  ")
+    ))
+}
+
+#[cfg(test)]
+fn test_relay(instance_id: u32, connections: Vec<structs::Connection>) -> structs::SclyObject {
+    use reader_writer::CStrConversionExtension;
+
+    structs::SclyObject {
+        instance_id,
+        connections: connections.into(),
+        property_data: structs::Relay {
+            name: b"relay\0".as_cstr(),
+            active: 1,
+        }
+        .into(),
+    }
+}
+
+#[test]
+fn test_costed_blast_shield_has_no_problems() {
+    // The damageable trigger of a blast shield which costs 10 missiles
+    let trigger = test_relay(
+        0x00100001,
+        vec![
+            structs::Connection {
+                state: ConnectionState::DEAD,
+                message: structs::ConnectionMsg::SET_TO_ZERO,
+                target_object_id: 0x00100002,
+            },
+            structs::Connection {
+                state: ConnectionState(AMMO_COST_CONNECTION_STATE),
+                message: structs::ConnectionMsg(crate::pickup_meta::PickupType::Missile.kind()),
+                target_object_id: 10,
+            },
+        ],
+    );
+    let relay = test_relay(0x00100002, vec![]);
+    let mut layer = SclyLayer::new();
+    layer.objects = vec![trigger, relay].into();
+    let layers: LazyArray<SclyLayer> = vec![layer].into();
+
+    assert_eq!(object_problems(&layers), Vec::<String>::new());
+}
+
+#[test]
+fn test_connection_to_missing_object() {
+    let relay = test_relay(
+        0x00100001,
+        vec![structs::Connection {
+            state: ConnectionState::DEAD,
+            message: structs::ConnectionMsg::SET_TO_ZERO,
+            target_object_id: 0x00100002,
+        }],
+    );
+    let mut layer = SclyLayer::new();
+    layer.objects = vec![relay].into();
+    let layers: LazyArray<SclyLayer> = vec![layer].into();
+
+    assert_eq!(object_problems(&layers).len(), 1);
+}
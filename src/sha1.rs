@@ -0,0 +1,147 @@
+// SHA-1 (FIPS 180-4), used for the per-file hashes of the patch manifest. It's what tools like
+// Dolphin show for files on a disc, so the hashes can be compared against them.
+
+const H0: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+const BLOCK_SIZE: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct Sha1 {
+    state: [u32; 5],
+    block: [u8; BLOCK_SIZE],
+    block_len: usize,
+    total_len: u64,
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha1 {
+    pub fn new() -> Self {
+        Sha1 {
+            state: H0,
+            block: [0; BLOCK_SIZE],
+            block_len: 0,
+            total_len: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        self.total_len += bytes.len() as u64;
+
+        if self.block_len > 0 {
+            let n = (BLOCK_SIZE - self.block_len).min(bytes.len());
+            self.block[self.block_len..self.block_len + n].copy_from_slice(&bytes[..n]);
+            self.block_len += n;
+            bytes = &bytes[n..];
+            if self.block_len < BLOCK_SIZE {
+                return;
+            }
+            let block = self.block;
+            self.compress(&block);
+            self.block_len = 0;
+        }
+
+        let mut chunks = bytes.chunks_exact(BLOCK_SIZE);
+        for chunk in &mut chunks {
+            self.compress(chunk.try_into().unwrap());
+        }
+
+        let rest = chunks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+        self.block_len = rest.len();
+    }
+
+    pub fn finish(mut self) -> [u8; 20] {
+        let bit_len = self.total_len.wrapping_mul(8);
+
+        let mut padding = vec![0x80];
+        let padded_len = (self.block_len + 1 + 8).next_multiple_of(BLOCK_SIZE);
+        padding.resize(padded_len - self.block_len - 8, 0);
+        padding.extend_from_slice(&bit_len.to_be_bytes());
+        self.update(&padding);
+        debug_assert_eq!(self.block_len, 0);
+
+        let mut digest = [0; 20];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state.iter()) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    fn compress(&mut self, block: &[u8; BLOCK_SIZE]) {
+        let mut w = [0u32; 80];
+        for (i, bytes) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = self.state;
+        for (i, w) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*w);
+
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (state, v) in self.state.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(v);
+        }
+    }
+}
+
+pub fn sha1(bytes: &[u8]) -> [u8; 20] {
+    let mut sha = Sha1::new();
+    sha.update(bytes);
+    sha.finish()
+}
+
+#[test]
+fn test_sha1_vectors() {
+    // The test vectors of FIPS 180-4's examples
+    let vectors: [(&[u8], &str); 3] = [
+        (b"", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+        (b"abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+        (
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+        ),
+    ];
+    for (bytes, expected) in vectors {
+        assert_eq!(crate::sha256::to_hex(&sha1(bytes)), expected);
+    }
+}
+
+#[test]
+fn test_sha1_million_a_in_chunks() {
+    // Chunks which don't line up with the blocks
+    let mut sha = Sha1::new();
+    let chunk = [b'a'; 999];
+    for _ in 0..1000 {
+        sha.update(&chunk);
+    }
+    sha.update(&[b'a'; 1000]);
+    assert_eq!(
+        crate::sha256::to_hex(&sha.finish()),
+        "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+    );
+}
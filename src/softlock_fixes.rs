@@ -0,0 +1,332 @@
+// Catalog of the places where the game can be softlocked (or crashes) once the item and door
+// layout is randomized, along with the fix for each.
+//
+// Most fixes only remove an object, resize a trigger or add a few connections, so they are
+// described as data and applied by `patch_softlock_fix`. Fixes which need new objects or layers
+// are still written by hand in patches.rs, but are listed here so they can be toggled the same
+// way.
+
+use std::collections::HashMap;
+
+use resource_info_table::{resource_info, ResourceInfo};
+use serde::{Deserialize, Serialize};
+use structs::{ConnectionMsg, ConnectionState};
+
+use crate::{
+    mlvl_wrapper,
+    patch_config::Version,
+    patcher::{PatcherState, PrimePatcher},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoftlockFix {
+    // Hand-written fixes
+    ChapelOfTheEldersItem,
+    PostParasiteQueenFrigate,
+    SunchamberWildBeforeFlaahgra,
+    ResearchLabAetherWall,
+    Observatory1stPass,
+
+    // Data-driven fixes
+    ResearchLabAetherGlass,
+    Observatory2ndPass,
+    MinesSecurityStation,
+    GravityChamberGrapplePoint,
+    HiveTotem,
+    OreProcessingDoorLock,
+    CentralDynamo,
+    OreProcessingDestructibleRock,
+    CentralDynamoPowerBombRock,
+}
+
+impl SoftlockFix {
+    pub fn is_enabled(&self, config: &HashMap<SoftlockFix, bool>) -> bool {
+        *config.get(self).unwrap_or(&true)
+    }
+}
+
+pub enum FixEdit {
+    // Remove every object with one of these instance IDs (layer bits ignored)
+    RemoveObjects(&'static [u32]),
+
+    // Remove every object of this type
+    RemoveObjectType(u8),
+
+    // Add connections (state, message, target) to an existing object
+    AddConnections(u32, &'static [(ConnectionState, ConnectionMsg, u32)]),
+
+    // Replace the given components of a trigger's scale
+    ScaleTrigger(u32, [Option<f32>; 3]),
+
+    // Deactivate every actor, platform or point of interest with one of these instance IDs
+    Deactivate(&'static [u32]),
+
+    // Replace the given components of an actor's hitbox, then move it by an offset
+    ResizeActor(u32, [Option<f32>; 3], [f32; 3]),
+}
+
+pub struct SoftlockFixEntry {
+    pub fix: SoftlockFix,
+    pub room: ResourceInfo,
+    pub versions: Option<&'static [Version]>, // None means every version
+    pub edits: &'static [FixEdit],
+}
+
+pub const SOFTLOCK_FIXES: &[SoftlockFixEntry] = &[
+    // Break the wall via the trigger in the lower area instead of relying on Edward's AI to
+    // jump through the glass when doing labs backwards
+    SoftlockFixEntry {
+        fix: SoftlockFix::ResearchLabAetherGlass,
+        room: resource_info!("12_ice_research_b.MREA"),
+        versions: None,
+        edits: &[FixEdit::AddConnections(
+            0x00330219,
+            &[
+                (
+                    ConnectionState::ENTERED,
+                    ConnectionMsg::RESET_AND_START,
+                    0x0033005D, // Timer to break wall
+                ),
+                (
+                    ConnectionState::ENTERED,
+                    ConnectionMsg::DEACTIVATE,
+                    0x0033007C, // Edward
+                ),
+            ],
+        )],
+    },
+    SoftlockFixEntry {
+        fix: SoftlockFix::Observatory2ndPass,
+        room: resource_info!("11_ice_observatory.MREA"),
+        versions: None,
+        edits: &[
+            FixEdit::AddConnections(
+                0x081E0460,
+                &[(
+                    ConnectionState::DEATH_RATTLE,
+                    ConnectionMsg::INCREMENT,
+                    0x001E02EA, // Counter - dead pirates active panel
+                )],
+            ),
+            FixEdit::AddConnections(
+                0x081E0461,
+                &[(
+                    ConnectionState::DEATH_RATTLE,
+                    ConnectionMsg::INCREMENT,
+                    0x001E02EA,
+                )],
+            ),
+        ],
+    },
+    SoftlockFixEntry {
+        fix: SoftlockFix::MinesSecurityStation,
+        room: resource_info!("02_mines_shotemup.MREA"),
+        versions: None,
+        edits: &[FixEdit::ScaleTrigger(
+            0x0007033F,
+            [Some(50.0), Some(100.0), Some(40.0)],
+        )],
+    },
+    // Remove the object that turns off the stalactites layer
+    SoftlockFixEntry {
+        fix: SoftlockFix::GravityChamberGrapplePoint,
+        room: resource_info!("18_ice_gravity_chamber.MREA"),
+        versions: None,
+        edits: &[FixEdit::RemoveObjects(&[0x0035013A])],
+    },
+    SoftlockFixEntry {
+        fix: SoftlockFix::HiveTotem,
+        room: resource_info!("19_hive_totem.MREA"),
+        versions: None,
+        edits: &[FixEdit::ScaleTrigger(0x002400CA, [None, Some(60.0), None])],
+    },
+    // 0-02 locks the door until the player has power bombs
+    SoftlockFixEntry {
+        fix: SoftlockFix::OreProcessingDoorLock,
+        room: resource_info!("04_mines_pillar.MREA"),
+        versions: Some(&[Version::NtscU0_02]),
+        edits: &[FixEdit::RemoveObjects(&[
+            0x0006036A, 0x0006036C, 0x00060368,
+        ])],
+    },
+    // Too many effects for the rooms to handle alongside our patches
+    // Patch is actually for QAA
+    SoftlockFixEntry {
+        fix: SoftlockFix::CentralDynamo,
+        room: resource_info!("00j_mines_connect.MREA"),
+        versions: None,
+        edits: &[FixEdit::RemoveObjectType(0x45)],
+    },
+    SoftlockFixEntry {
+        fix: SoftlockFix::CentralDynamo,
+        room: resource_info!("05_under_intro_zoo.MREA"), // biohazard containment
+        versions: None,
+        edits: &[FixEdit::RemoveObjectType(0x45)],
+    },
+    // These versions block the way down with a rock only power bombs destroy
+    SoftlockFixEntry {
+        fix: SoftlockFix::OreProcessingDestructibleRock,
+        room: resource_info!("04_mines_pillar.MREA"),
+        versions: Some(&[
+            Version::Pal,
+            Version::NtscJ,
+            Version::NtscUTrilogy,
+            Version::NtscJTrilogy,
+            Version::PalTrilogy,
+        ]),
+        edits: &[FixEdit::Deactivate(&[
+            0x00060372, // platform
+            0x00060378, // its scan
+            0x00060379, // blocker collision
+        ])],
+    },
+    // The collision of the power bomb rock extends beyond the door, so that Samus can't get
+    // through it coming from the other side
+    SoftlockFixEntry {
+        fix: SoftlockFix::CentralDynamoPowerBombRock,
+        room: resource_info!("07_mines_electric.MREA"),
+        versions: None,
+        edits: &[FixEdit::ResizeActor(
+            0x001B065F,
+            [None, Some(0.4), None],
+            [0.0, -0.8, 0.0],
+        )],
+    },
+];
+
+fn patch_softlock_fix(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+    entry: &SoftlockFixEntry,
+) -> Result<(), String> {
+    let mrea_id = area.mlvl_area.mrea.to_u32();
+    let scly = area.mrea().scly_section_mut();
+    let layers = scly.layers.as_mut_vec();
+
+    for edit in entry.edits.iter() {
+        match edit {
+            FixEdit::RemoveObjects(ids) => {
+                for layer in layers.iter_mut() {
+                    layer.objects.as_mut_vec().retain(|obj| {
+                        !ids.iter()
+                            .any(|id| obj.instance_id & 0x00FFFFFF == id & 0x00FFFFFF)
+                    });
+                }
+            }
+            FixEdit::RemoveObjectType(object_type) => {
+                for layer in layers.iter_mut() {
+                    layer
+                        .objects
+                        .as_mut_vec()
+                        .retain(|obj| obj.property_data.object_type() != *object_type);
+                }
+            }
+            FixEdit::AddConnections(id, connections) => {
+                let obj = layers
+                    .iter_mut()
+                    .flat_map(|layer| layer.objects.as_mut_vec().iter_mut())
+                    .find(|obj| obj.instance_id & 0x00FFFFFF == id & 0x00FFFFFF)
+                    .ok_or_else(|| {
+                        format!(
+                            "Softlock fix {:?}: object 0x{:X} not found in room 0x{:X}",
+                            entry.fix, id, mrea_id
+                        )
+                    })?;
+                for (state, message, target_object_id) in connections.iter() {
+                    obj.connections.as_mut_vec().push(structs::Connection {
+                        state: *state,
+                        message: *message,
+                        target_object_id: *target_object_id,
+                    });
+                }
+            }
+            FixEdit::ScaleTrigger(id, scale) => {
+                let trigger = layers
+                    .iter_mut()
+                    .flat_map(|layer| layer.objects.as_mut_vec().iter_mut())
+                    .find(|obj| obj.instance_id & 0x00FFFFFF == id & 0x00FFFFFF)
+                    .and_then(|obj| obj.property_data.as_trigger_mut())
+                    .ok_or_else(|| {
+                        format!(
+                            "Softlock fix {:?}: trigger 0x{:X} not found in room 0x{:X}",
+                            entry.fix, id, mrea_id
+                        )
+                    })?;
+                for (i, value) in scale.iter().enumerate() {
+                    if let Some(value) = value {
+                        trigger.scale[i] = *value;
+                    }
+                }
+            }
+            FixEdit::Deactivate(ids) => {
+                for id in ids.iter() {
+                    let obj = layers
+                        .iter_mut()
+                        .flat_map(|layer| layer.objects.as_mut_vec().iter_mut())
+                        .find(|obj| obj.instance_id & 0x00FFFFFF == id & 0x00FFFFFF)
+                        .ok_or_else(|| {
+                            format!(
+                                "Softlock fix {:?}: object 0x{:X} not found in room 0x{:X}",
+                                entry.fix, id, mrea_id
+                            )
+                        })?;
+                    if let Some(actor) = obj.property_data.as_actor_mut() {
+                        actor.active = 0;
+                    } else if let Some(platform) = obj.property_data.as_platform_mut() {
+                        platform.active = 0;
+                    } else if let Some(poi) = obj.property_data.as_point_of_interest_mut() {
+                        poi.active = 0;
+                    } else {
+                        Err(format!(
+                            "Softlock fix {:?}: object 0x{:X} in room 0x{:X} can't be deactivated",
+                            entry.fix, id, mrea_id
+                        ))?;
+                    }
+                }
+            }
+            FixEdit::ResizeActor(id, hitbox, offset) => {
+                let actor = layers
+                    .iter_mut()
+                    .flat_map(|layer| layer.objects.as_mut_vec().iter_mut())
+                    .find(|obj| obj.instance_id & 0x00FFFFFF == id & 0x00FFFFFF)
+                    .and_then(|obj| obj.property_data.as_actor_mut())
+                    .ok_or_else(|| {
+                        format!(
+                            "Softlock fix {:?}: actor 0x{:X} not found in room 0x{:X}",
+                            entry.fix, id, mrea_id
+                        )
+                    })?;
+                for (i, value) in hitbox.iter().enumerate() {
+                    if let Some(value) = value {
+                        actor.hitbox[i] = *value;
+                    }
+                }
+                for (i, value) in offset.iter().enumerate() {
+                    actor.position[i] += *value;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn patch_softlock_fixes(
+    patcher: &mut PrimePatcher,
+    version: Version,
+    config: &HashMap<SoftlockFix, bool>,
+) {
+    for entry in SOFTLOCK_FIXES.iter() {
+        if !entry.fix.is_enabled(config) {
+            continue;
+        }
+        if let Some(versions) = entry.versions {
+            if !versions.contains(&version) {
+                continue;
+            }
+        }
+        patcher.add_scly_patch(entry.room.into(), move |ps, area| {
+            patch_softlock_fix(ps, area, entry)
+        });
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::pickup_meta::PickupType;
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StartingItems {
@@ -95,6 +97,42 @@ impl StartingItems {
         spawn_point.flamethrower = self.flamethrower as u32;
     }
 
+    /// Adds the items a pickup gives, ignoring those a spawn point doesn't hold (e.g. artifacts)
+    pub fn add_pickup(&mut self, pickup_type: PickupType, amount: i32) {
+        match pickup_type {
+            PickupType::PowerBeam => self.power_beam = true,
+            PickupType::IceBeam => self.ice = true,
+            PickupType::WaveBeam => self.wave = true,
+            PickupType::PlasmaBeam => self.plasma = true,
+            PickupType::Missile => self.missiles += amount,
+            PickupType::ScanVisor => self.scan_visor = true,
+            PickupType::MorphBallBomb => self.bombs = true,
+            PickupType::PowerBomb => {
+                self.power_bombs = self.power_bombs.saturating_add(amount as i8)
+            }
+            PickupType::Flamethrower => self.flamethrower = true,
+            PickupType::ThermalVisor => self.thermal_visor = true,
+            PickupType::ChargeBeam => self.charge = true,
+            PickupType::SuperMissile => self.super_missile = true,
+            PickupType::GrappleBeam => self.grapple = true,
+            PickupType::XRayVisor => self.xray = true,
+            PickupType::IceSpreader => self.ice_spreader = true,
+            PickupType::SpaceJumpBoots => self.space_jump = true,
+            PickupType::MorphBall => self.morph_ball = true,
+            PickupType::CombatVisor => self.combat_visor = true,
+            PickupType::BoostBall => self.boost_ball = true,
+            PickupType::SpiderBall => self.spider_ball = true,
+            PickupType::GravitySuit => self.gravity_suit = true,
+            PickupType::VariaSuit => self.varia_suit = true,
+            PickupType::PhazonSuit => self.phazon_suit = true,
+            PickupType::EnergyTank => {
+                self.energy_tanks = self.energy_tanks.saturating_add(amount as i8)
+            }
+            PickupType::Wavebuster => self.wavebuster = true,
+            _ => {}
+        }
+    }
+
     /// Custom deserializataion function that accepts an int as well as the usual struct/object
     /// version
     pub fn custom_deserialize<'de, D>(deserializer: D) -> Result<Self, D::Error>
@@ -141,6 +179,50 @@ impl StartingItems {
             && !self.ice_spreader
             && !self.flamethrower
     }
+
+    /// Names of the items beyond the ones Samus always starts with, e.g. "Missile x5"
+    pub fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        let counts = [
+            ("Missile", self.missiles),
+            ("Energy Tank", i32::from(self.energy_tanks)),
+            ("Power Bomb", i32::from(self.power_bombs)),
+        ];
+        for (name, count) in counts.iter() {
+            if *count > 0 {
+                names.push(format!("{} x{}", name, count));
+            }
+        }
+
+        let items = [
+            ("Wave Beam", self.wave),
+            ("Ice Beam", self.ice),
+            ("Plasma Beam", self.plasma),
+            ("Charge Beam", self.charge),
+            ("Morph Ball", self.morph_ball),
+            ("Morph Ball Bomb", self.bombs),
+            ("Spider Ball", self.spider_ball),
+            ("Boost Ball", self.boost_ball),
+            ("Varia Suit", self.varia_suit),
+            ("Gravity Suit", self.gravity_suit),
+            ("Phazon Suit", self.phazon_suit),
+            ("Thermal Visor", self.thermal_visor),
+            ("X-Ray Visor", self.xray),
+            ("Space Jump Boots", self.space_jump),
+            ("Grapple Beam", self.grapple),
+            ("Super Missile", self.super_missile),
+            ("Wavebuster", self.wavebuster),
+            ("Ice Spreader", self.ice_spreader),
+            ("Flamethrower", self.flamethrower),
+        ];
+        for (name, has) in items.iter() {
+            if *has {
+                names.push(name.to_string());
+            }
+        }
+
+        names
+    }
 }
 
 impl Default for StartingItems {
@@ -0,0 +1,166 @@
+// Makes user-provided text displayable by the game's fonts.
+//
+// The fonts only contain glyphs for a subset of Latin-1, so characters commonly pasted from
+// other sources (typographic quotes, dashes, emoji, ...) would otherwise render as blank
+// boxes. Each character the font can't draw is replaced by its closest equivalent, or by '?'
+// if there is none, and a warning is logged for it. Markup tags such as `&just=center;` are
+// left untouched.
+
+use std::collections::HashSet;
+
+use log::warn;
+use reader_writer::{FourCC, Reader};
+
+use crate::ResourceData;
+
+// The font used by scans and HUD memos
+pub const TEXT_FONT: (u32, FourCC) = (0xC29C51F1, FourCC::from_bytes(b"FONT"));
+
+pub struct Transliterator {
+    glyphs: Option<HashSet<u16>>,
+    quiet: bool,
+}
+
+// The length of the markup tag `chars` starts with, if it does: '&', a name made of letters,
+// digits and dashes, optionally '=' and arguments, then ';' (e.g. `&push;`, `&just=center;`)
+fn markup_len(chars: &[char]) -> Option<usize> {
+    let name_len = chars[1..]
+        .iter()
+        .take_while(|c| c.is_ascii_alphanumeric() || **c == '-')
+        .count();
+    if name_len == 0 {
+        return None;
+    }
+    let mut len = 1 + name_len;
+    if chars.get(len) == Some(&'=') {
+        len += 1;
+        len += chars[len..]
+            .iter()
+            .take_while(|c| !matches!(c, ';' | '&') && !c.is_control())
+            .count();
+    }
+    match chars.get(len) {
+        Some(';') => Some(len + 1),
+        _ => None,
+    }
+}
+
+fn closest_equivalent(c: char) -> Option<&'static str> {
+    Some(match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' | '`' => "'",
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' | '«' | '»' => "\"",
+        '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}'
+        | '\u{2212}' => "-",
+        '\u{2026}' => "...",
+        '\u{2022}' | '\u{00B7}' => "*",
+        '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2009}' | '\u{200A}' | '\u{3000}' => " ",
+        '\u{200B}' | '\u{FEFF}' => "",
+        '\u{2122}' => "TM",
+        '©' => "(c)",
+        '®' => "(R)",
+        '×' => "x",
+        '÷' => "/",
+        '≤' => "<=",
+        '≥' => ">=",
+        '≠' => "!=",
+        '→' => "->",
+        '←' => "<-",
+        '½' => "1/2",
+        '¼' => "1/4",
+        '¾' => "3/4",
+        'ß' => "ss",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ø' => "o",
+        'Ø' => "O",
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'ç' | 'ć' | 'č' => "c",
+        'Ç' | 'Ć' | 'Č' => "C",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ī' | 'Į' | 'İ' => "I",
+        'ñ' | 'ń' | 'ň' => "n",
+        'Ñ' | 'Ń' | 'Ň' => "N",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ō' | 'ő' => "o",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ō' | 'Ő' => "O",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => "u",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ū' | 'Ů' | 'Ű' => "U",
+        'ý' | 'ÿ' => "y",
+        'Ý' | 'Ÿ' => "Y",
+        'ś' | 'š' => "s",
+        'Ś' | 'Š' => "S",
+        'ź' | 'ż' | 'ž' => "z",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ł' => "l",
+        'Ł' => "L",
+        _ => return None,
+    })
+}
+
+impl Transliterator {
+    // Uses the glyphs of `font` when it could be found, otherwise assumes the font covers
+    // printable Latin-1
+    pub fn new(font: Option<&structs::Resource>, quiet: bool) -> Self {
+        let glyphs = font.map(|res| {
+            let data = ResourceData::new(res).decompress().into_owned();
+            let font: structs::Font = Reader::new(&data[..]).read(());
+            font.characters().collect()
+        });
+        Transliterator { glyphs, quiet }
+    }
+
+    fn is_supported(&self, c: char) -> bool {
+        if c.is_control() {
+            return true;
+        }
+        match &self.glyphs {
+            Some(glyphs) => (c as u32) < 0x10000 && glyphs.contains(&(c as u16)),
+            None => (c as u32) <= 0xFF,
+        }
+    }
+
+    pub fn transliterate(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut output = String::with_capacity(text.len());
+        let mut tag_end = 0;
+        for (position, &c) in chars.iter().enumerate() {
+            if position < tag_end {
+                output.push(c);
+                continue;
+            }
+            if c == '&' {
+                if let Some(len) = markup_len(&chars[position..]) {
+                    tag_end = position + len;
+                    output.push(c);
+                    continue;
+                }
+            }
+
+            if self.is_supported(c) {
+                output.push(c);
+                continue;
+            }
+
+            let replacement = match closest_equivalent(c) {
+                Some(s) if s.chars().all(|c| self.is_supported(c)) => s,
+                _ => "?",
+            };
+            if !self.quiet {
+                warn!(
+                    "Character '{}' (U+{:04X}) at position {} of \"{}\" can't be displayed, replaced with \"{}\"",
+                    c,
+                    c as u32,
+                    position,
+                    text.trim_end_matches('\0'),
+                    replacement
+                );
+            }
+            output.push_str(replacement);
+        }
+        output
+    }
+}
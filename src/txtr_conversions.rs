@@ -1,8 +1,12 @@
-use std::convert::TryInto;
+use std::{collections::HashMap, convert::TryInto, path::Path};
 
+use rayon::prelude::*;
+use reader_writer::{Reader, Writable};
 use resource_info_table::{resource_info, ResourceInfo};
 use texpresso::Format;
 
+use crate::asset_cache::AssetCache;
+
 // 0 - Power
 // 1 - Gravity
 // 2 - Varia
@@ -170,23 +174,34 @@ impl CmprPixelIter {
 impl Iterator for CmprPixelIter {
     type Item = (usize, usize);
     fn next(&mut self) -> Option<Self::Item> {
-        let inner_x = self.cnt & 1;
-        let inner_y = (self.cnt & 2) >> 1;
-        let block_x = ((self.cnt & !3) >> 2) % (self.width / 8);
-        let block_y = ((self.cnt & !3) >> 2) / (self.width / 8);
-
-        let first_pixel_x = block_x * 8 + inner_x * 4;
-        // TODO: Check for underflow, if we did, return None?
-        let first_pixel_y = self.height - 4 - (block_y * 8 + inner_y * 4);
+        let first_pixel = cmpr_block_origin(self.cnt, self.width, self.height);
         self.cnt += 1;
-        Some((first_pixel_x, first_pixel_y))
+        Some(first_pixel)
     }
 }
 
+// The first pixel of the `cnt`th 4x4 block of a CMPR image, which stores 2x2 groups of them
+fn cmpr_block_origin(cnt: usize, width: usize, height: usize) -> (usize, usize) {
+    let inner_x = cnt & 1;
+    let inner_y = (cnt & 2) >> 1;
+    let block_x = ((cnt & !3) >> 2) % (width / 8);
+    let block_y = ((cnt & !3) >> 2) / (width / 8);
+
+    let first_pixel_x = block_x * 8 + inner_x * 4;
+    // TODO: Check for underflow, if we did, return None?
+    let first_pixel_y = height - 4 - (block_y * 8 + inner_y * 4);
+    (first_pixel_x, first_pixel_y)
+}
+
 pub fn cmpr_decompress(compressed: &[u8], width: usize, height: usize, decompressed: &mut [u8]) {
+    // The blocks are decoded in parallel, but their pixels are scattered across rows so they're
+    // copied into the image afterwards
+    let blocks: Vec<_> = compressed
+        .par_chunks_exact(8)
+        .map(decompress_dxt1gcn_block)
+        .collect();
     let cmpr_iter = CmprPixelIter::new(width, height);
-    for (chunk, (first_pixel_x, first_pixel_y)) in compressed.chunks_exact(8).zip(cmpr_iter) {
-        let decompressed_pixels = decompress_dxt1gcn_block(chunk);
+    for (decompressed_pixels, (first_pixel_x, first_pixel_y)) in blocks.into_iter().zip(cmpr_iter) {
         for y in 0..4 {
             for x in 0..4 {
                 let pixel_x = first_pixel_x + x;
@@ -200,23 +215,27 @@ pub fn cmpr_decompress(compressed: &[u8], width: usize, height: usize, decompres
 }
 
 pub fn cmpr_compress(uncompressed: &[u8], width: usize, height: usize, compressed: &mut [u8]) {
-    let cmpr_iter = CmprPixelIter::new(width, height);
-    for (chunk, (first_pixel_x, first_pixel_y)) in compressed.chunks_exact_mut(8).zip(cmpr_iter) {
-        let mut uncompressed_pixels = [[0u8; 4]; 16];
-
-        for y in 0..4 {
-            for x in 0..4 {
-                let pixel_x = first_pixel_x + x;
-                let pixel_y = first_pixel_y + (3 - y);
-                let pixel_start = (pixel_y * width + pixel_x) * 4;
-                uncompressed_pixels[y * 4 + x] = uncompressed[pixel_start..pixel_start + 4]
-                    .try_into()
-                    .unwrap();
+    // Each block only reads its own pixels, so they're all compressed in parallel
+    compressed
+        .par_chunks_exact_mut(8)
+        .enumerate()
+        .for_each(|(cnt, chunk)| {
+            let (first_pixel_x, first_pixel_y) = cmpr_block_origin(cnt, width, height);
+            let mut uncompressed_pixels = [[0u8; 4]; 16];
+
+            for y in 0..4 {
+                for x in 0..4 {
+                    let pixel_x = first_pixel_x + x;
+                    let pixel_y = first_pixel_y + (3 - y);
+                    let pixel_start = (pixel_y * width + pixel_x) * 4;
+                    uncompressed_pixels[y * 4 + x] = uncompressed[pixel_start..pixel_start + 4]
+                        .try_into()
+                        .unwrap();
+                }
             }
-        }
 
-        compress_dxt1gcn_block(uncompressed_pixels, chunk);
-    }
+            compress_dxt1gcn_block(uncompressed_pixels, chunk);
+        });
 }
 
 pub fn huerotate_matrix(angle: f32) -> [f32; 9] {
@@ -252,20 +271,202 @@ pub fn huerotate_color(matrix: [f32; 9], r: u8, g: u8, b: u8) -> [u8; 3] {
 
 // Adapted from image-rs
 pub fn huerotate_in_place(image: &mut [u8], width: usize, height: usize, matrix: [f32; 9]) {
-    for y in 0..height {
-        for x in 0..width {
-            let start = (y * width + x) * 4;
-            let pixel = &mut image[start..start + 4];
+    image[..width * height * 4]
+        .par_chunks_exact_mut(width * 4)
+        .for_each(|row| {
+            for pixel in row.chunks_exact_mut(4) {
+                let new_rgb = huerotate_color(matrix, pixel[0], pixel[1], pixel[2]);
+
+                let outpixel = [new_rgb[0], new_rgb[1], new_rgb[2], pixel[3]];
 
-            let new_rgb = huerotate_color(matrix, pixel[0], pixel[1], pixel[2]);
+                pixel.copy_from_slice(&outpixel[..]);
+            }
+        });
+}
 
-            let outpixel = [new_rgb[0], new_rgb[1], new_rgb[2], pixel[3]];
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColorRemap {
+    pub hue_deg: f32,
+    pub saturation: f32,
+    pub value: f32,
+    pub matrix: [f32; 9], // row-major RGB matrix applied after the HSV adjustments
+}
 
-            pixel.copy_from_slice(&outpixel[..]);
+impl Default for ColorRemap {
+    fn default() -> Self {
+        ColorRemap {
+            hue_deg: 0.0,
+            saturation: 1.0,
+            value: 1.0,
+            matrix: [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
         }
     }
 }
 
+fn rgb_to_hsv(r: f32, g: f32, b: f32) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+
+    [hue, saturation, max]
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
+    let c = v * s;
+    let x = c * (1.0 - ((h / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let m = v - c;
+
+    let (r, g, b) = match (h.rem_euclid(360.0) / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    [r + m, g + m, b + m]
+}
+
+pub fn remap_color(remap: &ColorRemap, r: u8, g: u8, b: u8) -> [u8; 3] {
+    let rgb = [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0];
+    remap_hsv_matrix(remap, rgb, true).map(|c| (c * 255.0) as u8)
+}
+
+// Same as remap_color, but for float colors such as particle color keys. These are left unclamped,
+// as particles can be overbright.
+pub fn remap_rgb(remap: &ColorRemap, rgb: [f32; 3]) -> [f32; 3] {
+    remap_hsv_matrix(remap, rgb, false)
+}
+
+fn remap_hsv_matrix(remap: &ColorRemap, rgb: [f32; 3], clamp: bool) -> [f32; 3] {
+    let clamp = |c: f32| if clamp { c.clamp(0.0, 1.0) } else { c };
+    let [h, s, v] = rgb_to_hsv(rgb[0], rgb[1], rgb[2]);
+    let [r, g, b] = hsv_to_rgb(
+        h + remap.hue_deg,
+        (s * remap.saturation).clamp(0.0, 1.0),
+        clamp(v * remap.value),
+    );
+
+    let matrix = remap.matrix;
+    [
+        clamp(matrix[0] * r + matrix[1] * g + matrix[2] * b),
+        clamp(matrix[3] * r + matrix[4] * g + matrix[5] * b),
+        clamp(matrix[6] * r + matrix[7] * g + matrix[8] * b),
+    ]
+}
+
+pub fn remap_in_place(image: &mut [u8], width: usize, height: usize, remap: &ColorRemap) {
+    image[..width * height * 4]
+        .par_chunks_exact_mut(4)
+        .for_each(|pixel| {
+            let new_rgb = remap_color(remap, pixel[0], pixel[1], pixel[2]);
+            pixel[..3].copy_from_slice(&new_rgb[..]);
+        });
+}
+
+// Hue rotates every mipmap of a CMPR texture, other formats are left untouched
+pub fn huerotate_txtr(
+    res: &mut structs::Resource,
+    matrix: [f32; 9],
+    cache: &AssetCache,
+) -> Result<(), String> {
+    let key: Vec<u8> = b"huerotate"
+        .iter()
+        .copied()
+        .chain(matrix.iter().flat_map(|x| x.to_be_bytes()))
+        .collect();
+    recolor_txtr(res, &key, cache, |image, w, h| {
+        huerotate_in_place(image, w, h, matrix)
+    })
+}
+
+// Same as huerotate_txtr, but with a full HSV/RGB remap
+pub fn remap_txtr(
+    res: &mut structs::Resource,
+    remap: &ColorRemap,
+    cache: &AssetCache,
+) -> Result<(), String> {
+    let key: Vec<u8> = b"remap"
+        .iter()
+        .copied()
+        .chain(
+            [remap.hue_deg, remap.saturation, remap.value]
+                .iter()
+                .chain(remap.matrix.iter())
+                .flat_map(|x| x.to_be_bytes()),
+        )
+        .collect();
+    recolor_txtr(res, &key, cache, |image, w, h| {
+        remap_in_place(image, w, h, remap)
+    })
+}
+
+// `key` identifies the recoloring for the cache
+fn recolor_txtr<F>(
+    res: &mut structs::Resource,
+    key: &[u8],
+    cache: &AssetCache,
+    recolor: F,
+) -> Result<(), String>
+where
+    F: Fn(&mut [u8], usize, usize),
+{
+    let res_data = match &res.kind {
+        structs::ResourceKind::Unknown(_, _) => crate::ResourceData::new(res),
+        structs::ResourceKind::External(_, _) => crate::ResourceData::new_external(res),
+        _ => return Err("Unsupported resource kind for recoloring.".to_string()),
+    };
+    let data = res_data.decompress().into_owned();
+    let mut reader = Reader::new(&data[..]);
+    let mut txtr: structs::Txtr = reader.read(());
+
+    if !matches!(txtr.format, structs::TxtrFormat::Cmpr) {
+        return Ok(());
+    }
+
+    let bytes = cache.get_or_build("TXTR", &[key, &data], || {
+        let mut w = txtr.width as usize;
+        let mut h = txtr.height as usize;
+        for mipmap in txtr.pixel_data.as_mut_vec() {
+            if w < 8 || h < 8 {
+                break;
+            }
+
+            let mut decompressed_bytes = vec![0u8; w * h * 4];
+            cmpr_decompress(&mipmap.as_mut_vec()[..], h, w, &mut decompressed_bytes[..]);
+            recolor(&mut decompressed_bytes[..], w, h);
+            cmpr_compress(
+                &(decompressed_bytes[..]),
+                w,
+                h,
+                &mut mipmap.as_mut_vec()[..],
+            );
+            w /= 2;
+            h /= 2;
+        }
+
+        let mut bytes = vec![];
+        txtr.write_to(&mut bytes).unwrap();
+        Ok(bytes)
+    })?;
+    res.kind = structs::ResourceKind::External(bytes, b"TXTR".into());
+    res.compressed = false;
+
+    Ok(())
+}
+
 pub fn compress_dxt1gcn_block(rgba: [[u8; 4]; 16], block: &mut [u8]) {
     Format::Bc1.compress_block_masked(
         rgba,
@@ -301,3 +502,284 @@ fn reverse_byte(byte: u8) -> u8 {
         | (byte & 0b00110000) >> 2
         | (byte & 0b11000000) >> 6
 }
+
+fn encode_rgb565(pixel: &[u8]) -> [u8; 2] {
+    let v = ((pixel[0] as u16 >> 3) << 11) | ((pixel[1] as u16 >> 2) << 5) | (pixel[2] as u16 >> 3);
+    v.to_be_bytes()
+}
+
+fn encode_rgb5a3(pixel: &[u8]) -> [u8; 2] {
+    let v = if pixel[3] == 0xff {
+        0x8000
+            | ((pixel[0] as u16 >> 3) << 10)
+            | ((pixel[1] as u16 >> 3) << 5)
+            | (pixel[2] as u16 >> 3)
+    } else {
+        ((pixel[3] as u16 >> 5) << 12)
+            | ((pixel[0] as u16 >> 4) << 8)
+            | ((pixel[1] as u16 >> 4) << 4)
+            | (pixel[2] as u16 >> 4)
+    };
+    v.to_be_bytes()
+}
+
+fn encode_ia8(pixel: &[u8]) -> [u8; 2] {
+    let intensity = (pixel[0] as u16 * 77 + pixel[1] as u16 * 150 + pixel[2] as u16 * 29) >> 8;
+    [pixel[3], intensity as u8]
+}
+
+fn encode_palette_color(format: structs::TxtrPaletteFormat, pixel: &[u8]) -> [u8; 2] {
+    match format {
+        structs::TxtrPaletteFormat::Ia8 => encode_ia8(pixel),
+        structs::TxtrPaletteFormat::Rgb565 => encode_rgb565(pixel),
+        structs::TxtrPaletteFormat::Rgb5A3 => encode_rgb5a3(pixel),
+    }
+}
+
+// Halves an RGBA image in both dimensions by averaging each 2x2 square of pixels
+fn box_filter_rgba(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut output = Vec::with_capacity(width * height);
+    for y in (0..height - 1).step_by(2) {
+        for x in (0..width - 1).step_by(2) {
+            for c in 0..4 {
+                let sum = pixels[(y * width + x) * 4 + c] as u16
+                    + pixels[(y * width + x + 1) * 4 + c] as u16
+                    + pixels[((y + 1) * width + x) * 4 + c] as u16
+                    + pixels[((y + 1) * width + x + 1) * 4 + c] as u16;
+                output.push((sum / 4) as u8);
+            }
+        }
+    }
+    output
+}
+
+// As in txtr_converter, TXTRs are stored bottom row first, apart from the paletted formats
+fn txtr_flipped(format: &structs::TxtrFormat) -> bool {
+    !matches!(
+        format,
+        structs::TxtrFormat::C4(_, _) | structs::TxtrFormat::C8(_, _)
+    )
+}
+
+fn flip_rows(pixels: &[u8], width: usize) -> Vec<u8> {
+    pixels.chunks(width * 4).rev().flatten().copied().collect()
+}
+
+// Encodes an RGBA image (rows top to bottom) into the tiled layout of a non-CMPR format,
+// bottom row first if `flipped` is set
+fn encode_tiled(
+    format: &structs::TxtrFormat,
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    palette: &HashMap<[u8; 2], u8>,
+    flipped: bool,
+) -> Vec<u8> {
+    let (block_w, block_h, bytes_per_block) = match format {
+        structs::TxtrFormat::C8(_, _) => (8, 4, 32),
+        structs::TxtrFormat::Rgba8 => (4, 4, 64),
+        _ => (4, 4, 32),
+    };
+
+    let mut blocks = vec![0u8; width * height * bytes_per_block / (block_w * block_h)];
+    for (i, block) in blocks.chunks_mut(bytes_per_block).enumerate() {
+        let outer_x = (i % (width / block_w)) * block_w;
+        let outer_y = (i / (width / block_w)) * block_h;
+        for inner_y in 0..block_h {
+            for inner_x in 0..block_w {
+                let y = outer_y + inner_y;
+                let y = if flipped { height - 1 - y } else { y };
+                let start = (y * width + outer_x + inner_x) * 4;
+                let pixel = &pixels[start..start + 4];
+                let texel = inner_y * block_w + inner_x;
+                match format {
+                    structs::TxtrFormat::C8(fmt, _) => {
+                        block[texel] = palette[&encode_palette_color(*fmt, pixel)];
+                    }
+                    structs::TxtrFormat::Rgba8 => {
+                        // Alpha and red are stored first, followed by green and blue
+                        block[texel * 2] = pixel[3];
+                        block[texel * 2 + 1] = pixel[0];
+                        block[32 + texel * 2] = pixel[1];
+                        block[32 + texel * 2 + 1] = pixel[2];
+                    }
+                    structs::TxtrFormat::Rgb565 => {
+                        block[texel * 2..texel * 2 + 2].copy_from_slice(&encode_rgb565(pixel));
+                    }
+                    _ => {
+                        block[texel * 2..texel * 2 + 2].copy_from_slice(&encode_rgb5a3(pixel));
+                    }
+                }
+            }
+        }
+    }
+    blocks
+}
+
+// Encodes an RGBA image as plain GX RGB5A3 tiles, as used by disc banners
+pub fn encode_rgb5a3_image(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let format = structs::TxtrFormat::Rgb5A3;
+    encode_tiled(&format, pixels, width, height, &HashMap::new(), false)
+}
+
+pub fn resize_rgba(
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+    new_width: usize,
+    new_height: usize,
+) -> Vec<u8> {
+    if (width, height) == (new_width, new_height) {
+        return pixels;
+    }
+    let image = image::RgbaImage::from_raw(width as u32, height as u32, pixels).unwrap();
+    image::imageops::resize(
+        &image,
+        new_width as u32,
+        new_height as u32,
+        image::imageops::FilterType::Triangle,
+    )
+    .into_raw()
+}
+
+// Decodes a PNG or DDS file into RGBA pixels
+pub fn read_texture_file(path: &Path) -> Result<(Vec<u8>, usize, usize), String> {
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to read texture '{}': {}", path.display(), e))?
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+    Ok((image.into_raw(), width as usize, height as usize))
+}
+
+// Builds a TXTR from an RGBA image, reusing the format and mipmap count of the texture being
+// replaced when possible. Formats that can't faithfully hold a color image (intensity and 4-bit
+// paletted textures) are replaced with RGBA8 textures instead.
+pub fn encode_txtr(
+    original: &structs::Txtr,
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+) -> Result<Vec<u8>, String> {
+    let mut format = match &original.format {
+        structs::TxtrFormat::Cmpr => structs::TxtrFormat::Cmpr,
+        structs::TxtrFormat::Rgb565 => structs::TxtrFormat::Rgb565,
+        structs::TxtrFormat::Rgb5A3 => structs::TxtrFormat::Rgb5A3,
+        structs::TxtrFormat::C8(fmt, _) => structs::TxtrFormat::C8(*fmt, Default::default()),
+        _ => structs::TxtrFormat::Rgba8,
+    };
+
+    let (block_w, block_h) = match format {
+        structs::TxtrFormat::Cmpr => (8, 8),
+        structs::TxtrFormat::C8(_, _) => (8, 4),
+        _ => (4, 4),
+    };
+    if width == 0 || height == 0 || width & (block_w - 1) != 0 || height & (block_h - 1) != 0 {
+        return Err(format!(
+            "Texture dimensions ({}, {}) must be a multiple of ({}, {})",
+            width, height, block_w, block_h
+        ));
+    }
+
+    let mut palette = HashMap::new();
+    if let structs::TxtrFormat::C8(fmt, buf) = &mut format {
+        for pixel in pixels.chunks(4) {
+            let encoded = encode_palette_color(*fmt, pixel);
+            if palette.contains_key(&encoded) {
+                continue;
+            }
+            let idx = palette.len();
+            if idx >= 256 {
+                return Err("Texture contains too many colors for a C8 texture".to_string());
+            }
+            buf[idx * 2..idx * 2 + 2].copy_from_slice(&encoded);
+            palette.insert(encoded, idx as u8);
+        }
+    }
+
+    let mipmap_count = match format {
+        structs::TxtrFormat::C8(_, _) => 1,
+        _ => {
+            let mut count = 1;
+            while count < original.pixel_data.len()
+                && (width >> count) >= block_w
+                && (height >> count) >= block_h
+                && (width >> count) & (block_w - 1) == 0
+                && (height >> count) & (block_h - 1) == 0
+            {
+                count += 1;
+            }
+            count
+        }
+    };
+
+    let mut mipmaps: Vec<reader_writer::LazyArray<u8>> = Vec::with_capacity(mipmap_count);
+    let mut pixels = pixels;
+    let (mut w, mut h) = (width, height);
+    let flipped = txtr_flipped(&format);
+    for _ in 0..mipmap_count {
+        let data = match format {
+            structs::TxtrFormat::Cmpr => {
+                let rows = if flipped {
+                    flip_rows(&pixels[..], w)
+                } else {
+                    pixels.clone()
+                };
+                let mut compressed = vec![0u8; w * h / 2];
+                cmpr_compress(&rows[..], w, h, &mut compressed[..]);
+                compressed
+            }
+            _ => encode_tiled(&format, &pixels[..], w, h, &palette, flipped),
+        };
+        mipmaps.push(data.into());
+        pixels = box_filter_rgba(&pixels[..], w, h);
+        w /= 2;
+        h /= 2;
+    }
+
+    let txtr = structs::Txtr {
+        format,
+        width: width as u16,
+        height: height as u16,
+        pixel_data: mipmaps.into(),
+    };
+    let mut bytes = vec![];
+    txtr.write_to(&mut bytes).unwrap();
+    Ok(bytes)
+}
+
+// Replaces the contents of a TXTR resource with an image read from disk. If `resize` is set, the
+// image is first scaled to the dimensions of the texture being replaced.
+pub fn replace_txtr(res: &mut structs::Resource, path: &Path, resize: bool) -> Result<(), String> {
+    let res_data = match &res.kind {
+        structs::ResourceKind::Unknown(_, _) => crate::ResourceData::new(res),
+        structs::ResourceKind::External(_, _) => crate::ResourceData::new_external(res),
+        _ => return Err("Unsupported resource kind for texture replacement.".to_string()),
+    };
+    let data = res_data.decompress().into_owned();
+    let mut reader = Reader::new(&data[..]);
+    let original: structs::Txtr = reader.read(());
+
+    let (mut pixels, mut width, mut height) = read_texture_file(path)?;
+    if resize {
+        let new_width = original.width as usize;
+        let new_height = original.height as usize;
+        pixels = resize_rgba(pixels, width, height, new_width, new_height);
+        width = new_width;
+        height = new_height;
+    }
+    let bytes = encode_txtr(&original, pixels, width, height)
+        .map_err(|e| format!("Failed to convert '{}': {}", path.display(), e))?;
+    res.kind = structs::ResourceKind::External(bytes, b"TXTR".into());
+    res.compressed = false;
+
+    Ok(())
+}
+
+#[test]
+fn test_remap_rgb_keeps_overbright_colors() {
+    let rgb = [1.5, 0.25, 2.0];
+    let remapped = remap_rgb(&ColorRemap::default(), rgb);
+    for (a, b) in rgb.iter().zip(remapped) {
+        assert!((a - b).abs() < 1e-5);
+    }
+}
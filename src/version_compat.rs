@@ -0,0 +1,260 @@
+use log::warn;
+
+use crate::patch_config::{
+    Beam, DifficultyBehavior, PalTiming, PatchConfig, PhazonDamageModifier, SuitDamageReduction,
+    Version, Visor,
+};
+
+const GAMECUBE: &[Version] = &[
+    Version::NtscU0_00,
+    Version::NtscU0_01,
+    Version::NtscU0_02,
+    Version::NtscK,
+    Version::NtscJ,
+    Version::Pal,
+];
+
+/// A config option whose implementation only exists for some versions of the game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feature {
+    // Everything patched into the DOL or the rel, which is skipped entirely for Trilogy
+    StartingVisor,
+    StartingBeam,
+    DifficultyBehavior,
+    EscapeSequenceCountsUp,
+    ForceFusion,
+    SuitColors,
+    SkipSplashScreens,
+    ShootInGrapple,
+    AutomaticCrashScreen,
+    NonvariaHeatDamage,
+    StaggeredSuitDamage,
+    MultiworldDolPatches,
+    PhazonDamageModifier,
+    WarpToStart,
+    SpringBall,
+    IceTraps,
+    EtankCapacity,
+    Quickplay,
+    TimerConfig,
+    GhostRecording,
+    GhostMemoryCardDump,
+    Tracker,
+    IntegrityCheck,
+    PickupCounter,
+    StartingPresets,
+    DebugFeatures,
+    PalTiming,
+    Widescreen,
+    LowEnergyAlarmThreshold,
+    StartingLogbook,
+    SaveIconAnimation,
+}
+
+impl Feature {
+    /// The config option the feature is requested with, as it's written in the JSON config
+    pub fn config_key(&self) -> &'static str {
+        match self {
+            Feature::StartingVisor => "gameConfig.startingVisor",
+            Feature::StartingBeam => "gameConfig.startingBeam",
+            Feature::DifficultyBehavior => "gameConfig.difficultyBehavior",
+            Feature::EscapeSequenceCountsUp => "gameConfig.escapeSequenceCountsUp",
+            Feature::ForceFusion => "preferences.forceFusion",
+            Feature::SuitColors => "preferences.suitColors",
+            Feature::SkipSplashScreens => "preferences.skipSplashScreens",
+            Feature::ShootInGrapple => "gameConfig.shootInGrapple",
+            Feature::AutomaticCrashScreen => "preferences.automaticCrashScreen",
+            Feature::NonvariaHeatDamage => "gameConfig.nonvariaHeatDamage",
+            Feature::StaggeredSuitDamage => "gameConfig.staggeredSuitDamage",
+            Feature::MultiworldDolPatches => "gameConfig.multiworldDolPatches",
+            Feature::PhazonDamageModifier => "gameConfig.phazonDamageModifier",
+            Feature::WarpToStart => "gameConfig.warpToStart",
+            Feature::SpringBall => "gameConfig.springBall",
+            Feature::IceTraps => "gameConfig.enableIceTraps",
+            Feature::EtankCapacity => "gameConfig.etankCapacity",
+            Feature::Quickplay => "preferences.quickplay",
+            Feature::TimerConfig => "preferences.timerConfig",
+            Feature::GhostRecording => "preferences.ghostRecordingAddress",
+            Feature::GhostMemoryCardDump => "preferences.ghostMemoryCardDump",
+            Feature::Tracker => "preferences.trackerAddress",
+            Feature::IntegrityCheck => "preferences.integrityCheck",
+            Feature::PickupCounter => "preferences.cosmetics.hud.pickupCounter",
+            Feature::StartingPresets => "gameConfig.startingPresets",
+            Feature::DebugFeatures => "preferences.debugFeatures",
+            Feature::PalTiming => "preferences.palTiming",
+            Feature::Widescreen => "preferences.display.widescreen",
+            Feature::LowEnergyAlarmThreshold => "preferences.cosmetics.lowEnergyAlarmThreshold",
+            Feature::StartingLogbook => "gameConfig.startingLogbook",
+            Feature::SaveIconAnimation => "gameConfig.gameBanner.saveIconSpeed",
+        }
+    }
+
+    /// The versions the feature is implemented for
+    pub fn supported_versions(&self) -> &'static [Version] {
+        match self {
+            Feature::StartingVisor
+            | Feature::StartingBeam
+            | Feature::DifficultyBehavior
+            | Feature::EscapeSequenceCountsUp
+            | Feature::ForceFusion
+            | Feature::SuitColors
+            | Feature::SkipSplashScreens
+            | Feature::ShootInGrapple
+            | Feature::AutomaticCrashScreen
+            | Feature::NonvariaHeatDamage
+            | Feature::StaggeredSuitDamage
+            | Feature::MultiworldDolPatches
+            | Feature::PhazonDamageModifier
+            | Feature::WarpToStart
+            | Feature::SpringBall
+            | Feature::IceTraps
+            | Feature::EtankCapacity
+            | Feature::Quickplay
+            | Feature::TimerConfig
+            | Feature::GhostRecording
+            | Feature::Tracker
+            | Feature::IntegrityCheck
+            | Feature::PickupCounter
+            | Feature::StartingPresets
+            | Feature::DebugFeatures => GAMECUBE,
+            Feature::PalTiming => &[Version::Pal],
+            // The memory card functions are only in the symbol tables of these
+            Feature::GhostMemoryCardDump => &[Version::NtscU0_00, Version::NtscU0_02],
+            Feature::Widescreen => &[Version::NtscU0_00, Version::NtscU0_02],
+            // CPlayerState::InitializeScanTimes is only in the symbol tables of these
+            Feature::StartingLogbook => &[Version::NtscU0_00, Version::NtscU0_02],
+            // CPlayer::IsEnergyLow and CCardFileInfo::LockIconToken are only in the symbol tables
+            // of these
+            Feature::LowEnergyAlarmThreshold | Feature::SaveIconAnimation => {
+                &[Version::NtscU0_00, Version::NtscU0_02, Version::Pal]
+            }
+        }
+    }
+
+    pub fn is_supported(&self, version: Version) -> bool {
+        self.supported_versions().contains(&version)
+    }
+}
+
+/// Lists the features which `config` asks for, i.e. the options which aren't left at a value
+/// that patches nothing
+pub fn requested_features(config: &PatchConfig) -> Vec<Feature> {
+    let requested = [
+        (
+            Feature::StartingVisor,
+            config.starting_visor != Visor::Combat,
+        ),
+        (Feature::StartingBeam, config.starting_beam != Beam::Power),
+        (
+            Feature::DifficultyBehavior,
+            config.difficulty_behavior != DifficultyBehavior::Either,
+        ),
+        (
+            Feature::EscapeSequenceCountsUp,
+            config.escape_sequence_counts_up,
+        ),
+        (Feature::ForceFusion, config.force_fusion),
+        (Feature::SuitColors, config.suit_colors.is_some()),
+        (Feature::SkipSplashScreens, config.skip_splash_screens),
+        (Feature::ShootInGrapple, config.shoot_in_grapple),
+        (Feature::AutomaticCrashScreen, config.automatic_crash_screen),
+        (Feature::NonvariaHeatDamage, config.nonvaria_heat_damage),
+        (
+            Feature::StaggeredSuitDamage,
+            config.staggered_suit_damage != SuitDamageReduction::Default,
+        ),
+        (Feature::MultiworldDolPatches, config.multiworld_dol_patches),
+        (
+            Feature::PhazonDamageModifier,
+            config.phazon_damage_modifier != PhazonDamageModifier::Default,
+        ),
+        (Feature::WarpToStart, config.warp_to_start),
+        (Feature::SpringBall, config.spring_ball),
+        (Feature::IceTraps, config.enable_ice_traps),
+        (Feature::EtankCapacity, config.etank_capacity != 100),
+        (Feature::Quickplay, config.quickplay),
+        (
+            Feature::TimerConfig,
+            config.timer_config.show_igt.unwrap_or(false)
+                || config.timer_config.show_room_timer.unwrap_or(false)
+                || config.timer_config.splits_address.is_some(),
+        ),
+        (
+            Feature::GhostRecording,
+            config.ghost_recording_address.is_some(),
+        ),
+        (Feature::GhostMemoryCardDump, config.ghost_memory_card_dump),
+        (Feature::Tracker, config.tracker_address.is_some()),
+        (Feature::IntegrityCheck, config.integrity_check),
+        (
+            Feature::PickupCounter,
+            config
+                .cosmetics
+                .hud
+                .as_ref()
+                .is_some_and(|hud| hud.pickup_counter.is_some()),
+        ),
+        (
+            Feature::StartingPresets,
+            !config.starting_presets.is_empty(),
+        ),
+        (Feature::DebugFeatures, config.debug_features),
+        (Feature::PalTiming, config.pal_timing != PalTiming::Default),
+        (
+            Feature::Widescreen,
+            config.display.widescreen.unwrap_or(false),
+        ),
+        (
+            Feature::LowEnergyAlarmThreshold,
+            config.cosmetics.low_energy_alarm_threshold.is_some(),
+        ),
+        (
+            Feature::StartingLogbook,
+            !config.starting_logbook.is_empty(),
+        ),
+        (
+            Feature::SaveIconAnimation,
+            config.game_banner.save_icon_speed.is_some()
+                || config
+                    .game_banner
+                    .save_icons
+                    .as_ref()
+                    .is_some_and(|icons| icons.len() > 2),
+        ),
+    ];
+
+    requested
+        .iter()
+        .filter(|(_, requested)| *requested)
+        .map(|(feature, _)| *feature)
+        .collect()
+}
+
+/// Checks that every feature `config` asks for is implemented for its version. The missing ones
+/// are an error with `strict_version`, otherwise they're only warned about.
+pub fn check_version_support(config: &PatchConfig) -> Result<(), String> {
+    let unsupported: Vec<&str> = requested_features(config)
+        .iter()
+        .filter(|feature| !feature.is_supported(config.version))
+        .map(|feature| feature.config_key())
+        .collect();
+    if unsupported.is_empty() {
+        return Ok(());
+    }
+
+    if config.strict_version {
+        Err(format!(
+            "These options are not supported for version {}: {}",
+            config.version,
+            unsupported.join(", ")
+        ))?;
+    }
+
+    warn!(
+        "These options are not supported for version {}: {}",
+        config.version,
+        unsupported.join(", ")
+    );
+
+    Ok(())
+}
@@ -327,11 +327,34 @@ pub struct CtwkBall<'r> {
     pub ball_gravity: f32,
     pub ball_water_gravity: f32,
     pub unknown1: GenericArray<f32, U3>,
-    pub dont_care0: GenericArray<f32, U27>,
+    pub dont_care0: GenericArray<f32, U7>, // alignment, tire and lean parameters
+    pub ball_camera_angle_per_second: f32,
+    pub ball_camera_offset: GenericArray<f32, U3>,
+    pub ball_camera_min_speed_distance: f32,
+    pub ball_camera_max_speed_distance: f32,
+    pub ball_camera_backwards_distance: f32,
+    pub unknown4: f32,
+    // Constant, max and tardis of the position, centroid, centroid distance and look at springs
+    pub ball_camera_springs: GenericArray<f32, U12>,
     pub unknown2: GenericArray<f32, U6>,
     pub conservative_door_cam_distance: f32,
     pub unknown3: f32,
-    pub dont_care1: GenericArray<f32, U27>,
+    pub ball_camera_chase_elevation: f32,
+    pub ball_camera_chase_dampen_angle: f32,
+    pub ball_camera_chase_distance: f32,
+    pub ball_camera_chase_yaw_speed: f32,
+    pub ball_camera_chase_angle_per_second: f32,
+    pub ball_camera_chase_look_at_offset: GenericArray<f32, U3>,
+    pub ball_camera_chase_spring: GenericArray<f32, U3>,
+    pub ball_camera_boost_elevation: f32,
+    pub ball_camera_boost_dampen_angle: f32,
+    pub ball_camera_boost_distance: f32,
+    pub ball_camera_boost_yaw_speed: f32,
+    pub ball_camera_boost_angle_per_second: f32,
+    pub ball_camera_boost_look_at_offset: GenericArray<f32, U3>,
+    pub ball_camera_boost_spring: GenericArray<f32, U3>,
+    pub ball_camera_control_distance: f32,
+    pub dont_care1: GenericArray<f32, U4>,
     pub boost_drain_time: f32,
     pub boost_min_charge_time: f32,
     pub boost_min_rel_speed_for_damage: f32,
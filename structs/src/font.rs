@@ -60,3 +60,9 @@ pub struct FontKerning {
     char2: u16,
     kerning_adjust: i32,
 }
+
+impl<'r> Font<'r> {
+    pub fn characters(&self) -> impl Iterator<Item = u16> + 'r {
+        self.glyphs.iter().map(|glyph| glyph.utf16_char)
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    io::{self, Write},
+    io::{self, Read, Seek, Write},
     iter,
 };
 
@@ -7,6 +7,7 @@ use auto_struct_macros::auto_struct;
 use reader_writer::{
     generic_array::GenericArray, typenum::*, CStr, Readable, Reader, RoArray, WithRead, Writable,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{bnr::Bnr, pak::Pak, thp::Thp};
 
@@ -14,11 +15,45 @@ use crate::{bnr::Bnr, pak::Pak, thp::Thp};
 
 pub const GC_DISC_LENGTH: usize = 1_459_978_240;
 
+/// Replaces the original on-disc order of the files when writing the disc.
+#[derive(Clone, Debug)]
+pub struct FileLayout {
+    /// File names in the order the game reads them. Unlisted files keep their original order
+    /// and are placed after every listed file.
+    pub access_order: Vec<Vec<u8>>,
+    /// Alignment of the start of each PAK, files are otherwise aligned to 32 bytes.
+    pub pak_alignment: u32,
+}
+
+/// Where each file of a written disc ended up, in FST order.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscManifest {
+    pub files: Vec<DiscManifestEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscManifestEntry {
+    pub name: String,
+    pub offset: u32,
+    pub length: u32,
+    /// FNV-1a hash of the file's contents
+    pub checksum: u64,
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
 pub struct GcDisc<'r> {
     pub header: GcDiscHeader,
     header_info: GenericArray<u8, U8192>,
     apploader: GcDiscApploader<'r>,
     pub file_system_root: FstEntry<'r>,
+    pub file_layout: Option<FileLayout>,
 }
 
 impl<'r> Readable<'r> for GcDisc<'r> {
@@ -42,6 +77,7 @@ impl<'r> Readable<'r> for GcDisc<'r> {
             header_info,
             apploader,
             file_system_root: fst,
+            file_layout: None,
         }
     }
 
@@ -50,11 +86,58 @@ impl<'r> Readable<'r> for GcDisc<'r> {
     }
 }
 
+/// The stages of patching an ISO, in the order they happen.
+#[derive(Clone, Copy, Debug)]
+pub enum ProgressStage<'a> {
+    /// Reading the file system of the input disc.
+    Extract,
+    /// Building the custom assets and gathering the game resources they depend on.
+    BuildAssets,
+    /// Applying the patches to a single file. `index` counts from 0 up to `count`.
+    PatchPak {
+        name: &'a [u8],
+        index: usize,
+        count: usize,
+    },
+    /// Writing the output disc while compressing it.
+    Compress,
+    /// Writing the output disc.
+    Write,
+}
+
 pub trait ProgressNotifier {
     fn notify_total_bytes(&mut self, total_size: usize);
     fn notify_writing_file(&mut self, file_name: &CStr, file_bytes: usize);
     fn notify_writing_header(&mut self);
     fn notify_flushing_to_disk(&mut self);
+
+    /// Called when patching moves on to a new stage.
+    fn notify_stage(&mut self, _stage: ProgressStage) {}
+
+    /// Called each time part of a file has been written to the output disc.
+    fn notify_bytes_written(&mut self, _bytes: usize) {}
+}
+
+/// Forwards the number of bytes written to a `ProgressNotifier`.
+struct ProgressWriter<'a, W, N> {
+    writer: &'a mut W,
+    notifier: &'a mut N,
+}
+
+impl<'a, W, N> Write for ProgressWriter<'a, W, N>
+where
+    W: Write,
+    N: ProgressNotifier,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.notifier.notify_bytes_written(written);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
 }
 
 pub trait WriteExt {
@@ -76,8 +159,17 @@ impl<'r> GcDisc<'r> {
         W: Write + WriteExt,
         N: ProgressNotifier,
     {
-        let raw_fst = self.file_system_root.generate_raw_fst_data();
-        let header_size = self.header.size() + self.header_info.size() + self.apploader.size();
+        let file_system_root = &self.file_system_root;
+        let raw_fst = file_system_root.generate_raw_fst_data(self.file_layout.as_ref());
+        Self::write_header(
+            &mut self.header,
+            &self.header_info,
+            &self.apploader,
+            file_system_root.size() as u32,
+            writer,
+            notifier,
+            &raw_fst,
+        )?;
 
         let files_offset = raw_fst
             .iter()
@@ -85,6 +177,94 @@ impl<'r> GcDisc<'r> {
             .map(|entry| entry.raw_entry.offset)
             .min()
             .unwrap();
+        let fst_end = (self.header.fst_offset + self.header.fst_length) as u64;
+        writer.skip_bytes(files_offset as u64 - fst_end)?;
+        FstEntry::write_files(writer, notifier, &raw_fst)
+    }
+
+    /// Rewrites a disc that was previously written with the layout in `previous`. Files that
+    /// still fit in their old spot stay there and are only written if their contents changed.
+    /// The rest are placed below the lowest file that stayed. If they don't fit there the whole
+    /// disc is written again.
+    ///
+    /// Returns the layout of the disc as written, to be passed in the next time.
+    pub fn write_in_place<W, N>(
+        &mut self,
+        writer: &mut W,
+        notifier: &mut N,
+        previous: Option<&DiscManifest>,
+    ) -> io::Result<DiscManifest>
+    where
+        W: Write + Seek,
+        N: ProgressNotifier,
+    {
+        let file_system_root = &self.file_system_root;
+        let mut raw_fst = file_system_root.generate_raw_fst_data(self.file_layout.as_ref());
+        let fst_end = self.header.fst_offset + file_system_root.size() as u32;
+        let previous = previous.filter(|previous| {
+            FstEntry::pin_to_manifest(&mut raw_fst, previous, self.file_layout.as_ref(), fst_end)
+        });
+        if previous.is_none() {
+            // Start over from the regular layout
+            raw_fst = file_system_root.generate_raw_fst_data(self.file_layout.as_ref());
+        }
+
+        writer.seek(io::SeekFrom::Start(0))?;
+        Self::write_header(
+            &mut self.header,
+            &self.header_info,
+            &self.apploader,
+            file_system_root.size() as u32,
+            writer,
+            notifier,
+            &raw_fst,
+        )?;
+
+        let mut manifest = DiscManifest::default();
+        let mut file_bytes = vec![];
+        for (i, e) in raw_fst
+            .iter()
+            .filter(|e| !e.raw_entry.is_folder())
+            .enumerate()
+        {
+            file_bytes.clear();
+            if let Some(f) = e.file {
+                f.write_to(&mut file_bytes)?;
+            }
+            let entry = DiscManifestEntry {
+                name: e.name.to_str().unwrap_or_default().to_owned(),
+                offset: e.raw_entry.offset,
+                length: e.raw_entry.length,
+                checksum: fnv1a(&file_bytes),
+            };
+
+            if previous.map(|p| &p.files[i]) == Some(&entry) {
+                notifier.notify_bytes_written(file_bytes.len());
+            } else {
+                notifier.notify_writing_file(e.name, file_bytes.len());
+                writer.seek(io::SeekFrom::Start(e.raw_entry.offset as u64))?;
+                writer.write_all(&file_bytes)?;
+                notifier.notify_bytes_written(file_bytes.len());
+            }
+            manifest.files.push(entry);
+        }
+        Ok(manifest)
+    }
+
+    fn write_header<W, N>(
+        header: &mut GcDiscHeader,
+        header_info: &GenericArray<u8, U8192>,
+        apploader: &GcDiscApploader<'r>,
+        fst_length: u32,
+        writer: &mut W,
+        notifier: &mut N,
+        raw_fst: &[WrappedFstEntry],
+    ) -> io::Result<()>
+    where
+        W: Write + WriteExt,
+        N: ProgressNotifier,
+    {
+        let header_size = header.size() + header_info.size() + apploader.size();
 
         let file_system_size = raw_fst
             .iter()
@@ -104,26 +284,23 @@ impl<'r> GcDisc<'r> {
                 io::Error::new(io::ErrorKind::Other, "Couldn't find default.dol".to_owned())
             })?;
 
-        self.header.main_dol_offset = main_dol_offset;
-        self.header.fst_length = self.file_system_root.size() as u32;
-        self.header.fst_max_length = self.header.fst_length;
+        header.main_dol_offset = main_dol_offset;
+        header.fst_length = fst_length;
+        header.fst_max_length = header.fst_length;
 
         notifier.notify_writing_header();
-        self.header.write_to(writer)?;
-        self.header_info.write_to(writer)?;
-        self.apploader.write_to(writer)?;
+        header.write_to(writer)?;
+        header_info.write_to(writer)?;
+        apploader.write_to(writer)?;
 
-        writer.skip_bytes(self.header.fst_offset as u64 - header_size as u64)?;
+        writer.skip_bytes(header.fst_offset as u64 - header_size as u64)?;
         for e in raw_fst.iter() {
             e.raw_entry.write_to(writer)?;
         }
         for e in raw_fst.iter() {
             e.name.write_to(writer)?;
         }
-
-        let fst_end = (self.header.fst_offset + self.header.fst_length) as u64;
-        writer.skip_bytes(files_offset as u64 - fst_end)?;
-        FstEntry::write_files(writer, notifier, &raw_fst)
+        Ok(())
     }
 }
 
@@ -238,7 +415,10 @@ impl<'r> Readable<'r> for FstEntry<'r> {
 }
 
 impl<'r> FstEntry<'r> {
-    fn generate_raw_fst_data<'a>(&'a self) -> Vec<WrappedFstEntry<'a, 'r>> {
+    fn generate_raw_fst_data<'a>(
+        &'a self,
+        layout: Option<&FileLayout>,
+    ) -> Vec<WrappedFstEntry<'a, 'r>> {
         struct S<'a, 'r> {
             entries: Vec<WrappedFstEntry<'a, 'r>>,
             parent_index: u32,
@@ -318,17 +498,79 @@ impl<'r> FstEntry<'r> {
             .iter_mut()
             .filter(|e| !e.raw_entry.is_folder())
             .collect();
-        entries.sort_by(|l, r| l.raw_entry.offset.cmp(&r.raw_entry.offset).reverse());
+        if let Some(layout) = layout {
+            let access_index = |e: &WrappedFstEntry| {
+                layout
+                    .access_order
+                    .iter()
+                    .position(|name| &name[..] == e.name.to_bytes())
+                    .unwrap_or(layout.access_order.len())
+            };
+            // Files are placed from the end of the disc, so the last file is sorted first
+            entries.sort_by(|l, r| {
+                (access_index(l), l.raw_entry.offset)
+                    .cmp(&(access_index(r), r.raw_entry.offset))
+                    .reverse()
+            });
+        } else {
+            entries.sort_by(|l, r| l.raw_entry.offset.cmp(&r.raw_entry.offset).reverse());
+        }
         let mut last_file_offset = GC_DISC_LENGTH as u32;
         for e in entries {
-            // We need to round up to a mupliple of 32
-            last_file_offset -= (e.raw_entry.length + 31) & (u32::max_value() - 31);
+            // We need to round down to a mupliple of the alignment
+            last_file_offset = (last_file_offset - e.raw_entry.length) & !(e.alignment(layout) - 1);
             e.raw_entry.offset = last_file_offset;
         }
 
         state.entries
     }
 
+    /// Moves the files back to where they were on the previous disc if they still fit. Returns
+    /// false if the files of the previous disc don't match or the rest don't fit after the FST.
+    fn pin_to_manifest(
+        raw_fst: &mut [WrappedFstEntry],
+        previous: &DiscManifest,
+        layout: Option<&FileLayout>,
+        fst_end: u32,
+    ) -> bool {
+        let mut files: Vec<_> = raw_fst
+            .iter_mut()
+            .filter(|e| !e.raw_entry.is_folder())
+            .collect();
+        if files.len() != previous.files.len()
+            || files
+                .iter()
+                .zip(previous.files.iter())
+                .any(|(e, p)| e.name.to_bytes() != p.name.as_bytes())
+        {
+            return false;
+        }
+
+        let mut moved = vec![];
+        let mut lowest_pinned_offset = GC_DISC_LENGTH as u32;
+        for (e, p) in files.iter_mut().zip(previous.files.iter()) {
+            if e.raw_entry.length <= (p.length + 31) & !31 {
+                e.raw_entry.offset = p.offset;
+                lowest_pinned_offset = lowest_pinned_offset.min(p.offset);
+            } else {
+                moved.push(e);
+            }
+        }
+
+        let mut last_file_offset = lowest_pinned_offset;
+        for e in moved {
+            let alignment = e.alignment(layout);
+            match last_file_offset.checked_sub(e.raw_entry.length) {
+                Some(offset) if offset & !(alignment - 1) >= fst_end => {
+                    last_file_offset = offset & !(alignment - 1);
+                    e.raw_entry.offset = last_file_offset;
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+
     fn write_files<W, N>(
         writer: &mut W,
         notifier: &mut N,
@@ -356,12 +598,14 @@ impl<'r> FstEntry<'r> {
             .collect();
         entries_and_zeroes.push((entries[entries.len() - 1], 0));
 
-        let zero_bytes = [0u8; 32];
         for (e, zeroes) in entries_and_zeroes {
             if let Some(f) = e.file {
                 notifier.notify_writing_file(e.name, e.raw_entry.length as usize);
-                f.write_to(writer)?;
-                writer.write_all(&zero_bytes[0..zeroes as usize])?;
+                f.write_to(&mut ProgressWriter {
+                    writer: &mut *writer,
+                    notifier: &mut *notifier,
+                })?;
+                io::copy(&mut io::repeat(0).take(zeroes as u64), writer)?;
             }
         }
         Ok(())
@@ -391,6 +635,15 @@ struct WrappedFstEntry<'a, 'r> {
     name: &'a CStr<'r>,
 }
 
+impl<'a, 'r> WrappedFstEntry<'a, 'r> {
+    fn alignment(&self, layout: Option<&FileLayout>) -> u32 {
+        match layout {
+            Some(layout) if self.name.to_bytes().ends_with(b".pak") => layout.pak_alignment,
+            _ => 32,
+        }
+    }
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum FstEntryFile<'r> {
@@ -38,7 +38,7 @@ pub struct Mapa<'r> {
     _pad: (),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, PartialEq, Debug, Deserialize, Copy, Clone)]
 pub enum MapaObjectType {
     DoorNormal = 0,
     DoorShield = 1,
@@ -187,26 +187,43 @@ impl<'r> Mapa<'r> {
         }
     }
 
-    pub fn add_pickup(&mut self, editor_id: u32, pickup_pos: [f32; 3]) {
+    // `icon` selects which of the pickup dot textures is drawn
+    pub fn add_pickup(&mut self, editor_id: u32, pickup_pos: [f32; 3], icon: u32) {
+        self.add_object(
+            MapaObjectType::Pickup,
+            MapaObjectVisibilityMode::Always,
+            editor_id,
+            pickup_pos,
+        );
+        self.objects.as_mut_vec().last_mut().unwrap().seed1 = icon;
+    }
+
+    pub fn add_object(
+        &mut self,
+        type_: MapaObjectType,
+        visibility_mode: MapaObjectVisibilityMode,
+        editor_id: u32,
+        position: [f32; 3],
+    ) {
         let mappable_objects = &mut self.objects;
         let transform_matrix = [
             1.0f32,
             0.0f32,
             0.0f32,
-            pickup_pos[0],
+            position[0],
             0.0f32,
             1.0f32,
             0.0f32,
-            pickup_pos[1],
+            position[1],
             0.0f32,
             0.0f32,
             1.0f32,
-            pickup_pos[2],
+            position[2],
         ]
         .into();
         mappable_objects.as_mut_vec().push(MapaObject {
-            type_: MapaObjectType::Pickup as u32,
-            visibility_mode: MapaObjectVisibilityMode::Always as u32,
+            type_: type_ as u32,
+            visibility_mode: visibility_mode as u32,
             editor_id,
             seed1: 0xFFFFFFFF,
             transform_matrix,
@@ -135,6 +135,23 @@ impl<'r> ResourceList<'r> {
     pub fn clear(&mut self) {
         self.list.clear()
     }
+
+    /// Serializes every resource that was parsed to be patched. Call it once a pak won't be
+    /// patched anymore, so that only the bytes of the changed resources are held until the disc
+    /// is written, the others still being read from the source disc.
+    ///
+    /// This is a barrier: the compacted resources can't be parsed anymore, their `as_*`
+    /// accessors return None. Anything that reads them afterwards (e.g. the pak optimizer) has
+    /// to serialize them with `write_to` and parse the bytes itself. Resources that were never
+    /// parsed are left as they are, still borrowed from the source disc, so this doesn't make
+    /// the patcher stream the disc; it only drops the parsed copies.
+    pub fn compact(&mut self) {
+        for elem in self.list.iter_mut() {
+            if let ResourceListElem::Inst(res) = elem {
+                res.kind.compact();
+            }
+        }
+    }
 }
 
 impl<'r> Readable<'r> for ResourceList<'r> {
@@ -647,3 +664,16 @@ build_resource_data!(
     as_ctwk,
     as_ctwk_mut,
 );
+
+impl<'r> ResourceKind<'r> {
+    /// Replaces a parsed resource with its serialized form, which takes much less memory. The
+    /// resource can't be parsed again afterwards, see `ResourceList::compact`.
+    pub fn compact(&mut self) {
+        if let ResourceKind::Unknown(_, _) | ResourceKind::External(_, _) = self {
+            return;
+        }
+        let mut bytes = Vec::with_capacity(self.size());
+        self.write_to(&mut bytes).unwrap();
+        *self = ResourceKind::External(bytes, self.fourcc());
+    }
+}
@@ -8,7 +8,7 @@ use reader_writer::{
 
 use crate::{
     scly_props,
-    scly_structs::{DamageInfo, DamageVulnerability, HealthInfo, PatternedInfo},
+    scly_structs::{DamageInfo, DamageVulnerability, HealthInfo, LightParameters, PatternedInfo},
 };
 
 #[macro_export]
@@ -116,6 +116,22 @@ macro_rules! impl_patterned_info_with_auxillary {
     };
 }
 
+#[macro_export]
+macro_rules! impl_light_params {
+    ($($($actor_params:ident).+),+) => {
+        const SUPPORTS_LIGHT_PARAMS: bool = true;
+
+        fn impl_get_light_params(&self) -> Vec<$crate::scly_structs::LightParameters> {
+            vec![$(self.$($actor_params).+.light_params.clone()),+]
+        }
+
+        fn impl_set_light_params(&mut self, x: Vec<$crate::scly_structs::LightParameters>) {
+            let mut x = x.into_iter();
+            $(self.$($actor_params).+.light_params = x.next().unwrap();)+
+        }
+    };
+}
+
 // damage_infos handled case-by-case
 // vulnerabilities handled case-by-case
 
@@ -228,6 +244,16 @@ macro_rules! build_scly_property {
                 }
             }
 
+            pub fn object_type_name(&self) -> Option<&'static str>
+            {
+                let object_type = self.object_type();
+                #[allow(unreachable_patterns)] // ridley throws a warning because we have both PAL and NTSC ridley definitions
+                match object_type {
+                    $(<scly_props::$name as SclyPropertyData>::OBJECT_TYPE => Some(stringify!($name)),)*
+                    _ => None,
+                }
+            }
+
             /* Position */
 
             pub fn supports_position(&self) -> bool {
@@ -494,6 +520,73 @@ macro_rules! build_scly_property {
                 }
             }
 
+            /* Light Params */
+
+            pub fn supports_light_params(&self) -> bool {
+                let object_type = self.object_type();
+                #[allow(unreachable_patterns)] // ridley throws a warning because we have both PAL and NTSC ridley definitions
+                match object_type {
+                    $(<scly_props::$name as SclyPropertyData>::OBJECT_TYPE => <scly_props::$name as SclyPropertyData>::SUPPORTS_LIGHT_PARAMS,)*
+                    _ => false,
+                }
+            }
+
+            pub fn get_light_params(&mut self) -> Vec<LightParameters>
+            {
+                self.guess_kind();
+                match *self {
+                    SclyProperty::Unknown { object_type, .. } => panic!("0x{:X} doesn't support light params (get)", object_type),
+                    $(
+                        SclyProperty::$name(_) => {
+                            let prop = self.$accessor();
+                            prop.unwrap().impl_get_light_params()
+                        },
+                    )*
+                }
+            }
+
+            pub fn set_light_params(&mut self, x: Vec<LightParameters>)
+            {
+                self.guess_kind();
+                match *self {
+                    SclyProperty::Unknown { object_type, .. } => panic!("0x{:X} doesn't support light params (set)", object_type),
+                    $(
+                        SclyProperty::$name(_) => {
+                            self.$accessor_mut().unwrap().impl_set_light_params(x);
+                        },
+                    )*
+                }
+            }
+
+            /* Asset IDs */
+
+            pub fn supports_asset_ids(&self) -> bool {
+                let object_type = self.object_type();
+                #[allow(unreachable_patterns)] // ridley throws a warning because we have both PAL and NTSC ridley definitions
+                match object_type {
+                    $(<scly_props::$name as SclyPropertyData>::OBJECT_TYPE => <scly_props::$name as SclyPropertyData>::SUPPORTS_ASSET_IDS,)*
+                    _ => false,
+                }
+            }
+
+            // The assets the object references directly, without the invalid (0 and 0xFFFFFFFF)
+            // ids. Empty for the object types which don't list their assets.
+            pub fn get_asset_ids(&self) -> Vec<(u32, FourCC)>
+            {
+                #[allow(unreachable_patterns)] // ridley throws a warning because we have both PAL and NTSC ridley definitions
+                let mut asset_ids = match self.object_type() {
+                    $(
+                        <scly_props::$name as SclyPropertyData>::OBJECT_TYPE
+                            if <scly_props::$name as SclyPropertyData>::SUPPORTS_ASSET_IDS => {
+                            self.$accessor().unwrap().impl_get_asset_ids()
+                        },
+                    )*
+                    _ => vec![],
+                };
+                asset_ids.retain(|(id, _)| *id != 0 && *id != 0xFFFFFFFF);
+                asset_ids
+            }
+
             pub fn guess_kind(&mut self)
             {
                 if self.object_type() == 0x10 { // camera hint (TODO)
@@ -1119,6 +1212,33 @@ pub trait SclyPropertyData {
             Self::OBJECT_TYPE
         )
     }
+
+    /* Light Params */
+    const SUPPORTS_LIGHT_PARAMS: bool = false;
+
+    fn impl_get_light_params(&self) -> Vec<LightParameters> {
+        panic!(
+            "Script object type 0x{:X} does not implement the 'Light Params' property",
+            Self::OBJECT_TYPE
+        )
+    }
+
+    fn impl_set_light_params(&mut self, _: Vec<LightParameters>) {
+        panic!(
+            "Script object type 0x{:X} does not implement the 'Light Params' property",
+            Self::OBJECT_TYPE
+        )
+    }
+
+    /* Asset IDs */
+    const SUPPORTS_ASSET_IDS: bool = false;
+
+    fn impl_get_asset_ids(&self) -> Vec<(u32, FourCC)> {
+        panic!(
+            "Script object type 0x{:X} does not implement the 'Asset IDs' property",
+            Self::OBJECT_TYPE
+        )
+    }
 }
 
 #[auto_struct(Readable, FixedSize, Writable)]
@@ -1,5 +1,5 @@
 use auto_struct_macros::auto_struct;
-use reader_writer::{generic_array::GenericArray, typenum::*, CStr};
+use reader_writer::{generic_array::GenericArray, typenum::*, CStr, FourCC};
 
 use crate::{
     res_id::*,
@@ -44,12 +44,13 @@ pub struct Actor<'r> {
     pub unknown13: u8,
 }
 
-use crate::{impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for Actor<'r> {
     const OBJECT_TYPE: u8 = 0x0;
     impl_position!();
     impl_rotation!();
     impl_scale!();
+    impl_light_params!(actor_params);
 
     const SUPPORTS_VULNERABILITIES: bool = true;
 
@@ -70,4 +71,12 @@ impl<'r> SclyPropertyData for Actor<'r> {
     fn impl_set_health_infos(&mut self, x: Vec<HealthInfo>) {
         self.health_info = x[0].clone();
     }
+
+    const SUPPORTS_ASSET_IDS: bool = true;
+
+    fn impl_get_asset_ids(&self) -> Vec<(u32, FourCC)> {
+        let mut asset_ids = vec![self.cmdl.into(), self.ancs.file_id.into()];
+        asset_ids.extend(self.actor_params.asset_ids());
+        asset_ids
+    }
 }
@@ -30,13 +30,14 @@ pub struct ActorContraption<'r> {
     pub dont_care2: u8,
 }
 
-use crate::{impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for ActorContraption<'r> {
     const OBJECT_TYPE: u8 = 0x6E;
 
     impl_position!();
     impl_rotation!();
     impl_scale!();
+    impl_light_params!(actor_params);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
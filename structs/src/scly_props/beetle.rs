@@ -30,7 +30,7 @@ pub struct Beetle<'r> {
     pub retreat_time: f32,
 }
 
-use crate::{impl_patterned_info, impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_patterned_info, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for Beetle<'r> {
     const OBJECT_TYPE: u8 = 0x16;
 
@@ -38,6 +38,7 @@ impl<'r> SclyPropertyData for Beetle<'r> {
     impl_rotation!();
     impl_scale!();
     impl_patterned_info!();
+    impl_light_params!(actor_params);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
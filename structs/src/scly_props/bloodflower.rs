@@ -2,8 +2,8 @@ use auto_struct_macros::auto_struct;
 use reader_writer::{generic_array::GenericArray, typenum::*, CStr};
 
 use crate::{
-    impl_patterned_info, impl_position, impl_rotation, impl_scale, scly_props::structs::*,
-    SclyPropertyData,
+    impl_light_params, impl_patterned_info, impl_position, impl_rotation, impl_scale,
+    scly_props::structs::*, SclyPropertyData,
 };
 
 #[auto_struct(Readable, Writable)]
@@ -40,6 +40,7 @@ impl<'r> SclyPropertyData for Bloodflower<'r> {
     impl_rotation!();
     impl_scale!();
     impl_patterned_info!();
+    impl_light_params!(actor_params);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
@@ -1,5 +1,5 @@
 use auto_struct_macros::auto_struct;
-use reader_writer::{generic_array::GenericArray, typenum::*, CStr};
+use reader_writer::{generic_array::GenericArray, typenum::*, CStr, FourCC};
 
 use crate::{
     res_id::*,
@@ -58,4 +58,14 @@ impl<'r> SclyPropertyData for DamageableTrigger<'r> {
     fn impl_set_health_infos(&mut self, x: Vec<HealthInfo>) {
         self.health_info = x[0].clone();
     }
+
+    const SUPPORTS_ASSET_IDS: bool = true;
+
+    fn impl_get_asset_ids(&self) -> Vec<(u32, FourCC)> {
+        vec![
+            self.pattern_txtr0.into(),
+            self.pattern_txtr1.into(),
+            self.color_txtr.into(),
+        ]
+    }
 }
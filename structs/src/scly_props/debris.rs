@@ -1,7 +1,10 @@
 use auto_struct_macros::auto_struct;
 use reader_writer::{generic_array::GenericArray, typenum::*, CStr};
 
-use crate::{impl_position, impl_rotation, impl_scale, scly_props::structs::*, SclyPropertyData};
+use crate::{
+    impl_light_params, impl_position, impl_rotation, impl_scale, scly_props::structs::*,
+    SclyPropertyData,
+};
 
 #[auto_struct(Readable, Writable)]
 #[derive(Debug, Clone)]
@@ -30,4 +33,5 @@ impl<'r> SclyPropertyData for Debris<'r> {
     impl_position!();
     impl_rotation!();
     impl_scale!();
+    impl_light_params!(actor_params);
 }
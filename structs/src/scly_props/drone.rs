@@ -28,7 +28,7 @@ pub struct Drone<'r> {
     pub dont_care: GenericArray<u8, U273>,
 }
 
-use crate::{impl_patterned_info, impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_patterned_info, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for Drone<'r> {
     const OBJECT_TYPE: u8 = 0x43;
 
@@ -36,6 +36,7 @@ impl<'r> SclyPropertyData for Drone<'r> {
     impl_rotation!();
     impl_scale!();
     impl_patterned_info!();
+    impl_light_params!(actor_params);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
@@ -1,5 +1,5 @@
 use auto_struct_macros::auto_struct;
-use reader_writer::{generic_array::GenericArray, typenum::*, CStr};
+use reader_writer::{generic_array::GenericArray, typenum::*, CStr, FourCC};
 
 use crate::{res_id::*, scly_props::structs::LightParameters, ResId, SclyPropertyData};
 
@@ -46,4 +46,20 @@ impl<'r> SclyPropertyData for Effect<'r> {
     impl_position!();
     impl_rotation!();
     impl_scale!();
+
+    const SUPPORTS_LIGHT_PARAMS: bool = true;
+
+    fn impl_get_light_params(&self) -> Vec<LightParameters> {
+        vec![self.light_params.clone()]
+    }
+
+    fn impl_set_light_params(&mut self, x: Vec<LightParameters>) {
+        self.light_params = x[0].clone();
+    }
+
+    const SUPPORTS_ASSET_IDS: bool = true;
+
+    fn impl_get_asset_ids(&self) -> Vec<(u32, FourCC)> {
+        vec![self.part.into(), self.elsc.into()]
+    }
 }
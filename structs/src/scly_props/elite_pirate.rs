@@ -53,7 +53,7 @@ pub struct ElitePirate<'r> {
     pub unknown18: u8,
 }
 
-use crate::{impl_patterned_info, impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_patterned_info, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for ElitePirate<'r> {
     const OBJECT_TYPE: u8 = 0x26;
 
@@ -61,6 +61,7 @@ impl<'r> SclyPropertyData for ElitePirate<'r> {
     impl_rotation!();
     impl_scale!();
     impl_patterned_info!();
+    impl_light_params!(actor_params, actor_params2);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
@@ -2,8 +2,8 @@ use auto_struct_macros::auto_struct;
 use reader_writer::{generic_array::GenericArray, typenum::*, CStr};
 
 use crate::{
-    impl_patterned_info_with_auxillary, impl_position, impl_rotation, impl_scale,
-    scly_props::structs::*, SclyPropertyData,
+    impl_light_params, impl_patterned_info_with_auxillary, impl_position, impl_rotation,
+    impl_scale, scly_props::structs::*, SclyPropertyData,
 };
 
 #[auto_struct(Readable, Writable)]
@@ -33,4 +33,5 @@ impl<'r> SclyPropertyData for FireFlea<'r> {
     impl_rotation!();
     impl_scale!();
     impl_patterned_info_with_auxillary!();
+    impl_light_params!(actor_params);
 }
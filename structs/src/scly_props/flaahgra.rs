@@ -39,7 +39,7 @@ pub struct Flaahgra<'r> {
     pub dont_care6: u32,
 }
 
-use crate::{impl_patterned_info, impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_patterned_info, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for Flaahgra<'r> {
     const OBJECT_TYPE: u8 = 0x4D;
 
@@ -47,6 +47,7 @@ impl<'r> SclyPropertyData for Flaahgra<'r> {
     impl_rotation!();
     impl_scale!();
     impl_patterned_info!();
+    impl_light_params!(actor_params1, actor_params2);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
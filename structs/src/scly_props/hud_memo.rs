@@ -1,5 +1,5 @@
 use auto_struct_macros::auto_struct;
-use reader_writer::CStr;
+use reader_writer::{CStr, FourCC};
 
 use crate::{res_id::*, ResId, SclyPropertyData};
 
@@ -20,4 +20,10 @@ pub struct HudMemo<'r> {
 
 impl<'r> SclyPropertyData for HudMemo<'r> {
     const OBJECT_TYPE: u8 = 0x17;
+
+    const SUPPORTS_ASSET_IDS: bool = true;
+
+    fn impl_get_asset_ids(&self) -> Vec<(u32, FourCC)> {
+        vec![self.strg.into()]
+    }
 }
@@ -35,7 +35,7 @@ pub struct IceSheegoth<'r> {
     pub dont_care6: u8,
 }
 
-use crate::{impl_patterned_info, impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_patterned_info, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for IceSheegoth<'r> {
     const OBJECT_TYPE: u8 = 0x4B;
 
@@ -43,6 +43,7 @@ impl<'r> SclyPropertyData for IceSheegoth<'r> {
     impl_rotation!();
     impl_scale!();
     impl_patterned_info!();
+    impl_light_params!(actor_params);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
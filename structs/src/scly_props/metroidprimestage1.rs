@@ -142,13 +142,14 @@ pub struct ExoProjectileInfo {
     pub dont_cares2: GenericArray<u8, U4>,
 }
 
-use crate::{impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for MetroidPrimeStage1<'r> {
     const OBJECT_TYPE: u8 = 0x84;
 
     impl_position!();
     impl_rotation!();
     impl_scale!();
+    impl_light_params!(exo_struct_b.actor_params);
 
     const SUPPORTS_PATTERNED_INFOS: bool = true;
 
@@ -29,13 +29,14 @@ pub struct MetroidPrimeStage2<'r> {
     pub part2: ResId<PART>,
 }
 
-use crate::{impl_patterned_info, impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_patterned_info, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for MetroidPrimeStage2<'r> {
     const OBJECT_TYPE: u8 = 0x83;
     impl_position!();
     impl_rotation!();
     impl_scale!();
     impl_patterned_info!();
+    impl_light_params!(actor_parameters);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
@@ -31,7 +31,7 @@ pub struct NewIntroBoss<'r> {
     pub textures: GenericArray<ResId<TXTR>, U2>,
 }
 
-use crate::{impl_patterned_info, impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_patterned_info, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for NewIntroBoss<'r> {
     const OBJECT_TYPE: u8 = 0x0E;
 
@@ -39,6 +39,7 @@ impl<'r> SclyPropertyData for NewIntroBoss<'r> {
     impl_rotation!();
     impl_scale!();
     impl_patterned_info!();
+    impl_light_params!(actor_params);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
@@ -34,13 +34,14 @@ pub struct OmegaPirate<'r> {
     pub dont_care4: GenericArray<u8, U22>,
 }
 
-use crate::{impl_patterned_info, impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_patterned_info, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for OmegaPirate<'r> {
     const OBJECT_TYPE: u8 = 0x86;
     impl_position!();
     impl_rotation!();
     impl_scale!();
     impl_patterned_info!();
+    impl_light_params!(actor_params1, actor_params2);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
@@ -1,5 +1,5 @@
 use auto_struct_macros::auto_struct;
-use reader_writer::{generic_array::GenericArray, typenum::*, CStr};
+use reader_writer::{generic_array::GenericArray, typenum::*, CStr, FourCC};
 
 use crate::{
     res_id::*,
@@ -39,11 +39,20 @@ pub struct Pickup<'r> {
     pub part: ResId<PART>,
 }
 
-use crate::{impl_position, impl_rotation, impl_scale};
+use crate::{impl_light_params, impl_position, impl_rotation, impl_scale};
 impl<'r> SclyPropertyData for Pickup<'r> {
     const OBJECT_TYPE: u8 = 0x11;
 
     impl_position!();
     impl_rotation!();
     impl_scale!();
+    impl_light_params!(actor_params);
+
+    const SUPPORTS_ASSET_IDS: bool = true;
+
+    fn impl_get_asset_ids(&self) -> Vec<(u32, FourCC)> {
+        let mut asset_ids = vec![self.cmdl.into(), self.ancs.file_id.into(), self.part.into()];
+        asset_ids.extend(self.actor_params.asset_ids());
+        asset_ids
+    }
 }
@@ -45,11 +45,12 @@ pub struct SnakeWeedSwarm<'r> {
     pub unknown17: u32,
 }
 
-use crate::{impl_position, impl_scale};
+use crate::{impl_light_params, impl_position, impl_scale};
 impl<'r> SclyPropertyData for SnakeWeedSwarm<'r> {
     const OBJECT_TYPE: u8 = 0x6D;
     impl_position!();
     impl_scale!();
+    impl_light_params!(actor_params);
 
     const SUPPORTS_DAMAGE_INFOS: bool = true;
 
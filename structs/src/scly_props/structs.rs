@@ -1,5 +1,5 @@
 use auto_struct_macros::auto_struct;
-use reader_writer::{generic_array::GenericArray, typenum::*};
+use reader_writer::{generic_array::GenericArray, typenum::*, FourCC};
 
 use crate::{res_id::*, ResId};
 
@@ -39,6 +39,18 @@ pub struct ActorParameters {
     pub unknown5: f32,
 }
 
+impl ActorParameters {
+    pub fn asset_ids(&self) -> Vec<(u32, FourCC)> {
+        vec![
+            self.scan_params.scan.into(),
+            self.xray_cmdl.into(),
+            self.xray_cskr.into(),
+            self.thermal_cmdl.into(),
+            self.thermal_cskr.into(),
+        ]
+    }
+}
+
 #[auto_struct(Readable, Writable, FixedSize)]
 #[derive(Debug, Clone)]
 pub struct AnimationParameters {
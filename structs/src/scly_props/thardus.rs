@@ -28,11 +28,14 @@ pub struct Thardus<'r> {
     pub asset_ids2: GenericArray<u32, U6>,
 }
 
-use crate::{impl_patterned_info_with_auxillary, impl_position, impl_rotation, impl_scale};
+use crate::{
+    impl_light_params, impl_patterned_info_with_auxillary, impl_position, impl_rotation, impl_scale,
+};
 impl<'r> SclyPropertyData for Thardus<'r> {
     const OBJECT_TYPE: u8 = 0x58;
     impl_position!();
     impl_rotation!();
     impl_scale!();
     impl_patterned_info_with_auxillary!();
+    impl_light_params!(actor_parameters);
 }
@@ -1,7 +1,9 @@
 use std::{borrow::Cow, ffi::CString};
 
 use auto_struct_macros::auto_struct;
-use reader_writer::{generic_array::GenericArray, typenum::*, CStr, CStrConversionExtension};
+use reader_writer::{
+    generic_array::GenericArray, typenum::*, CStr, CStrConversionExtension, FourCC,
+};
 
 use crate::{res_id::*, scly_props::structs::AncsProp, ResId, SclyPropertyData};
 
@@ -50,6 +52,19 @@ pub struct WorldTransporterPalAdditions<'r> {
 
 impl<'r> SclyPropertyData for WorldTransporter<'r> {
     const OBJECT_TYPE: u8 = 0x062;
+
+    const SUPPORTS_ASSET_IDS: bool = true;
+
+    // The destination MLVL and MREA aren't dependencies of the area
+    fn impl_get_asset_ids(&self) -> Vec<(u32, FourCC)> {
+        vec![
+            self.ancs.file_id.into(),
+            self.platform_model.into(),
+            self.background_model.into(),
+            self.font.into(),
+            self.strg.into(),
+        ]
+    }
 }
 
 impl<'r> WorldTransporter<'r> {